@@ -2,6 +2,8 @@ use apca::api::v2::account::{self, GetError};
 use apca::{Client, RequestError};
 use clap::{Parser, ValueEnum};
 
+use crate::PriceRounding;
+
 /// Trading mode for Alpaca API
 #[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
 pub enum AlpacaTradingMode {
@@ -40,6 +42,10 @@ pub struct AlpacaAuthEnv {
     /// Trading mode: paper or live (defaults to paper for safety)
     #[clap(long, env, default_value = "paper")]
     pub alpaca_trading_mode: AlpacaTradingMode,
+
+    /// Rounding applied when converting fill prices to cents
+    #[clap(long, env, default_value = "half-even")]
+    pub price_rounding: PriceRounding,
 }
 
 impl std::fmt::Debug for AlpacaAuthEnv {
@@ -48,6 +54,7 @@ impl std::fmt::Debug for AlpacaAuthEnv {
             .field("alpaca_api_key", &"[REDACTED]")
             .field("alpaca_api_secret", &"[REDACTED]")
             .field("alpaca_trading_mode", &self.alpaca_trading_mode)
+            .field("price_rounding", &self.price_rounding)
             .finish()
     }
 }
@@ -110,6 +117,7 @@ mod tests {
             alpaca_api_key: "test_key_id".to_string(),
             alpaca_api_secret: "test_secret_key".to_string(),
             alpaca_trading_mode: AlpacaTradingMode::Paper,
+            price_rounding: PriceRounding::HalfEven,
         }
     }
 
@@ -118,6 +126,7 @@ mod tests {
             alpaca_api_key: "test_key_id".to_string(),
             alpaca_api_secret: "test_secret_key".to_string(),
             alpaca_trading_mode: AlpacaTradingMode::Live,
+            price_rounding: PriceRounding::HalfEven,
         }
     }
 
@@ -154,9 +163,10 @@ mod tests {
     #[test]
     fn test_alpaca_client_new_empty_credentials() {
         let empty_config = AlpacaAuthEnv {
-            alpaca_api_key: "".to_string(),
-            alpaca_api_secret: "".to_string(),
+            alpaca_api_key: String::new(),
+            alpaca_api_secret: String::new(),
             alpaca_trading_mode: AlpacaTradingMode::Paper,
+            price_rounding: PriceRounding::HalfEven,
         };
 
         let result = AlpacaClient::new(&empty_config);
@@ -183,9 +193,10 @@ mod tests {
             alpaca_api_key: "secret_key_id_123".to_string(),
             alpaca_api_secret: "super_secret_key_456".to_string(),
             alpaca_trading_mode: AlpacaTradingMode::Paper,
+            price_rounding: PriceRounding::HalfEven,
         };
 
-        let debug_output = format!("{:?}", config);
+        let debug_output = format!("{config:?}");
 
         assert!(debug_output.contains("[REDACTED]"));
         assert!(!debug_output.contains("secret_key_id_123"));
@@ -198,7 +209,7 @@ mod tests {
         let config = create_test_paper_config();
         let client = AlpacaClient::new(&config).unwrap();
 
-        let debug_output = format!("{:?}", client);
+        let debug_output = format!("{client:?}");
 
         assert!(!debug_output.contains("test_key_id"));
         assert!(!debug_output.contains("test_secret_key"));
@@ -5,12 +5,29 @@ use tracing::info;
 use uuid::Uuid;
 
 use super::auth::{AlpacaAuthEnv, AlpacaClient};
-use crate::{Broker, BrokerError, MarketOrder, OrderPlacement, OrderState, OrderUpdate};
+use super::order::BracketOrder;
+use crate::{
+    Broker, BrokerError, BrokerPosition, FractionalMarketOrder, FractionalOrderPlacement,
+    LimitOrder, MarketOrder, OrderPlacement, OrderState, OrderUpdate, PriceRounding, Quote, Symbol,
+};
 
 /// Alpaca broker implementation
 #[derive(Debug, Clone)]
 pub struct AlpacaBroker {
     client: Arc<AlpacaClient>,
+    price_rounding: PriceRounding,
+}
+
+impl AlpacaBroker {
+    /// Places a limit entry with a take-profit and a stop-loss exit attached,
+    /// as one Alpaca bracket order. Other brokers have no equivalent, so this
+    /// is not part of the [`Broker`] trait.
+    pub async fn place_bracket_order(
+        &self,
+        order: BracketOrder,
+    ) -> Result<OrderPlacement<String>, BrokerError> {
+        super::order::place_bracket_order(self.client.client(), order).await
+    }
 }
 
 #[async_trait]
@@ -37,6 +54,7 @@ impl Broker for AlpacaBroker {
 
         Ok(Self {
             client: Arc::new(client),
+            price_rounding: config.price_rounding,
         })
     }
 
@@ -44,6 +62,10 @@ impl Broker for AlpacaBroker {
         Ok(super::market_hours::wait_until_market_open(self.client.client()).await?)
     }
 
+    async fn time_until_market_close(&self) -> Result<Option<std::time::Duration>, Self::Error> {
+        Ok(super::market_hours::time_until_market_close(self.client.client()).await?)
+    }
+
     async fn place_market_order(
         &self,
         order: MarketOrder,
@@ -51,8 +73,32 @@ impl Broker for AlpacaBroker {
         super::order::place_market_order(self.client.client(), order).await
     }
 
+    fn supports_fractional_shares(&self) -> bool {
+        true
+    }
+
+    async fn place_fractional_market_order(
+        &self,
+        order: FractionalMarketOrder,
+    ) -> Result<FractionalOrderPlacement<Self::OrderId>, Self::Error> {
+        super::order::place_fractional_market_order(self.client.client(), order).await
+    }
+
+    async fn place_limit_order(
+        &self,
+        order: LimitOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        super::order::place_limit_order(self.client.client(), order).await
+    }
+
+    async fn preview_market_order(&self, order: &MarketOrder) -> Result<(), Self::Error> {
+        super::order::preview_market_order(self.client.client(), order).await
+    }
+
     async fn get_order_status(&self, order_id: &Self::OrderId) -> Result<OrderState, Self::Error> {
-        let order_update = super::order::get_order_status(self.client.client(), order_id).await?;
+        let order_update =
+            super::order::get_order_status(self.client.client(), order_id, self.price_rounding)
+                .await?;
 
         match order_update.status {
             crate::OrderStatus::Pending | crate::OrderStatus::Submitted => {
@@ -64,16 +110,37 @@ impl Broker for AlpacaBroker {
                 executed_at: order_update.updated_at,
                 order_id: order_id.clone(),
                 price_cents: order_update.price_cents.unwrap_or(0),
+                reported_price: order_update.reported_price,
             }),
             crate::OrderStatus::Failed => Ok(OrderState::Failed {
                 failed_at: order_update.updated_at,
                 error_reason: Some(format!("Order status: {:?}", order_update.status)),
             }),
+            crate::OrderStatus::Cancelled => Ok(OrderState::Cancelled {
+                cancelled_at: order_update.updated_at,
+                order_id: order_id.clone(),
+            }),
         }
     }
 
+    async fn cancel_order(&self, order_id: &Self::OrderId) -> Result<(), Self::Error> {
+        super::order::cancel_order(self.client.client(), order_id).await
+    }
+
     async fn poll_pending_orders(&self) -> Result<Vec<OrderUpdate<Self::OrderId>>, Self::Error> {
-        super::order::poll_pending_orders(self.client.client()).await
+        super::order::poll_pending_orders(self.client.client(), self.price_rounding).await
+    }
+
+    async fn get_quote(&self, symbol: &Symbol) -> Result<Quote, Self::Error> {
+        super::quote::get_latest_quote(self.client.client(), symbol).await
+    }
+
+    async fn get_adv(&self, symbol: &Symbol) -> Result<u64, Self::Error> {
+        super::quote::get_average_daily_volume(self.client.client(), symbol).await
+    }
+
+    async fn get_positions(&self) -> Result<Vec<BrokerPosition>, Self::Error> {
+        super::positions::get_positions(self.client.client()).await
     }
 
     fn to_supported_broker(&self) -> crate::SupportedBroker {
@@ -107,6 +174,7 @@ mod tests {
             alpaca_api_key: "test_key_id".to_string(),
             alpaca_api_secret: "test_secret_key".to_string(),
             alpaca_trading_mode: AlpacaTradingMode::Mock(base_url.to_string()),
+            price_rounding: PriceRounding::HalfEven,
         }
     }
 
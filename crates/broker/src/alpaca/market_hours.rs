@@ -1,7 +1,9 @@
 use apca::api::v2::clock;
 use apca::{Client, RequestError};
 use chrono::Utc;
-use tracing::{debug, info};
+use tracing::debug;
+
+use crate::market_hours::{MARKET_HOURS_RECHECK_INTERVAL, MarketSchedule, wait_for_open};
 
 #[derive(Debug, thiserror::Error)]
 pub enum MarketHoursError {
@@ -33,49 +35,55 @@ pub enum MarketHoursError {
 pub(super) async fn wait_until_market_open(
     client: &Client,
 ) -> Result<std::time::Duration, MarketHoursError> {
-    loop {
-        debug!("Checking market status via Alpaca Clock API");
-
-        let clock_data = client.issue::<clock::Get>(&()).await?;
-
-        if clock_data.open {
-            let now = Utc::now();
-            let next_close_utc = clock_data.next_close;
+    wait_for_open(|| market_schedule(client), MARKET_HOURS_RECHECK_INTERVAL).await
+}
 
-            if next_close_utc <= now {
-                return Err(MarketHoursError::MarketOpenButCloseInPast {
-                    next_close: next_close_utc,
-                    now,
-                });
-            }
+/// Time until the market closes, or `None` while it is closed.
+pub(super) async fn time_until_market_close(
+    client: &Client,
+) -> Result<Option<std::time::Duration>, MarketHoursError> {
+    Ok(market_schedule(client).await?.until_close())
+}
 
-            let duration = (next_close_utc - now)
-                .to_std()
-                .map_err(|_| MarketHoursError::DurationConversion)?;
+async fn market_schedule(client: &Client) -> Result<MarketSchedule, MarketHoursError> {
+    debug!("Checking market status via Alpaca Clock API");
 
-            return Ok(duration);
-        }
+    let clock_data = client.issue::<clock::Get>(&()).await?;
+    let now = Utc::now();
 
-        let now = Utc::now();
-        let next_open_utc = clock_data.next_open;
+    if clock_data.open {
+        let next_close_utc = clock_data.next_close;
 
-        if next_open_utc <= now {
-            return Err(MarketHoursError::MarketClosedButOpenInPast {
-                next_open: next_open_utc,
+        if next_close_utc <= now {
+            return Err(MarketHoursError::MarketOpenButCloseInPast {
+                next_close: next_close_utc,
                 now,
             });
         }
 
-        let wait_duration = (next_open_utc - now)
+        let until_close = (next_close_utc - now)
             .to_std()
             .map_err(|_| MarketHoursError::DurationConversion)?;
 
-        info!(
-            "Market closed, waiting {} seconds until open",
-            wait_duration.as_secs()
-        );
-        tokio::time::sleep(wait_duration).await;
+        return Ok(MarketSchedule::Open { until_close });
     }
+
+    let next_open_utc = clock_data.next_open;
+
+    if next_open_utc <= now {
+        return Err(MarketHoursError::MarketClosedButOpenInPast {
+            next_open: next_open_utc,
+            now,
+        });
+    }
+
+    let until_open = (next_open_utc - now)
+        .to_std()
+        .map_err(|_| MarketHoursError::DurationConversion)?;
+
+    Ok(MarketSchedule::Closed {
+        until_open: Some(until_open),
+    })
 }
 
 #[cfg(test)]
@@ -2,7 +2,10 @@ mod auth;
 mod broker;
 mod market_hours;
 mod order;
+mod positions;
+mod quote;
 
 pub use auth::AlpacaAuthEnv;
 pub use broker::AlpacaBroker;
 pub use market_hours::MarketHoursError;
+pub use order::BracketOrder;
@@ -1,12 +1,14 @@
-use apca::api::v2::{order, orders};
+use apca::api::v2::{asset, order, orders};
 use apca::{Client, RequestError};
 use chrono::Utc;
-use num_traits::ToPrimitive;
 use tracing::debug;
 use uuid::Uuid;
 
+use crate::order::format_price_cents;
+use crate::price::price_to_cents;
 use crate::{
-    BrokerError, Direction, MarketOrder, OrderPlacement, OrderStatus, OrderUpdate, Shares, Symbol,
+    BrokerError, Direction, FractionalMarketOrder, FractionalOrderPlacement, LimitOrder,
+    MarketOrder, OrderPlacement, OrderStatus, OrderUpdate, PriceRounding, Shares, Symbol,
 };
 
 pub(super) async fn place_market_order(
@@ -18,9 +20,54 @@ pub(super) async fn place_market_order(
         market_order.direction, market_order.shares, market_order.symbol
     );
 
-    let alpaca_side = match market_order.direction {
-        Direction::Buy => order::Side::Buy,
-        Direction::Sell => order::Side::Sell,
+    let order_init = order::CreateReqInit {
+        class: order::Class::Simple,
+        type_: order::Type::Market,
+        time_in_force: order::TimeInForce::Day,
+        extended_hours: false,
+        client_order_id: market_order.client_order_id.clone(),
+        ..Default::default()
+    };
+
+    let order_id = issue_order(
+        client,
+        order_init,
+        &market_order.symbol,
+        order::Amount::quantity(market_order.shares.value()),
+        market_order.direction,
+    )
+    .await?;
+
+    Ok(OrderPlacement {
+        order_id,
+        broker: crate::SupportedBroker::Alpaca,
+        symbol: market_order.symbol,
+        shares: market_order.shares,
+        direction: market_order.direction,
+        placed_at: chrono::Utc::now(),
+        request_payload: None,
+    })
+}
+
+/// Alpaca accepts fractional quantities on market day orders for
+/// fractionable assets.
+pub(super) async fn place_fractional_market_order(
+    client: &Client,
+    market_order: FractionalMarketOrder,
+) -> Result<FractionalOrderPlacement<String>, BrokerError> {
+    market_order.validate_quantity()?;
+
+    debug!(
+        "Placing Alpaca fractional market order: {} {} shares of {}",
+        market_order.direction, market_order.quantity, market_order.symbol
+    );
+
+    let amount = order::Amount::Quantity {
+        quantity: market_order.quantity.to_string().parse().map_err(|e| {
+            BrokerError::InvalidOrder {
+                reason: format!("Invalid quantity {}: {e}", market_order.quantity),
+            }
+        })?,
     };
 
     let order_init = order::CreateReqInit {
@@ -28,22 +75,221 @@ pub(super) async fn place_market_order(
         type_: order::Type::Market,
         time_in_force: order::TimeInForce::Day,
         extended_hours: false,
+        client_order_id: market_order.client_order_id.clone(),
         ..Default::default()
     };
 
-    let order_request = order_init.init(
-        market_order.symbol.to_string(),
-        alpaca_side,
-        order::Amount::quantity(market_order.shares.value()),
+    let order_id = issue_order(
+        client,
+        order_init,
+        &market_order.symbol,
+        amount,
+        market_order.direction,
+    )
+    .await?;
+
+    Ok(FractionalOrderPlacement {
+        order_id,
+        broker: crate::SupportedBroker::Alpaca,
+        symbol: market_order.symbol,
+        quantity: market_order.quantity,
+        direction: market_order.direction,
+        placed_at: chrono::Utc::now(),
+        request_payload: None,
+    })
+}
+
+pub(super) async fn place_limit_order(
+    client: &Client,
+    limit_order: LimitOrder,
+) -> Result<OrderPlacement<String>, BrokerError> {
+    debug!(
+        "Placing Alpaca limit order: {} {} shares of {} at {}",
+        limit_order.direction,
+        limit_order.shares,
+        limit_order.symbol,
+        limit_order.limit_price()
     );
 
+    let limit_price = limit_order
+        .limit_price()
+        .parse()
+        .map_err(|e| BrokerError::InvalidOrder {
+            reason: format!("Invalid limit price {}: {e}", limit_order.limit_price()),
+        })?;
+
+    let order_init = order::CreateReqInit {
+        class: order::Class::Simple,
+        type_: order::Type::Limit,
+        limit_price: Some(limit_price),
+        time_in_force: order::TimeInForce::Day,
+        extended_hours: false,
+        ..Default::default()
+    };
+
+    let order_id = issue_order(
+        client,
+        order_init,
+        &limit_order.symbol,
+        order::Amount::quantity(limit_order.shares.value()),
+        limit_order.direction,
+    )
+    .await?;
+
+    Ok(OrderPlacement {
+        order_id,
+        broker: crate::SupportedBroker::Alpaca,
+        symbol: limit_order.symbol,
+        shares: limit_order.shares,
+        direction: limit_order.direction,
+        placed_at: chrono::Utc::now(),
+        request_payload: None,
+    })
+}
+
+/// Limit entry order with exits attached: once the entry fills, a take-profit
+/// limit and a stop-loss stop order work against the position, and whichever
+/// fills first cancels the other.
+#[derive(Debug, Clone)]
+pub struct BracketOrder {
+    pub entry: LimitOrder,
+    /// Limit price per share of the take-profit exit
+    pub take_profit_cents: u64,
+    /// Stop price per share of the stop-loss exit
+    pub stop_loss_cents: u64,
+}
+
+impl BracketOrder {
+    /// The exits close the entry, so a buy must take profit above its stop
+    /// and a sell below it.
+    fn validate_exits(&self) -> Result<(), BrokerError> {
+        let valid = match self.entry.direction {
+            Direction::Buy => self.take_profit_cents > self.stop_loss_cents,
+            Direction::Sell => self.take_profit_cents < self.stop_loss_cents,
+        };
+
+        if !valid {
+            return Err(BrokerError::InvalidOrder {
+                reason: format!(
+                    "Take-profit {} is on the wrong side of stop-loss {} for a {}",
+                    format_price_cents(self.take_profit_cents),
+                    format_price_cents(self.stop_loss_cents),
+                    self.entry.direction
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) async fn place_bracket_order(
+    client: &Client,
+    bracket_order: BracketOrder,
+) -> Result<OrderPlacement<String>, BrokerError> {
+    bracket_order.validate_exits()?;
+
+    let BracketOrder {
+        entry,
+        take_profit_cents,
+        stop_loss_cents,
+    } = bracket_order;
+
+    debug!(
+        "Placing Alpaca bracket order: {} {} shares of {} at {}, take-profit {}, stop-loss {}",
+        entry.direction,
+        entry.shares,
+        entry.symbol,
+        entry.limit_price(),
+        format_price_cents(take_profit_cents),
+        format_price_cents(stop_loss_cents)
+    );
+
+    let order_init = order::CreateReqInit {
+        class: order::Class::Bracket,
+        type_: order::Type::Limit,
+        limit_price: Some(parse_price(&entry.limit_price())?),
+        take_profit: Some(order::TakeProfit::Limit(parse_price(&format_price_cents(
+            take_profit_cents,
+        ))?)),
+        stop_loss: Some(order::StopLoss::Stop(parse_price(&format_price_cents(
+            stop_loss_cents,
+        ))?)),
+        time_in_force: order::TimeInForce::Day,
+        extended_hours: false,
+        ..Default::default()
+    };
+
+    let order_id = issue_order(
+        client,
+        order_init,
+        &entry.symbol,
+        order::Amount::quantity(entry.shares.value()),
+        entry.direction,
+    )
+    .await?;
+
+    Ok(OrderPlacement {
+        order_id,
+        broker: crate::SupportedBroker::Alpaca,
+        symbol: entry.symbol,
+        shares: entry.shares,
+        direction: entry.direction,
+        placed_at: chrono::Utc::now(),
+        request_payload: None,
+    })
+}
+
+fn parse_price<T>(price: &str) -> Result<T, BrokerError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    price.parse().map_err(|e| BrokerError::InvalidOrder {
+        reason: format!("Invalid price {price}: {e}"),
+    })
+}
+
+/// Submits an order and returns the id Alpaca assigned to it.
+async fn issue_order(
+    client: &Client,
+    order_init: order::CreateReqInit,
+    symbol: &Symbol,
+    amount: order::Amount,
+    direction: Direction,
+) -> Result<String, BrokerError> {
+    let alpaca_side = match direction {
+        Direction::Buy => order::Side::Buy,
+        Direction::Sell => order::Side::Sell,
+    };
+
+    let order_request = order_init.init(symbol.to_string(), alpaca_side, amount);
+
     let order_response = client
         .issue::<order::Create>(&order_request)
         .await
         .map_err(|e| match e {
+            // Outages are told apart so a failover broker can take over
+            RequestError::Endpoint(order::CreateError::UnexpectedStatus(status, _))
+                if status.is_server_error() =>
+            {
+                BrokerError::Unavailable {
+                    message: format!("Alpaca order placement failed with status {status}"),
+                }
+            }
+            RequestError::Endpoint(order::CreateError::UnexpectedStatus(status, _))
+                if status.as_u16() == 401 =>
+            {
+                BrokerError::Authentication(format!(
+                    "Alpaca rejected credentials placing order: {status}"
+                ))
+            }
             RequestError::Endpoint(endpoint_error) => {
                 BrokerError::AlpacaRequest(format!("Order placement failed: {endpoint_error}"))
             }
+            RequestError::HyperUtil(hyper_util_error) if hyper_util_error.is_connect() => {
+                BrokerError::Network(format!("Connection failed: {hyper_util_error}"))
+            }
             RequestError::Hyper(hyper_error) => {
                 BrokerError::AlpacaRequest(format!("HTTP error: {hyper_error}"))
             }
@@ -55,20 +301,66 @@ pub(super) async fn place_market_order(
             }
         })?;
 
-    let order_id = order_response.id.to_string();
+    Ok(order_response.id.to_string())
+}
 
-    Ok(OrderPlacement {
-        order_id,
-        symbol: market_order.symbol,
-        shares: market_order.shares,
-        direction: market_order.direction,
-        placed_at: chrono::Utc::now(),
-    })
+/// Alpaca has no order preview endpoint, so the preview checks that the
+/// order's asset is active and tradable, which exercises the same
+/// authenticated trading API as order placement.
+pub(super) async fn preview_market_order(
+    client: &Client,
+    market_order: &MarketOrder,
+) -> Result<(), BrokerError> {
+    debug!(
+        "Previewing Alpaca market order: {} {} shares of {}",
+        market_order.direction, market_order.shares, market_order.symbol
+    );
+
+    let symbol = asset::Symbol::Sym(market_order.symbol.to_string());
+
+    let asset = client
+        .issue::<asset::Get>(&symbol)
+        .await
+        .map_err(|e| match e {
+            RequestError::Endpoint(endpoint_error) => {
+                BrokerError::AlpacaRequest(format!("Asset lookup failed: {endpoint_error}"))
+            }
+            RequestError::Hyper(hyper_error) => {
+                BrokerError::AlpacaRequest(format!("HTTP error: {hyper_error}"))
+            }
+            RequestError::HyperUtil(hyper_util_error) => {
+                BrokerError::AlpacaRequest(format!("HTTP util error: {hyper_util_error}"))
+            }
+            RequestError::Io(io_error) => {
+                BrokerError::AlpacaRequest(format!("IO error: {io_error}"))
+            }
+        })?;
+
+    if asset.status != asset::Status::Active || !asset.tradable {
+        return Err(BrokerError::InvalidOrder {
+            reason: format!("{} is not tradable on Alpaca", market_order.symbol),
+        });
+    }
+
+    Ok(())
+}
+
+pub(super) async fn cancel_order(client: &Client, order_id: &str) -> Result<(), BrokerError> {
+    debug!("Cancelling Alpaca order: {order_id}");
+
+    let order_uuid = Uuid::parse_str(order_id)
+        .map_err(|e| BrokerError::AlpacaRequest(format!("Invalid order ID format: {e}")))?;
+
+    client
+        .issue::<order::Delete>(&order::Id(order_uuid))
+        .await
+        .map_err(|e| BrokerError::AlpacaRequest(format!("Order cancellation failed: {e}")))
 }
 
 pub(super) async fn get_order_status(
     client: &Client,
     order_id: &str,
+    rounding: PriceRounding,
 ) -> Result<OrderUpdate<String>, BrokerError> {
     debug!("Querying Alpaca order status for order ID: {}", order_id);
 
@@ -108,7 +400,7 @@ pub(super) async fn get_order_status(
 
     let status = map_alpaca_status_to_order_status(order_response.status);
 
-    let price_cents = extract_price_cents_from_order(&order_response)?;
+    let (reported_price, price_cents) = extract_fill_price(&order_response, rounding)?.unzip();
 
     Ok(OrderUpdate {
         order_id: order_id.to_string(),
@@ -118,11 +410,13 @@ pub(super) async fn get_order_status(
         status,
         updated_at: Utc::now(),
         price_cents,
+        reported_price,
     })
 }
 
 pub(super) async fn poll_pending_orders(
     client: &Client,
+    rounding: PriceRounding,
 ) -> Result<Vec<OrderUpdate<String>>, BrokerError> {
     debug!("Polling all pending Alpaca orders");
 
@@ -165,7 +459,8 @@ pub(super) async fn poll_pending_orders(
 
             let status = map_alpaca_status_to_order_status(alpaca_order.status);
 
-            let price_cents = extract_price_cents_from_order(&alpaca_order)?;
+            let (reported_price, price_cents) =
+                extract_fill_price(&alpaca_order, rounding)?.unzip();
 
             Ok(OrderUpdate {
                 order_id: alpaca_order.id.to_string(),
@@ -175,6 +470,7 @@ pub(super) async fn poll_pending_orders(
                 status,
                 updated_at: Utc::now(),
                 price_cents,
+                reported_price,
             })
         })
         .collect::<Result<Vec<_>, BrokerError>>()?;
@@ -225,24 +521,21 @@ fn map_alpaca_status_to_order_status(status: order::Status) -> OrderStatus {
     }
 }
 
-/// Extracts price in cents from Alpaca order
-fn extract_price_cents_from_order(order: &order::Order) -> Result<Option<u64>, BrokerError> {
-    if let Some(avg_fill_price) = &order.average_fill_price {
-        let price_str = format!("{avg_fill_price}");
-        let price_f64 = price_str
-            .parse::<f64>()
-            .map_err(|e| BrokerError::AlpacaRequest(format!("Invalid fill price: {e}")))?;
-
-        let price_cents_float = (price_f64 * 100.0).round();
-
-        let price_cents = price_cents_float.to_u64().ok_or_else(|| {
-            BrokerError::AlpacaRequest(format!("Invalid price value: {price_f64}"))
-        })?;
-
-        Ok(Some(price_cents))
-    } else {
-        Ok(None)
-    }
+/// Extracts the average fill price from an Alpaca order, both as reported
+/// and converted to cents
+fn extract_fill_price(
+    order: &order::Order,
+    rounding: PriceRounding,
+) -> Result<Option<(String, u64)>, BrokerError> {
+    order
+        .average_fill_price
+        .as_ref()
+        .map(|avg_fill_price| {
+            let reported_price = avg_fill_price.to_string();
+            let price_cents = price_to_cents(&reported_price, rounding)?;
+            Ok((reported_price, price_cents))
+        })
+        .transpose()
 }
 
 /// Extracts shares from Alpaca Amount enum
@@ -276,6 +569,52 @@ mod tests {
         Client::new(api_info)
     }
 
+    fn asset_json(symbol: &str, tradable: bool) -> serde_json::Value {
+        json!({
+            "id": "904837e3-3b76-47ec-b432-046db621571b",
+            "class": "us_equity",
+            "exchange": "NASDAQ",
+            "symbol": symbol,
+            "status": "active",
+            "tradable": tradable,
+            "marginable": true,
+            "shortable": true,
+            "easy_to_borrow": true,
+            "fractionable": true
+        })
+    }
+
+    #[tokio::test]
+    async fn test_preview_market_order() {
+        let server = MockServer::start();
+        let client = create_test_client(&server);
+
+        let tradable_mock = server.mock(|when, then| {
+            when.method(GET).path("/v2/assets/AAPL");
+            then.status(200).json_body(asset_json("AAPL", true));
+        });
+        let halted_mock = server.mock(|when, then| {
+            when.method(GET).path("/v2/assets/HALT");
+            then.status(200).json_body(asset_json("HALT", false));
+        });
+
+        let order = |symbol| MarketOrder {
+            symbol: Symbol::new(symbol).unwrap(),
+            shares: Shares::new(1).unwrap(),
+            direction: Direction::Buy,
+            client_order_id: None,
+        };
+
+        preview_market_order(&client, &order("AAPL")).await.unwrap();
+        tradable_mock.assert();
+
+        let error = preview_market_order(&client, &order("HALT"))
+            .await
+            .unwrap_err();
+        halted_mock.assert();
+        assert!(matches!(error, BrokerError::InvalidOrder { .. }));
+    }
+
     #[tokio::test]
     async fn test_place_market_order_buy_success() {
         let server = MockServer::start();
@@ -333,6 +672,7 @@ mod tests {
             symbol: Symbol::new("AAPL".to_string()).unwrap(),
             shares: Shares::new(100).unwrap(),
             direction: Direction::Buy,
+            client_order_id: None,
         };
 
         let result = place_market_order(&client, market_order).await;
@@ -345,6 +685,206 @@ mod tests {
         assert_eq!(placement.direction, Direction::Buy);
     }
 
+    #[tokio::test]
+    async fn test_place_fractional_market_order_sends_decimal_qty() {
+        let server = MockServer::start();
+
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/v2/orders").json_body_partial(
+                r#"{"symbol": "AAPL", "qty": "0.25", "side": "sell", "type": "market"}"#,
+            );
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "id": "904837e3-3b76-47ec-b432-046db621571b",
+                    "client_order_id": "",
+                    "symbol": "AAPL",
+                    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+                    "asset_class": "us_equity",
+                    "qty": "0.25",
+                    "filled_qty": "0",
+                    "side": "sell",
+                    "order_class": "simple",
+                    "type": "market",
+                    "time_in_force": "day",
+                    "limit_price": null,
+                    "stop_price": null,
+                    "trail_price": null,
+                    "trail_percent": null,
+                    "status": "new",
+                    "extended_hours": false,
+                    "legs": [],
+                    "created_at": "2030-01-15T09:30:00.000Z",
+                    "updated_at": null,
+                    "submitted_at": null,
+                    "filled_at": null,
+                    "expired_at": null,
+                    "canceled_at": null,
+                    "average_fill_price": null
+                }));
+        });
+
+        let client = create_test_client(&server);
+        let market_order = FractionalMarketOrder {
+            symbol: Symbol::new("AAPL".to_string()).unwrap(),
+            quantity: rust_decimal::Decimal::new(25, 2),
+            direction: Direction::Sell,
+            client_order_id: None,
+        };
+
+        let placement = place_fractional_market_order(&client, market_order)
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(placement.order_id, "904837e3-3b76-47ec-b432-046db621571b");
+        assert_eq!(placement.quantity, rust_decimal::Decimal::new(25, 2));
+    }
+
+    fn bracket_leg_json(
+        id: &str,
+        type_: &str,
+        limit_price: Option<&str>,
+        stop_price: Option<&str>,
+    ) -> serde_json::Value {
+        json!({
+            "id": id,
+            "client_order_id": "",
+            "symbol": "AAPL",
+            "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+            "asset_class": "us_equity",
+            "qty": "10",
+            "filled_qty": "0",
+            "side": "sell",
+            "order_class": "bracket",
+            "type": type_,
+            "time_in_force": "day",
+            "limit_price": limit_price,
+            "stop_price": stop_price,
+            "trail_price": null,
+            "trail_percent": null,
+            "status": "new",
+            "extended_hours": false,
+            "legs": [],
+            "created_at": "2030-01-15T09:30:00.000Z",
+            "updated_at": null,
+            "submitted_at": null,
+            "filled_at": null,
+            "expired_at": null,
+            "canceled_at": null,
+            "average_fill_price": null
+        })
+    }
+
+    #[tokio::test]
+    async fn test_place_bracket_order_sends_exits_and_decodes_legs() {
+        let server = MockServer::start();
+        let take_profit_leg = bracket_leg_json(
+            "8c2f3b1e-6c1a-4d8e-9a51-3f1b2c4d5e6f",
+            "limit",
+            Some("160.75"),
+            None,
+        );
+        let stop_loss_leg = bracket_leg_json(
+            "1d7e9a2b-4f3c-4b6a-8e2d-7c9b0a1f2e3d",
+            "stop",
+            None,
+            Some("145.35"),
+        );
+
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/v2/orders").json_body_partial(
+                r#"{
+                    "symbol": "AAPL",
+                    "qty": "10",
+                    "side": "buy",
+                    "type": "limit",
+                    "order_class": "bracket",
+                    "limit_price": "150.25",
+                    "take_profit": {"limit_price": "160.75"},
+                    "stop_loss": {"stop_price": "145.35"}
+                }"#,
+            );
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "id": "904837e3-3b76-47ec-b432-046db621571b",
+                    "client_order_id": "",
+                    "symbol": "AAPL",
+                    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+                    "asset_class": "us_equity",
+                    "qty": "10",
+                    "filled_qty": "0",
+                    "side": "buy",
+                    "order_class": "bracket",
+                    "type": "limit",
+                    "time_in_force": "day",
+                    "limit_price": "150.25",
+                    "stop_price": null,
+                    "trail_price": null,
+                    "trail_percent": null,
+                    "status": "new",
+                    "extended_hours": false,
+                    "legs": [take_profit_leg, stop_loss_leg],
+                    "created_at": "2030-01-15T09:30:00.000Z",
+                    "updated_at": null,
+                    "submitted_at": null,
+                    "filled_at": null,
+                    "expired_at": null,
+                    "canceled_at": null,
+                    "average_fill_price": null
+                }));
+        });
+
+        let client = create_test_client(&server);
+        let bracket_order = BracketOrder {
+            entry: LimitOrder {
+                symbol: Symbol::new("AAPL").unwrap(),
+                shares: Shares::new(10).unwrap(),
+                direction: Direction::Buy,
+                limit_price_cents: 15025,
+            },
+            take_profit_cents: 16075,
+            stop_loss_cents: 14535,
+        };
+
+        let placement = place_bracket_order(&client, bracket_order).await.unwrap();
+
+        mock.assert();
+        assert_eq!(placement.order_id, "904837e3-3b76-47ec-b432-046db621571b");
+        assert_eq!(placement.shares.value(), 10);
+        assert_eq!(placement.direction, Direction::Buy);
+    }
+
+    #[tokio::test]
+    async fn test_place_bracket_order_rejects_exits_on_wrong_side() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/v2/orders");
+            then.status(200);
+        });
+
+        let client = create_test_client(&server);
+        // A sell takes profit below its stop
+        let bracket_order = BracketOrder {
+            entry: LimitOrder {
+                symbol: Symbol::new("AAPL").unwrap(),
+                shares: Shares::new(10).unwrap(),
+                direction: Direction::Sell,
+                limit_price_cents: 15025,
+            },
+            take_profit_cents: 16075,
+            stop_loss_cents: 14535,
+        };
+
+        let error = place_bracket_order(&client, bracket_order)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, BrokerError::InvalidOrder { .. }));
+        mock.assert_hits(0);
+    }
+
     #[tokio::test]
     async fn test_place_market_order_sell_success() {
         let server = MockServer::start();
@@ -358,7 +898,7 @@ mod tests {
                 "time_in_force": "day",
                 "order_class": "simple",
                 "extended_hours": false,
-                "client_order_id": null,
+                "client_order_id": "st0x-TSLA-7",
                 "limit_price": null,
                 "stop_price": null,
                 "trail_price": null,
@@ -402,6 +942,7 @@ mod tests {
             symbol: Symbol::new("TSLA".to_string()).unwrap(),
             shares: Shares::new(50).unwrap(),
             direction: Direction::Sell,
+            client_order_id: Some("st0x-TSLA-7".to_string()),
         };
 
         let result = place_market_order(&client, market_order).await;
@@ -433,6 +974,7 @@ mod tests {
             symbol: Symbol::new("INVALID".to_string()).unwrap(),
             shares: Shares::new(10).unwrap(),
             direction: Direction::Buy,
+            client_order_id: None,
         };
 
         let result = place_market_order(&client, market_order).await;
@@ -461,13 +1003,14 @@ mod tests {
             symbol: Symbol::new("AAPL".to_string()).unwrap(),
             shares: Shares::new(100).unwrap(),
             direction: Direction::Buy,
+            client_order_id: None,
         };
 
         let result = place_market_order(&client, market_order).await;
 
         mock.assert();
         let error = result.unwrap_err();
-        assert!(matches!(error, BrokerError::AlpacaRequest(_)));
+        assert!(matches!(error, BrokerError::Authentication(_)));
     }
 
     #[tokio::test]
@@ -488,13 +1031,14 @@ mod tests {
             symbol: Symbol::new("SPY".to_string()).unwrap(),
             shares: Shares::new(25).unwrap(),
             direction: Direction::Buy,
+            client_order_id: None,
         };
 
         let result = place_market_order(&client, market_order).await;
 
         mock.assert();
         let error = result.unwrap_err();
-        assert!(matches!(error, BrokerError::AlpacaRequest(_)));
+        assert!(matches!(error, BrokerError::Unavailable { .. }));
     }
 
     #[tokio::test]
@@ -504,7 +1048,7 @@ mod tests {
 
         let mock = server.mock(|when, then| {
             when.method(GET)
-                .path(format!("/v2/orders/{}", order_id.replace("-", "")));
+                .path(format!("/v2/orders/{}", order_id.replace('-', "")));
             then.status(200)
                 .header("content-type", "application/json")
                 .json_body(json!({
@@ -537,7 +1081,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = get_order_status(&client, order_id).await;
+        let result = get_order_status(&client, order_id, PriceRounding::HalfEven).await;
 
         mock.assert();
         let order_update = result.unwrap();
@@ -556,7 +1100,7 @@ mod tests {
 
         let mock = server.mock(|when, then| {
             when.method(GET)
-                .path(format!("/v2/orders/{}", order_id.replace("-", "")));
+                .path(format!("/v2/orders/{}", order_id.replace('-', "")));
             then.status(200)
                 .header("content-type", "application/json")
                 .json_body(json!({
@@ -589,7 +1133,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = get_order_status(&client, order_id).await;
+        let result = get_order_status(&client, order_id, PriceRounding::HalfEven).await;
 
         mock.assert();
         let order_update = result.unwrap();
@@ -599,6 +1143,59 @@ mod tests {
         assert_eq!(order_update.direction, Direction::Sell);
         assert_eq!(order_update.status, OrderStatus::Filled);
         assert_eq!(order_update.price_cents, Some(24567));
+        assert_eq!(order_update.reported_price.as_deref(), Some("245.67"));
+    }
+
+    #[tokio::test]
+    async fn test_get_order_status_sub_cent_fill_uses_rounding_mode() {
+        let server = MockServer::start();
+        let order_id = "61e7b016-9c91-4a97-b912-615c9d365c9d";
+
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/v2/orders/{}", order_id.replace('-', "")));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "id": order_id,
+                    "client_order_id": "",
+                    "symbol": "TSLA",
+                    "asset_id": "61e7b016-9c91-4a97-b912-615c9d365c9d",
+                    "asset_class": "us_equity",
+                    "qty": "50",
+                    "filled_qty": "50",
+                    "side": "sell",
+                    "order_class": "simple",
+                    "type": "market",
+                    "time_in_force": "day",
+                    "status": "filled",
+                    "extended_hours": false,
+                    "legs": [],
+                    "created_at": "2030-01-15T09:30:00.000Z",
+                    "updated_at": "2030-01-15T09:31:00.000Z",
+                    "submitted_at": "2030-01-15T09:30:00.000Z",
+                    "filled_at": "2030-01-15T09:31:00.000Z",
+                    "expired_at": null,
+                    "canceled_at": null,
+                    "filled_avg_price": "245.665",
+                    "limit_price": null,
+                    "stop_price": null,
+                    "trail_price": null,
+                    "trail_percent": null
+                }));
+        });
+
+        let client = create_test_client(&server);
+
+        for (rounding, expected_cents) in [
+            (PriceRounding::HalfEven, 24566),
+            (PriceRounding::HalfUp, 24567),
+            (PriceRounding::Truncate, 24566),
+        ] {
+            let order_update = get_order_status(&client, order_id, rounding).await.unwrap();
+            assert_eq!(order_update.price_cents, Some(expected_cents));
+            assert_eq!(order_update.reported_price.as_deref(), Some("245.665"));
+        }
     }
 
     #[tokio::test]
@@ -608,7 +1205,7 @@ mod tests {
 
         let mock = server.mock(|when, then| {
             when.method(GET)
-                .path(format!("/v2/orders/{}", order_id.replace("-", "")));
+                .path(format!("/v2/orders/{}", order_id.replace('-', "")));
             then.status(200)
                 .header("content-type", "application/json")
                 .json_body(json!({
@@ -641,7 +1238,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = get_order_status(&client, order_id).await;
+        let result = get_order_status(&client, order_id, PriceRounding::HalfEven).await;
 
         mock.assert();
         let order_update = result.unwrap();
@@ -660,7 +1257,7 @@ mod tests {
 
         let mock = server.mock(|when, then| {
             when.method(GET)
-                .path(format!("/v2/orders/{}", order_id.replace("-", "")));
+                .path(format!("/v2/orders/{}", order_id.replace('-', "")));
             then.status(200)
                 .header("content-type", "application/json")
                 .json_body(json!({
@@ -693,7 +1290,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = get_order_status(&client, order_id).await;
+        let result = get_order_status(&client, order_id, PriceRounding::HalfEven).await;
 
         mock.assert();
         let order_update = result.unwrap();
@@ -786,7 +1383,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = poll_pending_orders(&client).await;
+        let result = poll_pending_orders(&client, PriceRounding::HalfEven).await;
 
         mock.assert();
         let order_updates = result.unwrap();
@@ -827,7 +1424,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = poll_pending_orders(&client).await;
+        let result = poll_pending_orders(&client, PriceRounding::HalfEven).await;
 
         mock.assert();
         let order_updates = result.unwrap();
@@ -877,7 +1474,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = poll_pending_orders(&client).await;
+        let result = poll_pending_orders(&client, PriceRounding::HalfEven).await;
 
         mock.assert();
         let order_updates = result.unwrap();
@@ -913,7 +1510,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = poll_pending_orders(&client).await;
+        let result = poll_pending_orders(&client, PriceRounding::HalfEven).await;
 
         mock.assert();
         let error = result.unwrap_err();
@@ -938,7 +1535,7 @@ mod tests {
         });
 
         let client = create_test_client(&server);
-        let result = poll_pending_orders(&client).await;
+        let result = poll_pending_orders(&client, PriceRounding::HalfEven).await;
 
         mock.assert();
         let error = result.unwrap_err();
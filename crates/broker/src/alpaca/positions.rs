@@ -0,0 +1,37 @@
+use apca::Client;
+use apca::api::v2::position::{Position, Side};
+use apca::api::v2::positions;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use tracing::debug;
+
+use super::quote::{price_to_cents, request_error};
+use crate::{BrokerError, BrokerPosition, Symbol};
+
+pub(super) async fn get_positions(client: &Client) -> Result<Vec<BrokerPosition>, BrokerError> {
+    debug!("Querying Alpaca open positions");
+
+    let positions = client
+        .issue::<positions::List>(&())
+        .await
+        .map_err(|e| request_error("Positions", e))?;
+
+    positions.iter().map(to_broker_position).collect()
+}
+
+fn to_broker_position(position: &Position) -> Result<BrokerPosition, BrokerError> {
+    let quantity = Decimal::from_str(&position.quantity.to_string())
+        .map_err(|e| {
+            BrokerError::AlpacaRequest(format!("Invalid quantity for {}: {e}", position.symbol))
+        })?
+        .abs();
+
+    Ok(BrokerPosition {
+        symbol: Symbol::new(position.symbol.clone())?,
+        quantity: match position.side {
+            Side::Long => quantity,
+            Side::Short => -quantity,
+        },
+        average_price_cents: price_to_cents(&position.average_entry_price)?,
+    })
+}
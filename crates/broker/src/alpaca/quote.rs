@@ -0,0 +1,135 @@
+use apca::data::v2::{bars, last_quotes};
+use apca::{Client, RequestError};
+use chrono::Utc;
+use num_traits::ToPrimitive;
+use std::fmt::Display;
+use tracing::debug;
+
+use crate::{BrokerError, Quote, Symbol};
+
+pub(super) async fn get_latest_quote(
+    client: &Client,
+    symbol: &Symbol,
+) -> Result<Quote, BrokerError> {
+    debug!("Querying Alpaca latest quote for {symbol}");
+
+    let request = last_quotes::GetReqInit::default().init([symbol.to_string()]);
+
+    let quotes = client
+        .issue::<last_quotes::Get>(&request)
+        .await
+        .map_err(|e| request_error("Quote", e))?;
+
+    let (_, quote) = quotes
+        .into_iter()
+        .find(|(quoted_symbol, _)| *quoted_symbol == symbol.to_string())
+        .ok_or_else(|| BrokerError::AlpacaRequest(format!("No quote returned for {symbol}")))?;
+
+    Ok(Quote {
+        symbol: symbol.clone(),
+        bid_price_cents: price_to_cents(&quote.bid_price)?,
+        ask_price_cents: price_to_cents(&quote.ask_price)?,
+        quoted_at: quote.time,
+    })
+}
+
+/// Number of most recent daily bars averaged into the ADV
+const ADV_SESSIONS: usize = 10;
+
+/// Calendar lookback that reliably covers `ADV_SESSIONS` trading days,
+/// including holidays
+const ADV_LOOKBACK_DAYS: i64 = 21;
+
+/// Recent SIP data is only available with a delay on the free data plan
+const SIP_DATA_DELAY_MINUTES: i64 = 15;
+
+pub(super) async fn get_average_daily_volume(
+    client: &Client,
+    symbol: &Symbol,
+) -> Result<u64, BrokerError> {
+    debug!("Querying Alpaca daily bars for {symbol} to compute ADV");
+
+    let end = Utc::now() - chrono::Duration::minutes(SIP_DATA_DELAY_MINUTES);
+    let start = end - chrono::Duration::days(ADV_LOOKBACK_DAYS);
+    let request =
+        bars::ListReqInit::default().init(symbol.to_string(), start, end, bars::TimeFrame::OneDay);
+
+    let response = client
+        .issue::<bars::List>(&request)
+        .await
+        .map_err(|e| request_error("Bars", e))?;
+
+    let volumes = response
+        .bars
+        .iter()
+        .map(|bar| bar.volume)
+        .collect::<Vec<_>>();
+
+    average_recent_volume(&volumes).ok_or_else(|| {
+        BrokerError::AlpacaRequest(format!(
+            "Cannot compute average daily volume for {symbol} from {} daily bars",
+            volumes.len()
+        ))
+    })
+}
+
+/// Mean of the last `ADV_SESSIONS` daily volumes, or `None` without any bars
+/// (or if the total overflows)
+fn average_recent_volume(volumes: &[usize]) -> Option<u64> {
+    let recent = &volumes[volumes.len().saturating_sub(ADV_SESSIONS)..];
+    let sessions = u64::try_from(recent.len()).ok().filter(|n| *n > 0)?;
+    let total = recent.iter().try_fold(0u64, |total, volume| {
+        total.checked_add(u64::try_from(*volume).ok()?)
+    })?;
+
+    Some(total / sessions)
+}
+
+pub(super) fn request_error<E: Display>(query: &str, error: RequestError<E>) -> BrokerError {
+    match error {
+        RequestError::Endpoint(endpoint_error) => {
+            BrokerError::AlpacaRequest(format!("{query} query failed: {endpoint_error}"))
+        }
+        RequestError::Hyper(hyper_error) => {
+            BrokerError::AlpacaRequest(format!("HTTP error: {hyper_error}"))
+        }
+        RequestError::HyperUtil(hyper_util_error) => {
+            BrokerError::AlpacaRequest(format!("HTTP util error: {hyper_util_error}"))
+        }
+        RequestError::Io(io_error) => BrokerError::AlpacaRequest(format!("IO error: {io_error}")),
+    }
+}
+
+pub(super) fn price_to_cents(price: &impl Display) -> Result<u64, BrokerError> {
+    let price_f64 = format!("{price}")
+        .parse::<f64>()
+        .map_err(|e| BrokerError::AlpacaRequest(format!("Invalid quote price: {e}")))?;
+
+    (price_f64 * 100.0)
+        .round()
+        .to_u64()
+        .ok_or_else(|| BrokerError::AlpacaRequest(format!("Invalid quote price: {price_f64}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_to_cents() {
+        assert_eq!(price_to_cents(&"150.10").unwrap(), 15010);
+        assert_eq!(price_to_cents(&"0").unwrap(), 0);
+        assert!(price_to_cents(&"-1.50").is_err());
+        assert!(price_to_cents(&"abc").is_err());
+    }
+
+    #[test]
+    fn test_average_recent_volume_uses_last_sessions() {
+        assert_eq!(average_recent_volume(&[]), None);
+        assert_eq!(average_recent_volume(&[100, 300]), Some(200));
+
+        // Only the last ten sessions count towards the average
+        let volumes = [1_000_000, 10, 10, 10, 10, 10, 10, 10, 10, 10, 20];
+        assert_eq!(average_recent_volume(&volumes), Some(11));
+    }
+}
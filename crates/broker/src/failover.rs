@@ -0,0 +1,539 @@
+//! Broker that fails over from a primary to a secondary broker.
+//!
+//! Every order placement the primary fails because it is out (unreachable,
+//! rejecting our credentials or failing on its side) counts against its
+//! health and any success resets it. Rejected orders, rate limits and
+//! timeouts, after which the order may still have been placed, are returned
+//! without failing over. Once the primary fails `failure_threshold` times in
+//! a row, new orders go to the secondary and the failover is logged at error
+//! level for alerting. While failed over, one order per `probe_interval` is
+//! tried on the primary first; if it succeeds, orders fail back to the primary,
+//! otherwise the order still goes to the secondary.
+//!
+//! Every placement reports the broker that holds the order. Callers store it
+//! with the order and ask for its status or cancel it through
+//! [`Broker::get_order_status_at`] and [`Broker::cancel_order_at`], which
+//! route to that broker. [`Broker::poll_pending_orders`] and
+//! [`Broker::to_supported_broker`] always refer to the primary.
+
+use async_trait::async_trait;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::{
+    Broker, BrokerPosition, FractionalMarketOrder, FractionalOrderPlacement, LimitOrder,
+    MarketOrder, OrderPlacement, OrderState, OrderUpdate, Quote, SupportedBroker, Symbol,
+    is_broker_outage,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct FailoverPolicy {
+    /// Consecutive primary placement failures after which orders go to the
+    /// secondary
+    pub failure_threshold: NonZeroU32,
+    /// How often the primary is retried while failed over
+    pub probe_interval: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct FailoverConfig<P, S> {
+    pub primary: P,
+    pub secondary: S,
+    pub policy: FailoverPolicy,
+}
+
+#[derive(Debug, Default)]
+struct PrimaryHealth {
+    consecutive_failures: u32,
+    /// When orders last started going to the secondary without trying the
+    /// primary; `None` while the primary is healthy
+    failed_over_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Primary,
+    Secondary,
+}
+
+#[derive(Debug, Clone)]
+pub struct FailoverBroker<P, S> {
+    primary: P,
+    secondary: S,
+    policy: FailoverPolicy,
+    health: Arc<Mutex<PrimaryHealth>>,
+}
+
+impl<P, S> FailoverBroker<P, S>
+where
+    P: Broker,
+    S: Broker<OrderId = P::OrderId, Error = P::Error>,
+{
+    pub fn new(primary: P, secondary: S, policy: FailoverPolicy) -> Self {
+        Self {
+            primary,
+            secondary,
+            policy,
+            health: Arc::default(),
+        }
+    }
+
+    /// Whether new orders currently go to the secondary.
+    pub fn is_failed_over(&self) -> bool {
+        self.lock_health().failed_over_at.is_some()
+    }
+
+    fn lock_health(&self) -> std::sync::MutexGuard<'_, PrimaryHealth> {
+        self.health.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Broker for the next order placement. A due probe restarts the probe
+    /// interval so concurrent placements keep going to the secondary.
+    fn route_placement(&self) -> Route {
+        let mut health = self.lock_health();
+        match health.failed_over_at {
+            None => Route::Primary,
+            Some(failed_over_at) if failed_over_at.elapsed() >= self.policy.probe_interval => {
+                info!("Probing primary broker for recovery");
+                health.failed_over_at = Some(Instant::now());
+                Route::Primary
+            }
+            Some(_) => Route::Secondary,
+        }
+    }
+
+    /// Broker for reads that should follow the current placement route.
+    fn route_reads(&self) -> Route {
+        if self.is_failed_over() {
+            Route::Secondary
+        } else {
+            Route::Primary
+        }
+    }
+
+    fn record_primary_success(&self) {
+        let mut health = self.lock_health();
+        if health.failed_over_at.is_some() {
+            info!("Primary broker recovered, failing back from secondary broker");
+        }
+        *health = PrimaryHealth::default();
+    }
+
+    /// Counts a primary failure and returns whether orders now go to the
+    /// secondary.
+    fn record_primary_failure(&self, error: &P::Error) -> bool {
+        let (consecutive_failures, already_failed_over) = {
+            let mut health = self.lock_health();
+            health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+            let already_failed_over = health.failed_over_at.is_some();
+            if !already_failed_over
+                && health.consecutive_failures >= self.policy.failure_threshold.get()
+            {
+                health.failed_over_at = Some(Instant::now());
+            }
+            (health.consecutive_failures, already_failed_over)
+        };
+
+        if already_failed_over {
+            warn!(
+                "Primary broker still failing after {consecutive_failures} consecutive failures: {error}"
+            );
+            return true;
+        }
+
+        if consecutive_failures >= self.policy.failure_threshold.get() {
+            error!(
+                "Primary broker failed {consecutive_failures} consecutive times, failing over new orders to secondary broker: {error}"
+            );
+            return true;
+        }
+
+        warn!(
+            "Primary broker failure {consecutive_failures} of {} before failover: {error}",
+            self.policy.failure_threshold
+        );
+        false
+    }
+
+    /// Whether the order is held by the secondary rather than the primary.
+    fn is_secondary_broker(&self, broker: SupportedBroker) -> bool {
+        broker == self.secondary.to_supported_broker()
+    }
+}
+
+/// Aborts the wrapped maintenance tasks when the task awaiting them is
+/// aborted.
+struct AbortOnDrop(Vec<JoinHandle<()>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        for handle in &self.0 {
+            handle.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl<P, S> Broker for FailoverBroker<P, S>
+where
+    P: Broker,
+    S: Broker<OrderId = P::OrderId, Error = P::Error>,
+{
+    type Error = P::Error;
+    type OrderId = P::OrderId;
+    type Config = FailoverConfig<P::Config, S::Config>;
+
+    async fn try_from_config(config: Self::Config) -> Result<Self, Self::Error> {
+        let primary = P::try_from_config(config.primary).await?;
+        let secondary = S::try_from_config(config.secondary).await?;
+
+        info!(
+            "Initialized {} broker with failover to {} after {} consecutive failures",
+            primary.to_supported_broker(),
+            secondary.to_supported_broker(),
+            config.policy.failure_threshold
+        );
+
+        Ok(Self::new(primary, secondary, config.policy))
+    }
+
+    async fn wait_until_market_open(&self) -> Result<Duration, Self::Error> {
+        match self.primary.wait_until_market_open().await {
+            Ok(duration) => Ok(duration),
+            Err(e) => {
+                warn!("Primary broker market hours unavailable, asking secondary broker: {e}");
+                self.secondary.wait_until_market_open().await
+            }
+        }
+    }
+
+    async fn time_until_market_close(&self) -> Result<Option<Duration>, Self::Error> {
+        match self.primary.time_until_market_close().await {
+            Ok(until_close) => Ok(until_close),
+            Err(e) => {
+                warn!("Primary broker market hours unavailable, asking secondary broker: {e}");
+                self.secondary.time_until_market_close().await
+            }
+        }
+    }
+
+    async fn place_market_order(
+        &self,
+        order: MarketOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        if self.route_placement() == Route::Primary {
+            match self.primary.place_market_order(order.clone()).await {
+                Ok(placement) => {
+                    self.record_primary_success();
+                    return Ok(placement);
+                }
+                Err(e) if is_broker_outage(&e) && self.record_primary_failure(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        info!(
+            "Placing market order with secondary broker: {} {} shares of {}",
+            order.direction, order.shares, order.symbol
+        );
+        self.secondary.place_market_order(order).await
+    }
+
+    /// Either broker may end up placing the order, so both must support it
+    fn supports_fractional_shares(&self) -> bool {
+        self.primary.supports_fractional_shares() && self.secondary.supports_fractional_shares()
+    }
+
+    async fn place_fractional_market_order(
+        &self,
+        order: FractionalMarketOrder,
+    ) -> Result<FractionalOrderPlacement<Self::OrderId>, Self::Error> {
+        if self.route_placement() == Route::Primary {
+            match self
+                .primary
+                .place_fractional_market_order(order.clone())
+                .await
+            {
+                Ok(placement) => {
+                    self.record_primary_success();
+                    return Ok(placement);
+                }
+                Err(e) if is_broker_outage(&e) && self.record_primary_failure(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        info!(
+            "Placing fractional market order with secondary broker: {} {} shares of {}",
+            order.direction, order.quantity, order.symbol
+        );
+        self.secondary.place_fractional_market_order(order).await
+    }
+
+    async fn place_limit_order(
+        &self,
+        order: LimitOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        if self.route_placement() == Route::Primary {
+            match self.primary.place_limit_order(order.clone()).await {
+                Ok(placement) => {
+                    self.record_primary_success();
+                    return Ok(placement);
+                }
+                Err(e) if is_broker_outage(&e) && self.record_primary_failure(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        info!(
+            "Placing limit order with secondary broker: {} {} shares of {} at {}",
+            order.direction,
+            order.shares,
+            order.symbol,
+            order.limit_price()
+        );
+        self.secondary.place_limit_order(order).await
+    }
+
+    async fn preview_market_order(&self, order: &MarketOrder) -> Result<(), Self::Error> {
+        match self.route_reads() {
+            Route::Primary => self.primary.preview_market_order(order).await,
+            Route::Secondary => self.secondary.preview_market_order(order).await,
+        }
+    }
+
+    async fn get_order_status(&self, order_id: &Self::OrderId) -> Result<OrderState, Self::Error> {
+        self.primary.get_order_status(order_id).await
+    }
+
+    async fn cancel_order(&self, order_id: &Self::OrderId) -> Result<(), Self::Error> {
+        self.primary.cancel_order(order_id).await
+    }
+
+    fn order_brokers(&self) -> Vec<SupportedBroker> {
+        vec![
+            self.primary.to_supported_broker(),
+            self.secondary.to_supported_broker(),
+        ]
+    }
+
+    async fn get_order_status_at(
+        &self,
+        broker: SupportedBroker,
+        order_id: &Self::OrderId,
+    ) -> Result<OrderState, Self::Error> {
+        if self.is_secondary_broker(broker) {
+            self.secondary.get_order_status(order_id).await
+        } else {
+            self.primary.get_order_status(order_id).await
+        }
+    }
+
+    async fn cancel_order_at(
+        &self,
+        broker: SupportedBroker,
+        order_id: &Self::OrderId,
+    ) -> Result<(), Self::Error> {
+        if self.is_secondary_broker(broker) {
+            self.secondary.cancel_order(order_id).await
+        } else {
+            self.primary.cancel_order(order_id).await
+        }
+    }
+
+    async fn poll_pending_orders(&self) -> Result<Vec<OrderUpdate<Self::OrderId>>, Self::Error> {
+        self.primary.poll_pending_orders().await
+    }
+
+    async fn get_quote(&self, symbol: &Symbol) -> Result<Quote, Self::Error> {
+        match self.route_reads() {
+            Route::Primary => self.primary.get_quote(symbol).await,
+            Route::Secondary => self.secondary.get_quote(symbol).await,
+        }
+    }
+
+    async fn get_adv(&self, symbol: &Symbol) -> Result<u64, Self::Error> {
+        match self.route_reads() {
+            Route::Primary => self.primary.get_adv(symbol).await,
+            Route::Secondary => self.secondary.get_adv(symbol).await,
+        }
+    }
+
+    async fn get_positions(&self) -> Result<Vec<BrokerPosition>, Self::Error> {
+        match self.route_reads() {
+            Route::Primary => self.primary.get_positions().await,
+            Route::Secondary => self.secondary.get_positions().await,
+        }
+    }
+
+    fn to_supported_broker(&self) -> SupportedBroker {
+        self.primary.to_supported_broker()
+    }
+
+    fn parse_order_id(&self, order_id_str: &str) -> Result<Self::OrderId, Self::Error> {
+        self.primary.parse_order_id(order_id_str)
+    }
+
+    async fn run_broker_maintenance(&self) -> Option<JoinHandle<()>> {
+        let handles: Vec<_> = [
+            self.primary.run_broker_maintenance().await,
+            self.secondary.run_broker_maintenance().await,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if handles.is_empty() {
+            return None;
+        }
+
+        Some(tokio::spawn(async move {
+            let mut tasks = AbortOnDrop(handles);
+            for handle in &mut tasks.0 {
+                if let Err(e) = handle.await {
+                    error!("Broker maintenance task ended unexpectedly: {e}");
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BrokerError, Direction, MockBroker, Shares, SimBroker, SimBrokerConfig};
+
+    const POLICY: FailoverPolicy = FailoverPolicy {
+        failure_threshold: NonZeroU32::new(2).unwrap(),
+        probe_interval: Duration::ZERO,
+    };
+
+    fn market_order() -> MarketOrder {
+        MarketOrder {
+            symbol: Symbol::new("AAPL").unwrap(),
+            shares: Shares::new(1).unwrap(),
+            direction: Direction::Buy,
+            client_order_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orders_stay_on_primary_below_failure_threshold() {
+        let primary = MockBroker::with_outage("primary down");
+        let secondary = MockBroker::new();
+        let broker = FailoverBroker::new(primary, secondary.clone(), POLICY);
+
+        assert!(broker.place_market_order(market_order()).await.is_err());
+
+        assert!(!broker.is_failed_over());
+        assert_eq!(secondary.orders_placed(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_primary_failures_route_orders_to_secondary() {
+        let primary = MockBroker::with_outage("primary down");
+        let secondary = MockBroker::new();
+        let broker = FailoverBroker::new(
+            primary.clone(),
+            secondary.clone(),
+            FailoverPolicy {
+                probe_interval: Duration::from_hours(1),
+                ..POLICY
+            },
+        );
+
+        broker.place_market_order(market_order()).await.unwrap_err();
+        // The failure that crosses the threshold is retried on the secondary
+        broker.place_market_order(market_order()).await.unwrap();
+        assert!(broker.is_failed_over());
+
+        // Until the next probe, orders skip the primary entirely
+        primary.set_failing(false);
+        broker.place_market_order(market_order()).await.unwrap();
+
+        assert_eq!(primary.orders_placed(), 0);
+        assert_eq!(secondary.orders_placed(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_and_rate_limited_orders_do_not_fail_over() {
+        let primary = MockBroker::with_failure("insufficient buying power");
+        let secondary = MockBroker::new();
+        let broker = FailoverBroker::new(primary.clone(), secondary.clone(), POLICY);
+
+        for _ in 0..3 {
+            broker.place_market_order(market_order()).await.unwrap_err();
+        }
+
+        primary.set_failing(false);
+        for _ in 0..3 {
+            primary.rate_limit_next_placement(1);
+            let error = broker.place_market_order(market_order()).await.unwrap_err();
+            assert!(matches!(error, BrokerError::RateLimit { .. }));
+        }
+
+        assert!(!broker.is_failed_over());
+        assert_eq!(secondary.orders_placed(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_order_status_and_cancel_follow_the_holding_broker() {
+        let primary = MockBroker::with_outage("primary down");
+        let secondary = SimBroker::new(SimBrokerConfig::default());
+        let broker = FailoverBroker::new(primary.clone(), secondary, POLICY);
+        assert_eq!(
+            broker.order_brokers(),
+            vec![SupportedBroker::DryRun, SupportedBroker::Sim]
+        );
+
+        broker.place_market_order(market_order()).await.unwrap_err();
+        let placement = broker.place_market_order(market_order()).await.unwrap();
+        assert_eq!(placement.broker, SupportedBroker::Sim);
+
+        // A fresh broker knows nothing of earlier placements, as after a
+        // restart, and still asks the broker that holds the order
+        let restarted = FailoverBroker::new(primary, broker.secondary.clone(), POLICY);
+        let state = restarted
+            .get_order_status_at(placement.broker, &placement.order_id)
+            .await
+            .unwrap();
+        assert!(matches!(state, OrderState::Submitted { .. }));
+
+        restarted
+            .cancel_order_at(placement.broker, &placement.order_id)
+            .await
+            .unwrap();
+        let state = restarted
+            .get_order_status_at(placement.broker, &placement.order_id)
+            .await
+            .unwrap();
+        assert!(matches!(state, OrderState::Cancelled { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_primary_recovery_routes_orders_back() {
+        let primary = MockBroker::with_outage("primary down");
+        let secondary = MockBroker::new();
+        let broker = FailoverBroker::new(primary.clone(), secondary.clone(), POLICY);
+
+        broker.place_market_order(market_order()).await.unwrap_err();
+        broker.place_market_order(market_order()).await.unwrap();
+        assert!(broker.is_failed_over());
+
+        // A failed probe keeps orders on the secondary
+        broker.place_market_order(market_order()).await.unwrap();
+        assert!(broker.is_failed_over());
+        assert_eq!(secondary.orders_placed(), 2);
+
+        primary.set_failing(false);
+        broker.place_market_order(market_order()).await.unwrap();
+        broker.place_market_order(market_order()).await.unwrap();
+
+        assert!(!broker.is_failed_over());
+        assert_eq!(primary.orders_placed(), 2);
+        assert_eq!(secondary.orders_placed(), 2);
+    }
+}
@@ -1,21 +1,40 @@
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
 use std::fmt::{Debug, Display};
+use std::time::Duration;
 use tokio::task::JoinHandle;
 
 pub mod alpaca;
 pub mod error;
+pub mod failover;
+pub mod market_hours;
 pub mod mock;
 pub mod order;
+pub mod paper;
+pub mod position;
+pub mod price;
+pub mod quote;
 pub mod schwab;
+pub mod sim;
 
 #[cfg(test)]
 pub mod test_utils;
 
 pub use alpaca::AlpacaBroker;
 pub use error::PersistenceError;
+pub use failover::{FailoverBroker, FailoverConfig, FailoverPolicy};
 pub use mock::{MockBroker, MockBrokerConfig};
-pub use order::{MarketOrder, OrderPlacement, OrderState, OrderStatus, OrderUpdate};
+pub use order::{
+    FractionalMarketOrder, FractionalOrderPlacement, LimitOrder, MarketOrder, OrderPlacement,
+    OrderState, OrderStatus, OrderType, OrderUpdate,
+};
+pub use paper::{PaperBroker, PaperBrokerConfig};
+pub use position::BrokerPosition;
+pub use price::PriceRounding;
+pub use quote::Quote;
 pub use schwab::SchwabBroker;
+pub use sim::{SimBroker, SimBrokerConfig};
 
 use alpaca::{AlpacaAuthEnv, MarketHoursError};
 
@@ -35,6 +54,10 @@ pub trait Broker: Send + Sync + 'static {
     /// Implementations without market hours should return a very long duration
     async fn wait_until_market_open(&self) -> Result<std::time::Duration, Self::Error>;
 
+    /// Time until the market closes, or None if it is closed, without waiting
+    /// Used to notice an early close while a market session is running
+    async fn time_until_market_close(&self) -> Result<Option<std::time::Duration>, Self::Error>;
+
     /// Place a market order for the specified symbol and quantity
     /// Returns order placement details including broker-assigned order ID
     async fn place_market_order(
@@ -42,14 +65,78 @@ pub trait Broker: Send + Sync + 'static {
         order: MarketOrder,
     ) -> Result<OrderPlacement<Self::OrderId>, Self::Error>;
 
+    /// Whether the account can trade fractional shares, so that
+    /// `place_fractional_market_order` may be used
+    fn supports_fractional_shares(&self) -> bool;
+
+    /// Place a market order for a fractional quantity of shares
+    /// Fails with an invalid order error when fractional shares are not supported
+    async fn place_fractional_market_order(
+        &self,
+        order: FractionalMarketOrder,
+    ) -> Result<FractionalOrderPlacement<Self::OrderId>, Self::Error>;
+
+    /// Place a limit order that fills at the limit price or better
+    /// Returns order placement details including broker-assigned order ID
+    async fn place_limit_order(
+        &self,
+        order: LimitOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error>;
+
+    /// Check that the broker would accept the order without executing it
+    /// Used by the startup canary to confirm end-to-end broker connectivity
+    async fn preview_market_order(&self, order: &MarketOrder) -> Result<(), Self::Error>;
+
     /// Get the current status of a specific order
     /// Used to check if pending orders have been filled or failed
     async fn get_order_status(&self, order_id: &Self::OrderId) -> Result<OrderState, Self::Error>;
 
+    /// Cancel a submitted order that has not filled yet
+    /// Succeeds once the broker accepts the cancellation
+    async fn cancel_order(&self, order_id: &Self::OrderId) -> Result<(), Self::Error>;
+
+    /// Brokers that may hold the orders placed through this broker
+    /// Used to find the submitted orders whose status this broker can answer
+    fn order_brokers(&self) -> Vec<SupportedBroker> {
+        vec![self.to_supported_broker()]
+    }
+
+    /// Get the status of an order held by `broker`, one of [`Broker::order_brokers`]
+    /// Brokers that place every order themselves ignore `broker`
+    async fn get_order_status_at(
+        &self,
+        _broker: SupportedBroker,
+        order_id: &Self::OrderId,
+    ) -> Result<OrderState, Self::Error> {
+        self.get_order_status(order_id).await
+    }
+
+    /// Cancel an order held by `broker`, one of [`Broker::order_brokers`]
+    /// Brokers that place every order themselves ignore `broker`
+    async fn cancel_order_at(
+        &self,
+        _broker: SupportedBroker,
+        order_id: &Self::OrderId,
+    ) -> Result<(), Self::Error> {
+        self.cancel_order(order_id).await
+    }
+
     /// Poll all pending orders for status updates
     /// More efficient than individual get_order_status calls for multiple orders
     async fn poll_pending_orders(&self) -> Result<Vec<OrderUpdate<Self::OrderId>>, Self::Error>;
 
+    /// Get the latest bid/ask quote for a symbol
+    /// Used as a pricing reference when onchain pricing is unavailable
+    async fn get_quote(&self, symbol: &Symbol) -> Result<Quote, Self::Error>;
+
+    /// Get the recent average daily traded volume for a symbol, in shares
+    /// Used to keep order sizes small relative to the symbol's liquidity
+    async fn get_adv(&self, symbol: &Symbol) -> Result<u64, Self::Error>;
+
+    /// Get the equity positions currently held in the account
+    /// Used to reconcile the hedged position against what the broker reports
+    async fn get_positions(&self) -> Result<Vec<BrokerPosition>, Self::Error>;
+
     /// Return the enum variant representing this broker type
     /// Used for database storage and conditional logic
     fn to_supported_broker(&self) -> SupportedBroker;
@@ -69,7 +156,9 @@ pub trait Broker: Send + Sync + 'static {
 ///
 /// Ensures symbols are non-empty and provides type safety to prevent
 /// mixing symbols with other string types.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Serializes as a plain string and is re-validated when deserialized.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct Symbol(String);
 
 impl Symbol {
@@ -88,6 +177,20 @@ impl Symbol {
     }
 }
 
+impl TryFrom<String> for Symbol {
+    type Error = BrokerError;
+
+    fn try_from(symbol: String) -> Result<Self, Self::Error> {
+        Self::new(symbol)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0
+    }
+}
+
 impl Display for Symbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -98,7 +201,9 @@ impl Display for Symbol {
 ///
 /// Represents whole share quantities with bounds checking.
 /// Values are constrained to 1..=u32::MAX for practical trading limits.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Serializes as a plain number and is re-validated when deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u64", into = "u32")]
 pub struct Shares(u32);
 
 impl Shares {
@@ -124,6 +229,20 @@ impl Shares {
     }
 }
 
+impl TryFrom<u64> for Shares {
+    type Error = BrokerError;
+
+    fn try_from(shares: u64) -> Result<Self, Self::Error> {
+        Self::new(shares)
+    }
+}
+
+impl From<Shares> for u32 {
+    fn from(shares: Shares) -> Self {
+        shares.0
+    }
+}
+
 impl Display for Shares {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -141,11 +260,14 @@ impl std::fmt::Display for InvalidDirectionError {
 
 impl std::error::Error for InvalidDirectionError {}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
 pub enum SupportedBroker {
     Schwab,
     Alpaca,
     DryRun,
+    Sim,
+    Paper,
 }
 
 impl std::fmt::Display for SupportedBroker {
@@ -154,6 +276,8 @@ impl std::fmt::Display for SupportedBroker {
             Self::Schwab => write!(f, "schwab"),
             Self::Alpaca => write!(f, "alpaca"),
             Self::DryRun => write!(f, "dry_run"),
+            Self::Sim => write!(f, "sim"),
+            Self::Paper => write!(f, "paper"),
         }
     }
 }
@@ -170,12 +294,15 @@ impl std::str::FromStr for SupportedBroker {
             "schwab" => Ok(Self::Schwab),
             "alpaca" => Ok(Self::Alpaca),
             "dry_run" => Ok(Self::DryRun),
+            "sim" => Ok(Self::Sim),
+            "paper" => Ok(Self::Paper),
             _ => Err(InvalidBrokerError(s.to_string())),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum Direction {
     Buy,
     Sell,
@@ -214,7 +341,7 @@ pub enum BrokerError {
     Database(#[from] sqlx::Error),
 
     #[error("Schwab API error: {0}")]
-    Schwab(#[from] schwab::SchwabError),
+    Schwab(#[source] schwab::SchwabError),
 
     #[error("Alpaca API error: {0}")]
     Alpaca(Box<apca::Error>),
@@ -254,6 +381,90 @@ pub enum BrokerError {
 
     #[error("Price conversion failed: {price} cannot be converted to cents")]
     PriceConversion { price: f64 },
+
+    #[error("Invalid broker price '{price}': cannot be converted to cents")]
+    InvalidPrice { price: String },
+}
+
+impl BrokerError {
+    /// Short label of the error variant, e.g. for metrics. Schwab errors are
+    /// labelled by their [`schwab::SchwabError`] variant.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "database",
+            Self::Schwab(e) => e.kind(),
+            Self::Alpaca(_) => "alpaca",
+            Self::AlpacaRequest(_) => "alpaca_request",
+            Self::MarketHours(_) => "market_hours",
+            Self::Authentication(_) => "authentication",
+            Self::OrderPlacement(_) => "order_placement",
+            Self::OrderNotFound { .. } => "order_not_found",
+            Self::Network(_) => "network",
+            Self::RateLimit { .. } => "rate_limit",
+            Self::Unavailable { .. } => "unavailable",
+            Self::InvalidOrder { .. } => "invalid_order",
+            Self::NumericConversion(_) => "numeric_conversion",
+            Self::DateTimeParse(_) => "date_time_parse",
+            Self::PriceConversion { .. } => "price_conversion",
+            Self::InvalidPrice { .. } => "invalid_price",
+        }
+    }
+
+    /// Whether the broker could not be reached, would not authenticate us or
+    /// failed on its side. Rejected orders, rate limits and requests that may
+    /// have reached the broker, like timeouts, are not outages.
+    pub fn is_outage(&self) -> bool {
+        match self {
+            Self::Network(_) | Self::Authentication(_) | Self::Unavailable { .. } => true,
+            Self::Schwab(e) => e.is_outage(),
+            _ => false,
+        }
+    }
+
+    /// Delay the broker asked for before the next request, if it rate
+    /// limited this one.
+    pub const fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimit {
+                retry_after_seconds,
+            } => Some(Duration::from_secs(*retry_after_seconds)),
+            _ => None,
+        }
+    }
+}
+
+/// Delay requested by a rate limited broker call, for callers generic over
+/// [`Broker::Error`]. Errors other than [`BrokerError::RateLimit`] yield
+/// `None`.
+pub fn rate_limit_delay<E: std::error::Error + 'static>(error: &E) -> Option<Duration> {
+    (error as &dyn Any)
+        .downcast_ref::<BrokerError>()
+        .and_then(BrokerError::retry_after)
+}
+
+/// Whether a broker call failed because the broker is out, for callers
+/// generic over [`Broker::Error`]. Errors other than [`BrokerError`] are not
+/// outages.
+pub fn is_broker_outage<E: std::error::Error + 'static>(error: &E) -> bool {
+    (error as &dyn Any)
+        .downcast_ref::<BrokerError>()
+        .is_some_and(BrokerError::is_outage)
+}
+
+/// Schwab rate limits surface as [`BrokerError::RateLimit`], so callers can
+/// wait them out the same way for every broker.
+impl From<schwab::SchwabError> for BrokerError {
+    fn from(error: schwab::SchwabError) -> Self {
+        match error {
+            schwab::SchwabError::RateLimited {
+                retry_after_seconds,
+                ..
+            } => Self::RateLimit {
+                retry_after_seconds,
+            },
+            error => Self::Schwab(error),
+        }
+    }
 }
 
 impl From<apca::Error> for BrokerError {
@@ -297,6 +508,24 @@ impl TryIntoBroker for MockBrokerConfig {
     }
 }
 
+#[async_trait]
+impl TryIntoBroker for SimBrokerConfig {
+    type Broker = SimBroker;
+
+    async fn try_into_broker(self) -> Result<Self::Broker, <Self::Broker as Broker>::Error> {
+        SimBroker::try_from_config(self).await
+    }
+}
+
+#[async_trait]
+impl TryIntoBroker for PaperBrokerConfig {
+    type Broker = PaperBroker;
+
+    async fn try_into_broker(self) -> Result<Self::Broker, <Self::Broker as Broker>::Error> {
+        PaperBroker::try_from_config(self).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,10 +573,10 @@ mod tests {
 
     #[test]
     fn test_shares_new_max_boundary() {
-        let shares = Shares::new(u32::MAX as u64).unwrap();
+        let shares = Shares::new(u64::from(u32::MAX)).unwrap();
         assert_eq!(shares.to_string(), u32::MAX.to_string());
 
-        let result = Shares::new(u32::MAX as u64 + 1);
+        let result = Shares::new(u64::from(u32::MAX) + 1);
         assert!(result.is_err());
         assert!(matches!(
             result.unwrap_err(),
@@ -360,4 +589,30 @@ mod tests {
         let shares = Shares::new(1).unwrap();
         assert_eq!(shares.to_string(), "1");
     }
+
+    #[test]
+    fn test_outages_are_told_apart_from_rejections() {
+        let schwab_status = |status| {
+            BrokerError::from(schwab::SchwabError::RequestFailed {
+                action: "place order".to_string(),
+                status,
+                body: String::new(),
+            })
+        };
+
+        assert!(BrokerError::Network("connection refused".to_string()).is_outage());
+        assert!(BrokerError::Authentication("bad key".to_string()).is_outage());
+        assert!(schwab_status(reqwest::StatusCode::BAD_GATEWAY).is_outage());
+        assert!(schwab_status(reqwest::StatusCode::UNAUTHORIZED).is_outage());
+        assert!(BrokerError::from(schwab::SchwabError::RefreshTokenExpired).is_outage());
+
+        assert!(!schwab_status(reqwest::StatusCode::BAD_REQUEST).is_outage());
+        assert!(!BrokerError::OrderPlacement("rejected".to_string()).is_outage());
+        assert!(
+            !BrokerError::RateLimit {
+                retry_after_seconds: 1
+            }
+            .is_outage()
+        );
+    }
 }
@@ -0,0 +1,114 @@
+use std::future::Future;
+use std::time::Duration;
+use tracing::info;
+
+/// Longest single sleep while waiting for the market to open or close.
+///
+/// A schedule change (an early close, a newly announced holiday) or a
+/// shutdown is noticed within this long instead of after the whole wait.
+pub const MARKET_HOURS_RECHECK_INTERVAL: Duration = Duration::from_mins(5);
+
+/// Wait before asking again when the market is closed and the next open is
+/// not known.
+const UNKNOWN_OPEN_RETRY: Duration = Duration::from_mins(1);
+
+/// Market state as reported by a broker's market hours source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MarketSchedule {
+    Open {
+        until_close: Duration,
+    },
+    /// `until_open` is `None` when the next open is not known
+    Closed {
+        until_open: Option<Duration>,
+    },
+}
+
+impl MarketSchedule {
+    pub(crate) const fn until_close(self) -> Option<Duration> {
+        match self {
+            Self::Open { until_close } => Some(until_close),
+            Self::Closed { .. } => None,
+        }
+    }
+}
+
+/// Waits until `check` reports the market open and returns the time until it
+/// closes. The schedule is checked again at least every `recheck_interval`,
+/// so an open that moves closer is not overslept.
+pub(crate) async fn wait_for_open<F, Fut, E>(
+    mut check: F,
+    recheck_interval: Duration,
+) -> Result<Duration, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<MarketSchedule, E>>,
+{
+    loop {
+        match check().await? {
+            MarketSchedule::Open { until_close } => return Ok(until_close),
+            MarketSchedule::Closed {
+                until_open: Some(until_open),
+            } => {
+                info!(
+                    "Market closed, waiting {} seconds until open",
+                    until_open.as_secs()
+                );
+                tokio::time::sleep(until_open.min(recheck_interval)).await;
+            }
+            MarketSchedule::Closed { until_open: None } => {
+                tokio::time::sleep(UNKNOWN_OPEN_RETRY.min(recheck_interval)).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_wait_shortens_when_open_moves_closer() {
+        // Reports an open an hour away, then moved to just ahead, then open
+        let schedules = Arc::new(Mutex::new(vec![
+            MarketSchedule::Open {
+                until_close: Duration::from_hours(6),
+            },
+            MarketSchedule::Closed {
+                until_open: Some(Duration::from_millis(20)),
+            },
+            MarketSchedule::Closed {
+                until_open: Some(Duration::from_hours(1)),
+            },
+        ]));
+        let checks = Arc::clone(&schedules);
+
+        let started = Instant::now();
+        let until_close = wait_for_open(
+            || {
+                let schedule = checks.lock().unwrap().pop().unwrap();
+                async move { Ok::<_, ()>(schedule) }
+            },
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(until_close, Duration::from_hours(6));
+        assert!(schedules.lock().unwrap().is_empty());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_wait_returns_check_error() {
+        let result = wait_for_open(
+            || async { Err::<MarketSchedule, _>("market hours unavailable") },
+            MARKET_HOURS_RECHECK_INTERVAL,
+        )
+        .await;
+
+        assert_eq!(result.unwrap_err(), "market hours unavailable");
+    }
+}
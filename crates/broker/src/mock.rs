@@ -1,15 +1,26 @@
 use async_trait::async_trait;
+use std::collections::HashSet;
 use std::sync::{
-    Arc,
-    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, PoisonError,
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
 };
+use std::time::Duration;
 use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
+use crate::position::{Fill, positions_from_fills};
 use crate::{
-    Broker, BrokerError, MarketOrder, OrderPlacement, OrderState, OrderUpdate, SupportedBroker,
+    Broker, BrokerError, BrokerPosition, Direction, FractionalMarketOrder,
+    FractionalOrderPlacement, LimitOrder, MarketOrder, OrderPlacement, OrderState, OrderUpdate,
+    Quote, Shares, SupportedBroker, Symbol,
 };
 
+/// Average daily volume reported for every symbol in dry-run mode
+pub const MOCK_AVERAGE_DAILY_VOLUME: u64 = 1_000_000;
+
+/// Price every order fills at in dry-run mode
+const MOCK_FILL_PRICE_CENTS: u64 = 10_000;
+
 /// Configuration for MockBroker
 #[derive(Debug, Clone, Default)]
 pub struct MockBrokerConfig;
@@ -18,31 +29,142 @@ pub struct MockBrokerConfig;
 #[derive(Debug, Clone)]
 pub struct MockBroker {
     order_counter: Arc<AtomicU64>,
-    should_fail: bool,
+    should_fail: Arc<AtomicBool>,
     failure_message: String,
+    /// Whether failing placements report the broker as unavailable rather
+    /// than rejecting the order
+    outage: bool,
+    order_latency: Option<Duration>,
+    orders_in_flight: Arc<AtomicUsize>,
+    peak_orders_in_flight: Arc<AtomicUsize>,
+    cancelled_orders: Arc<Mutex<HashSet<String>>>,
+    /// Orders placed so far, which fill unless cancelled
+    placed_orders: Arc<Mutex<Vec<(String, Fill)>>>,
+    rate_limit_next_placement: Arc<Mutex<Option<u64>>>,
+    fractional_shares: bool,
 }
 
 impl MockBroker {
     pub fn new() -> Self {
         Self {
             order_counter: Arc::new(AtomicU64::new(1)),
-            should_fail: false,
+            should_fail: Arc::new(AtomicBool::new(false)),
             failure_message: String::new(),
+            outage: false,
+            order_latency: None,
+            orders_in_flight: Arc::new(AtomicUsize::new(0)),
+            peak_orders_in_flight: Arc::new(AtomicUsize::new(0)),
+            cancelled_orders: Arc::default(),
+            placed_orders: Arc::default(),
+            rate_limit_next_placement: Arc::default(),
+            fractional_shares: false,
         }
     }
 
     pub fn with_failure(message: impl Into<String>) -> Self {
         Self {
-            order_counter: Arc::new(AtomicU64::new(1)),
-            should_fail: true,
+            should_fail: Arc::new(AtomicBool::new(true)),
             failure_message: message.into(),
+            ..Self::new()
         }
     }
 
+    /// Like [`Self::with_failure`], but failing placements report the broker
+    /// as unavailable, as an outage would.
+    pub fn with_outage(message: impl Into<String>) -> Self {
+        Self {
+            outage: true,
+            ..Self::with_failure(message)
+        }
+    }
+
+    /// Makes every order placement take `latency`, so that concurrent
+    /// placements overlap and show up in [`Self::peak_orders_in_flight`].
+    #[must_use]
+    pub const fn with_order_latency(mut self, latency: Duration) -> Self {
+        self.order_latency = Some(latency);
+        self
+    }
+
+    /// Makes the broker accept fractional-share orders, as an account with
+    /// fractional trading enabled would.
+    #[must_use]
+    pub const fn with_fractional_shares(mut self) -> Self {
+        self.fractional_shares = true;
+        self
+    }
+
+    /// Makes this broker and its clones fail (or stop failing) every call
+    /// that `with_failure` would, e.g. to simulate an outage and recovery.
+    pub fn set_failing(&self, failing: bool) {
+        self.should_fail.store(failing, Ordering::SeqCst);
+    }
+
+    /// Makes the next order placement of this broker or its clones fail with
+    /// [`BrokerError::RateLimit`] asking to retry after `retry_after_seconds`.
+    pub fn rate_limit_next_placement(&self, retry_after_seconds: u64) {
+        *self
+            .rate_limit_next_placement
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(retry_after_seconds);
+    }
+
+    fn is_failing(&self) -> bool {
+        self.should_fail.load(Ordering::SeqCst)
+    }
+
+    fn placement_failure(&self) -> BrokerError {
+        if self.outage {
+            BrokerError::Unavailable {
+                message: self.failure_message.clone(),
+            }
+        } else {
+            BrokerError::OrderPlacement(self.failure_message.clone())
+        }
+    }
+
+    /// Number of orders placed successfully.
+    pub fn orders_placed(&self) -> u64 {
+        self.order_counter.load(Ordering::SeqCst) - 1
+    }
+
+    /// Highest number of order placements that were in progress at once.
+    pub fn peak_orders_in_flight(&self) -> usize {
+        self.peak_orders_in_flight.load(Ordering::SeqCst)
+    }
+
+    fn is_cancelled(&self, order_id: &str) -> bool {
+        self.cancelled_orders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(order_id)
+    }
+
     fn generate_order_id(&self) -> String {
         let id = self.order_counter.fetch_add(1, Ordering::SeqCst);
         format!("TEST_{id}")
     }
+
+    fn record_placement(
+        &self,
+        order_id: &str,
+        symbol: &Symbol,
+        shares: Shares,
+        direction: Direction,
+    ) {
+        self.placed_orders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((
+                order_id.to_string(),
+                Fill {
+                    symbol: symbol.clone(),
+                    direction,
+                    shares: u64::from(shares.value()),
+                    price_cents: MOCK_FILL_PRICE_CENTS,
+                },
+            ));
+    }
 }
 
 impl Default for MockBroker {
@@ -69,16 +191,40 @@ impl Broker for MockBroker {
         Ok(std::time::Duration::MAX)
     }
 
+    async fn time_until_market_close(&self) -> Result<Option<std::time::Duration>, Self::Error> {
+        Ok(Some(std::time::Duration::MAX))
+    }
+
     #[tracing::instrument(skip(self), fields(symbol = %order.symbol, shares = %order.shares, direction = %order.direction), level = tracing::Level::INFO)]
     async fn place_market_order(
         &self,
         order: MarketOrder,
     ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
-        if self.should_fail {
-            return Err(BrokerError::OrderPlacement(self.failure_message.clone()));
+        if self.is_failing() {
+            return Err(self.placement_failure());
+        }
+
+        let rate_limited = self
+            .rate_limit_next_placement
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(retry_after_seconds) = rate_limited {
+            return Err(BrokerError::RateLimit {
+                retry_after_seconds,
+            });
         }
 
+        let in_flight = self.orders_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak_orders_in_flight
+            .fetch_max(in_flight, Ordering::SeqCst);
+        if let Some(latency) = self.order_latency {
+            tokio::time::sleep(latency).await;
+        }
+        self.orders_in_flight.fetch_sub(1, Ordering::SeqCst);
+
         let order_id = self.generate_order_id();
+        self.record_placement(&order_id, &order.symbol, order.shares, order.direction);
 
         warn!(
             "[TEST] Would execute order: {} {} shares of {} (order_id: {})",
@@ -87,33 +233,146 @@ impl Broker for MockBroker {
 
         Ok(OrderPlacement {
             order_id,
+            broker: SupportedBroker::DryRun,
             symbol: order.symbol,
             shares: order.shares,
             direction: order.direction,
             placed_at: chrono::Utc::now(),
+            request_payload: None,
+        })
+    }
+
+    fn supports_fractional_shares(&self) -> bool {
+        self.fractional_shares
+    }
+
+    async fn place_fractional_market_order(
+        &self,
+        order: FractionalMarketOrder,
+    ) -> Result<FractionalOrderPlacement<Self::OrderId>, Self::Error> {
+        if !self.fractional_shares {
+            return Err(BrokerError::InvalidOrder {
+                reason: format!(
+                    "Mock broker does not support fractional orders: {} {} shares of {}",
+                    order.direction, order.quantity, order.symbol
+                ),
+            });
+        }
+        order.validate_quantity()?;
+
+        if self.is_failing() {
+            return Err(self.placement_failure());
+        }
+
+        let order_id = self.generate_order_id();
+
+        warn!(
+            "[TEST] Would execute fractional order: {} {} shares of {} (order_id: {})",
+            order.direction, order.quantity, order.symbol, order_id
+        );
+
+        Ok(FractionalOrderPlacement {
+            order_id,
+            broker: SupportedBroker::DryRun,
+            symbol: order.symbol,
+            quantity: order.quantity,
+            direction: order.direction,
+            placed_at: chrono::Utc::now(),
+            request_payload: None,
         })
     }
 
+    async fn place_limit_order(
+        &self,
+        order: LimitOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        if self.is_failing() {
+            return Err(self.placement_failure());
+        }
+
+        let order_id = self.generate_order_id();
+        self.record_placement(&order_id, &order.symbol, order.shares, order.direction);
+
+        warn!(
+            "[TEST] Would execute limit order: {} {} shares of {} at {} (order_id: {})",
+            order.direction,
+            order.shares,
+            order.symbol,
+            order.limit_price(),
+            order_id
+        );
+
+        Ok(OrderPlacement {
+            order_id,
+            broker: SupportedBroker::DryRun,
+            symbol: order.symbol,
+            shares: order.shares,
+            direction: order.direction,
+            placed_at: chrono::Utc::now(),
+            request_payload: None,
+        })
+    }
+
+    async fn preview_market_order(&self, order: &MarketOrder) -> Result<(), Self::Error> {
+        if self.is_failing() {
+            return Err(BrokerError::OrderPlacement(self.failure_message.clone()));
+        }
+
+        warn!(
+            "[TEST] Would accept order: {} {} shares of {}",
+            order.direction, order.shares, order.symbol
+        );
+
+        Ok(())
+    }
+
     async fn get_order_status(&self, order_id: &Self::OrderId) -> Result<OrderState, Self::Error> {
-        if self.should_fail {
+        if self.is_failing() {
             return Err(BrokerError::OrderNotFound {
                 order_id: order_id.clone(),
             });
         }
 
         warn!("[TEST] Checking status for order: {}", order_id);
+
+        if self.is_cancelled(order_id) {
+            warn!("[TEST] Returning mock CANCELLED status");
+            return Ok(OrderState::Cancelled {
+                cancelled_at: chrono::Utc::now(),
+                order_id: order_id.clone(),
+            });
+        }
+
         warn!("[TEST] Returning mock FILLED status with test price");
 
         // Always return filled status in test mode with mock price
         Ok(OrderState::Filled {
             executed_at: chrono::Utc::now(),
             order_id: order_id.clone(),
-            price_cents: 10000, // $100.00 mock price
+            price_cents: MOCK_FILL_PRICE_CENTS,
+            reported_price: Some("100.00".to_string()),
         })
     }
 
+    async fn cancel_order(&self, order_id: &Self::OrderId) -> Result<(), Self::Error> {
+        if self.is_failing() {
+            return Err(BrokerError::OrderNotFound {
+                order_id: order_id.clone(),
+            });
+        }
+
+        warn!("[TEST] Would cancel order: {order_id}");
+
+        self.cancelled_orders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(order_id.clone());
+
+        Ok(())
+    }
+
     async fn poll_pending_orders(&self) -> Result<Vec<OrderUpdate<Self::OrderId>>, Self::Error> {
-        if self.should_fail {
+        if self.is_failing() {
             return Err(BrokerError::Network(self.failure_message.clone()));
         }
 
@@ -123,6 +382,51 @@ impl Broker for MockBroker {
         Ok(Vec::new())
     }
 
+    async fn get_quote(&self, symbol: &Symbol) -> Result<Quote, Self::Error> {
+        if self.is_failing() {
+            return Err(BrokerError::Network(self.failure_message.clone()));
+        }
+
+        warn!("[TEST] Returning mock quote for {symbol}");
+
+        // Quote centered on the mock fill price
+        Ok(Quote {
+            symbol: symbol.clone(),
+            bid_price_cents: 9995,
+            ask_price_cents: 10005,
+            quoted_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn get_adv(&self, symbol: &Symbol) -> Result<u64, Self::Error> {
+        if self.is_failing() {
+            return Err(BrokerError::Network(self.failure_message.clone()));
+        }
+
+        warn!("[TEST] Returning mock average daily volume for {symbol}");
+
+        Ok(MOCK_AVERAGE_DAILY_VOLUME)
+    }
+
+    async fn get_positions(&self) -> Result<Vec<BrokerPosition>, Self::Error> {
+        if self.is_failing() {
+            return Err(BrokerError::Network(self.failure_message.clone()));
+        }
+
+        let placed_orders = self
+            .placed_orders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+
+        Ok(positions_from_fills(
+            placed_orders
+                .into_iter()
+                .filter(|(order_id, _)| !self.is_cancelled(order_id))
+                .map(|(_, fill)| fill),
+        ))
+    }
+
     fn to_supported_broker(&self) -> SupportedBroker {
         SupportedBroker::DryRun
     }
@@ -147,10 +451,46 @@ mod tests {
         assert!(result.is_ok());
 
         let broker = result.unwrap();
-        assert!(!broker.should_fail);
+        assert!(!broker.is_failing());
         assert_eq!(broker.failure_message, "");
     }
 
+    #[tokio::test]
+    async fn test_place_limit_order_echoes_order() {
+        let broker = MockBroker::new();
+
+        let placement = broker
+            .place_limit_order(LimitOrder {
+                symbol: Symbol::new("AAPL").unwrap(),
+                shares: crate::Shares::new(5).unwrap(),
+                direction: crate::Direction::Buy,
+                limit_price_cents: 15_025,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(placement.symbol, Symbol::new("AAPL").unwrap());
+        assert_eq!(placement.shares, crate::Shares::new(5).unwrap());
+        assert_eq!(placement.direction, crate::Direction::Buy);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_reports_cancelled_status() {
+        let broker = MockBroker::new();
+        let order_id = "TEST_1".to_string();
+
+        broker.cancel_order(&order_id).await.unwrap();
+
+        let state = broker.get_order_status(&order_id).await.unwrap();
+        assert!(matches!(state, OrderState::Cancelled { order_id: id, .. } if id == "TEST_1"));
+
+        let other = broker
+            .get_order_status(&"TEST_2".to_string())
+            .await
+            .unwrap();
+        assert!(matches!(other, OrderState::Filled { .. }));
+    }
+
     #[tokio::test]
     async fn test_wait_until_market_open_always_returns_none() {
         let broker = MockBroker::new();
@@ -178,6 +518,61 @@ mod tests {
         assert_eq!(parsed, test_id);
     }
 
+    #[tokio::test]
+    async fn test_get_quote_centered_on_mock_fill_price() {
+        let broker = MockBroker::new();
+        let quote = broker
+            .get_quote(&Symbol::new("AAPL").unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(quote.mid_price_cents(), 10000);
+
+        let failing = MockBroker::with_failure("Test failure");
+        assert!(
+            failing
+                .get_quote(&Symbol::new("AAPL").unwrap())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_positions_net_filled_orders_and_skip_cancelled_ones() {
+        let broker = MockBroker::new();
+        let order = |symbol: &str, shares: u64, direction: Direction| MarketOrder {
+            symbol: Symbol::new(symbol).unwrap(),
+            shares: Shares::new(shares).unwrap(),
+            direction,
+            client_order_id: None,
+        };
+
+        broker
+            .place_market_order(order("AAPL", 10, Direction::Buy))
+            .await
+            .unwrap();
+        broker
+            .place_market_order(order("AAPL", 4, Direction::Sell))
+            .await
+            .unwrap();
+        let cancelled = broker
+            .place_market_order(order("MSFT", 7, Direction::Sell))
+            .await
+            .unwrap();
+        broker.cancel_order(&cancelled.order_id).await.unwrap();
+
+        let positions = broker.get_positions().await.unwrap();
+
+        assert_eq!(
+            positions,
+            vec![BrokerPosition {
+                symbol: Symbol::new("AAPL").unwrap(),
+                quantity: rust_decimal::Decimal::from(6),
+                average_price_cents: MOCK_FILL_PRICE_CENTS,
+            }]
+        );
+    }
+
     #[tokio::test]
     async fn test_to_supported_broker() {
         let broker = MockBroker::new();
@@ -1,21 +1,30 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+pub mod order_type;
 pub mod state;
 pub mod status;
 
+pub use order_type::OrderType;
 pub use state::OrderState;
 pub use status::OrderStatus;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OrderPlacement<OrderId> {
     pub order_id: OrderId,
+    /// Broker that holds the order, which is asked for its status
+    pub broker: crate::SupportedBroker,
     pub symbol: crate::Symbol,
     pub shares: crate::Shares,
     pub direction: crate::Direction,
     pub placed_at: chrono::DateTime<chrono::Utc>,
+    /// Order body exactly as sent to the broker, when the broker is
+    /// configured to capture it
+    pub request_payload: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OrderUpdate<OrderId> {
     pub order_id: OrderId,
     pub symbol: crate::Symbol,
@@ -24,6 +33,8 @@ pub struct OrderUpdate<OrderId> {
     pub status: OrderStatus,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub price_cents: Option<u64>,
+    /// Fill price exactly as the broker reported it, before rounding to cents
+    pub reported_price: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -31,4 +42,145 @@ pub struct MarketOrder {
     pub symbol: crate::Symbol,
     pub shares: crate::Shares,
     pub direction: crate::Direction,
+    /// Caller-assigned id sent with the order (Alpaca's `client_order_id`,
+    /// Schwab's order `tag`). Schwab looks up a recent order with the same
+    /// tag before placing, so a retried placement does not place it twice
+    pub client_order_id: Option<String>,
+}
+
+/// Market order for a quantity that need not be a whole number of shares,
+/// placed only with brokers that report
+/// [`Broker::supports_fractional_shares`](crate::Broker::supports_fractional_shares)
+#[derive(Debug, Clone)]
+pub struct FractionalMarketOrder {
+    pub symbol: crate::Symbol,
+    /// Shares to trade, greater than zero
+    pub quantity: Decimal,
+    pub direction: crate::Direction,
+    /// Caller-assigned id sent with the order, as for [`MarketOrder`]
+    pub client_order_id: Option<String>,
+}
+
+impl FractionalMarketOrder {
+    pub(crate) fn validate_quantity(&self) -> Result<(), crate::BrokerError> {
+        if self.quantity <= Decimal::ZERO {
+            return Err(crate::BrokerError::InvalidOrder {
+                reason: format!("Quantity must be greater than 0, got {}", self.quantity),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FractionalOrderPlacement<OrderId> {
+    pub order_id: OrderId,
+    /// Broker that holds the order, which is asked for its status
+    pub broker: crate::SupportedBroker,
+    pub symbol: crate::Symbol,
+    pub quantity: Decimal,
+    pub direction: crate::Direction,
+    pub placed_at: chrono::DateTime<chrono::Utc>,
+    /// Order body exactly as sent to the broker, when the broker is
+    /// configured to capture it
+    pub request_payload: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LimitOrder {
+    pub symbol: crate::Symbol,
+    pub shares: crate::Shares,
+    pub direction: crate::Direction,
+    /// Worst price per share the order may fill at
+    pub limit_price_cents: u64,
+}
+
+impl LimitOrder {
+    /// Limit price in dollars with two decimals, as brokers accept it
+    pub fn limit_price(&self) -> String {
+        format_price_cents(self.limit_price_cents)
+    }
+}
+
+/// Formats a price in cents as dollars with two decimals
+pub(crate) fn format_price_cents(cents: u64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Direction, Shares, Symbol};
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn test_order_update_json_shape() {
+        let update = OrderUpdate {
+            order_id: "1004055538123".to_string(),
+            symbol: Symbol::new("AAPL").unwrap(),
+            shares: Shares::new(100).unwrap(),
+            direction: Direction::Sell,
+            status: OrderStatus::Filled,
+            updated_at: Utc.with_ymd_and_hms(2025, 1, 15, 14, 30, 0).unwrap(),
+            price_cents: Some(15025),
+            reported_price: Some("150.2500".to_string()),
+        };
+
+        let value = serde_json::to_value(&update).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "order_id": "1004055538123",
+                "symbol": "AAPL",
+                "shares": 100,
+                "direction": "SELL",
+                "status": "FILLED",
+                "updated_at": "2025-01-15T14:30:00Z",
+                "price_cents": 15025,
+                "reported_price": "150.2500"
+            })
+        );
+
+        let round_trip: OrderUpdate<String> = serde_json::from_value(value).unwrap();
+        assert_eq!(round_trip.symbol, update.symbol);
+        assert_eq!(round_trip.shares, update.shares);
+        assert_eq!(round_trip.status, update.status);
+        assert_eq!(round_trip.updated_at, update.updated_at);
+    }
+
+    #[test]
+    fn test_deserialize_rejects_invalid_symbol_and_shares() {
+        let placement = json!({
+            "order_id": "1",
+            "symbol": "AAPL",
+            "shares": 0,
+            "direction": "BUY",
+            "placed_at": "2025-01-15T14:30:00Z"
+        });
+        assert!(serde_json::from_value::<OrderPlacement<String>>(placement).is_err());
+
+        let placement = json!({
+            "order_id": "1",
+            "symbol": "",
+            "shares": 10,
+            "direction": "BUY",
+            "placed_at": "2025-01-15T14:30:00Z"
+        });
+        assert!(serde_json::from_value::<OrderPlacement<String>>(placement).is_err());
+    }
+
+    #[test]
+    fn test_limit_price_keeps_two_decimals() {
+        let order = |limit_price_cents| LimitOrder {
+            symbol: Symbol::new("AAPL").unwrap(),
+            shares: Shares::new(1).unwrap(),
+            direction: Direction::Buy,
+            limit_price_cents,
+        };
+
+        assert_eq!(order(15_025).limit_price(), "150.25");
+        assert_eq!(order(15_000).limit_price(), "150.00");
+        assert_eq!(order(5).limit_price(), "0.05");
+    }
 }
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::PersistenceError;
+
+/// Kind of order submitted for an execution (matches CHECK constraint pattern)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OrderType {
+    Market,
+    Limit,
+}
+
+impl OrderType {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Market => "MARKET",
+            Self::Limit => "LIMIT",
+        }
+    }
+
+    /// Records the kind of order submitted for an execution, along with the
+    /// limit price for limit orders.
+    pub async fn store_update(
+        self,
+        sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        execution_id: i64,
+        limit_price_cents: Option<u64>,
+    ) -> Result<(), PersistenceError> {
+        let order_type = self.as_str();
+        let limit_price_cents = limit_price_cents
+            .map(i64::try_from)
+            .transpose()
+            .map_err(|_| PersistenceError::InvalidPriceCents(i64::MAX))?;
+
+        sqlx::query!(
+            "UPDATE offchain_trades SET order_type = ?1, limit_price_cents = ?2 WHERE id = ?3",
+            order_type,
+            limit_price_cents,
+            execution_id
+        )
+        .execute(&mut **sql_tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Kind of order submitted for an execution; executions recorded before
+    /// limit orders existed are market orders.
+    pub async fn find(pool: &SqlitePool, execution_id: i64) -> Result<Self, PersistenceError> {
+        let order_type = sqlx::query_scalar!(
+            "SELECT order_type FROM offchain_trades WHERE id = ?1",
+            execution_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        order_type
+            .parse()
+            .map_err(|e: ParseOrderTypeError| PersistenceError::InvalidTradeStatus(e.to_string()))
+    }
+}
+
+impl std::fmt::Display for OrderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseOrderTypeError {
+    #[error("Invalid order type: '{0}'. Expected one of: MARKET, LIMIT")]
+    InvalidType(String),
+}
+
+impl std::str::FromStr for OrderType {
+    type Err = ParseOrderTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "MARKET" => Ok(Self::Market),
+            "LIMIT" => Ok(Self::Limit),
+            _ => Err(ParseOrderTypeError::InvalidType(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+    use crate::{Direction, OrderState, Shares, SupportedBroker, Symbol};
+
+    async fn store_pending_execution(pool: &SqlitePool) -> i64 {
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = OrderState::Pending
+            .store(
+                &mut sql_tx,
+                &Symbol::new("AAPL").unwrap(),
+                Shares::new(10).unwrap(),
+                Direction::Buy,
+                SupportedBroker::Schwab,
+            )
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+        execution_id
+    }
+
+    #[tokio::test]
+    async fn test_executions_default_to_market_orders() {
+        let pool = setup_test_db().await;
+        let execution_id = store_pending_execution(&pool).await;
+
+        assert_eq!(
+            OrderType::find(&pool, execution_id).await.unwrap(),
+            OrderType::Market
+        );
+    }
+
+    #[tokio::test]
+    async fn test_limit_order_type_round_trips() {
+        let pool = setup_test_db().await;
+        let execution_id = store_pending_execution(&pool).await;
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        OrderType::Limit
+            .store_update(&mut sql_tx, execution_id, Some(15_025))
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert_eq!(
+            OrderType::find(&pool, execution_id).await.unwrap(),
+            OrderType::Limit
+        );
+    }
+
+    #[tokio::test]
+    async fn test_limit_price_is_stored_only_for_limit_orders() {
+        let pool = setup_test_db().await;
+        let execution_id = store_pending_execution(&pool).await;
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        assert!(
+            OrderType::Limit
+                .store_update(&mut sql_tx, execution_id, None)
+                .await
+                .is_err()
+        );
+        assert!(
+            OrderType::Market
+                .store_update(&mut sql_tx, execution_id, Some(15_025))
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_order_type_string_round_trip() {
+        for order_type in [OrderType::Market, OrderType::Limit] {
+            assert_eq!(
+                order_type.as_str().parse::<OrderType>().unwrap(),
+                order_type
+            );
+        }
+        assert!("STOP".parse::<OrderType>().is_err());
+    }
+}
@@ -9,6 +9,7 @@ pub(crate) struct OrderStateDbFields {
     pub(crate) order_id: Option<String>,
     pub(crate) price_cents: Option<i64>,
     pub(crate) executed_at: Option<chrono::NaiveDateTime>,
+    pub(crate) reported_price: Option<String>,
 }
 
 // Stateful enum with associated data for runtime use
@@ -22,11 +23,18 @@ pub enum OrderState {
         executed_at: DateTime<Utc>,
         order_id: String,
         price_cents: u64,
+        /// Fill price exactly as the broker reported it, kept for audit
+        reported_price: Option<String>,
     },
     Failed {
         failed_at: DateTime<Utc>,
         error_reason: Option<String>,
     },
+    /// Pulled back from the broker before it filled
+    Cancelled {
+        cancelled_at: DateTime<Utc>,
+        order_id: String,
+    },
 }
 
 impl OrderState {
@@ -36,6 +44,7 @@ impl OrderState {
             Self::Submitted { .. } => OrderStatus::Submitted,
             Self::Filled { .. } => OrderStatus::Filled,
             Self::Failed { .. } => OrderStatus::Failed,
+            Self::Cancelled { .. } => OrderStatus::Cancelled,
         }
     }
 
@@ -69,6 +78,7 @@ impl OrderState {
                     executed_at: Utc.from_utc_datetime(&executed_at),
                     order_id,
                     price_cents: price_cents.try_into()?,
+                    reported_price: None, // Audit-only column, not needed at runtime
                 })
             }
             OrderStatus::Failed => {
@@ -80,6 +90,18 @@ impl OrderState {
                     error_reason: None, // We don't store error_reason in database yet
                 })
             }
+            OrderStatus::Cancelled => {
+                let order_id = order_id.ok_or_else(|| BrokerError::InvalidOrder {
+                    reason: "CANCELLED requires order_id".to_string(),
+                })?;
+                let cancelled_at = executed_at.ok_or_else(|| BrokerError::InvalidOrder {
+                    reason: "CANCELLED requires executed_at timestamp".to_string(),
+                })?;
+                Ok(Self::Cancelled {
+                    cancelled_at: Utc.from_utc_datetime(&cancelled_at),
+                    order_id,
+                })
+            }
         }
     }
 
@@ -90,17 +112,22 @@ impl OrderState {
     ) -> Result<(), crate::PersistenceError> {
         let status_str = self.status().as_str();
         let db_fields = self.to_db_fields()?;
+        let placed_at = self.placed_at();
 
+        // An order keeps the time it was first submitted
         sqlx::query!(
             "
             UPDATE offchain_trades
-            SET status = ?1, order_id = ?2, price_cents = ?3, executed_at = ?4
-            WHERE id = ?5
+            SET status = ?1, order_id = ?2, price_cents = ?3, executed_at = ?4,
+                reported_price = ?5, placed_at = COALESCE(placed_at, ?6)
+            WHERE id = ?7
             ",
             status_str,
             db_fields.order_id,
             db_fields.price_cents,
             db_fields.executed_at,
+            db_fields.reported_price,
+            placed_at,
             execution_id
         )
         .execute(&mut **sql_tx)
@@ -119,6 +146,7 @@ impl OrderState {
     ) -> Result<i64, crate::PersistenceError> {
         let status_str = self.status().as_str();
         let db_fields = self.to_db_fields()?;
+        let placed_at = self.placed_at();
 
         let symbol_str = symbol.to_string();
         let shares_i64 = i64::from(shares.value());
@@ -135,9 +163,11 @@ impl OrderState {
                 order_id,
                 price_cents,
                 status,
-                executed_at
+                executed_at,
+                reported_price,
+                placed_at
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             "#,
             symbol_str,
             shares_i64,
@@ -146,7 +176,9 @@ impl OrderState {
             db_fields.order_id,
             db_fields.price_cents,
             status_str,
-            db_fields.executed_at
+            db_fields.executed_at,
+            db_fields.reported_price,
+            placed_at
         )
         .execute(&mut **sql_tx)
         .await?;
@@ -154,26 +186,36 @@ impl OrderState {
         Ok(result.last_insert_rowid())
     }
 
+    /// Time to record as the order's placement when storing a submitted
+    /// order.
+    fn placed_at(&self) -> Option<chrono::NaiveDateTime> {
+        matches!(self, Self::Submitted { .. }).then(|| Utc::now().naive_utc())
+    }
+
     pub(crate) fn to_db_fields(&self) -> Result<OrderStateDbFields, BrokerError> {
         match self {
             Self::Pending => Ok(OrderStateDbFields {
                 order_id: None,
                 price_cents: None,
                 executed_at: None,
+                reported_price: None,
             }),
             Self::Submitted { order_id } => Ok(OrderStateDbFields {
                 order_id: Some(order_id.clone()),
                 price_cents: None,
                 executed_at: None,
+                reported_price: None,
             }),
             Self::Filled {
                 executed_at,
                 order_id,
                 price_cents,
+                reported_price,
             } => Ok(OrderStateDbFields {
                 order_id: Some(order_id.clone()),
                 price_cents: Some((*price_cents).try_into()?),
                 executed_at: Some(executed_at.naive_utc()),
+                reported_price: reported_price.clone(),
             }),
             Self::Failed {
                 failed_at,
@@ -182,6 +224,16 @@ impl OrderState {
                 order_id: None,
                 price_cents: None,
                 executed_at: Some(failed_at.naive_utc()),
+                reported_price: None,
+            }),
+            Self::Cancelled {
+                cancelled_at,
+                order_id,
+            } => Ok(OrderStateDbFields {
+                order_id: Some(order_id.clone()),
+                price_cents: None,
+                executed_at: Some(cancelled_at.naive_utc()),
+                reported_price: None,
             }),
         }
     }
@@ -231,6 +283,7 @@ mod tests {
                 executed_at,
                 order_id,
                 price_cents,
+                reported_price: _,
             } => {
                 assert_eq!(order_id, "ORDER123");
                 assert_eq!(price_cents, 15000);
@@ -258,6 +311,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cancelled_round_trips_through_db_fields() {
+        let state = OrderState::Cancelled {
+            cancelled_at: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            order_id: "ORDER123".to_string(),
+        };
+
+        let db_fields = state.to_db_fields().unwrap();
+        let restored = OrderState::from_db_row(
+            state.status(),
+            db_fields.order_id,
+            db_fields.price_cents,
+            db_fields.executed_at,
+        )
+        .unwrap();
+
+        assert_eq!(restored, state);
+        assert_eq!(state.status().as_str(), "CANCELLED");
+    }
+
+    #[test]
+    fn test_from_db_row_cancelled_missing_order_id() {
+        let timestamp = Utc::now().naive_utc();
+        let result = OrderState::from_db_row(OrderStatus::Cancelled, None, None, Some(timestamp));
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_from_db_row_submitted_missing_order_id() {
         let result = OrderState::from_db_row(OrderStatus::Submitted, None, None, None);
@@ -328,11 +408,13 @@ mod tests {
             executed_at: timestamp,
             order_id: "ORDER123".to_string(),
             price_cents: 15000,
+            reported_price: Some("150.0012".to_string()),
         };
         let db_fields = state.to_db_fields().unwrap();
         assert_eq!(db_fields.order_id, Some("ORDER123".to_string()));
         assert_eq!(db_fields.price_cents, Some(15000));
         assert_eq!(db_fields.executed_at, Some(timestamp.naive_utc()));
+        assert_eq!(db_fields.reported_price, Some("150.0012".to_string()));
     }
 
     #[test]
@@ -363,6 +445,7 @@ mod tests {
                 executed_at: Utc::now(),
                 order_id: "ORDER123".to_string(),
                 price_cents: 15000,
+                reported_price: None,
             }
             .status(),
             OrderStatus::Filled
@@ -1,10 +1,14 @@
+use serde::{Deserialize, Serialize};
+
 /// Flat enum for database storage (matches CHECK constraint pattern)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum OrderStatus {
     Pending,
     Submitted,
     Filled,
     Failed,
+    Cancelled,
 }
 
 impl OrderStatus {
@@ -14,6 +18,7 @@ impl OrderStatus {
             Self::Submitted => "SUBMITTED",
             Self::Filled => "FILLED",
             Self::Failed => "FAILED",
+            Self::Cancelled => "CANCELLED",
         }
     }
 }
@@ -26,7 +31,9 @@ impl std::fmt::Display for OrderStatus {
 
 #[derive(Debug, thiserror::Error)]
 pub enum ParseOrderStatusError {
-    #[error("Invalid order status: '{0}'. Expected one of: PENDING, SUBMITTED, FILLED, FAILED")]
+    #[error(
+        "Invalid order status: '{0}'. Expected one of: PENDING, SUBMITTED, FILLED, FAILED, CANCELLED"
+    )]
     InvalidStatus(String),
 }
 
@@ -39,6 +46,7 @@ impl std::str::FromStr for OrderStatus {
             "SUBMITTED" => Ok(Self::Submitted),
             "FILLED" => Ok(Self::Filled),
             "FAILED" => Ok(Self::Failed),
+            "CANCELLED" => Ok(Self::Cancelled),
             _ => Err(ParseOrderStatusError::InvalidStatus(s.to_string())),
         }
     }
@@ -0,0 +1,692 @@
+//! Paper-trading broker that records intended orders instead of placing them.
+//!
+//! Unlike [`crate::MockBroker`], which fills every order at a fixed price, and
+//! [`crate::SimBroker`], which fills around a configured reference price,
+//! [`PaperBroker`] persists each order to the `paper_orders` table and fills
+//! it at the prevailing Pyth price: the one recorded with the latest onchain
+//! trade of the symbol. Everything upstream of the broker runs exactly as it
+//! does live, so the recorded fills show what the strategy would have made
+//! against real market data. Limit orders stay SUBMITTED until the prevailing
+//! price is within their limit.
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::mock::MOCK_AVERAGE_DAILY_VOLUME;
+use crate::position::{Fill, positions_from_fills};
+use crate::price::{PriceRounding, price_to_cents};
+use crate::{
+    Broker, BrokerError, BrokerPosition, Direction, FractionalMarketOrder,
+    FractionalOrderPlacement, LimitOrder, MarketOrder, OrderPlacement, OrderState, OrderStatus,
+    OrderUpdate, Quote, Shares, SupportedBroker, Symbol,
+};
+
+const ORDER_ID_PREFIX: &str = "PAPER_";
+
+/// Paper-trading broker configuration
+#[derive(Debug, Clone)]
+pub struct PaperBrokerConfig {
+    pub pool: SqlitePool,
+}
+
+/// Broker that records orders in `paper_orders` and fills them at the
+/// prevailing Pyth price
+#[derive(Debug, Clone)]
+pub struct PaperBroker {
+    pool: SqlitePool,
+}
+
+struct PythPrice {
+    cents: u64,
+    /// Price in dollars as recorded with the onchain trade
+    reported: String,
+}
+
+struct PaperOrderRow {
+    id: i64,
+    symbol: String,
+    shares: i64,
+    direction: String,
+    limit_price_cents: Option<i64>,
+    status: String,
+    price_cents: Option<i64>,
+    reported_price: Option<String>,
+    placed_at: NaiveDateTime,
+    executed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug)]
+struct PaperOrder {
+    id: i64,
+    symbol: Symbol,
+    shares: Shares,
+    direction: Direction,
+    limit_price_cents: Option<u64>,
+    placed_at: chrono::DateTime<Utc>,
+    state: OrderState,
+}
+
+impl TryFrom<PaperOrderRow> for PaperOrder {
+    type Error = BrokerError;
+
+    fn try_from(row: PaperOrderRow) -> Result<Self, Self::Error> {
+        let invalid = |reason: String| BrokerError::InvalidOrder { reason };
+
+        let status: OrderStatus = row.status.parse().map_err(|e| invalid(format!("{e}")))?;
+        let mut state = OrderState::from_db_row(
+            status,
+            Some(paper_order_id(row.id)),
+            row.price_cents,
+            row.executed_at,
+        )?;
+        if let OrderState::Filled { reported_price, .. } = &mut state {
+            reported_price.clone_from(&row.reported_price);
+        }
+
+        Ok(Self {
+            id: row.id,
+            symbol: Symbol::new(row.symbol)?,
+            shares: Shares::new(row.shares.try_into()?)?,
+            direction: row.direction.parse().map_err(|e| invalid(format!("{e}")))?,
+            limit_price_cents: row.limit_price_cents.map(u64::try_from).transpose()?,
+            placed_at: Utc.from_utc_datetime(&row.placed_at),
+            state,
+        })
+    }
+}
+
+impl PaperOrder {
+    fn placement(&self) -> OrderPlacement<String> {
+        OrderPlacement {
+            order_id: paper_order_id(self.id),
+            broker: SupportedBroker::Paper,
+            symbol: self.symbol.clone(),
+            shares: self.shares,
+            direction: self.direction,
+            placed_at: self.placed_at,
+            request_payload: None,
+        }
+    }
+
+    fn update(&self) -> OrderUpdate<String> {
+        let (price_cents, reported_price) = match &self.state {
+            OrderState::Filled {
+                price_cents,
+                reported_price,
+                ..
+            } => (Some(*price_cents), reported_price.clone()),
+            _ => (None, None),
+        };
+
+        OrderUpdate {
+            order_id: paper_order_id(self.id),
+            symbol: self.symbol.clone(),
+            shares: self.shares,
+            direction: self.direction,
+            status: self.state.status(),
+            updated_at: Utc::now(),
+            price_cents,
+            reported_price,
+        }
+    }
+}
+
+fn paper_order_id(id: i64) -> String {
+    format!("{ORDER_ID_PREFIX}{id}")
+}
+
+impl PaperBroker {
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Pyth price recorded with the latest onchain trade of `symbol`, which
+    /// is traded onchain as its tokenized forms (`AAPL0x`, `AAPLs1`).
+    async fn prevailing_price(&self, symbol: &Symbol) -> Result<Option<PythPrice>, BrokerError> {
+        let symbol = symbol.to_string();
+        let pyth_price = sqlx::query_scalar!(
+            r#"
+            SELECT pyth_price AS "pyth_price!: f64"
+            FROM onchain_trades
+            WHERE pyth_price IS NOT NULL
+              AND symbol IN (?1, ?1 || '0x', ?1 || 's1')
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+            "#,
+            symbol
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        pyth_price
+            .map(|price| {
+                let reported = price.to_string();
+                let cents = price_to_cents(&reported, PriceRounding::default())?;
+                Ok(PythPrice { cents, reported })
+            })
+            .transpose()
+    }
+
+    async fn load(&self, order_id: &str) -> Result<PaperOrder, BrokerError> {
+        let not_found = || BrokerError::OrderNotFound {
+            order_id: order_id.to_string(),
+        };
+        let id: i64 = order_id
+            .strip_prefix(ORDER_ID_PREFIX)
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(not_found)?;
+
+        sqlx::query_as!(
+            PaperOrderRow,
+            r#"
+            SELECT id AS "id!", symbol, shares, direction, limit_price_cents, status,
+                   price_cents, reported_price, placed_at AS "placed_at: NaiveDateTime",
+                   executed_at AS "executed_at: NaiveDateTime"
+            FROM paper_orders
+            WHERE id = ?1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(not_found)?
+        .try_into()
+    }
+
+    async fn find_by_client_order_id(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Option<PaperOrder>, BrokerError> {
+        sqlx::query_as!(
+            PaperOrderRow,
+            r#"
+            SELECT id AS "id!", symbol, shares, direction, limit_price_cents, status,
+                   price_cents, reported_price, placed_at AS "placed_at: NaiveDateTime",
+                   executed_at AS "executed_at: NaiveDateTime"
+            FROM paper_orders
+            WHERE client_order_id = ?1
+            "#,
+            client_order_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(PaperOrder::try_from)
+        .transpose()
+    }
+
+    async fn record(
+        &self,
+        symbol: Symbol,
+        shares: Shares,
+        direction: Direction,
+        client_order_id: Option<&str>,
+        limit_price_cents: Option<u64>,
+    ) -> Result<PaperOrder, BrokerError> {
+        let symbol_str = symbol.to_string();
+        let shares_i64 = i64::from(shares.value());
+        let direction_str = direction.as_str();
+        let limit_price_i64 = limit_price_cents.map(i64::try_from).transpose()?;
+        let placed_at = Utc::now();
+        let placed_at_naive = placed_at.naive_utc();
+
+        let id = sqlx::query!(
+            "
+            INSERT INTO paper_orders
+                (symbol, shares, direction, client_order_id, limit_price_cents, status, placed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, 'SUBMITTED', ?6)
+            ",
+            symbol_str,
+            shares_i64,
+            direction_str,
+            client_order_id,
+            limit_price_i64,
+            placed_at_naive
+        )
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid();
+
+        Ok(PaperOrder {
+            id,
+            symbol,
+            shares,
+            direction,
+            limit_price_cents,
+            placed_at,
+            state: OrderState::Submitted {
+                order_id: paper_order_id(id),
+            },
+        })
+    }
+
+    /// Fills a SUBMITTED `order` at the prevailing price, if one is known
+    /// and, for a limit order, within the limit.
+    async fn try_fill(&self, order: &mut PaperOrder) -> Result<(), BrokerError> {
+        if !matches!(order.state, OrderState::Submitted { .. }) {
+            return Ok(());
+        }
+
+        let Some(price) = self.prevailing_price(&order.symbol).await? else {
+            return Ok(());
+        };
+
+        let within_limit = order
+            .limit_price_cents
+            .is_none_or(|limit| match order.direction {
+                Direction::Buy => price.cents <= limit,
+                Direction::Sell => price.cents >= limit,
+            });
+        if !within_limit {
+            return Ok(());
+        }
+
+        let price_cents = i64::try_from(price.cents)?;
+        let executed_at = Utc::now();
+        let executed_at_naive = executed_at.naive_utc();
+
+        sqlx::query!(
+            "
+            UPDATE paper_orders
+            SET status = 'FILLED', price_cents = ?1, reported_price = ?2, executed_at = ?3
+            WHERE id = ?4 AND status = 'SUBMITTED'
+            ",
+            price_cents,
+            price.reported,
+            executed_at_naive,
+            order.id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        info!(
+            "[PAPER] Filled order {}: {} {} shares of {} at {} cents",
+            paper_order_id(order.id),
+            order.direction,
+            order.shares,
+            order.symbol,
+            price.cents
+        );
+
+        order.state = OrderState::Filled {
+            executed_at,
+            order_id: paper_order_id(order.id),
+            price_cents: price.cents,
+            reported_price: Some(price.reported),
+        };
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Broker for PaperBroker {
+    type Error = BrokerError;
+    type OrderId = String;
+    type Config = PaperBrokerConfig;
+
+    async fn try_from_config(config: Self::Config) -> Result<Self, Self::Error> {
+        warn!("[PAPER] Initializing paper-trading broker: orders are recorded, never placed");
+        Ok(Self::new(config.pool))
+    }
+
+    async fn wait_until_market_open(&self) -> Result<std::time::Duration, Self::Error> {
+        // Paper orders fill whenever a price is known
+        Ok(std::time::Duration::MAX)
+    }
+
+    async fn time_until_market_close(&self) -> Result<Option<std::time::Duration>, Self::Error> {
+        Ok(Some(std::time::Duration::MAX))
+    }
+
+    async fn place_market_order(
+        &self,
+        order: MarketOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        let recorded = match &order.client_order_id {
+            Some(client_order_id) => self
+                .find_by_client_order_id(client_order_id)
+                .await?
+                .map(|recorded| (client_order_id, recorded)),
+            None => None,
+        };
+
+        if let Some((client_order_id, recorded)) = recorded {
+            info!(
+                "[PAPER] Order with client id {client_order_id} already recorded as {}",
+                paper_order_id(recorded.id)
+            );
+            return Ok(recorded.placement());
+        }
+
+        // A market order fills at once, so it needs a price to fill at
+        if self.prevailing_price(&order.symbol).await?.is_none() {
+            return Err(BrokerError::Unavailable {
+                message: format!("no Pyth price recorded for {}", order.symbol),
+            });
+        }
+
+        let mut paper_order = self
+            .record(
+                order.symbol,
+                order.shares,
+                order.direction,
+                order.client_order_id.as_deref(),
+                None,
+            )
+            .await?;
+        self.try_fill(&mut paper_order).await?;
+
+        Ok(paper_order.placement())
+    }
+
+    fn supports_fractional_shares(&self) -> bool {
+        false
+    }
+
+    async fn place_fractional_market_order(
+        &self,
+        order: FractionalMarketOrder,
+    ) -> Result<FractionalOrderPlacement<Self::OrderId>, Self::Error> {
+        Err(BrokerError::InvalidOrder {
+            reason: format!(
+                "Paper broker does not support fractional orders: {} {} shares of {}",
+                order.direction, order.quantity, order.symbol
+            ),
+        })
+    }
+
+    async fn place_limit_order(
+        &self,
+        order: LimitOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        let mut paper_order = self
+            .record(
+                order.symbol,
+                order.shares,
+                order.direction,
+                None,
+                Some(order.limit_price_cents),
+            )
+            .await?;
+        self.try_fill(&mut paper_order).await?;
+
+        Ok(paper_order.placement())
+    }
+
+    async fn preview_market_order(&self, order: &MarketOrder) -> Result<(), Self::Error> {
+        warn!(
+            "[PAPER] Would record order: {} {} shares of {}",
+            order.direction, order.shares, order.symbol
+        );
+
+        Ok(())
+    }
+
+    async fn get_order_status(&self, order_id: &Self::OrderId) -> Result<OrderState, Self::Error> {
+        let mut paper_order = self.load(order_id).await?;
+        self.try_fill(&mut paper_order).await?;
+
+        Ok(paper_order.state)
+    }
+
+    async fn cancel_order(&self, order_id: &Self::OrderId) -> Result<(), Self::Error> {
+        let paper_order = self.load(order_id).await?;
+        let cancelled_at = Utc::now().naive_utc();
+
+        let cancelled = sqlx::query!(
+            "
+            UPDATE paper_orders
+            SET status = 'CANCELLED', executed_at = ?1
+            WHERE id = ?2 AND status = 'SUBMITTED'
+            ",
+            cancelled_at,
+            paper_order.id
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if cancelled == 0 {
+            return Err(BrokerError::InvalidOrder {
+                reason: format!(
+                    "Order {order_id} is {} and cannot be cancelled",
+                    paper_order.state.status()
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn poll_pending_orders(&self) -> Result<Vec<OrderUpdate<Self::OrderId>>, Self::Error> {
+        let rows = sqlx::query_as!(
+            PaperOrderRow,
+            r#"
+            SELECT id AS "id!", symbol, shares, direction, limit_price_cents, status,
+                   price_cents, reported_price, placed_at AS "placed_at: NaiveDateTime",
+                   executed_at AS "executed_at: NaiveDateTime"
+            FROM paper_orders
+            WHERE status = 'SUBMITTED'
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut updates = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut paper_order = PaperOrder::try_from(row)?;
+            self.try_fill(&mut paper_order).await?;
+            updates.push(paper_order.update());
+        }
+
+        Ok(updates)
+    }
+
+    async fn get_quote(&self, symbol: &Symbol) -> Result<Quote, Self::Error> {
+        let price =
+            self.prevailing_price(symbol)
+                .await?
+                .ok_or_else(|| BrokerError::Unavailable {
+                    message: format!("no Pyth price recorded for {symbol}"),
+                })?;
+
+        Ok(Quote {
+            symbol: symbol.clone(),
+            bid_price_cents: price.cents,
+            ask_price_cents: price.cents,
+            quoted_at: Utc::now(),
+        })
+    }
+
+    async fn get_adv(&self, _symbol: &Symbol) -> Result<u64, Self::Error> {
+        Ok(MOCK_AVERAGE_DAILY_VOLUME)
+    }
+
+    async fn get_positions(&self) -> Result<Vec<BrokerPosition>, Self::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT symbol, shares, direction, price_cents AS "price_cents!"
+            FROM paper_orders
+            WHERE status = 'FILLED'
+            ORDER BY executed_at, id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let fills =
+            rows.into_iter()
+                .map(|row| {
+                    Ok(Fill {
+                        symbol: Symbol::new(row.symbol)?,
+                        direction: row.direction.parse().map_err(|e| {
+                            BrokerError::InvalidOrder {
+                                reason: format!("{e}"),
+                            }
+                        })?,
+                        shares: row.shares.try_into()?,
+                        price_cents: row.price_cents.try_into()?,
+                    })
+                })
+                .collect::<Result<Vec<_>, BrokerError>>()?;
+
+        Ok(positions_from_fills(fills))
+    }
+
+    fn to_supported_broker(&self) -> SupportedBroker {
+        SupportedBroker::Paper
+    }
+
+    fn parse_order_id(&self, order_id_str: &str) -> Result<Self::OrderId, Self::Error> {
+        Ok(order_id_str.to_string())
+    }
+
+    async fn run_broker_maintenance(&self) -> Option<JoinHandle<()>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    async fn record_pyth_price(pool: &SqlitePool, log_index: i64, symbol: &str, price: f64) {
+        sqlx::query(
+            "INSERT INTO onchain_trades
+                (tx_hash, log_index, symbol, amount, direction, price_usdc, pyth_price)
+             VALUES
+                ('0x1111111111111111111111111111111111111111111111111111111111111111',
+                 ?1, ?2, '1', 'BUY', '100', ?3)",
+        )
+        .bind(log_index)
+        .bind(symbol)
+        .bind(price)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    fn market_order(symbol: &str, shares: u64, client_order_id: Option<&str>) -> MarketOrder {
+        MarketOrder {
+            symbol: Symbol::new(symbol).unwrap(),
+            shares: Shares::new(shares).unwrap(),
+            direction: Direction::Buy,
+            client_order_id: client_order_id.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_market_order_recorded_and_filled_at_prevailing_pyth_price() {
+        let pool = setup_test_db().await;
+        let broker = PaperBroker::new(pool.clone());
+        record_pyth_price(&pool, 1, "AAPL0x", 150.25).await;
+        record_pyth_price(&pool, 2, "AAPL0x", 151.5).await;
+
+        let placement = broker
+            .place_market_order(market_order("AAPL", 10, Some("exec-1")))
+            .await
+            .unwrap();
+        assert_eq!(placement.order_id, "PAPER_1");
+
+        let OrderState::Filled {
+            price_cents,
+            reported_price,
+            ..
+        } = broker.get_order_status(&placement.order_id).await.unwrap()
+        else {
+            panic!("Expected paper order to be filled");
+        };
+        assert_eq!(price_cents, 15150);
+        assert_eq!(reported_price.as_deref(), Some("151.5"));
+
+        // A retried placement returns the recorded order
+        let retried = broker
+            .place_market_order(market_order("AAPL", 10, Some("exec-1")))
+            .await
+            .unwrap();
+        assert_eq!(retried.order_id, placement.order_id);
+        let recorded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM paper_orders")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(recorded, 1);
+        assert!(broker.poll_pending_orders().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_positions_net_filled_paper_orders() {
+        let pool = setup_test_db().await;
+        let broker = PaperBroker::new(pool.clone());
+        record_pyth_price(&pool, 1, "AAPL0x", 150.0).await;
+
+        broker
+            .place_market_order(market_order("AAPL", 10, None))
+            .await
+            .unwrap();
+        record_pyth_price(&pool, 2, "AAPL0x", 160.0).await;
+        broker
+            .place_market_order(MarketOrder {
+                direction: Direction::Sell,
+                ..market_order("AAPL", 4, None)
+            })
+            .await
+            .unwrap();
+
+        let positions = broker.get_positions().await.unwrap();
+
+        assert_eq!(
+            positions,
+            vec![BrokerPosition {
+                symbol: Symbol::new("AAPL").unwrap(),
+                quantity: rust_decimal::Decimal::from(6),
+                average_price_cents: 15000,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_market_order_without_pyth_price_is_rejected() {
+        let pool = setup_test_db().await;
+        let broker = PaperBroker::new(pool);
+
+        let result = broker
+            .place_market_order(market_order("MSFT", 1, None))
+            .await;
+
+        assert!(matches!(result, Err(BrokerError::Unavailable { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_limit_order_fills_once_price_is_within_limit() {
+        let pool = setup_test_db().await;
+        let broker = PaperBroker::new(pool.clone());
+        record_pyth_price(&pool, 1, "TSLAs1", 250.0).await;
+
+        let placement = broker
+            .place_limit_order(LimitOrder {
+                symbol: Symbol::new("TSLA").unwrap(),
+                shares: Shares::new(2).unwrap(),
+                direction: Direction::Buy,
+                limit_price_cents: 24_900,
+            })
+            .await
+            .unwrap();
+
+        let updates = broker.poll_pending_orders().await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].status, OrderStatus::Submitted);
+
+        record_pyth_price(&pool, 2, "TSLAs1", 248.75).await;
+        let updates = broker.poll_pending_orders().await.unwrap();
+        assert_eq!(updates[0].status, OrderStatus::Filled);
+        assert_eq!(updates[0].price_cents, Some(24_875));
+
+        let result = broker.cancel_order(&placement.order_id).await;
+        assert!(matches!(result, Err(BrokerError::InvalidOrder { .. })));
+    }
+}
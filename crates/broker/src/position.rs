@@ -0,0 +1,127 @@
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::{Direction, Symbol};
+
+/// Position held in a symbol as reported by the broker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokerPosition {
+    pub symbol: Symbol,
+    /// Net shares held, negative for a short position
+    pub quantity: Decimal,
+    /// Average price paid (or received, for a short position) per share
+    pub average_price_cents: u64,
+}
+
+/// A filled order of a broker that keeps its fills in memory or in its own
+/// tables rather than in a real account
+#[derive(Debug, Clone)]
+pub(crate) struct Fill {
+    pub(crate) symbol: Symbol,
+    pub(crate) direction: Direction,
+    pub(crate) shares: u64,
+    pub(crate) price_cents: u64,
+}
+
+#[derive(Debug, Default)]
+struct OpenPosition {
+    quantity: i128,
+    average_price_cents: u128,
+}
+
+impl OpenPosition {
+    /// Adds a fill of `quantity` signed shares. Fills that grow the position
+    /// move its average price, fills that reduce it leave it unchanged and a
+    /// fill that flips it opens the new side at its own price.
+    fn apply(&mut self, quantity: i128, price_cents: u128) {
+        let held = self.quantity.unsigned_abs();
+        let resulting = self.quantity + quantity;
+
+        if self.quantity == 0 || self.quantity.signum() == quantity.signum() {
+            let total = resulting.unsigned_abs();
+            let cost = held * self.average_price_cents + quantity.unsigned_abs() * price_cents;
+            self.average_price_cents = (cost + total / 2) / total;
+        } else if resulting == 0 {
+            self.average_price_cents = 0;
+        } else if resulting.signum() != self.quantity.signum() {
+            self.average_price_cents = price_cents;
+        }
+
+        self.quantity = resulting;
+    }
+}
+
+/// Net positions built up by `fills`, applied in order, ordered by symbol.
+/// Symbols whose fills net out to nothing are left out.
+pub(crate) fn positions_from_fills(fills: impl IntoIterator<Item = Fill>) -> Vec<BrokerPosition> {
+    let mut open: HashMap<Symbol, OpenPosition> = HashMap::new();
+
+    for fill in fills {
+        let shares = i128::from(fill.shares);
+        let quantity = match fill.direction {
+            Direction::Buy => shares,
+            Direction::Sell => -shares,
+        };
+        open.entry(fill.symbol)
+            .or_default()
+            .apply(quantity, u128::from(fill.price_cents));
+    }
+
+    let mut positions: Vec<_> = open
+        .into_iter()
+        .filter(|(_, position)| position.quantity != 0)
+        .map(|(symbol, position)| BrokerPosition {
+            symbol,
+            quantity: Decimal::from_i128_with_scale(position.quantity, 0),
+            average_price_cents: u64::try_from(position.average_price_cents).unwrap_or(u64::MAX),
+        })
+        .collect();
+    positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(symbol: &str, direction: Direction, shares: u64, price_cents: u64) -> Fill {
+        Fill {
+            symbol: Symbol::new(symbol).unwrap(),
+            direction,
+            shares,
+            price_cents,
+        }
+    }
+
+    #[test]
+    fn test_positions_from_fills_track_net_quantity_and_average_price() {
+        let positions = positions_from_fills([
+            fill("MSFT", Direction::Buy, 10, 10_000),
+            fill("MSFT", Direction::Buy, 30, 12_000),
+            fill("MSFT", Direction::Sell, 20, 15_000),
+            fill("AAPL", Direction::Sell, 5, 20_000),
+            fill("AAPL", Direction::Buy, 8, 19_000),
+            fill("TSLA", Direction::Buy, 3, 30_000),
+            fill("TSLA", Direction::Sell, 3, 31_000),
+        ]);
+
+        assert_eq!(
+            positions,
+            vec![
+                // Flipped from short to long at the price of the flipping fill
+                BrokerPosition {
+                    symbol: Symbol::new("AAPL").unwrap(),
+                    quantity: Decimal::from(3),
+                    average_price_cents: 19_000,
+                },
+                // Reduced without moving the average price
+                BrokerPosition {
+                    symbol: Symbol::new("MSFT").unwrap(),
+                    quantity: Decimal::from(20),
+                    average_price_cents: 11_500,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Conversion of broker-reported fill prices to integer cents.
+//!
+//! Brokers report fill prices at different precisions (Schwab as JSON
+//! numbers, Alpaca as decimal strings), so both go through
+//! [`price_to_cents`] with the same [`PriceRounding`] mode. The price is
+//! parsed from its decimal text rather than multiplied as a float, so a
+//! sub-cent fill rounds the same way regardless of which broker reported it.
+
+use num_traits::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::str::FromStr;
+
+use crate::BrokerError;
+
+/// How sub-cent fill prices are rounded to whole cents.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceRounding {
+    /// Round to the nearest cent, ties to the even cent (banker's rounding)
+    #[default]
+    HalfEven,
+    /// Round to the nearest cent, ties away from zero
+    HalfUp,
+    /// Drop any fraction of a cent
+    Truncate,
+}
+
+impl PriceRounding {
+    const fn strategy(self) -> RoundingStrategy {
+        match self {
+            Self::HalfEven => RoundingStrategy::MidpointNearestEven,
+            Self::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            Self::Truncate => RoundingStrategy::ToZero,
+        }
+    }
+}
+
+/// Converts a broker-reported price in dollars, e.g. `"150.2550"`, to cents.
+pub fn price_to_cents(reported_price: &str, rounding: PriceRounding) -> Result<u64, BrokerError> {
+    let invalid = || BrokerError::InvalidPrice {
+        price: reported_price.to_string(),
+    };
+
+    let dollars = Decimal::from_str(reported_price.trim()).map_err(|_| invalid())?;
+
+    dollars
+        .checked_mul(Decimal::ONE_HUNDRED)
+        .map(|cents| cents.round_dp_with_strategy(0, rounding.strategy()))
+        .and_then(|cents| cents.to_u64())
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_cent_fills_round_consistently_across_brokers() {
+        // Schwab reports a JSON number, which reaches us as an f64; Alpaca
+        // reports a decimal string
+        let schwab_price = 100.385_f64.to_string();
+        let alpaca_price = "100.385";
+
+        for (rounding, expected) in [
+            (PriceRounding::HalfEven, 10038),
+            (PriceRounding::HalfUp, 10039),
+            (PriceRounding::Truncate, 10038),
+        ] {
+            assert_eq!(price_to_cents(&schwab_price, rounding).unwrap(), expected);
+            assert_eq!(price_to_cents(alpaca_price, rounding).unwrap(), expected);
+        }
+
+        assert_eq!(
+            price_to_cents("22.7299", PriceRounding::HalfEven).unwrap(),
+            2273
+        );
+        assert_eq!(
+            price_to_cents("22.7299", PriceRounding::Truncate).unwrap(),
+            2272
+        );
+        assert_eq!(
+            price_to_cents("100.375", PriceRounding::HalfEven).unwrap(),
+            10038
+        );
+        assert_eq!(
+            price_to_cents("150", PriceRounding::HalfEven).unwrap(),
+            15000
+        );
+    }
+
+    #[test]
+    fn test_invalid_prices_are_rejected() {
+        for price in ["", "abc", "-1.00", "NaN"] {
+            assert!(matches!(
+                price_to_cents(price, PriceRounding::HalfEven).unwrap_err(),
+                BrokerError::InvalidPrice { .. }
+            ));
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+
+use crate::Symbol;
+
+/// Latest top-of-book quote for a symbol as reported by the broker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    pub symbol: Symbol,
+    pub bid_price_cents: u64,
+    pub ask_price_cents: u64,
+    pub quoted_at: DateTime<Utc>,
+}
+
+impl Quote {
+    /// Midpoint between bid and ask, rounded down to the nearest cent
+    pub fn mid_price_cents(&self) -> u64 {
+        self.bid_price_cents.midpoint(self.ask_price_cents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mid_price_cents() {
+        let quote = Quote {
+            symbol: Symbol::new("AAPL").unwrap(),
+            bid_price_cents: 15010,
+            ask_price_cents: 15013,
+            quoted_at: Utc::now(),
+        };
+
+        assert_eq!(quote.mid_price_cents(), 15011);
+    }
+}
@@ -9,6 +9,7 @@ use sqlx::SqlitePool;
 use tracing::{debug, info};
 
 use super::{SchwabError, tokens::SchwabTokens};
+use crate::PriceRounding;
 
 #[derive(Parser, Debug, Clone)]
 pub struct SchwabAuthEnv {
@@ -22,6 +23,30 @@ pub struct SchwabAuthEnv {
     pub schwab_base_url: String,
     #[clap(long, env, default_value = "0")]
     pub schwab_account_index: usize,
+    /// Log orders held by Schwab for manual review at warn level instead of
+    /// info; they are polled as open orders either way
+    #[clap(long, env)]
+    pub schwab_alert_on_manual_review: bool,
+    /// Minimum seconds between calls to the market hours endpoint; the last
+    /// response is persisted so this holds across restarts (0 disables)
+    #[clap(long, env, default_value = "60")]
+    pub schwab_market_hours_min_interval_secs: u64,
+    /// Poll pending orders with a single account-wide orders query covering
+    /// this many hours (at most 60 days) instead of one request per order.
+    /// Orders entered before the window are still looked up individually
+    #[clap(long, env)]
+    pub schwab_order_batch_lookback_hours: Option<u64>,
+    /// Store the JSON body of each placed order on its execution, as a record
+    /// of exactly what was requested
+    #[clap(long, env)]
+    pub schwab_persist_order_payloads: bool,
+    /// The account is enabled for fractional share trading, so fractional
+    /// market orders may be placed instead of only whole shares
+    #[clap(long, env)]
+    pub schwab_fractional_shares: bool,
+    /// Rounding applied when converting fill prices to cents
+    #[clap(long, env, default_value = "half-even")]
+    pub price_rounding: PriceRounding,
     #[clap(long, env)]
     pub encryption_key: FixedBytes<32>,
 }
@@ -252,6 +277,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: "https://api.schwabapi.com".to_string(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -263,6 +294,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: mock_server.base_url(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -282,6 +319,12 @@ mod tests {
             schwab_redirect_uri: "https://custom.redirect.com".to_string(),
             schwab_base_url: "https://custom.api.com".to_string(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         };
         let expected_url = "https://custom.api.com/v1/oauth/authorize?client_id=custom_key&redirect_uri=https%3A%2F%2Fcustom.redirect.com";
@@ -296,6 +339,12 @@ mod tests {
             schwab_redirect_uri: "https://example.com/callback?param=value&other=test".to_string(),
             schwab_base_url: "https://api.schwabapi.com".to_string(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         };
         let expected_url = "https://api.schwabapi.com/v1/oauth/authorize?client_id=test%20key%20with%20spaces%20%26%20symbols%21&redirect_uri=https%3A%2F%2Fexample.com%2Fcallback%3Fparam%3Dvalue%26other%3Dtest";
@@ -570,6 +619,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: "https://api.schwabapi.com".to_string(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         };
 
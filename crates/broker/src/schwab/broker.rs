@@ -1,15 +1,109 @@
 use async_trait::async_trait;
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use tokio::task::JoinHandle;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::market_hours::{MARKET_HOURS_RECHECK_INTERVAL, MarketSchedule, wait_for_open};
 use crate::schwab::auth::SchwabAuthEnv;
-use crate::schwab::market_hours::{MarketStatus, fetch_market_hours};
+use crate::schwab::market_hours::{MarketHours, MarketStatus, fetch_market_hours_cached};
+use crate::schwab::order_status::{OrderStatusResponse, StatusCategory};
+use crate::schwab::positions::fetch_positions;
+use crate::schwab::quote::{fetch_average_daily_volume, fetch_quote};
 use crate::schwab::tokens::{SchwabTokens, spawn_automatic_token_refresh};
 use crate::{
-    Broker, BrokerError, MarketOrder, OrderPlacement, OrderState, OrderUpdate, Shares, Symbol,
+    Broker, BrokerError, BrokerPosition, FractionalMarketOrder, FractionalOrderPlacement,
+    LimitOrder, MarketOrder, OrderPlacement, OrderState, OrderUpdate, Quote, Shares, Symbol,
 };
 
+/// Schwab only accepts order queries entered within the last 60 days.
+const MAX_ORDER_LOOKBACK_HOURS: i64 = 60 * 24;
+
+/// How far back to look for an order already placed under a client order
+/// key. Retries happen on restart, well within a week of the first attempt.
+const CLIENT_ORDER_LOOKBACK_HOURS: i64 = 7 * 24;
+
+const fn to_schwab_instruction(direction: crate::Direction) -> crate::schwab::order::Instruction {
+    match direction {
+        crate::Direction::Buy => crate::schwab::order::Instruction::Buy,
+        crate::Direction::Sell => crate::schwab::order::Instruction::Sell,
+    }
+}
+
+/// Market state at the current time. An open market without a future close
+/// is given an hour before it is checked again.
+fn market_schedule(market_hours: &MarketHours) -> MarketSchedule {
+    const FALLBACK_SESSION: std::time::Duration = std::time::Duration::from_hours(1);
+
+    let now = chrono::Utc::now();
+    match market_hours.current_status() {
+        MarketStatus::Open => {
+            let until_close = market_hours
+                .end
+                .map(|end| end.with_timezone(&chrono::Utc))
+                .filter(|close| *close > now)
+                .and_then(|close| (close - now).to_std().ok())
+                .unwrap_or(FALLBACK_SESSION);
+
+            MarketSchedule::Open { until_close }
+        }
+        MarketStatus::Closed => {
+            // No start time or one already passed leaves the next open unknown
+            let until_open = market_hours
+                .start
+                .map(|start| start.with_timezone(&chrono::Utc))
+                .filter(|open| *open > now)
+                .and_then(|open| (open - now).to_std().ok());
+
+            MarketSchedule::Closed { until_open }
+        }
+    }
+}
+
+fn to_schwab_order(order: &MarketOrder) -> crate::schwab::order::Order {
+    crate::schwab::order::Order {
+        tag: order.client_order_id.clone(),
+        ..crate::schwab::order::Order::new(
+            order.symbol.to_string(),
+            to_schwab_instruction(order.direction),
+            order.shares.value().into(),
+        )
+    }
+}
+
+fn to_schwab_fractional_order(order: &FractionalMarketOrder) -> crate::schwab::order::Order {
+    crate::schwab::order::Order {
+        tag: order.client_order_id.clone(),
+        ..crate::schwab::order::Order::fractional(
+            order.symbol.to_string(),
+            to_schwab_instruction(order.direction),
+            order.quantity,
+        )
+    }
+}
+
+fn to_schwab_limit_order(order: &LimitOrder) -> crate::schwab::order::Order {
+    crate::schwab::order::Order::limit(
+        order.symbol.to_string(),
+        to_schwab_instruction(order.direction),
+        order.shares.value().into(),
+        order.limit_price(),
+    )
+}
+
+/// An order entered on the Schwab account, as reported in its order history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchwabOrderRecord {
+    pub order_id: String,
+    /// Client order key the order was placed with, if any
+    pub tag: Option<String>,
+    pub symbol: Option<Symbol>,
+    pub shares: Option<Shares>,
+    pub direction: Option<crate::Direction>,
+    pub entered_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub state: OrderState,
+}
+
 /// Configuration for SchwabBroker containing auth environment and database pool
 #[derive(Debug, Clone)]
 pub struct SchwabConfig {
@@ -24,6 +118,201 @@ pub struct SchwabBroker {
     pool: SqlitePool,
 }
 
+impl SchwabBroker {
+    async fn place_order(
+        &self,
+        schwab_order: crate::schwab::order::Order,
+        symbol: Symbol,
+        shares: Shares,
+        direction: crate::Direction,
+    ) -> Result<OrderPlacement<String>, BrokerError> {
+        let response = schwab_order.place(&self.auth, &self.pool).await?;
+
+        Ok(OrderPlacement {
+            order_id: response.order_id,
+            broker: crate::SupportedBroker::Schwab,
+            symbol,
+            shares,
+            direction,
+            placed_at: chrono::Utc::now(),
+            request_payload: self
+                .auth
+                .schwab_persist_order_payloads
+                .then_some(response.order_json),
+        })
+    }
+
+    fn order_state(
+        &self,
+        order_id: &str,
+        order_response: &OrderStatusResponse,
+    ) -> Result<OrderState, BrokerError> {
+        match order_response.category() {
+            StatusCategory::Filled => {
+                let price_cents = order_response
+                    .price_in_cents(self.auth.price_rounding)?
+                    .ok_or_else(|| {
+                        BrokerError::Network(
+                            "Order marked as filled but price information is not available"
+                                .to_string(),
+                        )
+                    })?;
+
+                let close_time_str = order_response.close_time.as_ref().ok_or_else(|| {
+                    BrokerError::Network(
+                        "Order marked as filled but close_time is missing".to_string(),
+                    )
+                })?;
+
+                let executed_at =
+                    chrono::DateTime::parse_from_str(close_time_str, "%Y-%m-%dT%H:%M:%S%z")?
+                        .with_timezone(&chrono::Utc);
+
+                Ok(OrderState::Filled {
+                    executed_at,
+                    order_id: order_id.to_string(),
+                    price_cents,
+                    reported_price: order_response.reported_price(),
+                })
+            }
+            StatusCategory::Failed => {
+                let close_time_str = order_response.close_time.as_ref().ok_or_else(|| {
+                    BrokerError::Network(
+                        "Order marked as failed but close_time is missing".to_string(),
+                    )
+                })?;
+
+                let failed_at =
+                    chrono::DateTime::parse_from_str(close_time_str, "%Y-%m-%dT%H:%M:%S%z")?
+                        .with_timezone(&chrono::Utc);
+
+                Ok(OrderState::Failed {
+                    failed_at,
+                    error_reason: Some(format!("Order status: {:?}", order_response.status)),
+                })
+            }
+            StatusCategory::UnderReview => {
+                if self.auth.schwab_alert_on_manual_review {
+                    warn!(
+                        "Schwab order {order_id} is held for manual review ({:?}); it will not fill until Schwab releases it",
+                        order_response.status
+                    );
+                } else {
+                    info!(
+                        "Schwab order {order_id} is under review ({:?}), continuing to poll",
+                        order_response.status
+                    );
+                }
+
+                Ok(OrderState::Submitted {
+                    order_id: order_id.to_string(),
+                })
+            }
+            StatusCategory::Working => Ok(OrderState::Submitted {
+                order_id: order_id.to_string(),
+            }),
+        }
+    }
+
+    /// Id of a recent order placed with `client_order_id` as its tag.
+    async fn find_order_id_by_tag(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Option<String>, BrokerError> {
+        let to = chrono::Utc::now();
+        let from = to - chrono::Duration::hours(CLIENT_ORDER_LOOKBACK_HOURS);
+
+        let orders = crate::schwab::order::Order::get_orders_entered_between(
+            from, to, &self.auth, &self.pool,
+        )
+        .await?;
+
+        Ok(orders
+            .into_iter()
+            .find(|order| order.tag.as_deref() == Some(client_order_id))
+            .and_then(|order| order.order_id))
+    }
+
+    /// Orders entered on the account since `from` (at most 60 days back),
+    /// with their states parsed like [`Broker::get_order_status`], for
+    /// reconciling recorded executions against Schwab's order history. Orders
+    /// whose state cannot be parsed are logged and left out.
+    pub async fn get_order_history(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<SchwabOrderRecord>, BrokerError> {
+        let to = chrono::Utc::now();
+        let from = from.max(to - chrono::Duration::hours(MAX_ORDER_LOOKBACK_HOURS));
+
+        let orders = crate::schwab::order::Order::get_orders_entered_between(
+            from, to, &self.auth, &self.pool,
+        )
+        .await?;
+
+        Ok(orders
+            .into_iter()
+            .filter_map(|order| {
+                let order_id = order.order_id.clone()?;
+                let state = match self.order_state(&order_id, &order) {
+                    Ok(state) => state,
+                    Err(e) => {
+                        warn!("Skipping Schwab order {order_id} with unparseable state: {e}");
+                        return None;
+                    }
+                };
+
+                Some(SchwabOrderRecord {
+                    symbol: order.symbol().and_then(|symbol| Symbol::new(symbol).ok()),
+                    shares: order
+                        .whole_shares()
+                        .and_then(|shares| Shares::new(shares).ok()),
+                    direction: order.direction(),
+                    entered_at: order.entered_time.as_deref().and_then(|entered_time| {
+                        chrono::DateTime::parse_from_str(entered_time, "%Y-%m-%dT%H:%M:%S%z")
+                            .ok()
+                            .map(|entered_at| entered_at.with_timezone(&chrono::Utc))
+                    }),
+                    tag: order.tag,
+                    order_id,
+                    state,
+                })
+            })
+            .collect())
+    }
+
+    /// Orders entered within the configured lookback window, keyed by order
+    /// id, or nothing if batched polling is disabled.
+    async fn fetch_recent_orders(
+        &self,
+    ) -> Result<HashMap<String, OrderStatusResponse>, BrokerError> {
+        let Some(lookback_hours) = self.auth.schwab_order_batch_lookback_hours else {
+            return Ok(HashMap::new());
+        };
+
+        let lookback_hours = i64::try_from(lookback_hours)
+            .map_or(MAX_ORDER_LOOKBACK_HOURS, |hours| {
+                hours.min(MAX_ORDER_LOOKBACK_HOURS)
+            });
+        let to = chrono::Utc::now();
+        let from = to - chrono::Duration::hours(lookback_hours);
+
+        let orders = crate::schwab::order::Order::get_orders_entered_between(
+            from, to, &self.auth, &self.pool,
+        )
+        .await?;
+
+        info!(
+            "Fetched {} orders entered in the last {lookback_hours} hours",
+            orders.len()
+        );
+
+        Ok(orders
+            .into_iter()
+            .filter_map(|order| Some((order.order_id.clone()?, order)))
+            .collect())
+    }
+}
+
 #[async_trait]
 impl Broker for SchwabBroker {
     type Error = BrokerError;
@@ -43,47 +332,19 @@ impl Broker for SchwabBroker {
     }
 
     async fn wait_until_market_open(&self) -> Result<std::time::Duration, Self::Error> {
-        loop {
-            let market_hours = fetch_market_hours(&self.auth, &self.pool, None).await?;
-
-            match market_hours.current_status() {
-                MarketStatus::Open => {
-                    // Market is open, return time until close
-                    if let Some(end_time) = market_hours.end {
-                        let market_close = end_time.with_timezone(&chrono::Utc);
-                        let now = chrono::Utc::now();
-                        if market_close > now {
-                            let duration = (market_close - now)
-                                .to_std()
-                                .unwrap_or(std::time::Duration::from_secs(3600));
-                            return Ok(duration);
-                        }
-                    }
-                    // No end time or already passed, return default timeout
-                    return Ok(std::time::Duration::from_secs(3600));
-                }
-                MarketStatus::Closed => {
-                    // Market is closed, wait until next open
-                    if let Some(start_time) = market_hours.start {
-                        let next_open = start_time.with_timezone(&chrono::Utc);
-                        let now = chrono::Utc::now();
-                        if next_open > now {
-                            let wait_duration = (next_open - now)
-                                .to_std()
-                                .unwrap_or(std::time::Duration::from_secs(3600));
-                            info!(
-                                "Market closed, waiting {} seconds until open",
-                                wait_duration.as_secs()
-                            );
-                            tokio::time::sleep(wait_duration).await;
-                            continue; // Re-check market status
-                        }
-                    }
-                    // No start time or already passed, wait a bit and retry
-                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
-                }
-            }
-        }
+        wait_for_open(
+            || async {
+                let market_hours = fetch_market_hours_cached(&self.auth, &self.pool).await?;
+                Ok::<_, BrokerError>(market_schedule(&market_hours))
+            },
+            MARKET_HOURS_RECHECK_INTERVAL,
+        )
+        .await
+    }
+
+    async fn time_until_market_close(&self) -> Result<Option<std::time::Duration>, Self::Error> {
+        let market_hours = fetch_market_hours_cached(&self.auth, &self.pool).await?;
+        Ok(market_schedule(&market_hours).until_close())
     }
 
     #[tracing::instrument(skip(self), fields(symbol = %order.symbol, shares = %order.shares, direction = %order.direction), level = tracing::Level::INFO)]
@@ -96,31 +357,136 @@ impl Broker for SchwabBroker {
             order.direction, order.shares, order.symbol
         );
 
-        // Convert Direction to Schwab Instruction
-        let instruction = match order.direction {
-            crate::Direction::Buy => crate::schwab::order::Instruction::Buy,
-            crate::Direction::Sell => crate::schwab::order::Instruction::Sell,
+        let placed = match &order.client_order_id {
+            Some(client_order_id) => self
+                .find_order_id_by_tag(client_order_id)
+                .await?
+                .map(|order_id| (client_order_id, order_id)),
+            None => None,
         };
 
-        // Create Schwab order
-        let schwab_order = crate::schwab::order::Order::new(
-            order.symbol.to_string(),
-            instruction,
-            order.shares.value().into(),
+        if let Some((client_order_id, order_id)) = placed {
+            info!(
+                "Order {order_id} was already placed for client order id {client_order_id}, not placing it again"
+            );
+
+            return Ok(OrderPlacement {
+                order_id,
+                broker: crate::SupportedBroker::Schwab,
+                symbol: order.symbol,
+                shares: order.shares,
+                direction: order.direction,
+                placed_at: chrono::Utc::now(),
+                request_payload: None,
+            });
+        }
+
+        self.place_order(
+            to_schwab_order(&order),
+            order.symbol,
+            order.shares,
+            order.direction,
+        )
+        .await
+    }
+
+    fn supports_fractional_shares(&self) -> bool {
+        self.auth.schwab_fractional_shares
+    }
+
+    #[tracing::instrument(skip(self), fields(symbol = %order.symbol, quantity = %order.quantity, direction = %order.direction), level = tracing::Level::INFO)]
+    async fn place_fractional_market_order(
+        &self,
+        order: FractionalMarketOrder,
+    ) -> Result<FractionalOrderPlacement<Self::OrderId>, Self::Error> {
+        if !self.auth.schwab_fractional_shares {
+            return Err(BrokerError::InvalidOrder {
+                reason: "Fractional shares are not enabled for this Schwab account".to_string(),
+            });
+        }
+        order.validate_quantity()?;
+
+        info!(
+            "Placing fractional market order: {} {} shares of {}",
+            order.direction, order.quantity, order.symbol
         );
 
-        // Place the order using Schwab API
-        let response = schwab_order.place(&self.auth, &self.pool).await?;
+        let placed = match &order.client_order_id {
+            Some(client_order_id) => self
+                .find_order_id_by_tag(client_order_id)
+                .await?
+                .map(|order_id| (client_order_id, order_id)),
+            None => None,
+        };
 
-        Ok(OrderPlacement {
+        if let Some((client_order_id, order_id)) = placed {
+            info!(
+                "Order {order_id} was already placed for client order id {client_order_id}, not placing it again"
+            );
+
+            return Ok(FractionalOrderPlacement {
+                order_id,
+                broker: crate::SupportedBroker::Schwab,
+                symbol: order.symbol,
+                quantity: order.quantity,
+                direction: order.direction,
+                placed_at: chrono::Utc::now(),
+                request_payload: None,
+            });
+        }
+
+        let response = to_schwab_fractional_order(&order)
+            .place(&self.auth, &self.pool)
+            .await?;
+
+        Ok(FractionalOrderPlacement {
             order_id: response.order_id,
+            broker: crate::SupportedBroker::Schwab,
             symbol: order.symbol,
-            shares: order.shares,
+            quantity: order.quantity,
             direction: order.direction,
             placed_at: chrono::Utc::now(),
+            request_payload: self
+                .auth
+                .schwab_persist_order_payloads
+                .then_some(response.order_json),
         })
     }
 
+    #[tracing::instrument(skip(self), fields(symbol = %order.symbol, shares = %order.shares, direction = %order.direction, limit_price_cents = order.limit_price_cents), level = tracing::Level::INFO)]
+    async fn place_limit_order(
+        &self,
+        order: LimitOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        info!(
+            "Placing limit order: {} {} shares of {} at {}",
+            order.direction,
+            order.shares,
+            order.symbol,
+            order.limit_price()
+        );
+
+        self.place_order(
+            to_schwab_limit_order(&order),
+            order.symbol,
+            order.shares,
+            order.direction,
+        )
+        .await
+    }
+
+    async fn preview_market_order(&self, order: &MarketOrder) -> Result<(), Self::Error> {
+        info!(
+            "Previewing market order: {} {} shares of {}",
+            order.direction, order.shares, order.symbol
+        );
+
+        to_schwab_order(order)
+            .preview(&self.auth, &self.pool)
+            .await?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self), fields(order_id), level = tracing::Level::DEBUG)]
     async fn get_order_status(&self, order_id: &Self::OrderId) -> Result<OrderState, Self::Error> {
         info!("Getting order status for: {}", order_id);
@@ -128,44 +494,15 @@ impl Broker for SchwabBroker {
         let order_response =
             crate::schwab::order::Order::get_order_status(order_id, &self.auth, &self.pool).await?;
 
-        if order_response.is_filled() {
-            let price_cents = order_response.price_in_cents()?.ok_or_else(|| {
-                BrokerError::Network(
-                    "Order marked as filled but price information is not available".to_string(),
-                )
-            })?;
-
-            let close_time_str = order_response.close_time.as_ref().ok_or_else(|| {
-                BrokerError::Network("Order marked as filled but close_time is missing".to_string())
-            })?;
+        self.order_state(order_id, &order_response)
+    }
 
-            let executed_at =
-                chrono::DateTime::parse_from_str(close_time_str, "%Y-%m-%dT%H:%M:%S%z")?
-                    .with_timezone(&chrono::Utc);
+    #[tracing::instrument(skip(self), fields(order_id), level = tracing::Level::INFO)]
+    async fn cancel_order(&self, order_id: &Self::OrderId) -> Result<(), Self::Error> {
+        info!("Cancelling order: {order_id}");
 
-            Ok(OrderState::Filled {
-                executed_at,
-                order_id: order_id.clone(),
-                price_cents,
-            })
-        } else if order_response.is_terminal_failure() {
-            let close_time_str = order_response.close_time.as_ref().ok_or_else(|| {
-                BrokerError::Network("Order marked as failed but close_time is missing".to_string())
-            })?;
-
-            let failed_at =
-                chrono::DateTime::parse_from_str(close_time_str, "%Y-%m-%dT%H:%M:%S%z")?
-                    .with_timezone(&chrono::Utc);
-
-            Ok(OrderState::Failed {
-                failed_at,
-                error_reason: Some(format!("Order status: {:?}", order_response.status)),
-            })
-        } else {
-            Ok(OrderState::Submitted {
-                order_id: order_id.clone(),
-            })
-        }
+        crate::schwab::order::Order::cancel(order_id, &self.auth, &self.pool).await?;
+        Ok(())
     }
 
     async fn poll_pending_orders(&self) -> Result<Vec<OrderUpdate<Self::OrderId>>, Self::Error> {
@@ -178,6 +515,11 @@ impl Broker for SchwabBroker {
         .fetch_all(&self.pool)
         .await?;
 
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut recent_orders = self.fetch_recent_orders().await?;
         let mut updates = Vec::new();
 
         for row in rows {
@@ -193,14 +535,24 @@ impl Broker for SchwabBroker {
                 });
             };
 
-            // Get current status from Schwab API
-            match self.get_order_status(&order_id_value).await {
+            // Use the batched response when it covers the order, otherwise ask
+            // Schwab for this order alone
+            let current_state = match recent_orders.remove(&order_id_value) {
+                Some(order_response) => self.order_state(&order_id_value, &order_response),
+                None => self.get_order_status(&order_id_value).await,
+            };
+
+            match current_state {
                 Ok(current_state) => {
                     // Only include orders that have changed status
                     if !matches!(current_state, OrderState::Submitted { .. }) {
-                        let price_cents = match &current_state {
-                            OrderState::Filled { price_cents, .. } => Some(*price_cents),
-                            _ => None,
+                        let (price_cents, reported_price) = match &current_state {
+                            OrderState::Filled {
+                                price_cents,
+                                reported_price,
+                                ..
+                            } => (Some(*price_cents), reported_price.clone()),
+                            _ => (None, None),
                         };
 
                         let symbol =
@@ -234,6 +586,7 @@ impl Broker for SchwabBroker {
                             status: current_state.status(),
                             updated_at: chrono::Utc::now(),
                             price_cents,
+                            reported_price,
                         });
                     }
                 }
@@ -248,6 +601,18 @@ impl Broker for SchwabBroker {
         Ok(updates)
     }
 
+    async fn get_quote(&self, symbol: &Symbol) -> Result<Quote, Self::Error> {
+        fetch_quote(&self.auth, &self.pool, symbol).await
+    }
+
+    async fn get_adv(&self, symbol: &Symbol) -> Result<u64, Self::Error> {
+        fetch_average_daily_volume(&self.auth, &self.pool, symbol).await
+    }
+
+    async fn get_positions(&self) -> Result<Vec<BrokerPosition>, Self::Error> {
+        fetch_positions(&self.auth, &self.pool).await
+    }
+
     fn to_supported_broker(&self) -> crate::SupportedBroker {
         crate::SupportedBroker::Schwab
     }
@@ -281,7 +646,7 @@ mod tests {
     use crate::schwab::SchwabError;
     use crate::schwab::auth::SchwabAuthEnv;
     use crate::schwab::tokens::SchwabTokens;
-    use crate::test_utils::{TEST_ENCRYPTION_KEY, setup_test_db};
+    use crate::test_utils::{TEST_ENCRYPTION_KEY, setup_test_db, setup_test_tokens};
     use chrono::{Duration, Utc};
     use httpmock::prelude::*;
     use serde_json::json;
@@ -294,6 +659,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: "https://test.com".to_string(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -305,6 +676,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: server.base_url(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -313,7 +690,7 @@ mod tests {
     async fn test_try_from_config_with_no_tokens() {
         let pool = setup_test_db().await;
         let auth = create_test_auth_env();
-        let config = (auth, pool);
+        let config = SchwabConfig { auth, pool };
 
         let result = SchwabBroker::try_from_config(config).await;
 
@@ -339,7 +716,7 @@ mod tests {
             .await
             .unwrap();
 
-        let config = (auth, pool);
+        let config = SchwabConfig { auth, pool };
         let result = SchwabBroker::try_from_config(config).await;
 
         assert!(result.is_ok());
@@ -382,7 +759,10 @@ mod tests {
                 }));
         });
 
-        let config = (auth, pool.clone());
+        let config = SchwabConfig {
+            auth,
+            pool: pool.clone(),
+        };
         let result = SchwabBroker::try_from_config(config).await;
 
         assert!(result.is_ok());
@@ -414,7 +794,7 @@ mod tests {
             .await
             .unwrap();
 
-        let config = (auth, pool);
+        let config = SchwabConfig { auth, pool };
         let result = SchwabBroker::try_from_config(config).await;
 
         assert!(result.is_err());
@@ -604,4 +984,621 @@ mod tests {
 
         assert_eq!(broker.to_supported_broker(), crate::SupportedBroker::Schwab);
     }
+
+    #[tokio::test]
+    async fn test_get_order_status_awaiting_manual_review_stays_submitted() {
+        let pool = setup_test_db().await;
+        let server = MockServer::start();
+        let mut auth = create_test_auth_env_with_server(&server);
+        auth.schwab_alert_on_manual_review = true;
+
+        let valid_tokens = SchwabTokens {
+            access_token: "valid_access_token".to_string(),
+            access_token_fetched_at: Utc::now() - Duration::minutes(10),
+            refresh_token: "valid_refresh_token".to_string(),
+            refresh_token_fetched_at: Utc::now() - Duration::days(1),
+        };
+        valid_tokens
+            .store(&pool, &TEST_ENCRYPTION_KEY)
+            .await
+            .unwrap();
+
+        let account_mock = server.mock(|when, then| {
+            when.method(GET).path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        let order_status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders/1004055538999");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "orderId": 1_004_055_538_999_i64,
+                    "status": "AWAITING_MANUAL_REVIEW",
+                    "filledQuantity": 0.0,
+                    "remainingQuantity": 100.0,
+                    "enteredTime": "2023-10-15T10:25:00Z",
+                    "closeTime": null,
+                    "orderActivityCollection": []
+                }));
+        });
+
+        let broker = SchwabBroker { auth, pool };
+        let state = broker
+            .get_order_status(&"1004055538999".to_string())
+            .await
+            .unwrap();
+
+        account_mock.assert();
+        order_status_mock.assert();
+        assert_eq!(
+            state,
+            OrderState::Submitted {
+                order_id: "1004055538999".to_string()
+            }
+        );
+        assert_eq!(state.status(), crate::OrderStatus::Submitted);
+    }
+
+    #[tokio::test]
+    async fn test_get_order_status_rate_limited_surfaces_retry_after() {
+        let pool = setup_test_db().await;
+        let server = MockServer::start();
+        let auth = create_test_auth_env_with_server(&server);
+        setup_test_tokens(&pool, &auth).await;
+
+        server.mock(|when, then| {
+            when.method(GET).path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        let order_status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders/1004055538999");
+            then.status(429).header("Retry-After", "3");
+        });
+
+        let broker = SchwabBroker { auth, pool };
+        let error = broker
+            .get_order_status(&"1004055538999".to_string())
+            .await
+            .unwrap_err();
+
+        order_status_mock.assert();
+        assert!(matches!(
+            error,
+            BrokerError::RateLimit {
+                retry_after_seconds: 3
+            }
+        ));
+        assert_eq!(error.kind(), "rate_limit");
+        assert_eq!(
+            crate::rate_limit_delay(&error),
+            Some(std::time::Duration::from_secs(3))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order_sends_delete() {
+        let pool = setup_test_db().await;
+        let server = MockServer::start();
+        let auth = create_test_auth_env_with_server(&server);
+
+        let valid_tokens = SchwabTokens {
+            access_token: "valid_access_token".to_string(),
+            access_token_fetched_at: Utc::now() - Duration::minutes(10),
+            refresh_token: "valid_refresh_token".to_string(),
+            refresh_token_fetched_at: Utc::now() - Duration::days(1),
+        };
+        valid_tokens
+            .store(&pool, &TEST_ENCRYPTION_KEY)
+            .await
+            .unwrap();
+
+        let account_mock = server.mock(|when, then| {
+            when.method(GET).path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        let cancel_mock = server.mock(|when, then| {
+            when.method(DELETE)
+                .path("/trader/v1/accounts/ABC123DEF456/orders/1004055538123")
+                .header("authorization", "Bearer valid_access_token");
+            then.status(200);
+        });
+
+        let broker = SchwabBroker { auth, pool };
+        broker
+            .cancel_order(&"1004055538123".to_string())
+            .await
+            .unwrap();
+
+        account_mock.assert();
+        cancel_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_place_limit_order_sends_limit_price() {
+        let pool = setup_test_db().await;
+        let server = MockServer::start();
+        let auth = create_test_auth_env_with_server(&server);
+
+        let valid_tokens = SchwabTokens {
+            access_token: "valid_access_token".to_string(),
+            access_token_fetched_at: Utc::now() - Duration::minutes(10),
+            refresh_token: "valid_refresh_token".to_string(),
+            refresh_token_fetched_at: Utc::now() - Duration::days(1),
+        };
+        valid_tokens
+            .store(&pool, &TEST_ENCRYPTION_KEY)
+            .await
+            .unwrap();
+
+        let account_mock = server.mock(|when, then| {
+            when.method(GET).path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        let order_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/trader/v1/accounts/ABC123DEF456/orders")
+                .json_body(json!({
+                    "orderType": "LIMIT",
+                    "session": "NORMAL",
+                    "duration": "DAY",
+                    "orderStrategyType": "SINGLE",
+                    "orderLegCollection": [{
+                        "instruction": "SELL",
+                        "quantity": 10,
+                        "instrument": {"symbol": "AAPL", "assetType": "EQUITY"}
+                    }],
+                    "price": "150.25"
+                }));
+            then.status(201).header(
+                "location",
+                "/trader/v1/accounts/ABC123DEF456/orders/1004055538123",
+            );
+        });
+
+        let broker = SchwabBroker { auth, pool };
+        let placement = broker
+            .place_limit_order(LimitOrder {
+                symbol: Symbol::new("AAPL").unwrap(),
+                shares: Shares::new(10).unwrap(),
+                direction: crate::Direction::Sell,
+                limit_price_cents: 15_025,
+            })
+            .await
+            .unwrap();
+
+        account_mock.assert();
+        order_mock.assert();
+        assert_eq!(placement.order_id, "1004055538123");
+        assert_eq!(placement.direction, crate::Direction::Sell);
+    }
+
+    async fn broker_with_account_mock(server: &MockServer) -> SchwabBroker {
+        let pool = setup_test_db().await;
+        let auth = create_test_auth_env_with_server(server);
+        setup_test_tokens(&pool, &auth).await;
+
+        server.mock(|when, then| {
+            when.method(GET).path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        SchwabBroker { auth, pool }
+    }
+
+    fn tagged_market_order(client_order_id: &str) -> MarketOrder {
+        MarketOrder {
+            symbol: Symbol::new("AAPL").unwrap(),
+            shares: Shares::new(9).unwrap(),
+            direction: crate::Direction::Buy,
+            client_order_id: Some(client_order_id.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_place_market_order_sends_client_order_id_as_tag() {
+        let server = MockServer::start();
+        let broker = broker_with_account_mock(&server).await;
+
+        let lookup_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "orderId": 1_004_055_538_000_i64,
+                    "status": "FILLED",
+                    "tag": "st0x-AAPL-6"
+                }]));
+        });
+        let order_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/trader/v1/accounts/ABC123DEF456/orders")
+                .json_body(json!({
+                    "orderType": "MARKET",
+                    "session": "NORMAL",
+                    "duration": "DAY",
+                    "orderStrategyType": "SINGLE",
+                    "orderLegCollection": [{
+                        "instruction": "BUY",
+                        "quantity": 9,
+                        "instrument": {"symbol": "AAPL", "assetType": "EQUITY"}
+                    }],
+                    "tag": "st0x-AAPL-7"
+                }));
+            then.status(201).header(
+                "location",
+                "/trader/v1/accounts/ABC123DEF456/orders/1004055538123",
+            );
+        });
+
+        let placement = broker
+            .place_market_order(tagged_market_order("st0x-AAPL-7"))
+            .await
+            .unwrap();
+
+        lookup_mock.assert();
+        order_mock.assert();
+        assert_eq!(placement.order_id, "1004055538123");
+    }
+
+    #[tokio::test]
+    async fn test_place_fractional_market_order_requires_enabled_account() {
+        let server = MockServer::start();
+        let mut broker = broker_with_account_mock(&server).await;
+        let order = FractionalMarketOrder {
+            symbol: Symbol::new("AAPL").unwrap(),
+            quantity: rust_decimal::Decimal::new(25, 2),
+            direction: crate::Direction::Buy,
+            client_order_id: None,
+        };
+
+        assert!(!broker.supports_fractional_shares());
+        assert!(matches!(
+            broker
+                .place_fractional_market_order(order.clone())
+                .await
+                .unwrap_err(),
+            BrokerError::InvalidOrder { .. }
+        ));
+
+        broker.auth.schwab_fractional_shares = true;
+        let order_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/trader/v1/accounts/ABC123DEF456/orders")
+                .json_body(json!({
+                    "orderType": "MARKET",
+                    "session": "NORMAL",
+                    "duration": "DAY",
+                    "orderStrategyType": "SINGLE",
+                    "orderLegCollection": [{
+                        "instruction": "BUY",
+                        "quantity": 0.25,
+                        "instrument": {"symbol": "AAPL", "assetType": "EQUITY"}
+                    }]
+                }));
+            then.status(201).header(
+                "location",
+                "/trader/v1/accounts/ABC123DEF456/orders/1004055538123",
+            );
+        });
+
+        let placement = broker.place_fractional_market_order(order).await.unwrap();
+
+        order_mock.assert();
+        assert_eq!(placement.order_id, "1004055538123");
+        assert_eq!(placement.quantity, rust_decimal::Decimal::new(25, 2));
+    }
+
+    #[tokio::test]
+    async fn test_place_market_order_reuses_order_already_placed_with_tag() {
+        let server = MockServer::start();
+        let broker = broker_with_account_mock(&server).await;
+
+        let lookup_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "orderId": 1_004_055_538_123_i64,
+                    "status": "WORKING",
+                    "tag": "st0x-AAPL-7"
+                }]));
+        });
+        let order_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/trader/v1/accounts/ABC123DEF456/orders");
+            then.status(201).header(
+                "location",
+                "/trader/v1/accounts/ABC123DEF456/orders/1004055538999",
+            );
+        });
+
+        // Placing the same execution twice leaves a single live order
+        for _ in 0..2 {
+            let placement = broker
+                .place_market_order(tagged_market_order("st0x-AAPL-7"))
+                .await
+                .unwrap();
+            assert_eq!(placement.order_id, "1004055538123");
+            assert_eq!(placement.request_payload, None);
+        }
+
+        lookup_mock.assert_hits(2);
+        order_mock.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn test_get_order_history_parses_orders_and_skips_unparseable_ones() {
+        let server = MockServer::start();
+        let broker = broker_with_account_mock(&server).await;
+
+        let history_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([
+                    {
+                        "orderId": 1_004_055_538_123_i64,
+                        "status": "FILLED",
+                        "quantity": 9.0,
+                        "tag": "st0x-AAPL-7",
+                        "enteredTime": "2025-10-30T14:30:00+0000",
+                        "closeTime": "2025-10-30T14:30:02+0000",
+                        "orderLegCollection": [{
+                            "instruction": "SELL",
+                            "quantity": 9.0,
+                            "instrument": {"symbol": "AAPL", "assetType": "EQUITY"}
+                        }],
+                        "orderActivityCollection": [{
+                            "activityType": "EXECUTION",
+                            "executionLegs": [{"quantity": 9.0, "price": 150.25}]
+                        }]
+                    },
+                    {
+                        "orderId": 1_004_055_538_124_i64,
+                        "status": "WORKING",
+                        "quantity": 2.5,
+                        "orderLegCollection": [{
+                            "instruction": "SELL_SHORT",
+                            "instrument": {"symbol": "MSFT", "assetType": "EQUITY"}
+                        }]
+                    },
+                    {
+                        "orderId": 1_004_055_538_125_i64,
+                        "status": "FILLED"
+                    }
+                ]));
+        });
+
+        let orders = broker
+            .get_order_history(Utc::now() - Duration::hours(24))
+            .await
+            .unwrap();
+
+        history_mock.assert();
+        assert_eq!(
+            orders,
+            vec![
+                SchwabOrderRecord {
+                    order_id: "1004055538123".to_string(),
+                    tag: Some("st0x-AAPL-7".to_string()),
+                    symbol: Some(Symbol::new("AAPL").unwrap()),
+                    shares: Some(Shares::new(9).unwrap()),
+                    direction: Some(crate::Direction::Sell),
+                    entered_at: Some("2025-10-30T14:30:00Z".parse().unwrap()),
+                    state: OrderState::Filled {
+                        executed_at: "2025-10-30T14:30:02Z".parse().unwrap(),
+                        order_id: "1004055538123".to_string(),
+                        price_cents: 15025,
+                        reported_price: Some("150.25".to_string()),
+                    },
+                },
+                SchwabOrderRecord {
+                    order_id: "1004055538124".to_string(),
+                    tag: None,
+                    symbol: Some(Symbol::new("MSFT").unwrap()),
+                    shares: None,
+                    direction: None,
+                    entered_at: None,
+                    state: OrderState::Submitted {
+                        order_id: "1004055538124".to_string(),
+                    },
+                },
+            ]
+        );
+    }
+
+    async fn insert_submitted_order(
+        pool: &SqlitePool,
+        symbol: &str,
+        shares: i64,
+        direction: &str,
+        order_id: &str,
+    ) {
+        sqlx::query(
+            "INSERT INTO offchain_trades (symbol, shares, direction, broker, order_id, status)
+             VALUES (?, ?, ?, 'schwab', ?, 'SUBMITTED')",
+        )
+        .bind(symbol)
+        .bind(shares)
+        .bind(direction)
+        .bind(order_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::too_many_lines)]
+    async fn test_poll_pending_orders_uses_single_batched_orders_query() {
+        let pool = setup_test_db().await;
+        let server = MockServer::start();
+        let mut auth = create_test_auth_env_with_server(&server);
+        auth.schwab_order_batch_lookback_hours = Some(24);
+
+        setup_test_tokens(&pool, &auth).await;
+
+        for (symbol, shares, direction, order_id) in [
+            ("AAPL", 10, "BUY", "1001"),
+            ("MSFT", 5, "SELL", "1002"),
+            ("TSLA", 3, "BUY", "1003"),
+            ("NVDA", 7, "SELL", "1004"),
+        ] {
+            insert_submitted_order(&pool, symbol, shares, direction, order_id).await;
+        }
+
+        let account_mock = server.mock(|when, then| {
+            when.method(GET).path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        // One response covers the first three orders, plus an unrelated one
+        let batch_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders")
+                .query_param_exists("fromEnteredTime")
+                .query_param_exists("toEnteredTime");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([
+                    {
+                        "orderId": 1001,
+                        "status": "FILLED",
+                        "filledQuantity": 10.0,
+                        "remainingQuantity": 0.0,
+                        "closeTime": "2023-10-15T10:30:00+0000",
+                        "orderActivityCollection": [{
+                            "activityType": "EXECUTION",
+                            "executionLegs": [{"quantity": 10.0, "price": 150.25}]
+                        }]
+                    },
+                    {
+                        "orderId": 1002,
+                        "status": "WORKING",
+                        "filledQuantity": 0.0,
+                        "remainingQuantity": 5.0
+                    },
+                    {
+                        "orderId": 1003,
+                        "status": "CANCELED",
+                        "filledQuantity": 0.0,
+                        "remainingQuantity": 3.0,
+                        "closeTime": "2023-10-15T10:31:00+0000"
+                    },
+                    {
+                        "orderId": 9999,
+                        "status": "FILLED",
+                        "filledQuantity": 1.0,
+                        "remainingQuantity": 0.0,
+                        "closeTime": "2023-10-15T10:32:00+0000"
+                    }
+                ]));
+        });
+
+        let batched_order_mocks = ["1001", "1002", "1003"].map(|order_id| {
+            server.mock(|when, then| {
+                when.method(GET).path(format!(
+                    "/trader/v1/accounts/ABC123DEF456/orders/{order_id}"
+                ));
+                then.status(500);
+            })
+        });
+
+        // Entered before the lookback window, so it is looked up on its own
+        let fallback_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders/1004");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "orderId": 1004,
+                    "status": "FILLED",
+                    "filledQuantity": 7.0,
+                    "remainingQuantity": 0.0,
+                    "closeTime": "2023-10-14T15:00:00+0000",
+                    "orderActivityCollection": [{
+                        "activityType": "EXECUTION",
+                        "executionLegs": [{"quantity": 7.0, "price": 420.5}]
+                    }]
+                }));
+        });
+
+        let broker = SchwabBroker { auth, pool };
+        let updates = broker.poll_pending_orders().await.unwrap();
+
+        batch_mock.assert_hits(1);
+        fallback_mock.assert_hits(1);
+        for mock in &batched_order_mocks {
+            mock.assert_hits(0);
+        }
+        assert!(account_mock.hits() >= 1);
+
+        let summary: Vec<_> = updates
+            .iter()
+            .map(|update| {
+                (
+                    update.order_id.as_str(),
+                    update.symbol.to_string(),
+                    update.status,
+                    update.price_cents,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "1001",
+                    "AAPL".to_string(),
+                    crate::OrderStatus::Filled,
+                    Some(15025)
+                ),
+                ("1003", "TSLA".to_string(), crate::OrderStatus::Failed, None),
+                (
+                    "1004",
+                    "NVDA".to_string(),
+                    crate::OrderStatus::Filled,
+                    Some(42050)
+                ),
+            ]
+        );
+    }
 }
@@ -16,6 +16,16 @@ pub enum MarketSession {
     AfterHours,
 }
 
+impl MarketSession {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PreMarket => "PRE_MARKET",
+            Self::Regular => "REGULAR",
+            Self::AfterHours => "AFTER_HOURS",
+        }
+    }
+}
+
 impl std::str::FromStr for MarketSession {
     type Err = String;
 
@@ -73,6 +83,138 @@ impl MarketHours {
     }
 }
 
+/// Last market hours fetched from Schwab together with when they were
+/// fetched, persisted so the minimum fetch interval survives restarts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MarketHoursCache {
+    pub(crate) market_hours: MarketHours,
+    pub(crate) fetched_at: DateTime<Utc>,
+}
+
+impl MarketHoursCache {
+    pub(crate) async fn load(pool: &SqlitePool) -> Result<Option<Self>, SchwabError> {
+        let Some(row) = sqlx::query!(
+            r#"
+            SELECT market_date, session_type, is_open, start_at, end_at, fetched_at
+            FROM schwab_market_hours_cache
+            WHERE id = 1
+            "#
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let date = parse_date(&row.market_date).map_err(|e| SchwabError::RequestFailed {
+            action: "load cached market hours".to_string(),
+            status: reqwest::StatusCode::OK,
+            body: format!("Invalid cached date '{}': {e}", row.market_date),
+        })?;
+        let session_type =
+            row.session_type
+                .parse()
+                .map_err(|e: String| SchwabError::RequestFailed {
+                    action: "load cached market hours".to_string(),
+                    status: reqwest::StatusCode::OK,
+                    body: e,
+                })?;
+        let to_eastern =
+            |naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc).with_timezone(&Eastern);
+
+        Ok(Some(Self {
+            market_hours: MarketHours {
+                date,
+                session_type,
+                start: row.start_at.map(to_eastern),
+                end: row.end_at.map(to_eastern),
+                is_open: row.is_open,
+            },
+            fetched_at: DateTime::from_naive_utc_and_offset(row.fetched_at, Utc),
+        }))
+    }
+
+    pub(crate) async fn store(&self, pool: &SqlitePool) -> Result<(), SchwabError> {
+        let market_date = self.market_hours.date.format("%Y-%m-%d").to_string();
+        let session_type = self.market_hours.session_type.as_str();
+        let start_at = self
+            .market_hours
+            .start
+            .map(|start| start.with_timezone(&Utc));
+        let end_at = self.market_hours.end.map(|end| end.with_timezone(&Utc));
+
+        sqlx::query!(
+            r#"
+            INSERT INTO schwab_market_hours_cache (
+                id,
+                market_date,
+                session_type,
+                is_open,
+                start_at,
+                end_at,
+                fetched_at
+            )
+            VALUES (1, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                market_date = excluded.market_date,
+                session_type = excluded.session_type,
+                is_open = excluded.is_open,
+                start_at = excluded.start_at,
+                end_at = excluded.end_at,
+                fetched_at = excluded.fetched_at
+            "#,
+            market_date,
+            session_type,
+            self.market_hours.is_open,
+            start_at,
+            end_at,
+            self.fetched_at,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn is_fresh(&self, now: DateTime<Utc>, min_interval: std::time::Duration) -> bool {
+        chrono::Duration::from_std(min_interval)
+            .is_ok_and(|min_interval| now < self.fetched_at + min_interval)
+    }
+}
+
+/// Fetch today's market hours, reusing the persisted response if it was
+/// fetched less than `schwab_market_hours_min_interval_secs` ago.
+pub async fn fetch_market_hours_cached(
+    env: &SchwabAuthEnv,
+    pool: &SqlitePool,
+) -> Result<MarketHours, SchwabError> {
+    let min_interval = std::time::Duration::from_secs(env.schwab_market_hours_min_interval_secs);
+
+    let fresh_cache = MarketHoursCache::load(pool)
+        .await?
+        .filter(|cache| cache.is_fresh(Utc::now(), min_interval));
+
+    if let Some(cache) = fresh_cache {
+        debug!(
+            "Using market hours cached at {} (min interval {}s)",
+            cache.fetched_at,
+            min_interval.as_secs()
+        );
+        return Ok(cache.market_hours);
+    }
+
+    let market_hours = fetch_market_hours(env, pool, None).await?;
+
+    MarketHoursCache {
+        market_hours: market_hours.clone(),
+        fetched_at: Utc::now(),
+    }
+    .store(pool)
+    .await?;
+
+    Ok(market_hours)
+}
+
 /// Raw API response structure for market hours endpoint.
 /// Uses serde to deserialize the Schwab API response format.
 #[derive(Debug, Deserialize)]
@@ -300,6 +442,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: mock_server.base_url(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -363,6 +511,78 @@ mod tests {
         assert!(market_hours.end.is_some());
     }
 
+    #[tokio::test]
+    async fn test_fetch_market_hours_cached_within_interval_reuses_response() {
+        let server = MockServer::start();
+        let mut env = create_test_env_with_mock_server(&server);
+        env.schwab_market_hours_min_interval_secs = 300;
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/marketdata/v1/markets/equity");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "equity": {
+                        "EQ": {
+                            "date": "2025-01-03",
+                            "marketType": "EQUITY",
+                            "product": "EQ",
+                            "isOpen": true,
+                            "sessionHours": {
+                                "regularMarket": [{
+                                    "start": "2025-01-03T09:30:00-05:00",
+                                    "end": "2025-01-03T16:00:00-05:00"
+                                }]
+                            }
+                        }
+                    }
+                }));
+        });
+
+        let first = fetch_market_hours_cached(&env, &pool).await.unwrap();
+        // Served from the persisted cache, as it would be after a restart
+        let second = fetch_market_hours_cached(&env, &pool).await.unwrap();
+
+        mock.assert_hits(1);
+        assert_eq!(first, second);
+        assert_eq!(
+            MarketHoursCache::load(&pool)
+                .await
+                .unwrap()
+                .unwrap()
+                .market_hours,
+            first
+        );
+
+        // Without a minimum interval every call goes to the API
+        env.schwab_market_hours_min_interval_secs = 0;
+        fetch_market_hours_cached(&env, &pool).await.unwrap();
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn test_market_hours_cache_expires_after_interval() {
+        let pool = setup_test_db().await;
+        let cache = MarketHoursCache {
+            market_hours: MarketHours {
+                date: NaiveDate::from_ymd_opt(2025, 1, 4).unwrap(),
+                session_type: MarketSession::Regular,
+                start: None,
+                end: None,
+                is_open: false,
+            },
+            fetched_at: Utc::now() - chrono::Duration::minutes(5),
+        };
+        cache.store(&pool).await.unwrap();
+
+        let loaded = MarketHoursCache::load(&pool).await.unwrap().unwrap();
+        assert_eq!(loaded.market_hours, cache.market_hours);
+        assert!(loaded.is_fresh(Utc::now(), std::time::Duration::from_mins(10)));
+        assert!(!loaded.is_fresh(Utc::now(), std::time::Duration::from_mins(1)));
+    }
+
     #[tokio::test]
     async fn test_fetch_market_hours_closed_market() {
         let server = MockServer::start();
@@ -7,14 +7,16 @@ mod encryption;
 mod market_hours;
 mod order;
 mod order_status;
+mod positions;
+mod quote;
 mod tokens;
 
 // Re-export only what's needed for broker construction
 pub use auth::SchwabAuthEnv;
-pub use broker::{SchwabBroker, SchwabConfig};
+pub use broker::{SchwabBroker, SchwabConfig, SchwabOrderRecord};
 
 // Re-export for auth CLI command (Schwab-specific, not part of generic broker API)
-pub use tokens::SchwabTokens;
+pub use tokens::{SchwabTokens, token_refresh_failures};
 
 /// Errors that can occur during Schwab broker operations including API calls,
 /// authentication, database operations, and order processing.
@@ -74,6 +76,20 @@ pub enum SchwabError {
         body: String,
     },
 
+    /// Schwab API rejected the request with HTTP 429 Too Many Requests.
+    /// `action`: Description of the attempted operation.
+    /// `retry_after_seconds`: Delay requested by the `Retry-After` header.
+    #[error("{action} rate limited, retry after {retry_after_seconds} seconds")]
+    RateLimited {
+        action: String,
+        retry_after_seconds: u64,
+    },
+
+    /// Schwab order preview reported validation rejects for the order.
+    /// `reasons`: Messages of the rejecting validation rules.
+    #[error("Order preview rejected: {}", reasons.join("; "))]
+    OrderPreviewRejected { reasons: Vec<String> },
+
     /// Broker configuration validation failed during initialization.
     #[error("Invalid configuration: {0}")]
     InvalidConfiguration(String),
@@ -100,6 +116,46 @@ pub enum SchwabError {
     Encryption(#[from] encryption::EncryptionError),
 }
 
+impl SchwabError {
+    /// Short label of the error variant, e.g. for metrics
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidHeader(_) => "invalid_header",
+            Self::Reqwest(_) => "request",
+            Self::Sqlx(_) => "database",
+            Self::Io(_) => "io",
+            Self::Url(_) => "url",
+            Self::MissingAuthCode { .. } => "missing_auth_code",
+            Self::JsonSerialization(_) => "json_serialization",
+            Self::RefreshTokenExpired => "refresh_token_expired",
+            Self::NoAccountsFound => "no_accounts_found",
+            Self::AccountIndexOutOfBounds { .. } => "account_index_out_of_bounds",
+            Self::RequestFailed { .. } => "request_failed",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::OrderPreviewRejected { .. } => "order_preview_rejected",
+            Self::InvalidConfiguration(_) => "invalid_configuration",
+            Self::ExecutionPersistence(_) => "execution_persistence",
+            Self::ApiResponseParse { .. } => "api_response_parse",
+            Self::Encryption(_) => "encryption",
+        }
+    }
+
+    /// Whether Schwab could not be reached, rejected our credentials or
+    /// failed on its side, see [`crate::BrokerError::is_outage`]
+    pub fn is_outage(&self) -> bool {
+        match self {
+            Self::Reqwest(e) => {
+                e.is_connect() || e.status().is_some_and(|status| status.is_server_error())
+            }
+            Self::RefreshTokenExpired => true,
+            Self::RequestFailed { status, .. } => {
+                status.is_server_error() || *status == reqwest::StatusCode::UNAUTHORIZED
+            }
+            _ => false,
+        }
+    }
+}
+
 pub fn extract_code_from_url(url: &str) -> Result<String, SchwabError> {
     let parsed_url = url::Url::parse(url)?;
 
@@ -126,6 +182,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: mock_server.base_url(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -1,6 +1,9 @@
 use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
+use num_traits::ToPrimitive;
 use reqwest::header::{self, HeaderMap, HeaderValue};
-use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sqlx::SqlitePool;
 use tracing::error;
 
@@ -12,6 +15,8 @@ use super::{SchwabAuthEnv, SchwabError, SchwabTokens, order_status::OrderStatusR
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct OrderPlacementResponse {
     pub order_id: String,
+    /// JSON body of the order request as sent
+    pub order_json: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -23,10 +28,23 @@ pub struct Order {
     pub duration: OrderDuration,
     pub order_strategy_type: OrderStrategyType,
     pub order_leg_collection: Vec<OrderLeg>,
+    /// Limit price in dollars, only sent for limit orders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+    /// Client order key identifying the order across retries, so a retried
+    /// placement can find the order instead of placing it twice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
 impl Order {
     pub fn new(symbol: String, instruction: Instruction, quantity: u64) -> Self {
+        Self::fractional(symbol, instruction, Decimal::from(quantity))
+    }
+
+    /// Day market order for a quantity that may include a fraction of a
+    /// share, accepted only on accounts enabled for fractional trading.
+    pub fn fractional(symbol: String, instruction: Instruction, quantity: Decimal) -> Self {
         let instrument = Instrument {
             symbol,
             asset_type: AssetType::Equity,
@@ -44,6 +62,17 @@ impl Order {
             duration: OrderDuration::Day,
             order_strategy_type: OrderStrategyType::Single,
             order_leg_collection: vec![order_leg],
+            price: None,
+            tag: None,
+        }
+    }
+
+    /// Day limit order for `quantity` shares at `price` dollars or better.
+    pub fn limit(symbol: String, instruction: Instruction, quantity: u64, price: String) -> Self {
+        Self {
+            order_type: OrderType::Limit,
+            price: Some(price),
+            ..Self::new(symbol, instruction, quantity)
         }
     }
 
@@ -86,6 +115,10 @@ impl Order {
         .retry(ExponentialBuilder::default())
         .await?;
 
+        if let Some(error) = rate_limit_error("place order", &response) {
+            return Err(error);
+        }
+
         if !response.status().is_success() {
             let status = response.status();
             let error_body = response.text().await.unwrap_or_default();
@@ -99,7 +132,135 @@ impl Order {
         // Extract order ID from Location header according to Schwab OpenAPI spec
         let order_id = extract_order_id_from_location_header(&response)?;
 
-        Ok(OrderPlacementResponse { order_id })
+        Ok(OrderPlacementResponse {
+            order_id,
+            order_json,
+        })
+    }
+
+    /// Validates the order with Schwab's preview endpoint without placing it.
+    ///
+    /// Fails if the request fails or if Schwab reports any validation rejects.
+    pub async fn preview(&self, env: &SchwabAuthEnv, pool: &SqlitePool) -> Result<(), SchwabError> {
+        let access_token = SchwabTokens::get_valid_access_token(pool, env).await?;
+        let account_hash = env.get_account_hash(pool).await?;
+
+        let headers = [
+            (
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {access_token}"))?,
+            ),
+            (header::ACCEPT, HeaderValue::from_str("application/json")?),
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_str("application/json")?,
+            ),
+        ]
+        .into_iter()
+        .collect::<HeaderMap>();
+
+        let order_json = serde_json::to_string(self)?;
+
+        let client = reqwest::Client::new();
+        let response = (|| async {
+            client
+                .post(format!(
+                    "{}/trader/v1/accounts/{}/previewOrder",
+                    env.schwab_base_url, account_hash
+                ))
+                .headers(headers.clone())
+                .body(order_json.clone())
+                .send()
+                .await
+        })
+        .retry(ExponentialBuilder::default())
+        .await?;
+
+        if let Some(error) = rate_limit_error("preview order", &response) {
+            return Err(error);
+        }
+
+        let status = response.status();
+        let response_text = response.text().await?;
+
+        if !status.is_success() {
+            return Err(SchwabError::RequestFailed {
+                action: "preview order".to_string(),
+                status,
+                body: response_text,
+            });
+        }
+
+        let preview: OrderPreviewResponse =
+            serde_json::from_str(&response_text).map_err(|e| SchwabError::ApiResponseParse {
+                action: "preview order".to_string(),
+                response_text: response_text.clone(),
+                parse_error: e.to_string(),
+            })?;
+
+        let reasons: Vec<String> = preview
+            .order_validation_result
+            .rejects
+            .into_iter()
+            .map(|reject| reject.message)
+            .collect();
+
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(SchwabError::OrderPreviewRejected { reasons })
+        }
+    }
+
+    /// Cancels a working order. Schwab answers 200 with an empty body once
+    /// the cancel request is accepted.
+    pub async fn cancel(
+        order_id: &str,
+        env: &SchwabAuthEnv,
+        pool: &SqlitePool,
+    ) -> Result<(), SchwabError> {
+        let access_token = SchwabTokens::get_valid_access_token(pool, env).await?;
+        let account_hash = env.get_account_hash(pool).await?;
+
+        let headers = [
+            (
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {access_token}"))?,
+            ),
+            (header::ACCEPT, HeaderValue::from_str("*/*")?),
+        ]
+        .into_iter()
+        .collect::<HeaderMap>();
+
+        let client = reqwest::Client::new();
+        let response = (|| async {
+            client
+                .delete(format!(
+                    "{}/trader/v1/accounts/{}/orders/{}",
+                    env.schwab_base_url, account_hash, order_id
+                ))
+                .headers(headers.clone())
+                .send()
+                .await
+        })
+        .retry(ExponentialBuilder::default())
+        .await?;
+
+        if let Some(error) = rate_limit_error("cancel order", &response) {
+            return Err(error);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(SchwabError::RequestFailed {
+                action: "cancel order".to_string(),
+                status,
+                body: error_body,
+            });
+        }
+
+        Ok(())
     }
 
     /// Get the status of a specific order from Schwab API.
@@ -136,6 +297,10 @@ impl Order {
         .retry(ExponentialBuilder::default())
         .await?;
 
+        if let Some(error) = rate_limit_error("get order status", &response) {
+            return Err(error);
+        }
+
         let status = response.status();
         if status == reqwest::StatusCode::NOT_FOUND {
             return Err(SchwabError::RequestFailed {
@@ -175,6 +340,131 @@ impl Order {
             }
         }
     }
+
+    /// Get every order entered on the account between `from` and `to` in a
+    /// single request, in the same shape as [`Order::get_order_status`].
+    pub(crate) async fn get_orders_entered_between(
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        env: &SchwabAuthEnv,
+        pool: &SqlitePool,
+    ) -> Result<Vec<OrderStatusResponse>, SchwabError> {
+        let access_token = SchwabTokens::get_valid_access_token(pool, env).await?;
+        let account_hash = env.get_account_hash(pool).await?;
+
+        let headers = [
+            (
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {access_token}"))?,
+            ),
+            (header::ACCEPT, HeaderValue::from_str("application/json")?),
+        ]
+        .into_iter()
+        .collect::<HeaderMap>();
+
+        let query = [
+            ("fromEnteredTime", format_entered_time(from)),
+            ("toEnteredTime", format_entered_time(to)),
+        ];
+
+        let client = reqwest::Client::new();
+        let response = (|| async {
+            client
+                .get(format!(
+                    "{}/trader/v1/accounts/{}/orders",
+                    env.schwab_base_url, account_hash
+                ))
+                .headers(headers.clone())
+                .query(&query)
+                .send()
+                .await
+        })
+        .retry(ExponentialBuilder::default())
+        .await?;
+
+        if let Some(error) = rate_limit_error("get orders", &response) {
+            return Err(error);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(SchwabError::RequestFailed {
+                action: "get orders".to_string(),
+                status,
+                body: error_body,
+            });
+        }
+
+        let response_text = response.text().await?;
+        tracing::debug!("Schwab orders response: {}", response_text);
+
+        serde_json::from_str(&response_text).map_err(|e| SchwabError::ApiResponseParse {
+            action: "get orders".to_string(),
+            response_text: response_text.clone(),
+            parse_error: e.to_string(),
+        })
+    }
+}
+
+/// Wait before retrying a rate limited request whose response carries no
+/// usable `Retry-After` header.
+const DEFAULT_RETRY_AFTER_SECS: u64 = 5;
+
+/// Turns a 429 response into [`SchwabError::RateLimited`] with the delay of
+/// its `Retry-After` header, or `None` for any other status.
+pub(super) fn rate_limit_error(action: &str, response: &reqwest::Response) -> Option<SchwabError> {
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let retry_after_seconds = response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after)
+        .unwrap_or(DEFAULT_RETRY_AFTER_SECS);
+
+    Some(SchwabError::RateLimited {
+        action: action.to_string(),
+        retry_after_seconds,
+    })
+}
+
+/// Parses a `Retry-After` value given either in seconds or as an HTTP date,
+/// a date in the past meaning no delay.
+fn parse_retry_after(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds);
+    }
+
+    let retry_at = DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = retry_at.with_timezone(&Utc) - Utc::now();
+    Some(u64::try_from(delay.num_seconds()).unwrap_or(0))
+}
+
+/// Formats a time as Schwab's `yyyy-MM-dd'T'HH:mm:ss.SSSZ` order query filter.
+fn format_entered_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderPreviewResponse {
+    #[serde(default)]
+    order_validation_result: OrderValidationResult,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OrderValidationResult {
+    #[serde(default)]
+    rejects: Vec<OrderValidationDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderValidationDetail {
+    message: String,
 }
 
 /// Extracts order ID from the Location header in Schwab order placement response.
@@ -298,10 +588,31 @@ pub(crate) enum AssetType {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct OrderLeg {
     pub instruction: Instruction,
-    pub quantity: u64,
+    /// Sent as an integer for whole shares, as Schwab has always received it
+    #[serde(
+        serialize_with = "serialize_quantity",
+        deserialize_with = "deserialize_quantity"
+    )]
+    pub quantity: Decimal,
     pub instrument: Instrument,
 }
 
+fn serialize_quantity<S: Serializer>(quantity: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    if let Some(whole) = quantity.to_u64().filter(|_| quantity.fract().is_zero()) {
+        return serializer.serialize_u64(whole);
+    }
+
+    let quantity = quantity
+        .to_f64()
+        .ok_or_else(|| serde::ser::Error::custom(format!("Invalid quantity: {quantity}")))?;
+    serializer.serialize_f64(quantity)
+}
+
+fn deserialize_quantity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let quantity = f64::deserialize(deserializer)?;
+    Decimal::try_from(quantity).map_err(serde::de::Error::custom)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Instrument {
@@ -327,7 +638,7 @@ mod tests {
 
         let leg = &order.order_leg_collection[0];
         assert_eq!(leg.instruction, Instruction::Buy);
-        assert_eq!(leg.quantity, 100);
+        assert_eq!(leg.quantity, Decimal::from(100));
         assert_eq!(leg.instrument.symbol, "AAPL");
         assert_eq!(leg.instrument.asset_type, AssetType::Equity);
     }
@@ -343,7 +654,7 @@ mod tests {
 
         let leg = &order.order_leg_collection[0];
         assert_eq!(leg.instruction, Instruction::Sell);
-        assert_eq!(leg.quantity, 50);
+        assert_eq!(leg.quantity, Decimal::from(50));
         assert_eq!(leg.instrument.symbol, "TSLA");
         assert_eq!(leg.instrument.asset_type, AssetType::Equity);
     }
@@ -354,7 +665,7 @@ mod tests {
 
         let leg = &order.order_leg_collection[0];
         assert_eq!(leg.instruction, Instruction::SellShort);
-        assert_eq!(leg.quantity, 26);
+        assert_eq!(leg.quantity, Decimal::from(26));
         assert_eq!(leg.instrument.symbol, "GME");
     }
 
@@ -364,7 +675,7 @@ mod tests {
 
         let leg = &order.order_leg_collection[0];
         assert_eq!(leg.instruction, Instruction::BuyToCover);
-        assert_eq!(leg.quantity, 15);
+        assert_eq!(leg.quantity, Decimal::from(15));
     }
 
     #[test]
@@ -373,7 +684,7 @@ mod tests {
 
         let leg = &order.order_leg_collection[0];
         assert_eq!(leg.instruction, Instruction::Buy);
-        assert_eq!(leg.quantity, 1);
+        assert_eq!(leg.quantity, Decimal::from(1));
         assert_eq!(leg.instrument.symbol, "SPY");
 
         // Test serialization uses whole numbers
@@ -381,6 +692,21 @@ mod tests {
         assert_eq!(json["orderLegCollection"][0]["quantity"], 1);
     }
 
+    #[test]
+    fn test_fractional_quantity_serialization() {
+        let order = Order::fractional("SPY".to_string(), Instruction::Sell, Decimal::new(25, 2));
+
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["orderType"], "MARKET");
+        assert_eq!(json["orderLegCollection"][0]["quantity"], 0.25);
+
+        let deserialized: Order = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            deserialized.order_leg_collection[0].quantity,
+            Decimal::new(25, 2)
+        );
+    }
+
     #[test]
     fn test_order_serialization() {
         let order = Order::new("MSFT".to_string(), Instruction::Buy, 25);
@@ -441,6 +767,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_limit_order_serializes_price() {
+        let order = Order::limit(
+            "XYZ".to_string(),
+            Instruction::Sell,
+            15,
+            "150.25".to_string(),
+        );
+
+        let json = serde_json::to_value(&order).unwrap();
+
+        assert_eq!(json["orderType"], "LIMIT");
+        assert_eq!(json["price"], "150.25");
+        assert_eq!(json["orderLegCollection"][0]["instruction"], "SELL");
+        assert_eq!(json["orderLegCollection"][0]["quantity"], 15);
+    }
+
+    #[test]
+    fn test_market_order_omits_price() {
+        let order = Order::new("XYZ".to_string(), Instruction::Buy, 15);
+
+        let json = serde_json::to_value(&order).unwrap();
+
+        assert!(json.get("price").is_none());
+    }
+
     #[tokio::test]
     async fn test_place_order_success() {
         let server = httpmock::MockServer::start();
@@ -514,6 +866,61 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_preview_order() {
+        let server = httpmock::MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        let accepted_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/trader/v1/accounts/ABC123DEF456/previewOrder")
+                .header("authorization", "Bearer test_access_token")
+                .body_contains(r#""symbol":"AAPL""#);
+            then.status(200).json_body(json!({
+                "orderValidationResult": {"accepts": [], "rejects": []}
+            }));
+        });
+
+        let rejected_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/trader/v1/accounts/ABC123DEF456/previewOrder")
+                .body_contains(r#""symbol":"XYZ""#);
+            then.status(200).json_body(json!({
+                "orderValidationResult": {
+                    "rejects": [{"validationRuleName": "symbol", "message": "Unknown symbol"}]
+                }
+            }));
+        });
+
+        Order::new("AAPL".to_string(), Instruction::Buy, 1)
+            .preview(&env, &pool)
+            .await
+            .unwrap();
+        accepted_mock.assert();
+
+        let error = Order::new("XYZ".to_string(), Instruction::Buy, 1)
+            .preview(&env, &pool)
+            .await
+            .unwrap_err();
+        rejected_mock.assert();
+        assert!(
+            matches!(error, SchwabError::OrderPreviewRejected { reasons } if reasons == ["Unknown symbol"])
+        );
+    }
+
     fn create_test_env_with_mock_server(mock_server: &httpmock::MockServer) -> SchwabAuthEnv {
         SchwabAuthEnv {
             schwab_app_key: "test_app_key".to_string(),
@@ -521,6 +928,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: mock_server.base_url(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -683,6 +1096,87 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_order_placement_rate_limited() {
+        let server = httpmock::MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        let order_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/trader/v1/accounts/ABC123DEF456/orders");
+            then.status(429).header("Retry-After", "3");
+        });
+
+        let order = Order::new("AAPL".to_string(), Instruction::Buy, 100);
+        let error = order.place(&env, &pool).await.unwrap_err();
+
+        order_mock.assert();
+        assert!(matches!(
+            error,
+            SchwabError::RateLimited { action, retry_after_seconds: 3 } if action == "place order"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_order_status_rate_limited_without_retry_after() {
+        let server = httpmock::MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders/1004055538123");
+            then.status(429);
+        });
+
+        let error = Order::get_order_status("1004055538123", &env, &pool)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            SchwabError::RateLimited { action, retry_after_seconds }
+            if action == "get order status" && retry_after_seconds == DEFAULT_RETRY_AFTER_SECS
+        ));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("3"), Some(3));
+        assert_eq!(parse_retry_after(" 120 "), Some(120));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(0));
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let retry_at = (Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        let delay = parse_retry_after(&retry_at).unwrap();
+        assert!((58..=60).contains(&delay), "unexpected delay {delay}");
+    }
+
     #[tokio::test]
     async fn test_order_placement_server_error_500() {
         let server = httpmock::MockServer::start();
@@ -872,7 +1366,12 @@ mod tests {
         assert!((order_status.filled_quantity.unwrap() - 100.0).abs() < f64::EPSILON);
         let avg_price = order_status.calculate_weighted_average_price().unwrap();
         assert!((avg_price - 150.25).abs() < f64::EPSILON);
-        assert_eq!(order_status.price_in_cents().unwrap(), Some(15025));
+        assert_eq!(
+            order_status
+                .price_in_cents(crate::PriceRounding::HalfEven)
+                .unwrap(),
+            Some(15025)
+        );
     }
 
     #[tokio::test]
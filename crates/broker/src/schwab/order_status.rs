@@ -2,6 +2,7 @@ use num_traits::ToPrimitive;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::BrokerError;
+use crate::price::{PriceRounding, price_to_cents};
 
 /// Deserialize orderId from Schwab API as int64 and convert to string for database compatibility.
 ///
@@ -39,6 +40,52 @@ pub(crate) enum OrderStatus {
     Replaced,
 }
 
+/// How a Schwab order status maps onto our flat [`crate::OrderStatus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StatusCategory {
+    Filled,
+    Failed,
+    /// Live at the exchange or queued at Schwab; keep polling.
+    Working,
+    /// Held by Schwab for review. Non-terminal: keep polling, but the order
+    /// will not progress until someone at Schwab acts on it.
+    UnderReview,
+}
+
+impl StatusCategory {
+    #[cfg(test)]
+    pub(crate) const fn order_status(self) -> crate::OrderStatus {
+        match self {
+            Self::Filled => crate::OrderStatus::Filled,
+            Self::Failed => crate::OrderStatus::Failed,
+            Self::Working | Self::UnderReview => crate::OrderStatus::Submitted,
+        }
+    }
+}
+
+impl OrderStatus {
+    pub(crate) const fn category(self) -> StatusCategory {
+        match self {
+            Self::Filled => StatusCategory::Filled,
+            Self::Canceled | Self::Rejected | Self::Expired => StatusCategory::Failed,
+            Self::PendingReview | Self::AwaitingManualReview => StatusCategory::UnderReview,
+            Self::Queued
+            | Self::Working
+            | Self::PendingActivation
+            | Self::Accepted
+            | Self::AwaitingParentOrder
+            | Self::AwaitingCondition
+            | Self::AwaitingStopCondition
+            | Self::New
+            | Self::AwaitingReleaseTime
+            | Self::PendingReplace
+            // We never replace orders ourselves; keep polling rather than
+            // guessing at the outcome of a replacement made outside the bot
+            | Self::Replaced => StatusCategory::Working,
+        }
+    }
+}
+
 /// Order status response from Schwab API
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,10 +97,33 @@ pub(crate) struct OrderStatusResponse {
     pub remaining_quantity: Option<f64>,
     pub entered_time: Option<String>,
     pub close_time: Option<String>,
+    /// Client tag the order was placed with, see [`super::order::Order::tag`]
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Total quantity ordered
+    #[serde(default)]
+    pub quantity: Option<f64>,
+    #[serde(default)]
+    pub order_leg_collection: Option<Vec<OrderLegStatus>>,
     #[serde(rename = "orderActivityCollection")]
     pub order_activity_collection: Option<Vec<OrderActivity>>,
 }
 
+/// Order leg from Schwab API orderLegCollection, as reported back for an
+/// entered order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OrderLegStatus {
+    pub instruction: Option<String>,
+    pub instrument: Option<InstrumentStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InstrumentStatus {
+    pub symbol: Option<String>,
+}
+
 /// Order activity from Schwab API orderActivityCollection
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -91,21 +161,67 @@ impl OrderStatusResponse {
         }
     }
 
-    /// Convert price to cents for database storage
-    pub(crate) fn price_in_cents(&self) -> Result<Option<u64>, BrokerError> {
+    /// Weighted average fill price in dollars as reported, before rounding
+    pub(crate) fn reported_price(&self) -> Option<String> {
         self.calculate_weighted_average_price()
-            .map(|price| {
-                (price * 100.0)
-                    .round()
-                    .to_u64()
-                    .ok_or(BrokerError::PriceConversion { price })
-            })
+            .map(|price| price.to_string())
+    }
+
+    /// Convert price to cents for database storage
+    pub(crate) fn price_in_cents(
+        &self,
+        rounding: PriceRounding,
+    ) -> Result<Option<u64>, BrokerError> {
+        self.reported_price()
+            .map(|price| price_to_cents(&price, rounding))
             .transpose()
     }
 
+    /// Symbol of the order's first leg
+    pub(crate) fn symbol(&self) -> Option<&str> {
+        self.order_leg_collection
+            .as_ref()?
+            .first()?
+            .instrument
+            .as_ref()?
+            .symbol
+            .as_deref()
+    }
+
+    /// Direction of the order's first leg; other instructions, e.g. short
+    /// sales, have none
+    pub(crate) fn direction(&self) -> Option<crate::Direction> {
+        let leg = self.order_leg_collection.as_ref()?.first()?;
+        match leg.instruction.as_deref()? {
+            "BUY" => Some(crate::Direction::Buy),
+            "SELL" => Some(crate::Direction::Sell),
+            _ => None,
+        }
+    }
+
+    /// Ordered quantity in whole shares; fractional quantities have none
+    pub(crate) fn whole_shares(&self) -> Option<u64> {
+        let quantity = self.quantity?;
+        if quantity.fract() > 0.0 {
+            return None;
+        }
+
+        quantity.to_u64()
+    }
+
+    /// Category of the reported status; a missing status is treated as
+    /// still working so the order keeps being polled.
+    pub(crate) const fn category(&self) -> StatusCategory {
+        match self.status {
+            Some(status) => status.category(),
+            None => StatusCategory::Working,
+        }
+    }
+
     /// Check if order is completely filled
+    #[cfg(test)]
     pub(crate) const fn is_filled(&self) -> bool {
-        matches!(self.status, Some(OrderStatus::Filled))
+        matches!(self.category(), StatusCategory::Filled)
     }
 
     /// Check if order is still pending/working
@@ -131,11 +247,9 @@ impl OrderStatusResponse {
     }
 
     /// Check if order was canceled or rejected
+    #[cfg(test)]
     pub(crate) const fn is_terminal_failure(&self) -> bool {
-        matches!(
-            self.status,
-            Some(OrderStatus::Canceled | OrderStatus::Rejected | OrderStatus::Expired)
-        )
+        matches!(self.category(), StatusCategory::Failed)
     }
 }
 
@@ -195,6 +309,9 @@ mod tests {
             remaining_quantity: Some(0.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: Some("2023-10-15T10:30:00Z".to_string()),
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![OrderActivity {
                 activity_type: Some("EXECUTION".to_string()),
                 execution_legs: Some(vec![ExecutionLeg {
@@ -216,6 +333,9 @@ mod tests {
             remaining_quantity: Some(0.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: Some("2023-10-15T10:30:10Z".to_string()),
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![OrderActivity {
                 activity_type: Some("EXECUTION".to_string()),
                 execution_legs: Some(vec![
@@ -243,6 +363,9 @@ mod tests {
             remaining_quantity: Some(0.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: Some("2023-10-15T10:30:10Z".to_string()),
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![OrderActivity {
                 activity_type: Some("EXECUTION".to_string()),
                 execution_legs: Some(vec![
@@ -270,6 +393,9 @@ mod tests {
             remaining_quantity: Some(100.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: None,
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![]),
         };
 
@@ -285,6 +411,9 @@ mod tests {
             remaining_quantity: Some(0.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: Some("2023-10-15T10:30:00Z".to_string()),
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![OrderActivity {
                 activity_type: Some("EXECUTION".to_string()),
                 execution_legs: Some(vec![ExecutionLeg {
@@ -294,7 +423,10 @@ mod tests {
             }]),
         };
 
-        assert_eq!(response.price_in_cents().unwrap(), Some(15025));
+        assert_eq!(
+            response.price_in_cents(PriceRounding::HalfEven).unwrap(),
+            Some(15025)
+        );
     }
 
     #[test]
@@ -306,10 +438,16 @@ mod tests {
             remaining_quantity: Some(100.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: None,
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![]),
         };
 
-        assert_eq!(response.price_in_cents().unwrap(), None);
+        assert_eq!(
+            response.price_in_cents(PriceRounding::HalfEven).unwrap(),
+            None
+        );
     }
 
     #[test]
@@ -321,6 +459,9 @@ mod tests {
             remaining_quantity: Some(0.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: Some("2023-10-15T10:30:00Z".to_string()),
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![OrderActivity {
                 activity_type: Some("EXECUTION".to_string()),
                 execution_legs: Some(vec![ExecutionLeg {
@@ -330,7 +471,45 @@ mod tests {
             }]),
         };
 
-        assert_eq!(response.price_in_cents().unwrap(), Some(15025));
+        assert_eq!(
+            response.price_in_cents(PriceRounding::HalfEven).unwrap(),
+            Some(15025)
+        );
+    }
+
+    #[test]
+    fn test_price_in_cents_sub_cent_fill_uses_rounding_mode() {
+        let response = OrderStatusResponse {
+            order_id: Some("1004055538123".to_string()),
+            status: Some(OrderStatus::Filled),
+            filled_quantity: Some(50.0),
+            remaining_quantity: Some(0.0),
+            entered_time: Some("2023-10-15T10:25:00Z".to_string()),
+            close_time: Some("2023-10-15T10:30:00Z".to_string()),
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
+            order_activity_collection: Some(vec![OrderActivity {
+                activity_type: Some("EXECUTION".to_string()),
+                execution_legs: Some(vec![ExecutionLeg {
+                    quantity: 50.0,
+                    price: 245.665,
+                }]),
+            }]),
+        };
+
+        // Same sub-cent price and expectations as the Alpaca fill test
+        assert_eq!(response.reported_price().as_deref(), Some("245.665"));
+        for (rounding, expected_cents) in [
+            (PriceRounding::HalfEven, 24566),
+            (PriceRounding::HalfUp, 24567),
+            (PriceRounding::Truncate, 24566),
+        ] {
+            assert_eq!(
+                response.price_in_cents(rounding).unwrap(),
+                Some(expected_cents)
+            );
+        }
     }
 
     #[test]
@@ -342,6 +521,9 @@ mod tests {
             remaining_quantity: Some(0.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: Some("2023-10-15T10:30:00Z".to_string()),
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![]),
         };
 
@@ -351,6 +533,43 @@ mod tests {
         assert!(!response.is_filled());
     }
 
+    #[test]
+    fn test_review_statuses_stay_pending() {
+        for status in [
+            OrderStatus::AwaitingManualReview,
+            OrderStatus::PendingReview,
+        ] {
+            let json = serde_json::to_string(&status).unwrap();
+            let parsed: OrderStatus = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(parsed.category(), StatusCategory::UnderReview);
+            assert_eq!(
+                parsed.category().order_status(),
+                crate::OrderStatus::Submitted
+            );
+        }
+
+        let response: OrderStatusResponse = serde_json::from_str(
+            r#"{"orderId": 1004055538123, "status": "AWAITING_MANUAL_REVIEW"}"#,
+        )
+        .unwrap();
+        assert_eq!(response.category(), StatusCategory::UnderReview);
+        assert!(response.is_pending());
+        assert!(!response.is_filled());
+        assert!(!response.is_terminal_failure());
+    }
+
+    #[test]
+    fn test_status_categories() {
+        assert_eq!(OrderStatus::Filled.category(), StatusCategory::Filled);
+        assert_eq!(OrderStatus::Rejected.category(), StatusCategory::Failed);
+        assert_eq!(OrderStatus::Working.category(), StatusCategory::Working);
+        assert_eq!(
+            StatusCategory::Failed.order_status(),
+            crate::OrderStatus::Failed
+        );
+    }
+
     #[test]
     fn test_is_pending() {
         let pending_states = [
@@ -376,6 +595,9 @@ mod tests {
                 remaining_quantity: Some(100.0),
                 entered_time: Some("2023-10-15T10:25:00Z".to_string()),
                 close_time: None,
+                tag: None,
+                quantity: None,
+                order_leg_collection: None,
                 order_activity_collection: Some(vec![]),
             };
             assert!(response.is_pending(), "Status {status:?} should be pending");
@@ -397,6 +619,9 @@ mod tests {
                 remaining_quantity: Some(0.0),
                 entered_time: Some("2023-10-15T10:25:00Z".to_string()),
                 close_time: Some("2023-10-15T10:30:00Z".to_string()),
+                tag: None,
+                quantity: None,
+                order_leg_collection: None,
                 order_activity_collection: Some(vec![]),
             };
             assert!(
@@ -422,6 +647,9 @@ mod tests {
                 remaining_quantity: Some(100.0),
                 entered_time: Some("2023-10-15T10:25:00Z".to_string()),
                 close_time: Some("2023-10-15T10:30:00Z".to_string()),
+                tag: None,
+                quantity: None,
+                order_leg_collection: None,
                 order_activity_collection: Some(vec![]),
             };
             assert!(
@@ -445,6 +673,9 @@ mod tests {
                 remaining_quantity: Some(100.0),
                 entered_time: Some("2023-10-15T10:25:00Z".to_string()),
                 close_time: None,
+                tag: None,
+                quantity: None,
+                order_leg_collection: None,
                 order_activity_collection: Some(vec![]),
             };
             assert!(
@@ -498,7 +729,10 @@ mod tests {
         // Test weighted average: (150 * 100.25 + 50 * 100.75) / 200 = (15037.5 + 5037.5) / 200 = 100.375
         let avg_price = response.calculate_weighted_average_price().unwrap();
         assert!((avg_price - 100.375).abs() < f64::EPSILON);
-        assert_eq!(response.price_in_cents().unwrap(), Some(10038)); // Rounded
+        assert_eq!(
+            response.price_in_cents(PriceRounding::HalfEven).unwrap(),
+            Some(10038)
+        ); // Rounded
     }
 
     #[test]
@@ -510,6 +744,9 @@ mod tests {
             remaining_quantity: Some(100.0),
             entered_time: Some("2023-10-15T10:25:00Z".to_string()),
             close_time: None,
+            tag: None,
+            quantity: None,
+            order_leg_collection: None,
             order_activity_collection: Some(vec![OrderActivity {
                 activity_type: Some("EXECUTION".to_string()),
                 execution_legs: Some(vec![ExecutionLeg {
@@ -520,7 +757,10 @@ mod tests {
         };
 
         assert_eq!(response.calculate_weighted_average_price(), None);
-        assert_eq!(response.price_in_cents().unwrap(), None);
+        assert_eq!(
+            response.price_in_cents(PriceRounding::HalfEven).unwrap(),
+            None
+        );
     }
 
     #[test]
@@ -599,7 +839,7 @@ mod tests {
         assert!((avg_price.unwrap() - 22.7299).abs() < f64::EPSILON);
 
         // Verify price in cents conversion
-        let price_cents = parsed.price_in_cents().unwrap();
+        let price_cents = parsed.price_in_cents(PriceRounding::HalfEven).unwrap();
         assert_eq!(price_cents, Some(2273)); // 22.7299 * 100 rounded = 2273 cents
     }
 
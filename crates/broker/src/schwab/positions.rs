@@ -0,0 +1,289 @@
+use backon::{ExponentialBuilder, Retryable};
+use num_traits::ToPrimitive;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use tracing::debug;
+
+use super::order::rate_limit_error;
+use super::{SchwabAuthEnv, SchwabError, SchwabTokens};
+use crate::{BrokerError, BrokerPosition, Symbol};
+
+/// Raw API response of the account endpoint with the `positions` field.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountResponse {
+    securities_account: SecuritiesAccount,
+}
+
+#[derive(Debug, Deserialize)]
+struct SecuritiesAccount {
+    /// Left out entirely by Schwab when the account holds nothing
+    #[serde(default)]
+    positions: Vec<PositionEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionEntry {
+    #[serde(default)]
+    long_quantity: f64,
+    #[serde(default)]
+    short_quantity: f64,
+    average_price: f64,
+    instrument: Instrument,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Instrument {
+    asset_type: String,
+    symbol: String,
+}
+
+/// Fetch the equity positions held in the configured account.
+///
+/// Uses the `/trader/v1/accounts/{accountHash}` endpoint with the
+/// `positions` field. Non-equity holdings, e.g. the cash sweep, are skipped.
+pub(crate) async fn fetch_positions(
+    env: &SchwabAuthEnv,
+    pool: &SqlitePool,
+) -> Result<Vec<BrokerPosition>, BrokerError> {
+    let access_token = SchwabTokens::get_valid_access_token(pool, env).await?;
+    let account_hash = env.get_account_hash(pool).await?;
+
+    let headers = [
+        (
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {access_token}")).map_err(SchwabError::from)?,
+        ),
+        (
+            header::ACCEPT,
+            HeaderValue::from_str("application/json").map_err(SchwabError::from)?,
+        ),
+    ]
+    .into_iter()
+    .collect::<HeaderMap>();
+
+    let url = format!("{}/trader/v1/accounts/{account_hash}", env.schwab_base_url);
+    debug!("Fetching positions from: {url}");
+
+    let client = reqwest::Client::new();
+    let response = (|| async {
+        client
+            .get(&url)
+            .query(&[("fields", "positions")])
+            .headers(headers.clone())
+            .send()
+            .await
+    })
+    .retry(ExponentialBuilder::default())
+    .await
+    .map_err(SchwabError::from)?;
+
+    if let Some(error) = rate_limit_error("get positions", &response) {
+        return Err(error.into());
+    }
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(SchwabError::RequestFailed {
+            action: "get positions".to_string(),
+            status,
+            body,
+        }
+        .into());
+    }
+
+    let response_text = response.text().await.map_err(SchwabError::from)?;
+    parse_positions(&response_text)
+}
+
+fn parse_positions(response_text: &str) -> Result<Vec<BrokerPosition>, BrokerError> {
+    let parse_error = |parse_error: String| SchwabError::ApiResponseParse {
+        action: "get positions".to_string(),
+        response_text: response_text.to_string(),
+        parse_error,
+    };
+
+    let account: AccountResponse =
+        serde_json::from_str(response_text).map_err(|e| parse_error(e.to_string()))?;
+
+    account
+        .securities_account
+        .positions
+        .into_iter()
+        .filter(|entry| entry.instrument.asset_type == "EQUITY")
+        .map(|entry| {
+            let quantity = Decimal::try_from(entry.long_quantity - entry.short_quantity)
+                .map_err(|e| parse_error(format!("Invalid quantity: {e}")))?;
+            let average_price_cents = (entry.average_price * 100.0).round().to_u64().ok_or(
+                BrokerError::PriceConversion {
+                    price: entry.average_price,
+                },
+            )?;
+
+            Ok(BrokerPosition {
+                symbol: Symbol::new(entry.instrument.symbol)?,
+                quantity,
+                average_price_cents,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TEST_ENCRYPTION_KEY, setup_test_db, setup_test_tokens};
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn create_test_env_with_mock_server(mock_server: &MockServer) -> SchwabAuthEnv {
+        SchwabAuthEnv {
+            schwab_app_key: "test_app_key".to_string(),
+            schwab_app_secret: "test_app_secret".to_string(),
+            schwab_redirect_uri: "https://127.0.0.1".to_string(),
+            schwab_base_url: mock_server.base_url(),
+            schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
+            encryption_key: TEST_ENCRYPTION_KEY,
+        }
+    }
+
+    #[test]
+    fn test_parse_positions_payload() {
+        let payload = json!({
+            "securitiesAccount": {
+                "type": "MARGIN",
+                "accountNumber": "123456789",
+                "roundTrips": 0,
+                "isDayTrader": false,
+                "positions": [
+                    {
+                        "shortQuantity": 0.0,
+                        "averagePrice": 150.2534,
+                        "currentDayProfitLoss": 12.5,
+                        "longQuantity": 25.0,
+                        "settledLongQuantity": 25.0,
+                        "settledShortQuantity": 0.0,
+                        "instrument": {
+                            "assetType": "EQUITY",
+                            "cusip": "037833100",
+                            "symbol": "AAPL",
+                            "netChange": 0.5
+                        },
+                        "marketValue": 3768.75
+                    },
+                    {
+                        "shortQuantity": 10.0,
+                        "averagePrice": 410.1,
+                        "longQuantity": 0.0,
+                        "averageShortPrice": 410.1,
+                        "instrument": {
+                            "assetType": "EQUITY",
+                            "cusip": "594918104",
+                            "symbol": "MSFT"
+                        },
+                        "marketValue": -4101.0
+                    },
+                    {
+                        "shortQuantity": 0.0,
+                        "averagePrice": 1.0,
+                        "longQuantity": 520.12,
+                        "instrument": {
+                            "assetType": "CASH_EQUIVALENT",
+                            "symbol": "MMDA1"
+                        },
+                        "marketValue": 520.12
+                    }
+                ],
+                "currentBalances": {
+                    "cashBalance": 1000.0
+                }
+            }
+        });
+
+        let positions = parse_positions(&payload.to_string()).unwrap();
+
+        assert_eq!(
+            positions,
+            vec![
+                BrokerPosition {
+                    symbol: Symbol::new("AAPL").unwrap(),
+                    quantity: Decimal::from(25),
+                    average_price_cents: 15025,
+                },
+                BrokerPosition {
+                    symbol: Symbol::new("MSFT").unwrap(),
+                    quantity: Decimal::from(-10),
+                    average_price_cents: 41010,
+                },
+            ]
+        );
+
+        // An account without holdings has no positions array
+        let empty = json!({"securitiesAccount": {"type": "CASH"}});
+        assert!(parse_positions(&empty.to_string()).unwrap().is_empty());
+
+        assert!(matches!(
+            parse_positions("{}").unwrap_err(),
+            BrokerError::Schwab(SchwabError::ApiResponseParse { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_positions_from_account_endpoint() {
+        let server = MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        let account_mock = server.mock(|when, then| {
+            when.method(GET).path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+        let positions_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/trader/v1/accounts/ABC123DEF456")
+                .query_param("fields", "positions")
+                .header("authorization", "Bearer test_access_token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "securitiesAccount": {
+                        "positions": [{
+                            "longQuantity": 3.0,
+                            "averagePrice": 99.5,
+                            "instrument": {"assetType": "EQUITY", "symbol": "TSLA"}
+                        }]
+                    }
+                }));
+        });
+
+        let positions = fetch_positions(&env, &pool).await.unwrap();
+
+        account_mock.assert();
+        positions_mock.assert();
+        assert_eq!(
+            positions,
+            vec![BrokerPosition {
+                symbol: Symbol::new("TSLA").unwrap(),
+                quantity: Decimal::from(3),
+                average_price_cents: 9950,
+            }]
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
+use num_traits::ToPrimitive;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use tracing::debug;
+
+use super::{SchwabAuthEnv, SchwabError, SchwabTokens};
+use crate::{BrokerError, Quote, Symbol};
+
+/// Raw API response entry for a single symbol from the quotes endpoint.
+#[derive(Debug, Deserialize)]
+struct QuoteResponseEntry {
+    quote: Option<QuoteDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteDetail {
+    bid_price: f64,
+    ask_price: f64,
+    /// Milliseconds since the Unix epoch
+    quote_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct FundamentalResponseEntry {
+    fundamental: Option<FundamentalDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FundamentalDetail {
+    avg10_days_volume: f64,
+}
+
+/// Fetch the latest quote for a symbol from the Schwab Market Data API.
+///
+/// Uses the `/marketdata/v1/quotes` endpoint restricted to the `quote` field.
+pub(crate) async fn fetch_quote(
+    env: &SchwabAuthEnv,
+    pool: &SqlitePool,
+    symbol: &Symbol,
+) -> Result<Quote, BrokerError> {
+    let response_text = fetch_quotes_field(env, pool, symbol, "quote").await?;
+    let mut entries: HashMap<String, QuoteResponseEntry> = serde_json::from_str(&response_text)
+        .map_err(|e| SchwabError::ApiResponseParse {
+            action: "fetch quote".to_string(),
+            response_text: response_text.clone(),
+            parse_error: e.to_string(),
+        })?;
+
+    let detail = entries
+        .remove(&symbol.to_string())
+        .and_then(|entry| entry.quote)
+        .ok_or_else(|| SchwabError::ApiResponseParse {
+            action: "fetch quote".to_string(),
+            response_text: response_text.clone(),
+            parse_error: format!("No quote returned for symbol {symbol}"),
+        })?;
+
+    let quoted_at = DateTime::<Utc>::from_timestamp_millis(detail.quote_time).ok_or_else(|| {
+        SchwabError::ApiResponseParse {
+            action: "fetch quote".to_string(),
+            response_text: response_text.clone(),
+            parse_error: format!("Quote time {} is out of range", detail.quote_time),
+        }
+    })?;
+
+    Ok(Quote {
+        symbol: symbol.clone(),
+        bid_price_cents: dollars_to_cents(detail.bid_price)?,
+        ask_price_cents: dollars_to_cents(detail.ask_price)?,
+        quoted_at,
+    })
+}
+
+/// Fetch the 10-day average daily volume (in shares) for a symbol.
+///
+/// Uses the `/marketdata/v1/quotes` endpoint restricted to the `fundamental`
+/// field, which carries Schwab's `avg10DaysVolume`.
+pub(crate) async fn fetch_average_daily_volume(
+    env: &SchwabAuthEnv,
+    pool: &SqlitePool,
+    symbol: &Symbol,
+) -> Result<u64, BrokerError> {
+    let response_text = fetch_quotes_field(env, pool, symbol, "fundamental").await?;
+    let mut entries: HashMap<String, FundamentalResponseEntry> =
+        serde_json::from_str(&response_text).map_err(|e| SchwabError::ApiResponseParse {
+            action: "fetch average daily volume".to_string(),
+            response_text: response_text.clone(),
+            parse_error: e.to_string(),
+        })?;
+
+    let detail = entries
+        .remove(&symbol.to_string())
+        .and_then(|entry| entry.fundamental)
+        .ok_or_else(|| SchwabError::ApiResponseParse {
+            action: "fetch average daily volume".to_string(),
+            response_text: response_text.clone(),
+            parse_error: format!("No fundamentals returned for symbol {symbol}"),
+        })?;
+
+    detail.avg10_days_volume.round().to_u64().ok_or_else(|| {
+        SchwabError::ApiResponseParse {
+            action: "fetch average daily volume".to_string(),
+            response_text: response_text.clone(),
+            parse_error: format!(
+                "Average daily volume {} is not a valid share count",
+                detail.avg10_days_volume
+            ),
+        }
+        .into()
+    })
+}
+
+/// Request a single field group for `symbol` from the quotes endpoint and
+/// return the raw response body.
+async fn fetch_quotes_field(
+    env: &SchwabAuthEnv,
+    pool: &SqlitePool,
+    symbol: &Symbol,
+    field: &str,
+) -> Result<String, BrokerError> {
+    let access_token = SchwabTokens::get_valid_access_token(pool, env).await?;
+
+    let headers = [
+        (
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {access_token}")).map_err(SchwabError::from)?,
+        ),
+        (
+            header::ACCEPT,
+            HeaderValue::from_str("application/json").map_err(SchwabError::from)?,
+        ),
+    ]
+    .into_iter()
+    .collect::<HeaderMap>();
+
+    let url = format!("{}/marketdata/v1/quotes", env.schwab_base_url);
+    let query = [
+        ("symbols", symbol.to_string()),
+        ("fields", field.to_string()),
+    ];
+
+    debug!("Fetching {field} for {symbol} from: {url}");
+
+    let client = reqwest::Client::new();
+    let response = (|| async {
+        client
+            .get(&url)
+            .query(&query)
+            .headers(headers.clone())
+            .send()
+            .await
+    })
+    .retry(ExponentialBuilder::default())
+    .await
+    .map_err(SchwabError::from)?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Failed to read response body".to_string());
+        return Err(SchwabError::RequestFailed {
+            action: format!("fetch {field}"),
+            status,
+            body,
+        }
+        .into());
+    }
+
+    Ok(response.text().await.map_err(SchwabError::from)?)
+}
+
+fn dollars_to_cents(price: f64) -> Result<u64, BrokerError> {
+    (price * 100.0)
+        .round()
+        .to_u64()
+        .ok_or(BrokerError::PriceConversion { price })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TEST_ENCRYPTION_KEY, setup_test_db, setup_test_tokens};
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn create_test_env_with_mock_server(mock_server: &MockServer) -> SchwabAuthEnv {
+        SchwabAuthEnv {
+            schwab_app_key: "test_app_key".to_string(),
+            schwab_app_secret: "test_app_secret".to_string(),
+            schwab_redirect_uri: "https://127.0.0.1".to_string(),
+            schwab_base_url: mock_server.base_url(),
+            schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
+            encryption_key: TEST_ENCRYPTION_KEY,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_quote_success() {
+        let server = MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/marketdata/v1/quotes")
+                .query_param("symbols", "AAPL")
+                .query_param("fields", "quote")
+                .header("authorization", "Bearer test_access_token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "AAPL": {
+                        "assetMainType": "EQUITY",
+                        "symbol": "AAPL",
+                        "quote": {
+                            "bidPrice": 150.10,
+                            "askPrice": 150.15,
+                            "lastPrice": 150.12,
+                            "quoteTime": 1_736_000_000_000_i64
+                        }
+                    }
+                }));
+        });
+
+        let quote = fetch_quote(&env, &pool, &Symbol::new("AAPL").unwrap())
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(quote.symbol, Symbol::new("AAPL").unwrap());
+        assert_eq!(quote.bid_price_cents, 15010);
+        assert_eq!(quote.ask_price_cents, 15015);
+        assert_eq!(quote.quoted_at.timestamp(), 1_736_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_quote_missing_symbol() {
+        let server = MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/marketdata/v1/quotes");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({}));
+        });
+
+        let result = fetch_quote(&env, &pool, &Symbol::new("AAPL").unwrap()).await;
+
+        mock.assert();
+        assert!(matches!(
+            result.unwrap_err(),
+            BrokerError::Schwab(SchwabError::ApiResponseParse { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_quote_request_failure() {
+        let server = MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/marketdata/v1/quotes");
+            then.status(400).body("Bad Request");
+        });
+
+        let result = fetch_quote(&env, &pool, &Symbol::new("AAPL").unwrap()).await;
+
+        mock.assert();
+        assert!(matches!(
+            result.unwrap_err(),
+            BrokerError::Schwab(SchwabError::RequestFailed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_average_daily_volume_success() {
+        let server = MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/marketdata/v1/quotes")
+                .query_param("symbols", "AAPL")
+                .query_param("fields", "fundamental")
+                .header("authorization", "Bearer test_access_token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "AAPL": {
+                        "assetMainType": "EQUITY",
+                        "symbol": "AAPL",
+                        "fundamental": {
+                            "avg10DaysVolume": 48_512_345.6,
+                            "avg1YearVolume": 55_000_000.0
+                        }
+                    }
+                }));
+        });
+
+        let adv = fetch_average_daily_volume(&env, &pool, &Symbol::new("AAPL").unwrap())
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(adv, 48_512_346);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_average_daily_volume_missing_fundamentals() {
+        let server = MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, &env).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/marketdata/v1/quotes");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({"AAPL": {"symbol": "AAPL"}}));
+        });
+
+        let result = fetch_average_daily_volume(&env, &pool, &Symbol::new("AAPL").unwrap()).await;
+
+        mock.assert();
+        assert!(matches!(
+            result.unwrap_err(),
+            BrokerError::Schwab(SchwabError::ApiResponseParse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dollars_to_cents() {
+        assert_eq!(dollars_to_cents(150.1).unwrap(), 15010);
+        assert!(matches!(
+            dollars_to_cents(-1.0).unwrap_err(),
+            BrokerError::PriceConversion { .. }
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
 use sqlx::SqlitePool;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::task::JoinHandle;
 use tokio::time::{Duration as TokioDuration, interval};
 use tracing::{error, info, warn};
@@ -15,6 +16,13 @@ const ACCESS_TOKEN_DURATION_MINUTES: i64 = 30;
 const REFRESH_TOKEN_DURATION_DAYS: i64 = 7;
 const ENCRYPTION_VERSION: i64 = 1;
 
+static TOKEN_REFRESH_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+/// Automatic token refreshes that failed since the process started
+pub fn token_refresh_failures() -> u64 {
+    TOKEN_REFRESH_FAILURES.load(Ordering::Relaxed)
+}
+
 #[derive(Debug)]
 pub struct SchwabTokens {
     /// Expires every 30 minutes
@@ -238,10 +246,12 @@ async fn handle_token_refresh(pool: &SqlitePool, env: &SchwabAuthEnv) -> Result<
         }
         Ok(_) => Ok(()),
         Err(SchwabError::RefreshTokenExpired) => {
+            TOKEN_REFRESH_FAILURES.fetch_add(1, Ordering::Relaxed);
             error!("Refresh token expired, manual re-authentication required");
             Err(SchwabError::RefreshTokenExpired)
         }
         Err(e) => {
+            TOKEN_REFRESH_FAILURES.fetch_add(1, Ordering::Relaxed);
             warn!("Failed to refresh token: {e}");
             Ok(())
         }
@@ -265,6 +275,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: mock_server.base_url(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -276,6 +292,12 @@ mod tests {
             schwab_redirect_uri: "https://127.0.0.1".to_string(),
             schwab_base_url: "https://api.schwabapi.com".to_string(),
             schwab_account_index: 0,
+            schwab_alert_on_manual_review: false,
+            schwab_market_hours_min_interval_secs: 0,
+            schwab_order_batch_lookback_hours: None,
+            schwab_persist_order_payloads: false,
+            schwab_fractional_shares: false,
+            price_rounding: crate::PriceRounding::HalfEven,
             encryption_key: TEST_ENCRYPTION_KEY,
         }
     }
@@ -651,6 +673,28 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_failed_automatic_refresh_is_counted() {
+        let server = MockServer::start();
+        let env = create_test_env_with_mock_server(&server);
+        let pool = setup_test_db().await;
+        let now = Utc::now();
+
+        let tokens = SchwabTokens {
+            access_token: "expired_access_token".to_string(),
+            access_token_fetched_at: now - Duration::minutes(31),
+            refresh_token: "expired_refresh_token".to_string(),
+            refresh_token_fetched_at: now - Duration::days(8),
+        };
+        tokens.store(&pool, &env.encryption_key).await.unwrap();
+
+        let failures_before = token_refresh_failures();
+        handle_token_refresh(&pool, &env).await.unwrap_err();
+
+        // Other tests may fail refreshes concurrently
+        assert!(token_refresh_failures() > failures_before);
+    }
+
     #[tokio::test]
     async fn test_refresh_if_needed_no_refresh_needed() {
         let server = MockServer::start();
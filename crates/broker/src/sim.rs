@@ -0,0 +1,633 @@
+//! Dry-run broker that simulates realistic fills.
+//!
+//! Unlike [`crate::MockBroker`], which reports every order as filled at a
+//! fixed price straight away, [`SimBroker`] keeps orders SUBMITTED for a
+//! configurable number of status polls, then fills them with a configurable
+//! probability per poll at a reference price moved against the order by a
+//! slippage in basis points. This exercises the order poller and the
+//! accounting of fill prices end to end without touching a real broker.
+
+use async_trait::async_trait;
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::{
+    Arc, Mutex, PoisonError,
+    atomic::{AtomicU64, Ordering},
+};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::mock::MOCK_AVERAGE_DAILY_VOLUME;
+use crate::position::{Fill, positions_from_fills};
+use crate::{
+    Broker, BrokerError, BrokerPosition, Direction, FractionalMarketOrder,
+    FractionalOrderPlacement, LimitOrder, MarketOrder, OrderPlacement, OrderState, OrderUpdate,
+    Quote, Shares, SupportedBroker, Symbol,
+};
+
+const BPS_PER_UNIT: u64 = 10_000;
+
+/// Simulated broker environment configuration
+#[derive(Parser, Debug, Clone)]
+pub struct SimBrokerConfig {
+    /// Number of status polls an order stays SUBMITTED before it can fill
+    #[clap(long, env, default_value = "3")]
+    pub sim_fill_delay_polls: u32,
+
+    /// Probability that an order due to fill fills on a given poll
+    #[clap(long, env, default_value = "1.0", value_parser = parse_fill_probability)]
+    pub sim_fill_probability: f64,
+
+    /// Slippage applied against the order to the reference price, in basis
+    /// points
+    #[clap(long, env, default_value = "5", value_parser = clap::value_parser!(u32).range(0..10_000))]
+    pub sim_slippage_bps: u32,
+
+    /// Reference price per share for symbols without an injected price
+    #[clap(long, env, default_value = "10000")]
+    pub sim_reference_price_cents: u64,
+
+    /// Seed for the fill randomness, for reproducible runs
+    #[clap(long, env)]
+    pub sim_seed: Option<u64>,
+}
+
+impl Default for SimBrokerConfig {
+    fn default() -> Self {
+        Self {
+            sim_fill_delay_polls: 3,
+            sim_fill_probability: 1.0,
+            sim_slippage_bps: 5,
+            sim_reference_price_cents: 10_000,
+            sim_seed: None,
+        }
+    }
+}
+
+fn parse_fill_probability(value: &str) -> Result<f64, String> {
+    let probability: f64 = value.parse().map_err(|e| format!("{e}"))?;
+    if (0.0..=1.0).contains(&probability) {
+        Ok(probability)
+    } else {
+        Err(format!("{probability} is not between 0 and 1"))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SimOrder {
+    symbol: Symbol,
+    shares: Shares,
+    direction: Direction,
+    limit_price_cents: Option<u64>,
+    polls: u32,
+    state: OrderState,
+}
+
+/// Broker that fills orders after a delay at a slipped reference price
+#[derive(Debug, Clone)]
+pub struct SimBroker {
+    config: SimBrokerConfig,
+    order_counter: Arc<AtomicU64>,
+    orders: Arc<Mutex<HashMap<String, SimOrder>>>,
+    reference_prices: Arc<Mutex<HashMap<Symbol, u64>>>,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl SimBroker {
+    pub fn new(config: SimBrokerConfig) -> Self {
+        let rng = config
+            .sim_seed
+            .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+
+        Self {
+            config,
+            order_counter: Arc::new(AtomicU64::new(1)),
+            orders: Arc::default(),
+            reference_prices: Arc::default(),
+            rng: Arc::new(Mutex::new(rng)),
+        }
+    }
+
+    /// Sets the price orders for `symbol` fill around, before slippage.
+    pub fn set_reference_price(&self, symbol: Symbol, price_cents: u64) {
+        self.reference_prices
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(symbol, price_cents);
+    }
+
+    fn reference_price_cents(&self, symbol: &Symbol) -> u64 {
+        self.reference_prices
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.config.sim_reference_price_cents)
+    }
+
+    /// Reference price moved against `direction` by the configured slippage,
+    /// rounded to the nearest cent.
+    fn slipped_price_cents(&self, symbol: &Symbol, direction: Direction) -> u64 {
+        let reference = u128::from(self.reference_price_cents(symbol));
+        let bps = u128::from(self.config.sim_slippage_bps);
+        let factor = match direction {
+            Direction::Buy => u128::from(BPS_PER_UNIT) + bps,
+            Direction::Sell => u128::from(BPS_PER_UNIT) - bps,
+        };
+        let half = u128::from(BPS_PER_UNIT / 2);
+
+        u64::try_from((reference * factor + half) / u128::from(BPS_PER_UNIT)).unwrap_or(u64::MAX)
+    }
+
+    fn generate_order_id(&self) -> String {
+        let id = self.order_counter.fetch_add(1, Ordering::SeqCst);
+        format!("SIM_{id}")
+    }
+
+    fn submit(
+        &self,
+        symbol: Symbol,
+        shares: Shares,
+        direction: Direction,
+        limit_price_cents: Option<u64>,
+    ) -> OrderPlacement<String> {
+        let order_id = self.generate_order_id();
+
+        self.orders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                order_id.clone(),
+                SimOrder {
+                    symbol: symbol.clone(),
+                    shares,
+                    direction,
+                    limit_price_cents,
+                    polls: 0,
+                    state: OrderState::Submitted {
+                        order_id: order_id.clone(),
+                    },
+                },
+            );
+
+        OrderPlacement {
+            order_id,
+            broker: SupportedBroker::Sim,
+            symbol,
+            shares,
+            direction,
+            placed_at: chrono::Utc::now(),
+            request_payload: None,
+        }
+    }
+
+    /// Counts one status poll of `order` and fills it once it has been
+    /// SUBMITTED for the configured number of polls and the fill probability
+    /// allows it. Limit orders only fill when the slipped price is within
+    /// their limit.
+    fn advance(&self, order_id: &str, order: &mut SimOrder) {
+        if !matches!(order.state, OrderState::Submitted { .. }) {
+            return;
+        }
+
+        order.polls = order.polls.saturating_add(1);
+        if order.polls <= self.config.sim_fill_delay_polls {
+            return;
+        }
+
+        let fills = self
+            .rng
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .gen_bool(self.config.sim_fill_probability);
+        if !fills {
+            return;
+        }
+
+        let price_cents = self.slipped_price_cents(&order.symbol, order.direction);
+        let within_limit = order
+            .limit_price_cents
+            .is_none_or(|limit| match order.direction {
+                Direction::Buy => price_cents <= limit,
+                Direction::Sell => price_cents >= limit,
+            });
+        if !within_limit {
+            return;
+        }
+
+        info!(
+            "[SIM] Filled order {order_id}: {} {} shares of {} at {price_cents} cents after {} polls",
+            order.direction, order.shares, order.symbol, order.polls
+        );
+
+        order.state = OrderState::Filled {
+            executed_at: chrono::Utc::now(),
+            order_id: order_id.to_string(),
+            price_cents,
+            reported_price: Some(format!("{}.{:02}", price_cents / 100, price_cents % 100)),
+        };
+    }
+}
+
+#[async_trait]
+impl Broker for SimBroker {
+    type Error = BrokerError;
+    type OrderId = String;
+    type Config = SimBrokerConfig;
+
+    async fn try_from_config(config: Self::Config) -> Result<Self, Self::Error> {
+        warn!(
+            "[SIM] Initializing simulated broker: fills after {} polls with probability {} and {} bps slippage",
+            config.sim_fill_delay_polls, config.sim_fill_probability, config.sim_slippage_bps
+        );
+        Ok(Self::new(config))
+    }
+
+    async fn wait_until_market_open(&self) -> Result<std::time::Duration, Self::Error> {
+        // The simulated market never closes
+        Ok(std::time::Duration::MAX)
+    }
+
+    async fn time_until_market_close(&self) -> Result<Option<std::time::Duration>, Self::Error> {
+        Ok(Some(std::time::Duration::MAX))
+    }
+
+    async fn place_market_order(
+        &self,
+        order: MarketOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        let placement = self.submit(order.symbol, order.shares, order.direction, None);
+
+        warn!(
+            "[SIM] Submitted order: {} {} shares of {} (order_id: {})",
+            placement.direction, placement.shares, placement.symbol, placement.order_id
+        );
+
+        Ok(placement)
+    }
+
+    fn supports_fractional_shares(&self) -> bool {
+        false
+    }
+
+    async fn place_fractional_market_order(
+        &self,
+        order: FractionalMarketOrder,
+    ) -> Result<FractionalOrderPlacement<Self::OrderId>, Self::Error> {
+        Err(BrokerError::InvalidOrder {
+            reason: format!(
+                "Sim broker does not support fractional orders: {} {} shares of {}",
+                order.direction, order.quantity, order.symbol
+            ),
+        })
+    }
+
+    async fn place_limit_order(
+        &self,
+        order: LimitOrder,
+    ) -> Result<OrderPlacement<Self::OrderId>, Self::Error> {
+        let limit_price_cents = order.limit_price_cents;
+        let placement = self.submit(
+            order.symbol,
+            order.shares,
+            order.direction,
+            Some(limit_price_cents),
+        );
+
+        warn!(
+            "[SIM] Submitted limit order: {} {} shares of {} at {limit_price_cents} cents (order_id: {})",
+            placement.direction, placement.shares, placement.symbol, placement.order_id
+        );
+
+        Ok(placement)
+    }
+
+    async fn preview_market_order(&self, order: &MarketOrder) -> Result<(), Self::Error> {
+        warn!(
+            "[SIM] Would accept order: {} {} shares of {}",
+            order.direction, order.shares, order.symbol
+        );
+
+        Ok(())
+    }
+
+    async fn get_order_status(&self, order_id: &Self::OrderId) -> Result<OrderState, Self::Error> {
+        let mut orders = self.orders.lock().unwrap_or_else(PoisonError::into_inner);
+        let order = orders
+            .get_mut(order_id)
+            .ok_or_else(|| BrokerError::OrderNotFound {
+                order_id: order_id.clone(),
+            })?;
+
+        self.advance(order_id, order);
+        let state = order.state.clone();
+        drop(orders);
+
+        Ok(state)
+    }
+
+    async fn cancel_order(&self, order_id: &Self::OrderId) -> Result<(), Self::Error> {
+        let mut orders = self.orders.lock().unwrap_or_else(PoisonError::into_inner);
+        let order = orders
+            .get_mut(order_id)
+            .ok_or_else(|| BrokerError::OrderNotFound {
+                order_id: order_id.clone(),
+            })?;
+
+        if !matches!(order.state, OrderState::Submitted { .. }) {
+            return Err(BrokerError::InvalidOrder {
+                reason: format!(
+                    "Order {order_id} is {} and cannot be cancelled",
+                    order.state.status()
+                ),
+            });
+        }
+
+        order.state = OrderState::Cancelled {
+            cancelled_at: chrono::Utc::now(),
+            order_id: order_id.clone(),
+        };
+        drop(orders);
+
+        Ok(())
+    }
+
+    async fn poll_pending_orders(&self) -> Result<Vec<OrderUpdate<Self::OrderId>>, Self::Error> {
+        let mut orders = self.orders.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut updates = Vec::new();
+
+        for (order_id, order) in orders.iter_mut() {
+            if !matches!(order.state, OrderState::Submitted { .. }) {
+                continue;
+            }
+
+            self.advance(order_id, order);
+
+            let (price_cents, reported_price) = match &order.state {
+                OrderState::Filled {
+                    price_cents,
+                    reported_price,
+                    ..
+                } => (Some(*price_cents), reported_price.clone()),
+                _ => (None, None),
+            };
+
+            updates.push(OrderUpdate {
+                order_id: order_id.clone(),
+                symbol: order.symbol.clone(),
+                shares: order.shares,
+                direction: order.direction,
+                status: order.state.status(),
+                updated_at: chrono::Utc::now(),
+                price_cents,
+                reported_price,
+            });
+        }
+        drop(orders);
+
+        updates.sort_by(|a, b| a.order_id.cmp(&b.order_id));
+
+        Ok(updates)
+    }
+
+    async fn get_quote(&self, symbol: &Symbol) -> Result<Quote, Self::Error> {
+        Ok(Quote {
+            symbol: symbol.clone(),
+            bid_price_cents: self.slipped_price_cents(symbol, Direction::Sell),
+            ask_price_cents: self.slipped_price_cents(symbol, Direction::Buy),
+            quoted_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn get_adv(&self, _symbol: &Symbol) -> Result<u64, Self::Error> {
+        Ok(MOCK_AVERAGE_DAILY_VOLUME)
+    }
+
+    async fn get_positions(&self) -> Result<Vec<BrokerPosition>, Self::Error> {
+        let mut fills: Vec<_> = self
+            .orders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(|(order_id, order)| match &order.state {
+                OrderState::Filled {
+                    executed_at,
+                    price_cents,
+                    ..
+                } => Some((
+                    *executed_at,
+                    order_id.clone(),
+                    Fill {
+                        symbol: order.symbol.clone(),
+                        direction: order.direction,
+                        shares: u64::from(order.shares.value()),
+                        price_cents: *price_cents,
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+        fills.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+        Ok(positions_from_fills(
+            fills.into_iter().map(|(_, _, fill)| fill),
+        ))
+    }
+
+    fn to_supported_broker(&self) -> SupportedBroker {
+        SupportedBroker::Sim
+    }
+
+    fn parse_order_id(&self, order_id_str: &str) -> Result<Self::OrderId, Self::Error> {
+        Ok(order_id_str.to_string())
+    }
+
+    async fn run_broker_maintenance(&self) -> Option<JoinHandle<()>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderStatus;
+
+    fn config(fill_delay_polls: u32, fill_probability: f64, slippage_bps: u32) -> SimBrokerConfig {
+        SimBrokerConfig {
+            sim_fill_delay_polls: fill_delay_polls,
+            sim_fill_probability: fill_probability,
+            sim_slippage_bps: slippage_bps,
+            sim_reference_price_cents: 10_000,
+            sim_seed: Some(42),
+        }
+    }
+
+    fn market_order(symbol: &str, shares: u64, direction: Direction) -> MarketOrder {
+        MarketOrder {
+            symbol: Symbol::new(symbol).unwrap(),
+            shares: Shares::new(shares).unwrap(),
+            direction,
+            client_order_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_stays_submitted_for_delay_then_fills_with_slippage() {
+        let broker = SimBroker::new(config(3, 1.0, 10));
+        broker.set_reference_price(Symbol::new("AAPL").unwrap(), 15_000);
+
+        let placement = broker
+            .place_market_order(market_order("AAPL", 10, Direction::Buy))
+            .await
+            .unwrap();
+        assert_eq!(placement.order_id, "SIM_1");
+
+        for _ in 0..3 {
+            let updates = broker.poll_pending_orders().await.unwrap();
+            assert_eq!(updates.len(), 1);
+            assert_eq!(updates[0].status, OrderStatus::Submitted);
+            assert_eq!(updates[0].price_cents, None);
+        }
+
+        let updates = broker.poll_pending_orders().await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].order_id, "SIM_1");
+        assert_eq!(updates[0].status, OrderStatus::Filled);
+        // $150.00 moved up 10 bps for a buy
+        assert_eq!(updates[0].price_cents, Some(15_015));
+        assert_eq!(updates[0].reported_price.as_deref(), Some("150.15"));
+
+        // Filled orders are no longer pending but keep reporting their fill
+        assert!(broker.poll_pending_orders().await.unwrap().is_empty());
+        let state = broker.get_order_status(&placement.order_id).await.unwrap();
+        assert!(matches!(
+            state,
+            OrderState::Filled {
+                price_cents: 15_015,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_order_status_counts_as_poll_and_sell_slips_down() {
+        let broker = SimBroker::new(config(1, 1.0, 25));
+
+        let placement = broker
+            .place_market_order(market_order("MSFT", 5, Direction::Sell))
+            .await
+            .unwrap();
+
+        let state = broker.get_order_status(&placement.order_id).await.unwrap();
+        assert!(matches!(state, OrderState::Submitted { .. }));
+
+        let state = broker.get_order_status(&placement.order_id).await.unwrap();
+        assert!(matches!(
+            state,
+            OrderState::Filled {
+                price_cents: 9_975,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_zero_fill_probability_never_fills() {
+        let broker = SimBroker::new(config(0, 0.0, 0));
+        let placement = broker
+            .place_market_order(market_order("AAPL", 1, Direction::Buy))
+            .await
+            .unwrap();
+
+        for _ in 0..20 {
+            let updates = broker.poll_pending_orders().await.unwrap();
+            assert_eq!(updates[0].status, OrderStatus::Submitted);
+        }
+
+        broker.cancel_order(&placement.order_id).await.unwrap();
+        let state = broker.get_order_status(&placement.order_id).await.unwrap();
+        assert!(matches!(state, OrderState::Cancelled { .. }));
+        assert!(broker.poll_pending_orders().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_limit_order_waits_until_slipped_price_is_within_limit() {
+        let broker = SimBroker::new(config(0, 1.0, 10));
+        let symbol = Symbol::new("AAPL").unwrap();
+
+        let placement = broker
+            .place_limit_order(LimitOrder {
+                symbol: symbol.clone(),
+                shares: Shares::new(1).unwrap(),
+                direction: Direction::Buy,
+                limit_price_cents: 10_000,
+            })
+            .await
+            .unwrap();
+
+        // $100.00 plus 10 bps is above the limit
+        let state = broker.get_order_status(&placement.order_id).await.unwrap();
+        assert!(matches!(state, OrderState::Submitted { .. }));
+
+        broker.set_reference_price(symbol, 9_980);
+        let state = broker.get_order_status(&placement.order_id).await.unwrap();
+        assert!(matches!(
+            state,
+            OrderState::Filled {
+                price_cents: 9_990,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_filled_order_fails() {
+        let broker = SimBroker::new(config(0, 1.0, 0));
+        let placement = broker
+            .place_market_order(market_order("AAPL", 1, Direction::Buy))
+            .await
+            .unwrap();
+        broker.get_order_status(&placement.order_id).await.unwrap();
+
+        assert!(matches!(
+            broker.cancel_order(&placement.order_id).await.unwrap_err(),
+            BrokerError::InvalidOrder { .. }
+        ));
+        assert!(matches!(
+            broker
+                .get_order_status(&"SIM_99".to_string())
+                .await
+                .unwrap_err(),
+            BrokerError::OrderNotFound { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_quote_brackets_reference_price_by_slippage() {
+        let broker = SimBroker::new(config(0, 1.0, 50));
+        let quote = broker
+            .get_quote(&Symbol::new("AAPL").unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(quote.bid_price_cents, 9_950);
+        assert_eq!(quote.ask_price_cents, 10_050);
+        assert_eq!(broker.to_supported_broker(), SupportedBroker::Sim);
+    }
+
+    #[test]
+    fn test_config_from_env_defaults_and_validation() {
+        let config = SimBrokerConfig::try_parse_from(["test"]).unwrap();
+        assert_eq!(config.sim_fill_delay_polls, 3);
+        assert!((config.sim_fill_probability - 1.0).abs() < f64::EPSILON);
+        assert_eq!(config.sim_slippage_bps, 5);
+        assert_eq!(config.sim_reference_price_cents, 10_000);
+
+        assert!(
+            SimBrokerConfig::try_parse_from(["test", "--sim-fill-probability", "1.5"]).is_err()
+        );
+        assert!(SimBrokerConfig::try_parse_from(["test", "--sim-slippage-bps", "10000"]).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+-- Audit trail for the configurable price source chain
+-- One row per source consulted when pricing the onchain leg of a trade
+CREATE TABLE onchain_trade_price_sources (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  tx_hash TEXT NOT NULL CHECK (length(tx_hash) = 66 AND tx_hash LIKE '0x%'),
+  log_index INTEGER NOT NULL CHECK (log_index >= 0),
+  priority INTEGER NOT NULL CHECK (priority >= 0),  -- Position of the source in the configured chain
+  source TEXT NOT NULL CHECK (source IN ('pyth', 'broker_quote', 'onchain_ratio')),
+  price REAL,  -- Price returned by the source, NULL when unavailable
+  outcome TEXT NOT NULL CHECK (outcome IN ('SELECTED', 'UNAVAILABLE', 'INVALID', 'NOT_CONSULTED')),
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (tx_hash, log_index, source)
+);
+
+CREATE INDEX idx_onchain_trade_price_sources_trade ON onchain_trade_price_sources(tx_hash, log_index);
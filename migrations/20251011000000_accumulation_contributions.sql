@@ -0,0 +1,15 @@
+-- Audit trail of the accumulator math behind each offchain execution
+-- One row per onchain trade that still had unallocated shares in the
+-- executed bucket when the execution fired. Across an execution,
+-- SUM(available_shares) = executed shares + remainder carried forward.
+CREATE TABLE accumulation_contributions (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  execution_id INTEGER NOT NULL REFERENCES offchain_trades(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  trade_id INTEGER NOT NULL REFERENCES onchain_trades(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  available_shares REAL NOT NULL CHECK (available_shares > 0.0),  -- Unallocated fractional shares of the trade before this execution
+  allocated_shares REAL NOT NULL CHECK (allocated_shares >= 0.0 AND allocated_shares <= available_shares),  -- Portion consumed by this execution
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (execution_id, trade_id)
+);
+
+CREATE INDEX idx_accumulation_contributions_execution ON accumulation_contributions(execution_id);
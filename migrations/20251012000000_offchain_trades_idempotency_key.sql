@@ -0,0 +1,8 @@
+-- Idempotency key (symbol + direction + shares + time bucket) stamped on
+-- executions created by the accumulator. The unique index rejects a second
+-- identical execution within the same dedup window.
+ALTER TABLE offchain_trades ADD COLUMN idempotency_key TEXT CHECK (idempotency_key IS NULL OR idempotency_key != '');
+
+CREATE UNIQUE INDEX idx_offchain_trades_idempotency_key
+  ON offchain_trades(idempotency_key)
+  WHERE idempotency_key IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Last market hours fetched from Schwab, persisted with the fetch time so the
+-- minimum interval between market-hours API calls holds across restarts.
+CREATE TABLE schwab_market_hours_cache (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  market_date TEXT NOT NULL,
+  session_type TEXT CHECK (session_type IN ('PRE_MARKET', 'REGULAR', 'AFTER_HOURS')) NOT NULL,
+  is_open BOOLEAN NOT NULL,
+  start_at DATETIME,
+  end_at DATETIME,
+  fetched_at DATETIME NOT NULL
+);
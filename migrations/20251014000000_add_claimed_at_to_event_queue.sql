@@ -0,0 +1,3 @@
+-- Set when the queue processor picks up an event and cleared if processing
+-- fails; a claim left behind by a crash is released on the next startup
+ALTER TABLE event_queue ADD COLUMN claimed_at TIMESTAMP;
@@ -0,0 +1,3 @@
+-- Fill price exactly as reported by the broker, kept alongside the rounded
+-- price_cents for auditing
+ALTER TABLE offchain_trades ADD COLUMN reported_price TEXT;
@@ -0,0 +1,13 @@
+-- Onchain trades that were not hedged because they need a human to look at
+-- them, e.g. a token whose symbol() call reverted and so cannot be mapped to
+-- a broker ticker
+CREATE TABLE trades_for_review (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  tx_hash TEXT NOT NULL CHECK (length(tx_hash) = 66 AND tx_hash LIKE '0x%'),
+  log_index INTEGER NOT NULL CHECK (log_index >= 0),
+  token_address TEXT NOT NULL,
+  fallback_symbol TEXT NOT NULL CHECK (fallback_symbol != ''),
+  reason TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (tx_hash, log_index)
+);
@@ -0,0 +1,7 @@
+-- Highest block number observed on the live event stream, used on restart to
+-- resync only the blocks missed while the bot was down
+CREATE TABLE last_seen_block (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  block_number INTEGER NOT NULL CHECK (block_number >= 0),
+  updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Serialization format of event_data. Rows queued before versioning was
+-- introduced use the original TradeEvent JSON, which is format 1
+ALTER TABLE event_queue ADD COLUMN format_version INTEGER NOT NULL DEFAULT 1;
+
+-- Set when a queued event cannot be decoded (e.g. an unknown format version)
+-- so it is set aside for inspection instead of blocking the queue
+ALTER TABLE event_queue ADD COLUMN dead_lettered_at TIMESTAMP;
+ALTER TABLE event_queue ADD COLUMN dead_letter_reason TEXT;
@@ -0,0 +1,10 @@
+-- Monotonic nonce assigned to each execution and included in the client order
+-- id sent to the broker, so identical executions never share an order id.
+-- Existing executions take their row id, which keeps later nonces increasing
+ALTER TABLE offchain_trades ADD COLUMN nonce INTEGER;
+
+UPDATE offchain_trades SET nonce = id;
+
+CREATE UNIQUE INDEX idx_offchain_trades_nonce
+ON offchain_trades(nonce)
+WHERE nonce IS NOT NULL;
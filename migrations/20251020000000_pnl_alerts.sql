@@ -0,0 +1,11 @@
+-- P&L thresholds currently crossed and already alerted on, per symbol (or '*'
+-- for the total across symbols). A row is removed once the P&L moves back
+-- inside the threshold, so a later crossing alerts again
+CREATE TABLE pnl_alerts (
+  scope TEXT NOT NULL CHECK (scope != ''),
+  kind TEXT NOT NULL CHECK (kind IN ('LOSS', 'PROFIT')),
+  threshold REAL NOT NULL,
+  cumulative_pnl REAL NOT NULL,
+  alerted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (scope, kind)
+);
@@ -0,0 +1,5 @@
+-- Asset class (or sector) of the row's symbol, from the reporter's
+-- --asset-class mapping at insert time; NULL for untagged symbols
+ALTER TABLE metrics_pnl ADD COLUMN asset_class TEXT;
+
+CREATE INDEX idx_metrics_pnl_asset_class ON metrics_pnl(asset_class);
@@ -0,0 +1,5 @@
+-- Block of the latest onchain trade that contributed to the execution,
+-- denormalized from event_queue so the triggering block can be read without
+-- joining through trade_execution_links. NULL for executions created before
+-- this column existed or whose trades have no queued event
+ALTER TABLE offchain_trades ADD COLUMN block_number INTEGER CHECK (block_number >= 0);
@@ -0,0 +1,14 @@
+-- Terminal conversion outcome of each queued event, for analysing why events
+-- do not produce executions. An event that errors and is later retried keeps
+-- only its latest outcome
+CREATE TABLE conversion_outcomes (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  tx_hash TEXT NOT NULL CHECK (length(tx_hash) = 66 AND tx_hash LIKE '0x%'),
+  log_index INTEGER NOT NULL CHECK (log_index >= 0),
+  outcome TEXT NOT NULL CHECK (outcome IN ('converted', 'filtered', 'error')),
+  reason TEXT,
+  at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (tx_hash, log_index)
+);
+
+CREATE INDEX idx_conversion_outcomes_outcome ON conversion_outcomes(outcome);
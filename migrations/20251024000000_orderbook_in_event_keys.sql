@@ -0,0 +1,123 @@
+-- Make the orderbook that emitted an event part of the uniqueness key of
+-- queued events and onchain trades, so identical (tx_hash, log_index) pairs
+-- from different orderbooks are both kept. Changing a UNIQUE constraint needs
+-- a table rebuild. Foreign keys cannot be disabled inside the migration's
+-- transaction and dropping onchain_trades would cascade into the tables
+-- referencing it, so those are rebuilt against the new table first, as in
+-- the broker abstraction migration.
+--
+-- Existing rows predate the column and get a NULL orderbook; the bot assigns
+-- them the configured orderbook on startup.
+
+CREATE TABLE event_queue_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  tx_hash TEXT NOT NULL CHECK (length(tx_hash) = 66 AND tx_hash LIKE '0x%'),
+  log_index INTEGER NOT NULL CHECK (log_index >= 0),
+  orderbook TEXT CHECK (orderbook IS NULL OR orderbook != ''),
+  block_number INTEGER NOT NULL CHECK (block_number >= 0),
+  event_data TEXT NOT NULL,
+  processed BOOLEAN NOT NULL DEFAULT 0,
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  processed_at TIMESTAMP,
+  block_timestamp TIMESTAMP,
+  claimed_at TIMESTAMP,
+  format_version INTEGER NOT NULL DEFAULT 1,
+  dead_lettered_at TIMESTAMP,
+  dead_letter_reason TEXT,
+  UNIQUE (tx_hash, log_index, orderbook),
+  CHECK (event_data != '')
+);
+
+INSERT INTO event_queue_new (
+  id, tx_hash, log_index, block_number, event_data, processed, created_at,
+  processed_at, block_timestamp, claimed_at, format_version, dead_lettered_at,
+  dead_letter_reason
+)
+SELECT
+  id, tx_hash, log_index, block_number, event_data, processed, created_at,
+  processed_at, block_timestamp, claimed_at, format_version, dead_lettered_at,
+  dead_letter_reason
+FROM event_queue;
+
+DROP TABLE event_queue;
+ALTER TABLE event_queue_new RENAME TO event_queue;
+
+CREATE INDEX idx_event_queue_processed ON event_queue(processed);
+CREATE INDEX idx_event_queue_block_number ON event_queue(block_number);
+CREATE INDEX idx_event_queue_created_at ON event_queue(created_at);
+
+CREATE TABLE onchain_trades_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  tx_hash TEXT NOT NULL CHECK (length(tx_hash) = 66 AND tx_hash LIKE '0x%'),
+  log_index INTEGER NOT NULL CHECK (log_index >= 0),
+  orderbook TEXT CHECK (orderbook IS NULL OR orderbook != ''),
+  symbol TEXT NOT NULL CHECK (symbol != ''),
+  amount REAL NOT NULL CHECK (amount > 0.0),
+  direction TEXT CHECK (direction IN ('BUY', 'SELL')) NOT NULL,
+  price_usdc REAL NOT NULL CHECK (price_usdc > 0.0),
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  block_timestamp TIMESTAMP,
+  gas_used INTEGER
+    CHECK (gas_used IS NULL OR (gas_used >= 0 AND gas_used <= 9223372036854775807)),
+  effective_gas_price INTEGER
+    CHECK (effective_gas_price IS NULL OR (effective_gas_price >= 0 AND effective_gas_price <= 9223372036854775807)),
+  pyth_price REAL,
+  pyth_confidence REAL CHECK (pyth_confidence IS NULL OR pyth_confidence >= 0),
+  pyth_exponent INTEGER,
+  pyth_publish_time TIMESTAMP,
+  UNIQUE (tx_hash, log_index, orderbook)
+);
+
+INSERT INTO onchain_trades_new (
+  id, tx_hash, log_index, symbol, amount, direction, price_usdc, created_at,
+  block_timestamp, gas_used, effective_gas_price, pyth_price, pyth_confidence,
+  pyth_exponent, pyth_publish_time
+)
+SELECT
+  id, tx_hash, log_index, symbol, amount, direction, price_usdc, created_at,
+  block_timestamp, gas_used, effective_gas_price, pyth_price, pyth_confidence,
+  pyth_exponent, pyth_publish_time
+FROM onchain_trades;
+
+-- Point the tables referencing onchain_trades at the new table
+CREATE TABLE trade_execution_links_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  trade_id INTEGER NOT NULL REFERENCES onchain_trades_new(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  execution_id INTEGER NOT NULL REFERENCES offchain_trades(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  contributed_shares REAL NOT NULL CHECK (contributed_shares > 0.0),  -- Fractional shares from this trade that contributed to this execution
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (trade_id, execution_id)  -- Prevent duplicate linkages between same trade/execution pair
+);
+
+INSERT INTO trade_execution_links_new SELECT * FROM trade_execution_links;
+
+DROP TABLE trade_execution_links;
+ALTER TABLE trade_execution_links_new RENAME TO trade_execution_links;
+
+CREATE INDEX idx_trade_execution_links_trade_id ON trade_execution_links(trade_id);
+CREATE INDEX idx_trade_execution_links_execution_id ON trade_execution_links(execution_id);
+CREATE INDEX idx_trade_execution_links_trade_exec ON trade_execution_links(trade_id, execution_id);
+
+CREATE TABLE accumulation_contributions_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  execution_id INTEGER NOT NULL REFERENCES offchain_trades(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  trade_id INTEGER NOT NULL REFERENCES onchain_trades_new(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  available_shares REAL NOT NULL CHECK (available_shares > 0.0),  -- Unallocated fractional shares of the trade before this execution
+  allocated_shares REAL NOT NULL CHECK (allocated_shares >= 0.0 AND allocated_shares <= available_shares),  -- Portion consumed by this execution
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (execution_id, trade_id)
+);
+
+INSERT INTO accumulation_contributions_new SELECT * FROM accumulation_contributions;
+
+DROP TABLE accumulation_contributions;
+ALTER TABLE accumulation_contributions_new RENAME TO accumulation_contributions;
+
+CREATE INDEX idx_accumulation_contributions_execution ON accumulation_contributions(execution_id);
+
+-- Nothing references the old table any more, so dropping it cascades nowhere,
+-- and renaming the new one updates the references above
+DROP TABLE onchain_trades;
+ALTER TABLE onchain_trades_new RENAME TO onchain_trades;
+
+CREATE INDEX idx_onchain_trades_symbol ON onchain_trades(symbol);
@@ -0,0 +1,5 @@
+-- Completed scheduled VACUUMs, for spacing them by the configured interval
+CREATE TABLE vacuum_runs (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  completed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
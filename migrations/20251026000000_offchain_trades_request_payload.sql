@@ -0,0 +1,4 @@
+-- JSON body of the order request exactly as sent to the broker, kept as a
+-- record of what was requested for disputes. NULL unless payload capture is
+-- enabled for the broker
+ALTER TABLE offchain_trades ADD COLUMN request_payload TEXT CHECK (request_payload != '');
@@ -0,0 +1,8 @@
+-- Kind of order submitted for the execution, so order status polling can tell
+-- market orders from limit orders that may rest unfilled. Existing executions
+-- were all market orders. Limit orders, and only they, carry a limit price
+ALTER TABLE offchain_trades ADD COLUMN order_type TEXT NOT NULL DEFAULT 'MARKET'
+  CHECK (order_type IN ('MARKET', 'LIMIT'));
+ALTER TABLE offchain_trades ADD COLUMN limit_price_cents INTEGER
+  CHECK (limit_price_cents IS NULL OR limit_price_cents > 0)
+  CHECK ((order_type = 'LIMIT') = (limit_price_cents IS NOT NULL));
@@ -0,0 +1,131 @@
+-- Allow executions to be CANCELLED: pulled back from the broker before they
+-- filled, keeping the broker order id and the cancellation time.
+--
+-- SQLite cannot alter a CHECK constraint, so the table is rebuilt. Foreign
+-- keys cannot be disabled inside the migration's transaction and dropping
+-- offchain_trades would cascade into the tables referencing it, so those are
+-- rebuilt against the new table first, as in the broker abstraction
+-- migration.
+
+CREATE TABLE offchain_trades_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  symbol TEXT NOT NULL CHECK (symbol != ''),
+  shares INTEGER NOT NULL CHECK (shares > 0),
+  direction TEXT CHECK (direction IN ('BUY', 'SELL')) NOT NULL,
+  broker TEXT NOT NULL DEFAULT 'schwab' CHECK (broker != ''),
+  broker_order_id TEXT CHECK (broker_order_id IS NULL OR broker_order_id != ''),
+  order_id TEXT CHECK (order_id IS NULL OR order_id != ''),
+  price_cents INTEGER CHECK (price_cents IS NULL OR price_cents >= 0),
+  status TEXT CHECK (status IN ('PENDING', 'SUBMITTED', 'FILLED', 'FAILED', 'CANCELLED')) NOT NULL DEFAULT 'PENDING',
+  executed_at TIMESTAMP,
+  idempotency_key TEXT CHECK (idempotency_key IS NULL OR idempotency_key != ''),
+  reported_price TEXT,
+  nonce INTEGER,
+  block_number INTEGER CHECK (block_number >= 0),
+  request_payload TEXT CHECK (request_payload != ''),
+  order_type TEXT NOT NULL DEFAULT 'MARKET' CHECK (order_type IN ('MARKET', 'LIMIT')),
+  limit_price_cents INTEGER CHECK (limit_price_cents IS NULL OR limit_price_cents > 0),
+  CHECK ((order_type = 'LIMIT') = (limit_price_cents IS NOT NULL)),
+  CHECK (
+    (status = 'PENDING' AND executed_at IS NULL) OR
+    (status = 'SUBMITTED' AND (broker_order_id IS NOT NULL OR order_id IS NOT NULL) AND executed_at IS NULL) OR
+    (status = 'FILLED' AND (broker_order_id IS NOT NULL OR order_id IS NOT NULL) AND executed_at IS NOT NULL AND price_cents IS NOT NULL) OR
+    (status = 'FAILED' AND executed_at IS NOT NULL) OR
+    (status = 'CANCELLED' AND (broker_order_id IS NOT NULL OR order_id IS NOT NULL) AND executed_at IS NOT NULL)
+  )
+);
+
+INSERT INTO offchain_trades_new (
+  id, symbol, shares, direction, broker, broker_order_id, order_id, price_cents,
+  status, executed_at, idempotency_key, reported_price, nonce, block_number,
+  request_payload, order_type, limit_price_cents
+)
+SELECT
+  id, symbol, shares, direction, broker, broker_order_id, order_id, price_cents,
+  status, executed_at, idempotency_key, reported_price, nonce, block_number,
+  request_payload, order_type, limit_price_cents
+FROM offchain_trades;
+
+-- Point the tables referencing offchain_trades at the new table
+CREATE TABLE trade_accumulators_new (
+  symbol TEXT PRIMARY KEY NOT NULL,
+  net_position REAL NOT NULL DEFAULT 0.0,  -- Running position for threshold checking
+  accumulated_long REAL NOT NULL DEFAULT 0.0 CHECK (accumulated_long >= 0.0),  -- Fractional shares accumulated for buying
+  accumulated_short REAL NOT NULL DEFAULT 0.0 CHECK (accumulated_short >= 0.0),  -- Fractional shares accumulated for selling
+  pending_execution_id INTEGER REFERENCES offchain_trades_new(id) ON DELETE SET NULL ON UPDATE CASCADE,  -- Current pending execution if any
+  last_updated TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  CHECK (symbol != '')  -- Ensure symbol is not empty
+);
+
+INSERT INTO trade_accumulators_new SELECT * FROM trade_accumulators;
+
+DROP TABLE trade_accumulators;
+ALTER TABLE trade_accumulators_new RENAME TO trade_accumulators;
+
+CREATE UNIQUE INDEX idx_trade_accumulators_pending_execution
+ON trade_accumulators(pending_execution_id)
+WHERE pending_execution_id IS NOT NULL;
+
+CREATE TRIGGER update_trade_accumulators_last_updated
+AFTER UPDATE ON trade_accumulators
+FOR EACH ROW
+WHEN OLD.last_updated = NEW.last_updated
+BEGIN
+  UPDATE trade_accumulators SET last_updated = CURRENT_TIMESTAMP WHERE rowid = NEW.rowid;
+END;
+
+CREATE TABLE trade_execution_links_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  trade_id INTEGER NOT NULL REFERENCES onchain_trades(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  execution_id INTEGER NOT NULL REFERENCES offchain_trades_new(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  contributed_shares REAL NOT NULL CHECK (contributed_shares > 0.0),  -- Fractional shares from this trade that contributed to this execution
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (trade_id, execution_id)  -- Prevent duplicate linkages between same trade/execution pair
+);
+
+INSERT INTO trade_execution_links_new SELECT * FROM trade_execution_links;
+
+DROP TABLE trade_execution_links;
+ALTER TABLE trade_execution_links_new RENAME TO trade_execution_links;
+
+CREATE INDEX idx_trade_execution_links_trade_id ON trade_execution_links(trade_id);
+CREATE INDEX idx_trade_execution_links_execution_id ON trade_execution_links(execution_id);
+CREATE INDEX idx_trade_execution_links_trade_exec ON trade_execution_links(trade_id, execution_id);
+
+CREATE TABLE accumulation_contributions_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  execution_id INTEGER NOT NULL REFERENCES offchain_trades_new(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  trade_id INTEGER NOT NULL REFERENCES onchain_trades(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  available_shares REAL NOT NULL CHECK (available_shares > 0.0),  -- Unallocated fractional shares of the trade before this execution
+  allocated_shares REAL NOT NULL CHECK (allocated_shares >= 0.0 AND allocated_shares <= available_shares),  -- Portion consumed by this execution
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  UNIQUE (execution_id, trade_id)
+);
+
+INSERT INTO accumulation_contributions_new SELECT * FROM accumulation_contributions;
+
+DROP TABLE accumulation_contributions;
+ALTER TABLE accumulation_contributions_new RENAME TO accumulation_contributions;
+
+CREATE INDEX idx_accumulation_contributions_execution ON accumulation_contributions(execution_id);
+
+-- Nothing references the old table any more, so dropping it cascades nowhere,
+-- and renaming the new one updates the references above
+DROP TABLE offchain_trades;
+ALTER TABLE offchain_trades_new RENAME TO offchain_trades;
+
+CREATE INDEX idx_offchain_trades_symbol ON offchain_trades(symbol);
+CREATE INDEX idx_offchain_trades_status ON offchain_trades(status);
+CREATE INDEX idx_offchain_trades_broker ON offchain_trades(broker);
+
+CREATE UNIQUE INDEX idx_unique_in_progress_execution_per_symbol
+ON offchain_trades(symbol)
+WHERE status IN ('PENDING', 'SUBMITTED');
+
+CREATE UNIQUE INDEX idx_offchain_trades_idempotency_key
+ON offchain_trades(idempotency_key)
+WHERE idempotency_key IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_offchain_trades_nonce
+ON offchain_trades(nonce)
+WHERE nonce IS NOT NULL;
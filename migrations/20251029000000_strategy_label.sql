@@ -0,0 +1,11 @@
+-- Strategy (or desk) the bot instance that recorded the row was configured
+-- for via STRATEGY_LABEL; NULL when unlabelled
+ALTER TABLE onchain_trades ADD COLUMN strategy_label TEXT;
+
+-- Inherited from the latest onchain trade linked to the execution
+ALTER TABLE offchain_trades ADD COLUMN strategy_label TEXT;
+
+-- Copied from the trade the P&L row was computed for
+ALTER TABLE metrics_pnl ADD COLUMN strategy_label TEXT;
+
+CREATE INDEX idx_metrics_pnl_strategy_label ON metrics_pnl(strategy_label);
@@ -0,0 +1,7 @@
+-- Last block range end fully enqueued by the backfill for each orderbook, so a
+-- restart resumes after the last committed batch instead of the deployment block
+CREATE TABLE backfill_checkpoint (
+  orderbook TEXT PRIMARY KEY NOT NULL CHECK (orderbook != ''),
+  block_number INTEGER NOT NULL CHECK (block_number >= 0),
+  updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,49 @@
+-- Allow synthetic MARK rows valuing each open position at its current mark
+-- price. SQLite cannot alter a CHECK constraint, so the table is rebuilt.
+--
+-- A MARK row has no underlying trade: trade_id is 0, trade_direction is the
+-- side of the open position (BUY for long, SELL for short), quantity is the
+-- absolute position, price_per_share is the mark price and unrealized_pnl
+-- values the remaining FIFO lots at that price. Only the latest mark of each
+-- (strategy_label, symbol) position is kept.
+
+CREATE TABLE metrics_pnl_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  symbol TEXT NOT NULL CHECK (symbol != ''),
+  timestamp TIMESTAMP NOT NULL,
+  trade_type TEXT NOT NULL CHECK (trade_type IN ('ONCHAIN', 'OFFCHAIN', 'MARK')),
+  trade_id INTEGER NOT NULL,
+  trade_direction TEXT NOT NULL CHECK (trade_direction IN ('BUY', 'SELL')),
+  quantity REAL NOT NULL CHECK (quantity > 0),
+  price_per_share REAL NOT NULL CHECK (price_per_share > 0),
+  realized_pnl REAL,
+  cumulative_pnl REAL NOT NULL,
+  net_position_after REAL NOT NULL,
+  asset_class TEXT,
+  strategy_label TEXT,
+  unrealized_pnl REAL CHECK ((trade_type = 'MARK') = (unrealized_pnl IS NOT NULL))
+);
+
+INSERT INTO metrics_pnl_new (
+  id, symbol, timestamp, trade_type, trade_id, trade_direction, quantity,
+  price_per_share, realized_pnl, cumulative_pnl, net_position_after,
+  asset_class, strategy_label
+)
+SELECT
+  id, symbol, timestamp, trade_type, trade_id, trade_direction, quantity,
+  price_per_share, realized_pnl, cumulative_pnl, net_position_after,
+  asset_class, strategy_label
+FROM metrics_pnl;
+
+DROP TABLE metrics_pnl;
+ALTER TABLE metrics_pnl_new RENAME TO metrics_pnl;
+
+CREATE UNIQUE INDEX idx_metrics_pnl_trade
+  ON metrics_pnl(trade_type, trade_id) WHERE trade_type != 'MARK';
+CREATE UNIQUE INDEX idx_metrics_pnl_mark
+  ON metrics_pnl(symbol, IFNULL(strategy_label, '')) WHERE trade_type = 'MARK';
+CREATE INDEX idx_metrics_pnl_symbol_timestamp ON metrics_pnl(symbol, timestamp);
+CREATE INDEX idx_metrics_pnl_symbol ON metrics_pnl(symbol);
+CREATE INDEX idx_metrics_pnl_timestamp ON metrics_pnl(timestamp);
+CREATE INDEX idx_metrics_pnl_asset_class ON metrics_pnl(asset_class);
+CREATE INDEX idx_metrics_pnl_strategy_label ON metrics_pnl(strategy_label);
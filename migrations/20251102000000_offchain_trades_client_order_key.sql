@@ -0,0 +1,14 @@
+-- Client order key sent to the broker with each execution's order, so a
+-- retried placement can find the order already placed for the execution
+-- instead of placing a second one. Existing executions take the key derived
+-- from their nonce, matching the client order id they were placed with
+ALTER TABLE offchain_trades ADD COLUMN client_order_key TEXT
+  CHECK (client_order_key IS NULL OR client_order_key != '');
+
+UPDATE offchain_trades
+SET client_order_key = 'st0x-' || symbol || '-' || nonce
+WHERE nonce IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_offchain_trades_client_order_key
+ON offchain_trades(client_order_key)
+WHERE client_order_key IS NOT NULL;
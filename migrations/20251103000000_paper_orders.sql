@@ -0,0 +1,23 @@
+-- Orders the paper-trading broker recorded instead of placing, filled at the
+-- Pyth price prevailing when they were placed (or, for limit orders, first
+-- within their limit)
+CREATE TABLE paper_orders (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  symbol TEXT NOT NULL CHECK (symbol != ''),
+  shares INTEGER NOT NULL CHECK (shares > 0),
+  direction TEXT NOT NULL CHECK (direction IN ('BUY', 'SELL')),
+  client_order_id TEXT UNIQUE CHECK (client_order_id IS NULL OR client_order_id != ''),
+  limit_price_cents INTEGER CHECK (limit_price_cents IS NULL OR limit_price_cents > 0),
+  status TEXT NOT NULL CHECK (status IN ('SUBMITTED', 'FILLED', 'CANCELLED')),
+  price_cents INTEGER CHECK (price_cents IS NULL OR price_cents >= 0),
+  reported_price TEXT,
+  placed_at TIMESTAMP NOT NULL,
+  executed_at TIMESTAMP,
+  CHECK (
+    (status = 'SUBMITTED' AND price_cents IS NULL AND executed_at IS NULL)
+    OR (status = 'FILLED' AND price_cents IS NOT NULL AND executed_at IS NOT NULL)
+    OR (status = 'CANCELLED' AND price_cents IS NULL AND executed_at IS NOT NULL)
+  )
+);
+
+CREATE INDEX idx_paper_orders_status ON paper_orders(status);
@@ -0,0 +1,8 @@
+-- When the execution's order was placed with the broker, i.e. first stored as
+-- SUBMITTED, so the order poller can fail orders that stay SUBMITTED for too
+-- long. Orders already submitted start their clock now
+ALTER TABLE offchain_trades ADD COLUMN placed_at TIMESTAMP;
+
+UPDATE offchain_trades
+SET placed_at = CURRENT_TIMESTAMP
+WHERE status = 'SUBMITTED';
@@ -0,0 +1,5 @@
+-- Fractional shares of the trade that were dropped rather than accumulated,
+-- for symbols that do not accumulate fractions; NULL when none were. Dropped
+-- shares are never linked to an execution
+ALTER TABLE onchain_trades ADD COLUMN dropped_amount TEXT
+  CHECK (dropped_amount IS NULL OR CAST(dropped_amount AS REAL) > 0.0);
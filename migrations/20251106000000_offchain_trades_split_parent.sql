@@ -0,0 +1,12 @@
+-- Execution a child order was split from when a position above the per-order
+-- value limit is executed as several compliant orders at once. Only the first
+-- order of a split, which has no parent, counts towards the one in-progress
+-- execution allowed per symbol
+ALTER TABLE offchain_trades ADD COLUMN split_parent_id INTEGER
+  REFERENCES offchain_trades(id);
+
+DROP INDEX idx_unique_in_progress_execution_per_symbol;
+
+CREATE UNIQUE INDEX idx_unique_in_progress_execution_per_symbol
+ON offchain_trades(symbol)
+WHERE status IN ('PENDING', 'SUBMITTED') AND split_parent_id IS NULL;
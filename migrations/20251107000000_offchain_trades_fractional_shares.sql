@@ -0,0 +1,6 @@
+-- Exact quantity, as a decimal string, of an execution placed as a
+-- fractional-share order with a broker that supports them. NULL for
+-- whole-share orders. The execution's shares hold the quantity rounded up,
+-- the most it can move the net position by
+ALTER TABLE offchain_trades ADD COLUMN fractional_shares TEXT
+  CHECK (fractional_shares IS NULL OR CAST(fractional_shares AS REAL) > 0.0);
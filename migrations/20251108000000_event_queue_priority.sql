@@ -0,0 +1,10 @@
+-- Base symbol of the tokenized equity a queued event trades and the
+-- processing priority configured for it, resolved once per event so picking
+-- the next prioritized event is a single ordered query. NULL priority marks
+-- an event not ranked yet; symbol stays NULL when it cannot be resolved
+ALTER TABLE event_queue ADD COLUMN symbol TEXT;
+ALTER TABLE event_queue ADD COLUMN priority INTEGER;
+
+CREATE INDEX idx_event_queue_pending_priority
+  ON event_queue(priority DESC, block_number, log_index)
+  WHERE processed = 0 AND dead_lettered_at IS NULL;
@@ -0,0 +1,71 @@
+#[cfg(test)]
+use sqlx::SqlitePool;
+
+/// Snapshot of one onchain trade's share of the accumulated total at the
+/// moment an execution fired.
+///
+/// Unlike [`crate::trade_execution_link::TradeExecutionLink`], which only
+/// records the shares consumed, this also keeps the fractional amount that
+/// was available, so the remainder carried forward can be reconstructed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AccumulationContribution {
+    pub(crate) execution_id: i64,
+    pub(crate) trade_id: i64,
+    pub(crate) available_shares: f64,
+    pub(crate) allocated_shares: f64,
+}
+
+impl AccumulationContribution {
+    pub(crate) async fn save_within_transaction(
+        &self,
+        sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    ) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO accumulation_contributions (
+                execution_id,
+                trade_id,
+                available_shares,
+                allocated_shares
+            )
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            self.execution_id,
+            self.trade_id,
+            self.available_shares,
+            self.allocated_shares
+        )
+        .execute(&mut **sql_tx)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    #[cfg(test)]
+    pub(crate) async fn find_for_execution(
+        pool: &SqlitePool,
+        execution_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT execution_id, trade_id, available_shares, allocated_shares
+            FROM accumulation_contributions
+            WHERE execution_id = ?1
+            ORDER BY id ASC
+            "#,
+            execution_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                execution_id: row.execution_id,
+                trade_id: row.trade_id,
+                available_shares: row.available_shares,
+                allocated_shares: row.allocated_shares,
+            })
+            .collect())
+    }
+}
@@ -1,26 +1,428 @@
 use chrono::{DateTime, Utc};
+use rocket::http::{ContentType, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::stream::{Event, EventStream};
 use rocket::serde::json::Json;
 use rocket::serde::{Deserialize, Serialize};
-use rocket::{Route, State, get, post, routes};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::{Route, Shutdown, State, get, post, routes};
 use sqlx::SqlitePool;
-
-use crate::env::{BrokerConfig, Config};
-use st0x_broker::schwab::extract_code_from_url;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::conductor::DEX_STREAM_STALE_AFTER;
+use crate::env::{BrokerConfig, Config, ReadPool};
+use crate::error::OnChainError;
+use crate::metrics::Metrics;
+use crate::offchain::execution::{
+    ExecutionFilter, OffchainExecution, find_execution_by_id, find_execution_origin_block,
+    list_executions,
+};
+use crate::onchain::last_seen_block::get_processed_block;
+use crate::queue::count_unprocessed;
+use crate::reporter::asset_class::{AssetClassPnl, load_pnl_by_asset_class};
+use crate::reporter::summary::{PnlSummary, load_pnl_summary};
+use crate::rpc_metrics::{RpcMetrics, RpcMetricsSnapshot};
+use crate::stats::{DexStreamStatus, Stats, StatsSnapshot};
+use crate::trade_execution_link::{TradeContribution, TradeExecutionLink};
+use st0x_broker::schwab::{SchwabTokens, extract_code_from_url};
+use st0x_broker::{Direction, OrderStatus, Shares, Symbol};
 
 #[derive(Serialize, Deserialize)]
 struct HealthResponse {
     status: String,
     timestamp: DateTime<Utc>,
+    database_reachable: bool,
+    dex_streams: DexStreamStatus,
+    processed_block: Option<u64>,
+    queue_depth: Option<u64>,
+    /// Validity of the stored broker tokens, absent for brokers without any
+    broker_tokens: Option<BrokerTokenHealth>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct BrokerTokenHealth {
+    access_token_valid: bool,
+    refresh_token_valid: bool,
+}
+
+/// Health and readiness for the orchestrator. Responds 503 when the database
+/// cannot be reached or the DEX streams are disconnected or stale; expired
+/// broker tokens are reported but do not fail the check.
 #[get("/health")]
-fn health() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "healthy".to_string(),
-        timestamp: Utc::now(),
+async fn health(
+    pool: &State<ReadPool>,
+    stats: &State<Arc<Stats>>,
+    config: &State<Config>,
+) -> (Status, Json<HealthResponse>) {
+    let database_reachable = match sqlx::query("SELECT 1").execute(pool.pool()).await {
+        Ok(_) => true,
+        Err(e) => {
+            error!("Health check database ping failed: {e}");
+            false
+        }
+    };
+
+    let (processed_block, queue_depth) = if database_reachable {
+        let processed_block = load_processed_block(pool).await.ok().flatten();
+        let queue_depth = match count_unprocessed(pool.pool()).await {
+            Ok(count) => u64::try_from(count).ok(),
+            Err(e) => {
+                error!("Failed to count unprocessed events: {e}");
+                None
+            }
+        };
+        (processed_block, queue_depth)
+    } else {
+        (None, None)
+    };
+
+    let broker_tokens = if database_reachable {
+        load_broker_token_health(pool, &config.broker).await
+    } else {
+        None
+    };
+
+    let dex_streams = stats.dex_stream_status(DEX_STREAM_STALE_AFTER);
+    let healthy = database_reachable && dex_streams.connected && !dex_streams.stale;
+    let (status_code, label) = if healthy {
+        (Status::Ok, "healthy")
+    } else {
+        (Status::ServiceUnavailable, "unhealthy")
+    };
+
+    (
+        status_code,
+        Json(HealthResponse {
+            status: label.to_string(),
+            timestamp: Utc::now(),
+            database_reachable,
+            dex_streams,
+            processed_block,
+            queue_depth,
+            broker_tokens,
+        }),
+    )
+}
+
+async fn load_broker_token_health(
+    pool: &ReadPool,
+    broker: &BrokerConfig,
+) -> Option<BrokerTokenHealth> {
+    let BrokerConfig::Schwab(schwab_auth) = broker else {
+        return None;
+    };
+
+    match SchwabTokens::load(pool.pool(), &schwab_auth.encryption_key).await {
+        Ok(tokens) => Some(BrokerTokenHealth {
+            access_token_valid: !tokens.is_access_token_expired(),
+            refresh_token_valid: !tokens.is_refresh_token_expired(),
+        }),
+        Err(e) => {
+            warn!("Failed to load Schwab tokens for health check: {e}");
+            Some(BrokerTokenHealth {
+                access_token_valid: false,
+                refresh_token_valid: false,
+            })
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatsResponse {
+    #[serde(flatten)]
+    stats: StatsSnapshot,
+    processed_block: Option<u64>,
+}
+
+#[get("/stats")]
+async fn stats(
+    stats: &State<Arc<Stats>>,
+    pool: &State<ReadPool>,
+) -> Result<Json<StatsResponse>, Status> {
+    let processed_block = load_processed_block(pool).await?;
+
+    Ok(Json(StatsResponse {
+        stats: stats.snapshot(),
+        processed_block,
+    }))
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProcessedBlockResponse {
+    processed_block: Option<u64>,
+}
+
+/// Highest block processed, for monitors to compare against the chain head.
+#[get("/status/processed-block")]
+async fn processed_block(pool: &State<ReadPool>) -> Result<Json<ProcessedBlockResponse>, Status> {
+    let processed_block = load_processed_block(pool).await?;
+
+    Ok(Json(ProcessedBlockResponse { processed_block }))
+}
+
+async fn load_processed_block(pool: &ReadPool) -> Result<Option<u64>, Status> {
+    get_processed_block(pool.pool()).await.map_err(|e| {
+        error!("Failed to load processed block: {e}");
+        Status::InternalServerError
     })
 }
 
+/// Prometheus scrape target, see [`crate::metrics`].
+#[get("/metrics")]
+fn metrics(metrics: &State<Arc<Metrics>>) -> (ContentType, String) {
+    (ContentType::Plain, metrics.snapshot().render())
+}
+
+#[get("/stats/rpc")]
+fn rpc_stats() -> Json<RpcMetricsSnapshot> {
+    Json(RpcMetrics::global().snapshot())
+}
+
+/// P&L summary, restricted to one strategy with `?strategy=<label>`.
+#[get("/pnl/summary?<strategy>")]
+async fn pnl_summary(
+    pool: &State<ReadPool>,
+    strategy: Option<&str>,
+) -> Result<Json<PnlSummary>, Status> {
+    load_pnl_summary(pool.pool(), strategy)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to load P&L summary: {e}");
+            Status::InternalServerError
+        })
+}
+
+#[get("/pnl/asset-classes")]
+async fn pnl_by_asset_class(pool: &State<ReadPool>) -> Result<Json<Vec<AssetClassPnl>>, Status> {
+    load_pnl_by_asset_class(pool.pool())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to load P&L by asset class: {e}");
+            Status::InternalServerError
+        })
+}
+
+/// Page size of `GET /executions` when `limit` is not given.
+const DEFAULT_EXECUTIONS_PAGE_SIZE: u32 = 50;
+
+/// Largest page `GET /executions` returns, whatever `limit` asks for.
+const MAX_EXECUTIONS_PAGE_SIZE: u32 = 500;
+
+#[derive(Serialize, Deserialize)]
+struct ExecutionSummary {
+    id: Option<i64>,
+    symbol: Symbol,
+    shares: Shares,
+    direction: Direction,
+    broker: String,
+    status: OrderStatus,
+}
+
+impl From<OffchainExecution> for ExecutionSummary {
+    fn from(execution: OffchainExecution) -> Self {
+        Self {
+            id: execution.id,
+            symbol: execution.symbol,
+            shares: execution.shares,
+            direction: execution.direction,
+            broker: execution.broker.to_string(),
+            status: execution.state.status(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExecutionPage {
+    executions: Vec<ExecutionSummary>,
+    limit: u32,
+    offset: u32,
+}
+
+/// Executions newest first, filtered by `symbol`, `status` and an RFC 3339
+/// `from`/`to` range on when they finished. Responds 400 for filters that
+/// do not parse.
+#[get("/executions?<symbol>&<status>&<from>&<to>&<limit>&<offset>")]
+async fn executions(
+    pool: &State<ReadPool>,
+    symbol: Option<&str>,
+    status: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Json<ExecutionPage>, Status> {
+    let parse_time = |time: Option<&str>| {
+        time.map(|time| DateTime::parse_from_rfc3339(time).map(|time| time.with_timezone(&Utc)))
+            .transpose()
+            .map_err(|_| Status::BadRequest)
+    };
+
+    let filter = ExecutionFilter {
+        symbol: symbol
+            .map(Symbol::new)
+            .transpose()
+            .map_err(|_| Status::BadRequest)?,
+        status: status
+            .map(str::parse)
+            .transpose()
+            .map_err(|_| Status::BadRequest)?,
+        from: parse_time(from)?,
+        to: parse_time(to)?,
+    };
+    let limit = limit
+        .unwrap_or(DEFAULT_EXECUTIONS_PAGE_SIZE)
+        .min(MAX_EXECUTIONS_PAGE_SIZE);
+    let offset = offset.unwrap_or(0);
+
+    let executions = list_executions(pool.pool(), &filter, limit, offset)
+        .await
+        .map_err(|e| {
+            error!("Failed to list executions: {e}");
+            Status::InternalServerError
+        })?;
+
+    Ok(Json(ExecutionPage {
+        executions: executions.into_iter().map(ExecutionSummary::from).collect(),
+        limit,
+        offset,
+    }))
+}
+
+#[derive(Serialize, Deserialize)]
+struct LinkedTradeResponse {
+    trade_id: i64,
+    tx_hash: String,
+    log_index: u64,
+    symbol: String,
+    direction: String,
+    amount: f64,
+    price_usdc: f64,
+    contributed_shares: f64,
+}
+
+impl From<TradeContribution> for LinkedTradeResponse {
+    fn from(trade: TradeContribution) -> Self {
+        Self {
+            trade_id: trade.trade_id,
+            tx_hash: trade.trade_tx_hash,
+            log_index: trade.trade_log_index,
+            symbol: trade.trade_symbol,
+            direction: trade.trade_direction,
+            amount: trade.trade_total_amount,
+            price_usdc: trade.trade_price_usdc,
+            contributed_shares: trade.contributed_shares,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExecutionResponse {
+    #[serde(flatten)]
+    execution: ExecutionSummary,
+    block_number: Option<u64>,
+    /// Onchain trades whose shares the execution hedges
+    trades: Vec<LinkedTradeResponse>,
+}
+
+#[get("/executions/<id>")]
+async fn execution(pool: &State<ReadPool>, id: i64) -> Result<Json<ExecutionResponse>, Status> {
+    let load = async {
+        let Some(execution) = find_execution_by_id(pool.pool(), id).await? else {
+            return Ok(None);
+        };
+        let block_number = find_execution_origin_block(pool.pool(), id).await?;
+        let trades = TradeExecutionLink::find_trades_for_execution(pool.pool(), id).await?;
+
+        Ok::<_, OnChainError>(Some(ExecutionResponse {
+            execution: ExecutionSummary::from(execution),
+            block_number,
+            trades: trades.into_iter().map(LinkedTradeResponse::from).collect(),
+        }))
+    };
+
+    match load.await {
+        Ok(Some(execution)) => Ok(Json(execution)),
+        Ok(None) => Err(Status::NotFound),
+        Err(e) => {
+            error!("Failed to load execution {id}: {e}");
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+/// Newly persisted trades and execution status changes as Server-Sent Events,
+/// see [`crate::trade_feed`]. Not found when the feed is disabled.
+#[get("/stream/trades")]
+fn trade_stream(config: &State<Config>, mut shutdown: Shutdown) -> Result<EventStream![], Status> {
+    let Some(mut trades) = config.trade_feed.subscribe() else {
+        return Err(Status::NotFound);
+    };
+
+    Ok(EventStream! {
+        loop {
+            let event = select! {
+                received = trades.recv() => match received {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Trade feed client fell {skipped} events behind, disconnecting");
+                        break;
+                    }
+                },
+                () = &mut shutdown => break,
+            };
+
+            yield Event::json(&event);
+        }
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct PromoteResponse {
+    promoted: bool,
+    message: String,
+}
+
+/// Guard for admin routes: only requests from the local machine's own socket
+/// address get through. The forwarded client IP headers are ignored, since
+/// anyone can set them.
+struct LocalOnly;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LocalOnly {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.remote() {
+            Some(remote) if remote.ip().is_loopback() => Outcome::Success(Self),
+            remote => {
+                warn!("Rejected admin request from non-local address {remote:?}");
+                Outcome::Error((Status::Forbidden, ()))
+            }
+        }
+    }
+}
+
+#[post("/admin/promote")]
+fn promote(_local: LocalOnly, config: &State<Config>) -> Json<PromoteResponse> {
+    if config.standby.promote() {
+        info!("Promoted from standby, executions enabled");
+        Json(PromoteResponse {
+            promoted: true,
+            message: "Promoted from standby, executions enabled".to_string(),
+        })
+    } else {
+        Json(PromoteResponse {
+            promoted: false,
+            message: "Already active".to_string(),
+        })
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 struct AuthRefreshRequest {
     redirect_url: String,
@@ -80,7 +482,20 @@ async fn auth_refresh(
 }
 
 pub(crate) fn routes() -> Vec<Route> {
-    routes![health, auth_refresh]
+    routes![
+        health,
+        stats,
+        processed_block,
+        rpc_stats,
+        metrics,
+        pnl_summary,
+        pnl_by_asset_class,
+        executions,
+        execution,
+        trade_stream,
+        promote,
+        auth_refresh
+    ]
 }
 
 #[cfg(test)]
@@ -89,18 +504,28 @@ mod tests {
     use backon::{ExponentialBuilder, Retryable};
     use httpmock::{Mock, MockServer};
     use reqwest::Client as ReqwestClient;
-    use rocket::http::{ContentType, Status};
+    use rocket::http::{ContentType, Header, Status};
     use rocket::local::asynchronous::Client;
+    use rocket::tokio::io::AsyncReadExt;
     use serde_json::json;
     use serial_test::serial;
+    use std::num::NonZeroUsize;
     use std::time::Duration;
     use url::Url;
 
     use super::*;
-    use crate::env::{BrokerConfig, Config, LogLevel};
+    use crate::env::{BrokerConfig, Config, LogFormat, LogLevel};
     use crate::launch;
-    use crate::onchain::EvmEnv;
-    use crate::test_utils::setup_test_db;
+    use crate::offchain::blackout::BlackoutCalendar;
+    use crate::offchain::trade_side::TradeSide;
+    use crate::onchain::io::SymbolConvention;
+    use crate::onchain::last_seen_block::record_last_seen_block;
+    use crate::onchain::price_source::PriceSource;
+    use crate::onchain::{DEFAULT_BACKFILL_BATCH_SIZE, EvmEnv};
+    use crate::test_utils::{
+        OffchainExecutionBuilder, OnchainTradeBuilder, setup_test_db, setup_test_tokens,
+    };
+    use crate::trade_feed::{TradeFeed, TradeFeedEvent};
     use st0x_broker::schwab::SchwabAuthEnv;
 
     const TEST_ENCRYPTION_KEY: FixedBytes<32> = FixedBytes::ZERO;
@@ -108,13 +533,17 @@ mod tests {
     fn create_test_config_with_mock_server(mock_server: &MockServer) -> Config {
         Config {
             database_url: ":memory:".to_string(),
+            read_database_url: None,
             log_level: crate::env::LogLevel::Debug,
+            log_format: crate::env::LogFormat::Pretty,
             server_port: 8080,
             evm: EvmEnv {
                 ws_rpc_url: Url::parse("ws://localhost:8545").unwrap(),
                 orderbook: address!("0x1111111111111111111111111111111111111111"),
                 order_owner: address!("0x2222222222222222222222222222222222222222"),
                 deployment_block: 0,
+                symbol_convention: SymbolConvention::default(),
+                backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
             },
             order_polling_interval: 15,
             order_polling_max_jitter: 5,
@@ -124,8 +553,59 @@ mod tests {
                 schwab_redirect_uri: "https://127.0.0.1".to_string(),
                 schwab_base_url: mock_server.base_url(),
                 schwab_account_index: 0,
+                schwab_alert_on_manual_review: false,
+                schwab_market_hours_min_interval_secs: 0,
+                schwab_order_batch_lookback_hours: None,
+                schwab_persist_order_payloads: false,
+                schwab_fractional_shares: false,
+                price_rounding: st0x_broker::PriceRounding::HalfEven,
                 encryption_key: TEST_ENCRYPTION_KEY,
             }),
+            price_sources: vec![PriceSource::OnchainRatio],
+            blackout: BlackoutCalendar::default(),
+            order_polling_dust_notional: None,
+            order_polling_dust_every: 4,
+            max_order_age: None,
+            execution_dedup_window: None,
+            liquidity: None,
+            trade_side: TradeSide::Both,
+            share_rounding: crate::onchain::position_calculator::ShareRounding::Truncate,
+            shutdown_drain_timeout: Duration::from_secs(30),
+            symbol_fallback: None,
+            max_concurrent_executions: std::num::NonZeroUsize::new(4).unwrap(),
+            max_concurrent_symbols: std::num::NonZeroUsize::new(4).unwrap(),
+            max_open_executions: None,
+            position_limits: crate::offchain::position_limit::PositionLimits::default(),
+            circuit_breakers: crate::conductor::circuit_breaker::SymbolCircuitBreakers::default(),
+            execution_batch_window: None,
+            execution_debounce: None,
+            strategy_label: None,
+            max_pyth_confidence_bps: None,
+            max_pyth_price_divergence_pct: None,
+            queue_poll_min_delay: Duration::from_millis(100),
+            queue_poll_max_delay: Duration::from_secs(30),
+            failover: None,
+            ws_connect_attempts: std::num::NonZeroUsize::new(1).unwrap(),
+            startup_canary: None,
+            contract_code_check: crate::onchain::contract_code::ContractCodeCheck::None,
+            persist_last_seen_block: false,
+            standby: crate::standby::Standby::default(),
+            trade_feed: crate::trade_feed::TradeFeed::default(),
+            order_notifier: crate::order_notifier::WebhookNotifier::default(),
+            record_conversion_outcomes: false,
+            inline_reporter_interval: None,
+            hedge_event_types: crate::onchain::hedge_events::HedgeEventType::ALL.to_vec(),
+            invert_direction: vec![],
+            no_accumulate: vec![],
+            symbol_filter: crate::symbol::filter::SymbolFilter::default(),
+            symbol_cache_capacity: None,
+            symbol_configuration_mode: crate::onchain::io::SymbolConfigurationMode::Lenient,
+            rpc_metrics: false,
+            locked_retry: crate::db_retry::LockedRetryPolicy::default(),
+            end_of_day_settlement: crate::offchain::end_of_day::EndOfDaySettlement::Hold,
+            end_of_day_settlement_lead: std::time::Duration::from_secs(300),
+            vacuum_interval: None,
+            event_priorities: crate::queue::EventPriorities::default(),
             hyperdx: None,
         }
     }
@@ -133,12 +613,40 @@ mod tests {
     #[test]
     fn test_num_of_routes() {
         let routes_list = routes();
-        assert_eq!(routes_list.len(), 2);
+        assert_eq!(routes_list.len(), 12);
     }
 
     #[tokio::test]
     async fn test_health_endpoint() {
-        let rocket = rocket::build().mount("/", routes![health]);
+        let pool = setup_test_db().await;
+        let server = MockServer::start();
+        let config = create_test_config_with_mock_server(&server);
+        let BrokerConfig::Schwab(schwab_auth) = &config.broker else {
+            unreachable!()
+        };
+        setup_test_tokens(&pool, schwab_auth).await;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO event_queue (tx_hash, log_index, block_number, event_data, processed)
+            VALUES
+                ('0x1111111111111111111111111111111111111111111111111111111111111111', 0, 100, '{}', 1),
+                ('0x2222222222222222222222222222222222222222222222222222222222222222', 0, 150, '{}', 0)
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let stats = Arc::new(Stats::default());
+        stats.record_dex_streams_opened();
+        stats.record_dex_activity();
+
+        let rocket = rocket::build()
+            .mount("/", routes![health])
+            .manage(stats.clone())
+            .manage(ReadPool::new(pool.clone()))
+            .manage(config);
         let client = Client::tracked(rocket)
             .await
             .expect("valid rocket instance");
@@ -147,11 +655,523 @@ mod tests {
         assert_eq!(response.status(), Status::Ok);
 
         let body = response.into_string().await.expect("response body");
+        assert!(!body.contains("test_access_token"));
         let health_response: HealthResponse =
             serde_json::from_str(&body).expect("valid JSON response");
 
         assert_eq!(health_response.status, "healthy");
         assert!(health_response.timestamp <= chrono::Utc::now());
+        assert!(health_response.database_reachable);
+        assert!(health_response.dex_streams.connected);
+        assert!(!health_response.dex_streams.stale);
+        assert_eq!(health_response.processed_block, Some(100));
+        assert_eq!(health_response.queue_depth, Some(1));
+        let tokens = health_response.broker_tokens.unwrap();
+        assert!(tokens.access_token_valid);
+        assert!(tokens.refresh_token_valid);
+
+        // Lost websocket streams make the instance unavailable
+        stats.record_dex_streams_closed();
+        let response = client.get("/health").dispatch().await;
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+
+        // So does an unreachable database
+        stats.record_dex_streams_opened();
+        pool.close().await;
+        let response = client.get("/health").dispatch().await;
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+        let body = response.into_string().await.expect("response body");
+        let health_response: HealthResponse =
+            serde_json::from_str(&body).expect("valid JSON response");
+        assert_eq!(health_response.status, "unhealthy");
+        assert!(!health_response.database_reachable);
+        assert!(health_response.dex_streams.connected);
+        assert_eq!(health_response.queue_depth, None);
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint_unavailable_before_streams_connect() {
+        let server = MockServer::start();
+        let mut config = create_test_config_with_mock_server(&server);
+        config.broker = BrokerConfig::DryRun;
+
+        let rocket = rocket::build()
+            .mount("/", routes![health])
+            .manage(Arc::new(Stats::default()))
+            .manage(ReadPool::new(setup_test_db().await))
+            .manage(config);
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/health").dispatch().await;
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+
+        let body = response.into_string().await.expect("response body");
+        let health_response: HealthResponse =
+            serde_json::from_str(&body).expect("valid JSON response");
+        assert!(health_response.database_reachable);
+        assert!(!health_response.dex_streams.connected);
+        assert!(health_response.dex_streams.stale);
+        assert!(health_response.broker_tokens.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stats_endpoint() {
+        let stats = Arc::new(Stats::default());
+        stats.record_event_received();
+        stats.record_fill();
+
+        let rocket = rocket::build()
+            .mount("/", routes![stats])
+            .manage(stats.clone())
+            .manage(ReadPool::new(setup_test_db().await));
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/stats").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let body = response.into_string().await.expect("response body");
+        let response: StatsResponse = serde_json::from_str(&body).expect("valid JSON response");
+
+        assert_eq!(response.stats, stats.snapshot());
+        assert_eq!(response.stats.events_received, 1);
+        assert_eq!(response.stats.fills, 1);
+        assert_eq!(response.processed_block, None);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let metrics = Arc::new(Metrics::default());
+        metrics.set_queue_depth(4);
+        metrics.record_order_placed(1);
+        metrics.record_order_filled(1);
+
+        let rocket = rocket::build()
+            .mount("/", routes![super::metrics])
+            .manage(metrics.clone());
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/metrics").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+
+        let body = response.into_string().await.expect("response body");
+        assert_eq!(body, metrics.snapshot().render());
+        assert!(body.contains("st0x_event_queue_depth 4\n"));
+        assert!(body.contains("st0x_fill_latency_seconds_count 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_processed_block_endpoint() {
+        let pool = setup_test_db().await;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO event_queue (tx_hash, log_index, block_number, event_data, processed)
+            VALUES
+                ('0x1111111111111111111111111111111111111111111111111111111111111111', 0, 100, '{}', 1),
+                ('0x2222222222222222222222222222222222222222222222222222222222222222', 0, 150, '{}', 1),
+                ('0x3333333333333333333333333333333333333333333333333333333333333333', 0, 200, '{}', 0)
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        record_last_seen_block(&pool, 120).await.unwrap();
+
+        let rocket = rocket::build()
+            .mount("/", routes![stats, processed_block])
+            .manage(Arc::new(Stats::default()))
+            .manage(ReadPool::new(pool.clone()));
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/status/processed-block").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().await.expect("response body");
+        let response: ProcessedBlockResponse =
+            serde_json::from_str(&body).expect("valid JSON response");
+        assert_eq!(response.processed_block, Some(150));
+
+        // A last-seen block ahead of the processed events wins
+        record_last_seen_block(&pool, 180).await.unwrap();
+
+        let response = client.get("/status/processed-block").dispatch().await;
+        let body = response.into_string().await.expect("response body");
+        let response: ProcessedBlockResponse =
+            serde_json::from_str(&body).expect("valid JSON response");
+        assert_eq!(response.processed_block, Some(180));
+
+        let response = client.get("/stats").dispatch().await;
+        let body = response.into_string().await.expect("response body");
+        let response: StatsResponse = serde_json::from_str(&body).expect("valid JSON response");
+        assert_eq!(response.processed_block, Some(180));
+    }
+
+    #[tokio::test]
+    async fn test_pnl_summary_endpoint() {
+        let pool = setup_test_db().await;
+        sqlx::query(
+            "INSERT INTO metrics_pnl (
+                symbol, timestamp, trade_type, trade_id, trade_direction, quantity,
+                price_per_share, realized_pnl, cumulative_pnl, net_position_after
+            ) VALUES
+                ('AAPL', '2025-01-06 15:30:00', 'ONCHAIN', 1, 'BUY', 10.0, 150.0, NULL, 0.0, 10.0),
+                ('AAPL', '2025-01-06 15:31:00', 'OFFCHAIN', 1, 'SELL', 10.0, 151.1, 11.0, 11.0, 0.0),
+                ('AAPL', '2025-01-06 15:40:00', 'ONCHAIN', 2, 'BUY', 5.0, 150.5, NULL, 11.0, 5.0),
+                ('MSFT', '2025-01-06 15:32:00', 'ONCHAIN', 3, 'SELL', 4.0, 300.0, NULL, 0.0, -4.0),
+                ('MSFT', '2025-01-06 15:33:00', 'OFFCHAIN', 2, 'BUY', 2.0, 295.0, 10.0, 10.0, -2.0),
+                ('TSLA', '2025-01-06 15:34:00', 'ONCHAIN', 4, 'BUY', 1.5, 250.0, NULL, 0.0, 1.5),
+                ('TSLA', '2025-01-06 15:35:00', 'OFFCHAIN', 3, 'SELL', 1.0, 247.75, -2.25, -2.25, 0.5)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let rocket = rocket::build()
+            .mount("/", routes![pnl_summary])
+            .manage(ReadPool::new(pool));
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/pnl/summary").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "symbols": [
+                    {
+                        "symbol": "AAPL",
+                        "realized_pnl": "11",
+                        "cumulative_pnl": "11",
+                        "net_position": 5.0
+                    },
+                    {
+                        "symbol": "MSFT",
+                        "realized_pnl": "10",
+                        "cumulative_pnl": "10",
+                        "net_position": -2.0
+                    },
+                    {
+                        "symbol": "TSLA",
+                        "realized_pnl": "-2.25",
+                        "cumulative_pnl": "-2.25",
+                        "net_position": 0.5
+                    }
+                ],
+                "strategies": [
+                    {
+                        "strategy_label": null,
+                        "realized_pnl": "18.75",
+                        "cumulative_pnl": "18.75"
+                    }
+                ],
+                "total": {
+                    "realized_pnl": "18.75",
+                    "cumulative_pnl": "18.75"
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execution_endpoint() {
+        let pool = setup_test_db().await;
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = OffchainExecutionBuilder::new()
+            .build()
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sqlx::query!(
+            "UPDATE offchain_trades SET block_number = 12345 WHERE id = ?1",
+            execution_id
+        )
+        .execute(&mut *sql_tx)
+        .await
+        .unwrap();
+        let trade_id = OnchainTradeBuilder::new()
+            .build()
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        TradeExecutionLink::new(trade_id, execution_id, 1.0)
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let rocket = rocket::build()
+            .mount("/", routes![execution])
+            .manage(ReadPool::new(pool));
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client
+            .get(format!("/executions/{execution_id}"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let mut body: serde_json::Value =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        let trades = body.as_object_mut().unwrap().remove("trades").unwrap();
+        assert_eq!(trades.as_array().unwrap().len(), 1);
+        assert_eq!(trades[0]["trade_id"], trade_id);
+        assert_eq!(trades[0]["symbol"], "AAPL0x");
+        assert_eq!(trades[0]["contributed_shares"], 1.0);
+        assert_eq!(
+            body,
+            json!({
+                "id": execution_id,
+                "symbol": "AAPL",
+                "shares": 100,
+                "direction": "BUY",
+                "broker": "schwab",
+                "status": "PENDING",
+                "block_number": 12345
+            })
+        );
+
+        let response = client.get("/executions/999").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_executions_endpoint_filters_and_caps_page_size() {
+        let pool = setup_test_db().await;
+        for symbol in ["AAPL", "MSFT"] {
+            let mut sql_tx = pool.begin().await.unwrap();
+            OffchainExecutionBuilder::new()
+                .with_symbol(symbol)
+                .build()
+                .save_within_transaction(&mut sql_tx)
+                .await
+                .unwrap();
+            sql_tx.commit().await.unwrap();
+        }
+
+        let rocket = rocket::build()
+            .mount("/", routes![executions])
+            .manage(ReadPool::new(pool));
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client
+            .get("/executions?symbol=MSFT&status=PENDING&limit=100000")
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        assert_eq!(body["limit"], 500);
+        assert_eq!(body["offset"], 0);
+        let executions = body["executions"].as_array().unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0]["symbol"], "MSFT");
+        assert_eq!(executions[0]["status"], "PENDING");
+
+        let response = client.get("/executions?offset=1").dispatch().await;
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        assert_eq!(body["executions"][0]["symbol"], "AAPL");
+
+        for query in ["status=DONE", "from=yesterday"] {
+            let response = client.get(format!("/executions?{query}")).dispatch().await;
+            assert_eq!(response.status(), Status::BadRequest);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_promote_endpoint() {
+        let server = MockServer::start();
+        let mut config = create_test_config_with_mock_server(&server);
+        config.standby = crate::standby::Standby::new(true);
+        let standby = config.standby.clone();
+
+        let rocket = rocket::build().mount("/", routes![promote]).manage(config);
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        // Anything but the local machine is turned away, whatever it claims
+        let response = client
+            .post("/admin/promote")
+            .remote("203.0.113.7:4000".parse().unwrap())
+            .header(Header::new("X-Real-IP", "127.0.0.1"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Forbidden);
+        assert!(standby.is_standby());
+
+        let local = "127.0.0.1:4000".parse().unwrap();
+        let response = client.post("/admin/promote").remote(local).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let body: PromoteResponse =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        assert!(body.promoted);
+        assert!(!standby.is_standby());
+
+        let response = client.post("/admin/promote").remote(local).dispatch().await;
+        let body: PromoteResponse =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        assert!(!body.promoted);
+    }
+
+    #[tokio::test]
+    async fn test_trade_stream_delivers_published_trades() {
+        let server = MockServer::start();
+        let mut config = create_test_config_with_mock_server(&server);
+        config.trade_feed = TradeFeed::new(NonZeroUsize::new(16));
+        let feed = config.trade_feed.clone();
+
+        let rocket = rocket::build()
+            .mount("/", routes![trade_stream])
+            .manage(config);
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let mut response = client.get("/stream/trades").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let trade = OnchainTradeBuilder::new().build();
+        feed.publish(TradeFeedEvent::from(&trade));
+
+        let mut received = String::new();
+        let mut chunk = [0; 1024];
+        while !received.contains("\n\n") {
+            let read = response.read(&mut chunk).await.unwrap();
+            assert!(read > 0, "stream ended before an event arrived");
+            received.push_str(std::str::from_utf8(&chunk[..read]).unwrap());
+        }
+
+        let data = received
+            .lines()
+            .find_map(|line| line.strip_prefix("data:"))
+            .expect("data line");
+        let event: TradeFeedEvent = serde_json::from_str(data.trim()).unwrap();
+        assert_eq!(event, TradeFeedEvent::from(&trade));
+    }
+
+    #[tokio::test]
+    async fn test_trade_stream_not_found_when_disabled() {
+        let server = MockServer::start();
+        let config = create_test_config_with_mock_server(&server);
+
+        let rocket = rocket::build()
+            .mount("/", routes![trade_stream])
+            .manage(config);
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/stream/trades").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_read_endpoints_go_through_configured_read_pool() {
+        let write_pool = setup_test_db().await;
+
+        let path = std::env::temp_dir().join(format!(
+            "read-pool-{}-{}.sqlite",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        let replica = sqlx::SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&path)
+                .create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        sqlx::migrate!().run(&replica).await.unwrap();
+        let mut sql_tx = replica.begin().await.unwrap();
+        let execution_id = OffchainExecutionBuilder::new()
+            .build()
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+        replica.close().await;
+
+        let read_database_url = format!("sqlite://{}", path.display());
+        let read_pool = ReadPool::connect_or_share(Some(&read_database_url), &write_pool)
+            .await
+            .unwrap();
+
+        // The read pool is read-only
+        sqlx::query("DELETE FROM offchain_trades")
+            .execute(read_pool.pool())
+            .await
+            .unwrap_err();
+
+        let rocket = rocket::build()
+            .mount("/", routes![execution])
+            .manage(write_pool.clone())
+            .manage(read_pool.clone());
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        // Only the read database has the execution
+        let response = client
+            .get(format!("/executions/{execution_id}"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        // Without a read database, reads go through the write pool
+        let shared = ReadPool::connect_or_share(None, &write_pool).await.unwrap();
+        let rocket = rocket::build()
+            .mount("/", routes![execution])
+            .manage(shared);
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+        let response = client
+            .get(format!("/executions/{execution_id}"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        drop(response);
+        drop(client);
+        read_pool.pool().close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pnl_summary_endpoint_empty() {
+        let pool = setup_test_db().await;
+        let rocket = rocket::build()
+            .mount("/", routes![pnl_summary])
+            .manage(ReadPool::new(pool));
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/pnl/summary").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let summary: PnlSummary =
+            serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+        assert!(summary.symbols.is_empty());
+        assert_eq!(summary.total.realized_pnl, rust_decimal::Decimal::ZERO);
     }
 
     #[tokio::test]
@@ -395,13 +1415,17 @@ mod tests {
 
         Config {
             database_url: ":memory:".to_string(),
+            read_database_url: None,
             log_level: LogLevel::Info,
+            log_format: LogFormat::Pretty,
             server_port,
             evm: EvmEnv {
                 ws_rpc_url: url::Url::parse("ws://127.0.0.1:8545").unwrap(),
                 orderbook: address!("0x1234567890123456789012345678901234567890"),
                 order_owner: address!("0xD2843D9E7738d46D90CB6Dff8D6C83db58B9c165"),
                 deployment_block: 1,
+                symbol_convention: SymbolConvention::default(),
+                backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
             },
             order_polling_interval: 15,
             order_polling_max_jitter: 5,
@@ -411,8 +1435,59 @@ mod tests {
                 schwab_redirect_uri: "https://127.0.0.1".to_string(),
                 schwab_base_url: base_url,
                 schwab_account_index: 0,
+                schwab_alert_on_manual_review: false,
+                schwab_market_hours_min_interval_secs: 0,
+                schwab_order_batch_lookback_hours: None,
+                schwab_persist_order_payloads: false,
+                schwab_fractional_shares: false,
+                price_rounding: st0x_broker::PriceRounding::HalfEven,
                 encryption_key: TEST_ENCRYPTION_KEY,
             }),
+            price_sources: vec![PriceSource::OnchainRatio],
+            blackout: BlackoutCalendar::default(),
+            order_polling_dust_notional: None,
+            order_polling_dust_every: 4,
+            max_order_age: None,
+            execution_dedup_window: None,
+            liquidity: None,
+            trade_side: TradeSide::Both,
+            share_rounding: crate::onchain::position_calculator::ShareRounding::Truncate,
+            shutdown_drain_timeout: Duration::from_secs(30),
+            symbol_fallback: None,
+            max_concurrent_executions: std::num::NonZeroUsize::new(4).unwrap(),
+            max_concurrent_symbols: std::num::NonZeroUsize::new(4).unwrap(),
+            max_open_executions: None,
+            position_limits: crate::offchain::position_limit::PositionLimits::default(),
+            circuit_breakers: crate::conductor::circuit_breaker::SymbolCircuitBreakers::default(),
+            execution_batch_window: None,
+            execution_debounce: None,
+            strategy_label: None,
+            max_pyth_confidence_bps: None,
+            max_pyth_price_divergence_pct: None,
+            queue_poll_min_delay: Duration::from_millis(100),
+            queue_poll_max_delay: Duration::from_secs(30),
+            failover: None,
+            ws_connect_attempts: std::num::NonZeroUsize::new(1).unwrap(),
+            startup_canary: None,
+            contract_code_check: crate::onchain::contract_code::ContractCodeCheck::None,
+            persist_last_seen_block: false,
+            standby: crate::standby::Standby::default(),
+            trade_feed: crate::trade_feed::TradeFeed::default(),
+            order_notifier: crate::order_notifier::WebhookNotifier::default(),
+            record_conversion_outcomes: false,
+            inline_reporter_interval: None,
+            hedge_event_types: crate::onchain::hedge_events::HedgeEventType::ALL.to_vec(),
+            invert_direction: vec![],
+            no_accumulate: vec![],
+            symbol_filter: crate::symbol::filter::SymbolFilter::default(),
+            symbol_cache_capacity: None,
+            symbol_configuration_mode: crate::onchain::io::SymbolConfigurationMode::Lenient,
+            rpc_metrics: false,
+            locked_retry: crate::db_retry::LockedRetryPolicy::default(),
+            end_of_day_settlement: crate::offchain::end_of_day::EndOfDaySettlement::Hold,
+            end_of_day_settlement_lead: std::time::Duration::from_secs(300),
+            vacuum_interval: None,
+            event_priorities: crate::queue::EventPriorities::default(),
             hyperdx: None,
         }
     }
@@ -479,7 +1554,7 @@ mod tests {
             .with_max_delay(Duration::from_secs(1))
             .with_max_times(20);
 
-        let health_check = || async { client.get(&health_url).send().await?.error_for_status() };
+        let health_check = || async { client.get(&health_url).send().await };
 
         health_check
             .retry(&retry_strategy)
@@ -492,12 +1567,15 @@ mod tests {
             .await
             .expect("Health endpoint should be accessible");
 
-        assert_eq!(health_response.status(), 200);
+        // No DEX websocket is reachable, so the bot reports itself unhealthy
+        assert_eq!(health_response.status(), 503);
         let health_data: serde_json::Value = health_response
             .json()
             .await
             .expect("Health response should be valid JSON");
-        assert_eq!(health_data["status"], "healthy");
+        assert_eq!(health_data["status"], "unhealthy");
+        assert_eq!(health_data["database_reachable"], true);
+        assert_eq!(health_data["dex_streams"]["connected"], false);
         assert!(health_data["timestamp"].is_string());
 
         let auth_request = json!({
@@ -5,7 +5,7 @@ use st0x_hedge::env::setup_tracing;
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv_override().ok();
     let (env, command) = cli::CliEnv::parse_and_convert()?;
-    setup_tracing(&env.log_level);
+    setup_tracing(&env.log_level, env.log_format);
 
     cli::run_command(env, command).await?;
     Ok(())
@@ -6,7 +6,9 @@ use st0x_hedge::reporter::{self, ReporterEnv};
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv_override().ok();
     let env = ReporterEnv::parse();
-    setup_tracing(env.log_level());
+    if !env.is_export() {
+        setup_tracing(env.log_level(), env.log_format());
+    }
 
     reporter::run(env).await
 }
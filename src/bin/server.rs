@@ -1,9 +1,10 @@
 use clap::Parser;
 use st0x_hedge::env::{Env, setup_tracing};
 use st0x_hedge::launch;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> anyhow::Result<ExitCode> {
     dotenvy::dotenv_override().ok();
     let parsed_env = Env::parse();
     let config = parsed_env.into_config()?;
@@ -13,12 +14,12 @@ async fn main() -> anyhow::Result<()> {
             Ok(guard) => Some(guard),
             Err(e) => {
                 eprintln!("Failed to setup telemetry: {e}");
-                setup_tracing(&config.log_level);
+                setup_tracing(&config.log_level, config.log_format);
                 None
             }
         }
     } else {
-        setup_tracing(&config.log_level);
+        setup_tracing(&config.log_level, config.log_format);
         None
     };
 
@@ -29,6 +30,5 @@ async fn main() -> anyhow::Result<()> {
     // provider, blocking until exports complete or timeout.
     drop(telemetry_guard);
 
-    result?;
-    Ok(())
+    Ok(result?.exit_code())
 }
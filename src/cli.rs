@@ -1,22 +1,33 @@
 use clap::{Parser, Subcommand};
 use sqlx::SqlitePool;
+use std::cmp::Ordering;
 use std::io::Write;
+use std::path::PathBuf;
 use thiserror::Error;
 use tracing::{error, info};
 
+use crate::conductor::{ReplayOutcome, cancel_offchain_execution, replay_queued_event};
 use crate::env::{BrokerConfig, Config, Env};
 use crate::error::OnChainError;
+use crate::offchain::execution::{find_client_order_id, save_request_payload};
+use crate::offchain::liquidity::LiquidityLimits;
+use crate::offchain::reconcile::{ReconcileSummary, reconcile_executions};
+use crate::onchain::position_calculator::ShareRounding;
 use crate::onchain::pyth::FeedIdCache;
+use crate::onchain::trade::TradeEvent;
 use crate::onchain::{OnchainTrade, accumulator};
+use crate::queue::{QueuedEvent, find_event, list_unprocessed_events};
+use crate::reporter::export::{ExportFormat, PnlExportFilter, export_pnl};
 use crate::symbol::cache::SymbolCache;
 use alloy::primitives::B256;
 use alloy::providers::{Provider, ProviderBuilder, WsConnect};
+use rust_decimal::Decimal;
 use st0x_broker::schwab::{
     SchwabAuthEnv, SchwabConfig, SchwabError, SchwabTokens, extract_code_from_url,
 };
 use st0x_broker::{
-    Broker, Direction, MarketOrder, MockBrokerConfig, OrderPlacement, OrderState, Shares, Symbol,
-    TryIntoBroker,
+    Broker, Direction, MarketOrder, MockBrokerConfig, OrderPlacement, OrderState,
+    PaperBrokerConfig, Shares, Symbol, TryIntoBroker,
 };
 
 #[derive(Debug, Error)]
@@ -64,10 +75,73 @@ pub enum Commands {
         #[arg(long = "tx-hash")]
         tx_hash: B256,
     },
+    /// Process several transaction hashes concurrently, reporting each outcome
+    ProcessTxs {
+        /// Comma-separated transaction hashes (0x prefixed, 64 hex characters)
+        #[arg(long = "tx-hashes", value_delimiter = ',', required = true)]
+        tx_hashes: Vec<B256>,
+    },
+    /// Export P&L metrics for offline analysis
+    ExportPnl {
+        /// Output file path
+        #[arg(long = "out")]
+        out: PathBuf,
+        /// Output format
+        #[arg(long = "format", value_enum, default_value = "csv")]
+        format: ExportFormat,
+    },
+    /// Show the accumulated position awaiting offchain execution for a symbol
+    ShowAccumulation {
+        /// Stock ticker symbol (e.g., AAPL, TSLA)
+        #[arg(short = 't', long = "ticker")]
+        ticker: String,
+    },
+    /// Cancel the broker order of a submitted execution that has not filled
+    CancelExecution {
+        /// Offchain execution ID
+        #[arg(long = "execution-id")]
+        execution_id: i64,
+    },
+    /// Compare open executions with Schwab's order history and correct
+    /// statuses the order poller missed
+    Reconcile {
+        /// How many hours of order history to compare against (at most 60 days)
+        #[arg(long = "lookback-hours", default_value = "72")]
+        lookback_hours: u32,
+        /// Only report discrepancies, without correcting them
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Inspect and replay events in the event queue
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommand,
+    },
     /// Perform Charles Schwab OAuth authentication flow
     Auth,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum QueueCommand {
+    /// List unprocessed events in processing order
+    List,
+    /// Show the decoded event of a queued event
+    Show {
+        /// Queued event ID
+        event_id: i64,
+    },
+    /// Convert a queued event against the live provider and print the
+    /// resulting trade or why it would be filtered out
+    Replay {
+        /// Queued event ID
+        event_id: i64,
+        /// Process the event for real: record the trade, mark the event
+        /// processed and place any execution it triggers
+        #[arg(long = "commit")]
+        commit: bool,
+    },
+}
+
 #[derive(Debug, Parser)]
 #[command(name = "schwab-cli")]
 #[command(about = "A CLI tool for Charles Schwab stock trading")]
@@ -158,47 +232,284 @@ async fn run_command_with_writers<W: Write>(
             info!("Processing transaction: tx_hash={tx_hash}");
             let ws = WsConnect::new(config.evm.ws_rpc_url.as_str());
             let provider = ProviderBuilder::new().connect_ws(ws).await?;
-            let cache = SymbolCache::default();
+            let cache = config.symbol_cache();
             process_tx_with_provider(tx_hash, &config, pool, stdout, &provider, &cache).await?;
         }
+        Commands::ProcessTxs { tx_hashes } => {
+            info!("Processing {} transactions", tx_hashes.len());
+            let ws = WsConnect::new(config.evm.ws_rpc_url.as_str());
+            let provider = ProviderBuilder::new().connect_ws(ws).await?;
+            let cache = config.symbol_cache();
+            process_txs_with_provider(tx_hashes, &config, pool, stdout, &provider, &cache).await?;
+        }
+        Commands::ExportPnl { out, format } => {
+            info!("Exporting P&L metrics to {} as {format:?}", out.display());
+            let file = std::io::BufWriter::new(std::fs::File::create(&out)?);
+            let exported = export_pnl(pool, format, &PnlExportFilter::default(), file).await?;
+            writeln!(
+                stdout,
+                "✅ Exported {exported} P&L rows to {}",
+                out.display()
+            )?;
+        }
+        Commands::ShowAccumulation { ticker } => {
+            show_accumulation(
+                pool,
+                &validate_ticker(&ticker)?,
+                config.share_rounding,
+                stdout,
+            )
+            .await?;
+        }
+        Commands::CancelExecution { execution_id } => {
+            info!("Cancelling execution: execution_id={execution_id}");
+            cancel_execution(&config, pool, execution_id).await?;
+            writeln!(stdout, "✅ Cancelled execution {execution_id}")?;
+        }
+        Commands::Reconcile {
+            lookback_hours,
+            dry_run,
+        } => {
+            info!("Reconciling executions: lookback_hours={lookback_hours}, dry_run={dry_run}");
+            reconcile(&config, pool, lookback_hours, dry_run, stdout).await?;
+        }
+        Commands::Queue { command } => {
+            run_queue_command(&config, pool, command, stdout).await?;
+        }
         Commands::Auth => {
-            let BrokerConfig::Schwab(schwab_auth) = &config.broker else {
-                anyhow::bail!("Auth command is only supported for Schwab broker")
-            };
+            run_auth_command(pool, &config.broker, stdout).await?;
+        }
+    }
+
+    info!("CLI operation completed successfully");
+    Ok(())
+}
+
+async fn show_accumulation<W: Write>(
+    pool: &SqlitePool,
+    ticker: &str,
+    rounding: ShareRounding,
+    stdout: &mut W,
+) -> anyhow::Result<()> {
+    info!("Showing accumulation: ticker={ticker}");
+    let Some((calculator, pending_execution_id)) =
+        accumulator::find_by_symbol(pool, ticker).await?
+    else {
+        writeln!(stdout, "No accumulated position for {ticker}")?;
+        return Ok(());
+    };
+
+    let net_position = calculator.net_position();
+    let whole_shares = calculator.calculate_executable_shares(rounding)?;
+    // Negative when rounding takes more shares than the position holds
+    let fractional_remainder = net_position.abs() - Decimal::from(whole_shares);
+
+    // Net long exposure is offset by a SELL, net short exposure by a BUY
+    let direction = match net_position.cmp(&Decimal::ZERO) {
+        Ordering::Greater => Direction::Sell.as_str(),
+        Ordering::Less => Direction::Buy.as_str(),
+        Ordering::Equal => "none",
+    };
+
+    writeln!(stdout, "📊 Accumulated position for {ticker}:")?;
+    writeln!(stdout, "   Whole shares: {whole_shares}")?;
+    writeln!(stdout, "   Fractional remainder: {fractional_remainder}")?;
+    writeln!(stdout, "   Direction: {direction}")?;
+    match pending_execution_id {
+        Some(execution_id) => writeln!(stdout, "   Pending execution: {execution_id}")?,
+        None => writeln!(stdout, "   Pending execution: none")?,
+    }
+
+    Ok(())
+}
+
+async fn run_auth_command<W: Write>(
+    pool: &SqlitePool,
+    broker: &BrokerConfig,
+    stdout: &mut W,
+) -> anyhow::Result<()> {
+    let BrokerConfig::Schwab(schwab_auth) = broker else {
+        anyhow::bail!("Auth command is only supported for Schwab broker")
+    };
+
+    info!("Starting OAuth authentication flow");
+    writeln!(
+        stdout,
+        "🔄 Starting Charles Schwab OAuth authentication process..."
+    )?;
+    writeln!(
+        stdout,
+        "   You will be guided through the authentication process."
+    )?;
 
-            info!("Starting OAuth authentication flow");
+    match run_oauth_flow(pool, schwab_auth).await {
+        Ok(()) => {
+            info!("OAuth authentication completed successfully");
+            writeln!(stdout, "✅ Authentication successful!")?;
             writeln!(
                 stdout,
-                "🔄 Starting Charles Schwab OAuth authentication process..."
+                "   Your tokens have been saved and are ready to use."
             )?;
+        }
+        Err(oauth_error) => {
+            error!("OAuth authentication failed: {oauth_error:?}");
+            writeln!(stdout, "❌ Authentication failed: {oauth_error}")?;
             writeln!(
                 stdout,
-                "   You will be guided through the authentication process."
+                "   Please ensure you have a valid Charles Schwab account and try again."
             )?;
+            return Err(oauth_error.into());
+        }
+    }
 
-            match run_oauth_flow(pool, schwab_auth).await {
-                Ok(()) => {
-                    info!("OAuth authentication completed successfully");
-                    writeln!(stdout, "✅ Authentication successful!")?;
-                    writeln!(
-                        stdout,
-                        "   Your tokens have been saved and are ready to use."
-                    )?;
-                }
-                Err(oauth_error) => {
-                    error!("OAuth authentication failed: {oauth_error:?}");
-                    writeln!(stdout, "❌ Authentication failed: {oauth_error}")?;
-                    writeln!(
-                        stdout,
-                        "   Please ensure you have a valid Charles Schwab account and try again."
-                    )?;
-                    return Err(oauth_error.into());
-                }
+    Ok(())
+}
+
+async fn run_queue_command<W: Write>(
+    config: &Config,
+    pool: &SqlitePool,
+    command: QueueCommand,
+    stdout: &mut W,
+) -> anyhow::Result<()> {
+    match command {
+        QueueCommand::List => {
+            let events = list_unprocessed_events(pool).await?;
+            writeln!(stdout, "📋 {} unprocessed events", events.len())?;
+            for queued_event in &events {
+                writeln!(
+                    stdout,
+                    "   #{} block={} log_index={} tx_hash={} type={}",
+                    queued_event.id.unwrap_or_default(),
+                    queued_event.block_number,
+                    queued_event.log_index,
+                    queued_event.tx_hash,
+                    event_type(&queued_event.event)
+                )?;
             }
         }
+        QueueCommand::Show { event_id } => {
+            let queued_event = find_queued_event(pool, event_id).await?;
+            writeln!(
+                stdout,
+                "🔍 Event #{event_id} ({}), block={} log_index={} tx_hash={} processed={}",
+                event_type(&queued_event.event),
+                queued_event.block_number,
+                queued_event.log_index,
+                queued_event.tx_hash,
+                queued_event.processed
+            )?;
+            writeln!(
+                stdout,
+                "{}",
+                serde_json::to_string_pretty(&queued_event.event)?
+            )?;
+        }
+        QueueCommand::Replay { event_id, commit } => {
+            info!("Replaying queued event: event_id={event_id}, commit={commit}");
+            let queued_event = find_queued_event(pool, event_id).await?;
+            let ws = WsConnect::new(config.evm.ws_rpc_url.as_str());
+            let provider = ProviderBuilder::new().connect_ws(ws).await?;
+            replay_with_provider(config, pool, &queued_event, commit, &provider, stdout).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn find_queued_event(pool: &SqlitePool, event_id: i64) -> anyhow::Result<QueuedEvent> {
+    find_event(pool, event_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Queued event {event_id} not found"))
+}
+
+const fn event_type(event: &TradeEvent) -> &'static str {
+    match event {
+        TradeEvent::ClearV2(_) => "ClearV2",
+        TradeEvent::TakeOrderV2(_) => "TakeOrderV2",
+    }
+}
+
+async fn replay_with_provider<W: Write, P: Provider + Clone>(
+    config: &Config,
+    pool: &SqlitePool,
+    queued_event: &QueuedEvent,
+    commit: bool,
+    provider: &P,
+    stdout: &mut W,
+) -> anyhow::Result<()> {
+    let outcome = match &config.broker {
+        BrokerConfig::Schwab(schwab_auth) => {
+            let broker = SchwabConfig {
+                auth: schwab_auth.clone(),
+                pool: pool.clone(),
+            }
+            .try_into_broker()
+            .await?;
+            replay_queued_event(&broker, config, pool, provider, queued_event, commit).await?
+        }
+        BrokerConfig::Alpaca(alpaca_auth) => {
+            let broker = alpaca_auth.clone().try_into_broker().await?;
+            replay_queued_event(&broker, config, pool, provider, queued_event, commit).await?
+        }
+        BrokerConfig::DryRun => {
+            let broker = MockBrokerConfig.try_into_broker().await?;
+            replay_queued_event(&broker, config, pool, provider, queued_event, commit).await?
+        }
+        BrokerConfig::Sim(sim_config) => {
+            let broker = sim_config.clone().try_into_broker().await?;
+            replay_queued_event(&broker, config, pool, provider, queued_event, commit).await?
+        }
+        BrokerConfig::Paper => {
+            let broker = PaperBrokerConfig { pool: pool.clone() }
+                .try_into_broker()
+                .await?;
+            replay_queued_event(&broker, config, pool, provider, queued_event, commit).await?
+        }
+    };
+
+    match outcome {
+        ReplayOutcome::Trade {
+            trade,
+            price_resolution,
+            hedged,
+            execution_ids,
+        } => {
+            display_trade_details(&trade, stdout)?;
+            match price_resolution {
+                Some(resolution) => writeln!(
+                    stdout,
+                    "   Resolved Price: ${:.2} from {}",
+                    resolution.price, resolution.source
+                )?,
+                None => writeln!(
+                    stdout,
+                    "   Resolved Price: none, hedged at the onchain price"
+                )?,
+            }
+            if !hedged {
+                writeln!(
+                    stdout,
+                    "   Not hedged: event type is not in the hedged event types"
+                )?;
+            }
+            for execution_id in execution_ids {
+                writeln!(stdout, "🎯 Placed execution {execution_id}")?;
+            }
+        }
+        ReplayOutcome::Filtered { reason } => {
+            writeln!(stdout, "⏭️  Event would be filtered out: {reason}")?;
+        }
+    }
+
+    if commit {
+        writeln!(stdout, "✅ Event processed")?;
+    } else {
+        writeln!(
+            stdout,
+            "Dry run: event left unprocessed, pass --commit to process it"
+        )?;
     }
 
-    info!("CLI operation completed successfully");
     Ok(())
 }
 
@@ -300,6 +611,7 @@ async fn execute_order_with_writers<W: Write>(
         symbol: Symbol::new(ticker.clone())?,
         shares: Shares::new(quantity)?,
         direction,
+        client_order_id: None,
     };
 
     info!("Created order: ticker={ticker}, direction={direction:?}, quantity={quantity}");
@@ -371,6 +683,41 @@ async fn process_tx_with_provider<W: Write, P: Provider + Clone>(
     Ok(())
 }
 
+async fn process_txs_with_provider<W: Write, P: Provider + Clone>(
+    tx_hashes: Vec<B256>,
+    config: &Config,
+    pool: &SqlitePool,
+    stdout: &mut W,
+    provider: &P,
+    cache: &SymbolCache,
+) -> anyhow::Result<()> {
+    let feed_id_cache = FeedIdCache::new();
+
+    let outcomes =
+        OnchainTrade::try_from_tx_hashes(tx_hashes, provider, cache, &config.evm, &feed_id_cache)
+            .await;
+
+    for (tx_hash, outcome) in outcomes {
+        match outcome {
+            Ok(Some(onchain_trade)) => {
+                writeln!(stdout, "🔍 Found trade in transaction {tx_hash}")?;
+                process_found_trade(onchain_trade, config, pool, stdout).await?;
+            }
+            Ok(None) => {
+                writeln!(
+                    stdout,
+                    "❌ No tradeable events found in transaction {tx_hash}"
+                )?;
+            }
+            Err(e) => {
+                writeln!(stdout, "❌ Error processing transaction {tx_hash}: {e}")?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 async fn execute_broker_order<W: Write>(
     config: &Config,
     pool: &SqlitePool,
@@ -416,7 +763,143 @@ async fn execute_broker_order<W: Write>(
             )?;
             Ok(placement)
         }
+        BrokerConfig::Sim(sim_config) => {
+            writeln!(stdout, "🔄 Executing simulated order...")?;
+            let broker = sim_config.clone().try_into_broker().await?;
+            let placement = broker.place_market_order(market_order).await?;
+            writeln!(
+                stdout,
+                "✅ Simulated order placed with ID: {}",
+                placement.order_id
+            )?;
+            Ok(placement)
+        }
+        BrokerConfig::Paper => {
+            writeln!(stdout, "🔄 Recording paper order...")?;
+            let broker = PaperBrokerConfig { pool: pool.clone() }
+                .try_into_broker()
+                .await?;
+            let placement = broker.place_market_order(market_order).await?;
+            writeln!(
+                stdout,
+                "✅ Paper order recorded with ID: {}",
+                placement.order_id
+            )?;
+            Ok(placement)
+        }
+    }
+}
+
+async fn reconcile<W: Write>(
+    config: &Config,
+    pool: &SqlitePool,
+    lookback_hours: u32,
+    dry_run: bool,
+    stdout: &mut W,
+) -> anyhow::Result<()> {
+    let BrokerConfig::Schwab(schwab_auth) = &config.broker else {
+        anyhow::bail!("Reconcile command is only supported for Schwab broker")
+    };
+
+    let broker = SchwabConfig {
+        auth: schwab_auth.clone(),
+        pool: pool.clone(),
+    }
+    .try_into_broker()
+    .await?;
+    let since = chrono::Utc::now() - chrono::Duration::hours(i64::from(lookback_hours));
+    let orders = broker.get_order_history(since).await?;
+
+    let summary = reconcile_executions(pool, &orders, dry_run).await?;
+    write_reconcile_summary(&summary, orders.len(), dry_run, stdout)
+}
+
+fn write_reconcile_summary<W: Write>(
+    summary: &ReconcileSummary,
+    orders: usize,
+    dry_run: bool,
+    stdout: &mut W,
+) -> anyhow::Result<()> {
+    writeln!(
+        stdout,
+        "🔍 Checked {} open executions against {orders} Schwab orders",
+        summary.executions_checked
+    )?;
+
+    for discrepancy in &summary.discrepancies {
+        writeln!(
+            stdout,
+            "   Execution {} ({}): recorded {}, order {} is {}",
+            discrepancy.execution_id,
+            discrepancy.symbol,
+            discrepancy.recorded.as_str(),
+            discrepancy.order_id,
+            discrepancy.broker_state.status().as_str()
+        )?;
+    }
+
+    if !summary.unmatched.is_empty() {
+        writeln!(
+            stdout,
+            "   No matching order for executions: {:?}",
+            summary.unmatched
+        )?;
+    }
+
+    if dry_run {
+        writeln!(
+            stdout,
+            "Dry run: {} discrepancies found, none corrected",
+            summary.discrepancies.len()
+        )?;
+    } else {
+        writeln!(
+            stdout,
+            "✅ {} discrepancies found, {} corrected",
+            summary.discrepancies.len(),
+            summary.corrected
+        )?;
+    }
+
+    Ok(())
+}
+
+async fn cancel_execution(
+    config: &Config,
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> anyhow::Result<()> {
+    match &config.broker {
+        BrokerConfig::Schwab(schwab_auth) => {
+            let broker = SchwabConfig {
+                auth: schwab_auth.clone(),
+                pool: pool.clone(),
+            }
+            .try_into_broker()
+            .await?;
+            cancel_offchain_execution(&broker, pool, execution_id).await?;
+        }
+        BrokerConfig::Alpaca(alpaca_auth) => {
+            let broker = alpaca_auth.clone().try_into_broker().await?;
+            cancel_offchain_execution(&broker, pool, execution_id).await?;
+        }
+        BrokerConfig::DryRun => {
+            let broker = MockBrokerConfig.try_into_broker().await?;
+            cancel_offchain_execution(&broker, pool, execution_id).await?;
+        }
+        BrokerConfig::Sim(sim_config) => {
+            let broker = sim_config.clone().try_into_broker().await?;
+            cancel_offchain_execution(&broker, pool, execution_id).await?;
+        }
+        BrokerConfig::Paper => {
+            let broker = PaperBrokerConfig { pool: pool.clone() }
+                .try_into_broker()
+                .await?;
+            cancel_offchain_execution(&broker, pool, execution_id).await?;
+        }
     }
+
+    Ok(())
 }
 
 async fn process_found_trade<W: Write>(
@@ -429,16 +912,35 @@ async fn process_found_trade<W: Write>(
 
     writeln!(stdout, "🔄 Processing trade with TradeAccumulator...")?;
 
+    // Manually processed transactions are not capped by the ADV limit, only by
+    // the net position limits, and are placed as whole-share market orders
+    let liquidity = LiquidityLimits::default().with_position_limits(config.position_limits.clone());
+    let accumulate = config.accumulates(onchain_trade.symbol.base());
     let mut sql_tx = pool.begin().await?;
-    let execution = accumulator::process_onchain_trade(
+    let executions = accumulator::process_onchain_trade(
         &mut sql_tx,
         onchain_trade,
-        config.broker.to_supported_broker(),
+        config.execution_rules(config.broker.to_supported_broker(), false, &liquidity),
+        accumulate,
+        config.standby.is_standby(),
+        config.strategy_label.as_deref(),
     )
     .await?;
     sql_tx.commit().await?;
 
-    if let Some(execution) = execution {
+    if executions.is_empty() {
+        writeln!(
+            stdout,
+            "📊 Trade accumulated but did not trigger execution yet."
+        )?;
+        writeln!(
+            stdout,
+            "   (Waiting to accumulate enough shares for a whole share execution)"
+        )?;
+        return Ok(());
+    }
+
+    for execution in executions {
         let execution_id = execution
             .id
             .ok_or_else(|| anyhow::anyhow!("OffchainExecution missing ID after accumulation"))?;
@@ -452,6 +954,7 @@ async fn process_found_trade<W: Write>(
             symbol: execution.symbol,
             shares: execution.shares,
             direction: execution.direction,
+            client_order_id: find_client_order_id(pool, execution_id).await?,
         };
 
         let placement = execute_broker_order(config, pool, market_order, stdout).await?;
@@ -465,18 +968,14 @@ async fn process_found_trade<W: Write>(
             .store_update(&mut sql_tx, execution_id)
             .await?;
         sql_tx.commit().await?;
-        writeln!(stdout, "🎯 Trade processing completed!")?;
-    } else {
-        writeln!(
-            stdout,
-            "📊 Trade accumulated but did not trigger execution yet."
-        )?;
-        writeln!(
-            stdout,
-            "   (Waiting to accumulate enough shares for a whole share execution)"
-        )?;
+
+        if let Some(request_payload) = &placement.request_payload {
+            save_request_payload(pool, execution_id, request_payload).await?;
+        }
     }
 
+    writeln!(stdout, "🎯 Trade processing completed!")?;
+
     Ok(())
 }
 
@@ -504,27 +1003,29 @@ fn display_trade_details<W: Write>(
 mod tests {
     use super::*;
     use crate::bindings::IERC20::symbolCall;
-    use crate::bindings::IOrderBookV4::{AfterClear, ClearConfig, ClearStateChange, ClearV2};
-    use crate::env::LogLevel;
+    use crate::env::{LogFormat, LogLevel};
+    use crate::offchain::blackout::BlackoutCalendar;
     use crate::offchain::execution::find_executions_by_symbol_status_and_broker;
-    use crate::onchain::EvmEnv;
+    use crate::offchain::trade_side::TradeSide;
+    use crate::onchain::io::SymbolConvention;
+    use crate::onchain::price_source::PriceSource;
     use crate::onchain::trade::OnchainTrade;
-    use crate::test_utils::get_test_order;
+    use crate::onchain::{DEFAULT_BACKFILL_BATCH_SIZE, EvmEnv};
     use crate::test_utils::setup_test_db;
     use crate::test_utils::setup_test_tokens;
+    use crate::test_utils::{MockBlockchainData, create_mock_blockchain_data};
     use crate::tokenized_symbol;
-    use alloy::hex;
-    use alloy::primitives::{FixedBytes, IntoLogData, U256, address, fixed_bytes};
+    use alloy::primitives::{FixedBytes, address, fixed_bytes};
     use alloy::providers::mock::Asserter;
-    use alloy::sol_types::{SolCall, SolEvent};
+    use alloy::sol_types::SolCall;
     use chrono::{Duration, Utc};
     use clap::CommandFactory;
     use httpmock::MockServer;
+    use rust_decimal_macros::dec;
     use serde_json::json;
     use st0x_broker::Direction;
     use st0x_broker::OrderStatus;
     use st0x_broker::schwab::SchwabAuthEnv;
-    use std::str::FromStr;
 
     const TEST_ENCRYPTION_KEY: FixedBytes<32> = FixedBytes::ZERO;
 
@@ -982,13 +1483,17 @@ mod tests {
     fn create_test_config_for_cli(mock_server: &MockServer) -> Config {
         Config {
             database_url: ":memory:".to_string(),
+            read_database_url: None,
             log_level: LogLevel::Debug,
+            log_format: LogFormat::Pretty,
             server_port: 8080,
             evm: EvmEnv {
                 ws_rpc_url: url::Url::parse("ws://localhost:8545").unwrap(),
                 orderbook: address!("0x1234567890123456789012345678901234567890"),
                 order_owner: address!("0x0000000000000000000000000000000000000000"),
                 deployment_block: 1,
+                symbol_convention: SymbolConvention::default(),
+                backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
             },
             order_polling_interval: 15,
             order_polling_max_jitter: 5,
@@ -998,100 +1503,63 @@ mod tests {
                 schwab_redirect_uri: "https://127.0.0.1".to_string(),
                 schwab_base_url: mock_server.base_url(),
                 schwab_account_index: 0,
+                schwab_alert_on_manual_review: false,
+                schwab_market_hours_min_interval_secs: 0,
+                schwab_order_batch_lookback_hours: None,
+                schwab_persist_order_payloads: false,
+                schwab_fractional_shares: false,
+                price_rounding: st0x_broker::PriceRounding::HalfEven,
                 encryption_key: TEST_ENCRYPTION_KEY,
             }),
+            price_sources: vec![PriceSource::OnchainRatio],
+            blackout: BlackoutCalendar::default(),
+            order_polling_dust_notional: None,
+            order_polling_dust_every: 4,
+            max_order_age: None,
+            execution_dedup_window: None,
+            liquidity: None,
+            trade_side: TradeSide::Both,
+            share_rounding: crate::onchain::position_calculator::ShareRounding::Truncate,
+            shutdown_drain_timeout: std::time::Duration::from_secs(30),
+            symbol_fallback: None,
+            max_concurrent_executions: std::num::NonZeroUsize::new(4).unwrap(),
+            max_concurrent_symbols: std::num::NonZeroUsize::new(4).unwrap(),
+            max_open_executions: None,
+            position_limits: crate::offchain::position_limit::PositionLimits::default(),
+            circuit_breakers: crate::conductor::circuit_breaker::SymbolCircuitBreakers::default(),
+            execution_batch_window: None,
+            execution_debounce: None,
+            strategy_label: None,
+            max_pyth_confidence_bps: None,
+            max_pyth_price_divergence_pct: None,
+            queue_poll_min_delay: std::time::Duration::from_millis(100),
+            queue_poll_max_delay: std::time::Duration::from_secs(30),
+            failover: None,
+            ws_connect_attempts: std::num::NonZeroUsize::new(1).unwrap(),
+            startup_canary: None,
+            contract_code_check: crate::onchain::contract_code::ContractCodeCheck::None,
+            persist_last_seen_block: false,
+            standby: crate::standby::Standby::default(),
+            trade_feed: crate::trade_feed::TradeFeed::default(),
+            order_notifier: crate::order_notifier::WebhookNotifier::default(),
+            record_conversion_outcomes: false,
+            inline_reporter_interval: None,
+            hedge_event_types: crate::onchain::hedge_events::HedgeEventType::ALL.to_vec(),
+            invert_direction: vec![],
+            no_accumulate: vec![],
+            symbol_filter: crate::symbol::filter::SymbolFilter::default(),
+            symbol_cache_capacity: None,
+            symbol_configuration_mode: crate::onchain::io::SymbolConfigurationMode::Lenient,
+            rpc_metrics: false,
+            locked_retry: crate::db_retry::LockedRetryPolicy::default(),
+            end_of_day_settlement: crate::offchain::end_of_day::EndOfDaySettlement::Hold,
+            end_of_day_settlement_lead: std::time::Duration::from_secs(300),
+            vacuum_interval: None,
+            event_priorities: crate::queue::EventPriorities::default(),
             hyperdx: None,
         }
     }
 
-    struct MockBlockchainData {
-        order_owner: alloy::primitives::Address,
-        receipt_json: serde_json::Value,
-        after_clear_log: alloy::rpc::types::Log,
-    }
-
-    fn create_mock_blockchain_data(
-        orderbook: alloy::primitives::Address,
-        tx_hash: alloy::primitives::B256,
-        alice_output_shares: &str, // e.g., "9000000000000000000" for 9 shares
-        bob_output_usdc: u64,      // e.g., 100_000_000 for 100 USDC
-    ) -> MockBlockchainData {
-        let order = get_test_order();
-        let order_owner = order.owner;
-
-        let clear_event = ClearV2 {
-            sender: address!("0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef"),
-            alice: order.clone(),
-            bob: order,
-            clearConfig: ClearConfig {
-                aliceInputIOIndex: U256::from(0),
-                aliceOutputIOIndex: U256::from(1),
-                bobInputIOIndex: U256::from(1),
-                bobOutputIOIndex: U256::from(0),
-                aliceBountyVaultId: U256::ZERO,
-                bobBountyVaultId: U256::ZERO,
-            },
-        };
-
-        let receipt_json = json!({
-            "transactionHash": tx_hash,
-            "transactionIndex": "0x0",
-            "blockHash": "0x1111111111111111111111111111111111111111111111111111111111111111",
-            "blockNumber": "0x64",
-            "from": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
-            "to": "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
-            "contractAddress": null,
-            "gasUsed": "0x5208",
-            "cumulativeGasUsed": "0xf4240",
-            "effectiveGasPrice": "0x3b9aca00",
-            "status": "0x1",
-            "type": "0x2",
-            "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
-            "logs": [{
-                "address": orderbook,
-                "topics": [ClearV2::SIGNATURE_HASH],
-                "data": format!("0x{}", hex::encode(clear_event.into_log_data().data)),
-                "blockNumber": "0x64",
-                "transactionHash": tx_hash,
-                "transactionIndex": "0x0",
-                "logIndex": "0x0",
-                "removed": false
-            }]
-        });
-
-        let after_clear_event = AfterClear {
-            sender: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
-            clearStateChange: ClearStateChange {
-                aliceOutput: U256::from_str(alice_output_shares).unwrap(),
-                bobOutput: U256::from(bob_output_usdc),
-                aliceInput: U256::from(bob_output_usdc),
-                bobInput: U256::from_str(alice_output_shares).unwrap(),
-            },
-        };
-
-        let after_clear_log = alloy::rpc::types::Log {
-            inner: alloy::primitives::Log {
-                address: orderbook,
-                data: after_clear_event.into_log_data(),
-            },
-            block_hash: Some(fixed_bytes!(
-                "0x1111111111111111111111111111111111111111111111111111111111111111"
-            )),
-            block_number: Some(100),
-            block_timestamp: None,
-            transaction_hash: Some(tx_hash),
-            transaction_index: Some(0),
-            log_index: Some(1),
-            removed: false,
-        };
-
-        MockBlockchainData {
-            order_owner,
-            receipt_json,
-            after_clear_log,
-        }
-    }
-
     fn setup_mock_provider_for_process_tx(
         mock_data: &MockBlockchainData,
         input_symbol: &str,
@@ -1133,9 +1601,23 @@ mod tests {
                 .header("location", "/trader/v1/accounts/ABC123DEF456/orders/12345");
         });
 
+        setup_order_lookup_mock(server);
+
         (account_mock, order_mock)
     }
 
+    /// Mocks the recent orders query made before placing an order with a
+    /// client order id, finding no order placed under it yet.
+    fn setup_order_lookup_mock(server: &MockServer) -> httpmock::Mock<'_> {
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([]));
+        })
+    }
+
     #[test]
     fn test_validate_ticker_valid() {
         assert_eq!(validate_ticker("AAPL").unwrap(), "AAPL");
@@ -1597,6 +2079,40 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_process_txs_reports_each_transaction() {
+        let server = MockServer::start();
+        let config = create_test_config_for_cli(&server);
+        let pool = setup_test_db().await;
+
+        let first_hash =
+            fixed_bytes!("0xbeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+        let second_hash =
+            fixed_bytes!("0xceeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+        let mut stdout = Vec::new();
+
+        let asserter = Asserter::new();
+        asserter.push_success(&json!(null));
+        asserter.push_success(&json!(null));
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let cache = SymbolCache::default();
+
+        process_txs_with_provider(
+            vec![first_hash, second_hash],
+            &config,
+            &pool,
+            &mut stdout,
+            &provider,
+            &cache,
+        )
+        .await
+        .unwrap();
+
+        let stdout_str = String::from_utf8(stdout).unwrap();
+        assert!(stdout_str.contains(&format!("Error processing transaction {first_hash}")));
+        assert!(stdout_str.contains(&format!("Error processing transaction {second_hash}")));
+    }
+
     #[tokio::test]
     async fn test_integration_invalid_order_parameters() {
         let server = MockServer::start();
@@ -1699,7 +2215,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(trade.symbol.to_string(), "AAPL0x"); // Tokenized symbol
-        assert!((trade.amount - 9.0).abs() < f64::EPSILON); // Amount from the test data
+        assert_eq!(trade.amount, dec!(9.0)); // Amount from the test data
 
         // Verify OffchainExecution was created (due to TradeAccumulator)
         // Executions are now in SUBMITTED status with order_id stored for order status polling
@@ -1730,7 +2246,8 @@ mod tests {
         );
 
         // Verify Schwab API was called
-        account_mock.assert();
+        // Account hash is fetched for the client order id lookup and the placement
+        account_mock.assert_hits(2);
         order_mock.assert();
 
         // Verify stdout output
@@ -1740,6 +2257,258 @@ mod tests {
         assert!(stdout_str.contains("Trade processing completed"));
     }
 
+    #[tokio::test]
+    async fn test_process_tx_persists_order_payload_sent_to_schwab() {
+        let server = MockServer::start();
+        let mut config = create_test_config_for_cli(&server);
+        let BrokerConfig::Schwab(schwab_auth) = &mut config.broker else {
+            unreachable!()
+        };
+        schwab_auth.schwab_persist_order_payloads = true;
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, get_schwab_auth_from_config(&config)).await;
+
+        let tx_hash =
+            fixed_bytes!("0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef");
+        let mock_data = create_mock_blockchain_data(
+            config.evm.orderbook,
+            tx_hash,
+            "9000000000000000000",
+            100_000_000,
+        );
+        config.evm.order_owner = mock_data.order_owner;
+
+        let expected_payload = json!({
+            "orderType": "MARKET",
+            "session": "NORMAL",
+            "duration": "DAY",
+            "orderStrategyType": "SINGLE",
+            "orderLegCollection": [{
+                "instruction": "BUY",
+                "quantity": 9,
+                "instrument": {"symbol": "AAPL", "assetType": "EQUITY"}
+            }],
+            "tag": "st0x-AAPL-1"
+        });
+
+        let account_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+        let lookup_mock = setup_order_lookup_mock(&server);
+        let order_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/trader/v1/accounts/ABC123DEF456/orders")
+                .json_body(expected_payload.clone());
+            then.status(201)
+                .header("location", "/trader/v1/accounts/ABC123DEF456/orders/12345");
+        });
+
+        let provider = setup_mock_provider_for_process_tx(&mock_data, "USDC", "AAPL0x");
+        let mut stdout = Vec::new();
+
+        process_tx_with_provider(
+            tx_hash,
+            &config,
+            &pool,
+            &mut stdout,
+            &provider,
+            &SymbolCache::default(),
+        )
+        .await
+        .unwrap();
+
+        // Account hash is fetched for the client order id lookup and the placement
+        account_mock.assert_hits(2);
+        lookup_mock.assert();
+        order_mock.assert();
+
+        let row = sqlx::query!("SELECT request_payload FROM offchain_trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let stored_payload: serde_json::Value =
+            serde_json::from_str(&row.request_payload.unwrap()).unwrap();
+        assert_eq!(stored_payload, expected_payload);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_promotes_missed_fill() {
+        let server = MockServer::start();
+        let config = create_test_config_for_cli(&server);
+        let pool = setup_test_db().await;
+        setup_test_tokens(&pool, get_schwab_auth_from_config(&config)).await;
+
+        let mut execution = crate::test_utils::OffchainExecutionBuilder::new().build();
+        execution.state = OrderState::Submitted {
+            order_id: "1004055538123".to_string(),
+        };
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/accountNumbers");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "accountNumber": "123456789",
+                    "hashValue": "ABC123DEF456"
+                }]));
+        });
+        let history_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/trader/v1/accounts/ABC123DEF456/orders");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([{
+                    "orderId": 1_004_055_538_123_i64,
+                    "status": "FILLED",
+                    "closeTime": "2025-10-30T14:30:02+0000",
+                    "orderActivityCollection": [{
+                        "activityType": "EXECUTION",
+                        "executionLegs": [{"quantity": 100.0, "price": 150.25}]
+                    }]
+                }]));
+        });
+
+        let mut stdout = Vec::new();
+        run_command_with_writers(
+            config.clone(),
+            Commands::Reconcile {
+                lookback_hours: 24,
+                dry_run: true,
+            },
+            &pool,
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        let output = String::from_utf8(stdout).unwrap();
+        assert!(output.contains(&format!(
+            "Execution {execution_id} (AAPL): recorded SUBMITTED, order 1004055538123 is FILLED"
+        )));
+        assert!(output.contains("Dry run: 1 discrepancies found, none corrected"));
+        let execution = crate::offchain::execution::find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.state.status(), OrderStatus::Submitted);
+
+        let mut stdout = Vec::new();
+        run_command_with_writers(
+            config,
+            Commands::Reconcile {
+                lookback_hours: 24,
+                dry_run: false,
+            },
+            &pool,
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        assert!(
+            String::from_utf8(stdout)
+                .unwrap()
+                .contains("1 discrepancies found, 1 corrected")
+        );
+        history_mock.assert_hits(2);
+
+        let execution = crate::offchain::execution::find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let OrderState::Filled {
+            price_cents,
+            executed_at,
+            ..
+        } = execution.state
+        else {
+            panic!("Expected a filled execution, got {:?}", execution.state);
+        };
+        assert_eq!(price_cents, 15025);
+        assert_eq!(
+            executed_at,
+            "2025-10-30T14:30:02Z"
+                .parse::<chrono::DateTime<Utc>>()
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_show_accumulation_after_fractional_trade() {
+        let server = MockServer::start();
+        let config = create_test_config_for_cli(&server);
+        let pool = setup_test_db().await;
+
+        let mut stdout = Vec::new();
+        run_command_with_writers(
+            config.clone(),
+            Commands::ShowAccumulation {
+                ticker: "aapl".to_string(),
+            },
+            &pool,
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        assert!(
+            String::from_utf8(stdout)
+                .unwrap()
+                .contains("No accumulated position for AAPL")
+        );
+
+        let tx_hash =
+            fixed_bytes!("0xfeedbeeffeedbeeffeedbeeffeedbeeffeedbeeffeedbeeffeedbeeffeedbeef");
+
+        // 2.5 shares: two execute, half a share stays accumulated
+        let mock_data = create_mock_blockchain_data(
+            config.evm.orderbook,
+            tx_hash,
+            "2500000000000000000",
+            250_000_000,
+        );
+
+        let mut config = config;
+        config.evm.order_owner = mock_data.order_owner;
+        setup_test_tokens(&pool, get_schwab_auth_from_config(&config)).await;
+        let (_account_mock, _order_mock) = setup_schwab_api_mocks(&server);
+
+        let provider = setup_mock_provider_for_process_tx(&mock_data, "USDC", "AAPL0x");
+        let cache = SymbolCache::default();
+        process_tx_with_provider(tx_hash, &config, &pool, &mut Vec::new(), &provider, &cache)
+            .await
+            .unwrap();
+
+        let mut stdout = Vec::new();
+        run_command_with_writers(
+            config,
+            Commands::ShowAccumulation {
+                ticker: "AAPL".to_string(),
+            },
+            &pool,
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+
+        let stdout_str = String::from_utf8(stdout).unwrap();
+        assert!(stdout_str.contains("Accumulated position for AAPL"));
+        assert!(stdout_str.contains("Whole shares: 0"));
+        assert!(stdout_str.contains("Fractional remainder: 0.5"));
+        assert!(stdout_str.contains("Direction: BUY"));
+    }
+
     #[tokio::test]
     async fn test_process_tx_database_duplicate_handling() {
         let server = MockServer::start();
@@ -1763,6 +2532,7 @@ mod tests {
         config.evm.order_owner = mock_data.order_owner;
 
         // Set up Schwab API mocks for first call
+        setup_order_lookup_mock(&server);
         let account_mock = server.mock(|when, then| {
             when.method(httpmock::Method::GET)
                 .path("/trader/v1/accounts/accountNumbers");
@@ -1816,7 +2586,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(trade.symbol.to_string(), "TSLA0x"); // Tokenized symbol
-        assert!((trade.amount - 5.0).abs() < f64::EPSILON); // Amount from the test data
+        assert_eq!(trade.amount, dec!(5.0)); // Amount from the test data
 
         // Verify stdout output for first call
         let stdout_str1 = String::from_utf8(stdout1).unwrap();
@@ -1860,8 +2630,9 @@ mod tests {
         assert!(stdout_str2.contains("Trade accumulated but did not trigger execution yet"));
 
         // Since the duplicate is handled gracefully and doesn't trigger a new execution,
-        // the Schwab API should still only be called once (for the first trade)
-        account_mock.assert_hits(1);
+        // the Schwab API should still only be called for the first trade, whose
+        // account hash is fetched for the client order id lookup and the placement
+        account_mock.assert_hits(2);
         order_mock.assert_hits(1);
     }
 
@@ -1887,10 +2658,11 @@ mod tests {
             id: None,
             tx_hash,
             log_index: 42,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("GOOG0x"),
-            amount: 2.5,
+            amount: dec!(2.5),
             direction: Direction::Buy,
-            price_usdc: 20000.0,
+            price_usdc: dec!(20000.0),
             block_timestamp: None,
             created_at: None,
             gas_used: None,
@@ -1925,7 +2697,135 @@ mod tests {
         assert_eq!(trade.tx_hash, tx_hash);
         assert_eq!(trade.log_index, 42);
         assert_eq!(trade.symbol.to_string(), "GOOG0x");
-        assert!((trade.amount - 2.5).abs() < f64::EPSILON);
-        assert!((trade.price_usdc - 20000.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(2.5));
+        assert_eq!(trade.price_usdc, dec!(20000.0));
+    }
+
+    /// Queues a clear between two orders the bot does not own, which every
+    /// conversion filters out without calling the provider.
+    async fn enqueue_unowned_clear(pool: &SqlitePool) {
+        let mut alice = crate::test_utils::get_test_order();
+        alice.owner = address!("0x1111111111111111111111111111111111111111");
+        let mut bob = crate::test_utils::get_test_order();
+        bob.owner = address!("0x2222222222222222222222222222222222222222");
+
+        let clear_event = crate::bindings::IOrderBookV4::ClearV2 {
+            sender: address!("0x3333333333333333333333333333333333333333"),
+            alice,
+            bob,
+            clearConfig: crate::bindings::IOrderBookV4::ClearConfig {
+                aliceInputIOIndex: alloy::primitives::U256::from(0),
+                aliceOutputIOIndex: alloy::primitives::U256::from(1),
+                bobInputIOIndex: alloy::primitives::U256::from(1),
+                bobOutputIOIndex: alloy::primitives::U256::from(0),
+                aliceBountyVaultId: alloy::primitives::U256::ZERO,
+                bobBountyVaultId: alloy::primitives::U256::ZERO,
+            },
+        };
+
+        crate::queue::enqueue(pool, &clear_event, &crate::test_utils::get_test_log())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queue_list_and_show() {
+        let server = MockServer::start();
+        let config = create_test_config_for_cli(&server);
+        let pool = setup_test_db().await;
+        enqueue_unowned_clear(&pool).await;
+        let log = crate::test_utils::get_test_log();
+
+        let mut stdout = Vec::new();
+        run_command_with_writers(
+            config.clone(),
+            Commands::Queue {
+                command: QueueCommand::List,
+            },
+            &pool,
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        let output = String::from_utf8(stdout).unwrap();
+        assert!(output.contains("1 unprocessed events"));
+        assert!(output.contains(&format!(
+            "#1 block={} log_index={} tx_hash={} type=ClearV2",
+            log.block_number.unwrap(),
+            log.log_index.unwrap(),
+            log.transaction_hash.unwrap()
+        )));
+
+        let mut stdout = Vec::new();
+        run_command_with_writers(
+            config.clone(),
+            Commands::Queue {
+                command: QueueCommand::Show { event_id: 1 },
+            },
+            &pool,
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        let output = String::from_utf8(stdout).unwrap();
+        assert!(output.contains("Event #1 (ClearV2)"));
+        assert!(output.contains("\"clearConfig\""));
+
+        let error = run_command_with_writers(
+            config,
+            Commands::Queue {
+                command: QueueCommand::Show { event_id: 2 },
+            },
+            &pool,
+            &mut Vec::new(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Queued event 2 not found");
+    }
+
+    #[tokio::test]
+    async fn test_queue_replay_only_processes_event_with_commit() {
+        let server = MockServer::start();
+        let mut config = create_test_config_for_cli(&server);
+        config.broker = BrokerConfig::DryRun;
+        let pool = setup_test_db().await;
+        enqueue_unowned_clear(&pool).await;
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+        let queued_event = find_queued_event(&pool, 1).await.unwrap();
+
+        let mut stdout = Vec::new();
+        replay_with_provider(&config, &pool, &queued_event, false, &provider, &mut stdout)
+            .await
+            .unwrap();
+        let output = String::from_utf8(stdout).unwrap();
+        assert!(output.contains("Event would be filtered out: no matching owner"));
+        assert!(output.contains("Dry run: event left unprocessed"));
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 1);
+
+        let mut stdout = Vec::new();
+        replay_with_provider(&config, &pool, &queued_event, true, &provider, &mut stdout)
+            .await
+            .unwrap();
+        assert!(
+            String::from_utf8(stdout)
+                .unwrap()
+                .contains("✅ Event processed")
+        );
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 0);
+
+        // A processed event can still be inspected, but not committed again
+        let queued_event = find_queued_event(&pool, 1).await.unwrap();
+        assert!(queued_event.processed);
+        replay_with_provider(
+            &config,
+            &pool,
+            &queued_event,
+            true,
+            &provider,
+            &mut Vec::new(),
+        )
+        .await
+        .unwrap_err();
     }
 }
@@ -0,0 +1,172 @@
+//! Batching window for executions created by the queue processor.
+//!
+//! Without a window, each execution is placed with the broker as soon as the
+//! event that triggered it is processed. With one, the first execution opens a
+//! batch, executions created while it is open join it, and once the window has
+//! elapsed they are all placed together, trading a little latency for bursts of
+//! orders instead of a trickle. A drained queue processor places its open batch
+//! before stopping, but executions left in a batch when the trading tasks are
+//! aborted stay pending until the stale execution cleanup releases them.
+
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use st0x_broker::Broker;
+
+use super::circuit_breaker::SymbolCircuitBreakers;
+use super::execute_waiting_out_rate_limits;
+use crate::db_retry::LockedRetryPolicy;
+use crate::stats::Stats;
+
+/// Executions collected since the current batch opened.
+#[derive(Debug)]
+pub(crate) struct ExecutionBatch {
+    window: Duration,
+    opened_at: Option<Instant>,
+    execution_ids: Vec<i64>,
+}
+
+impl ExecutionBatch {
+    pub(crate) const fn new(window: Duration) -> Self {
+        Self {
+            window,
+            opened_at: None,
+            execution_ids: Vec::new(),
+        }
+    }
+
+    /// Adds an execution, opening a batch at `now` if none is open.
+    pub(crate) fn push(&mut self, execution_id: i64, now: Instant) {
+        self.opened_at.get_or_insert(now);
+        self.execution_ids.push(execution_id);
+    }
+
+    /// Takes the collected executions once the window has elapsed at `now`.
+    pub(crate) fn take_if_due(&mut self, now: Instant) -> Option<Vec<i64>> {
+        let opened_at = self.opened_at?;
+        if now.duration_since(opened_at) < self.window {
+            return None;
+        }
+
+        self.opened_at = None;
+        Some(std::mem::take(&mut self.execution_ids))
+    }
+
+    /// Takes the collected executions regardless of the window, if any.
+    pub(crate) fn take_all(&mut self) -> Option<Vec<i64>> {
+        self.opened_at.take()?;
+        Some(std::mem::take(&mut self.execution_ids))
+    }
+}
+
+/// Places every execution in a batch with the broker concurrently, waiting
+/// out broker rate limits until `drain` is cancelled and logging each
+/// failure.
+pub(crate) async fn dispatch_batch<B: Broker + Clone + Send + 'static>(
+    broker: &B,
+    pool: &SqlitePool,
+    stats: &Stats,
+    circuit_breakers: &SymbolCircuitBreakers,
+    locked_retry: LockedRetryPolicy,
+    execution_ids: Vec<i64>,
+    drain: &CancellationToken,
+) {
+    info!(
+        "Dispatching batch of {} executions: {execution_ids:?}",
+        execution_ids.len()
+    );
+
+    let placements = execution_ids.iter().map(|&execution_id| async move {
+        let result = execute_waiting_out_rate_limits(
+            broker,
+            pool,
+            stats,
+            circuit_breakers,
+            locked_retry,
+            execution_id,
+            drain,
+        )
+        .await;
+        (execution_id, result)
+    });
+
+    for (execution_id, result) in join_all(placements).await {
+        if let Err(e) = result {
+            error!("Failed to execute offchain order {execution_id}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{OffchainExecutionBuilder, setup_test_db};
+    use st0x_broker::{MockBroker, Symbol};
+
+    const WINDOW: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn test_executions_within_window_are_taken_together() {
+        let opened = Instant::now();
+        let mut batch = ExecutionBatch::new(WINDOW);
+
+        batch.push(1, opened);
+        batch.push(2, opened + WINDOW / 2);
+        batch.push(3, opened + WINDOW / 2);
+        assert_eq!(batch.take_if_due(opened + WINDOW / 2), None);
+
+        assert_eq!(batch.take_if_due(opened + WINDOW), Some(vec![1, 2, 3]));
+
+        // The next execution opens a new batch
+        let reopened = opened + WINDOW * 2;
+        batch.push(4, reopened);
+        assert_eq!(batch.take_if_due(reopened), None);
+        assert_eq!(batch.take_if_due(reopened + WINDOW), Some(vec![4]));
+    }
+
+    #[test]
+    fn test_empty_batch_is_never_due() {
+        let mut batch = ExecutionBatch::new(WINDOW);
+
+        assert_eq!(batch.take_if_due(Instant::now() + WINDOW * 10), None);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_places_batch_concurrently() {
+        let pool = setup_test_db().await;
+        let broker = MockBroker::new().with_order_latency(Duration::from_millis(50));
+        let stats = Stats::default();
+
+        let mut execution_ids = Vec::new();
+        for symbol in ["AAPL", "MSFT", "TSLA"] {
+            let mut execution = OffchainExecutionBuilder::new().build();
+            execution.symbol = Symbol::new(symbol).unwrap();
+            let mut sql_tx = pool.begin().await.unwrap();
+            execution_ids.push(
+                execution
+                    .save_within_transaction(&mut sql_tx)
+                    .await
+                    .unwrap(),
+            );
+            sql_tx.commit().await.unwrap();
+        }
+
+        dispatch_batch(
+            &broker,
+            &pool,
+            &stats,
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            execution_ids,
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert_eq!(stats.snapshot().executions_placed, 3);
+        assert_eq!(broker.peak_orders_in_flight(), 3);
+    }
+}
@@ -3,8 +3,11 @@ use alloy::rpc::types::Log;
 use alloy::sol_types;
 use futures_util::Stream;
 use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 
 use st0x_broker::Broker;
@@ -12,20 +15,23 @@ use st0x_broker::Broker;
 use crate::bindings::IOrderBookV4::{ClearV2, TakeOrderV2};
 use crate::env::Config;
 use crate::onchain::trade::TradeEvent;
+use crate::stats::Stats;
 use crate::symbol::cache::SymbolCache;
 
 use super::{
-    Conductor, spawn_event_processor, spawn_onchain_event_receiver, spawn_order_poller,
-    spawn_periodic_accumulated_position_check, spawn_queue_processor,
+    Conductor, QueueProcessor, Resubscribe, spawn_event_processor, spawn_onchain_event_receiver,
+    spawn_order_poller, spawn_periodic_accumulated_position_check, spawn_queue_processor,
 };
 
-type ClearStream = Box<dyn Stream<Item = Result<(ClearV2, Log), sol_types::Error>> + Unpin + Send>;
-type TakeStream =
+pub(crate) type ClearStream =
+    Box<dyn Stream<Item = Result<(ClearV2, Log), sol_types::Error>> + Unpin + Send>;
+pub(crate) type TakeStream =
     Box<dyn Stream<Item = Result<(TakeOrderV2, Log), sol_types::Error>> + Unpin + Send>;
 
 struct CommonFields<P, B> {
     config: Config,
     pool: SqlitePool,
+    stats: Arc<Stats>,
     cache: SymbolCache,
     provider: P,
     broker: B,
@@ -37,10 +43,11 @@ pub(crate) struct WithBrokerMaintenance {
     broker_maintenance: Option<JoinHandle<()>>,
 }
 
-pub(crate) struct WithDexStreams {
+pub(crate) struct WithDexStreams<P> {
     broker_maintenance: Option<JoinHandle<()>>,
     clear_stream: ClearStream,
     take_stream: TakeStream,
+    resubscribe: Option<Resubscribe<P>>,
     event_sender: UnboundedSender<(TradeEvent, Log)>,
     event_receiver: UnboundedReceiver<(TradeEvent, Log)>,
 }
@@ -56,6 +63,7 @@ impl<P: Provider + Clone + Send + 'static, B: Broker + Clone + Send + 'static>
     pub(crate) fn new(
         config: Config,
         pool: SqlitePool,
+        stats: Arc<Stats>,
         cache: SymbolCache,
         provider: P,
         broker: B,
@@ -64,6 +72,7 @@ impl<P: Provider + Clone + Send + 'static, B: Broker + Clone + Send + 'static>
             common: CommonFields {
                 config,
                 pool,
+                stats,
                 cache,
                 provider,
                 broker,
@@ -96,7 +105,7 @@ impl<P: Provider + Clone + Send + 'static, B: Broker + Clone + Send + 'static>
         + Unpin
         + Send
         + 'static,
-    ) -> ConductorBuilder<P, B, WithDexStreams> {
+    ) -> ConductorBuilder<P, B, WithDexStreams<P>> {
         let (event_sender, event_receiver) =
             tokio::sync::mpsc::unbounded_channel::<(TradeEvent, Log)>();
 
@@ -106,6 +115,7 @@ impl<P: Provider + Clone + Send + 'static, B: Broker + Clone + Send + 'static>
                 broker_maintenance: self.state.broker_maintenance,
                 clear_stream: Box::new(clear_stream),
                 take_stream: Box::new(take_stream),
+                resubscribe: None,
                 event_sender,
                 event_receiver,
             },
@@ -114,8 +124,15 @@ impl<P: Provider + Clone + Send + 'static, B: Broker + Clone + Send + 'static>
 }
 
 impl<P: Provider + Clone + Send + 'static, B: Broker + Clone + Send + 'static>
-    ConductorBuilder<P, B, WithDexStreams>
+    ConductorBuilder<P, B, WithDexStreams<P>>
 {
+    /// Resubscribes the DEX event streams through `resubscribe` whenever they
+    /// end or keep failing, instead of stopping the event receiver.
+    pub(crate) fn with_dex_resubscription(mut self, resubscribe: Resubscribe<P>) -> Self {
+        self.state.resubscribe = Some(resubscribe);
+        self
+    }
+
     pub(crate) fn spawn(self) -> Conductor {
         info!("Starting conductor orchestration");
 
@@ -131,28 +148,48 @@ impl<P: Provider + Clone + Send + 'static, B: Broker + Clone + Send + 'static>
             &self.common.config,
             &self.common.pool,
             self.common.broker.clone(),
+            self.common.stats.clone(),
         );
         let dex_event_receiver = spawn_onchain_event_receiver(
             self.state.event_sender,
             self.state.clear_stream,
             self.state.take_stream,
+            self.common.provider.clone(),
+            self.common.stats.clone(),
+            self.state.resubscribe,
+        );
+        let event_processor = spawn_event_processor(
+            self.common.pool.clone(),
+            self.common.stats.clone(),
+            self.state.event_receiver,
+            self.common.config.persist_last_seen_block,
         );
-        let event_processor =
-            spawn_event_processor(self.common.pool.clone(), self.state.event_receiver);
+        let drain = CancellationToken::new();
+        let symbol_permits = Arc::new(Semaphore::new(
+            self.common.config.max_concurrent_symbols.get(),
+        ));
         let position_checker = spawn_periodic_accumulated_position_check(
             self.common.broker.clone(),
+            self.common.config.clone(),
             self.common.pool.clone(),
+            self.common.stats.clone(),
+            symbol_permits.clone(),
+            drain.clone(),
         );
-        let queue_processor = spawn_queue_processor(
-            self.common.broker,
-            &self.common.config,
-            &self.common.pool,
-            &self.common.cache,
-            self.common.provider,
-        );
+        let queue_processor = spawn_queue_processor(QueueProcessor {
+            broker: self.common.broker,
+            config: self.common.config,
+            pool: self.common.pool,
+            cache: self.common.cache,
+            provider: self.common.provider,
+            stats: self.common.stats,
+            symbol_permits,
+            drain: drain.clone(),
+        });
 
         Conductor {
             broker_maintenance,
+            drain,
             order_poller,
             dex_event_receiver,
             event_processor,
@@ -0,0 +1,207 @@
+//! Per-symbol circuit breaker for broker order placement.
+//!
+//! When the broker keeps rejecting orders for a symbol, e.g. a halted or
+//! delisted ticker, retrying every accumulated position only piles up
+//! rejections. After `failure_threshold` consecutive placement failures within
+//! `failure_window` the breaker of the symbol opens and its executions are
+//! skipped for `cooldown`, while its trades keep accumulating. The first
+//! execution after the cooldown is a half-open probe: a success closes the
+//! breaker, a failure opens it for another cooldown.
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use st0x_broker::Symbol;
+use tracing::{info, warn};
+
+const DEFAULT_FAILURE_THRESHOLD: NonZeroU32 = match NonZeroU32::new(5) {
+    Some(threshold) => threshold,
+    None => unreachable!(),
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CircuitBreakerPolicy {
+    pub(crate) failure_threshold: NonZeroU32,
+    pub(crate) failure_window: Duration,
+    pub(crate) cooldown: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            failure_window: Duration::from_mins(10),
+            cooldown: Duration::from_mins(15),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BreakerState {
+    /// Consecutive failures since `first_failure_at`, below the threshold
+    Closed {
+        failures: u32,
+        first_failure_at: Instant,
+    },
+    Open {
+        until: Instant,
+    },
+    /// The cooldown elapsed and a probe placement is allowed through
+    HalfOpen,
+}
+
+/// Shared circuit breakers keyed by broker symbol; clones observe the same
+/// breakers. Symbols without failures have no entry.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SymbolCircuitBreakers {
+    policy: CircuitBreakerPolicy,
+    states: Arc<Mutex<HashMap<Symbol, BreakerState>>>,
+}
+
+impl SymbolCircuitBreakers {
+    pub(crate) fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            policy,
+            states: Arc::default(),
+        }
+    }
+
+    fn with_states<T>(&self, f: impl FnOnce(&mut HashMap<Symbol, BreakerState>) -> T) -> T {
+        f(&mut self.states.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Whether an order for `symbol` may be placed at `now`. An open breaker
+    /// whose cooldown has elapsed turns half-open and lets the order through
+    /// as a probe.
+    pub(crate) fn allows_placement(&self, symbol: &Symbol, now: Instant) -> bool {
+        self.with_states(|states| {
+            let Some(state) = states.get_mut(symbol) else {
+                return true;
+            };
+            let BreakerState::Open { until } = *state else {
+                return true;
+            };
+            if now < until {
+                return false;
+            }
+
+            info!("Circuit breaker for {symbol} half-open, probing with the next order");
+            *state = BreakerState::HalfOpen;
+            true
+        })
+    }
+
+    pub(crate) fn record_success(&self, symbol: &Symbol) {
+        self.with_states(|states| {
+            if let Some(BreakerState::HalfOpen | BreakerState::Open { .. }) = states.remove(symbol)
+            {
+                info!("Circuit breaker for {symbol} closed after a successful order");
+            }
+        });
+    }
+
+    pub(crate) fn record_failure(&self, symbol: &Symbol, now: Instant) {
+        let policy = self.policy;
+
+        self.with_states(|states| {
+            let (failures, first_failure_at) = match states.get(symbol) {
+                Some(&BreakerState::Closed {
+                    failures,
+                    first_failure_at,
+                }) if now.duration_since(first_failure_at) <= policy.failure_window => {
+                    (failures + 1, first_failure_at)
+                }
+                Some(BreakerState::HalfOpen | BreakerState::Open { .. }) => {
+                    warn!(
+                        "Circuit breaker probe for {symbol} failed, pausing executions for {:?}",
+                        policy.cooldown
+                    );
+                    states.insert(
+                        symbol.clone(),
+                        BreakerState::Open {
+                            until: now + policy.cooldown,
+                        },
+                    );
+                    return;
+                }
+                None | Some(BreakerState::Closed { .. }) => (1, now),
+            };
+
+            let state = if failures >= policy.failure_threshold.get() {
+                warn!(
+                    "Circuit breaker for {symbol} opened after {failures} consecutive order \
+                     failures, pausing executions for {:?}",
+                    policy.cooldown
+                );
+                BreakerState::Open {
+                    until: now + policy.cooldown,
+                }
+            } else {
+                BreakerState::Closed {
+                    failures,
+                    first_failure_at,
+                }
+            };
+            states.insert(symbol.clone(), state);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakers(failure_threshold: u32) -> SymbolCircuitBreakers {
+        SymbolCircuitBreakers::new(CircuitBreakerPolicy {
+            failure_threshold: NonZeroU32::new(failure_threshold).unwrap(),
+            failure_window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(300),
+        })
+    }
+
+    #[test]
+    fn test_breaker_opens_after_consecutive_failures_and_probes_after_cooldown() {
+        let breakers = breakers(3);
+        let aapl = Symbol::new("AAPL").unwrap();
+        let msft = Symbol::new("MSFT").unwrap();
+        let start = Instant::now();
+
+        breakers.record_failure(&aapl, start);
+        breakers.record_failure(&aapl, start + Duration::from_secs(1));
+        assert!(breakers.allows_placement(&aapl, start + Duration::from_secs(2)));
+
+        breakers.record_failure(&aapl, start + Duration::from_secs(2));
+        assert!(!breakers.allows_placement(&aapl, start + Duration::from_secs(3)));
+        assert!(breakers.allows_placement(&msft, start + Duration::from_secs(3)));
+
+        // A failed probe opens the breaker for another cooldown
+        let probe = start + Duration::from_secs(302);
+        assert!(breakers.allows_placement(&aapl, probe));
+        breakers.record_failure(&aapl, probe);
+        assert!(!breakers.allows_placement(&aapl, probe + Duration::from_secs(299)));
+
+        // A successful probe closes it
+        let probe = probe + Duration::from_secs(300);
+        assert!(breakers.allows_placement(&aapl, probe));
+        breakers.record_success(&aapl);
+        breakers.record_failure(&aapl, probe);
+        assert!(breakers.allows_placement(&aapl, probe));
+    }
+
+    #[test]
+    fn test_failures_outside_the_window_or_broken_by_a_success_do_not_open() {
+        let breakers = breakers(2);
+        let aapl = Symbol::new("AAPL").unwrap();
+        let start = Instant::now();
+
+        breakers.record_failure(&aapl, start);
+        breakers.record_failure(&aapl, start + Duration::from_secs(61));
+        assert!(breakers.allows_placement(&aapl, start + Duration::from_secs(62)));
+
+        breakers.record_success(&aapl);
+        breakers.record_failure(&aapl, start + Duration::from_secs(63));
+        assert!(breakers.allows_placement(&aapl, start + Duration::from_secs(64)));
+    }
+}
@@ -0,0 +1,98 @@
+//! Debounce window for executions of accumulated positions.
+//!
+//! Without a window, an execution is created as soon as a trade makes its
+//! symbol's position ready, so several fills of one symbol landing in the same
+//! block can each place a small order. With one, trades only accumulate: the
+//! first check that sees a symbol ready starts its window, and the position is
+//! executed, with every fill that arrived in the meantime, once the window has
+//! elapsed. Later fills never restart a running window, so a position waits at
+//! most one window for them.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use st0x_broker::Symbol;
+
+/// Shared debounce windows keyed by symbol; clones observe the same windows.
+#[derive(Debug, Clone)]
+pub(crate) struct ExecutionDebounce {
+    window: Duration,
+    ready_since: Arc<Mutex<HashMap<Symbol, Instant>>>,
+}
+
+impl ExecutionDebounce {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            ready_since: Arc::default(),
+        }
+    }
+
+    /// Starts a window at `now` for every symbol in `ready` without one,
+    /// forgets symbols that are no longer ready, and returns the ready symbols
+    /// whose window has elapsed at `now`.
+    pub(crate) fn due(&self, ready: &[Symbol], now: Instant) -> Vec<Symbol> {
+        let mut ready_since = self
+            .ready_since
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        ready_since.retain(|symbol, _| ready.contains(symbol));
+
+        let due = ready
+            .iter()
+            .filter(|symbol| {
+                let since = *ready_since.entry((*symbol).clone()).or_insert(now);
+                now.duration_since(since) >= self.window
+            })
+            .cloned()
+            .collect();
+        drop(ready_since);
+
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_starts_when_first_seen_ready_and_is_not_restarted() {
+        let debounce = ExecutionDebounce::new(Duration::from_secs(2));
+        let aapl = Symbol::new("AAPL").unwrap();
+        let msft = Symbol::new("MSFT").unwrap();
+        let start = Instant::now();
+
+        assert!(debounce.due(std::slice::from_ref(&aapl), start).is_empty());
+        // A later fill keeps AAPL ready and starts MSFT's own window
+        assert!(
+            debounce
+                .due(
+                    &[aapl.clone(), msft.clone()],
+                    start + Duration::from_secs(1)
+                )
+                .is_empty()
+        );
+        assert_eq!(
+            debounce.due(
+                &[aapl.clone(), msft.clone()],
+                start + Duration::from_secs(2)
+            ),
+            vec![aapl.clone()]
+        );
+        assert_eq!(
+            debounce.due(std::slice::from_ref(&msft), start + Duration::from_secs(3)),
+            vec![msft]
+        );
+
+        // Executed positions are forgotten, so becoming ready again starts anew
+        assert!(debounce.due(&[], start + Duration::from_secs(4)).is_empty());
+        assert!(
+            debounce
+                .due(&[aapl], start + Duration::from_secs(5))
+                .is_empty()
+        );
+    }
+}
@@ -1,36 +1,88 @@
+mod batch;
 mod builder;
+pub(crate) mod circuit_breaker;
+pub(crate) mod debounce;
+mod poll_backoff;
 
-use alloy::providers::{Provider, ProviderBuilder, WsConnect};
+use alloy::primitives::Address;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder, WsConnect};
 use alloy::rpc::types::Log;
 use alloy::sol_types;
+use backon::{BackoffBuilder, ExponentialBuilder, Retryable};
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use futures_util::stream::FusedStream;
 use futures_util::{Stream, StreamExt};
+use rust_decimal::Decimal;
 use sqlx::SqlitePool;
-use std::time::Duration;
-use tokio::sync::mpsc::UnboundedSender;
-use tokio::task::JoinHandle;
+use std::fmt::Display;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, mpsc::UnboundedSender};
+use tokio::task::{JoinHandle, JoinSet};
 use tokio::time::sleep;
-use tracing::{debug, error, info, trace};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, trace, warn};
 
-use st0x_broker::{Broker, MarketOrder, SupportedBroker};
+use st0x_broker::market_hours::MARKET_HOURS_RECHECK_INTERVAL;
+use st0x_broker::{Broker, FractionalMarketOrder, MarketOrder, OrderState, rate_limit_delay};
 
+use self::batch::{ExecutionBatch, dispatch_batch};
+use self::debounce::ExecutionDebounce;
+use self::poll_backoff::{PollBackoff, with_jitter};
 use crate::bindings::IOrderBookV4::{ClearV2, IOrderBookV4Instance, TakeOrderV2};
+use crate::conversion_outcome::{ConversionOutcome, Outcome};
+use crate::db_retry::{LockedRetryPolicy, retry_when_locked};
 use crate::env::Config;
-use crate::error::EventProcessingError;
-use crate::offchain::execution::{OffchainExecution, find_execution_by_id};
+use crate::error::{EventProcessingError, EventQueueError, OnChainError, TradeValidationError};
+use crate::lock::{clear_execution_lease, clear_pending_execution_id};
+use crate::metrics::Metrics;
+use crate::offchain::end_of_day::settle_residual_positions;
+use crate::offchain::execution::{
+    OffchainExecution, find_client_order_id, find_execution_by_client_key, find_execution_by_id,
+    find_fractional_shares, record_order_broker_within_transaction, save_request_payload,
+};
+use crate::offchain::liquidity::{LiquidityLimits, fetch_liquidity_limits};
+use crate::offchain::open_executions::is_at_open_execution_cap;
 use crate::offchain::order_poller::OrderStatusPoller;
-use crate::onchain::accumulator::check_all_accumulated_positions;
-use crate::onchain::backfill::backfill_events;
-use crate::onchain::pyth::FeedIdCache;
+use crate::onchain::accumulator::{
+    ExecutionRules, check_accumulated_positions_for, check_all_accumulated_positions,
+    find_ready_symbols,
+};
+use crate::onchain::backfill::{backfill_block_range, backfill_events};
+use crate::onchain::contract_code::{ContractCodeError, verify_contract_code};
+use crate::onchain::hedge_events::{HedgeEventType, is_hedged};
+use crate::onchain::io::SymbolConfigurationMode;
+use crate::onchain::last_seen_block::{BlockGap, detect_block_gap, record_last_seen_block};
+use crate::onchain::price_source::{
+    PriceResolution, PriceSourceError, resolve_trade_price, save_price_resolution,
+};
+use crate::onchain::pyth::{FeedIdCache, confidence_bps};
 use crate::onchain::trade::TradeEvent;
 use crate::onchain::{EvmEnv, OnchainTrade, accumulator};
-use crate::queue::{QueuedEvent, enqueue, get_next_unprocessed_event, mark_event_processed};
+use crate::queue::{
+    QueuedEvent, claim_event, enqueue, get_next_prioritized_event, mark_event_processed,
+    release_claim, release_claims,
+};
+use crate::rpc_metrics::{RpcMethod, instrumented};
+use crate::stats::Stats;
 use crate::symbol::cache::SymbolCache;
 use crate::symbol::lock::get_symbol_lock;
+use crate::trade_feed::TradeFeedEvent;
+use crate::trade_review::TradeReview;
+use crate::vacuum::{MarketWindow, vacuum_if_due};
 
 pub(crate) use builder::ConductorBuilder;
+use builder::{ClearStream, TakeStream};
+use circuit_breaker::SymbolCircuitBreakers;
 
 pub(crate) struct Conductor {
     pub(crate) broker_maintenance: Option<JoinHandle<()>>,
+    /// Cancelled to have the queue processor and the position checker stop
+    /// taking new work, see [`Conductor::drain`]
+    pub(crate) drain: CancellationToken,
     pub(crate) order_poller: JoinHandle<()>,
     pub(crate) dex_event_receiver: JoinHandle<()>,
     pub(crate) event_processor: JoinHandle<()>,
@@ -38,18 +90,29 @@ pub(crate) struct Conductor {
     pub(crate) queue_processor: JoinHandle<()>,
 }
 
+/// Runs a conductor for every market session until `shutdown` is cancelled,
+/// which drains the executions in flight before stopping the conductor.
 pub(crate) async fn run_market_hours_loop<B: Broker + Clone + Send + 'static>(
     broker: B,
     config: Config,
     pool: SqlitePool,
+    stats: Arc<Stats>,
     broker_maintenance: Option<JoinHandle<()>>,
+    shutdown: CancellationToken,
 ) -> anyhow::Result<()> {
     const RERUN_DELAY_SECS: u64 = 10;
 
-    let timeout = broker
-        .wait_until_market_open()
-        .await
-        .map_err(|e| anyhow::anyhow!("Market hours check failed: {e}"))?;
+    let timeout = tokio::select! {
+        biased;
+
+        () = shutdown.cancelled() => {
+            info!("Shutdown requested before the market session started");
+            return Ok(());
+        }
+        result = broker.wait_until_market_open() => {
+            result.map_err(|e| anyhow::anyhow!("Market hours check failed: {e}"))?
+        }
+    };
 
     let timeout_minutes = timeout.as_secs() / 60;
     if timeout_minutes < 60 * 24 {
@@ -58,23 +121,39 @@ pub(crate) async fn run_market_hours_loop<B: Broker + Clone + Send + 'static>(
         info!("Starting conductor (no market hours restrictions)");
     }
 
-    let mut conductor = match Conductor::start(&config, &pool, broker.clone(), broker_maintenance)
-        .await
-    {
-        Ok(c) => c,
-        Err(e) => {
-            error!(
-                "Failed to start conductor: {e}, retrying in {} seconds",
-                RERUN_DELAY_SECS
-            );
+    let mut conductor =
+        match Conductor::start(&config, &pool, &stats, broker.clone(), broker_maintenance).await {
+            Ok(c) => c,
+            // Retrying cannot deploy a missing contract
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<ContractCodeError>(),
+                    Some(ContractCodeError::NoCode { .. })
+                ) =>
+            {
+                return Err(e);
+            }
+            Err(e) => {
+                error!(
+                    "Failed to start conductor: {e}, retrying in {} seconds",
+                    RERUN_DELAY_SECS
+                );
 
-            tokio::time::sleep(std::time::Duration::from_secs(RERUN_DELAY_SECS)).await;
+                tokio::time::sleep(std::time::Duration::from_secs(RERUN_DELAY_SECS)).await;
 
-            let new_maintenance = broker.run_broker_maintenance().await;
+                let new_maintenance = broker.run_broker_maintenance().await;
 
-            return Box::pin(run_market_hours_loop(broker, config, pool, new_maintenance)).await;
-        }
-    };
+                return Box::pin(run_market_hours_loop(
+                    broker,
+                    config,
+                    pool,
+                    stats,
+                    new_maintenance,
+                    shutdown,
+                ))
+                .await;
+            }
+        };
 
     info!("Market opened, conductor running");
 
@@ -86,26 +165,195 @@ pub(crate) async fn run_market_hours_loop<B: Broker + Clone + Send + 'static>(
             info!("Conductor completed successfully, continuing to next market session");
             Ok(())
         }
-        () = tokio::time::sleep(timeout) => {
+        () = wait_for_market_close(&broker, &config, &pool, &stats, timeout) => {
             info!("Market closed, shutting down trading tasks");
             conductor.abort_trading_tasks();
-            let next_maintenance = conductor.broker_maintenance;
+            conductor.join_trading_tasks().await;
             info!("Trading tasks shutdown, DEX events buffering");
-            Box::pin(run_market_hours_loop(broker, config, pool, next_maintenance)).await
+
+            let window = conductor.market_window();
+            if let Err(e) = vacuum_if_due(&pool, config.vacuum_interval, window).await {
+                error!("Database vacuum failed: {e}");
+            }
+
+            let next_maintenance = conductor.broker_maintenance;
+
+            Box::pin(run_market_hours_loop(
+                broker,
+                config,
+                pool,
+                stats,
+                next_maintenance,
+                shutdown,
+            ))
+            .await
+        }
+        () = shutdown.cancelled() => {
+            info!("Shutdown requested, draining in-flight executions");
+            conductor.drain(config.shutdown_drain_timeout).await;
+            conductor.abort_all();
+            Ok(())
+        }
+    }
+}
+
+/// Sleeps until the market closes in `until_close`, settling residual
+/// positions `end_of_day_settlement_lead` before then. Sessions without market
+/// hours restrictions have no end of day to settle.
+async fn wait_for_market_close<B: Broker + Clone + Send + 'static>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    stats: &Arc<Stats>,
+    until_close: Duration,
+) {
+    const UNRESTRICTED_SESSION: Duration = Duration::from_hours(24);
+
+    if until_close >= UNRESTRICTED_SESSION {
+        sleep(until_close).await;
+        return;
+    }
+
+    let mut until_close = until_close;
+    sleep_until_before_close(broker, &mut until_close, config.end_of_day_settlement_lead).await;
+
+    if let Err(e) = settle_end_of_day(broker, config, pool, stats).await {
+        error!("End-of-day settlement failed: {e}");
+    }
+
+    sleep_until_before_close(broker, &mut until_close, Duration::ZERO).await;
+}
+
+/// Sleeps until `lead` before the market closes, at most
+/// [`MARKET_HOURS_RECHECK_INTERVAL`] at a time, asking the broker for the
+/// time left after each sleep so an early close is noticed. `until_close` is
+/// kept up to date; it counts down by the time slept when the broker cannot
+/// be asked.
+async fn sleep_until_before_close<B: Broker>(
+    broker: &B,
+    until_close: &mut Duration,
+    lead: Duration,
+) {
+    loop {
+        let remaining = until_close.saturating_sub(lead);
+        if remaining.is_zero() {
+            return;
+        }
+
+        let step = remaining.min(MARKET_HOURS_RECHECK_INTERVAL);
+        sleep(step).await;
+
+        *until_close = match broker.time_until_market_close().await {
+            Ok(Some(reported)) => reported,
+            Ok(None) => {
+                info!("Broker reports the market closed");
+                Duration::ZERO
+            }
+            Err(e) => {
+                warn!("Failed to check time until market close: {e}");
+                until_close.saturating_sub(step)
+            }
+        };
+    }
+}
+
+/// Applies the configured end-of-day settlement to residual positions and
+/// places any rounding orders it creates.
+async fn settle_end_of_day<B: Broker + Clone + Send + 'static>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    stats: &Arc<Stats>,
+) -> Result<(), EventProcessingError> {
+    if config.standby.is_standby() {
+        info!("Running as standby, skipping end-of-day settlement");
+        return Ok(());
+    }
+
+    info!(
+        "Settling residual positions before market close: {:?}",
+        config.end_of_day_settlement
+    );
+
+    let executions = settle_residual_positions(
+        pool,
+        config.end_of_day_settlement,
+        &config.blackout,
+        broker.to_supported_broker(),
+        &config.evm.symbol_convention,
+        config.trade_side,
+    )
+    .await?;
+
+    for execution in executions {
+        let Some(execution_id) = execution.id else {
+            error!("Rounding execution for {} has no ID", execution.symbol);
+            continue;
+        };
+
+        if let Err(e) = execute_pending_offchain_execution(
+            broker,
+            pool,
+            stats,
+            &config.circuit_breakers,
+            config.locked_retry,
+            execution_id,
+        )
+        .await
+        {
+            error!("Failed to place rounding order for execution {execution_id}: {e}");
         }
     }
+
+    Ok(())
+}
+
+/// Backoff between attempts to open the WebSocket RPC connection at session
+/// start, making `attempts` attempts in total.
+fn ws_connect_retry_strat(attempts: NonZeroUsize) -> ExponentialBuilder {
+    ExponentialBuilder::default()
+        .with_max_times(attempts.get() - 1)
+        .with_min_delay(Duration::from_secs(1))
+        .with_max_delay(Duration::from_secs(30))
+        .with_jitter()
+}
+
+/// Runs `connect` until it succeeds or `retry_strategy` gives up, so a
+/// transient RPC outage at startup does not fail the whole session.
+async fn connect_with_retry<T, E, F, Fut, B>(retry_strategy: B, connect: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+    B: BackoffBuilder,
+{
+    connect
+        .retry(retry_strategy)
+        .notify(|err, dur| {
+            warn!("Failed to connect to WebSocket RPC, retrying in {dur:?}: {err}");
+        })
+        .await
 }
 
 impl Conductor {
     pub(crate) async fn start<B: Broker + Clone + Send + 'static>(
         config: &Config,
         pool: &SqlitePool,
+        stats: &Arc<Stats>,
         broker: B,
         broker_maintenance: Option<JoinHandle<()>>,
     ) -> anyhow::Result<Self> {
-        let ws = WsConnect::new(config.evm.ws_rpc_url.as_str());
-        let provider = ProviderBuilder::new().connect_ws(ws).await?;
-        let cache = SymbolCache::default();
+        let ws_rpc_url = config.evm.ws_rpc_url.as_str();
+        let provider =
+            connect_with_retry(ws_connect_retry_strat(config.ws_connect_attempts), || {
+                ProviderBuilder::new().connect_ws(WsConnect::new(ws_rpc_url))
+            })
+            .await?
+            .erased();
+
+        verify_contract_code(&provider, config.contract_code_check, config.evm.orderbook).await?;
+
+        let cache = config.symbol_cache();
         let orderbook = IOrderBookV4Instance::new(config.evm.orderbook, &provider);
 
         let mut clear_stream = orderbook.ClearV2_filter().watch().await?.into_stream();
@@ -114,14 +362,34 @@ impl Conductor {
         let cutoff_block =
             get_cutoff_block(&mut clear_stream, &mut take_stream, &provider, pool).await?;
 
-        backfill_events(pool, &provider, &config.evm, cutoff_block - 1).await?;
+        let block_gap = if config.persist_last_seen_block {
+            detect_block_gap(pool, cutoff_block).await?
+        } else {
+            BlockGap::Unknown
+        };
 
-        Ok(
-            ConductorBuilder::new(config.clone(), pool.clone(), cache, provider, broker)
-                .with_broker_maintenance(broker_maintenance)
-                .with_dex_event_streams(clear_stream, take_stream)
-                .spawn(),
+        match block_gap {
+            BlockGap::Missed { start, end } => {
+                backfill_block_range(pool, &provider, &config.evm, start, end).await?;
+            }
+            BlockGap::None => {}
+            BlockGap::Unknown => {
+                backfill_events(pool, &provider, &config.evm, cutoff_block - 1).await?;
+            }
+        }
+
+        Ok(ConductorBuilder::new(
+            config.clone(),
+            pool.clone(),
+            stats.clone(),
+            cache,
+            provider,
+            broker,
         )
+        .with_broker_maintenance(broker_maintenance)
+        .with_dex_event_streams(clear_stream, take_stream)
+        .with_dex_resubscription(dex_resubscription(config, pool, cutoff_block))
+        .spawn())
     }
 
     pub(crate) async fn wait_for_completion(&mut self) -> Result<(), anyhow::Error> {
@@ -183,6 +451,70 @@ impl Conductor {
         info!("Trading tasks aborted successfully (DEX events will continue buffering)");
     }
 
+    /// Waits for the aborted trading tasks to stop. Finished tasks are skipped
+    /// as `wait_for_completion` may already have polled them to completion.
+    pub(crate) async fn join_trading_tasks(&mut self) {
+        for task in [
+            &mut self.order_poller,
+            &mut self.event_processor,
+            &mut self.position_checker,
+            &mut self.queue_processor,
+        ] {
+            if task.is_finished() {
+                continue;
+            }
+
+            match task.await {
+                Err(e) if !e.is_cancelled() => error!("Trading task failed: {e}"),
+                _ => {}
+            }
+        }
+    }
+
+    /// Trading until every trading task has stopped.
+    pub(crate) fn market_window(&self) -> MarketWindow {
+        let stopped = [
+            &self.order_poller,
+            &self.event_processor,
+            &self.position_checker,
+            &self.queue_processor,
+        ]
+        .iter()
+        .all(|task| task.is_finished());
+
+        if stopped {
+            MarketWindow::Closed
+        } else {
+            MarketWindow::Trading
+        }
+    }
+
+    /// Asks the queue processor and the position checker to stop taking new
+    /// work, then waits up to `timeout` for the executions they are placing to
+    /// be recorded. Tasks still running afterwards are left to `abort_all`.
+    pub(crate) async fn drain(&mut self, timeout: Duration) {
+        self.drain.cancel();
+
+        let in_flight = async {
+            for task in [&mut self.queue_processor, &mut self.position_checker] {
+                if task.is_finished() {
+                    continue;
+                }
+
+                match task.await {
+                    Err(e) if !e.is_cancelled() => error!("Trading task failed: {e}"),
+                    _ => {}
+                }
+            }
+        };
+
+        if tokio::time::timeout(timeout, in_flight).await.is_ok() {
+            info!("In-flight executions drained");
+        } else {
+            warn!("Executions still in flight after {timeout:?}, aborting them");
+        }
+    }
+
     pub(crate) fn abort_all(self) {
         info!("Aborting all background tasks");
 
@@ -203,6 +535,7 @@ fn spawn_order_poller<B: Broker + Clone + Send + 'static>(
     config: &Config,
     pool: &SqlitePool,
     broker: B,
+    stats: Arc<Stats>,
 ) -> JoinHandle<()> {
     let poller_config = config.get_order_poller_config();
     info!(
@@ -210,7 +543,7 @@ fn spawn_order_poller<B: Broker + Clone + Send + 'static>(
         poller_config.polling_interval, poller_config.max_jitter
     );
 
-    let poller = OrderStatusPoller::new(poller_config, pool.clone(), broker);
+    let poller = OrderStatusPoller::new(poller_config, pool.clone(), broker, stats);
     tokio::spawn(async move {
         if let Err(e) = poller.run().await {
             error!("Order poller failed: {e}");
@@ -220,66 +553,203 @@ fn spawn_order_poller<B: Broker + Clone + Send + 'static>(
     })
 }
 
-fn spawn_onchain_event_receiver(
+/// How often the DEX event receiver checks the websocket while no events
+/// arrive.
+const DEX_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// DEX streams without an event or heartbeat for this long are reported
+/// stale by `GET /health`.
+pub(crate) const DEX_STREAM_STALE_AFTER: Duration = Duration::from_secs(90);
+
+/// Consecutive stream errors after which the DEX event streams are
+/// resubscribed.
+const MAX_CONSECUTIVE_STREAM_ERRORS: u32 = 5;
+
+/// Rebuilds the websocket provider and DEX event streams once they end, given
+/// the block of the last event received on the previous streams, if any.
+pub(crate) type Resubscribe<P> = Box<
+    dyn FnMut(Option<u64>) -> BoxFuture<'static, anyhow::Result<(P, ClearStream, TakeStream)>>
+        + Send,
+>;
+
+fn spawn_onchain_event_receiver<P: Provider + Clone + Send + 'static>(
     event_sender: UnboundedSender<(TradeEvent, Log)>,
-    clear_stream: impl Stream<Item = Result<(ClearV2, Log), sol_types::Error>> + Unpin + Send + 'static,
-    take_stream: impl Stream<Item = Result<(TakeOrderV2, Log), sol_types::Error>>
-    + Unpin
-    + Send
-    + 'static,
+    clear_stream: ClearStream,
+    take_stream: TakeStream,
+    provider: P,
+    stats: Arc<Stats>,
+    resubscribe: Option<Resubscribe<P>>,
 ) -> JoinHandle<()> {
     info!("Starting blockchain event receiver");
     tokio::spawn(receive_blockchain_events(
         clear_stream,
         take_stream,
         event_sender,
+        provider,
+        stats,
+        resubscribe,
     ))
 }
 
+/// Resubscription of the conductor's DEX event streams through a new
+/// websocket connection. Events from the block of the last event received on
+/// the old streams, or `cutoff_block` if none was, up to the new streams'
+/// cutoff block are backfilled, so the new streams pick up exactly where the
+/// old ones left off.
+fn dex_resubscription(
+    config: &Config,
+    pool: &SqlitePool,
+    cutoff_block: u64,
+) -> Resubscribe<DynProvider> {
+    let config = config.clone();
+    let pool = pool.clone();
+
+    Box::new(move |last_seen_block| {
+        Box::pin(resubscribe_dex_streams(
+            config.clone(),
+            pool.clone(),
+            last_seen_block.unwrap_or(cutoff_block),
+        ))
+    })
+}
+
+async fn resubscribe_dex_streams(
+    config: Config,
+    pool: SqlitePool,
+    resume_block: u64,
+) -> anyhow::Result<(DynProvider, ClearStream, TakeStream)> {
+    let provider = ProviderBuilder::new()
+        .connect_ws(WsConnect::new(config.evm.ws_rpc_url.as_str()))
+        .await?
+        .erased();
+
+    let orderbook = IOrderBookV4Instance::new(config.evm.orderbook, &provider);
+    let mut clear_stream = orderbook.ClearV2_filter().watch().await?.into_stream();
+    let mut take_stream = orderbook.TakeOrderV2_filter().watch().await?.into_stream();
+
+    let cutoff_block =
+        get_cutoff_block(&mut clear_stream, &mut take_stream, &provider, &pool).await?;
+
+    if resume_block < cutoff_block {
+        info!("Backfilling blocks {resume_block} to {cutoff_block} missed while resubscribing");
+        backfill_block_range(
+            &pool,
+            &provider,
+            &config.evm,
+            resume_block,
+            cutoff_block - 1,
+        )
+        .await?;
+    }
+
+    Ok((provider, Box::new(clear_stream), Box::new(take_stream)))
+}
+
+/// Backoff between attempts to resubscribe the DEX event streams, which are
+/// retried for as long as it takes.
+fn dex_resubscribe_retry_strat() -> ExponentialBuilder {
+    ExponentialBuilder::default()
+        .without_max_times()
+        .with_min_delay(Duration::from_secs(1))
+        .with_max_delay(Duration::from_mins(1))
+        .with_jitter()
+}
+
+/// Runs `resubscribe` until it succeeds, logging every attempt.
+async fn resubscribe_with_backoff<P>(
+    resubscribe: &mut Resubscribe<P>,
+    last_seen_block: Option<u64>,
+) -> (P, ClearStream, TakeStream) {
+    let mut delays = dex_resubscribe_retry_strat().build();
+    let mut attempt = 0_u64;
+
+    loop {
+        attempt += 1;
+        info!(
+            "Resubscribing to DEX event streams (attempt {attempt}, last seen block {last_seen_block:?})"
+        );
+
+        match resubscribe(last_seen_block).await {
+            Ok(streams) => {
+                info!("Resubscribed to DEX event streams after {attempt} attempt(s)");
+                return streams;
+            }
+            Err(e) => {
+                let delay = delays.next().unwrap_or(Duration::from_mins(1));
+                warn!("Failed to resubscribe to DEX event streams, retrying in {delay:?}: {e}");
+                sleep(delay).await;
+            }
+        }
+    }
+}
+
 fn spawn_event_processor(
     pool: SqlitePool,
+    stats: Arc<Stats>,
     mut event_receiver: tokio::sync::mpsc::UnboundedReceiver<(TradeEvent, Log)>,
+    persist_last_seen_block: bool,
 ) -> JoinHandle<()> {
     info!("Starting event processor");
     tokio::spawn(async move {
         while let Some((event, log)) = event_receiver.recv().await {
+            stats.record_event_received();
             trace!(
                 "Processing live event: tx_hash={:?}, log_index={:?}",
                 log.transaction_hash, log.log_index
             );
+            let block_number = log.block_number;
             if let Err(e) = process_live_event(&pool, event, log).await {
                 error!("Failed to process live event: {e}");
+                continue;
+            }
+
+            let Some(block_number) = block_number.filter(|_| persist_last_seen_block) else {
+                continue;
+            };
+            if let Err(e) = record_last_seen_block(&pool, block_number).await {
+                error!("Failed to record last-seen block {block_number}: {e}");
             }
         }
         info!("Event processing loop ended");
     })
 }
 
+/// Everything the queue processor needs to convert queued events and execute
+/// the positions they make ready.
+struct QueueProcessor<P, B> {
+    broker: B,
+    config: Config,
+    pool: SqlitePool,
+    cache: SymbolCache,
+    provider: P,
+    stats: Arc<Stats>,
+    symbol_permits: Arc<Semaphore>,
+    drain: CancellationToken,
+}
+
 fn spawn_queue_processor<
     P: Provider + Clone + Send + 'static,
     B: Broker + Clone + Send + 'static,
 >(
-    broker: B,
-    config: &Config,
-    pool: &SqlitePool,
-    cache: &SymbolCache,
-    provider: P,
+    processor: QueueProcessor<P, B>,
 ) -> JoinHandle<()> {
     info!("Starting queue processor service");
-    let config_clone = config.clone();
-    let pool_clone = pool.clone();
-    let cache_clone = cache.clone();
 
     tokio::spawn(async move {
-        run_queue_processor(&broker, &config_clone, &pool_clone, &cache_clone, provider).await;
+        run_queue_processor(&processor).await;
     })
 }
 
 fn spawn_periodic_accumulated_position_check<B: Broker + Clone + Send + 'static>(
     broker: B,
+    config: Config,
     pool: SqlitePool,
+    stats: Arc<Stats>,
+    symbol_permits: Arc<Semaphore>,
+    drain: CancellationToken,
 ) -> JoinHandle<()> {
     info!("Starting periodic accumulated position checker");
+    let execution_permits = Arc::new(Semaphore::new(config.max_concurrent_executions.get()));
 
     tokio::spawn(async move {
         const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
@@ -288,39 +758,116 @@ fn spawn_periodic_accumulated_position_check<B: Broker + Clone + Send + 'static>
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                () = drain.cancelled() => break,
+            }
             debug!("Running periodic accumulated position check");
-            if let Err(e) = check_and_execute_accumulated_positions(&broker, &pool).await {
+
+            match is_at_open_execution_cap(&pool, config.max_open_executions, &stats).await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => {
+                    error!("Failed to count open executions: {e}");
+                    continue;
+                }
+            }
+
+            if let Err(e) = check_and_execute_accumulated_positions(
+                &broker,
+                &config,
+                &pool,
+                &stats,
+                &execution_permits,
+                &symbol_permits,
+                config.execution_debounce.as_ref(),
+            )
+            .await
+            {
                 error!("Periodic accumulated position check failed: {e}");
             }
         }
+
+        info!("Periodic accumulated position checker drained");
     })
 }
 
-async fn receive_blockchain_events<S1, S2>(
-    mut clear_stream: S1,
-    mut take_stream: S2,
+/// Forwards DEX events to the event processor, recording stream liveness in
+/// `stats`. While no events arrive the websocket is checked every
+/// [`DEX_HEARTBEAT_INTERVAL`] by asking for the latest block.
+///
+/// When both streams end, or [`MAX_CONSECUTIVE_STREAM_ERRORS`] events in a
+/// row fail, the streams are replaced through `resubscribe` with exponential
+/// backoff. Without `resubscribe` the receiver stops once both streams end.
+async fn receive_blockchain_events<P: Provider>(
+    clear_stream: ClearStream,
+    take_stream: TakeStream,
     event_sender: UnboundedSender<(TradeEvent, Log)>,
-) where
-    S1: Stream<Item = Result<(ClearV2, Log), sol_types::Error>> + Unpin,
-    S2: Stream<Item = Result<(TakeOrderV2, Log), sol_types::Error>> + Unpin,
-{
+    mut provider: P,
+    stats: Arc<Stats>,
+    mut resubscribe: Option<Resubscribe<P>>,
+) {
+    let mut clear_stream = clear_stream.fuse();
+    let mut take_stream = take_stream.fuse();
+    let mut heartbeat = tokio::time::interval(DEX_HEARTBEAT_INTERVAL);
+    heartbeat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_seen_block = None;
+    let mut consecutive_errors = 0;
+
+    stats.record_dex_streams_opened();
+    stats.record_dex_activity();
+
     loop {
+        let streams_ended = clear_stream.is_terminated() && take_stream.is_terminated();
+        if streams_ended || consecutive_errors >= MAX_CONSECUTIVE_STREAM_ERRORS {
+            let Some(resubscribe) = resubscribe.as_mut() else {
+                error!("All event streams ended, shutting down event receiver");
+                break;
+            };
+
+            if streams_ended {
+                warn!("All event streams ended, resubscribing");
+            } else {
+                warn!("{consecutive_errors} consecutive event stream errors, resubscribing");
+            }
+
+            stats.record_dex_streams_closed();
+            let (new_provider, new_clear_stream, new_take_stream) =
+                resubscribe_with_backoff(resubscribe, last_seen_block).await;
+            provider = new_provider;
+            clear_stream = new_clear_stream.fuse();
+            take_stream = new_take_stream.fuse();
+            consecutive_errors = 0;
+            stats.record_dex_streams_opened();
+            stats.record_dex_activity();
+            continue;
+        }
+
+        // A stream that just ended goes back to the top of the loop, so ended
+        // streams are resubscribed without waiting for the next heartbeat
         let event_result = tokio::select! {
-            Some(result) = clear_stream.next() => {
+            result = clear_stream.next(), if !clear_stream.is_terminated() => {
+                let Some(result) = result else { continue };
                 result.map(|(event, log)| (TradeEvent::ClearV2(Box::new(event)), log))
             }
-            Some(result) = take_stream.next() => {
+            result = take_stream.next(), if !take_stream.is_terminated() => {
+                let Some(result) = result else { continue };
                 result.map(|(event, log)| (TradeEvent::TakeOrderV2(Box::new(event)), log))
             }
-            else => {
-                error!("All event streams ended, shutting down event receiver");
-                break;
+            _ = heartbeat.tick() => {
+                match provider.get_block_number().await {
+                    Ok(_) => stats.record_dex_activity(),
+                    Err(e) => warn!("DEX websocket heartbeat failed: {e}"),
+                }
+                continue;
             }
         };
 
         match event_result {
             Ok((event, log)) => {
+                consecutive_errors = 0;
+                last_seen_block = last_seen_block.max(log.block_number);
+                stats.record_dex_activity();
                 trace!(
                     "Received blockchain event: tx_hash={:?}, log_index={:?}, block_number={:?}",
                     log.transaction_hash, log.log_index, log.block_number
@@ -331,10 +878,13 @@ async fn receive_blockchain_events<S1, S2>(
                 }
             }
             Err(e) => {
+                consecutive_errors += 1;
                 error!("Error in event stream: {e}");
             }
         }
     }
+
+    stats.record_dex_streams_closed();
 }
 
 pub(crate) async fn get_cutoff_block<S1, S2, P>(
@@ -353,19 +903,28 @@ where
     let first_event_result = wait_for_first_event_with_timeout(
         clear_stream,
         take_stream,
+        provider,
         std::time::Duration::from_secs(5),
     )
     .await;
 
     let Some((mut event_buffer, block_number)) = first_event_result else {
-        let current_block = provider.get_block_number().await?;
+        let current_block =
+            instrumented(RpcMethod::GetBlockNumber, provider.get_block_number()).await?;
         info!(
             "No subscription events within timeout, using current block {current_block} as cutoff"
         );
         return Ok(current_block);
     };
 
-    buffer_live_events(clear_stream, take_stream, &mut event_buffer, block_number).await;
+    buffer_live_events(
+        clear_stream,
+        take_stream,
+        provider,
+        &mut event_buffer,
+        block_number,
+    )
+    .await;
 
     crate::queue::enqueue_buffer(pool, event_buffer).await;
 
@@ -403,78 +962,619 @@ async fn process_live_event(
     Ok(())
 }
 
+/// How long the queue processor waits before re-checking a reached open
+/// execution cap.
+const OPEN_EXECUTION_CAP_RECHECK: Duration = Duration::from_secs(1);
+
 async fn run_queue_processor<P: Provider + Clone, B: Broker + Clone>(
-    broker: &B,
-    config: &Config,
-    pool: &SqlitePool,
-    cache: &SymbolCache,
-    provider: P,
+    processor: &QueueProcessor<P, B>,
 ) {
+    let QueueProcessor {
+        broker,
+        config,
+        pool,
+        cache,
+        provider,
+        stats,
+        symbol_permits,
+        drain,
+    } = processor;
     info!("Starting queue processor service");
 
-    let feed_id_cache = FeedIdCache::default();
+    let feed_id_cache = FeedIdCache::with_capacity(config.symbol_cache_capacity);
 
-    match crate::queue::count_unprocessed(pool).await {
-        Ok(count) if count > 0 => {
-            info!("Found {count} unprocessed events from previous sessions to process");
+    resume_unprocessed_events(pool).await;
+
+    let mut batch = config.execution_batch_window.map(ExecutionBatch::new);
+    let mut backoff = PollBackoff::new(config.queue_poll_min_delay, config.queue_poll_max_delay);
+    let execution_permits = Arc::new(Semaphore::new(config.max_concurrent_executions.get()));
+
+    loop {
+        if drain.is_cancelled() {
+            drain_queue_processor(processor, batch.as_mut(), &execution_permits).await;
+            return;
         }
-        Ok(_) => {
-            info!("No unprocessed events found, starting fresh");
+
+        if let Some(execution_ids) = batch
+            .as_mut()
+            .and_then(|batch| batch.take_if_due(Instant::now()))
+        {
+            dispatch_batch(
+                broker,
+                pool,
+                stats,
+                &config.circuit_breakers,
+                config.locked_retry,
+                execution_ids,
+                drain,
+            )
+            .await;
         }
-        Err(e) => {
-            error!("Failed to count unprocessed events: {e}");
+
+        match is_at_open_execution_cap(pool, config.max_open_executions, stats).await {
+            Ok(true) => {
+                sleep_unless_drained(OPEN_EXECUTION_CAP_RECHECK, drain).await;
+                continue;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                error!("Failed to count open executions: {e}");
+                sleep_unless_drained(with_jitter(backoff.next_delay()), drain).await;
+                continue;
+            }
+        }
+
+        if let Some(debounce) = &config.execution_debounce {
+            execute_debounced_positions(
+                broker,
+                config,
+                pool,
+                stats,
+                &execution_permits,
+                symbol_permits,
+                Some(debounce),
+            )
+            .await;
         }
-    }
 
-    let broker_type = broker.to_supported_broker();
+        // Held through conversion and execution, released before any backoff
+        let result = {
+            let _symbol_permit = symbol_permits.acquire().await;
+            let result = process_next_queued_event(
+                broker,
+                config,
+                pool,
+                cache,
+                provider,
+                &feed_id_cache,
+                stats,
+            )
+            .await;
 
-    loop {
-        match process_next_queued_event(broker_type, config, pool, cache, &provider, &feed_id_cache)
-            .await
-        {
-            Ok(Some(execution)) => {
-                if let Some(exec_id) = execution.id {
-                    if let Err(e) = execute_pending_offchain_execution(broker, pool, exec_id).await
-                    {
-                        error!("Failed to execute offchain order {exec_id}: {e}");
-                    }
+            if let Ok(executions) = &result {
+                for exec_id in executions.iter().filter_map(|execution| execution.id) {
+                    hand_off_execution(processor, batch.as_mut(), exec_id).await;
                 }
             }
-            Ok(None) => {
-                sleep(Duration::from_millis(100)).await;
+
+            result
+        };
+
+        match &result {
+            Ok(_) => {}
+            Err(e @ EventProcessingError::StrictSymbolConfiguration(..)) => {
+                error!(
+                    "Halting queue processor, investigate the orderbook configuration \
+                     before restarting: {e}"
+                );
+                return;
             }
             Err(e) => {
                 error!("Error processing queued event: {e}");
-                sleep(Duration::from_millis(500)).await;
             }
         }
+
+        if let Some(delay) = backoff.after(&result) {
+            sleep_unless_drained(with_jitter(delay), drain).await;
+        }
+    }
+}
+
+/// Releases claims left by an interrupted run so their events are retried, and
+/// reports how many events previous sessions left unprocessed.
+async fn resume_unprocessed_events(pool: &SqlitePool) {
+    match release_claims(pool).await {
+        Ok(0) => {}
+        Ok(released) => {
+            warn!("Released {released} event claims left by an interrupted run");
+        }
+        Err(e) => {
+            error!("Failed to release stale event claims: {e}");
+        }
+    }
+
+    match crate::queue::count_unprocessed(pool).await {
+        Ok(count) if count > 0 => {
+            info!("Found {count} unprocessed events from previous sessions to process");
+        }
+        Ok(_) => {
+            info!("No unprocessed events found, starting fresh");
+        }
+        Err(e) => {
+            error!("Failed to count unprocessed events: {e}");
+        }
+    }
+}
+
+/// Executes the positions still waiting out their debounce window and places
+/// the executions already created, without pulling new events.
+async fn drain_queue_processor<P: Provider + Clone, B: Broker + Clone>(
+    processor: &QueueProcessor<P, B>,
+    batch: Option<&mut ExecutionBatch>,
+    execution_permits: &Arc<Semaphore>,
+) {
+    let QueueProcessor {
+        broker,
+        config,
+        pool,
+        stats,
+        symbol_permits,
+        drain,
+        ..
+    } = processor;
+
+    if config.execution_debounce.is_some() {
+        execute_debounced_positions(
+            broker,
+            config,
+            pool,
+            stats,
+            execution_permits,
+            symbol_permits,
+            None,
+        )
+        .await;
+    }
+
+    if let Some(execution_ids) = batch.and_then(ExecutionBatch::take_all) {
+        dispatch_batch(
+            broker,
+            pool,
+            stats,
+            &config.circuit_breakers,
+            config.locked_retry,
+            execution_ids,
+            drain,
+        )
+        .await;
+    }
+
+    info!("Queue processor drained");
+}
+
+/// Adds a newly created execution to `batch`, or places it right away
+/// without batching.
+async fn hand_off_execution<P: Provider, B: Broker + Clone>(
+    processor: &QueueProcessor<P, B>,
+    batch: Option<&mut ExecutionBatch>,
+    execution_id: i64,
+) {
+    if let Some(batch) = batch {
+        batch.push(execution_id, Instant::now());
+        return;
+    }
+
+    if let Err(e) = execute_waiting_out_rate_limits(
+        &processor.broker,
+        &processor.pool,
+        &processor.stats,
+        &processor.config.circuit_breakers,
+        processor.config.locked_retry,
+        execution_id,
+        &processor.drain,
+    )
+    .await
+    {
+        error!("Failed to execute offchain order {execution_id}: {e}");
+    }
+}
+
+/// Executes the ready positions whose debounce window has elapsed, or every
+/// ready position without `debounce`, logging any failure.
+async fn execute_debounced_positions<B: Broker + Clone + Send + 'static>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    stats: &Arc<Stats>,
+    execution_permits: &Arc<Semaphore>,
+    symbol_permits: &Arc<Semaphore>,
+    debounce: Option<&ExecutionDebounce>,
+) {
+    if let Err(e) = check_and_execute_accumulated_positions(
+        broker,
+        config,
+        pool,
+        stats,
+        execution_permits,
+        symbol_permits,
+        debounce,
+    )
+    .await
+    {
+        error!("Failed to execute debounced positions: {e}");
+    }
+}
+
+/// Sleeps for `delay`, waking early once `drain` is cancelled.
+async fn sleep_unless_drained(delay: Duration, drain: &CancellationToken) {
+    tokio::select! {
+        () = sleep(delay) => {}
+        () = drain.cancelled() => {}
     }
 }
 
 #[tracing::instrument(skip_all, level = tracing::Level::DEBUG)]
-async fn process_next_queued_event<P: Provider + Clone>(
-    broker_type: SupportedBroker,
+async fn process_next_queued_event<P: Provider + Clone, B: Broker>(
+    broker: &B,
     config: &Config,
     pool: &SqlitePool,
     cache: &SymbolCache,
     provider: &P,
     feed_id_cache: &FeedIdCache,
-) -> Result<Option<OffchainExecution>, EventProcessingError> {
-    let queued_event = get_next_unprocessed_event(pool).await?;
+    stats: &Stats,
+) -> Result<Vec<OffchainExecution>, EventProcessingError> {
+    let queued_event =
+        get_next_prioritized_event(pool, &config.event_priorities, cache, provider).await?;
     let Some(queued_event) = queued_event else {
-        return Ok(None);
+        return Ok(vec![]);
+    };
+
+    let conversion = EventConversion {
+        cache,
+        provider,
+        feed_id_cache,
+    };
+    process_queued_event(broker, config, pool, &conversion, stats, &queued_event).await
+}
+
+/// Claims and processes `queued_event`, releasing the claim if processing
+/// fails so the event is retried.
+async fn process_queued_event<P: Provider + Clone, B: Broker>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    conversion: &EventConversion<'_, P>,
+    stats: &Stats,
+    queued_event: &QueuedEvent,
+) -> Result<Vec<OffchainExecution>, EventProcessingError> {
+    let event_id = extract_event_id(queued_event)?;
+    if !claim_event(pool, event_id).await? {
+        warn!("Skipping event {event_id}, it is already claimed");
+        return Ok(vec![]);
+    }
+
+    let result = process_claimed_event(
+        broker,
+        config,
+        pool,
+        conversion,
+        stats,
+        queued_event,
+        event_id,
+    )
+    .await;
+
+    if let Err(e) = &result {
+        // Put the event straight back so the next iteration retries it
+        if let Err(e) = release_claim(pool, event_id).await {
+            error!("Failed to release claim on event {event_id}: {e}");
+        }
+
+        record_conversion_outcome(
+            config,
+            pool,
+            queued_event,
+            Outcome::Error,
+            Some(e.to_string()),
+        )
+        .await;
+    }
+
+    result
+}
+
+/// What replaying a queued event found.
+#[derive(Debug)]
+pub(crate) enum ReplayOutcome {
+    /// The event converts to `trade`, priced by `price_resolution` when a
+    /// source in the chain could price it, and is hedged unless its event
+    /// type is not. A committed replay also records it and places the
+    /// executions it triggered, if any.
+    Trade {
+        trade: Box<OnchainTrade>,
+        price_resolution: Option<PriceResolution>,
+        hedged: bool,
+        execution_ids: Vec<i64>,
+    },
+    /// Processing would skip the event for `reason`
+    Filtered { reason: String },
+}
+
+/// Converts `queued_event` as the queue processor would, without touching
+/// the queue. With `commit` the event is then processed for real: its trade
+/// is recorded (or its filtering logged), the event marked processed and any
+/// execution it triggers placed with `broker`.
+pub(crate) async fn replay_queued_event<P: Provider + Clone, B: Broker + Clone + Send + 'static>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    provider: &P,
+    queued_event: &QueuedEvent,
+    commit: bool,
+) -> Result<ReplayOutcome, EventProcessingError> {
+    let cache = config.symbol_cache();
+    let feed_id_cache = FeedIdCache::with_capacity(config.symbol_cache_capacity);
+
+    let mut outcome = convert_for_replay(
+        broker,
+        config,
+        &cache,
+        provider,
+        &feed_id_cache,
+        queued_event,
+    )
+    .await?;
+    if !commit {
+        return Ok(outcome);
+    }
+
+    if queued_event.processed {
+        return Err(EventProcessingError::Queue(EventQueueError::Processing(
+            "Event was already processed".to_string(),
+        )));
+    }
+
+    let stats = Stats::default();
+    let conversion = EventConversion {
+        cache: &cache,
+        provider,
+        feed_id_cache: &feed_id_cache,
     };
+    let executions =
+        process_queued_event(broker, config, pool, &conversion, &stats, queued_event).await?;
+
+    for id in executions.iter().filter_map(|execution| execution.id) {
+        execute_pending_offchain_execution(
+            broker,
+            pool,
+            &stats,
+            &config.circuit_breakers,
+            config.locked_retry,
+            id,
+        )
+        .await?;
+
+        if let ReplayOutcome::Trade { execution_ids, .. } = &mut outcome {
+            execution_ids.push(id);
+        }
+    }
 
-    let event_id = extract_event_id(&queued_event)?;
+    Ok(outcome)
+}
 
+/// The trade `queued_event` converts to, or why processing would skip it,
+/// checked and priced as [`process_claimed_event`] would.
+async fn convert_for_replay<P: Provider + Clone, B: Broker>(
+    broker: &B,
+    config: &Config,
+    cache: &SymbolCache,
+    provider: &P,
+    feed_id_cache: &FeedIdCache,
+    queued_event: &QueuedEvent,
+) -> Result<ReplayOutcome, EventProcessingError> {
     let onchain_trade =
-        convert_event_to_trade(config, cache, provider, &queued_event, feed_id_cache).await?;
+        match convert_event_to_trade(config, cache, provider, queued_event, feed_id_cache).await {
+            Err(EventProcessingError::OnChain(OnChainError::Validation(
+                TradeValidationError::UnresolvedSymbol { token, .. },
+            ))) => {
+                return Ok(ReplayOutcome::Filtered {
+                    reason: format!("symbol() reverted for token {token}"),
+                });
+            }
+            Err(EventProcessingError::OnChain(OnChainError::Validation(
+                TradeValidationError::InvalidSymbolConfiguration(input_symbol, output_symbol),
+            ))) if config.symbol_configuration_mode == SymbolConfigurationMode::Lenient => {
+                return Ok(ReplayOutcome::Filtered {
+                    reason: format!(
+                        "unexpected symbol configuration {input_symbol} and {output_symbol}"
+                    ),
+                });
+            }
+            Err(EventProcessingError::OnChain(OnChainError::Validation(
+                TradeValidationError::Filtered(reason),
+            ))) => {
+                return Ok(ReplayOutcome::Filtered {
+                    reason: reason.to_string(),
+                });
+            }
+            result => result?,
+        };
 
     let Some(trade) = onchain_trade else {
-        return handle_filtered_event(pool, &queued_event, event_id).await;
+        return Ok(ReplayOutcome::Filtered {
+            reason: "no matching owner".to_string(),
+        });
+    };
+
+    if let Some((_, reason)) = trade_skip_reason(config, &trade) {
+        return Ok(ReplayOutcome::Filtered { reason });
+    }
+
+    let price_resolution = resolve_audit_price(broker, config, &trade).await?;
+
+    Ok(ReplayOutcome::Trade {
+        trade: Box::new(trade),
+        price_resolution,
+        hedged: is_hedged(&config.hedge_event_types, &queued_event.event),
+        execution_ids: vec![],
+    })
+}
+
+async fn process_claimed_event<P: Provider + Clone, B: Broker>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    conversion: &EventConversion<'_, P>,
+    stats: &Stats,
+    queued_event: &QueuedEvent,
+    event_id: i64,
+) -> Result<Vec<OffchainExecution>, EventProcessingError> {
+    let onchain_trade = match conversion.convert(config, queued_event).await {
+        Err(EventProcessingError::OnChain(OnChainError::Validation(
+            TradeValidationError::UnresolvedSymbol {
+                token,
+                fallback_symbol,
+            },
+        ))) => {
+            flag_event_for_review(pool, queued_event, event_id, token, fallback_symbol).await?;
+            record_conversion_outcome(
+                config,
+                pool,
+                queued_event,
+                Outcome::Filtered,
+                Some(format!("symbol() reverted for token {token}")),
+            )
+            .await;
+            stats.record_event_filtered();
+            return Ok(vec![]);
+        }
+        Err(EventProcessingError::OnChain(OnChainError::Validation(
+            TradeValidationError::InvalidSymbolConfiguration(input_symbol, output_symbol),
+        ))) => match config.symbol_configuration_mode {
+            SymbolConfigurationMode::Lenient => {
+                warn!(
+                    "Skipping trade with unexpected symbol configuration {input_symbol} and \
+                         {output_symbol}: tx_hash={:?}, log_index={}",
+                    queued_event.tx_hash, queued_event.log_index
+                );
+                Err((
+                    "unexpected symbol configuration",
+                    format!("unexpected symbol configuration {input_symbol} and {output_symbol}"),
+                ))
+            }
+            SymbolConfigurationMode::Strict => {
+                return Err(EventProcessingError::StrictSymbolConfiguration(
+                    input_symbol,
+                    output_symbol,
+                ));
+            }
+        },
+        Err(EventProcessingError::OnChain(OnChainError::Validation(
+            TradeValidationError::Filtered(reason),
+        ))) => Err((reason.label(), reason.to_string())),
+        result => result?.ok_or_else(|| ("no matching owner", "no matching owner".to_string())),
+    };
+
+    let trade = match onchain_trade
+        .and_then(|trade| trade_skip_reason(config, &trade).map_or(Ok(trade), Err))
+    {
+        Ok(trade) => trade,
+        Err((reason, outcome_reason)) => {
+            return skip_filtered_event(
+                config,
+                pool,
+                stats,
+                queued_event,
+                event_id,
+                reason,
+                outcome_reason,
+            )
+            .await;
+        }
+    };
+
+    let price_resolution = resolve_audit_price(broker, config, &trade).await?;
+
+    let executions = process_valid_trade(
+        broker,
+        config,
+        pool,
+        queued_event,
+        event_id,
+        trade,
+        price_resolution.as_ref(),
+    )
+    .await?;
+
+    record_conversion_outcome(config, pool, queued_event, Outcome::Converted, None).await;
+    stats.record_event_processed();
+    Metrics::global().record_event_processed();
+
+    Ok(executions)
+}
+
+/// Resolves the price of `trade` through the configured price source chain.
+///
+/// The trade is hedged at its onchain price; the resolved one is only recorded
+/// with the audit of the chain. A trade no source can price is still hedged,
+/// just without an audit record, so `None` is returned instead of an error.
+async fn resolve_audit_price<B: Broker>(
+    broker: &B,
+    config: &Config,
+    trade: &OnchainTrade,
+) -> Result<Option<PriceResolution>, EventProcessingError> {
+    let price_resolution = match resolve_trade_price(broker, &config.price_sources, trade).await {
+        Ok(price_resolution) => price_resolution,
+        Err(PriceSourceError::NoValidPrice { chain }) => {
+            warn!(
+                symbol = %trade.symbol,
+                tx_hash = ?trade.tx_hash,
+                log_index = trade.log_index,
+                "No price source in {chain:?} produced a valid price, hedging at the onchain price"
+            );
+            return Ok(None);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    info!(
+        "Resolved trade price from {}: price={}, onchain_ratio={}, tx_hash={:?}, log_index={}",
+        price_resolution.source,
+        price_resolution.price,
+        trade.price_usdc,
+        trade.tx_hash,
+        trade.log_index
+    );
+
+    Ok(Some(price_resolution))
+}
+
+/// Records how processing of `queued_event` ended when conversion outcomes are
+/// enabled. Failing to record only logs, it never fails event processing.
+async fn record_conversion_outcome(
+    config: &Config,
+    pool: &SqlitePool,
+    queued_event: &QueuedEvent,
+    outcome: Outcome,
+    reason: Option<String>,
+) {
+    if !config.record_conversion_outcomes {
+        return;
+    }
+
+    let conversion_outcome = ConversionOutcome {
+        tx_hash: queued_event.tx_hash,
+        log_index: queued_event.log_index,
+        outcome,
+        reason,
     };
 
-    process_valid_trade(broker_type, pool, &queued_event, event_id, trade).await
+    if let Err(e) = conversion_outcome.save(pool).await {
+        error!(
+            "Failed to record {outcome:?} conversion outcome: tx_hash={:?}, log_index={}: {e}",
+            queued_event.tx_hash, queued_event.log_index
+        );
+    }
 }
 
 fn extract_event_id(queued_event: &QueuedEvent) -> Result<i64, EventProcessingError> {
@@ -485,6 +1585,30 @@ fn extract_event_id(queued_event: &QueuedEvent) -> Result<i64, EventProcessingEr
     })
 }
 
+/// Lookups used to convert queued events into trades.
+struct EventConversion<'a, P> {
+    cache: &'a SymbolCache,
+    provider: &'a P,
+    feed_id_cache: &'a FeedIdCache,
+}
+
+impl<P: Provider + Clone> EventConversion<'_, P> {
+    async fn convert(
+        &self,
+        config: &Config,
+        queued_event: &QueuedEvent,
+    ) -> Result<Option<OnchainTrade>, EventProcessingError> {
+        convert_event_to_trade(
+            config,
+            self.cache,
+            self.provider,
+            queued_event,
+            self.feed_id_cache,
+        )
+        .await
+    }
+}
+
 #[tracing::instrument(skip_all, level = tracing::Level::DEBUG)]
 async fn convert_event_to_trade<P: Provider + Clone>(
     config: &Config,
@@ -523,14 +1647,81 @@ async fn convert_event_to_trade<P: Provider + Clone>(
     Ok(onchain_trade)
 }
 
+/// Width in basis points of the trade's Pyth confidence interval when it is
+/// wider than `max_bps`. Trades without a Pyth price are not checked.
+fn excessive_pyth_confidence(trade: &OnchainTrade, max_bps: Option<Decimal>) -> Option<Decimal> {
+    let max_bps = max_bps?;
+    let bps = confidence_bps(trade.pyth_price?, trade.pyth_confidence?)?;
+
+    (bps > max_bps).then_some(bps)
+}
+
+/// Why a converted `trade` is skipped instead of hedged, with the reason
+/// recorded as its conversion outcome, or `None` if it is hedged.
+fn trade_skip_reason(config: &Config, trade: &OnchainTrade) -> Option<(&'static str, String)> {
+    if let Some(reason) = config.symbol_filter.skip_reason(trade.symbol.base()) {
+        warn!(
+            symbol = %trade.symbol,
+            tx_hash = ?trade.tx_hash,
+            log_index = trade.log_index,
+            "Skipping trade: {reason}"
+        );
+        return Some((reason, format!("{reason}: {}", trade.symbol.base())));
+    }
+
+    let confidence_bps = excessive_pyth_confidence(trade, config.max_pyth_confidence_bps)?;
+    warn!(
+        symbol = %trade.symbol,
+        confidence_bps = %confidence_bps.round_dp(2),
+        tx_hash = ?trade.tx_hash,
+        log_index = trade.log_index,
+        "Skipping trade whose Pyth confidence interval is too wide"
+    );
+    Some((
+        "Pyth confidence too wide",
+        format!(
+            "Pyth confidence of {} bps too wide",
+            confidence_bps.round_dp(2)
+        ),
+    ))
+}
+
+/// Marks a filtered event processed, recording `outcome_reason` as its
+/// conversion outcome and counting it in `stats`.
+async fn skip_filtered_event(
+    config: &Config,
+    pool: &SqlitePool,
+    stats: &Stats,
+    queued_event: &QueuedEvent,
+    event_id: i64,
+    reason: &str,
+    outcome_reason: String,
+) -> Result<Vec<OffchainExecution>, EventProcessingError> {
+    let filtered =
+        handle_filtered_event(pool, config.locked_retry, queued_event, event_id, reason).await?;
+    record_conversion_outcome(
+        config,
+        pool,
+        queued_event,
+        Outcome::Filtered,
+        Some(outcome_reason),
+    )
+    .await;
+    stats.record_event_filtered();
+
+    Ok(filtered)
+}
+
 #[tracing::instrument(skip(pool, queued_event), fields(event_id), level = tracing::Level::DEBUG)]
 async fn handle_filtered_event(
     pool: &SqlitePool,
+    locked_retry: LockedRetryPolicy,
     queued_event: &QueuedEvent,
     event_id: i64,
-) -> Result<Option<OffchainExecution>, EventProcessingError> {
+    reason: &str,
+) -> Result<Vec<OffchainExecution>, EventProcessingError> {
     info!(
-        "Event filtered out (no matching owner): event_type={:?}, tx_hash={:?}, log_index={}",
+        "Event filtered out ({reason}): event_type={:?}, tx_hash={:?}, log_index={}",
         match &queued_event.event {
             TradeEvent::ClearV2(_) => "ClearV2",
             TradeEvent::TakeOrderV2(_) => "TakeOrderV2",
@@ -539,113 +1730,321 @@ async fn handle_filtered_event(
         queued_event.log_index
     );
 
+    retry_when_locked(locked_retry, "filtered event marking", || async {
+        let mut sql_tx = pool.begin().await?;
+        mark_event_processed(&mut sql_tx, event_id).await?;
+        sql_tx.commit().await?;
+        Ok::<_, EventQueueError>(())
+    })
+    .await
+    .map_err(|e| {
+        error!("Failed to mark filtered event {event_id} as processed: {e}");
+        EventProcessingError::Queue(e)
+    })?;
+
+    Ok(vec![])
+}
+
+/// Records a trade that cannot be hedged automatically for manual review and
+/// marks its event processed, so one misbehaving token does not stall the
+/// queue.
+async fn flag_event_for_review(
+    pool: &SqlitePool,
+    queued_event: &QueuedEvent,
+    event_id: i64,
+    token: Address,
+    fallback_symbol: String,
+) -> Result<(), EventProcessingError> {
+    warn!(
+        "Flagging trade for review, symbol() reverted for token {token} \
+         (fallback symbol '{fallback_symbol}'): tx_hash={:?}, log_index={}",
+        queued_event.tx_hash, queued_event.log_index
+    );
+
+    let review = TradeReview {
+        tx_hash: queued_event.tx_hash,
+        log_index: queued_event.log_index,
+        token,
+        fallback_symbol,
+        reason: "symbol() reverted, cannot map to a broker ticker".to_string(),
+    };
+
     let mut sql_tx = pool.begin().await.map_err(|e| {
-        error!("Failed to begin transaction for filtered event: {e}");
         EventProcessingError::Queue(crate::error::EventQueueError::Processing(format!(
             "Failed to begin transaction: {e}"
         )))
     })?;
 
+    review
+        .save_within_transaction(&mut sql_tx)
+        .await
+        .map_err(|e| {
+            EventProcessingError::Queue(crate::error::EventQueueError::Processing(format!(
+                "Failed to save trade for review: {e}"
+            )))
+        })?;
+
     mark_event_processed(&mut sql_tx, event_id).await?;
 
     sql_tx.commit().await.map_err(|e| {
-        error!("Failed to commit transaction for filtered event: {e}");
         EventProcessingError::Queue(crate::error::EventQueueError::Processing(format!(
             "Failed to commit transaction: {e}"
         )))
     })?;
 
-    Ok(None)
+    Ok(())
 }
 
-#[tracing::instrument(skip(pool, queued_event, trade), fields(event_id, symbol = %trade.symbol), level = tracing::Level::INFO)]
-async fn process_valid_trade(
-    broker_type: SupportedBroker,
+/// Saves a trade from an event type that is not hedged and marks its event
+/// processed, leaving the accumulator untouched.
+async fn record_unhedged_trade(
     pool: &SqlitePool,
     queued_event: &QueuedEvent,
     event_id: i64,
-    trade: OnchainTrade,
-) -> Result<Option<OffchainExecution>, EventProcessingError> {
+    trade: &OnchainTrade,
+    price_resolution: Option<&PriceResolution>,
+) -> Result<(), EventProcessingError> {
     info!(
-        "Event successfully converted to trade: event_type={:?}, tx_hash={:?}, log_index={}, symbol={}, amount={}",
-        match &queued_event.event {
-            TradeEvent::ClearV2(_) => "ClearV2",
-            TradeEvent::TakeOrderV2(_) => "TakeOrderV2",
-        },
-        trade.tx_hash,
-        trade.log_index,
+        "Recording trade without hedging, {:?} events are not hedged: symbol={}, amount={}, tx_hash={:?}, log_index={}",
+        HedgeEventType::of(&queued_event.event),
         trade.symbol,
-        trade.amount
-    );
-
-    let symbol_lock = get_symbol_lock(trade.symbol.base()).await;
-    let _guard = symbol_lock.lock().await;
-
-    info!(
-        "Processing queued trade: symbol={}, amount={}, direction={:?}, tx_hash={:?}, log_index={}",
-        trade.symbol, trade.amount, trade.direction, trade.tx_hash, trade.log_index
+        trade.amount,
+        queued_event.tx_hash,
+        queued_event.log_index
     );
 
-    process_trade_within_transaction(broker_type, pool, queued_event, event_id, trade).await
-}
-
-async fn process_trade_within_transaction(
-    broker_type: SupportedBroker,
-    pool: &SqlitePool,
-    queued_event: &QueuedEvent,
-    event_id: i64,
-    trade: OnchainTrade,
-) -> Result<Option<OffchainExecution>, EventProcessingError> {
     let mut sql_tx = pool.begin().await.map_err(|e| {
-        error!("Failed to begin transaction for event processing: {e}");
         EventProcessingError::AccumulatorProcessing(format!("Failed to begin transaction: {e}"))
     })?;
 
-    info!(
-        "Started transaction for atomic event processing: event_id={}, tx_hash={:?}, log_index={}",
-        event_id, queued_event.tx_hash, queued_event.log_index
-    );
-
-    let execution = accumulator::process_onchain_trade(&mut sql_tx, trade, broker_type)
+    trade
+        .save_within_transaction(&mut sql_tx)
         .await
         .map_err(|e| {
-            error!(
-                "Failed to process trade through accumulator: {e}, tx_hash={:?}, log_index={}",
-                queued_event.tx_hash, queued_event.log_index
-            );
             EventProcessingError::AccumulatorProcessing(format!(
-                "Failed to process trade through accumulator: {e}"
+                "Failed to save unhedged trade: {e}"
             ))
         })?;
 
-    mark_event_processed(&mut sql_tx, event_id)
+    if let Some(price_resolution) = price_resolution {
+        save_price_resolution(
+            &mut sql_tx,
+            queued_event.tx_hash,
+            queued_event.log_index,
+            price_resolution,
+        )
         .await
         .map_err(|e| {
-            error!("Failed to mark event {event_id} as processed: {e}");
-            EventProcessingError::Queue(e)
+            EventProcessingError::AccumulatorProcessing(format!(
+                "Failed to record price sources: {e}"
+            ))
         })?;
+    }
+
+    mark_event_processed(&mut sql_tx, event_id).await?;
 
     sql_tx.commit().await.map_err(|e| {
-        error!(
-            "Failed to commit transaction for event processing: {e}, event_id={}, tx_hash={:?}",
-            event_id, queued_event.tx_hash
-        );
         EventProcessingError::AccumulatorProcessing(format!("Failed to commit transaction: {e}"))
     })?;
 
-    info!(
-        "Successfully committed atomic event processing: event_id={}, tx_hash={:?}, log_index={}",
-        event_id, queued_event.tx_hash, queued_event.log_index
-    );
-
-    Ok(execution)
+    Ok(())
 }
 
-fn reconstruct_log_from_queued_event(
-    evm_env: &EvmEnv,
-    queued_event: &crate::queue::QueuedEvent,
-) -> Log {
-    use alloy::primitives::IntoLogData;
+#[tracing::instrument(skip(broker, config, pool, queued_event, trade, price_resolution), fields(event_id, symbol = %trade.symbol), level = tracing::Level::INFO)]
+async fn process_valid_trade<B: Broker>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    queued_event: &QueuedEvent,
+    event_id: i64,
+    trade: OnchainTrade,
+    price_resolution: Option<&PriceResolution>,
+) -> Result<Vec<OffchainExecution>, EventProcessingError> {
+    info!(
+        "Event successfully converted to trade: event_type={:?}, tx_hash={:?}, log_index={}, symbol={}, amount={}",
+        match &queued_event.event {
+            TradeEvent::ClearV2(_) => "ClearV2",
+            TradeEvent::TakeOrderV2(_) => "TakeOrderV2",
+        },
+        trade.tx_hash,
+        trade.log_index,
+        trade.symbol,
+        trade.amount
+    );
+
+    if !is_hedged(&config.hedge_event_types, &queued_event.event) {
+        record_unhedged_trade(pool, queued_event, event_id, &trade, price_resolution).await?;
+        config.trade_feed.publish(TradeFeedEvent::from(&trade));
+        return Ok(vec![]);
+    }
+
+    let symbol_lock = get_symbol_lock(trade.symbol.base()).await;
+    let _guard = symbol_lock.lock().await;
+
+    info!(
+        "Processing queued trade: symbol={}, amount={}, direction={:?}, tx_hash={:?}, log_index={}",
+        trade.symbol, trade.amount, trade.direction, trade.tx_hash, trade.log_index
+    );
+
+    // Fetched before the transaction so broker latency never holds the write lock
+    let liquidity = fetch_liquidity_limits(
+        broker,
+        config.liquidity.as_ref(),
+        [trade.symbol.base().clone()],
+    )
+    .await
+    .with_position_limits(config.position_limits.clone());
+
+    let trade_event = TradeFeedEvent::from(&trade);
+    let executions = process_trade_within_transaction(
+        config,
+        pool,
+        queued_event,
+        event_id,
+        trade,
+        price_resolution,
+        config.execution_rules(
+            broker.to_supported_broker(),
+            broker.supports_fractional_shares(),
+            &liquidity,
+        ),
+    )
+    .await?;
+
+    config.trade_feed.publish(trade_event);
+    for execution in &executions {
+        let Some(execution_id) = execution.id else {
+            continue;
+        };
+
+        config.trade_feed.publish(TradeFeedEvent::ExecutionStatus {
+            execution_id,
+            symbol: execution.symbol.clone(),
+            status: execution.state.status(),
+        });
+    }
+
+    Ok(executions)
+}
+
+/// Failure of one step of the event processing transaction, kept typed so a
+/// locked database can be told apart from other failures and retried.
+#[derive(Debug, thiserror::Error)]
+enum TradeTransactionError {
+    #[error("Failed to begin transaction: {0}")]
+    Begin(#[source] sqlx::Error),
+    #[error("Failed to process trade through accumulator: {0}")]
+    Accumulator(#[source] OnChainError),
+    #[error("Failed to record price sources: {0}")]
+    PriceSources(#[source] sqlx::Error),
+    #[error("Failed to mark event as processed: {0}")]
+    MarkProcessed(#[source] EventQueueError),
+    #[error("Failed to commit transaction: {0}")]
+    Commit(#[source] sqlx::Error),
+}
+
+impl From<TradeTransactionError> for EventProcessingError {
+    fn from(err: TradeTransactionError) -> Self {
+        match err {
+            TradeTransactionError::MarkProcessed(e) => Self::Queue(e),
+            other => Self::AccumulatorProcessing(other.to_string()),
+        }
+    }
+}
+
+async fn process_trade_within_transaction(
+    config: &Config,
+    pool: &SqlitePool,
+    queued_event: &QueuedEvent,
+    event_id: i64,
+    trade: OnchainTrade,
+    price_resolution: Option<&PriceResolution>,
+    rules: ExecutionRules<'_>,
+) -> Result<Vec<OffchainExecution>, EventProcessingError> {
+    let executions = retry_when_locked(config.locked_retry, "event processing", || {
+        try_process_trade_within_transaction(
+            config,
+            pool,
+            queued_event,
+            event_id,
+            trade.clone(),
+            price_resolution,
+            rules,
+        )
+    })
+    .await
+    .map_err(|e| {
+        error!(
+            "{e}, event_id={}, tx_hash={:?}, log_index={}",
+            event_id, queued_event.tx_hash, queued_event.log_index
+        );
+        EventProcessingError::from(e)
+    })?;
+
+    info!(
+        "Successfully committed atomic event processing: event_id={}, tx_hash={:?}, log_index={}",
+        event_id, queued_event.tx_hash, queued_event.log_index
+    );
+
+    Ok(executions)
+}
+
+async fn try_process_trade_within_transaction(
+    config: &Config,
+    pool: &SqlitePool,
+    queued_event: &QueuedEvent,
+    event_id: i64,
+    trade: OnchainTrade,
+    price_resolution: Option<&PriceResolution>,
+    rules: ExecutionRules<'_>,
+) -> Result<Vec<OffchainExecution>, TradeTransactionError> {
+    let mut sql_tx = pool.begin().await.map_err(TradeTransactionError::Begin)?;
+
+    info!(
+        "Started transaction for atomic event processing: event_id={}, tx_hash={:?}, log_index={}",
+        event_id, queued_event.tx_hash, queued_event.log_index
+    );
+
+    let accumulate = config.accumulates(trade.symbol.base());
+    let executions = accumulator::process_onchain_trade(
+        &mut sql_tx,
+        trade,
+        rules,
+        accumulate,
+        config.standby.is_standby() || config.execution_debounce.is_some(),
+        config.strategy_label.as_deref(),
+    )
+    .await
+    .map_err(TradeTransactionError::Accumulator)?;
+
+    if let Some(price_resolution) = price_resolution {
+        save_price_resolution(
+            &mut sql_tx,
+            queued_event.tx_hash,
+            queued_event.log_index,
+            price_resolution,
+        )
+        .await
+        .map_err(TradeTransactionError::PriceSources)?;
+    }
+
+    mark_event_processed(&mut sql_tx, event_id)
+        .await
+        .map_err(TradeTransactionError::MarkProcessed)?;
+
+    sql_tx
+        .commit()
+        .await
+        .map_err(TradeTransactionError::Commit)?;
+
+    Ok(executions)
+}
+
+fn reconstruct_log_from_queued_event(
+    evm_env: &EvmEnv,
+    queued_event: &crate::queue::QueuedEvent,
+) -> Log {
+    use alloy::primitives::IntoLogData;
 
     let log_data = match &queued_event.event {
         TradeEvent::ClearV2(clear_event) => clear_event.as_ref().clone().into_log_data(),
@@ -658,7 +2057,7 @@ fn reconstruct_log_from_queued_event(
 
     Log {
         inner: alloy::primitives::Log {
-            address: evm_env.orderbook,
+            address: queued_event.orderbook.unwrap_or(evm_env.orderbook),
             data: log_data,
         },
         block_hash: None,
@@ -671,13 +2070,57 @@ fn reconstruct_log_from_queued_event(
     }
 }
 
+/// Executes every accumulated position that is ready, placing at most as many
+/// broker orders at once as `execution_permits` allows, and waits for all of
+/// them so each outcome is logged before the next check. With `debounce`, a
+/// ready position is only executed once its debounce window has elapsed.
 #[tracing::instrument(skip_all, level = tracing::Level::DEBUG)]
 async fn check_and_execute_accumulated_positions<B: Broker + Clone + Send + 'static>(
     broker: &B,
+    config: &Config,
     pool: &SqlitePool,
+    stats: &Arc<Stats>,
+    execution_permits: &Arc<Semaphore>,
+    symbol_permits: &Arc<Semaphore>,
+    debounce: Option<&ExecutionDebounce>,
 ) -> Result<(), EventProcessingError> {
-    let broker_type = broker.to_supported_broker();
-    let executions = check_all_accumulated_positions(pool, broker_type).await?;
+    if config.standby.is_standby() {
+        debug!("Running as standby, not executing accumulated positions");
+        return Ok(());
+    }
+
+    let due = match debounce {
+        Some(debounce) => {
+            let due = debounce.due(&find_ready_symbols(pool).await?, Instant::now());
+            if due.is_empty() {
+                debug!("No accumulated positions past their debounce window");
+                return Ok(());
+            }
+            Some(due)
+        }
+        None => None,
+    };
+
+    let liquidity = match config.liquidity.as_ref() {
+        Some(policy) => {
+            let symbols = match &due {
+                Some(due) => due.clone(),
+                None => find_ready_symbols(pool).await?,
+            };
+            fetch_liquidity_limits(broker, Some(policy), symbols).await
+        }
+        None => LiquidityLimits::default(),
+    }
+    .with_position_limits(config.position_limits.clone());
+    let rules = config.execution_rules(
+        broker.to_supported_broker(),
+        broker.supports_fractional_shares(),
+        &liquidity,
+    );
+    let executions = match &due {
+        Some(due) => check_accumulated_positions_for(pool, due, rules).await?,
+        None => check_all_accumulated_positions(pool, rules).await?,
+    };
 
     if executions.is_empty() {
         debug!("No accumulated positions ready for execution");
@@ -689,6 +2132,33 @@ async fn check_and_execute_accumulated_positions<B: Broker + Clone + Send + 'sta
         executions.len()
     );
 
+    execute_accumulated_positions(
+        broker,
+        config,
+        pool,
+        stats,
+        execution_permits,
+        symbol_permits,
+        executions,
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Places `executions` concurrently, bounded by `execution_permits` and
+/// `symbol_permits`, and logs how many succeeded.
+async fn execute_accumulated_positions<B: Broker + Clone + Send + 'static>(
+    broker: &B,
+    config: &Config,
+    pool: &SqlitePool,
+    stats: &Arc<Stats>,
+    execution_permits: &Arc<Semaphore>,
+    symbol_permits: &Arc<Semaphore>,
+    executions: Vec<OffchainExecution>,
+) {
+    let mut tasks = JoinSet::new();
+
     for execution in executions {
         let Some(execution_id) = execution.id else {
             error!("Execution returned from check_all_accumulated_positions has None ID");
@@ -702,30 +2172,64 @@ async fn check_and_execute_accumulated_positions<B: Broker + Clone + Send + 'sta
 
         let pool_clone = pool.clone();
         let broker_clone = broker.clone();
-        tokio::spawn(async move {
-            if let Err(e) =
-                execute_pending_offchain_execution(&broker_clone, &pool_clone, execution_id).await
-            {
-                error!(
-                    "Failed to execute accumulated position for execution_id {}: {e}",
-                    execution_id
-                );
-            } else {
+        let stats_clone = stats.clone();
+        let circuit_breakers = config.circuit_breakers.clone();
+        let locked_retry = config.locked_retry;
+        let permits = execution_permits.clone();
+        let symbol_permits = symbol_permits.clone();
+        tasks.spawn(async move {
+            let _symbol_permit = symbol_permits.acquire_owned().await;
+            let _permit = permits.acquire_owned().await;
+            let result = execute_pending_offchain_execution(
+                &broker_clone,
+                &pool_clone,
+                &stats_clone,
+                &circuit_breakers,
+                locked_retry,
+                execution_id,
+            )
+            .await;
+            (execution_id, result)
+        });
+    }
+
+    let mut failed = 0_usize;
+    let mut succeeded = 0_usize;
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((execution_id, Ok(()))) => {
+                succeeded += 1;
                 info!(
                     "Successfully executed accumulated position for execution_id {}",
                     execution_id
                 );
             }
-        });
+            Ok((execution_id, Err(e))) => {
+                failed += 1;
+                error!(
+                    "Failed to execute accumulated position for execution_id {}: {e}",
+                    execution_id
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                error!("Accumulated position execution task panicked: {e}");
+            }
+        }
     }
 
-    Ok(())
+    info!("Accumulated position executions finished: succeeded={succeeded}, failed={failed}");
 }
 
-#[tracing::instrument(skip(broker, pool), level = tracing::Level::INFO)]
+/// Places the broker order of a pending execution unless the circuit breaker
+/// of its symbol is open.
+#[tracing::instrument(skip(broker, pool, stats, circuit_breakers), level = tracing::Level::INFO)]
 async fn execute_pending_offchain_execution<B: Broker + Clone + Send + 'static>(
     broker: &B,
     pool: &SqlitePool,
+    stats: &Stats,
+    circuit_breakers: &SymbolCircuitBreakers,
+    locked_retry: LockedRetryPolicy,
     execution_id: i64,
 ) -> Result<(), EventProcessingError> {
     let execution = find_execution_by_id(pool, execution_id)
@@ -736,47 +2240,257 @@ async fn execute_pending_offchain_execution<B: Broker + Clone + Send + 'static>(
             ))
         })?;
 
+    // The broker order is tagged with the client order key, so a retry of an
+    // execution whose order is already recorded must not place another one
+    let client_order_id = find_client_order_id(pool, execution_id).await?;
+    let placed = match &client_order_id {
+        Some(client_order_id) => find_execution_by_client_key(pool, client_order_id)
+            .await?
+            .filter(|placed| placed.state != OrderState::Pending)
+            .map(|placed| (client_order_id, placed)),
+        None => None,
+    };
+    if let Some((client_order_id, placed)) = placed {
+        info!(
+            "Execution {execution_id} already has order {client_order_id} in state {}, not placing it again",
+            placed.state.status()
+        );
+        return Ok(());
+    }
+
+    // Serializes placements per symbol, so a half-open breaker lets exactly
+    // one probe through and sees its outcome before the next placement
+    let symbol_lock = get_symbol_lock(&execution.symbol).await;
+    let _symbol_guard = symbol_lock.lock().await;
+    if !circuit_breakers.allows_placement(&execution.symbol, Instant::now()) {
+        return Err(EventProcessingError::CircuitBreakerOpen(
+            execution.symbol.to_string(),
+        ));
+    }
+
     info!("Executing offchain order: {execution:?}");
 
-    let market_order = MarketOrder {
-        symbol: execution.symbol.clone(),
-        shares: execution.shares,
-        direction: execution.direction,
+    let placement = match find_fractional_shares(pool, execution_id).await? {
+        Some(quantity) => broker
+            .place_fractional_market_order(FractionalMarketOrder {
+                symbol: execution.symbol.clone(),
+                quantity,
+                direction: execution.direction,
+                client_order_id,
+            })
+            .await
+            .map(|placement| {
+                (
+                    placement.order_id,
+                    placement.broker,
+                    placement.request_payload,
+                )
+            }),
+        None => broker
+            .place_market_order(MarketOrder {
+                symbol: execution.symbol.clone(),
+                shares: execution.shares,
+                direction: execution.direction,
+                client_order_id,
+            })
+            .await
+            .map(|placement| {
+                (
+                    placement.order_id,
+                    placement.broker,
+                    placement.request_payload,
+                )
+            }),
     };
+    let (order_id, order_broker, request_payload) = placement.map_err(|e| {
+        Metrics::global().record_broker_error(broker.to_supported_broker(), &e);
+        // A rate limit says nothing about the symbol, the order is retried
+        // once the broker allows it
+        if let Some(delay) = rate_limit_delay(&e) {
+            return EventProcessingError::RateLimited(delay);
+        }
 
-    let placement = broker.place_market_order(market_order).await.map_err(|e| {
+        stats.record_failure();
+        circuit_breakers.record_failure(&execution.symbol, Instant::now());
         EventProcessingError::AccumulatorProcessing(format!("Order placement failed: {e}"))
     })?;
 
-    info!("Order placed with ID: {}", placement.order_id);
+    circuit_breakers.record_success(&execution.symbol);
+    stats.record_execution_placed();
+    Metrics::global().record_order_placed(execution_id);
+    info!("Order placed with ID: {order_id}");
+
+    // Recording the order id and the broker holding it lets the order poller
+    // track it and keeps a retry from placing the order again
+    let submitted = OrderState::Submitted {
+        order_id: order_id.to_string(),
+    };
+    retry_when_locked(locked_retry, "submitted order recording", || async {
+        let mut sql_tx = pool.begin().await?;
+        submitted.store_update(&mut sql_tx, execution_id).await?;
+        record_order_broker_within_transaction(&mut sql_tx, execution_id, order_broker).await?;
+        sql_tx.commit().await?;
+        Ok::<_, OnChainError>(())
+    })
+    .await?;
+
+    // The order is already placed, so a failure to record its payload must
+    // not fail the execution
+    let payload_saved = match &request_payload {
+        Some(request_payload) => save_request_payload(pool, execution_id, request_payload).await,
+        None => Ok(()),
+    };
+    if let Err(e) = payload_saved {
+        error!("Failed to store request payload for execution {execution_id}: {e}");
+    }
+
+    Ok(())
+}
+
+/// Places the broker order of a pending execution like
+/// [`execute_pending_offchain_execution`], sleeping for as long as the broker
+/// asks whenever it rate limits the placement and retrying, until `drain` is
+/// cancelled.
+async fn execute_waiting_out_rate_limits<B: Broker + Clone + Send + 'static>(
+    broker: &B,
+    pool: &SqlitePool,
+    stats: &Stats,
+    circuit_breakers: &SymbolCircuitBreakers,
+    locked_retry: LockedRetryPolicy,
+    execution_id: i64,
+    drain: &CancellationToken,
+) -> Result<(), EventProcessingError> {
+    loop {
+        match execute_pending_offchain_execution(
+            broker,
+            pool,
+            stats,
+            circuit_breakers,
+            locked_retry,
+            execution_id,
+        )
+        .await
+        {
+            Err(EventProcessingError::RateLimited(delay)) if !drain.is_cancelled() => {
+                warn!("Broker rate limited execution {execution_id}, retrying in {delay:?}");
+                sleep_unless_drained(delay, drain).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Cancels the broker order of a submitted execution, marks the execution
+/// CANCELLED and releases the symbol for the next execution.
+///
+/// Shares of a partially filled order are not accounted for; only cancel
+/// orders that have not started filling.
+pub(crate) async fn cancel_offchain_execution<B: Broker>(
+    broker: &B,
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<(), EventProcessingError> {
+    let execution = find_execution_by_id(pool, execution_id)
+        .await?
+        .ok_or_else(|| {
+            EventProcessingError::AccumulatorProcessing(format!(
+                "Execution with ID {execution_id} not found"
+            ))
+        })?;
+
+    let OrderState::Submitted { order_id } = execution.state else {
+        return Err(EventProcessingError::AccumulatorProcessing(format!(
+            "Execution {execution_id} is {}, only SUBMITTED executions can be cancelled",
+            execution.state.status()
+        )));
+    };
+
+    let parsed_order_id = broker.parse_order_id(&order_id).map_err(|e| {
+        EventProcessingError::AccumulatorProcessing(format!("Invalid order ID {order_id}: {e}"))
+    })?;
+
+    broker
+        .cancel_order_at(execution.broker, &parsed_order_id)
+        .await
+        .map_err(|e| {
+            EventProcessingError::AccumulatorProcessing(format!("Order cancellation failed: {e}"))
+        })?;
+
+    info!("Cancelled order {order_id} for execution {execution_id}");
+
+    let mut sql_tx = pool.begin().await.map_err(OnChainError::from)?;
+    OrderState::Cancelled {
+        cancelled_at: Utc::now(),
+        order_id,
+    }
+    .store_update(&mut sql_tx, execution_id)
+    .await
+    .map_err(OnChainError::from)?;
+    clear_pending_execution_id(&mut sql_tx, &execution.symbol).await?;
+    clear_execution_lease(&mut sql_tx, &execution.symbol).await?;
+    sql_tx.commit().await.map_err(OnChainError::from)?;
 
     Ok(())
 }
 
-async fn wait_for_first_event_with_timeout<S1, S2>(
+/// Returns the log with its block number, fetching the transaction receipt
+/// when the subscription delivered the log without one.
+///
+/// Returns `None` (after logging) when the block number cannot be resolved.
+async fn resolve_block_number<P: Provider>(provider: &P, mut log: Log) -> Option<(Log, u64)> {
+    if let Some(block_number) = log.block_number {
+        return Some((log, block_number));
+    }
+
+    let Some(tx_hash) = log.transaction_hash else {
+        error!("Live event missing both block number and transaction hash");
+        return None;
+    };
+
+    match provider.get_transaction_receipt(tx_hash).await {
+        Ok(Some(receipt)) => {
+            let Some(block_number) = receipt.block_number else {
+                error!("Receipt for {tx_hash} has no block number yet");
+                return None;
+            };
+            debug!("Resolved missing block number {block_number} for {tx_hash} via receipt");
+            log.block_number = Some(block_number);
+            Some((log, block_number))
+        }
+        Ok(None) => {
+            error!("No receipt found for {tx_hash} while resolving missing block number");
+            None
+        }
+        Err(e) => {
+            error!("Failed to fetch receipt for {tx_hash} to resolve block number: {e}");
+            None
+        }
+    }
+}
+
+async fn wait_for_first_event_with_timeout<S1, S2, P>(
     clear_stream: &mut S1,
     take_stream: &mut S2,
+    provider: &P,
     timeout: std::time::Duration,
 ) -> Option<(Vec<(TradeEvent, Log)>, u64)>
 where
     S1: Stream<Item = Result<(ClearV2, Log), sol_types::Error>> + Unpin,
     S2: Stream<Item = Result<(TakeOrderV2, Log), sol_types::Error>> + Unpin,
+    P: Provider,
 {
     let deadline = tokio::time::sleep(timeout);
     tokio::pin!(deadline);
 
-    let mut events = Vec::new();
-
     loop {
         tokio::select! {
             Some(result) = clear_stream.next() => {
                 match result {
                     Ok((event, log)) => {
-                        if let Some(block_number) = log.block_number {
-                            events.push((TradeEvent::ClearV2(Box::new(event)), log));
-                            return Some((events, block_number));
+                        if let Some((log, block_number)) = resolve_block_number(provider, log).await {
+                            return Some((vec![(TradeEvent::ClearV2(Box::new(event)), log)], block_number));
                         }
-                        error!("ClearV2 event missing block number");
+                        error!("Dropping ClearV2 event with unresolvable block number");
                     }
                     Err(e) => {
                         error!("Error in clear event stream during startup: {e}");
@@ -786,11 +2500,10 @@ where
             Some(result) = take_stream.next() => {
                 match result {
                     Ok((event, log)) => {
-                        if let Some(block_number) = log.block_number {
-                            events.push((TradeEvent::TakeOrderV2(Box::new(event)), log));
-                            return Some((events, block_number));
+                        if let Some((log, block_number)) = resolve_block_number(provider, log).await {
+                            return Some((vec![(TradeEvent::TakeOrderV2(Box::new(event)), log)], block_number));
                         }
-                        error!("TakeOrderV2 event missing block number");
+                        error!("Dropping TakeOrderV2 event with unresolvable block number");
                     }
                     Err(e) => {
                         error!("Error in take event stream during startup: {e}");
@@ -804,30 +2517,40 @@ where
     }
 }
 
-async fn buffer_live_events<S1, S2>(
+async fn buffer_live_events<S1, S2, P>(
     clear_stream: &mut S1,
     take_stream: &mut S2,
+    provider: &P,
     event_buffer: &mut Vec<(TradeEvent, Log)>,
     cutoff_block: u64,
 ) where
     S1: Stream<Item = Result<(ClearV2, Log), sol_types::Error>> + Unpin,
     S2: Stream<Item = Result<(TakeOrderV2, Log), sol_types::Error>> + Unpin,
+    P: Provider,
 {
     loop {
         tokio::select! {
             Some(result) = clear_stream.next() => match result {
-                Ok((event, log)) if log.block_number.unwrap_or(0) >= cutoff_block => {
-                    event_buffer.push((TradeEvent::ClearV2(Box::new(event)), log));
+                Ok((event, log)) => {
+                    if let Some((log, _)) = resolve_block_number(provider, log)
+                        .await
+                        .filter(|(_, block_number)| *block_number >= cutoff_block)
+                    {
+                        event_buffer.push((TradeEvent::ClearV2(Box::new(event)), log));
+                    }
                 }
                 Err(e) => error!("Error in clear event stream during backfill: {e}"),
-                _ => {}
             },
             Some(result) = take_stream.next() => match result {
-                Ok((event, log)) if log.block_number.unwrap_or(0) >= cutoff_block => {
-                    event_buffer.push((TradeEvent::TakeOrderV2(Box::new(event)), log));
+                Ok((event, log)) => {
+                    if let Some((log, _)) = resolve_block_number(provider, log)
+                        .await
+                        .filter(|(_, block_number)| *block_number >= cutoff_block)
+                    {
+                        event_buffer.push((TradeEvent::TakeOrderV2(Box::new(event)), log));
+                    }
                 }
                 Err(e) => error!("Error in take event stream during backfill: {e}"),
-                _ => {}
             },
             else => break,
         }
@@ -836,18 +2559,39 @@ async fn buffer_live_events<S1, S2>(
 
 #[cfg(test)]
 mod tests {
+    use super::circuit_breaker::CircuitBreakerPolicy;
     use super::*;
-    use crate::bindings::IOrderBookV4::{ClearConfig, ClearV2};
+    use crate::bindings::IERC20::symbolCall;
+    use crate::bindings::IOrderBookV4::{ClearConfig, ClearV2, TakeOrderConfigV3};
     use crate::env::tests::create_test_config;
+    use crate::offchain::blackout::BlackoutCalendar;
+    use crate::offchain::execution::{
+        ExecutionFilter, find_executions_by_symbol_status_and_broker, list_executions,
+    };
+    use crate::offchain::liquidity::OversizeAction;
+    use crate::offchain::position_limit::PositionLimits;
+    use crate::offchain::trade_side::TradeSide;
+    use crate::onchain::io::SymbolConvention;
+    use crate::onchain::position_calculator::ShareRounding;
+    use crate::onchain::price_source::PriceSource;
     use crate::onchain::trade::OnchainTrade;
-    use crate::test_utils::{OnchainTradeBuilder, setup_test_db};
+    use crate::standby::Standby;
+    use crate::symbol::cache::SymbolFallback;
+    use crate::test_utils::{OffchainExecutionBuilder, OnchainTradeBuilder, setup_test_db};
     use crate::tokenized_symbol;
+    use crate::trade_feed::TradeFeed;
     use alloy::primitives::{IntoLogData, address, fixed_bytes};
     use alloy::providers::ProviderBuilder;
     use alloy::providers::mock::Asserter;
     use alloy::sol_types;
+    use alloy::sol_types::SolCall;
     use futures_util::stream;
-    use st0x_broker::{Direction, MockBrokerConfig, TryIntoBroker};
+    use rust_decimal_macros::dec;
+    use st0x_broker::{
+        Direction, FailoverBroker, FailoverPolicy, MockBroker, MockBrokerConfig, OrderStatus,
+        Shares, SimBroker, SimBrokerConfig, SupportedBroker, Symbol, TryIntoBroker,
+    };
+    use std::num::NonZeroU32;
 
     #[tokio::test]
     async fn test_event_enqueued_when_trade_conversion_returns_none() {
@@ -890,8 +2634,8 @@ mod tests {
             ))
             .with_log_index(293)
             .with_symbol("AAPL0x")
-            .with_amount(5.0)
-            .with_price(20000.0)
+            .with_amount(dec!(5.0))
+            .with_price(dec!(20000.0))
             .build();
         let mut sql_tx = pool.begin().await.unwrap();
         existing_trade
@@ -920,10 +2664,11 @@ mod tests {
                 "0xbeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
             ),
             log_index: 293,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 5.0,
+            amount: dec!(5.0),
             direction: Direction::Sell,
-            price_usdc: 20000.0,
+            price_usdc: dec!(20000.0),
             block_timestamp: None,
             created_at: None,
             gas_used: None,
@@ -999,9 +2744,25 @@ mod tests {
             .await
             {
                 let mut sql_tx = pool.begin().await.unwrap();
-                accumulator::process_onchain_trade(&mut sql_tx, trade, SupportedBroker::DryRun)
-                    .await
-                    .unwrap();
+                accumulator::process_onchain_trade(
+                    &mut sql_tx,
+                    trade,
+                    ExecutionRules {
+                        broker_type: SupportedBroker::DryRun,
+                        convention: &SymbolConvention::default(),
+                        blackout: &BlackoutCalendar::default(),
+                        dedup_window: None,
+                        liquidity: &LiquidityLimits::default(),
+                        trade_side: TradeSide::Both,
+                        rounding: ShareRounding::Truncate,
+                        fractional_shares: false,
+                    },
+                    true,
+                    false,
+                    None,
+                )
+                .await
+                .unwrap();
                 sql_tx.commit().await.unwrap();
             }
         }
@@ -1228,7 +2989,7 @@ mod tests {
         assert!(matches!(queued_event.event, TradeEvent::ClearV2(_)));
 
         let reconstructed_log = reconstruct_log_from_queued_event(&config.evm, &queued_event);
-        assert_eq!(reconstructed_log.inner.address, config.evm.orderbook);
+        assert_eq!(reconstructed_log.inner.address, log.address());
         assert_eq!(
             reconstructed_log.transaction_hash.unwrap(),
             queued_event.tx_hash
@@ -1272,10 +3033,12 @@ mod tests {
     async fn test_wait_for_first_event_with_timeout_no_events() {
         let mut clear_stream = stream::empty();
         let mut take_stream = stream::empty();
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
 
         let result = wait_for_first_event_with_timeout(
             &mut clear_stream,
             &mut take_stream,
+            &provider,
             std::time::Duration::from_millis(10),
         )
         .await;
@@ -1303,10 +3066,12 @@ mod tests {
 
         let mut clear_stream = stream::iter(vec![Ok((clear_event, log.clone()))]);
         let mut take_stream = stream::empty::<Result<(TakeOrderV2, Log), sol_types::Error>>();
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
 
         let result = wait_for_first_event_with_timeout(
             &mut clear_stream,
             &mut take_stream,
+            &provider,
             std::time::Duration::from_secs(1),
         )
         .await;
@@ -1339,133 +3104,1514 @@ mod tests {
         let mut clear_stream = stream::iter(vec![Ok((clear_event, log))]);
         let mut take_stream = stream::empty::<Result<(TakeOrderV2, Log), sol_types::Error>>();
 
+        // The receipt fallback finds nothing, so the event is still dropped
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::Value::Null);
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
         let result = wait_for_first_event_with_timeout(
             &mut clear_stream,
             &mut take_stream,
+            &provider,
             std::time::Duration::from_millis(100),
         )
-        .await;
+        .await;
+
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_buffer_live_events_filtering() {
+        let clear_event = ClearV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            alice: crate::test_utils::get_test_order(),
+            bob: crate::test_utils::get_test_order(),
+            clearConfig: ClearConfig {
+                aliceInputIOIndex: alloy::primitives::U256::from(0),
+                aliceOutputIOIndex: alloy::primitives::U256::from(1),
+                bobInputIOIndex: alloy::primitives::U256::from(1),
+                bobOutputIOIndex: alloy::primitives::U256::from(0),
+                aliceBountyVaultId: alloy::primitives::U256::ZERO,
+                bobBountyVaultId: alloy::primitives::U256::ZERO,
+            },
+        };
+
+        let mut early_log = crate::test_utils::get_test_log();
+        early_log.block_number = Some(99);
+
+        let mut late_log = crate::test_utils::get_test_log();
+        late_log.block_number = Some(101);
+
+        let events = vec![
+            Ok((clear_event.clone(), early_log)),
+            Ok((clear_event, late_log)),
+        ];
+
+        let mut clear_stream = stream::iter(events);
+        let mut take_stream = stream::empty::<Result<(TakeOrderV2, Log), sol_types::Error>>();
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+        let mut event_buffer = Vec::new();
+
+        buffer_live_events(
+            &mut clear_stream,
+            &mut take_stream,
+            &provider,
+            &mut event_buffer,
+            100,
+        )
+        .await;
+
+        assert_eq!(event_buffer.len(), 1);
+        assert_eq!(event_buffer[0].1.block_number.unwrap(), 101);
+    }
+
+    #[tokio::test]
+    async fn test_buffer_live_events_resolves_missing_block_number_from_receipt() {
+        let clear_event = ClearV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            alice: crate::test_utils::get_test_order(),
+            bob: crate::test_utils::get_test_order(),
+            clearConfig: ClearConfig {
+                aliceInputIOIndex: alloy::primitives::U256::from(0),
+                aliceOutputIOIndex: alloy::primitives::U256::from(1),
+                bobInputIOIndex: alloy::primitives::U256::from(1),
+                bobOutputIOIndex: alloy::primitives::U256::from(0),
+                aliceBountyVaultId: alloy::primitives::U256::ZERO,
+                bobBountyVaultId: alloy::primitives::U256::ZERO,
+            },
+        };
+
+        let mut log = crate::test_utils::get_test_log();
+        log.block_number = None;
+        let tx_hash = log.transaction_hash.unwrap();
+
+        // Receipt places the transaction in block 100 (0x64)
+        let mock_data = crate::test_utils::create_mock_blockchain_data(
+            address!("0x1111111111111111111111111111111111111111"),
+            tx_hash,
+            "9000000000000000000",
+            100_000_000,
+        );
+        let asserter = Asserter::new();
+        asserter.push_success(&mock_data.receipt_json);
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let mut clear_stream = stream::iter(vec![Ok((clear_event, log))]);
+        let mut take_stream = stream::empty::<Result<(TakeOrderV2, Log), sol_types::Error>>();
+        let mut event_buffer = Vec::new();
+
+        buffer_live_events(
+            &mut clear_stream,
+            &mut take_stream,
+            &provider,
+            &mut event_buffer,
+            100,
+        )
+        .await;
+
+        assert_eq!(event_buffer.len(), 1);
+        assert_eq!(event_buffer[0].1.block_number, Some(100));
+        assert_eq!(event_buffer[0].1.transaction_hash, Some(tx_hash));
+    }
+
+    #[tokio::test]
+    async fn test_process_live_event_clear_v2() {
+        let pool = setup_test_db().await;
+
+        let clear_event = ClearV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            alice: crate::test_utils::get_test_order(),
+            bob: crate::test_utils::get_test_order(),
+            clearConfig: ClearConfig {
+                aliceInputIOIndex: alloy::primitives::U256::from(0),
+                aliceOutputIOIndex: alloy::primitives::U256::from(1),
+                bobInputIOIndex: alloy::primitives::U256::from(1),
+                bobOutputIOIndex: alloy::primitives::U256::from(0),
+                aliceBountyVaultId: alloy::primitives::U256::ZERO,
+                bobBountyVaultId: alloy::primitives::U256::ZERO,
+            },
+        };
+        let log = crate::test_utils::get_test_log();
+
+        let result =
+            process_live_event(&pool, TradeEvent::ClearV2(Box::new(clear_event)), log).await;
+
+        assert!(result.is_ok());
+
+        let count = crate::queue::count_unprocessed(&pool).await.unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_clear_v2_event_filtering_without_errors() {
+        let pool = setup_test_db().await;
+        let config = create_test_config();
+        let cache = SymbolCache::default();
+        let feed_id_cache = FeedIdCache::default();
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let mut alice_order = crate::test_utils::get_test_order();
+        let mut bob_order = crate::test_utils::get_test_order();
+
+        alice_order.owner = address!("0x1111111111111111111111111111111111111111");
+        bob_order.owner = address!("0x2222222222222222222222222222222222222222");
+
+        let clear_event = ClearV2 {
+            sender: address!("0x3333333333333333333333333333333333333333"),
+            alice: alice_order,
+            bob: bob_order,
+            clearConfig: ClearConfig {
+                aliceInputIOIndex: alloy::primitives::U256::from(0),
+                aliceOutputIOIndex: alloy::primitives::U256::from(1),
+                bobInputIOIndex: alloy::primitives::U256::from(1),
+                bobOutputIOIndex: alloy::primitives::U256::from(0),
+                aliceBountyVaultId: alloy::primitives::U256::ZERO,
+                bobBountyVaultId: alloy::primitives::U256::ZERO,
+            },
+        };
+        let log = crate::test_utils::get_test_log();
+
+        crate::queue::enqueue(&pool, &clear_event, &log)
+            .await
+            .unwrap();
+
+        let count = crate::queue::count_unprocessed(&pool).await.unwrap();
+        assert_eq!(count, 1);
+
+        let broker = MockBroker::new();
+        let stats = Stats::default();
+        let result = process_next_queued_event(
+            &broker,
+            &config,
+            &pool,
+            &cache,
+            &provider,
+            &feed_id_cache,
+            &stats,
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_empty());
+
+        let remaining_count = crate::queue::count_unprocessed(&pool).await.unwrap();
+        assert_eq!(remaining_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_trade_with_reverting_symbol_is_flagged_for_review() {
+        let pool = setup_test_db().await;
+        let order = crate::test_utils::get_test_order();
+        let mut config = crate::env::tests::create_test_config_with_order_owner(order.owner);
+        config.symbol_fallback = Some(SymbolFallback::default());
+        let cache = config.symbol_cache();
+        let feed_id_cache = FeedIdCache::default();
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3 {
+                order,
+                inputIOIndex: alloy::primitives::U256::from(0),
+                outputIOIndex: alloy::primitives::U256::from(1),
+                signedContext: vec![],
+            },
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::from(9_000_000_000_000_000_000u128),
+        };
+        let log = crate::test_utils::get_test_log();
+        crate::queue::enqueue(&pool, &take_event, &log)
+            .await
+            .unwrap();
+
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::Value::Null);
+        asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+            &"USDC".to_string(),
+        ));
+        asserter.push_failure_msg("execution reverted");
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let broker = MockBroker::new();
+        let stats = Stats::default();
+        let execution = process_next_queued_event(
+            &broker,
+            &config,
+            &pool,
+            &cache,
+            &provider,
+            &feed_id_cache,
+            &stats,
+        )
+        .await
+        .unwrap();
+
+        assert!(execution.is_empty());
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 0);
+        assert_eq!(stats.snapshot().events_filtered, 1);
+
+        let share_token = address!("0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+        let reviews = TradeReview::find_all(&pool).await.unwrap();
+        assert_eq!(
+            reviews,
+            vec![TradeReview {
+                tx_hash: log.transaction_hash.unwrap(),
+                log_index: log.log_index.unwrap(),
+                token: share_token,
+                fallback_symbol: share_token.to_checksum(None),
+                reason: "symbol() reverted, cannot map to a broker ticker".to_string(),
+            }]
+        );
+
+        let trades = sqlx::query!("SELECT COUNT(*) as count FROM onchain_trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(trades.count, 0);
+    }
+
+    /// Processes a fill whose IO pairs USDC with a token that is not a
+    /// tokenized equity, returning the result with the pool and stats.
+    async fn process_invalid_symbol_configuration(
+        mode: SymbolConfigurationMode,
+    ) -> (
+        Result<Vec<OffchainExecution>, EventProcessingError>,
+        SqlitePool,
+        Stats,
+    ) {
+        let pool = setup_test_db().await;
+        let order = crate::test_utils::get_test_order();
+        let mut config = crate::env::tests::create_test_config_with_order_owner(order.owner);
+        config.symbol_configuration_mode = mode;
+        config.record_conversion_outcomes = true;
+        let cache = config.symbol_cache();
+        let feed_id_cache = FeedIdCache::default();
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3 {
+                order,
+                inputIOIndex: alloy::primitives::U256::from(0),
+                outputIOIndex: alloy::primitives::U256::from(1),
+                signedContext: vec![],
+            },
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::from(9_000_000_000_000_000_000u128),
+        };
+        let log = crate::test_utils::get_test_log();
+        crate::queue::enqueue(&pool, &take_event, &log)
+            .await
+            .unwrap();
+
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::Value::Null);
+        for symbol in ["USDC", "BTC"] {
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &symbol.to_string(),
+            ));
+        }
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let stats = Stats::default();
+        let result = process_next_queued_event(
+            &MockBroker::new(),
+            &config,
+            &pool,
+            &cache,
+            &provider,
+            &feed_id_cache,
+            &stats,
+        )
+        .await;
+
+        (result, pool, stats)
+    }
+
+    #[tokio::test]
+    async fn test_lenient_mode_skips_unexpected_symbol_configuration() {
+        let (result, pool, stats) =
+            process_invalid_symbol_configuration(SymbolConfigurationMode::Lenient).await;
+
+        assert!(result.unwrap().is_empty());
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 0);
+        assert_eq!(stats.snapshot().events_filtered, 1);
+
+        let log = crate::test_utils::get_test_log();
+        assert_eq!(
+            ConversionOutcome::find_all(&pool).await.unwrap(),
+            vec![ConversionOutcome {
+                tx_hash: log.transaction_hash.unwrap(),
+                log_index: log.log_index.unwrap(),
+                outcome: Outcome::Filtered,
+                reason: Some("unexpected symbol configuration USDC and BTC".to_string()),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unpriceable_trade_records_filter_reason() {
+        let pool = setup_test_db().await;
+        let order = crate::test_utils::get_test_order();
+        let mut config = crate::env::tests::create_test_config_with_order_owner(order.owner);
+        config.record_conversion_outcomes = true;
+        let cache = config.symbol_cache();
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3 {
+                order,
+                inputIOIndex: alloy::primitives::U256::from(0),
+                outputIOIndex: alloy::primitives::U256::from(1),
+                signedContext: vec![],
+            },
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::ZERO,
+        };
+        let log = crate::test_utils::get_test_log();
+        crate::queue::enqueue(&pool, &take_event, &log)
+            .await
+            .unwrap();
+
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::Value::Null);
+        for symbol in ["USDC", "AAPL0x"] {
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &symbol.to_string(),
+            ));
+        }
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let stats = Stats::default();
+        let executions = process_next_queued_event(
+            &MockBroker::new(),
+            &config,
+            &pool,
+            &cache,
+            &provider,
+            &FeedIdCache::default(),
+            &stats,
+        )
+        .await
+        .unwrap();
+
+        assert!(executions.is_empty());
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 0);
+        assert_eq!(stats.snapshot().events_filtered, 1);
+        assert_eq!(
+            ConversionOutcome::find_all(&pool).await.unwrap(),
+            vec![ConversionOutcome {
+                tx_hash: log.transaction_hash.unwrap(),
+                log_index: log.log_index.unwrap(),
+                outcome: Outcome::Filtered,
+                reason: Some("equity amount 0 is not positive".to_string()),
+            }]
+        );
+    }
+
+    /// Dry-run replays a one-share AAPL TakeOrderV2 trade of the configured
+    /// owner under `config`.
+    async fn replay_owned_take_order(mut config: Config) -> ReplayOutcome {
+        let pool = setup_test_db().await;
+        let order = crate::test_utils::get_test_order();
+        config.evm.order_owner = order.owner;
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3 {
+                order,
+                inputIOIndex: alloy::primitives::U256::from(0),
+                outputIOIndex: alloy::primitives::U256::from(1),
+                signedContext: vec![],
+            },
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::from(1_000_000_000_000_000_000u128),
+        };
+        crate::queue::enqueue(&pool, &take_event, &crate::test_utils::get_test_log())
+            .await
+            .unwrap();
+        let queued_event = crate::queue::get_next_unprocessed_event(&pool)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::Value::Null);
+        for symbol in ["USDC", "AAPL0x"] {
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &symbol.to_string(),
+            ));
+        }
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        replay_queued_event(
+            &MockBroker::new(),
+            &config,
+            &pool,
+            &provider,
+            &queued_event,
+            false,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replay_checks_trades_as_processing_would() {
+        let mut config = create_test_config();
+        config.symbol_filter =
+            crate::symbol::filter::SymbolFilter::new([], [Symbol::new("AAPL").unwrap()]);
+        let outcome = replay_owned_take_order(config).await;
+        assert!(
+            matches!(
+                &outcome,
+                ReplayOutcome::Filtered { reason } if reason == "symbol is denylisted: AAPL"
+            ),
+            "{outcome:?}"
+        );
+
+        let mut config = create_test_config();
+        config.hedge_event_types = vec![HedgeEventType::ClearV2];
+        let ReplayOutcome::Trade {
+            price_resolution,
+            hedged,
+            ..
+        } = replay_owned_take_order(config).await
+        else {
+            panic!("Expected the trade to convert");
+        };
+        assert!(!hedged);
+        assert_eq!(
+            price_resolution.map(|resolution| resolution.source),
+            Some(PriceSource::OnchainRatio)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_halts_on_unexpected_symbol_configuration() {
+        let (result, pool, stats) =
+            process_invalid_symbol_configuration(SymbolConfigurationMode::Strict).await;
+
+        let error = result.unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                EventProcessingError::StrictSymbolConfiguration(input, output)
+                    if input == "USDC" && output == "BTC"
+            ),
+            "unexpected error: {error}"
+        );
+
+        // The event is left unprocessed for investigation
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 1);
+        assert_eq!(stats.snapshot().events_filtered, 0);
+
+        let log = crate::test_utils::get_test_log();
+        assert_eq!(
+            ConversionOutcome::find_all(&pool).await.unwrap(),
+            vec![ConversionOutcome {
+                tx_hash: log.transaction_hash.unwrap(),
+                log_index: log.log_index.unwrap(),
+                outcome: Outcome::Error,
+                reason: Some(error.to_string()),
+            }]
+        );
+
+        let trades = sqlx::query!("SELECT COUNT(*) as count FROM onchain_trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(trades.count, 0);
+    }
+
+    #[test]
+    fn test_excessive_pyth_confidence() {
+        let max_bps = Some(dec!(50));
+
+        // 0.75 on 150 is 50 bps, right at the limit
+        let at_limit = OnchainTradeBuilder::new()
+            .with_pyth_price(150.0)
+            .with_pyth_confidence(0.75)
+            .build();
+        assert_eq!(excessive_pyth_confidence(&at_limit, max_bps), None);
+
+        let too_wide = OnchainTradeBuilder::new()
+            .with_pyth_price(150.0)
+            .with_pyth_confidence(1.5)
+            .build();
+        assert_eq!(
+            excessive_pyth_confidence(&too_wide, max_bps),
+            Some(dec!(100))
+        );
+        assert_eq!(excessive_pyth_confidence(&too_wide, None), None);
+
+        // Trades without a Pyth price cannot be checked and go ahead
+        let without_pyth = OnchainTradeBuilder::new().build();
+        assert_eq!(excessive_pyth_confidence(&without_pyth, max_bps), None);
+    }
+
+    /// Processes one ClearV2 (AAPL) and one TakeOrderV2 (MSFT) trade of one
+    /// share each and returns whether each was hedged, checking that an
+    /// unhedged trade is still recorded but never accumulated.
+    async fn process_clear_and_take_order(hedge_event_types: Vec<HedgeEventType>) -> (bool, bool) {
+        let pool = setup_test_db().await;
+        let mut config = create_test_config();
+        config.hedge_event_types = hedge_event_types;
+        let broker = MockBroker::new();
+
+        let clear_event = ClearV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            alice: crate::test_utils::get_test_order(),
+            bob: crate::test_utils::get_test_order(),
+            clearConfig: ClearConfig {
+                aliceInputIOIndex: alloy::primitives::U256::from(0),
+                aliceOutputIOIndex: alloy::primitives::U256::from(1),
+                bobInputIOIndex: alloy::primitives::U256::from(1),
+                bobOutputIOIndex: alloy::primitives::U256::from(0),
+                aliceBountyVaultId: alloy::primitives::U256::ZERO,
+                bobBountyVaultId: alloy::primitives::U256::ZERO,
+            },
+        };
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3::default(),
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::from(1_000_000_000_000_000_000u128),
+        };
+        let clear_log = crate::test_utils::create_log(1);
+        let take_log = crate::test_utils::create_log(2);
+        crate::queue::enqueue(&pool, &clear_event, &clear_log)
+            .await
+            .unwrap();
+        crate::queue::enqueue(&pool, &take_event, &take_log)
+            .await
+            .unwrap();
+
+        let mut hedged = Vec::new();
+        for (log, symbol) in [(&clear_log, "AAPL0x"), (&take_log, "MSFT0x")] {
+            let queued_event = crate::queue::get_next_unprocessed_event(&pool)
+                .await
+                .unwrap()
+                .unwrap();
+            let trade = OnchainTradeBuilder::new()
+                .with_symbol(symbol)
+                .with_tx_hash(log.transaction_hash.unwrap())
+                .with_log_index(log.log_index.unwrap())
+                .build();
+            let price_resolution = resolve_trade_price(&broker, &config.price_sources, &trade)
+                .await
+                .unwrap();
+
+            let execution = process_valid_trade(
+                &broker,
+                &config,
+                &pool,
+                &queued_event,
+                queued_event.id.unwrap(),
+                trade.clone(),
+                Some(&price_resolution),
+            )
+            .await
+            .unwrap();
+
+            let saved =
+                OnchainTrade::find_by_tx_hash_and_log_index(&pool, trade.tx_hash, trade.log_index)
+                    .await
+                    .unwrap();
+            assert_eq!(saved.symbol, trade.symbol);
+
+            let accumulated = accumulator::find_by_symbol(&pool, &trade.symbol.base().to_string())
+                .await
+                .unwrap();
+            assert_eq!(!execution.is_empty(), accumulated.is_some());
+
+            hedged.push(!execution.is_empty());
+        }
+
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 0);
+
+        (hedged[0], hedged[1])
+    }
+
+    #[tokio::test]
+    async fn test_hedging_only_clear_v2_records_take_order_trades() {
+        assert_eq!(
+            process_clear_and_take_order(vec![HedgeEventType::ClearV2]).await,
+            (true, false)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hedging_only_take_order_v2_records_clear_trades() {
+        assert_eq!(
+            process_clear_and_take_order(vec![HedgeEventType::TakeOrderV2]).await,
+            (false, true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hedging_both_event_types() {
+        assert_eq!(
+            process_clear_and_take_order(HedgeEventType::ALL.to_vec()).await,
+            (true, true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_processed_trade_is_published_to_trade_feed() {
+        let pool = setup_test_db().await;
+        let mut config = create_test_config();
+        config.trade_feed = TradeFeed::new(NonZeroUsize::new(16));
+        let mut receiver = config.trade_feed.subscribe().unwrap();
+        let broker = MockBroker::new();
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3::default(),
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::from(1_000_000_000_000_000_000u128),
+        };
+        let log = crate::test_utils::create_log(1);
+        crate::queue::enqueue(&pool, &take_event, &log)
+            .await
+            .unwrap();
+        let queued_event = crate::queue::get_next_unprocessed_event(&pool)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let trade = OnchainTradeBuilder::new()
+            .with_symbol("AAPL0x")
+            .with_tx_hash(log.transaction_hash.unwrap())
+            .with_log_index(log.log_index.unwrap())
+            .build();
+        let price_resolution = resolve_trade_price(&broker, &config.price_sources, &trade)
+            .await
+            .unwrap();
+
+        let execution = process_valid_trade(
+            &broker,
+            &config,
+            &pool,
+            &queued_event,
+            queued_event.id.unwrap(),
+            trade.clone(),
+            Some(&price_resolution),
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+
+        let saved =
+            OnchainTrade::find_by_tx_hash_and_log_index(&pool, trade.tx_hash, trade.log_index)
+                .await
+                .unwrap();
+        assert_eq!(saved.symbol, trade.symbol);
+        assert_eq!(receiver.try_recv().unwrap(), TradeFeedEvent::from(&trade));
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            TradeFeedEvent::ExecutionStatus {
+                execution_id: execution.id.unwrap(),
+                symbol: execution.symbol,
+                status: OrderStatus::Pending,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trade_without_valid_price_is_still_hedged() {
+        let pool = setup_test_db().await;
+        let mut config = create_test_config();
+        config.price_sources = vec![PriceSource::Pyth];
+        let broker = MockBroker::new();
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3::default(),
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::from(1_000_000_000_000_000_000u128),
+        };
+        let log = crate::test_utils::create_log(1);
+        crate::queue::enqueue(&pool, &take_event, &log)
+            .await
+            .unwrap();
+        let queued_event = crate::queue::get_next_unprocessed_event(&pool)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // No Pyth price was extracted for the trade
+        let trade = OnchainTradeBuilder::new()
+            .with_tx_hash(log.transaction_hash.unwrap())
+            .with_log_index(log.log_index.unwrap())
+            .build();
+
+        let price_resolution = resolve_audit_price(&broker, &config, &trade).await.unwrap();
+        assert!(price_resolution.is_none());
+
+        process_valid_trade(
+            &broker,
+            &config,
+            &pool,
+            &queued_event,
+            queued_event.id.unwrap(),
+            trade.clone(),
+            price_resolution.as_ref(),
+        )
+        .await
+        .unwrap();
+
+        let saved =
+            OnchainTrade::find_by_tx_hash_and_log_index(&pool, trade.tx_hash, trade.log_index)
+                .await
+                .unwrap();
+        assert_eq!(saved.price_usdc, trade.price_usdc);
+        let accumulated = accumulator::find_by_symbol(&pool, &trade.symbol.base().to_string())
+            .await
+            .unwrap();
+        assert!(accumulated.is_some());
+        assert_eq!(crate::queue::count_unprocessed(&pool).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_processing_flow_updates_stats() {
+        let pool = setup_test_db().await;
+        let config = create_test_config();
+        let cache = SymbolCache::default();
+        let feed_id_cache = FeedIdCache::default();
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let stats = Stats::default();
+
+        let clear_event = ClearV2 {
+            sender: address!("0x3333333333333333333333333333333333333333"),
+            alice: crate::test_utils::get_test_order(),
+            bob: crate::test_utils::get_test_order(),
+            clearConfig: ClearConfig {
+                aliceInputIOIndex: alloy::primitives::U256::from(0),
+                aliceOutputIOIndex: alloy::primitives::U256::from(1),
+                bobInputIOIndex: alloy::primitives::U256::from(1),
+                bobOutputIOIndex: alloy::primitives::U256::from(0),
+                aliceBountyVaultId: alloy::primitives::U256::ZERO,
+                bobBountyVaultId: alloy::primitives::U256::ZERO,
+            },
+        };
+        crate::queue::enqueue(&pool, &clear_event, &crate::test_utils::get_test_log())
+            .await
+            .unwrap();
+
+        let broker = MockBroker::new();
+        process_next_queued_event(
+            &broker,
+            &config,
+            &pool,
+            &cache,
+            &provider,
+            &feed_id_cache,
+            &stats,
+        )
+        .await
+        .unwrap();
+
+        let execution = OffchainExecutionBuilder::new().build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        execute_pending_offchain_execution(
+            &broker,
+            &pool,
+            &stats,
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap();
+
+        // The placed execution is now SUBMITTED, so the failure needs another one
+        let execution = OffchainExecutionBuilder::new().with_symbol("MSFT").build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let failing_broker = MockBroker::with_failure("rejected");
+        execute_pending_offchain_execution(
+            &failing_broker,
+            &pool,
+            &stats,
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap_err();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.events_filtered, 1);
+        assert_eq!(snapshot.events_processed, 0);
+        assert_eq!(snapshot.executions_placed, 1);
+        assert_eq!(snapshot.failures, 1);
+    }
+
+    /// Accumulates a whole share per symbol while a zero liquidity limit
+    /// defers execution, leaving every position ready for the periodic check.
+    async fn accumulate_ready_positions(pool: &SqlitePool, symbols: &[&str]) {
+        for (log_index, symbol) in (1..).zip(symbols) {
+            let trade = OnchainTradeBuilder::new()
+                .with_symbol(&format!("{symbol}0x"))
+                .with_amount(dec!(1.0))
+                .with_log_index(log_index)
+                .build();
+            let deferred =
+                LiquidityLimits::new(OversizeAction::Defer, [(Symbol::new(*symbol).unwrap(), 0)]);
+
+            let mut sql_tx = pool.begin().await.unwrap();
+            let execution = accumulator::process_onchain_trade(
+                &mut sql_tx,
+                trade,
+                ExecutionRules {
+                    broker_type: SupportedBroker::DryRun,
+                    convention: &SymbolConvention::default(),
+                    blackout: &BlackoutCalendar::default(),
+                    dedup_window: None,
+                    liquidity: &deferred,
+                    trade_side: TradeSide::Both,
+                    rounding: ShareRounding::Truncate,
+                    fractional_shares: false,
+                },
+                true,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+            sql_tx.commit().await.unwrap();
+            assert!(execution.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accumulated_position_executions_are_bounded_and_awaited() {
+        let pool = setup_test_db().await;
+        let symbols = ["AAPL", "MSFT", "NVDA", "TSLA", "AMZN", "META"];
+        accumulate_ready_positions(&pool, &symbols).await;
+
+        let broker = MockBroker::new().with_order_latency(Duration::from_millis(50));
+        let stats = Arc::new(Stats::default());
+        let execution_permits = Arc::new(Semaphore::new(2));
+        let symbol_permits = Arc::new(Semaphore::new(4));
+
+        check_and_execute_accumulated_positions(
+            &broker,
+            &create_test_config(),
+            &pool,
+            &stats,
+            &execution_permits,
+            &symbol_permits,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(broker.peak_orders_in_flight(), 2);
+        assert_eq!(stats.snapshot().executions_placed, symbols.len() as u64);
+        assert!(find_ready_symbols(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fills_within_debounce_window_are_placed_as_one_order() {
+        let pool = setup_test_db().await;
+
+        for log_index in 1..=2 {
+            let trade = OnchainTradeBuilder::new()
+                .with_amount(dec!(1.0))
+                .with_log_index(log_index)
+                .build();
+
+            let mut sql_tx = pool.begin().await.unwrap();
+            let execution = accumulator::process_onchain_trade(
+                &mut sql_tx,
+                trade,
+                ExecutionRules {
+                    broker_type: SupportedBroker::DryRun,
+                    convention: &SymbolConvention::default(),
+                    blackout: &BlackoutCalendar::default(),
+                    dedup_window: None,
+                    liquidity: &LiquidityLimits::default(),
+                    trade_side: TradeSide::Both,
+                    rounding: ShareRounding::Truncate,
+                    fractional_shares: false,
+                },
+                true,
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+            sql_tx.commit().await.unwrap();
+            assert!(execution.is_empty());
+        }
+
+        let broker = MockBroker::new();
+        let stats = Arc::new(Stats::default());
+        let execution_permits = Arc::new(Semaphore::new(4));
+        let symbol_permits = Arc::new(Semaphore::new(4));
+        let debounce = ExecutionDebounce::new(Duration::from_millis(50));
+
+        // The first check only starts the window of the ready position
+        check_and_execute_accumulated_positions(
+            &broker,
+            &create_test_config(),
+            &pool,
+            &stats,
+            &execution_permits,
+            &symbol_permits,
+            Some(&debounce),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.snapshot().executions_placed, 0);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        check_and_execute_accumulated_positions(
+            &broker,
+            &create_test_config(),
+            &pool,
+            &stats,
+            &execution_permits,
+            &symbol_permits,
+            Some(&debounce),
+        )
+        .await
+        .unwrap();
+
+        let executions = list_executions(&pool, &ExecutionFilter::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(stats.snapshot().executions_placed, 1);
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].shares, Shares::new(2).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_symbol_processing_limit_is_shared_across_symbols() {
+        let pool = setup_test_db().await;
+        let symbols = ["AAPL", "MSFT", "NVDA", "TSLA", "AMZN", "META"];
+        accumulate_ready_positions(&pool, &symbols).await;
+
+        let broker = MockBroker::new().with_order_latency(Duration::from_millis(50));
+        let stats = Arc::new(Stats::default());
+        let execution_permits = Arc::new(Semaphore::new(symbols.len()));
+        let symbol_permits = Arc::new(Semaphore::new(3));
+
+        // The queue processor is converting an event for another symbol
+        let queue_permit = symbol_permits.clone().acquire_owned().await.unwrap();
+
+        check_and_execute_accumulated_positions(
+            &broker,
+            &create_test_config(),
+            &pool,
+            &stats,
+            &execution_permits,
+            &symbol_permits,
+            None,
+        )
+        .await
+        .unwrap();
+        drop(queue_permit);
+
+        assert_eq!(broker.peak_orders_in_flight(), 2);
+        assert_eq!(stats.snapshot().executions_placed, symbols.len() as u64);
+        assert_eq!(symbol_permits.available_permits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_execution_beyond_net_position_limit_is_skipped() {
+        let pool = setup_test_db().await;
+        accumulate_ready_positions(&pool, &["MSFT"]).await;
+
+        // Two shares already sold at the broker
+        let mut filled = OffchainExecutionBuilder::new().build();
+        filled.shares = Shares::new(2).unwrap();
+        filled.direction = Direction::Sell;
+        filled.state = OrderState::Filled {
+            order_id: "ORD1".to_string(),
+            executed_at: Utc::now(),
+            price_cents: 15025,
+            reported_price: None,
+        };
+        let mut sql_tx = pool.begin().await.unwrap();
+        filled.save_within_transaction(&mut sql_tx).await.unwrap();
+
+        // Two more accumulated onchain would take the position to -4
+        let trade = OnchainTradeBuilder::new()
+            .with_amount(dec!(2.0))
+            .with_log_index(2)
+            .build();
+        let deferred =
+            LiquidityLimits::new(OversizeAction::Defer, [(Symbol::new("AAPL").unwrap(), 0)]);
+        accumulator::process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: SupportedBroker::DryRun,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &deferred,
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let broker = MockBroker::new();
+        let stats = Arc::new(Stats::default());
+        let position_limits = PositionLimits::new(Some(3), []);
+
+        check_and_execute_accumulated_positions(
+            &broker,
+            &Config {
+                position_limits,
+                ..create_test_config()
+            },
+            &pool,
+            &stats,
+            &Arc::new(Semaphore::new(1)),
+            &Arc::new(Semaphore::new(1)),
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Only MSFT stays within the limit; AAPL keeps its shares accumulated
+        assert_eq!(broker.orders_placed(), 1);
+        assert_eq!(
+            find_ready_symbols(&pool).await.unwrap(),
+            vec![Symbol::new("AAPL").unwrap()]
+        );
+        let pending = find_executions_by_symbol_status_and_broker(
+            &pool,
+            Some(Symbol::new("AAPL").unwrap()),
+            OrderStatus::Pending,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_standby_accumulates_without_trading_until_promoted() {
+        let pool = setup_test_db().await;
+        let standby = Standby::new(true);
+        let config = Config {
+            standby: standby.clone(),
+            ..create_test_config()
+        };
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(1.5)).build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution = accumulator::process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: SupportedBroker::DryRun,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            standby.is_standby(),
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert!(execution.is_empty());
+        assert_eq!(
+            find_ready_symbols(&pool).await.unwrap(),
+            vec![Symbol::new("AAPL").unwrap()]
+        );
+
+        let broker = MockBroker::new();
+        let stats = Arc::new(Stats::default());
+        let execution_permits = Arc::new(Semaphore::new(1));
+        let symbol_permits = Arc::new(Semaphore::new(1));
+
+        check_and_execute_accumulated_positions(
+            &broker,
+            &config,
+            &pool,
+            &stats,
+            &execution_permits,
+            &symbol_permits,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.snapshot().executions_placed, 0);
 
-        assert!(result.is_none());
+        assert!(standby.promote());
+        assert!(!standby.promote());
+
+        check_and_execute_accumulated_positions(
+            &broker,
+            &config,
+            &pool,
+            &stats,
+            &execution_permits,
+            &symbol_permits,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.snapshot().executions_placed, 1);
+        assert!(find_ready_symbols(&pool).await.unwrap().is_empty());
     }
 
     #[tokio::test]
-    async fn test_buffer_live_events_filtering() {
-        let clear_event = ClearV2 {
-            sender: address!("0x1111111111111111111111111111111111111111"),
-            alice: crate::test_utils::get_test_order(),
-            bob: crate::test_utils::get_test_order(),
-            clearConfig: ClearConfig {
-                aliceInputIOIndex: alloy::primitives::U256::from(0),
-                aliceOutputIOIndex: alloy::primitives::U256::from(1),
-                bobInputIOIndex: alloy::primitives::U256::from(1),
-                bobOutputIOIndex: alloy::primitives::U256::from(0),
-                aliceBountyVaultId: alloy::primitives::U256::ZERO,
-                bobBountyVaultId: alloy::primitives::U256::ZERO,
-            },
+    async fn test_execution_with_recorded_order_is_not_placed_again() {
+        let pool = setup_test_db().await;
+        let stats = Stats::default();
+
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.state = OrderState::Submitted {
+            order_id: "TEST_1".to_string(),
         };
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
 
-        let mut early_log = crate::test_utils::get_test_log();
-        early_log.block_number = Some(99);
+        // Would fail if it were asked to place the order
+        let broker = MockBroker::with_failure("rejected");
+        execute_pending_offchain_execution(
+            &broker,
+            &pool,
+            &stats,
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap();
 
-        let mut late_log = crate::test_utils::get_test_log();
-        late_log.block_number = Some(101);
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.executions_placed, 0);
+        assert_eq!(snapshot.failures, 0);
+    }
 
-        let events = vec![
-            Ok((clear_event.clone(), early_log)),
-            Ok((clear_event, late_log)),
-        ];
+    #[tokio::test]
+    async fn test_fractional_execution_is_placed_as_fractional_order() {
+        let pool = setup_test_db().await;
+        let stats = Stats::default();
 
-        let mut clear_stream = stream::iter(events);
-        let mut take_stream = stream::empty::<Result<(TakeOrderV2, Log), sol_types::Error>>();
-        let mut event_buffer = Vec::new();
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.shares = Shares::new(1).unwrap();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        crate::offchain::execution::record_fractional_shares_within_transaction(
+            &mut sql_tx,
+            execution_id,
+            dec!(0.4),
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
 
-        buffer_live_events(&mut clear_stream, &mut take_stream, &mut event_buffer, 100).await;
+        // A whole-share broker rejects the order instead of buying a share
+        let whole_share_broker = MockBroker::new();
+        execute_pending_offchain_execution(
+            &whole_share_broker,
+            &pool,
+            &stats,
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(whole_share_broker.orders_placed(), 0);
 
-        assert_eq!(event_buffer.len(), 1);
-        assert_eq!(event_buffer[0].1.block_number.unwrap(), 101);
+        let broker = MockBroker::new().with_fractional_shares();
+        execute_pending_offchain_execution(
+            &broker,
+            &pool,
+            &stats,
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(broker.orders_placed(), 1);
+        let execution = find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.state.status(), OrderStatus::Submitted);
     }
 
     #[tokio::test]
-    async fn test_process_live_event_clear_v2() {
+    async fn test_failed_over_order_is_tracked_at_the_holding_broker() {
         let pool = setup_test_db().await;
+        let stats = Stats::default();
 
-        let clear_event = ClearV2 {
-            sender: address!("0x1111111111111111111111111111111111111111"),
-            alice: crate::test_utils::get_test_order(),
-            bob: crate::test_utils::get_test_order(),
-            clearConfig: ClearConfig {
-                aliceInputIOIndex: alloy::primitives::U256::from(0),
-                aliceOutputIOIndex: alloy::primitives::U256::from(1),
-                bobInputIOIndex: alloy::primitives::U256::from(1),
-                bobOutputIOIndex: alloy::primitives::U256::from(0),
-                aliceBountyVaultId: alloy::primitives::U256::ZERO,
-                bobBountyVaultId: alloy::primitives::U256::ZERO,
-            },
-        };
-        let log = crate::test_utils::get_test_log();
+        let execution = OffchainExecutionBuilder::new().build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
 
-        let result =
-            process_live_event(&pool, TradeEvent::ClearV2(Box::new(clear_event)), log).await;
+        let failover = |secondary| {
+            FailoverBroker::new(
+                MockBroker::with_outage("primary down"),
+                secondary,
+                FailoverPolicy {
+                    failure_threshold: NonZeroU32::MIN,
+                    probe_interval: Duration::from_hours(1),
+                },
+            )
+        };
+        let secondary = SimBroker::new(SimBrokerConfig::default());
+        execute_pending_offchain_execution(
+            &failover(secondary.clone()),
+            &pool,
+            &stats,
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap();
 
-        assert!(result.is_ok());
+        let execution = find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.broker, SupportedBroker::Sim);
+        assert_eq!(execution.state.status(), OrderStatus::Submitted);
 
-        let count = crate::queue::count_unprocessed(&pool).await.unwrap();
-        assert_eq!(count, 1);
+        // After a restart the order is still cancelled at the secondary
+        cancel_offchain_execution(&failover(secondary), &pool, execution_id)
+            .await
+            .unwrap();
+        let execution = find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.state.status(), OrderStatus::Cancelled);
     }
 
     #[tokio::test]
-    async fn test_clear_v2_event_filtering_without_errors() {
+    async fn test_circuit_breaker_skips_symbol_after_consecutive_failures() {
         let pool = setup_test_db().await;
-        let config = create_test_config();
-        let cache = SymbolCache::default();
-        let feed_id_cache = FeedIdCache::default();
-        let asserter = Asserter::new();
-        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let stats = Stats::default();
 
-        let mut alice_order = crate::test_utils::get_test_order();
-        let mut bob_order = crate::test_utils::get_test_order();
+        let execution = OffchainExecutionBuilder::new().build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
 
-        alice_order.owner = address!("0x1111111111111111111111111111111111111111");
-        bob_order.owner = address!("0x2222222222222222222222222222222222222222");
+        let broker = MockBroker::with_failure("symbol halted");
+        let breakers = SymbolCircuitBreakers::new(CircuitBreakerPolicy {
+            failure_threshold: NonZeroU32::new(2).unwrap(),
+            failure_window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(3600),
+        });
+        for _ in 0..2 {
+            let error = execute_pending_offchain_execution(
+                &broker,
+                &pool,
+                &stats,
+                &breakers,
+                LockedRetryPolicy::default(),
+                execution_id,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                error,
+                EventProcessingError::AccumulatorProcessing(_)
+            ));
+        }
 
-        let clear_event = ClearV2 {
-            sender: address!("0x3333333333333333333333333333333333333333"),
-            alice: alice_order,
-            bob: bob_order,
-            clearConfig: ClearConfig {
-                aliceInputIOIndex: alloy::primitives::U256::from(0),
-                aliceOutputIOIndex: alloy::primitives::U256::from(1),
-                bobInputIOIndex: alloy::primitives::U256::from(1),
-                bobOutputIOIndex: alloy::primitives::U256::from(0),
-                aliceBountyVaultId: alloy::primitives::U256::ZERO,
-                bobBountyVaultId: alloy::primitives::U256::ZERO,
-            },
-        };
-        let log = crate::test_utils::get_test_log();
+        // The broker would accept the order now, but the breaker is open
+        broker.set_failing(false);
+        let error = execute_pending_offchain_execution(
+            &broker,
+            &pool,
+            &stats,
+            &breakers,
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, EventProcessingError::CircuitBreakerOpen(_)));
+        assert_eq!(broker.orders_placed(), 0);
+        assert_eq!(stats.snapshot().failures, 2);
+
+        // Once the cooldown has elapsed a successful probe closes the breaker
+        let breakers = SymbolCircuitBreakers::new(CircuitBreakerPolicy {
+            failure_threshold: NonZeroU32::new(1).unwrap(),
+            failure_window: Duration::from_secs(60),
+            cooldown: Duration::ZERO,
+        });
+        broker.set_failing(true);
+        execute_pending_offchain_execution(
+            &broker,
+            &pool,
+            &stats,
+            &breakers,
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap_err();
+        broker.set_failing(false);
+        execute_pending_offchain_execution(
+            &broker,
+            &pool,
+            &stats,
+            &breakers,
+            LockedRetryPolicy::default(),
+            execution_id,
+        )
+        .await
+        .unwrap();
+        assert_eq!(broker.orders_placed(), 1);
+    }
 
-        crate::queue::enqueue(&pool, &clear_event, &log)
+    #[tokio::test]
+    async fn test_rate_limited_placement_retried_after_requested_delay() {
+        let pool = setup_test_db().await;
+        let stats = Stats::default();
+
+        let execution = OffchainExecutionBuilder::new().build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
             .await
             .unwrap();
+        sql_tx.commit().await.unwrap();
 
-        let count = crate::queue::count_unprocessed(&pool).await.unwrap();
-        assert_eq!(count, 1);
+        let broker = MockBroker::new();
+        // A breaker opening on the first failure shows the rate limit is not
+        // counted against the symbol
+        let breakers = SymbolCircuitBreakers::new(CircuitBreakerPolicy {
+            failure_threshold: NonZeroU32::new(1).unwrap(),
+            failure_window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(3600),
+        });
 
-        let result = process_next_queued_event(
-            SupportedBroker::DryRun,
-            &config,
+        broker.rate_limit_next_placement(1);
+        let error = execute_pending_offchain_execution(
+            &broker,
             &pool,
-            &cache,
-            &provider,
-            &feed_id_cache,
+            &stats,
+            &breakers,
+            LockedRetryPolicy::default(),
+            execution_id,
         )
-        .await;
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(error, EventProcessingError::RateLimited(delay) if delay == Duration::from_secs(1))
+        );
 
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_none());
+        broker.rate_limit_next_placement(1);
+        let started = Instant::now();
+        execute_waiting_out_rate_limits(
+            &broker,
+            &pool,
+            &stats,
+            &breakers,
+            LockedRetryPolicy::default(),
+            execution_id,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
 
-        let remaining_count = crate::queue::count_unprocessed(&pool).await.unwrap();
-        assert_eq!(remaining_count, 0);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(broker.orders_placed(), 1);
+        assert_eq!(stats.snapshot().failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_offchain_execution_marks_execution_cancelled() {
+        let pool = setup_test_db().await;
+        let broker = MockBroker::new();
+
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.state = OrderState::Submitted {
+            order_id: "TEST_1".to_string(),
+        };
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        cancel_offchain_execution(&broker, &pool, execution_id)
+            .await
+            .unwrap();
+
+        let stored = find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            stored.state,
+            OrderState::Cancelled { ref order_id, .. } if order_id == "TEST_1"
+        ));
+        assert!(matches!(
+            broker
+                .get_order_status(&"TEST_1".to_string())
+                .await
+                .unwrap(),
+            OrderState::Cancelled { .. }
+        ));
+
+        // Already cancelled, so there is nothing left to cancel
+        assert!(matches!(
+            cancel_offchain_execution(&broker, &pool, execution_id)
+                .await
+                .unwrap_err(),
+            EventProcessingError::AccumulatorProcessing(_)
+        ));
     }
 
     #[tokio::test]
@@ -1473,13 +4619,89 @@ mod tests {
         let pool = setup_test_db().await;
         let broker = MockBrokerConfig.try_into_broker().await.unwrap();
 
-        let result = execute_pending_offchain_execution(&broker, &pool, 99999).await;
+        let result = execute_pending_offchain_execution(
+            &broker,
+            &pool,
+            &Stats::default(),
+            &SymbolCircuitBreakers::default(),
+            LockedRetryPolicy::default(),
+            99999,
+        )
+        .await;
         assert!(matches!(
             result.unwrap_err(),
             EventProcessingError::AccumulatorProcessing(_)
         ));
     }
 
+    #[tokio::test]
+    async fn test_ended_streams_are_resubscribed_from_last_seen_block() {
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+        let (event_sender, mut event_receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            config: TakeOrderConfigV3 {
+                order: crate::test_utils::get_test_order(),
+                inputIOIndex: alloy::primitives::U256::from(0),
+                outputIOIndex: alloy::primitives::U256::from(1),
+                signedContext: vec![],
+            },
+            input: alloy::primitives::U256::from(100_000_000u64),
+            output: alloy::primitives::U256::from(9_000_000_000_000_000_000u128),
+        };
+        let mut log = crate::test_utils::get_test_log();
+        log.block_number = Some(150);
+
+        // The first resubscription delivers one event and ends, later ones
+        // stay open without events
+        let resubscriptions = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = resubscriptions.clone();
+        let resubscribed_provider = provider.clone();
+        let resubscribe: Resubscribe<_> = Box::new(move |last_seen_block| {
+            let mut recorded = recorded.lock().unwrap();
+            let (clear_stream, take_stream): (ClearStream, TakeStream) = if recorded.is_empty() {
+                (
+                    Box::new(stream::empty()),
+                    Box::new(stream::iter([Ok((take_event.clone(), log.clone()))])),
+                )
+            } else {
+                (Box::new(stream::pending()), Box::new(stream::pending()))
+            };
+            recorded.push(last_seen_block);
+            drop(recorded);
+
+            let provider = resubscribed_provider.clone();
+            Box::pin(async move { Ok((provider, clear_stream, take_stream)) })
+        });
+
+        let receiver = tokio::spawn(receive_blockchain_events(
+            Box::new(stream::empty()),
+            Box::new(stream::empty()),
+            event_sender,
+            provider,
+            Arc::default(),
+            Some(resubscribe),
+        ));
+
+        let (event, log) = event_receiver.recv().await.unwrap();
+        assert!(matches!(event, TradeEvent::TakeOrderV2(_)));
+        assert_eq!(log.block_number, Some(150));
+
+        // Once the new streams end too they are resubscribed from the block of
+        // the last event received
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while resubscriptions.lock().unwrap().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(*resubscriptions.lock().unwrap(), vec![None, Some(150)]);
+
+        receiver.abort();
+    }
+
     #[tokio::test]
     async fn test_conductor_abort_all() {
         let pool = setup_test_db().await;
@@ -1493,10 +4715,11 @@ mod tests {
 
         let broker = MockBrokerConfig.try_into_broker().await.unwrap();
 
-        let conductor = ConductorBuilder::new(config, pool, cache, provider, broker)
-            .with_broker_maintenance(None)
-            .with_dex_event_streams(clear_stream, take_stream)
-            .spawn();
+        let conductor =
+            ConductorBuilder::new(config, pool, Arc::default(), cache, provider, broker)
+                .with_broker_maintenance(None)
+                .with_dex_event_streams(clear_stream, take_stream)
+                .spawn();
 
         assert!(!conductor.order_poller.is_finished());
         assert!(!conductor.event_processor.is_finished());
@@ -1508,6 +4731,56 @@ mod tests {
         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
     }
 
+    #[tokio::test]
+    async fn test_drain_records_execution_placed_mid_flight() {
+        let pool = setup_test_db().await;
+        accumulate_ready_positions(&pool, &["AAPL"]).await;
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+        let broker = MockBroker::new().with_order_latency(Duration::from_millis(200));
+
+        let mut conductor = ConductorBuilder::new(
+            create_test_config(),
+            pool.clone(),
+            Arc::default(),
+            SymbolCache::default(),
+            provider,
+            broker.clone(),
+        )
+        .with_broker_maintenance(None)
+        .with_dex_event_streams(stream::empty(), stream::empty())
+        .spawn();
+
+        // Shut down while the periodic check is placing the order
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while broker.peak_orders_in_flight() == 0 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        conductor.drain(Duration::from_secs(5)).await;
+
+        assert!(conductor.queue_processor.is_finished());
+        assert!(conductor.position_checker.is_finished());
+        conductor.abort_all();
+
+        // The order poller may already have filled the submitted order
+        assert_eq!(broker.orders_placed(), 1);
+        let mut counts = Vec::new();
+        for status in [
+            OrderStatus::Pending,
+            OrderStatus::Submitted,
+            OrderStatus::Filled,
+        ] {
+            let executions = find_executions_by_symbol_status_and_broker(&pool, None, status, None)
+                .await
+                .unwrap();
+            counts.push(executions.len());
+        }
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[1] + counts[2], 1);
+    }
+
     #[tokio::test]
     async fn test_conductor_individual_abort() {
         let pool = setup_test_db().await;
@@ -1521,10 +4794,11 @@ mod tests {
 
         let broker = MockBrokerConfig.try_into_broker().await.unwrap();
 
-        let conductor = ConductorBuilder::new(config, pool, cache, provider, broker)
-            .with_broker_maintenance(None)
-            .with_dex_event_streams(clear_stream, take_stream)
-            .spawn();
+        let conductor =
+            ConductorBuilder::new(config, pool, Arc::default(), cache, provider, broker)
+                .with_broker_maintenance(None)
+                .with_dex_event_streams(clear_stream, take_stream)
+                .spawn();
 
         let order_handle = conductor.order_poller;
         let event_handle = conductor.event_processor;
@@ -1564,10 +4838,11 @@ mod tests {
 
         let broker = MockBrokerConfig.try_into_broker().await.unwrap();
 
-        let conductor = ConductorBuilder::new(config, pool, cache, provider, broker)
-            .with_broker_maintenance(None)
-            .with_dex_event_streams(clear_stream, take_stream)
-            .spawn();
+        let conductor =
+            ConductorBuilder::new(config, pool, Arc::default(), cache, provider, broker)
+                .with_broker_maintenance(None)
+                .with_dex_event_streams(clear_stream, take_stream)
+                .spawn();
 
         let elapsed = start_time.elapsed();
 
@@ -1578,4 +4853,42 @@ mod tests {
 
         conductor.abort_all();
     }
+
+    fn fast_connect_retry_strat(attempts: usize) -> ExponentialBuilder {
+        ws_connect_retry_strat(NonZeroUsize::new(attempts).unwrap())
+            .with_min_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_retry_succeeds_after_failed_attempt() {
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+
+        let result = connect_with_retry(fast_connect_retry_strat(3), || async {
+            let attempt = attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if attempt == 0 {
+                Err("connection refused")
+            } else {
+                Ok("connected")
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok("connected"));
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_retry_gives_up_after_configured_attempts() {
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+
+        let result: Result<(), _> = connect_with_retry(fast_connect_retry_strat(3), || async {
+            attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Err("connection refused")
+        })
+        .await;
+
+        assert_eq!(result, Err("connection refused"));
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
 }
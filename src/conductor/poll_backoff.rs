@@ -0,0 +1,95 @@
+//! Backoff between queue processor polls.
+//!
+//! An empty queue or a failed poll doubles the delay before the next poll, up
+//! to a ceiling, so long idle periods do not spin and repeated RPC failures do
+//! not hammer the provider. Processing an event snaps the delay back to the
+//! floor. Each wait adds up to a quarter of the delay as jitter so several
+//! instances restarted together do not poll in lockstep.
+
+use rand::Rng;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub(crate) struct PollBackoff {
+    floor: Duration,
+    ceiling: Duration,
+    current: Duration,
+}
+
+impl PollBackoff {
+    pub(crate) fn new(floor: Duration, ceiling: Duration) -> Self {
+        Self {
+            floor,
+            ceiling: ceiling.max(floor),
+            current: floor,
+        }
+    }
+
+    /// Delay before the next poll given the last poll's `result`, or `None`
+    /// when an event was processed and the next poll should follow at once.
+    pub(crate) fn after<T, E>(&mut self, result: &Result<Vec<T>, E>) -> Option<Duration> {
+        if result
+            .as_ref()
+            .is_ok_and(|executions| !executions.is_empty())
+        {
+            self.current = self.floor;
+            return None;
+        }
+
+        Some(self.next_delay())
+    }
+
+    /// Delay before the next poll after an empty or failed one, doubling the
+    /// delay for the poll after that.
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = self.current.saturating_mul(2).min(self.ceiling);
+        delay
+    }
+}
+
+/// Adds a random jitter of up to a quarter of `delay`.
+pub(crate) fn with_jitter(delay: Duration) -> Duration {
+    let max_jitter = delay / 4;
+    if max_jitter.is_zero() {
+        return delay;
+    }
+
+    delay + rand::thread_rng().gen_range(Duration::ZERO..=max_jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_on_errors_and_resets_on_processed_event() {
+        let mut backoff = PollBackoff::new(Duration::from_millis(100), Duration::from_millis(500));
+        let failed: Result<Vec<()>, &str> = Err("rpc failure");
+
+        assert_eq!(backoff.after(&failed), Some(Duration::from_millis(100)));
+        assert_eq!(backoff.after(&failed), Some(Duration::from_millis(200)));
+        assert_eq!(backoff.after(&failed), Some(Duration::from_millis(400)));
+        assert_eq!(backoff.after(&failed), Some(Duration::from_millis(500)));
+        assert_eq!(backoff.after(&failed), Some(Duration::from_millis(500)));
+
+        assert_eq!(backoff.after(&Ok::<_, &str>(vec![()])), None);
+        assert_eq!(backoff.after(&failed), Some(Duration::from_millis(100)));
+
+        // An empty queue backs off like an error
+        let empty: Result<Vec<()>, &str> = Ok(vec![]);
+        assert_eq!(backoff.after(&empty), Some(Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn test_with_jitter_stays_within_a_quarter_of_the_delay() {
+        let delay = Duration::from_millis(400);
+        for _ in 0..100 {
+            let jittered = with_jitter(delay);
+            assert!(jittered >= delay);
+            assert!(jittered <= Duration::from_millis(500));
+        }
+
+        assert_eq!(with_jitter(Duration::ZERO), Duration::ZERO);
+    }
+}
@@ -0,0 +1,107 @@
+use alloy::primitives::B256;
+use sqlx::SqlitePool;
+
+/// How processing of a queued event ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// The event became an onchain trade.
+    Converted,
+    /// The event was skipped on purpose, e.g. not for our order owner.
+    Filtered,
+    /// Processing the event failed; it stays queued and is retried.
+    Error,
+}
+
+impl Outcome {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Converted => "converted",
+            Self::Filtered => "filtered",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[cfg(test)]
+impl std::str::FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "converted" => Ok(Self::Converted),
+            "filtered" => Ok(Self::Filtered),
+            "error" => Ok(Self::Error),
+            other => Err(format!("Invalid conversion outcome: {other}")),
+        }
+    }
+}
+
+/// Latest conversion outcome of a queued event, with the reason it was
+/// filtered or failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConversionOutcome {
+    pub(crate) tx_hash: B256,
+    pub(crate) log_index: u64,
+    pub(crate) outcome: Outcome,
+    pub(crate) reason: Option<String>,
+}
+
+impl ConversionOutcome {
+    /// Records the outcome, replacing any earlier outcome of the same event.
+    pub(crate) async fn save(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let tx_hash = format!("{:#x}", self.tx_hash);
+        let log_index =
+            i64::try_from(self.log_index).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let outcome = self.outcome.as_str();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO conversion_outcomes (tx_hash, log_index, outcome, reason)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (tx_hash, log_index) DO UPDATE SET
+                outcome = excluded.outcome,
+                reason = excluded.reason,
+                at = CURRENT_TIMESTAMP
+            "#,
+            tx_hash,
+            log_index,
+            outcome,
+            self.reason
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT tx_hash, log_index, outcome, reason
+            FROM conversion_outcomes
+            ORDER BY id ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Self {
+                    tx_hash: row
+                        .tx_hash
+                        .parse()
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    log_index: u64::try_from(row.log_index)
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    outcome: row
+                        .outcome
+                        .parse()
+                        .map_err(|e: String| sqlx::Error::Decode(e.into()))?,
+                    reason: row.reason,
+                })
+            })
+            .collect()
+    }
+}
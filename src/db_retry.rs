@@ -0,0 +1,250 @@
+//! Retries for SQLite write transactions that fail with "database is locked".
+//!
+//! WAL mode and `busy_timeout` (see [`crate::env::configure_sqlite_pool`])
+//! absorb most write contention between the bot and the reporter, but under
+//! heavy concurrent writes a transaction can still fail with `SQLITE_BUSY` or
+//! `SQLITE_LOCKED`. Those failures are transient, so the critical write
+//! transactions (event marking and execution state updates) are rerun from the
+//! start with exponential backoff. Any other error fails immediately.
+
+use backon::{BackoffBuilder, ExponentialBuilder, Retryable};
+use std::error::Error;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// Primary SQLite result codes for a database held by another connection.
+/// Extended codes such as `SQLITE_BUSY_SNAPSHOT` keep these in the low byte.
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
+/// How often and how fast to retry a write transaction on a locked database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockedRetryPolicy {
+    pub max_retries: usize,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for LockedRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl LockedRetryPolicy {
+    fn backoff(&self) -> ExponentialBuilder {
+        ExponentialBuilder::default()
+            .with_max_times(self.max_retries)
+            .with_min_delay(self.initial_delay)
+            .with_max_delay(self.max_delay)
+            .with_jitter()
+    }
+}
+
+/// Whether `error`, or any error in its source chain, is SQLite reporting the
+/// database as busy or locked.
+pub(crate) fn is_database_locked(error: &(dyn Error + 'static)) -> bool {
+    let mut current = Some(error);
+
+    while let Some(error) = current {
+        if let Some(sqlx::Error::Database(db_error)) = error.downcast_ref::<sqlx::Error>() {
+            let is_locked = db_error
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED));
+
+            if is_locked {
+                return true;
+            }
+        }
+
+        current = error.source();
+    }
+
+    false
+}
+
+/// Runs the write transaction built by `transaction`, rerunning it according
+/// to `policy` while it fails because the database is locked.
+///
+/// `transaction` must begin and commit its own transaction so every attempt
+/// starts from a clean state.
+pub(crate) async fn retry_when_locked<T, E, F, Fut>(
+    policy: LockedRetryPolicy,
+    operation: &str,
+    transaction: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Error + 'static,
+{
+    transaction
+        .retry(policy.backoff().build())
+        .when(|err| is_database_locked(err))
+        .notify(|err, dur| {
+            warn!("Database locked during {operation}, retrying in {dur:?}: {err}");
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use sqlx::SqlitePool;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    use crate::error::EventQueueError;
+
+    fn test_policy() -> LockedRetryPolicy {
+        LockedRetryPolicy {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+        }
+    }
+
+    /// A file-backed pool that fails immediately on a held write lock instead
+    /// of waiting out a busy timeout.
+    async fn setup_file_db() -> (SqlitePool, PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "db-retry-{}-{}.sqlite",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .busy_timeout(Duration::ZERO);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .unwrap();
+
+        sqlx::query("CREATE TABLE writes (id INTEGER PRIMARY KEY)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        (pool, path)
+    }
+
+    async fn count_writes(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM writes")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_write_succeeds_after_transient_lock() {
+        let (pool, path) = setup_file_db().await;
+
+        // Another writer holds the write lock until the first attempt fails
+        let mut blocker = pool.acquire().await.unwrap();
+        sqlx::query("BEGIN IMMEDIATE")
+            .execute(&mut *blocker)
+            .await
+            .unwrap();
+        let blocker = Mutex::new(Some(blocker));
+
+        let attempts = AtomicUsize::new(0);
+        let result = retry_when_locked(test_policy(), "test write", || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+
+            let mut sql_tx = pool.begin().await?;
+            let written = sqlx::query("INSERT INTO writes DEFAULT VALUES")
+                .execute(&mut *sql_tx)
+                .await;
+
+            let unblocked = blocker.lock().await.take();
+            if let Some(mut blocker) = unblocked {
+                sqlx::query("COMMIT").execute(&mut *blocker).await.unwrap();
+            }
+
+            written?;
+            sql_tx.commit().await?;
+            Ok::<_, EventQueueError>(())
+        })
+        .await;
+
+        assert!(result.is_ok(), "expected write to succeed: {result:?}");
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(count_writes(&pool).await, 1);
+
+        pool.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_gives_up_when_lock_persists() {
+        let (pool, path) = setup_file_db().await;
+
+        let mut blocker = pool.acquire().await.unwrap();
+        sqlx::query("BEGIN IMMEDIATE")
+            .execute(&mut *blocker)
+            .await
+            .unwrap();
+
+        let attempts = AtomicUsize::new(0);
+        let result = retry_when_locked(test_policy(), "test write", || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            sqlx::query("INSERT INTO writes DEFAULT VALUES")
+                .execute(&pool)
+                .await
+        })
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(is_database_locked(&err));
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+        sqlx::query("ROLLBACK")
+            .execute(&mut *blocker)
+            .await
+            .unwrap();
+        drop(blocker);
+        pool.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_other_database_errors_are_not_retried() {
+        let (pool, path) = setup_file_db().await;
+
+        let attempts = AtomicUsize::new(0);
+        let result = retry_when_locked(test_policy(), "test write", || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            sqlx::query("INSERT INTO missing_table DEFAULT VALUES")
+                .execute(&pool)
+                .await
+        })
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(!is_database_locked(&err));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        pool.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_non_database_errors_are_not_locked() {
+        let err = EventQueueError::Processing("database is locked".to_string());
+        assert!(!is_database_locked(&err));
+
+        let err = EventQueueError::Database(sqlx::Error::RowNotFound);
+        assert!(!is_database_locked(&err));
+    }
+}
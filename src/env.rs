@@ -1,13 +1,43 @@
 use clap::Parser;
+use rust_decimal::Decimal;
 use sqlx::SqlitePool;
+use sqlx::sqlite::SqliteConnectOptions;
+use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
+use std::str::FromStr;
+use std::time::Duration;
 use tracing::Level;
 
+use crate::conductor::circuit_breaker::{CircuitBreakerPolicy, SymbolCircuitBreakers};
+use crate::conductor::debounce::ExecutionDebounce;
+use crate::db_retry::LockedRetryPolicy;
+use crate::offchain::blackout::{BlackoutCalendar, BlackoutWindow};
+use crate::offchain::canary::parse_canary_symbol;
+use crate::offchain::end_of_day::EndOfDaySettlement;
+use crate::offchain::liquidity::{
+    LiquidityLimits, LiquidityPolicy, OversizeAction, parse_adv_fraction, parse_max_order_value,
+};
+use crate::offchain::maintenance::{MaintenanceCalendar, MaintenanceWindow};
 use crate::offchain::order_poller::OrderPollerConfig;
+use crate::offchain::position_limit::{PositionLimits, SymbolPositionLimit};
+use crate::offchain::spread::SpreadPolicy;
+use crate::offchain::trade_side::TradeSide;
 use crate::onchain::EvmEnv;
+use crate::onchain::accumulator::{ExecutionRules, parse_non_accumulating_symbol};
+use crate::onchain::contract_code::ContractCodeCheck;
+use crate::onchain::hedge_events::HedgeEventType;
+use crate::onchain::io::SymbolConfigurationMode;
+use crate::onchain::position_calculator::ShareRounding;
+use crate::onchain::price_source::PriceSource;
+use crate::order_notifier::WebhookNotifier;
+use crate::queue::{EventPriorities, SymbolPriority};
+use crate::standby::Standby;
+use crate::symbol::cache::{SymbolCache, SymbolFallback, TokenSymbolAlias, parse_inverted_symbol};
+use crate::symbol::filter::{SymbolFilter, parse_filtered_symbol};
 use crate::telemetry::HyperDxConfig;
-use st0x_broker::SupportedBroker;
+use crate::trade_feed::TradeFeed;
 use st0x_broker::alpaca::AlpacaAuthEnv;
 use st0x_broker::schwab::SchwabAuthEnv;
+use st0x_broker::{FailoverPolicy, SimBrokerConfig, SupportedBroker, Symbol};
 
 // Dummy program name required by clap when parsing from environment variables.
 // clap's try_parse_from expects argv[0] to be the program name, but we only
@@ -19,18 +49,46 @@ pub enum BrokerConfig {
     Schwab(SchwabAuthEnv),
     Alpaca(AlpacaAuthEnv),
     DryRun,
+    Sim(SimBrokerConfig),
+    /// Records orders in `paper_orders`, filled at the prevailing Pyth price,
+    /// instead of placing them
+    Paper,
 }
 
 impl BrokerConfig {
+    /// Reads the credentials for `broker` from the environment.
+    fn from_env(broker: SupportedBroker) -> Result<Self, clap::Error> {
+        Ok(match broker {
+            SupportedBroker::Schwab => {
+                Self::Schwab(SchwabAuthEnv::try_parse_from(DUMMY_PROGRAM_NAME)?)
+            }
+            SupportedBroker::Alpaca => {
+                Self::Alpaca(AlpacaAuthEnv::try_parse_from(DUMMY_PROGRAM_NAME)?)
+            }
+            SupportedBroker::DryRun => Self::DryRun,
+            SupportedBroker::Sim => Self::Sim(SimBrokerConfig::try_parse_from(DUMMY_PROGRAM_NAME)?),
+            SupportedBroker::Paper => Self::Paper,
+        })
+    }
+
     pub fn to_supported_broker(&self) -> SupportedBroker {
         match self {
             Self::Schwab(_) => SupportedBroker::Schwab,
             Self::Alpaca(_) => SupportedBroker::Alpaca,
             Self::DryRun => SupportedBroker::DryRun,
+            Self::Sim(_) => SupportedBroker::Sim,
+            Self::Paper => SupportedBroker::Paper,
         }
     }
 }
 
+/// Secondary broker orders fail over to, see [`st0x_broker::FailoverBroker`].
+#[derive(Debug, Clone)]
+pub(crate) struct BrokerFailover {
+    pub(crate) broker: BrokerConfig,
+    pub(crate) policy: FailoverPolicy,
+}
+
 pub(crate) trait HasSqlite {
     async fn get_sqlite_pool(&self) -> Result<SqlitePool, sqlx::Error>;
 }
@@ -64,6 +122,39 @@ pub(crate) async fn configure_sqlite_pool(database_url: &str) -> Result<SqlitePo
     Ok(pool)
 }
 
+/// Pool that read-only queries (reporting and the API's read endpoints) go
+/// through, so they need not contend with the bot's writes. It is a separate
+/// read-only connection when a read database is configured, and the write
+/// pool otherwise.
+#[derive(Debug, Clone)]
+pub(crate) struct ReadPool(SqlitePool);
+
+impl ReadPool {
+    pub(crate) const fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    /// Read pool for `read_database_url`, falling back to `write_pool`.
+    pub(crate) async fn connect_or_share(
+        read_database_url: Option<&str>,
+        write_pool: &SqlitePool,
+    ) -> Result<Self, sqlx::Error> {
+        let Some(read_database_url) = read_database_url else {
+            return Ok(Self::new(write_pool.clone()));
+        };
+
+        let options = SqliteConnectOptions::from_str(read_database_url)?
+            .read_only(true)
+            .busy_timeout(Duration::from_secs(10));
+
+        Ok(Self::new(SqlitePool::connect_with(options).await?))
+    }
+
+    pub(crate) const fn pool(&self) -> &SqlitePool {
+        &self.0
+    }
+}
+
 #[derive(clap::ValueEnum, Debug, Clone)]
 pub enum LogLevel {
     Trace,
@@ -73,6 +164,16 @@ pub enum LogLevel {
     Error,
 }
 
+/// How log lines are written to stdout.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Pretty,
+    /// One JSON object per line, with event fields as top-level keys
+    Json,
+}
+
 impl From<LogLevel> for Level {
     fn from(log_level: LogLevel) -> Self {
         match log_level {
@@ -100,12 +201,59 @@ impl From<&LogLevel> for Level {
 #[derive(Debug, Clone)]
 pub struct Config {
     pub(crate) database_url: String,
+    pub(crate) read_database_url: Option<String>,
     pub log_level: LogLevel,
+    pub log_format: LogFormat,
     pub(crate) server_port: u16,
     pub(crate) evm: EvmEnv,
     pub(crate) order_polling_interval: u64,
     pub(crate) order_polling_max_jitter: u64,
+    pub(crate) order_polling_dust_notional: Option<f64>,
+    pub(crate) order_polling_dust_every: u64,
+    pub(crate) max_order_age: Option<Duration>,
     pub(crate) broker: BrokerConfig,
+    pub(crate) failover: Option<BrokerFailover>,
+    pub(crate) price_sources: Vec<PriceSource>,
+    pub(crate) blackout: BlackoutCalendar,
+    pub(crate) execution_dedup_window: Option<Duration>,
+    pub(crate) liquidity: Option<LiquidityPolicy>,
+    pub(crate) trade_side: TradeSide,
+    pub(crate) share_rounding: ShareRounding,
+    pub(crate) end_of_day_settlement: EndOfDaySettlement,
+    pub(crate) end_of_day_settlement_lead: Duration,
+    pub(crate) vacuum_interval: Option<Duration>,
+    pub(crate) hedge_event_types: Vec<HedgeEventType>,
+    pub(crate) shutdown_drain_timeout: Duration,
+    pub(crate) max_concurrent_executions: NonZeroUsize,
+    pub(crate) max_concurrent_symbols: NonZeroUsize,
+    pub(crate) max_open_executions: Option<NonZeroU64>,
+    pub(crate) position_limits: PositionLimits,
+    pub(crate) circuit_breakers: SymbolCircuitBreakers,
+    pub(crate) execution_batch_window: Option<Duration>,
+    pub(crate) execution_debounce: Option<ExecutionDebounce>,
+    pub(crate) strategy_label: Option<String>,
+    pub(crate) max_pyth_confidence_bps: Option<Decimal>,
+    pub(crate) max_pyth_price_divergence_pct: Option<Decimal>,
+    pub(crate) queue_poll_min_delay: Duration,
+    pub(crate) queue_poll_max_delay: Duration,
+    pub(crate) ws_connect_attempts: NonZeroUsize,
+    pub(crate) startup_canary: Option<Symbol>,
+    pub(crate) contract_code_check: ContractCodeCheck,
+    pub(crate) persist_last_seen_block: bool,
+    pub(crate) standby: Standby,
+    pub(crate) trade_feed: TradeFeed,
+    pub(crate) order_notifier: WebhookNotifier,
+    pub(crate) record_conversion_outcomes: bool,
+    pub(crate) inline_reporter_interval: Option<Duration>,
+    pub(crate) symbol_fallback: Option<SymbolFallback>,
+    pub(crate) invert_direction: Vec<Symbol>,
+    pub(crate) no_accumulate: Vec<Symbol>,
+    pub(crate) symbol_filter: SymbolFilter,
+    pub(crate) symbol_cache_capacity: Option<NonZeroUsize>,
+    pub(crate) symbol_configuration_mode: SymbolConfigurationMode,
+    pub(crate) event_priorities: EventPriorities,
+    pub(crate) rpc_metrics: bool,
+    pub(crate) locked_retry: LockedRetryPolicy,
     pub hyperdx: Option<HyperDxConfig>,
 }
 
@@ -113,21 +261,266 @@ pub struct Config {
 pub struct Env {
     #[clap(long = "db", env)]
     database_url: String,
+    /// Database (e.g. a replica of `--db`) opened read-only for reporting and
+    /// API read queries; those go through the main database when unset
+    #[clap(long, env)]
+    read_database_url: Option<String>,
     #[clap(long, env, default_value = "debug")]
     log_level: LogLevel,
+    #[clap(long, env, value_enum, default_value = "pretty")]
+    log_format: LogFormat,
     #[clap(long, env, default_value = "8080")]
     server_port: u16,
     #[clap(flatten)]
     pub(crate) evm: EvmEnv,
+    #[clap(flatten)]
+    rpc: RpcEnv,
     /// Interval in seconds between order status polling checks
     #[clap(long, env, default_value = "15")]
     order_polling_interval: u64,
     /// Maximum jitter in seconds for order polling to prevent thundering herd
     #[clap(long, env, default_value = "5")]
     order_polling_max_jitter: u64,
-    /// Broker to use for trading (required: schwab, alpaca, or dry-run)
+    /// Orders with an estimated notional in USD below this are polled less
+    /// often; larger orders are always polled first
+    #[clap(long, env)]
+    order_polling_dust_notional: Option<f64>,
+    /// Poll dust orders only on every Nth polling cycle
+    #[clap(long, env, default_value = "4", value_parser = clap::value_parser!(u64).range(1..))]
+    order_polling_dust_every: u64,
+    /// Seconds after placement at which an order still SUBMITTED is marked
+    /// FAILED, freeing its symbol for a new execution. Never if unset
+    #[clap(long, env, value_parser = clap::value_parser!(u64).range(1..))]
+    max_order_age_secs: Option<u64>,
+    /// Broker to use for trading (required: schwab, alpaca, dry-run, sim or
+    /// paper)
     #[clap(long, env)]
     broker: SupportedBroker,
+    /// Broker new orders fail over to while the primary broker keeps failing;
+    /// its credentials are read like the primary's. Disabled if unset
+    #[clap(long, env)]
+    failover_broker: Option<SupportedBroker>,
+    /// Consecutive failed order placements on the primary broker before
+    /// failing over
+    #[clap(long, env, default_value = "3")]
+    failover_after_failures: NonZeroU32,
+    /// Seconds between attempts to fail back to the primary broker
+    #[clap(long, env, default_value = "60")]
+    failover_probe_interval_secs: u64,
+    /// Comma-separated priority chain of sources used to price the onchain
+    /// leg of a trade (pyth, broker-quote, onchain-ratio)
+    #[clap(long, env, value_delimiter = ',', default_value = "onchain-ratio")]
+    price_sources: Vec<PriceSource>,
+    /// Comma-separated blackout windows during which executions are deferred
+    /// while trades keep accumulating, as `[SYMBOL:]YYYY-MM-DD[..YYYY-MM-DD]`
+    #[clap(long, env, value_delimiter = ',')]
+    blackout: Vec<BlackoutWindow>,
+    /// Comma-separated Schwab maintenance windows in US Eastern time, as
+    /// `[DAY ]HH:MM..HH:MM`, during which orders are neither placed nor polled
+    /// while trades keep accumulating
+    #[clap(long, env, value_delimiter = ',')]
+    schwab_maintenance_window: Vec<MaintenanceWindow>,
+    /// Window in seconds within which an identical execution (same symbol,
+    /// direction and shares) is rejected as a duplicate; 0 disables the check
+    #[clap(long, env, default_value = "60")]
+    execution_dedup_window_secs: u64,
+    /// Maximum execution size as a fraction of the symbol's recent average
+    /// daily volume (e.g. 0.01 for 1%); unset disables the check
+    #[clap(long, env, value_parser = parse_adv_fraction)]
+    max_adv_fraction: Option<f64>,
+    /// How to handle executions above the ADV limit: split (execute up to the
+    /// limit, keep the rest accumulated) or defer (wait until it fits)
+    #[clap(long, env, default_value = "split")]
+    oversize_order_action: OversizeAction,
+    /// Maximum value in dollars of a single broker order; larger executions
+    /// are split into orders within the limit at the current quote
+    #[clap(long, env, value_parser = parse_max_order_value)]
+    max_order_value: Option<u64>,
+    /// Minimum value in cents of an execution at the onchain price; smaller
+    /// executions are held back while their shares keep accumulating, since
+    /// the broker rejects such dust orders. Unset disables the check
+    #[clap(long, env)]
+    min_trade_notional_cents: Option<u64>,
+    /// Minimum spread in basis points between the onchain price and the
+    /// broker quote, net of estimated fees and slippage, for an execution to
+    /// go ahead; executions below it are deferred. Unset disables the check
+    #[clap(long, env)]
+    min_net_spread_bps: Option<f64>,
+    /// Estimated broker fees in basis points deducted from the hedge spread
+    #[clap(long, env, default_value = "0")]
+    estimated_fee_bps: f64,
+    /// Estimated slippage in basis points deducted from the hedge spread
+    #[clap(long, env, default_value = "0")]
+    estimated_slippage_bps: f64,
+    /// Restrict offchain executions to one direction (both, buy-only or
+    /// sell-only); trades on the other side keep accumulating
+    #[clap(long, env, default_value = "both")]
+    trade_side: TradeSide,
+    /// How an accumulated position's whole shares are taken once it reaches
+    /// one share: truncate (keep the fraction accumulated) or round (to the
+    /// nearest share, carrying any overshoot)
+    #[clap(long, env, default_value = "truncate")]
+    share_rounding: ShareRounding,
+    /// What to do shortly before market close with net positions below one
+    /// share: hold them overnight, or flatten them with a one-share rounding
+    /// order
+    #[clap(long, env, default_value = "hold")]
+    end_of_day_settlement: EndOfDaySettlement,
+    /// Seconds before market close at which end-of-day settlement runs
+    #[clap(long, env, default_value = "300")]
+    end_of_day_settlement_lead_secs: u64,
+    /// Minimum seconds between database VACUUMs, which run only after market
+    /// close to reclaim space freed by prunes. Disabled if unset
+    #[clap(long, env)]
+    vacuum_interval_secs: Option<u64>,
+    /// Comma-separated onchain event types whose trades are hedged
+    /// (clear-v2, take-order-v2); trades from other types are recorded only
+    #[clap(
+        long,
+        env,
+        value_delimiter = ',',
+        default_value = "clear-v2,take-order-v2"
+    )]
+    hedge_event_types: Vec<HedgeEventType>,
+    /// Seconds to wait on shutdown for executions already being placed with
+    /// the broker to complete and be recorded before aborting them
+    #[clap(long, env, default_value = "30")]
+    shutdown_drain_timeout_secs: u64,
+    /// Maximum number of accumulated positions the periodic check executes
+    /// against the broker at the same time
+    #[clap(long, env, default_value = "4")]
+    max_concurrent_executions: NonZeroUsize,
+    /// Maximum number of symbols converting onchain events or executing
+    /// against the broker at the same time across the queue processor and the
+    /// periodic check, bounding concurrent RPC calls to the provider
+    #[clap(long, env, default_value = "4")]
+    max_concurrent_symbols: NonZeroUsize,
+    /// Maximum number of executions open (not yet filled or failed) at the
+    /// same time; at the cap, queued events wait until one completes.
+    /// Unbounded if unset
+    #[clap(long, env)]
+    max_open_executions: Option<NonZeroU64>,
+    /// Maximum absolute net position, in shares, the bot may hold at the
+    /// broker in any symbol; executions that would exceed it are skipped and
+    /// their shares stay accumulated. Unbounded if unset
+    #[clap(long, env)]
+    max_net_shares: Option<u64>,
+    /// Comma-separated net position limits by symbol, as `SYMBOL=SHARES`,
+    /// overriding `--max-net-shares` for those symbols
+    #[clap(long, env, value_delimiter = ',')]
+    max_net_shares_per_symbol: Vec<SymbolPositionLimit>,
+    /// Consecutive order placement failures for a symbol, within
+    /// `--circuit-breaker-window-secs`, after which its executions are paused
+    #[clap(long, env, default_value = "5")]
+    circuit_breaker_failures: NonZeroU32,
+    /// Window in seconds within which placement failures count as consecutive
+    #[clap(long, env, default_value = "600")]
+    circuit_breaker_window_secs: u64,
+    /// Seconds executions for a symbol stay paused once its circuit breaker
+    /// opens, before a single probe order is let through
+    #[clap(long, env, default_value = "900")]
+    circuit_breaker_cooldown_secs: u64,
+    /// Milliseconds the queue processor collects newly created executions
+    /// before placing them with the broker together. Each execution is placed
+    /// as soon as it is created if unset
+    #[clap(long, env)]
+    execution_batch_window_ms: Option<u64>,
+    /// Milliseconds a ready position waits for more fills of its symbol before
+    /// it is executed, so fills landing together are placed as one order. The
+    /// wait starts with the first fill and is not extended by later ones.
+    /// Positions execute as soon as they are ready if unset
+    #[clap(long, env)]
+    execution_debounce_ms: Option<u64>,
+    /// Strategy (or desk) recorded with every onchain trade and execution, so
+    /// P&L can be segmented when several instances share a database
+    #[clap(long, env)]
+    strategy_label: Option<String>,
+    /// Widest Pyth confidence interval, in basis points of the Pyth price,
+    /// a trade is hedged at; trades above it are logged and skipped. Unset
+    /// disables the check
+    #[clap(long, env)]
+    max_pyth_confidence_bps: Option<Decimal>,
+    /// Largest difference, in percent of the Pyth price, between a trade's
+    /// onchain price per share and the Pyth price it is accepted at; trades
+    /// beyond it are logged and skipped. Unset disables the check
+    #[clap(long, env)]
+    max_pyth_price_divergence_pct: Option<Decimal>,
+    /// Milliseconds the queue processor waits before polling again after an
+    /// empty or failed poll; the wait doubles with every consecutive one
+    #[clap(long, env, default_value = "100", value_parser = clap::value_parser!(u64).range(1..))]
+    queue_poll_min_delay_ms: u64,
+    /// Upper bound in milliseconds on the queue processor's wait between polls
+    #[clap(long, env, default_value = "30000")]
+    queue_poll_max_delay_ms: u64,
+    /// Symbol for which a one-share order is previewed with the broker on
+    /// startup; startup aborts if the broker does not accept it
+    #[clap(long, env, value_parser = parse_canary_symbol)]
+    startup_canary: Option<Symbol>,
+    #[clap(flatten)]
+    startup_checks: StartupChecksEnv,
+    /// Persist the highest block seen on the live event stream and, on
+    /// restart, resync only the blocks missed since then
+    #[clap(long, env)]
+    persist_last_seen_block: bool,
+    /// Start as a warm standby that processes and accumulates every event but
+    /// creates no executions until promoted via `POST /admin/promote`
+    #[clap(long, env)]
+    standby: bool,
+    /// Serve newly persisted trades and execution status changes as
+    /// Server-Sent Events on `GET /stream/trades`, buffering up to this many
+    /// events for each client before disconnecting it. Disabled if unset
+    #[clap(long, env)]
+    trade_feed_capacity: Option<NonZeroUsize>,
+    /// Webhook (e.g. a Slack or Discord incoming webhook) that a JSON summary
+    /// of every filled or failed order is posted to
+    #[clap(long, env)]
+    order_webhook_url: Option<url::Url>,
+    /// Record the conversion outcome (converted, filtered or error, with the
+    /// reason) of every queued event in `conversion_outcomes`
+    #[clap(long, env)]
+    record_conversion_outcomes: bool,
+    #[clap(flatten)]
+    inline_reporter: InlineReporterEnv,
+    #[clap(flatten)]
+    symbol_fallback: SymbolFallbackEnv,
+    /// Comma-separated base symbols (e.g. inverse or short products) whose
+    /// onchain buys and sells are hedged in the opposite direction
+    #[clap(long, env, value_delimiter = ',', value_parser = parse_inverted_symbol)]
+    invert_direction: Vec<Symbol>,
+    /// Comma-separated base symbols that do not accumulate fractional
+    /// shares: each onchain trade's whole shares execute immediately and its
+    /// fraction is dropped
+    #[clap(long, env, value_delimiter = ',', value_parser = parse_non_accumulating_symbol)]
+    no_accumulate: Vec<Symbol>,
+    /// Comma-separated base symbols that are the only ones traded; every
+    /// symbol not on the denylist is traded if unset
+    #[clap(long, env, value_delimiter = ',', value_parser = parse_filtered_symbol)]
+    symbol_allowlist: Vec<Symbol>,
+    /// Comma-separated base symbols whose trades are skipped (e.g. pending a
+    /// corporate action), even if they are also allowlisted
+    #[clap(long, env, value_delimiter = ',', value_parser = parse_filtered_symbol)]
+    symbol_denylist: Vec<Symbol>,
+    /// Maximum number of entries kept in each of the token symbol and Pyth
+    /// feed ID caches, evicting the least recently used; unbounded if unset
+    #[clap(long, env)]
+    symbol_cache_capacity: Option<NonZeroUsize>,
+    /// How to handle fills whose IO is not USDC and one tokenized equity:
+    /// lenient skips the fill, strict halts the queue processor
+    #[clap(long, env, default_value = "lenient")]
+    symbol_configuration_mode: SymbolConfigurationMode,
+    /// Comma-separated queue processing priorities by base symbol, as
+    /// `SYMBOL=PRIORITY`; higher priorities are processed first and other
+    /// symbols have priority 0
+    #[clap(long, env, value_delimiter = ',')]
+    event_priority: Vec<SymbolPriority>,
+    /// Number of times a critical write transaction (event marking, execution
+    /// state updates) is retried when SQLite reports the database as locked
+    #[clap(long, env, default_value = "5")]
+    db_locked_retries: usize,
+    /// Initial backoff in milliseconds before retrying a write transaction on
+    /// a locked database, doubling on each retry
+    #[clap(long, env, default_value = "50")]
+    db_locked_retry_delay_ms: u64,
     /// HyperDX API key for observability (optional)
     #[clap(long, env)]
     hyperdx_api_key: Option<String>,
@@ -136,38 +529,221 @@ pub struct Env {
     hyperdx_service_name: String,
 }
 
+#[derive(clap::Args, Debug, Clone)]
+struct RpcEnv {
+    /// Number of attempts to connect to the WebSocket RPC when a session
+    /// starts, backing off between attempts, before the session fails
+    #[clap(long, env, default_value = "5")]
+    ws_connect_attempts: NonZeroUsize,
+    /// Record per-method call counts and latency histograms for provider RPC
+    /// calls, served by `GET /stats/rpc`
+    #[clap(long, env)]
+    rpc_metrics: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct StartupChecksEnv {
+    /// Allow a zero order owner or orderbook address, which would otherwise
+    /// be rejected at startup; only meant for testing
+    #[clap(long, env)]
+    allow_zero_owner: bool,
+    /// Contracts that must have code deployed at startup (none, orderbook,
+    /// orderbook-and-pyth); startup aborts if one has none, e.g. because of a
+    /// mistyped address or an RPC on the wrong chain
+    #[clap(long, env, default_value = "none")]
+    contract_code_check: ContractCodeCheck,
+}
+
+impl StartupChecksEnv {
+    /// Rejects a zero order owner or orderbook address unless allowed.
+    fn check_addresses(&self, evm: &EvmEnv) -> Result<(), clap::Error> {
+        if self.allow_zero_owner {
+            return Ok(());
+        }
+
+        evm.check_nonzero_addresses().map_err(|e| {
+            clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{e}\n"))
+        })
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct SymbolFallbackEnv {
+    /// Label tokens whose `symbol()` call reverts with their address (or an
+    /// alias from --token-symbol-aliases) instead of failing; their trades are
+    /// flagged for review rather than hedged
+    #[clap(long, env)]
+    symbol_revert_fallback: bool,
+    /// Comma-separated symbols to use for tokens whose `symbol()` call
+    /// reverts, as `ADDRESS=SYMBOL`
+    #[clap(long, env, value_delimiter = ',')]
+    token_symbol_aliases: Vec<TokenSymbolAlias>,
+}
+
+impl SymbolFallbackEnv {
+    fn into_fallback(self) -> Option<SymbolFallback> {
+        self.symbol_revert_fallback
+            .then(|| SymbolFallback::new(self.token_symbol_aliases))
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct InlineReporterEnv {
+    /// Run the P&L reporter as a task of this process on the shared pool,
+    /// instead of as the separate reporter binary
+    #[clap(long, env)]
+    run_reporter_inline: bool,
+    /// Interval in seconds between runs of the inline P&L reporter
+    #[clap(long, env, default_value = "30")]
+    reporter_processing_interval_secs: u64,
+}
+
+impl InlineReporterEnv {
+    /// Interval between runs of the P&L reporter if it runs inline.
+    fn interval(&self) -> Option<Duration> {
+        self.run_reporter_inline
+            .then(|| Duration::from_secs(self.reporter_processing_interval_secs))
+    }
+}
+
 impl Env {
     pub fn into_config(self) -> Result<Config, clap::Error> {
-        let broker = match self.broker {
-            SupportedBroker::Schwab => {
-                let schwab_auth = SchwabAuthEnv::try_parse_from(DUMMY_PROGRAM_NAME)?;
-                BrokerConfig::Schwab(schwab_auth)
-            }
-            SupportedBroker::Alpaca => {
-                let alpaca_auth = AlpacaAuthEnv::try_parse_from(DUMMY_PROGRAM_NAME)?;
-                BrokerConfig::Alpaca(alpaca_auth)
-            }
-            SupportedBroker::DryRun => BrokerConfig::DryRun,
-        };
+        self.startup_checks.check_addresses(&self.evm)?;
+
+        if self.queue_poll_max_delay_ms < self.queue_poll_min_delay_ms {
+            return Err(clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!(
+                    "QUEUE_POLL_MAX_DELAY_MS ({}) must not be below QUEUE_POLL_MIN_DELAY_MS ({})\n",
+                    self.queue_poll_max_delay_ms, self.queue_poll_min_delay_ms
+                ),
+            ));
+        }
+
+        let broker = BrokerConfig::from_env(self.broker)?;
+
+        let failover = self.failover()?;
+        let liquidity = self.liquidity_policy();
 
         let log_level_tracing: Level = (&self.log_level).into();
         let hyperdx = self.hyperdx_api_key.map(|api_key| HyperDxConfig {
             api_key,
             service_name: self.hyperdx_service_name,
             log_level: log_level_tracing,
+            log_format: self.log_format,
         });
 
         Ok(Config {
             database_url: self.database_url,
+            read_database_url: self.read_database_url,
             log_level: self.log_level,
+            log_format: self.log_format,
             server_port: self.server_port,
             evm: self.evm,
             order_polling_interval: self.order_polling_interval,
             order_polling_max_jitter: self.order_polling_max_jitter,
+            order_polling_dust_notional: self.order_polling_dust_notional,
+            order_polling_dust_every: self.order_polling_dust_every,
+            max_order_age: self.max_order_age_secs.map(Duration::from_secs),
             broker,
+            failover,
+            price_sources: self.price_sources,
+            blackout: BlackoutCalendar::new(self.blackout)
+                .with_maintenance(MaintenanceCalendar::new(self.schwab_maintenance_window)),
+            execution_dedup_window: (self.execution_dedup_window_secs > 0)
+                .then(|| Duration::from_secs(self.execution_dedup_window_secs)),
+            liquidity,
+            trade_side: self.trade_side,
+            share_rounding: self.share_rounding,
+            end_of_day_settlement: self.end_of_day_settlement,
+            end_of_day_settlement_lead: Duration::from_secs(self.end_of_day_settlement_lead_secs),
+            vacuum_interval: self.vacuum_interval_secs.map(Duration::from_secs),
+            hedge_event_types: self.hedge_event_types,
+            shutdown_drain_timeout: Duration::from_secs(self.shutdown_drain_timeout_secs),
+            max_concurrent_executions: self.max_concurrent_executions,
+            max_concurrent_symbols: self.max_concurrent_symbols,
+            max_open_executions: self.max_open_executions,
+            position_limits: PositionLimits::new(
+                self.max_net_shares,
+                self.max_net_shares_per_symbol,
+            ),
+            circuit_breakers: SymbolCircuitBreakers::new(CircuitBreakerPolicy {
+                failure_threshold: self.circuit_breaker_failures,
+                failure_window: Duration::from_secs(self.circuit_breaker_window_secs),
+                cooldown: Duration::from_secs(self.circuit_breaker_cooldown_secs),
+            }),
+            execution_batch_window: self.execution_batch_window_ms.map(Duration::from_millis),
+            execution_debounce: self
+                .execution_debounce_ms
+                .map(|ms| ExecutionDebounce::new(Duration::from_millis(ms))),
+            strategy_label: self.strategy_label,
+            max_pyth_confidence_bps: self.max_pyth_confidence_bps,
+            max_pyth_price_divergence_pct: self.max_pyth_price_divergence_pct,
+            queue_poll_min_delay: Duration::from_millis(self.queue_poll_min_delay_ms),
+            queue_poll_max_delay: Duration::from_millis(self.queue_poll_max_delay_ms),
+            ws_connect_attempts: self.rpc.ws_connect_attempts,
+            startup_canary: self.startup_canary,
+            contract_code_check: self.startup_checks.contract_code_check,
+            persist_last_seen_block: self.persist_last_seen_block,
+            standby: Standby::new(self.standby),
+            trade_feed: TradeFeed::new(self.trade_feed_capacity),
+            order_notifier: WebhookNotifier::new(self.order_webhook_url),
+            record_conversion_outcomes: self.record_conversion_outcomes,
+            inline_reporter_interval: self.inline_reporter.interval(),
+            symbol_fallback: self.symbol_fallback.into_fallback(),
+            invert_direction: self.invert_direction,
+            no_accumulate: self.no_accumulate,
+            symbol_filter: SymbolFilter::new(self.symbol_allowlist, self.symbol_denylist),
+            symbol_cache_capacity: self.symbol_cache_capacity,
+            symbol_configuration_mode: self.symbol_configuration_mode,
+            event_priorities: EventPriorities::new(self.event_priority),
+            rpc_metrics: self.rpc.rpc_metrics,
+            locked_retry: LockedRetryPolicy {
+                max_retries: self.db_locked_retries,
+                initial_delay: Duration::from_millis(self.db_locked_retry_delay_ms),
+                ..LockedRetryPolicy::default()
+            },
             hyperdx,
         })
     }
+
+    /// Reads the credentials and failure policy of the failover broker, if
+    /// one is configured.
+    fn failover(&self) -> Result<Option<BrokerFailover>, clap::Error> {
+        match self.failover_broker {
+            Some(failover_broker) if failover_broker == self.broker => Err(clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!("FAILOVER_BROKER must differ from BROKER ({failover_broker})\n"),
+            )),
+            Some(failover_broker) => Ok(Some(BrokerFailover {
+                broker: BrokerConfig::from_env(failover_broker)?,
+                policy: FailoverPolicy {
+                    failure_threshold: self.failover_after_failures,
+                    probe_interval: Duration::from_secs(self.failover_probe_interval_secs),
+                },
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Liquidity checks applied before placing an order, if any limit is set.
+    fn liquidity_policy(&self) -> Option<LiquidityPolicy> {
+        (self.max_adv_fraction.is_some()
+            || self.max_order_value.is_some()
+            || self.min_net_spread_bps.is_some()
+            || self.min_trade_notional_cents.is_some())
+        .then_some(LiquidityPolicy {
+            max_adv_fraction: self.max_adv_fraction,
+            action: self.oversize_order_action,
+            max_order_value_cents: self.max_order_value,
+            spread: self.min_net_spread_bps.map(|min_net_spread| SpreadPolicy {
+                min_net_spread,
+                estimated_fee: self.estimated_fee_bps,
+                estimated_slippage: self.estimated_slippage_bps,
+            }),
+            min_trade_notional_cents: self.min_trade_notional_cents,
+        })
+    }
 }
 
 impl Config {
@@ -175,30 +751,86 @@ impl Config {
         configure_sqlite_pool(&self.database_url).await
     }
 
-    pub const fn get_order_poller_config(&self) -> OrderPollerConfig {
+    pub(crate) async fn get_read_pool(
+        &self,
+        write_pool: &SqlitePool,
+    ) -> Result<ReadPool, sqlx::Error> {
+        ReadPool::connect_or_share(self.read_database_url.as_deref(), write_pool).await
+    }
+
+    /// Symbol cache carrying the configured capacity, symbol fallback,
+    /// direction inversions, symbol convention and Pyth price divergence
+    /// limit used when converting onchain events to trades.
+    pub(crate) fn symbol_cache(&self) -> SymbolCache {
+        SymbolCache::new(self.symbol_fallback.clone())
+            .with_capacity(self.symbol_cache_capacity)
+            .with_inverted_directions(self.invert_direction.iter().cloned())
+            .with_symbol_convention(self.evm.symbol_convention.clone())
+            .with_max_pyth_divergence(self.max_pyth_price_divergence_pct)
+    }
+
+    /// Whether fractional shares of `symbol` accumulate across trades.
+    pub(crate) fn accumulates(&self, symbol: &Symbol) -> bool {
+        !self.no_accumulate.contains(symbol)
+    }
+
+    /// Configured rules for executing positions on `broker_type`, which
+    /// trades fractional shares if `fractional_shares`, capped or deferred by
+    /// `liquidity`.
+    pub(crate) fn execution_rules<'a>(
+        &'a self,
+        broker_type: SupportedBroker,
+        fractional_shares: bool,
+        liquidity: &'a LiquidityLimits,
+    ) -> ExecutionRules<'a> {
+        ExecutionRules {
+            broker_type,
+            convention: &self.evm.symbol_convention,
+            blackout: &self.blackout,
+            dedup_window: self.execution_dedup_window,
+            liquidity,
+            trade_side: self.trade_side,
+            rounding: self.share_rounding,
+            fractional_shares,
+        }
+    }
+
+    pub fn get_order_poller_config(&self) -> OrderPollerConfig {
         OrderPollerConfig {
             polling_interval: std::time::Duration::from_secs(self.order_polling_interval),
             max_jitter: std::time::Duration::from_secs(self.order_polling_max_jitter),
+            dust_notional_threshold: self.order_polling_dust_notional,
+            dust_polling_every: self.order_polling_dust_every,
+            max_order_age: self.max_order_age,
+            locked_retry: self.locked_retry,
+            trade_feed: self.trade_feed.clone(),
+            maintenance: self.blackout.maintenance().clone(),
+            notifier: self.order_notifier.clone(),
         }
     }
 }
 
-pub fn setup_tracing(log_level: &LogLevel) {
+pub fn setup_tracing(log_level: &LogLevel, log_format: LogFormat) {
     let level: Level = log_level.into();
     let default_filter = format!("st0x_hedge={level},st0x_broker={level}");
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| default_filter.into());
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| default_filter.into()),
-        )
-        .init();
+    match log_format {
+        LogFormat::Pretty => tracing_subscriber::fmt().with_env_filter(env_filter).init(),
+        LogFormat::Json => tracing_subscriber::fmt()
+            .json()
+            .flatten_event(true)
+            .with_env_filter(env_filter)
+            .init(),
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::onchain::EvmEnv;
+    use crate::onchain::io::SymbolConvention;
+    use crate::onchain::{DEFAULT_BACKFILL_BATCH_SIZE, EvmEnv};
     use alloy::primitives::{FixedBytes, address};
     use st0x_broker::schwab::{SchwabAuthEnv, SchwabConfig};
     use st0x_broker::{MockBrokerConfig, TryIntoBroker};
@@ -208,13 +840,17 @@ pub mod tests {
     pub fn create_test_config_with_order_owner(order_owner: alloy::primitives::Address) -> Config {
         Config {
             database_url: ":memory:".to_string(),
+            read_database_url: None,
             log_level: LogLevel::Debug,
+            log_format: LogFormat::Pretty,
             server_port: 8080,
             evm: EvmEnv {
                 ws_rpc_url: url::Url::parse("ws://localhost:8545").unwrap(),
                 orderbook: address!("0x1111111111111111111111111111111111111111"),
                 order_owner,
                 deployment_block: 1,
+                symbol_convention: SymbolConvention::default(),
+                backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
             },
             order_polling_interval: 15,
             order_polling_max_jitter: 5,
@@ -224,8 +860,59 @@ pub mod tests {
                 schwab_redirect_uri: "https://127.0.0.1".to_string(),
                 schwab_base_url: "https://test.com".to_string(),
                 schwab_account_index: 0,
+                schwab_alert_on_manual_review: false,
+                schwab_market_hours_min_interval_secs: 0,
+                schwab_order_batch_lookback_hours: None,
+                schwab_persist_order_payloads: false,
+                schwab_fractional_shares: false,
+                price_rounding: st0x_broker::PriceRounding::HalfEven,
                 encryption_key: TEST_ENCRYPTION_KEY,
             }),
+            price_sources: vec![PriceSource::OnchainRatio],
+            blackout: BlackoutCalendar::default(),
+            order_polling_dust_notional: None,
+            order_polling_dust_every: 4,
+            max_order_age: None,
+            execution_dedup_window: None,
+            liquidity: None,
+            trade_side: TradeSide::Both,
+            share_rounding: ShareRounding::Truncate,
+            shutdown_drain_timeout: Duration::from_secs(30),
+            symbol_fallback: None,
+            max_concurrent_executions: std::num::NonZeroUsize::new(4).unwrap(),
+            max_concurrent_symbols: std::num::NonZeroUsize::new(4).unwrap(),
+            max_open_executions: None,
+            position_limits: PositionLimits::default(),
+            circuit_breakers: SymbolCircuitBreakers::default(),
+            execution_batch_window: None,
+            execution_debounce: None,
+            strategy_label: None,
+            max_pyth_confidence_bps: None,
+            max_pyth_price_divergence_pct: None,
+            queue_poll_min_delay: Duration::from_millis(100),
+            queue_poll_max_delay: Duration::from_secs(30),
+            failover: None,
+            ws_connect_attempts: std::num::NonZeroUsize::new(1).unwrap(),
+            startup_canary: None,
+            contract_code_check: ContractCodeCheck::None,
+            persist_last_seen_block: false,
+            standby: Standby::default(),
+            trade_feed: TradeFeed::default(),
+            order_notifier: WebhookNotifier::default(),
+            record_conversion_outcomes: false,
+            inline_reporter_interval: None,
+            hedge_event_types: HedgeEventType::ALL.to_vec(),
+            invert_direction: vec![],
+            no_accumulate: vec![],
+            symbol_filter: SymbolFilter::default(),
+            symbol_cache_capacity: None,
+            symbol_configuration_mode: SymbolConfigurationMode::Lenient,
+            rpc_metrics: false,
+            locked_retry: LockedRetryPolicy::default(),
+            end_of_day_settlement: EndOfDaySettlement::Hold,
+            end_of_day_settlement_lead: Duration::from_secs(300),
+            vacuum_interval: None,
+            event_priorities: EventPriorities::default(),
             hyperdx: None,
         }
     }
@@ -316,4 +1003,288 @@ pub mod tests {
         let config = env.into_config().unwrap();
         assert!(matches!(config.broker, BrokerConfig::DryRun));
     }
+
+    #[test]
+    fn test_sim_broker_selected_with_default_settings() {
+        let args = vec![
+            "test",
+            "--db",
+            ":memory:",
+            "--ws-rpc-url",
+            "ws://localhost:8545",
+            "--orderbook",
+            "0x1111111111111111111111111111111111111111",
+            "--order-owner",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "--deployment-block",
+            "1",
+            "--broker",
+            "sim",
+        ];
+
+        let env = Env::try_parse_from(args).unwrap();
+        let config = env.into_config().unwrap();
+        let BrokerConfig::Sim(sim_config) = &config.broker else {
+            panic!("Expected simulated broker, got {:?}", config.broker);
+        };
+        assert_eq!(sim_config.sim_fill_delay_polls, 3);
+        assert_eq!(config.broker.to_supported_broker(), SupportedBroker::Sim);
+    }
+
+    #[test]
+    fn test_paper_broker_selected() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        let broker_index = args.iter().position(|arg| *arg == "--broker").unwrap();
+        args[broker_index + 1] = "paper";
+
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+        assert!(matches!(config.broker, BrokerConfig::Paper));
+        assert_eq!(config.broker.to_supported_broker(), SupportedBroker::Paper);
+    }
+
+    fn dry_run_args<'a>(orderbook: &'a str, order_owner: &'a str) -> Vec<&'a str> {
+        vec![
+            "test",
+            "--db",
+            ":memory:",
+            "--ws-rpc-url",
+            "ws://localhost:8545",
+            "--orderbook",
+            orderbook,
+            "--order-owner",
+            order_owner,
+            "--deployment-block",
+            "1",
+            "--broker",
+            "dry-run",
+        ]
+    }
+
+    #[test]
+    fn test_zero_addresses_are_rejected() {
+        let zero = "0x0000000000000000000000000000000000000000";
+
+        let args = dry_run_args("0x1111111111111111111111111111111111111111", zero);
+        let err = Env::try_parse_from(args)
+            .unwrap()
+            .into_config()
+            .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("order owner is the zero address"));
+
+        let args = dry_run_args(zero, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let err = Env::try_parse_from(args)
+            .unwrap()
+            .into_config()
+            .unwrap_err();
+        assert!(err.to_string().contains("orderbook is the zero address"));
+    }
+
+    #[test]
+    fn test_zero_owner_allowed_with_override() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0x0000000000000000000000000000000000000000",
+        );
+        args.push("--allow-zero-owner");
+
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+        assert_eq!(config.evm.order_owner, alloy::primitives::Address::ZERO);
+    }
+
+    #[test]
+    fn test_failover_broker_must_differ_from_primary() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        args.extend(["--failover-broker", "dry-run"]);
+
+        let err = Env::try_parse_from(args)
+            .unwrap()
+            .into_config()
+            .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("FAILOVER_BROKER must differ"));
+    }
+
+    #[test]
+    fn test_min_trade_notional_alone_enables_liquidity_policy() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        let config = Env::try_parse_from(args.clone())
+            .unwrap()
+            .into_config()
+            .unwrap();
+        assert_eq!(config.liquidity, None);
+
+        args.extend(["--min-trade-notional-cents", "100"]);
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+        let policy = config.liquidity.unwrap();
+        assert_eq!(policy.min_trade_notional_cents, Some(100));
+        assert_eq!(policy.max_order_value_cents, None);
+        assert!(policy.spread.is_none());
+    }
+
+    #[test]
+    fn test_circuit_breaker_requires_at_least_one_failure() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        let mut zero_failures = args.clone();
+        zero_failures.extend(["--circuit-breaker-failures", "0"]);
+        assert!(Env::try_parse_from(zero_failures).is_err());
+
+        args.extend(["--circuit-breaker-failures", "3"]);
+        Env::try_parse_from(args).unwrap().into_config().unwrap();
+    }
+
+    #[test]
+    fn test_backfill_batch_size() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        let config = Env::try_parse_from(args.clone())
+            .unwrap()
+            .into_config()
+            .unwrap();
+        assert_eq!(config.evm.backfill_batch_size.get(), 1000);
+
+        let mut zero = args.clone();
+        zero.extend(["--backfill-batch-size", "0"]);
+        assert!(Env::try_parse_from(zero).is_err());
+
+        args.extend(["--backfill-batch-size", "5000"]);
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+        assert_eq!(config.evm.backfill_batch_size.get(), 5000);
+    }
+
+    #[test]
+    fn test_log_format() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        let config = Env::try_parse_from(args.clone())
+            .unwrap()
+            .into_config()
+            .unwrap();
+        assert_eq!(config.log_format, LogFormat::Pretty);
+
+        let mut unknown = args.clone();
+        unknown.extend(["--log-format", "logfmt"]);
+        assert!(Env::try_parse_from(unknown).is_err());
+
+        args.extend(["--log-format", "json"]);
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+        assert_eq!(config.log_format, LogFormat::Json);
+    }
+
+    #[test]
+    fn test_position_limits() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        args.extend([
+            "--max-net-shares",
+            "500",
+            "--max-net-shares-per-symbol",
+            "aapl=100,TSLA=0",
+        ]);
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+
+        let limit_for = |symbol| {
+            config
+                .position_limits
+                .limit_for(&Symbol::new(symbol).unwrap())
+        };
+        assert_eq!(limit_for("AAPL"), Some(100));
+        assert_eq!(limit_for("TSLA"), Some(0));
+        assert_eq!(limit_for("MSFT"), Some(500));
+    }
+
+    #[test]
+    fn test_symbol_allowlist_and_denylist() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        args.extend([
+            "--symbol-allowlist",
+            "aapl, GME",
+            "--symbol-denylist",
+            "gme",
+        ]);
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+
+        let skip_reason = |symbol| {
+            config
+                .symbol_filter
+                .skip_reason(&Symbol::new(symbol).unwrap())
+        };
+        assert_eq!(skip_reason("AAPL"), None);
+        assert_eq!(skip_reason("GME"), Some("symbol is denylisted"));
+        assert_eq!(skip_reason("TSLA"), Some("symbol is not allowlisted"));
+    }
+
+    #[test]
+    fn test_symbol_convention() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        let config = Env::try_parse_from(args.clone())
+            .unwrap()
+            .into_config()
+            .unwrap();
+        assert_eq!(config.evm.symbol_convention, SymbolConvention::default());
+
+        args.extend(["--tokenized-suffix", "x1", "--stablecoins", "USDC,USDC.e"]);
+        let config = Env::try_parse_from(args).unwrap().into_config().unwrap();
+        assert_eq!(
+            config.evm.symbol_convention,
+            SymbolConvention {
+                tokenized_suffix: "x1".to_string(),
+                stablecoins: vec!["USDC".to_string(), "USDC.e".to_string()],
+            }
+        );
+        assert!(
+            config
+                .symbol_cache()
+                .symbol_convention()
+                .is_stablecoin("USDC.e")
+        );
+    }
+
+    #[test]
+    fn test_queue_poll_delays() {
+        let mut args = dry_run_args(
+            "0x1111111111111111111111111111111111111111",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        );
+        args.extend(["--queue-poll-min-delay-ms", "250"]);
+
+        let config = Env::try_parse_from(args.clone())
+            .unwrap()
+            .into_config()
+            .unwrap();
+        assert_eq!(config.queue_poll_min_delay, Duration::from_millis(250));
+        assert_eq!(config.queue_poll_max_delay, Duration::from_secs(30));
+
+        args.extend(["--queue-poll-max-delay-ms", "200"]);
+        let err = Env::try_parse_from(args)
+            .unwrap()
+            .into_config()
+            .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("QUEUE_POLL_MAX_DELAY_MS"));
+    }
 }
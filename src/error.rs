@@ -1,13 +1,16 @@
 //! Domain-specific error types following clean error handling architecture.
 //! Separates concerns instead of mixing database, business logic, and external API errors.
 
-use alloy::primitives::{B256, ruint::FromUintError};
+use std::time::Duration;
+
+use alloy::primitives::{Address, B256, U256, ruint::FromUintError};
 use alloy::transports::{RpcError, TransportErrorKind};
+use rust_decimal::Decimal;
 use st0x_broker::order::status::ParseOrderStatusError;
 use st0x_broker::{InvalidBrokerError, PersistenceError};
-use std::num::ParseFloatError;
 
 use crate::onchain::position_calculator::ConversionError;
+use crate::onchain::price_source::PriceSourceError;
 
 /// Business logic validation errors for trade processing rules.
 #[derive(Debug, thiserror::Error)]
@@ -25,7 +28,7 @@ pub(crate) enum TradeValidationError {
     #[error("No output found at index: {0}")]
     NoOutputAtIndex(usize),
     #[error(
-        "Expected IO to contain USDC and one tokenized equity (t prefix, 0x or s1 suffix) but got {0} and {1}"
+        "Expected IO to contain a stablecoin and one tokenized equity (t prefix, 0x or configured suffix) but got {0} and {1}"
     )]
     InvalidSymbolConfiguration(String, String),
     #[error(
@@ -35,20 +38,50 @@ pub(crate) enum TradeValidationError {
         symbol: String,
         remaining_shares: f64,
     },
-    #[error("Failed to convert U256 to f64: {0}")]
-    U256ToF64(#[from] ParseFloatError),
+    #[error("Token amount {amount} with {decimals} decimals is too large to convert exactly")]
+    AmountTooLarge { amount: U256, decimals: u8 },
     #[error("Transaction not found: {0}")]
     TransactionNotFound(B256),
     #[error("No AfterClear log found for ClearV2 log")]
     NoAfterClearLog,
     #[error("Negative shares amount: {0}")]
-    NegativeShares(f64),
+    NegativeShares(Decimal),
     #[error("Negative USDC amount: {0}")]
-    NegativeUsdc(f64),
+    NegativeUsdc(Decimal),
     #[error(
         "Symbol '{0}' is not a tokenized equity (must start with 't' or end with '0x' or 's1')"
     )]
     NotTokenizedEquity(String),
+    #[error("symbol() reverted for token {token}, trade flagged for review as '{fallback_symbol}'")]
+    UnresolvedSymbol {
+        token: Address,
+        fallback_symbol: String,
+    },
+    #[error("Trade filtered: {0}")]
+    Filtered(#[from] TradeFilterReason),
+}
+
+/// Why a decoded fill for the order owner is deliberately not turned into a
+/// trade.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TradeFilterReason {
+    #[error("token amount {amount} with {decimals} decimals is too large to convert exactly")]
+    AmountTooLarge { amount: U256, decimals: u8 },
+    #[error("{0}")]
+    Unpriceable(String),
+    #[error("{0}")]
+    PythDivergence(String),
+}
+
+impl TradeFilterReason {
+    /// Short, stable label for logs and filtered-event outcomes.
+    pub(crate) const fn label(&self) -> &'static str {
+        match self {
+            Self::AmountTooLarge { .. } => "amount too large",
+            Self::Unpriceable(_) => "unpriceable trade",
+            Self::PythDivergence(_) => "Pyth price divergence",
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -68,6 +101,8 @@ pub(crate) enum EventQueueError {
     Database(#[from] sqlx::Error),
     #[error("Event queue error: {0}")]
     Processing(String),
+    #[error("Unknown queued event format version {0}")]
+    UnknownFormatVersion(i64),
 }
 
 /// Event processing errors for live event handling.
@@ -87,6 +122,14 @@ pub(crate) enum EventProcessingError {
     Schwab(#[from] st0x_broker::schwab::SchwabError),
     #[error("Broker error: {0}")]
     Broker(#[from] st0x_broker::BrokerError),
+    #[error("Price source error: {0}")]
+    PriceSource(#[from] PriceSourceError),
+    #[error("Unexpected symbol configuration {0} and {1} in strict mode")]
+    StrictSymbolConfiguration(String, String),
+    #[error("Circuit breaker open for {0}, execution skipped")]
+    CircuitBreakerOpen(String),
+    #[error("Broker rate limited the order, retry after {0:?}")]
+    RateLimited(Duration),
 }
 
 /// Order polling errors for order status monitoring.
@@ -94,6 +137,8 @@ pub(crate) enum EventProcessingError {
 pub(crate) enum OrderPollingError {
     #[error("Broker error: {0}")]
     Broker(Box<dyn std::error::Error + Send + Sync>),
+    #[error("Broker rate limited the status poll, retry after {0:?}")]
+    RateLimited(Duration),
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
     #[error("Persistence error: {0}")]
@@ -128,6 +173,8 @@ pub(crate) enum OnChainError {
     InvalidBroker(#[from] InvalidBrokerError),
     #[error("Numeric conversion error: {0}")]
     Conversion(#[from] ConversionError),
+    #[error("Duplicate execution rejected within dedup window: {0}")]
+    DuplicateExecution(String),
 }
 
 impl From<sqlx::Error> for OnChainError {
@@ -142,12 +189,6 @@ impl From<alloy::contract::Error> for OnChainError {
     }
 }
 
-impl From<ParseFloatError> for OnChainError {
-    fn from(err: ParseFloatError) -> Self {
-        Self::Validation(TradeValidationError::U256ToF64(err))
-    }
-}
-
 impl From<st0x_broker::InvalidDirectionError> for OnChainError {
     fn from(err: st0x_broker::InvalidDirectionError) -> Self {
         Self::Persistence(PersistenceError::InvalidDirection(err))
@@ -1,31 +1,55 @@
 use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, info_span, warn};
 
+mod accumulation_contribution;
 pub mod api;
 mod bindings;
 pub mod cli;
 mod conductor;
+mod conversion_outcome;
+mod db_retry;
 pub mod env;
 mod error;
 mod lock;
+mod lru;
+mod metrics;
 mod offchain;
 mod onchain;
+mod order_notifier;
 mod queue;
 pub mod reporter;
+mod rpc_metrics;
+mod shutdown;
+mod standby;
+mod stats;
 mod symbol;
 mod telemetry;
 mod trade_execution_link;
+mod trade_feed;
+mod trade_review;
+mod vacuum;
 
+pub use shutdown::ShutdownReason;
 pub use telemetry::{TelemetryError, TelemetryGuard};
 
 #[cfg(test)]
 pub mod test_utils;
 
 use crate::env::{BrokerConfig, Config};
+use crate::metrics::Metrics;
+use crate::offchain::canary::run_startup_canary;
+use crate::rpc_metrics::RpcMetrics;
+use crate::stats::Stats;
 use st0x_broker::schwab::{SchwabConfig, SchwabError};
-use st0x_broker::{Broker, BrokerError, MockBrokerConfig, TryIntoBroker};
+use st0x_broker::{
+    Broker, BrokerError, FailoverBroker, MockBrokerConfig, PaperBrokerConfig, TryIntoBroker,
+};
 
-pub async fn launch(config: Config) -> anyhow::Result<()> {
+/// Runs the bot and its HTTP server until one of them stops or a shutdown
+/// signal arrives, returning why.
+pub async fn launch(config: Config) -> anyhow::Result<ShutdownReason> {
     let launch_span = info_span!("launch");
     let _enter = launch_span.enter();
 
@@ -33,58 +57,90 @@ pub async fn launch(config: Config) -> anyhow::Result<()> {
 
     sqlx::migrate!().run(&pool).await?;
 
+    let adopted = queue::adopt_legacy_orderbook(&pool, config.evm.orderbook).await?;
+    if adopted > 0 {
+        info!(
+            "Assigned orderbook {} to {adopted} events queued before orderbooks were recorded",
+            config.evm.orderbook
+        );
+    }
+
+    if config.standby.is_standby() {
+        info!("Starting as warm standby, executions are disabled until POST /admin/promote");
+    }
+
     let rocket_config = rocket::Config::figment()
         .merge(("port", config.server_port))
         .merge(("address", "0.0.0.0"));
 
+    let stats = Arc::new(Stats::default());
+
+    if config.rpc_metrics {
+        RpcMetrics::global().enable();
+    }
+
+    let read_pool = config.get_read_pool(&pool).await?;
+
     let rocket = rocket::custom(rocket_config)
         .mount("/", api::routes())
         .manage(pool.clone())
-        .manage(config.clone());
+        .manage(read_pool.clone())
+        .manage(config.clone())
+        .manage(stats.clone())
+        .manage(Metrics::global().clone());
 
     let server_task = tokio::spawn(rocket.launch());
 
+    let reporter_task = config
+        .inline_reporter_interval
+        .map(|interval| reporter::spawn_inline_reporter(pool.clone(), read_pool, interval));
+
+    let drain_timeout = config.shutdown_drain_timeout;
+    let shutdown = CancellationToken::new();
     let bot_pool = pool.clone();
-    let bot_task = tokio::spawn(async move {
+    let bot_stats = stats.clone();
+    let bot_shutdown = shutdown.clone();
+    let mut bot_task = tokio::spawn(async move {
         let bot_span = info_span!("bot_task");
         let _enter = bot_span.enter();
 
-        if let Err(e) = Box::pin(run(config, bot_pool)).await {
-            error!("Bot failed: {e}");
-        }
+        Box::pin(run(config, bot_pool, bot_stats, bot_shutdown)).await
     });
 
-    tokio::select! {
-        _ = tokio::signal::ctrl_c() => {
-            info!("Received shutdown signal, shutting down gracefully...");
-        }
+    let reason =
+        shutdown::wait_for_shutdown(tokio::signal::ctrl_c(), server_task, &mut bot_task).await;
 
-        result = server_task => {
-            match result {
-                Ok(Ok(_)) => info!("Server completed successfully"),
-                Ok(Err(e)) => error!("Server failed: {e}"),
-                Err(e) => error!("Server task panicked: {e}"),
-            }
-        }
+    if reason == ShutdownReason::Signal {
+        shutdown::drain_bot(&shutdown, bot_task, drain_timeout).await;
+    }
 
-        result = bot_task => {
-            match result {
-                Ok(()) => info!("Bot task completed"),
-                Err(e) => error!("Bot task panicked: {e}"),
-            }
-        }
+    if let Some(reporter_task) = reporter_task {
+        reporter_task.abort();
     }
 
-    info!("Shutdown complete");
-    Ok(())
+    info!("Final stats: {}", stats.snapshot());
+
+    let exit_status = reason.exit_status();
+    if reason.is_failure() {
+        error!("Shutting down, reason: {reason}, exit code: {exit_status}");
+    } else {
+        info!("Shutting down, reason: {reason}, exit code: {exit_status}");
+    }
+
+    Ok(reason)
 }
 
 #[tracing::instrument(skip_all, level = tracing::Level::INFO)]
-async fn run(config: Config, pool: SqlitePool) -> anyhow::Result<()> {
+async fn run(
+    config: Config,
+    pool: SqlitePool,
+    stats: Arc<Stats>,
+    shutdown: CancellationToken,
+) -> anyhow::Result<()> {
     const RERUN_DELAY_SECS: u64 = 10;
 
     loop {
-        let result = Box::pin(run_bot_session(&config, &pool)).await;
+        let result = Box::pin(run_bot_session(&config, &pool, &stats, &shutdown)).await;
 
         match result {
             Ok(()) => {
@@ -114,12 +170,17 @@ async fn run(config: Config, pool: SqlitePool) -> anyhow::Result<()> {
 }
 
 #[tracing::instrument(skip_all, level = tracing::Level::INFO)]
-async fn run_bot_session(config: &Config, pool: &SqlitePool) -> anyhow::Result<()> {
+async fn run_bot_session(
+    config: &Config,
+    pool: &SqlitePool,
+    stats: &Arc<Stats>,
+    shutdown: &CancellationToken,
+) -> anyhow::Result<()> {
     match &config.broker {
         BrokerConfig::DryRun => {
             info!("Initializing test broker for dry-run mode");
             let broker = MockBrokerConfig.try_into_broker().await?;
-            Box::pin(run_with_broker(config.clone(), pool.clone(), broker)).await
+            Box::pin(run_with_failover(config, pool, stats, shutdown, broker)).await
         }
         BrokerConfig::Schwab(schwab_auth) => {
             info!("Initializing Schwab broker");
@@ -128,12 +189,117 @@ async fn run_bot_session(config: &Config, pool: &SqlitePool) -> anyhow::Result<(
                 pool: pool.clone(),
             };
             let broker = schwab_config.try_into_broker().await?;
-            Box::pin(run_with_broker(config.clone(), pool.clone(), broker)).await
+            Box::pin(run_with_failover(config, pool, stats, shutdown, broker)).await
         }
         BrokerConfig::Alpaca(alpaca_auth) => {
             info!("Initializing Alpaca broker");
             let broker = alpaca_auth.clone().try_into_broker().await?;
-            Box::pin(run_with_broker(config.clone(), pool.clone(), broker)).await
+            Box::pin(run_with_failover(config, pool, stats, shutdown, broker)).await
+        }
+        BrokerConfig::Sim(sim_config) => {
+            info!("Initializing simulated broker for dry-run mode");
+            let broker = sim_config.clone().try_into_broker().await?;
+            Box::pin(run_with_failover(config, pool, stats, shutdown, broker)).await
+        }
+        BrokerConfig::Paper => {
+            info!("Initializing paper-trading broker");
+            let broker = PaperBrokerConfig { pool: pool.clone() }
+                .try_into_broker()
+                .await?;
+            Box::pin(run_with_failover(config, pool, stats, shutdown, broker)).await
+        }
+    }
+}
+
+/// Runs with `primary`, failing over to the configured secondary broker if
+/// there is one.
+async fn run_with_failover<P>(
+    config: &Config,
+    pool: &SqlitePool,
+    stats: &Arc<Stats>,
+    shutdown: &CancellationToken,
+    primary: P,
+) -> anyhow::Result<()>
+where
+    P: Broker<OrderId = String, Error = BrokerError> + Clone + Send + 'static,
+{
+    let Some(failover) = &config.failover else {
+        return Box::pin(run_with_broker(
+            config.clone(),
+            pool.clone(),
+            stats.clone(),
+            shutdown.clone(),
+            primary,
+        ))
+        .await;
+    };
+
+    info!(
+        "Initializing {} as failover broker",
+        failover.broker.to_supported_broker()
+    );
+
+    match &failover.broker {
+        BrokerConfig::DryRun => {
+            let secondary = MockBrokerConfig.try_into_broker().await?;
+            Box::pin(run_with_broker(
+                config.clone(),
+                pool.clone(),
+                stats.clone(),
+                shutdown.clone(),
+                FailoverBroker::new(primary, secondary, failover.policy),
+            ))
+            .await
+        }
+        BrokerConfig::Schwab(schwab_auth) => {
+            let schwab_config = SchwabConfig {
+                auth: schwab_auth.clone(),
+                pool: pool.clone(),
+            };
+            let secondary = schwab_config.try_into_broker().await?;
+            Box::pin(run_with_broker(
+                config.clone(),
+                pool.clone(),
+                stats.clone(),
+                shutdown.clone(),
+                FailoverBroker::new(primary, secondary, failover.policy),
+            ))
+            .await
+        }
+        BrokerConfig::Alpaca(alpaca_auth) => {
+            let secondary = alpaca_auth.clone().try_into_broker().await?;
+            Box::pin(run_with_broker(
+                config.clone(),
+                pool.clone(),
+                stats.clone(),
+                shutdown.clone(),
+                FailoverBroker::new(primary, secondary, failover.policy),
+            ))
+            .await
+        }
+        BrokerConfig::Sim(sim_config) => {
+            let secondary = sim_config.clone().try_into_broker().await?;
+            Box::pin(run_with_broker(
+                config.clone(),
+                pool.clone(),
+                stats.clone(),
+                shutdown.clone(),
+                FailoverBroker::new(primary, secondary, failover.policy),
+            ))
+            .await
+        }
+        BrokerConfig::Paper => {
+            let secondary = PaperBrokerConfig { pool: pool.clone() }
+                .try_into_broker()
+                .await?;
+            Box::pin(run_with_broker(
+                config.clone(),
+                pool.clone(),
+                stats.clone(),
+                shutdown.clone(),
+                FailoverBroker::new(primary, secondary, failover.policy),
+            ))
+            .await
         }
     }
 }
@@ -141,11 +307,18 @@ async fn run_bot_session(config: &Config, pool: &SqlitePool) -> anyhow::Result<(
 async fn run_with_broker<B: Broker + Clone + Send + 'static>(
     config: Config,
     pool: SqlitePool,
+    stats: Arc<Stats>,
+    shutdown: CancellationToken,
     broker: B,
 ) -> anyhow::Result<()> {
+    if let Some(symbol) = &config.startup_canary {
+        run_startup_canary(&broker, symbol).await?;
+    }
+
     let broker_maintenance = broker.run_broker_maintenance().await;
 
-    conductor::run_market_hours_loop(broker, config, pool, broker_maintenance).await
+    conductor::run_market_hours_loop(broker, config, pool, stats, broker_maintenance, shutdown)
+        .await
 }
 
 #[cfg(test)]
@@ -164,7 +337,9 @@ mod tests {
         let mut config = create_test_config();
         let pool = create_test_pool().await;
         config.evm.ws_rpc_url = "ws://invalid.nonexistent.url:8545".parse().unwrap();
-        Box::pin(run(config, pool)).await.unwrap_err();
+        Box::pin(run(config, pool, Arc::default(), CancellationToken::new()))
+            .await
+            .unwrap_err();
     }
 
     #[tokio::test]
@@ -173,7 +348,9 @@ mod tests {
         let pool = create_test_pool().await;
         config.evm.orderbook = alloy::primitives::Address::ZERO;
         config.evm.ws_rpc_url = "ws://localhost:8545".parse().unwrap();
-        Box::pin(run(config, pool)).await.unwrap_err();
+        Box::pin(run(config, pool, Arc::default(), CancellationToken::new()))
+            .await
+            .unwrap_err();
     }
 
     #[tokio::test]
@@ -181,6 +358,8 @@ mod tests {
         let mut config = create_test_config();
         config.evm.ws_rpc_url = "ws://invalid.nonexistent.localhost:9999".parse().unwrap();
         let pool = create_test_pool().await;
-        Box::pin(run(config, pool)).await.unwrap_err();
+        Box::pin(run(config, pool, Arc::default(), CancellationToken::new()))
+            .await
+            .unwrap_err();
     }
 }
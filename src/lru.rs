@@ -0,0 +1,94 @@
+//! Least-recently-used map backing the in-memory lookup caches.
+//!
+//! Without a capacity the map grows like a plain `HashMap`. With one, every
+//! lookup or insert marks the key as most recently used, and an insert that
+//! grows the map beyond the capacity evicts the least recently used entry.
+//! Lookups update recency, so callers sharing a map behind a lock need write
+//! access even to read.
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+#[derive(Debug, Clone)]
+pub(crate) struct LruMap<K, V> {
+    capacity: Option<NonZeroUsize>,
+    entries: HashMap<K, (V, u64)>,
+    recency: BTreeMap<u64, K>,
+    next_tick: u64,
+}
+
+impl<K, V> Default for LruMap<K, V> {
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            next_tick: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> LruMap<K, V> {
+    /// Creates a map holding at most `capacity` entries, or unbounded if `None`.
+    pub(crate) fn new(capacity: Option<NonZeroUsize>) -> Self {
+        Self {
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (stored_key, _) = self.entries.get_key_value(key)?;
+        let stored_key = stored_key.clone();
+
+        let tick = self.tick();
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.recency.remove(last_used);
+        self.recency.insert(tick, stored_key);
+        *last_used = tick;
+        Some(value)
+    }
+
+    /// Inserts or replaces `key`, evicting the least recently used entry if
+    /// the map is over capacity afterwards.
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        let tick = self.tick();
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (value, tick)) {
+            self.recency.remove(&last_used);
+        }
+        self.recency.insert(tick, key);
+
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+
+        while self.entries.len() > capacity.get() {
+            let Some((_, evicted)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&evicted);
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn tick(&mut self) -> u64 {
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        tick
+    }
+}
@@ -0,0 +1,329 @@
+//! Prometheus metrics served by `GET /metrics`.
+//!
+//! A process-wide [`Metrics`] registry is updated from the hot paths, like
+//! [`crate::rpc_metrics::RpcMetrics`], and managed by Rocket so the endpoint
+//! renders it in the Prometheus text exposition format. All state sits
+//! behind a single lock so a scrape sees a consistent [`MetricsSnapshot`].
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use st0x_broker::{BrokerError, SupportedBroker};
+
+/// Upper bounds, in seconds, of the fill latency histogram buckets. Slower
+/// fills fall into the final `+Inf` bucket.
+const FILL_LATENCY_BUCKETS_SECS: [f64; 9] =
+    [1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0, 3_600.0, 14_400.0];
+
+static METRICS: LazyLock<Arc<Metrics>> = LazyLock::new(Arc::default);
+
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    queue_depth: u64,
+    pending_orders: u64,
+    events_processed: u64,
+    fill_latency_buckets: [u64; FILL_LATENCY_BUCKETS_SECS.len() + 1],
+    fill_latency_sum: Duration,
+    broker_errors: BTreeMap<(String, &'static str), u64>,
+    /// Placement time of orders awaiting a fill, by execution id
+    placed_at: HashMap<i64, Instant>,
+}
+
+/// Point-in-time copy of the metrics, rendered by `GET /metrics`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MetricsSnapshot {
+    pub(crate) queue_depth: u64,
+    pub(crate) pending_orders: u64,
+    pub(crate) events_processed: u64,
+    /// Cumulative fill counts per bucket upper bound, the last one `+Inf`
+    pub(crate) fill_latency_buckets: Vec<(Option<f64>, u64)>,
+    pub(crate) fill_latency_sum_secs: f64,
+    pub(crate) fill_latency_count: u64,
+    /// Broker errors by broker and error kind
+    pub(crate) broker_errors: BTreeMap<(String, &'static str), u64>,
+    pub(crate) token_refresh_failures: u64,
+}
+
+impl Metrics {
+    pub(crate) fn global() -> &'static Arc<Self> {
+        &METRICS
+    }
+
+    fn with_state<T>(&self, f: impl FnOnce(&mut MetricsState) -> T) -> T {
+        f(&mut self.state.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Unprocessed events in the event queue, see
+    /// [`crate::queue::count_unprocessed`].
+    pub(crate) fn set_queue_depth(&self, depth: u64) {
+        self.with_state(|state| state.queue_depth = depth);
+    }
+
+    /// Submitted orders the order poller is waiting on.
+    pub(crate) fn set_pending_orders(&self, count: u64) {
+        self.with_state(|state| state.pending_orders = count);
+    }
+
+    /// A queued event was converted to a trade and committed.
+    pub(crate) fn record_event_processed(&self) {
+        self.with_state(|state| state.events_processed += 1);
+    }
+
+    /// The broker accepted the order of `execution_id`, starting its fill
+    /// latency clock.
+    pub(crate) fn record_order_placed(&self, execution_id: i64) {
+        self.with_state(|state| state.placed_at.insert(execution_id, Instant::now()));
+    }
+
+    /// The order of `execution_id` filled. Orders placed before this process
+    /// started have no placement time and are not observed.
+    pub(crate) fn record_order_filled(&self, execution_id: i64) {
+        self.with_state(|state| {
+            let Some(placed_at) = state.placed_at.remove(&execution_id) else {
+                return;
+            };
+            state.observe_fill_latency(placed_at.elapsed());
+        });
+    }
+
+    /// The order of `execution_id` failed or was cancelled and will not fill.
+    pub(crate) fn record_order_closed(&self, execution_id: i64) {
+        self.with_state(|state| state.placed_at.remove(&execution_id));
+    }
+
+    /// A call to `broker` failed with `error`. Errors other than
+    /// [`BrokerError`] are counted under the `other` kind.
+    pub(crate) fn record_broker_error<E: std::error::Error + 'static>(
+        &self,
+        broker: SupportedBroker,
+        error: &E,
+    ) {
+        let kind = (error as &dyn Any)
+            .downcast_ref::<BrokerError>()
+            .map_or("other", BrokerError::kind);
+
+        self.with_state(|state| {
+            *state
+                .broker_errors
+                .entry((broker.to_string(), kind))
+                .or_default() += 1;
+        });
+    }
+
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        self.with_state(|state| {
+            let mut cumulative = 0;
+            let fill_latency_buckets = FILL_LATENCY_BUCKETS_SECS
+                .iter()
+                .map(|&le| Some(le))
+                .chain(std::iter::once(None))
+                .zip(state.fill_latency_buckets)
+                .map(|(le, count)| {
+                    cumulative += count;
+                    (le, cumulative)
+                })
+                .collect();
+
+            MetricsSnapshot {
+                queue_depth: state.queue_depth,
+                pending_orders: state.pending_orders,
+                events_processed: state.events_processed,
+                fill_latency_buckets,
+                fill_latency_sum_secs: state.fill_latency_sum.as_secs_f64(),
+                fill_latency_count: cumulative,
+                broker_errors: state.broker_errors.clone(),
+                token_refresh_failures: st0x_broker::schwab::token_refresh_failures(),
+            }
+        })
+    }
+}
+
+impl MetricsState {
+    fn observe_fill_latency(&mut self, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        let bucket = FILL_LATENCY_BUCKETS_SECS
+            .iter()
+            .position(|&le| secs <= le)
+            .unwrap_or(FILL_LATENCY_BUCKETS_SECS.len());
+
+        self.fill_latency_buckets[bucket] += 1;
+        self.fill_latency_sum += latency;
+    }
+}
+
+impl MetricsSnapshot {
+    /// Renders the snapshot in the Prometheus text exposition format.
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+
+        write_metric(
+            &mut out,
+            "st0x_event_queue_depth",
+            "gauge",
+            "Unprocessed events in the event queue",
+            [unlabelled(self.queue_depth)],
+        );
+        write_metric(
+            &mut out,
+            "st0x_pending_orders",
+            "gauge",
+            "Submitted broker orders awaiting a fill",
+            [unlabelled(self.pending_orders)],
+        );
+        write_metric(
+            &mut out,
+            "st0x_events_processed_total",
+            "counter",
+            "Queued events converted to trades",
+            [unlabelled(self.events_processed)],
+        );
+
+        let buckets = self.fill_latency_buckets.iter().map(|(le, count)| {
+            let le = le.map_or_else(|| "+Inf".to_string(), |le| le.to_string());
+            (format!("_bucket{{le=\"{le}\"}}"), count.to_string())
+        });
+        write_metric(
+            &mut out,
+            "st0x_fill_latency_seconds",
+            "histogram",
+            "Time from order placement to FILLED",
+            buckets.chain([
+                ("_sum".to_string(), self.fill_latency_sum_secs.to_string()),
+                ("_count".to_string(), self.fill_latency_count.to_string()),
+            ]),
+        );
+
+        write_metric(
+            &mut out,
+            "st0x_broker_errors_total",
+            "counter",
+            "Failed broker API calls by broker and error kind",
+            self.broker_errors.iter().map(|((broker, kind), count)| {
+                (
+                    format!("{{broker=\"{broker}\",kind=\"{kind}\"}}"),
+                    count.to_string(),
+                )
+            }),
+        );
+
+        write_metric(
+            &mut out,
+            "st0x_token_refresh_failures_total",
+            "counter",
+            "Failed automatic Schwab token refreshes",
+            [unlabelled(self.token_refresh_failures)],
+        );
+
+        out
+    }
+}
+
+fn unlabelled(value: u64) -> (String, String) {
+    (String::new(), value.to_string())
+}
+
+/// Writes the HELP and TYPE lines of `name` followed by one sample per
+/// `(suffix, value)`, where the suffix holds any name suffix and labels.
+fn write_metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: impl IntoIterator<Item = (String, String)>,
+) {
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    for (suffix, value) in samples {
+        let _ = writeln!(out, "{name}{suffix} {value}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use st0x_broker::schwab::SchwabError;
+
+    #[test]
+    fn test_fill_latency_observed_between_placement_and_fill() {
+        let metrics = Metrics::default();
+
+        metrics.record_order_placed(1);
+        metrics.record_order_placed(2);
+        metrics.record_order_filled(1);
+        metrics.record_order_closed(2);
+        // Neither a closed order nor one placed by another process is observed
+        metrics.record_order_filled(2);
+        metrics.record_order_filled(3);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.fill_latency_count, 1);
+        assert_eq!(snapshot.fill_latency_buckets[0], (Some(1.0), 1));
+        assert_eq!(snapshot.fill_latency_buckets.last(), Some(&(None, 1)));
+        assert!(metrics.with_state(|state| state.placed_at.is_empty()));
+    }
+
+    #[test]
+    fn test_latency_buckets_are_cumulative() {
+        let metrics = Metrics::default();
+        metrics.with_state(|state| {
+            state.observe_fill_latency(Duration::from_secs(3));
+            state.observe_fill_latency(Duration::from_secs(45));
+            state.observe_fill_latency(Duration::from_secs(20_000));
+        });
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.fill_latency_buckets[0], (Some(1.0), 0));
+        assert_eq!(snapshot.fill_latency_buckets[1], (Some(5.0), 1));
+        assert_eq!(snapshot.fill_latency_buckets[4], (Some(60.0), 2));
+        assert_eq!(snapshot.fill_latency_buckets[8], (Some(14_400.0), 2));
+        assert_eq!(snapshot.fill_latency_buckets[9], (None, 3));
+        assert_eq!(snapshot.fill_latency_count, 3);
+        assert!((snapshot.fill_latency_sum_secs - 20_048.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_broker_errors_counted_by_kind_and_rendered() {
+        let metrics = Metrics::default();
+        metrics.set_queue_depth(7);
+        metrics.record_event_processed();
+        metrics.record_broker_error(
+            SupportedBroker::Schwab,
+            &BrokerError::Schwab(SchwabError::RefreshTokenExpired),
+        );
+        metrics.record_broker_error(
+            SupportedBroker::Schwab,
+            &BrokerError::Schwab(SchwabError::RefreshTokenExpired),
+        );
+        metrics.record_broker_error(SupportedBroker::Alpaca, &std::fmt::Error);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.broker_errors,
+            BTreeMap::from([
+                (("alpaca".to_string(), "other"), 1),
+                (("schwab".to_string(), "refresh_token_expired"), 2),
+            ])
+        );
+
+        let rendered = snapshot.render();
+        assert!(
+            rendered.contains("# TYPE st0x_event_queue_depth gauge\nst0x_event_queue_depth 7\n")
+        );
+        assert!(rendered.contains("st0x_events_processed_total 1\n"));
+        assert!(rendered.contains(
+            "st0x_broker_errors_total{broker=\"schwab\",kind=\"refresh_token_expired\"} 2\n"
+        ));
+        assert!(rendered.contains("st0x_fill_latency_seconds_bucket{le=\"+Inf\"} 0\n"));
+        assert!(rendered.contains("st0x_fill_latency_seconds_count 0\n"));
+        assert!(rendered.contains("# TYPE st0x_token_refresh_failures_total counter\n"));
+    }
+}
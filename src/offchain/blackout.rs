@@ -0,0 +1,200 @@
+//! Blackout calendar for offchain executions.
+//!
+//! Blackout windows are inclusive date ranges (in US Eastern market time)
+//! during which executions must not be placed, either for every symbol or
+//! for a single one. Broker maintenance windows black out every symbol for
+//! their duration. Trades keep accumulating while blacked out; the periodic
+//! position check picks them up once the window has passed.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::US::Eastern;
+use std::str::FromStr;
+
+use super::maintenance::MaintenanceCalendar;
+use st0x_broker::Symbol;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum BlackoutParseError {
+    #[error("Invalid blackout date '{0}', expected YYYY-MM-DD")]
+    InvalidDate(String),
+    #[error("Blackout window ends ({end}) before it starts ({start})")]
+    EndBeforeStart { start: NaiveDate, end: NaiveDate },
+    #[error("Blackout symbol cannot be empty")]
+    EmptySymbol,
+}
+
+/// A single blackout window, parsed from `[SYMBOL:]START[..END]`.
+///
+/// Examples: `2025-12-17` (all symbols, one day),
+/// `AAPL:2025-10-28..2025-10-31` (AAPL only, four days).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlackoutWindow {
+    start: NaiveDate,
+    end: NaiveDate,
+    symbol: Option<Symbol>,
+}
+
+impl BlackoutWindow {
+    fn covers(&self, symbol: &Symbol, date: NaiveDate) -> bool {
+        (self.start..=self.end).contains(&date)
+            && self.symbol.as_ref().is_none_or(|blocked| blocked == symbol)
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, BlackoutParseError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| BlackoutParseError::InvalidDate(value.to_string()))
+}
+
+impl FromStr for BlackoutWindow {
+    type Err = BlackoutParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (symbol, range) = match value.split_once(':') {
+            Some((symbol, range)) => {
+                let symbol = Symbol::new(symbol.trim().to_uppercase())
+                    .map_err(|_| BlackoutParseError::EmptySymbol)?;
+                (Some(symbol), range)
+            }
+            None => (None, value),
+        };
+
+        let (start, end) = if let Some((start, end)) = range.split_once("..") {
+            (parse_date(start)?, parse_date(end)?)
+        } else {
+            let date = parse_date(range)?;
+            (date, date)
+        };
+
+        if end < start {
+            return Err(BlackoutParseError::EndBeforeStart { start, end });
+        }
+
+        Ok(Self { start, end, symbol })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct BlackoutCalendar {
+    windows: Vec<BlackoutWindow>,
+    maintenance: MaintenanceCalendar,
+}
+
+impl BlackoutCalendar {
+    pub(crate) fn new(windows: Vec<BlackoutWindow>) -> Self {
+        Self {
+            windows,
+            maintenance: MaintenanceCalendar::default(),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn with_maintenance(mut self, maintenance: MaintenanceCalendar) -> Self {
+        self.maintenance = maintenance;
+        self
+    }
+
+    pub(crate) const fn maintenance(&self) -> &MaintenanceCalendar {
+        &self.maintenance
+    }
+
+    /// Whether `symbol` may not be traded on the given market date.
+    pub(crate) fn is_blacked_out(&self, symbol: &Symbol, date: NaiveDate) -> bool {
+        self.windows
+            .iter()
+            .any(|window| window.covers(symbol, date))
+    }
+
+    /// Whether `symbol` may not be traded at `now`, using the US Eastern
+    /// calendar date so windows line up with exchange trading days, or the
+    /// broker is down for maintenance.
+    pub(crate) fn is_blacked_out_at(&self, symbol: &Symbol, now: DateTime<Utc>) -> bool {
+        self.maintenance.is_under_maintenance_at(now)
+            || self.is_blacked_out(symbol, now.with_timezone(&Eastern).date_naive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_single_day_for_all_symbols() {
+        let window: BlackoutWindow = "2025-12-17".parse().unwrap();
+        assert_eq!(
+            window,
+            BlackoutWindow {
+                start: date("2025-12-17"),
+                end: date("2025-12-17"),
+                symbol: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_symbol_range() {
+        let window: BlackoutWindow = "aapl:2025-10-28..2025-10-31".parse().unwrap();
+        assert_eq!(
+            window,
+            BlackoutWindow {
+                start: date("2025-10-28"),
+                end: date("2025-10-31"),
+                symbol: Some(Symbol::new("AAPL").unwrap()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "2025-13-01".parse::<BlackoutWindow>().unwrap_err(),
+            BlackoutParseError::InvalidDate(_)
+        ));
+        assert!(matches!(
+            "2025-10-31..2025-10-28"
+                .parse::<BlackoutWindow>()
+                .unwrap_err(),
+            BlackoutParseError::EndBeforeStart { .. }
+        ));
+        assert_eq!(
+            ":2025-10-28".parse::<BlackoutWindow>().unwrap_err(),
+            BlackoutParseError::EmptySymbol
+        );
+    }
+
+    #[test]
+    fn test_is_blacked_out_by_symbol_and_date() {
+        let calendar = BlackoutCalendar::new(vec![
+            "AAPL:2025-10-28..2025-10-31".parse().unwrap(),
+            "2025-12-17".parse().unwrap(),
+        ]);
+        let aapl = Symbol::new("AAPL").unwrap();
+        let msft = Symbol::new("MSFT").unwrap();
+
+        assert!(calendar.is_blacked_out(&aapl, date("2025-10-28")));
+        assert!(calendar.is_blacked_out(&aapl, date("2025-10-31")));
+        assert!(!calendar.is_blacked_out(&aapl, date("2025-11-01")));
+        assert!(!calendar.is_blacked_out(&msft, date("2025-10-29")));
+
+        assert!(calendar.is_blacked_out(&aapl, date("2025-12-17")));
+        assert!(calendar.is_blacked_out(&msft, date("2025-12-17")));
+    }
+
+    #[test]
+    fn test_is_blacked_out_at_uses_eastern_date() {
+        let calendar = BlackoutCalendar::new(vec!["2025-12-17".parse().unwrap()]);
+        let aapl = Symbol::new("AAPL").unwrap();
+
+        // 02:00 UTC on the 18th is still the evening of the 17th in New York
+        let late_evening = Utc.with_ymd_and_hms(2025, 12, 18, 2, 0, 0).unwrap();
+        assert!(calendar.is_blacked_out_at(&aapl, late_evening));
+
+        let next_morning = Utc.with_ymd_and_hms(2025, 12, 18, 14, 0, 0).unwrap();
+        assert!(!calendar.is_blacked_out_at(&aapl, next_morning));
+    }
+}
@@ -0,0 +1,89 @@
+//! Startup smoke test against the broker.
+//!
+//! When a canary symbol is configured, a one-share buy for it is previewed
+//! with the broker before any trading tasks start. Previewing goes through the
+//! same authentication and order validation as a real order without executing
+//! anything, so a broken deploy fails at startup instead of on the first hedge.
+
+use tracing::{error, info};
+
+use st0x_broker::{Broker, Direction, MarketOrder, Shares, Symbol};
+
+#[derive(Debug, thiserror::Error)]
+#[error("Startup canary order for {symbol} was not accepted by the broker: {reason}")]
+pub(crate) struct CanaryError {
+    symbol: Symbol,
+    reason: String,
+}
+
+/// Parses the canary symbol for the `--startup-canary` flag.
+pub(crate) fn parse_canary_symbol(value: &str) -> Result<Symbol, String> {
+    Symbol::new(value.trim().to_uppercase()).map_err(|e| format!("Invalid canary symbol: {e}"))
+}
+
+/// Previews a one-share order for `symbol`, failing if the broker rejects it
+/// or cannot be reached.
+pub(crate) async fn run_startup_canary<B: Broker>(
+    broker: &B,
+    symbol: &Symbol,
+) -> Result<(), CanaryError> {
+    let order = MarketOrder {
+        symbol: symbol.clone(),
+        shares: Shares::new(1).map_err(|e| CanaryError {
+            symbol: symbol.clone(),
+            reason: e.to_string(),
+        })?,
+        direction: Direction::Buy,
+        client_order_id: None,
+    };
+
+    info!("Running startup canary: previewing a 1 share buy of {symbol}");
+
+    match broker.preview_market_order(&order).await {
+        Ok(()) => {
+            info!("Startup canary for {symbol} succeeded");
+            Ok(())
+        }
+        Err(e) => {
+            error!("Startup canary for {symbol} failed: {e}");
+            Err(CanaryError {
+                symbol: symbol.clone(),
+                reason: e.to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use st0x_broker::MockBroker;
+
+    #[tokio::test]
+    async fn test_startup_canary_succeeds_when_order_is_accepted() {
+        let symbol = Symbol::new("SPY").unwrap();
+        run_startup_canary(&MockBroker::new(), &symbol)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_startup_canary_fails_when_order_is_rejected() {
+        let symbol = Symbol::new("SPY").unwrap();
+        let error = run_startup_canary(&MockBroker::with_failure("account restricted"), &symbol)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.symbol, symbol);
+        assert!(error.reason.contains("account restricted"));
+    }
+
+    #[test]
+    fn test_parse_canary_symbol() {
+        assert_eq!(
+            parse_canary_symbol(" spy ").unwrap(),
+            Symbol::new("SPY").unwrap()
+        );
+        assert!(parse_canary_symbol("").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! End-of-day handling of fractional positions left in the accumulators.
+//!
+//! Whole shares are executed as soon as they accumulate, but a net position
+//! below one share stays unhedged until later trades push it over. Shortly
+//! before the market closes, each such residual is either held overnight as
+//! it is, or flattened by rounding it up to a one-share order. A flattened
+//! residual overshoots by the rest of the share, which stays accumulated on
+//! the opposite side for the next session's trades to offset.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+use tracing::info;
+
+use st0x_broker::SupportedBroker;
+
+use crate::error::OnChainError;
+use crate::offchain::blackout::BlackoutCalendar;
+use crate::offchain::execution::OffchainExecution;
+use crate::offchain::trade_side::TradeSide;
+use crate::onchain::accumulator::{execute_residual_position, find_residual_positions};
+use crate::onchain::io::SymbolConvention;
+
+/// What to do with a fractional net position at the end of the trading day.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EndOfDaySettlement {
+    /// Keep fractional positions accumulated overnight
+    #[default]
+    Hold,
+    /// Round each fractional position up to a one-share order before close
+    Flatten,
+}
+
+/// Applies `settlement` to every fractional position, returning the rounding
+/// executions created for the caller to place. Symbols in a blackout window
+/// are held regardless.
+pub(crate) async fn settle_residual_positions(
+    pool: &SqlitePool,
+    settlement: EndOfDaySettlement,
+    blackout: &BlackoutCalendar,
+    broker_type: SupportedBroker,
+    convention: &SymbolConvention,
+    trade_side: TradeSide,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    let mut executions = Vec::new();
+
+    for (symbol, net_position) in find_residual_positions(pool).await? {
+        let hold_reason = match settlement {
+            EndOfDaySettlement::Hold => "settlement policy is hold",
+            EndOfDaySettlement::Flatten if blackout.is_blacked_out_at(&symbol, Utc::now()) => {
+                "symbol is in a blackout window"
+            }
+            EndOfDaySettlement::Flatten => {
+                if let Some(execution) =
+                    execute_residual_position(pool, &symbol, broker_type, convention, trade_side)
+                        .await?
+                {
+                    executions.push(execution);
+                }
+                continue;
+            }
+        };
+
+        info!(
+            symbol = %symbol,
+            net_position,
+            "Holding residual position overnight, {hold_reason}"
+        );
+    }
+
+    Ok(executions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offchain::liquidity::LiquidityLimits;
+    use crate::onchain::accumulator::{ExecutionRules, find_by_symbol, process_onchain_trade};
+    use crate::onchain::position_calculator::ShareRounding;
+    use crate::test_utils::{OnchainTradeBuilder, setup_test_db};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use st0x_broker::{Direction, Shares};
+
+    async fn setup_residual_position(amount: Decimal) -> SqlitePool {
+        let pool = setup_test_db().await;
+        let trade = OnchainTradeBuilder::new().with_amount(amount).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+        assert!(execution.is_empty());
+
+        pool
+    }
+
+    async fn net_position(pool: &SqlitePool) -> Decimal {
+        let (calculator, _) = find_by_symbol(pool, "AAPL").await.unwrap().unwrap();
+        calculator.net_position()
+    }
+
+    #[tokio::test]
+    async fn test_flatten_rounds_residual_position_to_one_share_order() {
+        let pool = setup_residual_position(dec!(0.6)).await;
+
+        let executions = settle_residual_positions(
+            &pool,
+            EndOfDaySettlement::Flatten,
+            &BlackoutCalendar::default(),
+            SupportedBroker::Schwab,
+            &SymbolConvention::default(),
+            TradeSide::Both,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].shares, Shares::new(1).unwrap());
+        // The residual came from an onchain buy, so it is offset by selling
+        assert_eq!(executions[0].direction, Direction::Sell);
+        assert_eq!(net_position(&pool).await, dec!(-0.4));
+
+        // Pending rounding executions are not rounded again
+        let again = settle_residual_positions(
+            &pool,
+            EndOfDaySettlement::Flatten,
+            &BlackoutCalendar::default(),
+            SupportedBroker::Schwab,
+            &SymbolConvention::default(),
+            TradeSide::Both,
+        )
+        .await
+        .unwrap();
+        assert!(again.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hold_keeps_residual_position_accumulated() {
+        let pool = setup_residual_position(dec!(0.6)).await;
+
+        let executions = settle_residual_positions(
+            &pool,
+            EndOfDaySettlement::Hold,
+            &BlackoutCalendar::default(),
+            SupportedBroker::Schwab,
+            &SymbolConvention::default(),
+            TradeSide::Both,
+        )
+        .await
+        .unwrap();
+
+        assert!(executions.is_empty());
+        assert_eq!(net_position(&pool).await, dec!(0.6));
+
+        let pending = sqlx::query!("SELECT COUNT(*) AS count FROM offchain_trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(pending.count, 0);
+    }
+}
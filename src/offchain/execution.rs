@@ -1,6 +1,10 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use sqlx::SqlitePool;
+use std::time::Duration;
 
 use crate::error::OnChainError;
+use crate::onchain::trade::parse_stored_decimal;
 use st0x_broker::{
     Direction, OrderState, OrderStatus, PersistenceError, Shares, SupportedBroker, Symbol,
 };
@@ -123,6 +127,223 @@ pub(crate) async fn find_execution_by_id(
     }
 }
 
+/// Submitted executions of `broker` whose order was placed before
+/// `placed_before`, oldest first.
+pub(crate) async fn find_submitted_executions_placed_before(
+    pool: &SqlitePool,
+    broker: SupportedBroker,
+    placed_before: DateTime<Utc>,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    let rows = sqlx::query_as::<_, ExecutionRow>(
+        "
+        SELECT
+            id,
+            symbol,
+            shares,
+            direction,
+            broker,
+            order_id,
+            price_cents,
+            status,
+            executed_at
+        FROM offchain_trades
+        WHERE status = 'SUBMITTED' AND broker = ?1 AND placed_at < ?2
+        ORDER BY placed_at ASC
+        ",
+    )
+    .bind(broker.to_string())
+    .bind(placed_before.naive_utc())
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(row_to_execution)
+        .collect::<Result<Vec<_>, _>>()
+}
+
+/// Filters for [`list_executions`]; unset filters match every execution.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExecutionFilter {
+    pub(crate) symbol: Option<Symbol>,
+    pub(crate) status: Option<OrderStatus>,
+    /// Inclusive lower bound on when the execution filled, failed or was
+    /// cancelled. Executions still in progress have no such time and are
+    /// left out once either bound is set
+    pub(crate) from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on when the execution finished
+    pub(crate) to: Option<DateTime<Utc>>,
+}
+
+/// One page of executions matching `filter`, newest first.
+pub(crate) async fn list_executions(
+    pool: &SqlitePool,
+    filter: &ExecutionFilter,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    let rows = sqlx::query_as::<_, ExecutionRow>(
+        "
+        SELECT
+            id,
+            symbol,
+            shares,
+            direction,
+            broker,
+            order_id,
+            price_cents,
+            status,
+            executed_at
+        FROM offchain_trades
+        WHERE (?1 IS NULL OR symbol = ?1)
+          AND (?2 IS NULL OR status = ?2)
+          AND (?3 IS NULL OR executed_at >= ?3)
+          AND (?4 IS NULL OR executed_at < ?4)
+        ORDER BY id DESC
+        LIMIT ?5 OFFSET ?6
+        ",
+    )
+    .bind(filter.symbol.as_ref().map(ToString::to_string))
+    .bind(filter.status.map(OrderStatus::as_str))
+    .bind(filter.from.map(|from| from.naive_utc()))
+    .bind(filter.to.map(|to| to.naive_utc()))
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(row_to_execution)
+        .collect::<Result<Vec<_>, _>>()
+}
+
+/// Estimated USD notional of an execution, valued at the onchain prices of
+/// the trades linked to it. `None` when no trades are linked yet.
+pub(crate) async fn find_execution_notional(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<Option<f64>, OnChainError> {
+    let notional = sqlx::query_scalar!(
+        r#"
+        SELECT SUM(tel.contributed_shares * CAST(ot.price_usdc AS REAL)) AS "notional?: f64"
+        FROM trade_execution_links tel
+        JOIN onchain_trades ot ON ot.id = tel.trade_id
+        WHERE tel.execution_id = ?1
+        "#,
+        execution_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(notional)
+}
+
+/// Records the order body sent to the broker for an execution.
+pub(crate) async fn save_request_payload(
+    pool: &SqlitePool,
+    execution_id: i64,
+    request_payload: &str,
+) -> Result<(), OnChainError> {
+    sqlx::query!(
+        "UPDATE offchain_trades SET request_payload = ?1 WHERE id = ?2",
+        request_payload,
+        execution_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Stamps the execution with the block of the latest onchain trade linked to
+/// it. Must run after the execution's trade links are saved.
+pub(crate) async fn record_origin_block_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    execution_id: i64,
+) -> Result<(), OnChainError> {
+    sqlx::query!(
+        r#"
+        UPDATE offchain_trades
+        SET block_number = (
+            SELECT MAX(eq.block_number)
+            FROM trade_execution_links tel
+            JOIN onchain_trades ot ON ot.id = tel.trade_id
+            JOIN event_queue eq
+                ON eq.tx_hash = ot.tx_hash
+                AND eq.log_index = ot.log_index
+                AND eq.orderbook = ot.orderbook
+            WHERE tel.execution_id = ?1
+        )
+        WHERE id = ?1
+        "#,
+        execution_id
+    )
+    .execute(&mut **sql_tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Tags the execution with the strategy label of the latest onchain trade
+/// linked to it. Must run after the execution's trade links are saved.
+pub(crate) async fn inherit_strategy_label_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    execution_id: i64,
+) -> Result<(), OnChainError> {
+    sqlx::query!(
+        r#"
+        UPDATE offchain_trades
+        SET strategy_label = (
+            SELECT ot.strategy_label
+            FROM trade_execution_links tel
+            JOIN onchain_trades ot ON ot.id = tel.trade_id
+            WHERE tel.execution_id = ?1
+            ORDER BY ot.id DESC
+            LIMIT 1
+        )
+        WHERE id = ?1
+        "#,
+        execution_id
+    )
+    .execute(&mut **sql_tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Block of the latest onchain trade that triggered the execution, if
+/// recorded.
+pub(crate) async fn find_execution_origin_block(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<Option<u64>, OnChainError> {
+    let block_number = sqlx::query_scalar!(
+        "SELECT block_number FROM offchain_trades WHERE id = ?1",
+        execution_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+
+    // block_number is constrained to be non-negative
+    Ok(block_number.and_then(|block| u64::try_from(block).ok()))
+}
+
+/// Number of executions that have not yet filled or failed.
+pub(crate) async fn count_open_executions(pool: &SqlitePool) -> Result<u64, OnChainError> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!: i64"
+        FROM offchain_trades
+        WHERE status IN ('PENDING', 'SUBMITTED')
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // COUNT(*) is never negative
+    Ok(u64::try_from(count).unwrap_or_default())
+}
+
 async fn query_by_status(
     pool: &SqlitePool,
     status_str: &str,
@@ -236,11 +457,66 @@ async fn query_by_symbol_status_and_broker(
 }
 
 impl OffchainExecution {
+    /// Key identifying this execution within a dedup window: two executions
+    /// with the same symbol, direction and shares created in the same
+    /// `window`-sized time bucket share a key.
+    pub(crate) fn idempotency_key(&self, now: DateTime<Utc>, window: Duration) -> String {
+        let window_secs = i64::try_from(window.as_secs()).unwrap_or(i64::MAX).max(1);
+        let bucket = now.timestamp().div_euclid(window_secs);
+
+        format!(
+            "{}:{}:{}:{bucket}",
+            self.symbol,
+            self.direction.as_str(),
+            self.shares
+        )
+    }
+
+    /// Saves the execution stamped with `idempotency_key`, or returns `None`
+    /// without saving anything if an execution already holds the key.
+    pub(crate) async fn save_with_idempotency_key_within_transaction(
+        &self,
+        sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        idempotency_key: &str,
+    ) -> Result<Option<i64>, OnChainError> {
+        let existing = sqlx::query_scalar!(
+            "SELECT id FROM offchain_trades WHERE idempotency_key = ?1",
+            idempotency_key
+        )
+        .fetch_optional(&mut **sql_tx)
+        .await?;
+
+        if existing.is_some() {
+            return Ok(None);
+        }
+
+        let execution_id = self.save_within_transaction(sql_tx).await?;
+
+        let result = sqlx::query!(
+            "UPDATE offchain_trades SET idempotency_key = ?1 WHERE id = ?2",
+            idempotency_key,
+            execution_id
+        )
+        .execute(&mut **sql_tx)
+        .await;
+
+        match result {
+            Ok(_) => Ok(Some(execution_id)),
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => Err(
+                OnChainError::DuplicateExecution(idempotency_key.to_string()),
+            ),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Saves the execution and assigns it the next nonce and the client order
+    /// key derived from it, see [`find_client_order_id`].
     pub(crate) async fn save_within_transaction(
         &self,
         sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     ) -> Result<i64, PersistenceError> {
-        self.state
+        let execution_id = self
+            .state
             .store(
                 sql_tx,
                 &self.symbol,
@@ -248,7 +524,183 @@ impl OffchainExecution {
                 self.direction,
                 self.broker,
             )
-            .await
+            .await?;
+
+        assign_client_order_key(sql_tx, execution_id).await?;
+
+        Ok(execution_id)
+    }
+
+    /// Saves the pending execution as a child order split from `parent_id`,
+    /// which may be in progress alongside its parent, and assigns it a nonce
+    /// and client order key like [`Self::save_within_transaction`].
+    pub(crate) async fn save_split_within_transaction(
+        &self,
+        sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        parent_id: i64,
+    ) -> Result<i64, PersistenceError> {
+        let symbol = self.symbol.to_string();
+        let shares = i64::from(self.shares.value());
+        let direction = self.direction.as_str();
+        let broker = self.broker.to_string();
+        let status = self.state.status().as_str();
+
+        let execution_id = sqlx::query!(
+            r#"
+            INSERT INTO offchain_trades (
+                symbol,
+                shares,
+                direction,
+                broker,
+                status,
+                split_parent_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            symbol,
+            shares,
+            direction,
+            broker,
+            status,
+            parent_id
+        )
+        .execute(&mut **sql_tx)
+        .await?
+        .last_insert_rowid();
+
+        assign_client_order_key(sql_tx, execution_id).await?;
+
+        Ok(execution_id)
+    }
+}
+
+/// Assigns a newly saved execution the next nonce and the client order key
+/// derived from it.
+async fn assign_client_order_key(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    execution_id: i64,
+) -> Result<(), PersistenceError> {
+    sqlx::query!(
+        r#"
+        UPDATE offchain_trades
+        SET nonce = (SELECT COALESCE(MAX(nonce), 0) + 1 FROM offchain_trades)
+        WHERE id = ?1
+        "#,
+        execution_id
+    )
+    .execute(&mut **sql_tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE offchain_trades
+        SET client_order_key = 'st0x-' || symbol || '-' || nonce
+        WHERE id = ?1
+        "#,
+        execution_id
+    )
+    .execute(&mut **sql_tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks a newly saved execution as a fractional-share order of exactly
+/// `quantity`, which its whole shares round up.
+pub(crate) async fn record_fractional_shares_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    execution_id: i64,
+    quantity: Decimal,
+) -> Result<(), OnChainError> {
+    let quantity = quantity.to_string();
+    sqlx::query!(
+        "UPDATE offchain_trades SET fractional_shares = ?1 WHERE id = ?2",
+        quantity,
+        execution_id
+    )
+    .execute(&mut **sql_tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the broker that holds an execution's order, which differs from
+/// the broker the execution was saved for when the order failed over.
+pub(crate) async fn record_order_broker_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    execution_id: i64,
+    broker: SupportedBroker,
+) -> Result<(), OnChainError> {
+    let broker = broker.to_string();
+    sqlx::query!(
+        "UPDATE offchain_trades SET broker = ?1 WHERE id = ?2",
+        broker,
+        execution_id
+    )
+    .execute(&mut **sql_tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Exact quantity of an execution to be placed as a fractional-share order,
+/// or `None` for a whole-share execution.
+pub(crate) async fn find_fractional_shares(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<Option<Decimal>, OnChainError> {
+    let fractional_shares = sqlx::query_scalar!(
+        "SELECT fractional_shares FROM offchain_trades WHERE id = ?1",
+        execution_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+
+    fractional_shares
+        .map(|quantity| {
+            parse_stored_decimal(&quantity).map_err(|e| sqlx::Error::Decode(Box::new(e)).into())
+        })
+        .transpose()
+}
+
+/// Client order id sent to the broker for an execution.
+///
+/// Built from the execution's persisted nonce rather than its symbol, shares
+/// and direction, so two otherwise identical executions never share an id and
+/// the id cannot change if the execution schema does. The id is stored as the
+/// execution's `client_order_key` and tags its broker order, which lets a
+/// retried placement find the order instead of placing it twice. `None` if
+/// the execution does not exist or predates nonces.
+pub(crate) async fn find_client_order_id(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<Option<String>, OnChainError> {
+    let client_order_key = sqlx::query_scalar!(
+        "SELECT client_order_key FROM offchain_trades WHERE id = ?1",
+        execution_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(client_order_key.flatten())
+}
+
+/// Finds the execution whose broker order is tagged with `client_order_key`.
+pub(crate) async fn find_execution_by_client_key(
+    pool: &SqlitePool,
+    client_order_key: &str,
+) -> Result<Option<OffchainExecution>, OnChainError> {
+    let execution_id = sqlx::query_scalar!(
+        r#"SELECT id AS "id!: i64" FROM offchain_trades WHERE client_order_key = ?1"#,
+        client_order_key
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    match execution_id {
+        Some(execution_id) => find_execution_by_id(pool, execution_id).await,
+        None => Ok(None),
     }
 }
 
@@ -256,9 +708,130 @@ impl OffchainExecution {
 mod tests {
     use super::*;
     use crate::test_utils::{OffchainExecutionBuilder, setup_test_db};
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
     use st0x_broker::OrderState;
 
+    #[tokio::test]
+    async fn test_identical_execution_in_same_bucket_is_rejected() {
+        let pool = setup_test_db().await;
+        let window = Duration::from_secs(60);
+        let now = DateTime::parse_from_rfc3339("2025-01-06T15:30:10Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // The first order has already filled when the same position fires again
+        let mut first = OffchainExecutionBuilder::new().build();
+        first.state = OrderState::Filled {
+            order_id: "ORD123".to_string(),
+            executed_at: now,
+            price_cents: 15025,
+            reported_price: None,
+        };
+        let key = first.idempotency_key(now, window);
+        let mut sql_tx = pool.begin().await.unwrap();
+        first
+            .save_with_idempotency_key_within_transaction(&mut sql_tx, &key)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let duplicate = OffchainExecutionBuilder::new().build();
+        let duplicate_key = duplicate.idempotency_key(now + chrono::Duration::seconds(20), window);
+        assert_eq!(duplicate_key, key);
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let duplicate_id = duplicate
+            .save_with_idempotency_key_within_transaction(&mut sql_tx, &duplicate_key)
+            .await
+            .unwrap();
+        assert_eq!(duplicate_id, None);
+        sql_tx.commit().await.unwrap();
+
+        // The next bucket is a new window and is accepted
+        let next_key = duplicate.idempotency_key(now + chrono::Duration::seconds(60), window);
+        assert_ne!(next_key, key);
+        assert_eq!(key, "AAPL:BUY:100:28936290");
+        let mut sql_tx = pool.begin().await.unwrap();
+        duplicate
+            .save_with_idempotency_key_within_transaction(&mut sql_tx, &next_key)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM offchain_trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_identical_executions_get_distinct_client_order_ids() {
+        let pool = setup_test_db().await;
+
+        // A rejected order followed by an identical retry
+        let mut rejected = OffchainExecutionBuilder::new().build();
+        rejected.state = OrderState::Failed {
+            failed_at: Utc::now(),
+            error_reason: Some("Rejected".to_string()),
+        };
+        let retry = OffchainExecutionBuilder::new().build();
+
+        let mut ids = Vec::new();
+        for execution in [rejected, retry] {
+            let mut sql_tx = pool.begin().await.unwrap();
+            let execution_id = execution
+                .save_within_transaction(&mut sql_tx)
+                .await
+                .unwrap();
+            sql_tx.commit().await.unwrap();
+
+            ids.push(
+                find_client_order_id(&pool, execution_id)
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            );
+        }
+
+        assert_eq!(
+            ids,
+            vec!["st0x-AAPL-1".to_string(), "st0x-AAPL-2".to_string()]
+        );
+        assert_eq!(find_client_order_id(&pool, 999).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_find_execution_by_client_key() {
+        let pool = setup_test_db().await;
+        let execution = OffchainExecutionBuilder::new().build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let client_order_key = find_client_order_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let found = find_execution_by_client_key(&pool, &client_order_key)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, Some(execution_id));
+        assert_eq!(found.symbol, execution.symbol);
+
+        assert_eq!(
+            find_execution_by_client_key(&pool, "st0x-AAPL-999")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
     #[tokio::test]
     async fn test_offchain_execution_save_and_find() {
         let pool = setup_test_db().await;
@@ -304,6 +877,7 @@ mod tests {
                 executed_at: Utc::now(),
                 order_id: "1004055538123".to_string(),
                 price_cents: 15025,
+                reported_price: None,
             },
         };
 
@@ -369,6 +943,96 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_list_executions_filters_and_pages() {
+        let pool = setup_test_db().await;
+        let filled_at = Utc.with_ymd_and_hms(2025, 1, 15, 14, 30, 0).unwrap();
+
+        let executions = [
+            ("AAPL", OrderState::Pending),
+            (
+                "AAPL",
+                OrderState::Filled {
+                    executed_at: filled_at,
+                    order_id: "1".to_string(),
+                    price_cents: 15025,
+                    reported_price: None,
+                },
+            ),
+            (
+                "MSFT",
+                OrderState::Failed {
+                    failed_at: filled_at + chrono::Duration::days(1),
+                    error_reason: None,
+                },
+            ),
+        ];
+        for (symbol, state) in executions {
+            let mut sql_tx = pool.begin().await.unwrap();
+            OffchainExecution {
+                id: None,
+                symbol: Symbol::new(symbol).unwrap(),
+                shares: Shares::new(10).unwrap(),
+                direction: Direction::Buy,
+                broker: SupportedBroker::Schwab,
+                state,
+            }
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+            sql_tx.commit().await.unwrap();
+        }
+
+        let listed = |executions: Vec<OffchainExecution>| {
+            executions
+                .into_iter()
+                .map(|execution| (execution.symbol.to_string(), execution.state.status()))
+                .collect::<Vec<_>>()
+        };
+
+        let all = list_executions(&pool, &ExecutionFilter::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            listed(all),
+            vec![
+                ("MSFT".to_string(), OrderStatus::Failed),
+                ("AAPL".to_string(), OrderStatus::Filled),
+                ("AAPL".to_string(), OrderStatus::Pending),
+            ]
+        );
+
+        let second_page = list_executions(&pool, &ExecutionFilter::default(), 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            listed(second_page),
+            vec![("AAPL".to_string(), OrderStatus::Filled)]
+        );
+
+        let filter = ExecutionFilter {
+            symbol: Some(Symbol::new("AAPL").unwrap()),
+            status: Some(OrderStatus::Pending),
+            ..ExecutionFilter::default()
+        };
+        let pending_aapl = list_executions(&pool, &filter, 10, 0).await.unwrap();
+        assert_eq!(
+            listed(pending_aapl),
+            vec![("AAPL".to_string(), OrderStatus::Pending)]
+        );
+
+        let filter = ExecutionFilter {
+            from: Some(filled_at),
+            to: Some(filled_at + chrono::Duration::hours(1)),
+            ..ExecutionFilter::default()
+        };
+        let in_range = list_executions(&pool, &filter, 10, 0).await.unwrap();
+        assert_eq!(
+            listed(in_range),
+            vec![("AAPL".to_string(), OrderStatus::Filled)]
+        );
+    }
+
     #[tokio::test]
     async fn test_database_tracks_different_brokers() {
         let pool = setup_test_db().await;
@@ -0,0 +1,461 @@
+//! Order-size limits relative to a symbol's recent liquidity and the broker's
+//! per-order value limit.
+//!
+//! When enabled, an execution may not exceed a configured fraction of the
+//! symbol's average daily volume (ADV) as reported by the broker. Oversized
+//! positions are either split, executing up to the limit and leaving the
+//! remainder accumulated for a later execution, or deferred entirely. Like
+//! blackout deferrals, anything left behind is picked up by the periodic
+//! position check.
+//!
+//! Brokers also reject orders above a notional value, so executions can be
+//! capped at a maximum order value as well. The cap is converted to shares at
+//! the broker's current quote, and an execution above it is always split into
+//! compliant orders, all created at once, since waiting would never make it
+//! fit.
+//!
+//! The same quotes back the minimum spread check in [`crate::offchain::spread`].
+//!
+//! Executions worth less than a minimum notional at the onchain price are
+//! held back too, since the broker rejects such dust orders. The shares stay
+//! accumulated until the position is worth executing.
+//!
+//! The limits of a round also carry the net position limits of
+//! [`crate::offchain::position_limit`], checked as each execution is created.
+
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::{debug, info, warn};
+
+use st0x_broker::{Broker, Direction, Symbol};
+
+use crate::offchain::position_limit::PositionLimits;
+use crate::offchain::spread::{SpreadCheck, SpreadPolicy};
+
+/// What to do with an execution larger than the liquidity limit.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OversizeAction {
+    /// Execute up to the limit now and keep the rest accumulated
+    #[default]
+    Split,
+    /// Execute nothing until the whole position fits within the limit
+    Defer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LiquidityPolicy {
+    pub(crate) max_adv_fraction: Option<f64>,
+    pub(crate) action: OversizeAction,
+    pub(crate) max_order_value_cents: Option<u64>,
+    pub(crate) spread: Option<SpreadPolicy>,
+    pub(crate) min_trade_notional_cents: Option<u64>,
+}
+
+/// Parses a fraction of ADV in `(0, 1]` for the `--max-adv-fraction` flag.
+pub(crate) fn parse_adv_fraction(value: &str) -> Result<f64, String> {
+    let fraction: f64 = value
+        .parse()
+        .map_err(|e| format!("Invalid ADV fraction '{value}': {e}"))?;
+
+    if fraction > 0.0 && fraction <= 1.0 {
+        Ok(fraction)
+    } else {
+        Err(format!("ADV fraction must be in (0, 1], got {fraction}"))
+    }
+}
+
+/// Parses a maximum order value in dollars, e.g. `250000`, into whole cents
+/// for the `--max-order-value` flag. Fractions of a cent are dropped so the
+/// cap is never exceeded.
+pub(crate) fn parse_max_order_value(value: &str) -> Result<u64, String> {
+    let dollars = Decimal::from_str(value.trim())
+        .map_err(|e| format!("Invalid max order value '{value}': {e}"))?;
+
+    dollars
+        .checked_mul(Decimal::ONE_HUNDRED)
+        .map(|cents| cents.trunc())
+        .and_then(|cents| cents.to_u64())
+        .filter(|cents| *cents > 0)
+        .ok_or_else(|| format!("Max order value must be at least one cent, got {value}"))
+}
+
+/// Per-symbol share limits resolved for one round of executions.
+///
+/// Symbols without an entry are unrestricted, which is also the default when
+/// no [`LiquidityPolicy`] is configured.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct LiquidityLimits {
+    action: OversizeAction,
+    max_shares: HashMap<Symbol, u64>,
+    max_order_shares: HashMap<Symbol, u64>,
+    spread: Option<SpreadCheck>,
+    min_trade_notional_cents: Option<u64>,
+    position_limits: PositionLimits,
+}
+
+impl LiquidityLimits {
+    pub(crate) fn new(
+        action: OversizeAction,
+        max_shares: impl IntoIterator<Item = (Symbol, u64)>,
+    ) -> Self {
+        Self {
+            action,
+            max_shares: max_shares.into_iter().collect(),
+            max_order_shares: HashMap::new(),
+            spread: None,
+            min_trade_notional_cents: None,
+            position_limits: PositionLimits::default(),
+        }
+    }
+
+    /// Adds per-symbol share limits derived from the maximum order value.
+    /// Executions above these are always split.
+    #[must_use]
+    pub(crate) fn with_max_order_shares(
+        mut self,
+        max_order_shares: impl IntoIterator<Item = (Symbol, u64)>,
+    ) -> Self {
+        self.max_order_shares = max_order_shares.into_iter().collect();
+        self
+    }
+
+    /// Adds the minimum spread check, deferring executions that fail it.
+    #[must_use]
+    pub(crate) fn with_spread_check(mut self, spread: SpreadCheck) -> Self {
+        self.spread = Some(spread);
+        self
+    }
+
+    /// Whether hedging `symbol` in `direction` against onchain fills at an
+    /// average of `onchain_price` captures the minimum spread, if one is set.
+    pub(crate) fn is_spread_viable(
+        &self,
+        symbol: &Symbol,
+        direction: Direction,
+        onchain_price: Option<f64>,
+    ) -> bool {
+        self.spread
+            .as_ref()
+            .is_none_or(|spread| spread.is_viable(symbol, direction, onchain_price))
+    }
+
+    pub(crate) const fn checks_spread(&self) -> bool {
+        self.spread.is_some()
+    }
+
+    /// Adds the minimum notional, deferring executions worth less.
+    #[must_use]
+    pub(crate) const fn with_min_trade_notional(mut self, min_trade_notional_cents: u64) -> Self {
+        self.min_trade_notional_cents = Some(min_trade_notional_cents);
+        self
+    }
+
+    /// Adds the net position limits executions have to stay within.
+    #[must_use]
+    pub(crate) fn with_position_limits(mut self, position_limits: PositionLimits) -> Self {
+        self.position_limits = position_limits;
+        self
+    }
+
+    pub(crate) const fn position_limits(&self) -> &PositionLimits {
+        &self.position_limits
+    }
+
+    pub(crate) const fn checks_trade_notional(&self) -> bool {
+        self.min_trade_notional_cents.is_some()
+    }
+
+    /// Whether `shares` of `symbol` at an onchain price of `onchain_price`
+    /// dollars are worth at least the minimum notional, if one is set. An
+    /// execution without a known onchain price cannot be valued and goes
+    /// ahead.
+    pub(crate) fn is_trade_notional_viable(
+        &self,
+        symbol: &Symbol,
+        shares: Decimal,
+        onchain_price: Option<f64>,
+    ) -> bool {
+        let (Some(min_cents), Some(price)) = (self.min_trade_notional_cents, onchain_price) else {
+            return true;
+        };
+
+        let notional_cents = shares.to_f64().unwrap_or(f64::MAX) * price * 100.0;
+        let min_cents = min_cents.to_f64().unwrap_or(f64::MAX);
+        if notional_cents >= min_cents {
+            return true;
+        }
+
+        debug!(
+            symbol = %symbol,
+            shares = %shares,
+            notional_cents,
+            min_cents,
+            "Execution below minimum notional, keeping it accumulated"
+        );
+
+        false
+    }
+
+    /// Orders, in shares, that may be executed now for a ready position of
+    /// `shares`, or none if the execution has to wait. A position above the
+    /// per-order value limit is split into compliant orders placed together.
+    pub(crate) fn order_sizes(&self, symbol: &Symbol, shares: u64) -> Vec<u64> {
+        let Some(allowed) = self.adv_allowed_shares(symbol, shares) else {
+            return vec![];
+        };
+
+        let Some(&max_order_shares) = self.max_order_shares.get(symbol) else {
+            return vec![allowed];
+        };
+
+        if allowed <= max_order_shares {
+            return vec![allowed];
+        }
+
+        if max_order_shares == 0 {
+            return vec![];
+        }
+
+        info!(
+            symbol = %symbol,
+            shares = allowed,
+            max_order_shares,
+            "Execution exceeds per-order value limit, splitting"
+        );
+
+        let mut sizes = vec![
+            max_order_shares;
+            usize::try_from(allowed / max_order_shares).unwrap_or(usize::MAX)
+        ];
+        if allowed % max_order_shares > 0 {
+            sizes.push(allowed % max_order_shares);
+        }
+
+        sizes
+    }
+
+    fn adv_allowed_shares(&self, symbol: &Symbol, shares: u64) -> Option<u64> {
+        let Some(&max_shares) = self.max_shares.get(symbol) else {
+            return Some(shares);
+        };
+
+        if shares <= max_shares {
+            return Some(shares);
+        }
+
+        let allowed = match self.action {
+            OversizeAction::Split => Some(max_shares).filter(|max| *max > 0),
+            OversizeAction::Defer => None,
+        };
+
+        info!(
+            symbol = %symbol,
+            shares,
+            max_shares,
+            action = ?self.action,
+            allowed_shares = ?allowed,
+            "Execution exceeds liquidity limit"
+        );
+
+        allowed
+    }
+}
+
+/// Largest order, in whole shares, allowed for a symbol trading `adv` shares
+/// per day. Zero (nothing executes) if the limit cannot be represented.
+pub(crate) fn max_shares_for_adv(adv: u64, max_adv_fraction: f64) -> u64 {
+    adv.to_f64()
+        .and_then(|adv| (adv * max_adv_fraction).floor().to_u64())
+        .unwrap_or(0)
+}
+
+/// Largest order, in whole shares, whose value at `price_cents` stays within
+/// `max_order_value_cents`. Zero (nothing executes) without a usable price.
+pub(crate) fn max_shares_for_value(max_order_value_cents: u64, price_cents: u64) -> u64 {
+    max_order_value_cents.checked_div(price_cents).unwrap_or(0)
+}
+
+/// Looks up the ADV and current quote of each symbol as needed by `policy`
+/// and derives its share limits.
+///
+/// A symbol whose ADV or quote cannot be fetched gets a limit of zero, so its
+/// executions wait rather than go out unchecked.
+pub(crate) async fn fetch_liquidity_limits<B: Broker>(
+    broker: &B,
+    policy: Option<&LiquidityPolicy>,
+    symbols: impl IntoIterator<Item = Symbol>,
+) -> LiquidityLimits {
+    let Some(policy) = policy else {
+        return LiquidityLimits::default();
+    };
+
+    let mut max_shares = Vec::new();
+    let mut max_order_shares = Vec::new();
+    let mut quotes = Vec::new();
+    for symbol in symbols {
+        if let Some(max_adv_fraction) = policy.max_adv_fraction {
+            let limit = match broker.get_adv(&symbol).await {
+                Ok(adv) => max_shares_for_adv(adv, max_adv_fraction),
+                Err(e) => {
+                    warn!(
+                        "Failed to fetch average daily volume for {symbol}, deferring execution: {e}"
+                    );
+                    0
+                }
+            };
+            max_shares.push((symbol.clone(), limit));
+        }
+
+        if policy.max_order_value_cents.is_none() && policy.spread.is_none() {
+            continue;
+        }
+
+        let quote = match broker.get_quote(&symbol).await {
+            Ok(quote) => Some(quote),
+            Err(e) => {
+                warn!("Failed to fetch quote for {symbol}, deferring execution: {e}");
+                None
+            }
+        };
+
+        if let Some(max_order_value_cents) = policy.max_order_value_cents {
+            // Sized at the higher side of the quote so neither a buy nor a
+            // sell can exceed the cap
+            let limit = quote.as_ref().map_or(0, |quote| {
+                max_shares_for_value(
+                    max_order_value_cents,
+                    quote.ask_price_cents.max(quote.bid_price_cents),
+                )
+            });
+            max_order_shares.push((symbol, limit));
+        }
+
+        // A symbol without a quote fails the spread check
+        quotes.extend(quote);
+    }
+
+    let limits =
+        LiquidityLimits::new(policy.action, max_shares).with_max_order_shares(max_order_shares);
+
+    let limits = match policy.min_trade_notional_cents {
+        Some(min_trade_notional_cents) => limits.with_min_trade_notional(min_trade_notional_cents),
+        None => limits,
+    };
+
+    match policy.spread {
+        Some(spread) => limits.with_spread_check(SpreadCheck::new(spread, quotes)),
+        None => limits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use st0x_broker::MockBroker;
+    use st0x_broker::mock::MOCK_AVERAGE_DAILY_VOLUME;
+
+    fn limits(action: OversizeAction, symbol: &Symbol, max_shares: u64) -> LiquidityLimits {
+        LiquidityLimits::new(action, [(symbol.clone(), max_shares)])
+    }
+
+    #[test]
+    fn test_order_within_adv_fraction_is_unchanged() {
+        let aapl = Symbol::new("AAPL").unwrap();
+        let max_shares = max_shares_for_adv(1_000_000, 0.01);
+        assert_eq!(max_shares, 10_000);
+
+        for action in [OversizeAction::Split, OversizeAction::Defer] {
+            let limits = limits(action, &aapl, max_shares);
+            assert_eq!(limits.order_sizes(&aapl, 500), vec![500]);
+            assert_eq!(limits.order_sizes(&aapl, 10_000), vec![10_000]);
+        }
+    }
+
+    #[test]
+    fn test_order_beyond_adv_fraction_is_split_or_deferred() {
+        let aapl = Symbol::new("AAPL").unwrap();
+        let max_shares = max_shares_for_adv(1_000_000, 0.01);
+
+        let split = limits(OversizeAction::Split, &aapl, max_shares);
+        assert_eq!(split.order_sizes(&aapl, 25_000), vec![10_000]);
+
+        let defer = limits(OversizeAction::Defer, &aapl, max_shares);
+        assert!(defer.order_sizes(&aapl, 25_000).is_empty());
+
+        // Too illiquid for even one share: splitting cannot make progress
+        let illiquid = limits(OversizeAction::Split, &aapl, max_shares_for_adv(50, 0.01));
+        assert!(illiquid.order_sizes(&aapl, 1).is_empty());
+
+        // Other symbols are not restricted
+        let msft = Symbol::new("MSFT").unwrap();
+        assert_eq!(defer.order_sizes(&msft, 25_000), vec![25_000]);
+    }
+
+    #[test]
+    fn test_parse_adv_fraction() {
+        assert!((parse_adv_fraction("0.05").unwrap() - 0.05).abs() < f64::EPSILON);
+        assert!((parse_adv_fraction("1").unwrap() - 1.0).abs() < f64::EPSILON);
+        assert!(parse_adv_fraction("0").is_err());
+        assert!(parse_adv_fraction("1.5").is_err());
+        assert!(parse_adv_fraction("abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_max_order_value() {
+        assert_eq!(parse_max_order_value("250000").unwrap(), 25_000_000);
+        assert_eq!(parse_max_order_value("1000.559").unwrap(), 100_055);
+        assert!(parse_max_order_value("0").is_err());
+        assert!(parse_max_order_value("-5").is_err());
+        assert!(parse_max_order_value("abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_order_value_limits_from_quote() {
+        let aapl = Symbol::new("AAPL").unwrap();
+        let policy = LiquidityPolicy {
+            max_adv_fraction: None,
+            action: OversizeAction::Defer,
+            max_order_value_cents: Some(50_000),
+            spread: None,
+            min_trade_notional_cents: None,
+        };
+
+        // The mock quote asks $100.05, so a $500 order fits 4 shares
+        let limits =
+            fetch_liquidity_limits(&MockBroker::new(), Some(&policy), [aapl.clone()]).await;
+        assert_eq!(limits.order_sizes(&aapl, 3), vec![3]);
+        assert_eq!(limits.order_sizes(&aapl, 10), vec![4, 4, 2]);
+
+        let failing = MockBroker::with_failure("Quote unavailable");
+        let limits = fetch_liquidity_limits(&failing, Some(&policy), [aapl.clone()]).await;
+        assert!(limits.order_sizes(&aapl, 1).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_liquidity_limits() {
+        let aapl = Symbol::new("AAPL").unwrap();
+        let policy = LiquidityPolicy {
+            max_adv_fraction: Some(0.01),
+            action: OversizeAction::Split,
+            max_order_value_cents: None,
+            spread: None,
+            min_trade_notional_cents: None,
+        };
+
+        let disabled = fetch_liquidity_limits(&MockBroker::new(), None, [aapl.clone()]).await;
+        assert_eq!(disabled, LiquidityLimits::default());
+
+        let limits =
+            fetch_liquidity_limits(&MockBroker::new(), Some(&policy), [aapl.clone()]).await;
+        assert_eq!(
+            limits.order_sizes(&aapl, u64::MAX),
+            vec![MOCK_AVERAGE_DAILY_VOLUME / 100]
+        );
+
+        let failing = MockBroker::with_failure("ADV unavailable");
+        let limits = fetch_liquidity_limits(&failing, Some(&policy), [aapl.clone()]).await;
+        assert!(limits.order_sizes(&aapl, 1).is_empty());
+    }
+}
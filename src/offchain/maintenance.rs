@@ -0,0 +1,184 @@
+//! Scheduled broker maintenance windows.
+//!
+//! Schwab takes its API down for scheduled maintenance while the market can
+//! still be open, failing every call in the meantime. Maintenance windows are
+//! recurring time-of-day ranges (in US Eastern time), either daily or on one
+//! weekday, during which no orders are placed and order statuses are not
+//! polled. Trades keep accumulating; the periodic position check places them
+//! once the window has passed.
+
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use chrono_tz::US::Eastern;
+use std::str::FromStr;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MaintenanceParseError {
+    #[error("Invalid maintenance window '{0}', expected [DAY ]HH:MM..HH:MM")]
+    InvalidFormat(String),
+    #[error("Invalid maintenance time '{0}', expected HH:MM")]
+    InvalidTime(String),
+    #[error("Invalid maintenance weekday '{0}', expected e.g. Sat")]
+    InvalidWeekday(String),
+    #[error("Maintenance window cannot start and end at {0}")]
+    Empty(NaiveTime),
+}
+
+/// A recurring maintenance window, parsed from `[DAY ]HH:MM..HH:MM`.
+///
+/// A window ending at or before its start time runs past midnight into the
+/// next day. Examples: `00:00..00:30` (every night), `Sat 22:00..06:00`
+/// (Saturday 10pm to Sunday 6am).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    weekday: Option<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl MaintenanceWindow {
+    fn covers(&self, weekday: Weekday, time: NaiveTime) -> bool {
+        let starts_on = |day: Weekday| self.weekday.is_none_or(|start_day| start_day == day);
+
+        if self.start < self.end {
+            starts_on(weekday) && (self.start..self.end).contains(&time)
+        } else {
+            (starts_on(weekday) && time >= self.start)
+                || (starts_on(weekday.pred()) && time < self.end)
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, MaintenanceParseError> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| MaintenanceParseError::InvalidTime(value.to_string()))
+}
+
+impl FromStr for MaintenanceWindow {
+    type Err = MaintenanceParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (weekday, range) = match value.trim().split_once(' ') {
+            Some((weekday, range)) => {
+                let weekday = weekday
+                    .parse::<Weekday>()
+                    .map_err(|_| MaintenanceParseError::InvalidWeekday(weekday.to_string()))?;
+                (Some(weekday), range)
+            }
+            None => (None, value),
+        };
+
+        let (start, end) = range
+            .split_once("..")
+            .ok_or_else(|| MaintenanceParseError::InvalidFormat(value.to_string()))?;
+        let (start, end) = (parse_time(start)?, parse_time(end)?);
+
+        if start == end {
+            return Err(MaintenanceParseError::Empty(start));
+        }
+
+        Ok(Self {
+            weekday,
+            start,
+            end,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct MaintenanceCalendar {
+    windows: Vec<MaintenanceWindow>,
+}
+
+impl MaintenanceCalendar {
+    pub(crate) fn new(windows: Vec<MaintenanceWindow>) -> Self {
+        Self { windows }
+    }
+
+    /// Whether the broker is down for scheduled maintenance at `now`.
+    pub(crate) fn is_under_maintenance_at(&self, now: DateTime<Utc>) -> bool {
+        let eastern = now.with_timezone(&Eastern);
+
+        self.windows
+            .iter()
+            .any(|window| window.covers(eastern.weekday(), eastern.time()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_parse_daily_and_weekly_windows() {
+        assert_eq!(
+            "00:00..00:30".parse::<MaintenanceWindow>().unwrap(),
+            MaintenanceWindow {
+                weekday: None,
+                start: time("00:00"),
+                end: time("00:30"),
+            }
+        );
+        assert_eq!(
+            "Sat 22:00..06:00".parse::<MaintenanceWindow>().unwrap(),
+            MaintenanceWindow {
+                weekday: Some(Weekday::Sat),
+                start: time("22:00"),
+                end: time("06:00"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "00:00".parse::<MaintenanceWindow>().unwrap_err(),
+            MaintenanceParseError::InvalidFormat(_)
+        ));
+        assert!(matches!(
+            "25:00..01:00".parse::<MaintenanceWindow>().unwrap_err(),
+            MaintenanceParseError::InvalidTime(_)
+        ));
+        assert!(matches!(
+            "Someday 01:00..02:00"
+                .parse::<MaintenanceWindow>()
+                .unwrap_err(),
+            MaintenanceParseError::InvalidWeekday(_)
+        ));
+        assert_eq!(
+            "01:00..01:00".parse::<MaintenanceWindow>().unwrap_err(),
+            MaintenanceParseError::Empty(time("01:00"))
+        );
+    }
+
+    #[test]
+    fn test_is_under_maintenance_in_eastern_time() {
+        let calendar = MaintenanceCalendar::new(vec!["00:00..00:30".parse().unwrap()]);
+
+        // 04:15 UTC is 00:15 in New York during daylight saving time
+        let inside = Utc.with_ymd_and_hms(2025, 10, 28, 4, 15, 0).unwrap();
+        assert!(calendar.is_under_maintenance_at(inside));
+
+        let end = Utc.with_ymd_and_hms(2025, 10, 28, 4, 30, 0).unwrap();
+        assert!(!calendar.is_under_maintenance_at(end));
+    }
+
+    #[test]
+    fn test_weekly_window_runs_past_midnight() {
+        let calendar = MaintenanceCalendar::new(vec!["Sat 22:00..06:00".parse().unwrap()]);
+
+        // Saturday 23:00 and Sunday 05:00 in New York
+        let saturday_night = Utc.with_ymd_and_hms(2025, 11, 2, 3, 0, 0).unwrap();
+        let sunday_morning = Utc.with_ymd_and_hms(2025, 11, 2, 10, 0, 0).unwrap();
+        assert!(calendar.is_under_maintenance_at(saturday_night));
+        assert!(calendar.is_under_maintenance_at(sunday_morning));
+
+        // Friday 23:00 in New York is outside the Saturday window
+        let friday_night = Utc.with_ymd_and_hms(2025, 11, 1, 3, 0, 0).unwrap();
+        assert!(!calendar.is_under_maintenance_at(friday_night));
+    }
+}
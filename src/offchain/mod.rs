@@ -1,2 +1,12 @@
+pub mod blackout;
+pub mod canary;
+pub mod end_of_day;
 pub mod execution;
+pub mod liquidity;
+pub mod maintenance;
+pub mod open_executions;
 pub mod order_poller;
+pub mod position_limit;
+pub mod reconcile;
+pub mod spread;
+pub mod trade_side;
@@ -0,0 +1,94 @@
+//! Caps how many executions may be open at the broker at once.
+//!
+//! An execution is open until the order poller sees it filled or failed.
+//! While the open count is at the cap, the queue processor stops taking events
+//! off the queue and the periodic position check creates no executions, so
+//! onchain events keep accumulating in the queue until enough executions
+//! reach a terminal state.
+
+use sqlx::SqlitePool;
+use std::num::NonZeroU64;
+use tracing::debug;
+
+use super::execution::count_open_executions;
+use crate::error::OnChainError;
+use crate::stats::Stats;
+
+/// Whether new executions are paused because `cap` open executions are
+/// already in flight. Records the current open count in `stats` either way.
+pub(crate) async fn is_at_open_execution_cap(
+    pool: &SqlitePool,
+    cap: Option<NonZeroU64>,
+    stats: &Stats,
+) -> Result<bool, OnChainError> {
+    let open = count_open_executions(pool).await?;
+    stats.record_open_executions(open);
+
+    let at_cap = cap.is_some_and(|cap| open >= cap.get());
+    if at_cap {
+        debug!("{open} executions open, pausing new executions until one completes");
+    }
+
+    Ok(at_cap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{OffchainExecutionBuilder, setup_test_db};
+    use chrono::Utc;
+    use st0x_broker::{OrderState, Symbol};
+
+    async fn save_pending_execution(pool: &SqlitePool, symbol: &str) -> i64 {
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.symbol = Symbol::new(symbol).unwrap();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_pauses_at_cap_and_resumes_below_it() {
+        let pool = setup_test_db().await;
+        let stats = Stats::default();
+        let cap = NonZeroU64::new(2);
+
+        save_pending_execution(&pool, "AAPL").await;
+        assert!(!is_at_open_execution_cap(&pool, cap, &stats).await.unwrap());
+        assert_eq!(stats.snapshot().open_executions, 1);
+
+        let filled_id = save_pending_execution(&pool, "MSFT").await;
+        assert!(is_at_open_execution_cap(&pool, cap, &stats).await.unwrap());
+        assert_eq!(stats.snapshot().open_executions, 2);
+
+        let filled = OrderState::Filled {
+            order_id: "ORD123".to_string(),
+            executed_at: Utc::now(),
+            price_cents: 15025,
+            reported_price: None,
+        };
+        let mut sql_tx = pool.begin().await.unwrap();
+        filled.store_update(&mut sql_tx, filled_id).await.unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert!(!is_at_open_execution_cap(&pool, cap, &stats).await.unwrap());
+        assert_eq!(stats.snapshot().open_executions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_never_pauses_without_cap() {
+        let pool = setup_test_db().await;
+        let stats = Stats::default();
+
+        save_pending_execution(&pool, "AAPL").await;
+        save_pending_execution(&pool, "MSFT").await;
+
+        assert!(!is_at_open_execution_cap(&pool, None, &stats).await.unwrap());
+        assert_eq!(stats.snapshot().open_executions, 2);
+    }
+}
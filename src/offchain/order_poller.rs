@@ -1,21 +1,50 @@
+use itertools::Itertools;
 use num_traits::ToPrimitive;
 use rand::Rng;
 use sqlx::SqlitePool;
+use std::cmp::Ordering;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::{Interval, interval};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use super::execution::{
-    OffchainExecution, find_execution_by_id, find_executions_by_symbol_status_and_broker,
+    OffchainExecution, find_execution_by_id, find_execution_notional,
+    find_executions_by_symbol_status_and_broker, find_submitted_executions_placed_before,
 };
+use super::maintenance::MaintenanceCalendar;
+use crate::db_retry::{LockedRetryPolicy, retry_when_locked};
 use crate::error::{OnChainError, OrderPollingError};
 use crate::lock::{clear_execution_lease, clear_pending_execution_id};
-use st0x_broker::{Broker, OrderState, OrderStatus, PersistenceError};
+use crate::metrics::Metrics;
+use crate::order_notifier::{OrderNotifier, WebhookNotifier};
+use crate::queue::count_unprocessed;
+use crate::stats::Stats;
+use crate::trade_feed::{TradeFeed, TradeFeedEvent};
+use st0x_broker::{
+    Broker, OrderState, OrderStatus, OrderType, OrderUpdate, PersistenceError, Symbol,
+    rate_limit_delay,
+};
 
 #[derive(Debug, Clone)]
 pub struct OrderPollerConfig {
     pub polling_interval: Duration,
     pub max_jitter: Duration,
+    /// Orders with an estimated notional (USD) below this are treated as dust
+    /// and only polled every `dust_polling_every` cycles.
+    pub dust_notional_threshold: Option<f64>,
+    pub dust_polling_every: u64,
+    /// Orders still SUBMITTED this long after they were placed are marked
+    /// FAILED, releasing their symbol. Never if `None`.
+    pub max_order_age: Option<Duration>,
+    /// Retries for execution state updates on a locked database.
+    pub locked_retry: LockedRetryPolicy,
+    /// Feed that filled and failed executions are published to.
+    pub(crate) trade_feed: TradeFeed,
+    /// Broker maintenance windows during which no statuses are polled.
+    pub(crate) maintenance: MaintenanceCalendar,
+    /// Notified of orders that filled or failed.
+    pub(crate) notifier: WebhookNotifier,
 }
 
 impl Default for OrderPollerConfig {
@@ -23,6 +52,13 @@ impl Default for OrderPollerConfig {
         Self {
             polling_interval: Duration::from_secs(15),
             max_jitter: Duration::from_secs(5),
+            dust_notional_threshold: None,
+            dust_polling_every: 1,
+            max_order_age: None,
+            locked_retry: LockedRetryPolicy::default(),
+            trade_feed: TradeFeed::default(),
+            maintenance: MaintenanceCalendar::default(),
+            notifier: WebhookNotifier::default(),
         }
     }
 }
@@ -32,10 +68,17 @@ pub struct OrderStatusPoller<B: Broker> {
     pool: SqlitePool,
     interval: Interval,
     broker: B,
+    stats: Arc<Stats>,
+    cycle: u64,
 }
 
 impl<B: Broker> OrderStatusPoller<B> {
-    pub fn new(config: OrderPollerConfig, pool: SqlitePool, broker: B) -> Self {
+    pub(crate) fn new(
+        config: OrderPollerConfig,
+        pool: SqlitePool,
+        broker: B,
+        stats: Arc<Stats>,
+    ) -> Self {
         let interval = interval(config.polling_interval);
 
         Self {
@@ -43,6 +86,8 @@ impl<B: Broker> OrderStatusPoller<B> {
             pool,
             interval,
             broker,
+            stats,
+            cycle: 0,
         }
     }
 
@@ -58,36 +103,94 @@ impl<B: Broker> OrderStatusPoller<B> {
             if let Err(e) = self.poll_pending_orders().await {
                 error!("Polling cycle failed: {e}");
             }
+
+            // Checked after polling so that an order which did fill or fail
+            // has its real status stored first
+            if let Err(e) = self.fail_stale_orders().await {
+                error!("Failed to expire stale orders: {e}");
+            }
         }
     }
 
     #[tracing::instrument(skip(self), level = tracing::Level::DEBUG)]
-    async fn poll_pending_orders(&self) -> Result<(), OrderPollingError> {
+    async fn poll_pending_orders(&mut self) -> Result<(), OrderPollingError> {
         debug!("Starting polling cycle for submitted orders");
 
-        let broker = self.broker.to_supported_broker();
-        let submitted_executions = find_executions_by_symbol_status_and_broker(
-            &self.pool,
-            None,
-            OrderStatus::Submitted,
-            Some(broker),
-        )
-        .await?;
+        match count_unprocessed(&self.pool).await {
+            Ok(depth) => Metrics::global().set_queue_depth(u64::try_from(depth).unwrap_or(0)),
+            Err(e) => error!("Failed to count unprocessed events: {e}"),
+        }
+
+        if self
+            .config
+            .maintenance
+            .is_under_maintenance_at(chrono::Utc::now())
+        {
+            debug!("Broker is in a maintenance window, skipping polling cycle");
+            return Ok(());
+        }
+
+        // Orders that failed over are held by another broker than the primary
+        let mut submitted_executions = Vec::new();
+        for broker in self.broker.order_brokers() {
+            submitted_executions.extend(
+                find_executions_by_symbol_status_and_broker(
+                    &self.pool,
+                    None,
+                    OrderStatus::Submitted,
+                    Some(broker),
+                )
+                .await?,
+            );
+        }
+
+        Metrics::global().set_pending_orders(submitted_executions.len() as u64);
 
         if submitted_executions.is_empty() {
             debug!("No submitted orders to poll");
             return Ok(());
         }
 
-        info!("Polling {} submitted orders", submitted_executions.len());
-
+        let mut with_notional = Vec::with_capacity(submitted_executions.len());
         for execution in submitted_executions {
+            let notional = match execution.id {
+                Some(execution_id) => find_execution_notional(&self.pool, execution_id).await?,
+                None => None,
+            };
+            with_notional.push((execution, notional));
+        }
+
+        let include_dust = self.cycle == 0;
+        self.cycle = (self.cycle + 1) % self.config.dust_polling_every.max(1);
+
+        let executions = prioritize_by_notional(
+            with_notional,
+            self.config.dust_notional_threshold,
+            include_dust,
+        );
+
+        info!("Polling {} submitted orders", executions.len());
+
+        for execution in executions {
             let Some(execution_id) = execution.id else {
                 continue;
             };
 
-            if let Err(e) = self.poll_execution_status(&execution).await {
-                error!("Failed to poll execution {execution_id}: {e}");
+            // A rate limited poll is retried once the broker allows it
+            loop {
+                match self.poll_execution_status(&execution).await {
+                    Ok(()) => break,
+                    Err(OrderPollingError::RateLimited(delay)) => {
+                        warn!(
+                            "Broker rate limited polling execution {execution_id}, retrying in {delay:?}"
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    Err(e) => {
+                        error!("Failed to poll execution {execution_id}: {e}");
+                        break;
+                    }
+                }
             }
 
             self.add_jittered_delay().await;
@@ -97,6 +200,57 @@ impl<B: Broker> OrderStatusPoller<B> {
         Ok(())
     }
 
+    /// Marks orders that have been SUBMITTED for longer than the configured
+    /// maximum order age as FAILED, so the accumulator can execute their
+    /// shares again. Nothing is expired during a maintenance window, when the
+    /// broker could not be asked for their status.
+    async fn fail_stale_orders(&self) -> Result<(), OrderPollingError> {
+        let Some(max_order_age) = self.config.max_order_age else {
+            return Ok(());
+        };
+
+        let now = chrono::Utc::now();
+        if self.config.maintenance.is_under_maintenance_at(now) {
+            return Ok(());
+        }
+
+        // An age too large to subtract from now expires nothing
+        let Some(placed_before) = chrono::Duration::from_std(max_order_age)
+            .ok()
+            .and_then(|age| now.checked_sub_signed(age))
+        else {
+            return Ok(());
+        };
+        let mut stale_executions = Vec::new();
+        for broker in self.broker.order_brokers() {
+            stale_executions.extend(
+                find_submitted_executions_placed_before(&self.pool, broker, placed_before).await?,
+            );
+        }
+
+        for execution in stale_executions {
+            let (Some(execution_id), OrderState::Submitted { order_id }) =
+                (execution.id, &execution.state)
+            else {
+                continue;
+            };
+
+            let reason =
+                format!("Order {order_id} still SUBMITTED {max_order_age:?} after it was placed");
+            warn!("Marking execution {execution_id} FAILED: {reason}");
+
+            let failed_state = OrderState::Failed {
+                failed_at: now,
+                error_reason: Some(reason),
+            };
+            self.handle_failed_order(execution_id, &failed_state)
+                .await?;
+            self.notify(&execution, order_id, &failed_state);
+        }
+
+        Ok(())
+    }
+
     async fn poll_execution_status(
         &self,
         execution: &OffchainExecution,
@@ -118,6 +272,10 @@ impl<B: Broker> OrderStatusPoller<B> {
                 debug!("Execution {execution_id} already failed, skipping poll");
                 return Ok(());
             }
+            OrderState::Cancelled { .. } => {
+                debug!("Execution {execution_id} already cancelled, skipping poll");
+                return Ok(());
+            }
         };
 
         let parsed_order_id = self
@@ -127,20 +285,35 @@ impl<B: Broker> OrderStatusPoller<B> {
 
         let order_state = self
             .broker
-            .get_order_status(&parsed_order_id)
+            .get_order_status_at(execution.broker, &parsed_order_id)
             .await
-            .map_err(|e| OrderPollingError::Broker(Box::new(e)))?;
+            .map_err(|e| {
+                Metrics::global().record_broker_error(execution.broker, &e);
+                rate_limit_delay(&e).map_or_else(
+                    || OrderPollingError::Broker(Box::new(e)),
+                    OrderPollingError::RateLimited,
+                )
+            })?;
 
         match &order_state {
             OrderState::Filled { .. } => {
                 self.handle_filled_order(execution_id, &order_state).await?;
+                self.notify(execution, &order_id, &order_state);
             }
             OrderState::Failed { .. } => {
                 self.handle_failed_order(execution_id, &order_state).await?;
+                self.notify(execution, &order_id, &order_state);
+            }
+            OrderState::Cancelled { .. } => {
+                self.handle_cancelled_order(execution_id, &order_state)
+                    .await?;
             }
             OrderState::Pending | OrderState::Submitted { .. } => {
+                // A limit order can rest unfilled for the whole session, a
+                // market order should not
+                let order_type = OrderType::find(&self.pool, execution_id).await?;
                 debug!(
-                    "Order {order_id} (execution {execution_id}) still pending with state: {:?}",
+                    "{order_type} order {order_id} (execution {execution_id}) still pending with state: {:?}",
                     order_state
                 );
             }
@@ -154,34 +327,19 @@ impl<B: Broker> OrderStatusPoller<B> {
         execution_id: i64,
         order_state: &OrderState,
     ) -> Result<(), OrderPollingError> {
-        let new_status = order_state.clone();
-
-        let mut tx = self.pool.begin().await?;
-
-        let Some(execution) = find_execution_by_id(&self.pool, execution_id).await? else {
-            error!("Execution {execution_id} not found in database");
-            return Err(OrderPollingError::OnChain(OnChainError::Persistence(
-                PersistenceError::InvalidTradeStatus("Execution not found".to_string()),
-            )));
-        };
-
-        new_status.store_update(&mut tx, execution_id).await?;
-
-        clear_pending_execution_id(&mut tx, &execution.symbol).await?;
-
-        clear_execution_lease(&mut tx, &execution.symbol).await?;
-
-        tx.commit().await?;
+        let symbol = self.store_terminal_state(execution_id, order_state).await?;
+        self.stats.record_fill();
+        Metrics::global().record_order_filled(execution_id);
 
         if let OrderState::Filled { price_cents, .. } = order_state {
             info!(
                 "Updated execution {execution_id} to FILLED with price: {} cents and cleared locks for symbol: {}",
-                price_cents, execution.symbol
+                price_cents, symbol
             );
         } else {
             info!(
                 "Updated execution {execution_id} to FILLED and cleared locks for symbol: {}",
-                execution.symbol
+                symbol
             );
         }
 
@@ -193,31 +351,106 @@ impl<B: Broker> OrderStatusPoller<B> {
         execution_id: i64,
         order_state: &OrderState,
     ) -> Result<(), OrderPollingError> {
-        let new_status = order_state.clone();
+        let symbol = self.store_terminal_state(execution_id, order_state).await?;
+        self.stats.record_failure();
+        Metrics::global().record_order_closed(execution_id);
+
+        info!(
+            "Updated execution {execution_id} to FAILED and cleared locks for symbol: {}",
+            symbol
+        );
+
+        Ok(())
+    }
+
+    async fn handle_cancelled_order(
+        &self,
+        execution_id: i64,
+        order_state: &OrderState,
+    ) -> Result<(), OrderPollingError> {
+        let symbol = self.store_terminal_state(execution_id, order_state).await?;
+        Metrics::global().record_order_closed(execution_id);
 
-        let mut tx = self.pool.begin().await?;
+        info!(
+            "Updated execution {execution_id} to CANCELLED and cleared locks for symbol: {}",
+            symbol
+        );
+
+        Ok(())
+    }
 
-        let Some(execution) = find_execution_by_id(&self.pool, execution_id).await? else {
-            error!("Execution {execution_id} not found in database");
-            return Err(OrderPollingError::OnChain(OnChainError::Persistence(
-                PersistenceError::InvalidTradeStatus("Execution not found".to_string()),
-            )));
+    /// Hands a filled or failed order to the notifier, which delivers it
+    /// without blocking the poll.
+    fn notify(&self, execution: &OffchainExecution, order_id: &str, order_state: &OrderState) {
+        let (updated_at, price_cents, reported_price) = match order_state {
+            OrderState::Filled {
+                executed_at,
+                price_cents,
+                reported_price,
+                ..
+            } => (*executed_at, Some(*price_cents), reported_price.clone()),
+            OrderState::Failed { failed_at, .. } => (*failed_at, None, None),
+            OrderState::Pending | OrderState::Submitted { .. } | OrderState::Cancelled { .. } => {
+                return;
+            }
         };
 
-        new_status.store_update(&mut tx, execution_id).await?;
+        self.config.notifier.on_order_update(&OrderUpdate {
+            order_id,
+            symbol: execution.symbol.clone(),
+            shares: execution.shares,
+            direction: execution.direction,
+            status: order_state.status(),
+            updated_at,
+            price_cents,
+            reported_price,
+        });
+    }
 
-        clear_pending_execution_id(&mut tx, &execution.symbol).await?;
+    /// Stores a filled, failed or cancelled order state and releases the symbol's
+    /// pending execution and lease, retrying while the database is locked.
+    /// Publishes the new status to the trade feed and returns the execution's
+    /// symbol.
+    async fn store_terminal_state(
+        &self,
+        execution_id: i64,
+        order_state: &OrderState,
+    ) -> Result<Symbol, OrderPollingError> {
+        let symbol = retry_when_locked(
+            self.config.locked_retry,
+            "execution state update",
+            || async {
+                let mut tx = self.pool.begin().await?;
 
-        clear_execution_lease(&mut tx, &execution.symbol).await?;
+                let Some(execution) = find_execution_by_id(&self.pool, execution_id).await? else {
+                    error!("Execution {execution_id} not found in database");
+                    return Err(OrderPollingError::OnChain(OnChainError::Persistence(
+                        PersistenceError::InvalidTradeStatus("Execution not found".to_string()),
+                    )));
+                };
 
-        tx.commit().await?;
+                order_state.store_update(&mut tx, execution_id).await?;
 
-        info!(
-            "Updated execution {execution_id} to FAILED and cleared locks for symbol: {}",
-            execution.symbol
-        );
+                clear_pending_execution_id(&mut tx, &execution.symbol).await?;
 
-        Ok(())
+                clear_execution_lease(&mut tx, &execution.symbol).await?;
+
+                tx.commit().await?;
+
+                Ok(execution.symbol)
+            },
+        )
+        .await?;
+
+        self.config
+            .trade_feed
+            .publish(TradeFeedEvent::ExecutionStatus {
+                execution_id,
+                symbol: symbol.clone(),
+                status: order_state.status(),
+            });
+
+        Ok(symbol)
     }
 
     async fn add_jittered_delay(&self) {
@@ -230,3 +463,143 @@ impl<B: Broker> OrderStatusPoller<B> {
         }
     }
 }
+
+/// Orders executions so the largest estimated notional is polled first,
+/// dropping dust below `dust_threshold` unless this is a dust cycle.
+///
+/// Executions without a known notional are polled first rather than being
+/// mistaken for dust.
+fn prioritize_by_notional(
+    executions: Vec<(OffchainExecution, Option<f64>)>,
+    dust_threshold: Option<f64>,
+    include_dust: bool,
+) -> Vec<OffchainExecution> {
+    executions
+        .into_iter()
+        .filter(|(_, notional)| {
+            include_dust
+                || match (dust_threshold, notional) {
+                    (Some(threshold), Some(notional)) => *notional >= threshold,
+                    _ => true,
+                }
+        })
+        .sorted_by(|(_, a), (_, b)| match (a, b) {
+            (Some(a), Some(b)) => b.total_cmp(a),
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+        .map(|(execution, _)| execution)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{OffchainExecutionBuilder, setup_test_db};
+    use st0x_broker::{MockBroker, SupportedBroker};
+
+    fn execution_with_id(id: i64) -> OffchainExecution {
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.id = Some(id);
+        execution
+    }
+
+    fn ids(executions: &[OffchainExecution]) -> Vec<Option<i64>> {
+        executions.iter().map(|execution| execution.id).collect()
+    }
+
+    #[test]
+    fn test_large_order_polled_before_small_order() {
+        let executions = vec![
+            (execution_with_id(1), Some(12.5)),
+            (execution_with_id(2), Some(48_000.0)),
+        ];
+
+        let ordered = prioritize_by_notional(executions, None, true);
+
+        assert_eq!(ids(&ordered), vec![Some(2), Some(1)]);
+    }
+
+    #[test]
+    fn test_dust_only_polled_on_dust_cycles() {
+        let executions = || {
+            vec![
+                (execution_with_id(1), Some(12.5)),
+                (execution_with_id(2), Some(48_000.0)),
+                (execution_with_id(3), None),
+            ]
+        };
+
+        let regular_cycle = prioritize_by_notional(executions(), Some(100.0), false);
+        assert_eq!(ids(&regular_cycle), vec![Some(3), Some(2)]);
+
+        let dust_cycle = prioritize_by_notional(executions(), Some(100.0), true);
+        assert_eq!(ids(&dust_cycle), vec![Some(3), Some(2), Some(1)]);
+    }
+
+    async fn save_submitted(pool: &SqlitePool, symbol: &str, order_id: &str) -> i64 {
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.symbol = Symbol::new(symbol).unwrap();
+        execution.broker = SupportedBroker::DryRun;
+        execution.state = OrderState::Submitted {
+            order_id: order_id.to_string(),
+        };
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        execution_id
+    }
+
+    #[tokio::test]
+    async fn test_submitted_order_older_than_max_age_is_marked_failed() {
+        let pool = setup_test_db().await;
+        let stale_id = save_submitted(&pool, "AAPL", "ORD1").await;
+        let recent_id = save_submitted(&pool, "MSFT", "ORD2").await;
+
+        let placed_at = (chrono::Utc::now() - chrono::Duration::hours(2)).naive_utc();
+        sqlx::query!(
+            "UPDATE offchain_trades SET placed_at = ?1 WHERE id = ?2",
+            placed_at,
+            stale_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let config = OrderPollerConfig {
+            max_order_age: Some(Duration::from_secs(60 * 60)),
+            ..OrderPollerConfig::default()
+        };
+        let poller = OrderStatusPoller::new(
+            config,
+            pool.clone(),
+            MockBroker::new(),
+            Arc::new(Stats::default()),
+        );
+
+        poller.fail_stale_orders().await.unwrap();
+
+        let stale = find_execution_by_id(&pool, stale_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stale.state.status(), OrderStatus::Failed);
+
+        let recent = find_execution_by_id(&pool, recent_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            recent.state,
+            OrderState::Submitted {
+                order_id: "ORD2".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Caps the net position the bot may build up at the broker in one symbol.
+//!
+//! The net position of a symbol is the shares bought minus the shares sold by
+//! its executions that are pending, submitted or filled. An execution that
+//! would take the absolute net position past the limit of its symbol is
+//! skipped when it would be created, and its shares stay accumulated, so the
+//! periodic position check executes them once trades in the other direction
+//! bring the position back within the limit or the limit is raised.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use st0x_broker::{Direction, Symbol};
+use tracing::warn;
+
+use crate::error::OnChainError;
+
+/// Maximum absolute net position of a symbol, parsed from `SYMBOL=SHARES`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SymbolPositionLimit {
+    pub(crate) symbol: Symbol,
+    pub(crate) max_net_shares: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SymbolPositionLimitParseError {
+    #[error("Expected SYMBOL=SHARES but got '{0}'")]
+    MissingSeparator(String),
+    #[error("Invalid symbol '{0}'")]
+    InvalidSymbol(String),
+    #[error("Invalid share limit '{0}'")]
+    InvalidShares(String),
+}
+
+impl FromStr for SymbolPositionLimit {
+    type Err = SymbolPositionLimitParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (symbol, shares) = value
+            .split_once('=')
+            .ok_or_else(|| SymbolPositionLimitParseError::MissingSeparator(value.to_string()))?;
+
+        let symbol = Symbol::new(symbol.trim().to_uppercase())
+            .map_err(|_| SymbolPositionLimitParseError::InvalidSymbol(symbol.to_string()))?;
+
+        let max_net_shares = shares
+            .trim()
+            .parse()
+            .map_err(|_| SymbolPositionLimitParseError::InvalidShares(shares.to_string()))?;
+
+        Ok(Self {
+            symbol,
+            max_net_shares,
+        })
+    }
+}
+
+/// Configured net position limits. A per-symbol limit takes precedence over
+/// the global one; symbols without either are unrestricted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PositionLimits {
+    global: Option<u64>,
+    per_symbol: HashMap<Symbol, u64>,
+}
+
+impl PositionLimits {
+    pub(crate) fn new(
+        global: Option<u64>,
+        per_symbol: impl IntoIterator<Item = SymbolPositionLimit>,
+    ) -> Self {
+        Self {
+            global,
+            per_symbol: per_symbol
+                .into_iter()
+                .map(|limit| (limit.symbol, limit.max_net_shares))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn limit_for(&self, symbol: &Symbol) -> Option<u64> {
+        self.per_symbol.get(symbol).copied().or(self.global)
+    }
+
+    /// Whether an execution of `shares` of `symbol` in `direction` keeps the
+    /// net position within its limit, logging the skip when it does not.
+    pub(crate) async fn allows(
+        &self,
+        sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        symbol: &Symbol,
+        direction: Direction,
+        shares: u64,
+    ) -> Result<bool, OnChainError> {
+        let Some(max_net_shares) = self.limit_for(symbol) else {
+            return Ok(true);
+        };
+
+        let net_shares = net_position_within_transaction(sql_tx, symbol).await?;
+        let resulting = match direction {
+            Direction::Buy => i128::from(net_shares) + i128::from(shares),
+            Direction::Sell => i128::from(net_shares) - i128::from(shares),
+        };
+
+        if resulting.unsigned_abs() <= u128::from(max_net_shares) {
+            return Ok(true);
+        }
+
+        warn!(
+            symbol = %symbol,
+            shares,
+            direction = ?direction,
+            net_shares,
+            max_net_shares,
+            "Execution would breach the net position limit, keeping it accumulated"
+        );
+
+        Ok(false)
+    }
+}
+
+/// Shares bought minus shares sold by the pending, submitted and filled
+/// executions of `symbol`, fractional-share orders at their exact quantity,
+/// rounded to whole shares.
+pub(crate) async fn net_position_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    symbol: &Symbol,
+) -> Result<i64, OnChainError> {
+    let symbol = symbol.to_string();
+    let net_shares = sqlx::query_scalar!(
+        r#"
+        SELECT CAST(ROUND(COALESCE(
+            SUM(
+                CASE direction
+                    WHEN 'BUY' THEN COALESCE(CAST(fractional_shares AS REAL), shares)
+                    ELSE -COALESCE(CAST(fractional_shares AS REAL), shares)
+                END
+            ),
+            0
+        )) AS INTEGER) AS "net_shares!: i64"
+        FROM offchain_trades
+        WHERE symbol = ?1
+          AND status IN ('PENDING', 'SUBMITTED', 'FILLED')
+        "#,
+        symbol
+    )
+    .fetch_one(&mut **sql_tx)
+    .await?;
+
+    Ok(net_shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{OffchainExecutionBuilder, setup_test_db};
+    use chrono::Utc;
+    use sqlx::SqlitePool;
+    use st0x_broker::{OrderState, Shares};
+
+    async fn save_execution(
+        pool: &SqlitePool,
+        symbol: &str,
+        shares: u64,
+        direction: Direction,
+        state: OrderState,
+    ) {
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.symbol = Symbol::new(symbol).unwrap();
+        execution.shares = Shares::new(shares).unwrap();
+        execution.direction = direction;
+        execution.state = state;
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+    }
+
+    #[test]
+    fn test_parse_symbol_position_limit() {
+        let parsed: SymbolPositionLimit = " aapl = 100 ".parse().unwrap();
+        assert_eq!(
+            parsed,
+            SymbolPositionLimit {
+                symbol: Symbol::new("AAPL").unwrap(),
+                max_net_shares: 100,
+            }
+        );
+
+        assert!(matches!(
+            "AAPL".parse::<SymbolPositionLimit>(),
+            Err(SymbolPositionLimitParseError::MissingSeparator(_))
+        ));
+        assert!(matches!(
+            "=1".parse::<SymbolPositionLimit>(),
+            Err(SymbolPositionLimitParseError::InvalidSymbol(_))
+        ));
+        assert!(matches!(
+            "AAPL=-1".parse::<SymbolPositionLimit>(),
+            Err(SymbolPositionLimitParseError::InvalidShares(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_per_symbol_limit_overrides_global_against_open_and_filled_executions() {
+        let pool = setup_test_db().await;
+        let limits = PositionLimits::new(
+            Some(10),
+            [SymbolPositionLimit {
+                symbol: Symbol::new("MSFT").unwrap(),
+                max_net_shares: 3,
+            }],
+        );
+        let filled = |order_id: &str| OrderState::Filled {
+            order_id: order_id.to_string(),
+            executed_at: Utc::now(),
+            price_cents: 15025,
+            reported_price: None,
+        };
+        let failed = OrderState::Failed {
+            failed_at: Utc::now(),
+            error_reason: None,
+        };
+
+        save_execution(&pool, "AAPL", 6, Direction::Buy, filled("ORD1")).await;
+        save_execution(&pool, "AAPL", 2, Direction::Buy, OrderState::Pending).await;
+        save_execution(&pool, "AAPL", 1, Direction::Sell, filled("ORD2")).await;
+        save_execution(&pool, "AAPL", 50, Direction::Buy, failed).await;
+        save_execution(&pool, "MSFT", 3, Direction::Sell, OrderState::Pending).await;
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let aapl = Symbol::new("AAPL").unwrap();
+        let msft = Symbol::new("MSFT").unwrap();
+        let tsla = Symbol::new("TSLA").unwrap();
+
+        assert_eq!(
+            net_position_within_transaction(&mut sql_tx, &aapl)
+                .await
+                .unwrap(),
+            7
+        );
+        assert!(
+            limits
+                .allows(&mut sql_tx, &aapl, Direction::Buy, 3)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !limits
+                .allows(&mut sql_tx, &aapl, Direction::Buy, 4)
+                .await
+                .unwrap()
+        );
+        assert!(
+            limits
+                .allows(&mut sql_tx, &aapl, Direction::Sell, 17)
+                .await
+                .unwrap()
+        );
+
+        assert!(
+            !limits
+                .allows(&mut sql_tx, &msft, Direction::Sell, 1)
+                .await
+                .unwrap()
+        );
+        assert!(
+            limits
+                .allows(&mut sql_tx, &msft, Direction::Buy, 6)
+                .await
+                .unwrap()
+        );
+
+        assert!(
+            PositionLimits::default()
+                .allows(&mut sql_tx, &tsla, Direction::Buy, 1_000)
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Reconciliation of open executions against the broker's order history.
+//!
+//! An execution can stay PENDING or SUBMITTED after its order has settled at
+//! the broker, e.g. when the order poller missed the update. Reconciling
+//! matches every open Schwab execution to an order entered on the account,
+//! first by the broker order id it recorded or its client order key, then by
+//! symbol, shares and direction (earliest entered order first), and stores
+//! the state the broker reports wherever it differs from the recorded one.
+
+use std::collections::HashSet;
+
+use sqlx::SqlitePool;
+use st0x_broker::schwab::SchwabOrderRecord;
+use st0x_broker::{OrderState, OrderStatus, SupportedBroker, Symbol};
+use tracing::info;
+
+use super::execution::{
+    OffchainExecution, find_client_order_id, find_executions_by_symbol_status_and_broker,
+};
+use crate::error::OnChainError;
+use crate::lock::{clear_execution_lease, clear_pending_execution_id};
+
+/// An open execution whose broker order is in a different state.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Discrepancy {
+    pub(crate) execution_id: i64,
+    pub(crate) symbol: Symbol,
+    pub(crate) recorded: OrderStatus,
+    pub(crate) order_id: String,
+    pub(crate) broker_state: OrderState,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ReconcileSummary {
+    pub(crate) executions_checked: usize,
+    /// Open executions no order in the history could be matched to
+    pub(crate) unmatched: Vec<i64>,
+    pub(crate) discrepancies: Vec<Discrepancy>,
+    pub(crate) corrected: usize,
+}
+
+/// Compares open Schwab executions with `orders` and, unless `dry_run`,
+/// stores the broker state of every mismatched execution, releasing its
+/// symbol once the order is terminal.
+pub(crate) async fn reconcile_executions(
+    pool: &SqlitePool,
+    orders: &[SchwabOrderRecord],
+    dry_run: bool,
+) -> Result<ReconcileSummary, OnChainError> {
+    let mut executions = Vec::new();
+    for status in [OrderStatus::Pending, OrderStatus::Submitted] {
+        executions.extend(
+            find_executions_by_symbol_status_and_broker(
+                pool,
+                None,
+                status,
+                Some(SupportedBroker::Schwab),
+            )
+            .await?,
+        );
+    }
+
+    let recorded_order_ids: HashSet<String> = sqlx::query_scalar!(
+        r#"SELECT order_id AS "order_id!" FROM offchain_trades WHERE order_id IS NOT NULL"#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut summary = ReconcileSummary {
+        executions_checked: executions.len(),
+        ..ReconcileSummary::default()
+    };
+    let mut claimed = HashSet::new();
+    let mut matches = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for execution in executions {
+        let Some(execution_id) = execution.id else {
+            continue;
+        };
+
+        let client_order_id = find_client_order_id(pool, execution_id).await?;
+        let recorded_order_id = match &execution.state {
+            OrderState::Submitted { order_id } => Some(order_id.as_str()),
+            _ => None,
+        };
+
+        let order = orders.iter().find(|order| {
+            recorded_order_id == Some(order.order_id.as_str())
+                || (client_order_id.is_some() && order.tag == client_order_id)
+        });
+
+        match order {
+            Some(order) => {
+                claimed.insert(order.order_id.as_str());
+                matches.push((execution_id, execution, order));
+            }
+            None => unmatched.push((execution_id, execution)),
+        }
+    }
+
+    // Only executions that never recorded an order fall back to matching on
+    // size, against orders no execution has recorded
+    let mut by_entry_time: Vec<_> = orders
+        .iter()
+        .filter(|order| !recorded_order_ids.contains(&order.order_id))
+        .collect();
+    by_entry_time.sort_by_key(|order| (order.entered_at.is_none(), order.entered_at));
+
+    for (execution_id, execution) in unmatched {
+        let order = if execution.state == OrderState::Pending {
+            by_entry_time.iter().copied().find(|order| {
+                !claimed.contains(order.order_id.as_str()) && matches_size(order, &execution)
+            })
+        } else {
+            None
+        };
+
+        match order {
+            Some(order) => {
+                claimed.insert(order.order_id.as_str());
+                matches.push((execution_id, execution, order));
+            }
+            None => summary.unmatched.push(execution_id),
+        }
+    }
+
+    for (execution_id, execution, order) in matches {
+        if order.state.status() == execution.state.status() {
+            continue;
+        }
+
+        let discrepancy = Discrepancy {
+            execution_id,
+            symbol: execution.symbol,
+            recorded: execution.state.status(),
+            order_id: order.order_id.clone(),
+            broker_state: order.state.clone(),
+        };
+
+        if !dry_run {
+            store_broker_state(pool, &discrepancy).await?;
+            summary.corrected += 1;
+        }
+
+        summary.discrepancies.push(discrepancy);
+    }
+
+    Ok(summary)
+}
+
+fn matches_size(order: &SchwabOrderRecord, execution: &OffchainExecution) -> bool {
+    order.symbol.as_ref() == Some(&execution.symbol)
+        && order.shares == Some(execution.shares)
+        && order.direction == Some(execution.direction)
+}
+
+async fn store_broker_state(
+    pool: &SqlitePool,
+    discrepancy: &Discrepancy,
+) -> Result<(), OnChainError> {
+    let Discrepancy {
+        execution_id,
+        symbol,
+        recorded,
+        order_id,
+        broker_state,
+    } = discrepancy;
+
+    let mut sql_tx = pool.begin().await?;
+    broker_state
+        .store_update(&mut sql_tx, *execution_id)
+        .await?;
+    if !matches!(broker_state, OrderState::Submitted { .. }) {
+        clear_pending_execution_id(&mut sql_tx, symbol).await?;
+        clear_execution_lease(&mut sql_tx, symbol).await?;
+    }
+    sql_tx.commit().await?;
+
+    info!(
+        "Reconciled execution {execution_id} ({symbol}) from {} to {} per order {order_id}",
+        recorded.as_str(),
+        broker_state.status().as_str()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offchain::execution::find_execution_by_id;
+    use crate::test_utils::{OffchainExecutionBuilder, setup_test_db};
+    use chrono::{DateTime, Utc};
+    use st0x_broker::{Direction, Shares};
+
+    async fn save_execution(pool: &SqlitePool, symbol: &str, state: OrderState) -> i64 {
+        let mut execution = OffchainExecutionBuilder::new().build();
+        execution.symbol = Symbol::new(symbol).unwrap();
+        execution.state = state;
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+        execution_id
+    }
+
+    fn filled_order(order_id: &str, symbol: &str, entered_at: &str) -> SchwabOrderRecord {
+        let execution = OffchainExecutionBuilder::new().build();
+        SchwabOrderRecord {
+            order_id: order_id.to_string(),
+            tag: None,
+            symbol: Some(Symbol::new(symbol).unwrap()),
+            shares: Some(execution.shares),
+            direction: Some(execution.direction),
+            entered_at: Some(entered_at.parse().unwrap()),
+            state: OrderState::Filled {
+                executed_at: "2025-10-30T14:30:02Z".parse::<DateTime<Utc>>().unwrap(),
+                order_id: order_id.to_string(),
+                price_cents: 15025,
+                reported_price: Some("150.25".to_string()),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submitted_execution_promoted_to_filled() {
+        let pool = setup_test_db().await;
+        let execution_id = save_execution(
+            &pool,
+            "AAPL",
+            OrderState::Submitted {
+                order_id: "1001".to_string(),
+            },
+        )
+        .await;
+        let orders = [filled_order("1001", "AAPL", "2025-10-30T14:30:00Z")];
+
+        let summary = reconcile_executions(&pool, &orders, true).await.unwrap();
+        assert_eq!(summary.discrepancies.len(), 1);
+        assert_eq!(summary.corrected, 0);
+        let execution = find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.state.status(), OrderStatus::Submitted);
+
+        let summary = reconcile_executions(&pool, &orders, false).await.unwrap();
+        assert_eq!(
+            summary,
+            ReconcileSummary {
+                executions_checked: 1,
+                unmatched: vec![],
+                discrepancies: vec![Discrepancy {
+                    execution_id,
+                    symbol: Symbol::new("AAPL").unwrap(),
+                    recorded: OrderStatus::Submitted,
+                    order_id: "1001".to_string(),
+                    broker_state: orders[0].state.clone(),
+                }],
+                corrected: 1,
+            }
+        );
+        let execution = find_execution_by_id(&pool, execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        // The reported price is only kept in its audit column, not read back
+        let mut expected_state = orders[0].state.clone();
+        if let OrderState::Filled { reported_price, .. } = &mut expected_state {
+            let stored_reported_price = sqlx::query_scalar!(
+                "SELECT reported_price FROM offchain_trades WHERE id = ?1",
+                execution_id
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            assert_eq!(stored_reported_price, reported_price.take());
+        }
+        assert_eq!(execution.state, expected_state);
+
+        // Nothing is left to reconcile
+        let summary = reconcile_executions(&pool, &orders, false).await.unwrap();
+        assert_eq!(summary, ReconcileSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_pending_execution_matched_by_size_to_earliest_unrecorded_order() {
+        let pool = setup_test_db().await;
+        let submitted_id = save_execution(
+            &pool,
+            "MSFT",
+            OrderState::Submitted {
+                order_id: "2000".to_string(),
+            },
+        )
+        .await;
+        let pending_id = save_execution(&pool, "AAPL", OrderState::Pending).await;
+        let orphan_id = save_execution(&pool, "TSLA", OrderState::Pending).await;
+
+        let mut other_direction = filled_order("1000", "AAPL", "2025-10-30T14:00:00Z");
+        other_direction.direction = Some(Direction::Sell);
+        let mut other_size = filled_order("1001", "AAPL", "2025-10-30T14:00:00Z");
+        other_size.shares = Some(Shares::new(1).unwrap());
+        let orders = [
+            filled_order("2000", "AAPL", "2025-10-30T13:00:00Z"),
+            other_direction,
+            other_size,
+            filled_order("1003", "AAPL", "2025-10-30T14:20:00Z"),
+            filled_order("1002", "AAPL", "2025-10-30T14:10:00Z"),
+        ];
+
+        let summary = reconcile_executions(&pool, &orders, false).await.unwrap();
+
+        assert_eq!(summary.executions_checked, 3);
+        assert_eq!(summary.unmatched, vec![orphan_id]);
+        let corrected: Vec<_> = summary
+            .discrepancies
+            .iter()
+            .map(|discrepancy| (discrepancy.execution_id, discrepancy.order_id.as_str()))
+            .collect();
+        assert_eq!(
+            corrected,
+            vec![(submitted_id, "2000"), (pending_id, "1002")]
+        );
+        assert_eq!(summary.corrected, 2);
+    }
+}
@@ -0,0 +1,162 @@
+//! Pre-trade check that a hedge still captures a minimum spread.
+//!
+//! Each hedge closes out onchain fills at the broker's quote: long exposure
+//! from onchain buys is sold at the bid and short exposure from onchain sells
+//! is bought at the ask. The spread between the average onchain price and
+//! that side of the quote, in basis points of the onchain price and net of
+//! the estimated fees and slippage, must reach the configured minimum.
+//! Executions below it are deferred, keeping the position accumulated for the
+//! periodic check to retry against a later quote.
+
+use num_traits::ToPrimitive;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use st0x_broker::{Direction, Quote, Symbol};
+
+const BASIS_POINTS: f64 = 10_000.0;
+
+/// Spread requirement and estimated trading costs, all in basis points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SpreadPolicy {
+    pub(crate) min_net_spread: f64,
+    pub(crate) estimated_fee: f64,
+    pub(crate) estimated_slippage: f64,
+}
+
+impl SpreadPolicy {
+    /// Spread in basis points, after estimated costs, from hedging fills made
+    /// onchain at `onchain_price` with a `direction` order at `quote`. `None`
+    /// without a positive onchain price.
+    pub(crate) fn net_spread_bps(
+        &self,
+        direction: Direction,
+        onchain_price: f64,
+        quote: &Quote,
+    ) -> Option<f64> {
+        if onchain_price <= 0.0 {
+            return None;
+        }
+
+        let gross = match direction {
+            Direction::Sell => quote.bid_price_cents.to_f64()? / 100.0 - onchain_price,
+            Direction::Buy => onchain_price - quote.ask_price_cents.to_f64()? / 100.0,
+        };
+
+        let gross_bps = gross / onchain_price * BASIS_POINTS;
+        Some(gross_bps - self.estimated_fee - self.estimated_slippage)
+    }
+}
+
+/// The spread policy together with the broker quotes resolved for one round
+/// of executions.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SpreadCheck {
+    policy: SpreadPolicy,
+    quotes: HashMap<Symbol, Quote>,
+}
+
+impl SpreadCheck {
+    pub(crate) fn new(policy: SpreadPolicy, quotes: impl IntoIterator<Item = Quote>) -> Self {
+        Self {
+            policy,
+            quotes: quotes
+                .into_iter()
+                .map(|quote| (quote.symbol.clone(), quote))
+                .collect(),
+        }
+    }
+
+    /// Whether hedging `symbol` in `direction` against fills at the average
+    /// `onchain_price` captures the minimum spread. Without a quote or an
+    /// onchain price the spread is unknown and the execution waits.
+    pub(crate) fn is_viable(
+        &self,
+        symbol: &Symbol,
+        direction: Direction,
+        onchain_price: Option<f64>,
+    ) -> bool {
+        let Some(quote) = self.quotes.get(symbol) else {
+            warn!("No quote for {symbol} to check spread against, deferring execution");
+            return false;
+        };
+
+        let Some(net_spread_bps) =
+            onchain_price.and_then(|price| self.policy.net_spread_bps(direction, price, quote))
+        else {
+            warn!("No onchain price for {symbol} to check spread against, deferring execution");
+            return false;
+        };
+
+        let viable = net_spread_bps >= self.policy.min_net_spread;
+
+        info!(
+            symbol = %symbol,
+            direction = ?direction,
+            onchain_price,
+            bid_price_cents = quote.bid_price_cents,
+            ask_price_cents = quote.ask_price_cents,
+            net_spread_bps,
+            min_net_spread_bps = self.policy.min_net_spread,
+            viable,
+            "Checked hedge spread"
+        );
+
+        viable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn quote(bid_price_cents: u64, ask_price_cents: u64) -> Quote {
+        Quote {
+            symbol: Symbol::new("AAPL").unwrap(),
+            bid_price_cents,
+            ask_price_cents,
+            quoted_at: Utc::now(),
+        }
+    }
+
+    const POLICY: SpreadPolicy = SpreadPolicy {
+        min_net_spread: 10.0,
+        estimated_fee: 2.0,
+        estimated_slippage: 3.0,
+    };
+
+    #[test]
+    fn test_net_spread_uses_quote_side_of_the_hedge() {
+        let quote = quote(10_050, 10_060);
+
+        // Bought at $100 onchain, sold at the $100.50 bid: 50bps less 5bps costs
+        let sell = POLICY
+            .net_spread_bps(Direction::Sell, 100.0, &quote)
+            .unwrap();
+        assert!((sell - 45.0).abs() < 1e-9);
+
+        // Sold at $100 onchain, bought back at the $100.60 ask: a loss
+        let buy = POLICY
+            .net_spread_bps(Direction::Buy, 100.0, &quote)
+            .unwrap();
+        assert!((buy + 65.0).abs() < 1e-9);
+
+        assert_eq!(POLICY.net_spread_bps(Direction::Buy, 0.0, &quote), None);
+    }
+
+    #[test]
+    fn test_spread_check_requires_quote_and_minimum_spread() {
+        let aapl = Symbol::new("AAPL").unwrap();
+        let check = SpreadCheck::new(POLICY, [quote(10_050, 10_060)]);
+
+        assert!(check.is_viable(&aapl, Direction::Sell, Some(100.0)));
+        assert!(!check.is_viable(&aapl, Direction::Buy, Some(100.0)));
+        // 14bps gross leaves 9bps after costs, below the minimum
+        assert!(!check.is_viable(&aapl, Direction::Sell, Some(100.36)));
+        assert!(!check.is_viable(&aapl, Direction::Sell, None));
+
+        let msft = Symbol::new("MSFT").unwrap();
+        assert!(!check.is_viable(&msft, Direction::Sell, Some(100.0)));
+    }
+}
@@ -0,0 +1,40 @@
+//! Restricts which side of the market the bot may hedge on.
+//!
+//! Trades on the disallowed side are still recorded and accumulated; only
+//! the offchain execution is withheld, so switching back to [`TradeSide::Both`]
+//! lets the periodic position check execute what has built up.
+
+use st0x_broker::Direction;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TradeSide {
+    #[default]
+    Both,
+    BuyOnly,
+    SellOnly,
+}
+
+impl TradeSide {
+    /// Whether an offchain execution in `direction` may be placed.
+    pub(crate) const fn allows(self, direction: Direction) -> bool {
+        matches!(
+            (self, direction),
+            (Self::Both, _) | (Self::BuyOnly, Direction::Buy) | (Self::SellOnly, Direction::Sell)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_only_permitted_direction() {
+        assert!(TradeSide::Both.allows(Direction::Buy));
+        assert!(TradeSide::Both.allows(Direction::Sell));
+        assert!(TradeSide::BuyOnly.allows(Direction::Buy));
+        assert!(!TradeSide::BuyOnly.allows(Direction::Sell));
+        assert!(!TradeSide::SellOnly.allows(Direction::Buy));
+        assert!(TradeSide::SellOnly.allows(Direction::Sell));
+    }
+}
@@ -1,63 +1,148 @@
+use chrono::Utc;
 use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
 use sqlx::SqlitePool;
-use tracing::info;
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
 use super::OnchainTrade;
+use crate::accumulation_contribution::AccumulationContribution;
 use crate::error::{OnChainError, TradeValidationError};
 use crate::lock::{clear_execution_lease, set_pending_execution_id, try_acquire_execution_lease};
-use crate::offchain::execution::OffchainExecution;
-use crate::onchain::position_calculator::{AccumulationBucket, PositionCalculator};
+use crate::offchain::blackout::BlackoutCalendar;
+use crate::offchain::execution::{
+    OffchainExecution, inherit_strategy_label_within_transaction,
+    record_fractional_shares_within_transaction, record_origin_block_within_transaction,
+};
+use crate::offchain::liquidity::LiquidityLimits;
+use crate::offchain::trade_side::TradeSide;
+use crate::onchain::io::SymbolConvention;
+use crate::onchain::position_calculator::{
+    AccumulationBucket, ConversionError, PositionCalculator, ShareRounding,
+};
 use crate::trade_execution_link::TradeExecutionLink;
 use st0x_broker::{Direction, OrderState, Shares, SupportedBroker, Symbol};
 
+/// Settings deciding whether and how accumulated positions are executed.
+///
+/// Executions are created for `broker_type`, and withheld while their symbol is
+/// in a `blackout` window, when they repeat an execution within
+/// `dedup_window`, or when their direction is not allowed by `trade_side`.
+/// `liquidity` caps or defers them, and whole shares are taken from positions
+/// according to `rounding`. With `fractional_shares`, for brokers that can
+/// trade them, the whole shares are truncated and the fraction left below one
+/// share executes as a fractional-share order instead of waiting for more
+/// trades.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutionRules<'a> {
+    pub broker_type: SupportedBroker,
+    pub convention: &'a SymbolConvention,
+    pub blackout: &'a BlackoutCalendar,
+    pub dedup_window: Option<Duration>,
+    pub liquidity: &'a LiquidityLimits,
+    pub trade_side: TradeSide,
+    pub rounding: ShareRounding,
+    pub fractional_shares: bool,
+}
+
+impl ExecutionRules<'_> {
+    /// Rounding of the whole shares executed. A fraction rounded up would be
+    /// executed twice when fractional shares are traded, so they truncate.
+    const fn whole_share_rounding(&self) -> ShareRounding {
+        if self.fractional_shares {
+            ShareRounding::Truncate
+        } else {
+            self.rounding
+        }
+    }
+
+    /// Bucket `calculator`'s position executes from, if it is ready: a whole
+    /// share, or any fraction when fractional shares are traded.
+    fn ready_execution_type(
+        &self,
+        symbol: &Symbol,
+        calculator: &PositionCalculator,
+    ) -> Option<AccumulationBucket> {
+        calculator
+            .determine_execution_type()
+            .or_else(|| {
+                calculator
+                    .determine_residual_type()
+                    .filter(|_| self.fractional_shares)
+            })
+            .filter(|execution_type| is_side_allowed(self.trade_side, symbol, *execution_type))
+    }
+}
+
 /// Processes an onchain trade through the accumulation system with duplicate detection.
 ///
 /// This function handles the complete trade processing pipeline:
-/// 1. Checks for duplicate trades (same tx_hash + log_index) and skips if already processed
+/// 1. Checks for duplicate trades (same tx_hash + log_index + orderbook) and skips if already processed
 /// 2. Saves the trade to the onchain_trades table
 /// 3. Updates the position accumulator for the symbol
-/// 4. Attempts to create a Schwab execution if position thresholds are met
+/// 4. Attempts to create a Schwab execution if position thresholds are met, as allowed
+///    by `rules` (in a blackout window the trade is still accumulated)
+///
+/// When `accumulate` is false, only the trade's whole shares under the rounding of
+/// `rules` are added to the position so it executes immediately, and the fractional
+/// difference is dropped. Dropped shares
+/// are recorded on the trade so they are never linked to an execution.
+///
+/// When `defer_execution` is true (running as standby, or waiting out an execution
+/// debounce window) the trade is accumulated but no execution is created.
 ///
-/// Returns `Some(OffchainExecution)` if a Schwab order was created, `None` if the trade
-/// was accumulated but didn't trigger an execution (or was a duplicate).
+/// The trade is saved with `strategy_label`, which executions it contributes to
+/// inherit.
+///
+/// Returns the Schwab orders the trade triggered, more than one if the position
+/// was split to stay within the per-order value limit, and none if the trade
+/// was accumulated without triggering an execution (or was a duplicate).
 ///
 /// The transaction must be committed by the caller.
-#[tracing::instrument(skip(sql_tx, trade), fields(symbol = %trade.symbol, amount = %trade.amount, direction = ?trade.direction), level = tracing::Level::INFO)]
+#[tracing::instrument(skip(sql_tx, trade, rules), fields(symbol = %trade.symbol, amount = %trade.amount, direction = ?trade.direction), level = tracing::Level::INFO)]
 pub async fn process_onchain_trade(
     sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     trade: OnchainTrade,
-    broker_type: st0x_broker::SupportedBroker,
-) -> Result<Option<OffchainExecution>, OnChainError> {
+    rules: ExecutionRules<'_>,
+    accumulate: bool,
+    defer_execution: bool,
+    strategy_label: Option<&str>,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
     // Check if trade already exists to handle duplicates gracefully
     let tx_hash_str = trade.tx_hash.to_string();
     let log_index_i64 = i64::try_from(trade.log_index)
         .map_err(|_| OnChainError::Validation(crate::error::TradeValidationError::NoLogIndex))?;
 
+    let orderbook_str = trade.orderbook.to_string();
+
     let existing_trade = sqlx::query!(
         "
         SELECT id
         FROM onchain_trades
-        WHERE tx_hash = ?1 AND log_index = ?2
+        WHERE tx_hash = ?1 AND log_index = ?2 AND orderbook = ?3
         ",
         tx_hash_str,
-        log_index_i64
+        log_index_i64,
+        orderbook_str
     )
     .fetch_optional(&mut **sql_tx)
     .await?;
 
     if existing_trade.is_some() {
         info!(
-            "Trade already exists (tx_hash={:?}, log_index={}), skipping duplicate processing",
-            trade.tx_hash, trade.log_index
+            "Trade already exists (tx_hash={:?}, log_index={}, orderbook={}), skipping duplicate processing",
+            trade.tx_hash, trade.log_index, trade.orderbook
         );
-        return Ok(None);
+        return Ok(vec![]);
     }
 
-    let trade_id = trade.save_within_transaction(sql_tx).await?;
+    let trade_id = trade
+        .save_labelled_within_transaction(sql_tx, strategy_label)
+        .await?;
     info!(
         trade_id = trade_id,
         symbol = %trade.symbol,
-        amount = trade.amount,
+        amount = %trade.amount,
         direction = ?trade.direction,
         tx_hash = ?trade.tx_hash,
         log_index = trade.log_index,
@@ -75,27 +160,38 @@ pub async fn process_onchain_trade(
         Direction::Sell => AccumulationBucket::ShortExposure, // Sold stock -> short exposure
         Direction::Buy => AccumulationBucket::LongExposure,   // Bought stock -> long exposure
     };
-    calculator.add_trade(trade.amount, exposure_bucket);
+    let amount = accumulated_amount(&trade, accumulate, rules.rounding)?;
+    if amount < trade.amount {
+        record_dropped_amount(sql_tx, trade_id, trade.amount - amount).await?;
+    }
+    calculator.add_trade(amount, exposure_bucket);
 
     info!(
         symbol = %base_symbol,
-        net_position = calculator.net_position(),
-        accumulated_long = calculator.accumulated_long,
-        accumulated_short = calculator.accumulated_short,
+        net_position = %calculator.net_position(),
+        accumulated_long = %calculator.accumulated_long,
+        accumulated_short = %calculator.accumulated_short,
         exposure_bucket = ?exposure_bucket,
-        trade_amount = trade.amount,
+        trade_amount = %trade.amount,
         "Updated calculator"
     );
 
     // Clean up any stale executions for this symbol before attempting new execution
     clean_up_stale_executions(sql_tx, base_symbol).await?;
 
-    let execution = if try_acquire_execution_lease(sql_tx, base_symbol).await? {
-        let result =
-            try_create_execution_if_ready(sql_tx, base_symbol, &mut calculator, broker_type)
-                .await?;
+    let executions = if defer_execution {
+        info!(
+            symbol = %base_symbol,
+            "Execution deferred, accumulating without creating an execution"
+        );
+        vec![]
+    } else if is_deferred_by_blackout(rules.blackout, base_symbol) {
+        vec![]
+    } else if try_acquire_execution_lease(sql_tx, base_symbol).await? {
+        let executions =
+            try_create_execution_if_ready(sql_tx, base_symbol, &mut calculator, rules).await?;
 
-        match &result {
+        match executions.first() {
             Some(execution) => {
                 let execution_id = execution
                     .id
@@ -107,22 +203,123 @@ pub async fn process_onchain_trade(
             }
         }
 
-        result
+        executions
     } else {
         info!(
             symbol = %base_symbol,
             "Another worker holds execution lease, skipping execution creation"
         );
-        None
+        vec![]
     };
 
-    let pending_execution_id = execution.as_ref().and_then(|e| e.id);
+    let pending_execution_id = executions.first().and_then(|e| e.id);
     save_within_transaction(&mut *sql_tx, base_symbol, &calculator, pending_execution_id).await?;
 
-    Ok(execution)
+    Ok(executions)
+}
+
+/// Parses a base symbol for the `--no-accumulate` flag.
+pub(crate) fn parse_non_accumulating_symbol(value: &str) -> Result<Symbol, String> {
+    Symbol::new(value.trim().to_uppercase())
+        .map_err(|e| format!("Invalid symbol for disabling accumulation: {e}"))
+}
+
+/// Exact amount of `trade` added to its symbol's position: all of it, or only
+/// its whole shares under `rounding` for a symbol that does not accumulate
+/// fractions.
+fn accumulated_amount(
+    trade: &OnchainTrade,
+    accumulate: bool,
+    rounding: ShareRounding,
+) -> Result<Decimal, OnChainError> {
+    if accumulate {
+        return Ok(trade.amount);
+    }
+
+    let whole_shares = Decimal::from(rounding.whole_shares(trade.amount)?);
+    let dropped = trade.amount - whole_shares;
+    if !dropped.is_zero() {
+        info!(
+            symbol = %trade.symbol,
+            trade_amount = %trade.amount,
+            dropped_shares = %dropped,
+            "Symbol does not accumulate fractions, dropping fractional shares"
+        );
+    }
+
+    Ok(whole_shares)
+}
+
+/// Records the shares of trade `trade_id` that were dropped rather than
+/// accumulated.
+async fn record_dropped_amount(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    trade_id: i64,
+    dropped: Decimal,
+) -> Result<(), OnChainError> {
+    let dropped_str = dropped.to_string();
+    sqlx::query!(
+        "UPDATE onchain_trades SET dropped_amount = ?1 WHERE id = ?2",
+        dropped_str,
+        trade_id
+    )
+    .execute(sql_tx.as_mut())
+    .await?;
+
+    Ok(())
+}
+
+fn is_deferred_by_blackout(blackout: &BlackoutCalendar, symbol: &Symbol) -> bool {
+    let now = Utc::now();
+
+    // Logged quietly as every trade during the window lands here
+    if blackout.maintenance().is_under_maintenance_at(now) {
+        debug!(
+            symbol = %symbol,
+            "Broker is in a maintenance window, deferring execution"
+        );
+        return true;
+    }
+
+    let blacked_out = blackout.is_blacked_out_at(symbol, now);
+
+    if blacked_out {
+        info!(
+            symbol = %symbol,
+            "Symbol is in a blackout window, deferring execution"
+        );
+    }
+
+    blacked_out
+}
+
+const fn execution_direction(execution_type: AccumulationBucket) -> Direction {
+    match execution_type {
+        AccumulationBucket::LongExposure => Direction::Sell, // Long exposure -> Schwab SELL to offset
+        AccumulationBucket::ShortExposure => Direction::Buy, // Short exposure -> Schwab BUY to offset
+    }
+}
+
+fn is_side_allowed(
+    trade_side: TradeSide,
+    symbol: &Symbol,
+    execution_type: AccumulationBucket,
+) -> bool {
+    let direction = execution_direction(execution_type);
+    let allowed = trade_side.allows(direction);
+
+    if !allowed {
+        info!(
+            symbol = %symbol,
+            direction = ?direction,
+            trade_side = ?trade_side,
+            "Execution direction not allowed by trade side, keeping position accumulated"
+        );
+    }
+
+    allowed
 }
 
-#[cfg(test)]
 pub async fn find_by_symbol(
     pool: &SqlitePool,
     symbol: &str,
@@ -131,11 +328,12 @@ pub async fn find_by_symbol(
         .fetch_optional(pool)
         .await?;
 
-    Ok(row.map(|row| {
+    row.map(|row| {
         let calculator =
-            PositionCalculator::with_positions(row.accumulated_long, row.accumulated_short);
-        (calculator, row.pending_execution_id)
-    }))
+            PositionCalculator::from_stored(row.accumulated_long, row.accumulated_short)?;
+        Ok((calculator, row.pending_execution_id))
+    })
+    .transpose()
 }
 
 async fn get_or_create_within_transaction(
@@ -151,10 +349,10 @@ async fn get_or_create_within_transaction(
     .await?;
 
     if let Some(row) = row {
-        Ok(PositionCalculator::with_positions(
+        Ok(PositionCalculator::from_stored(
             row.accumulated_long,
             row.accumulated_short,
-        ))
+        )?)
     } else {
         let new_calculator = PositionCalculator::new();
         save_within_transaction(sql_tx, symbol, &new_calculator, None).await?;
@@ -169,6 +367,7 @@ pub async fn save_within_transaction(
     pending_execution_id: Option<i64>,
 ) -> Result<(), OnChainError> {
     let symbol_str = symbol.to_string();
+    let (accumulated_long, accumulated_short) = calculator.to_stored()?;
     sqlx::query!(
         r#"
         INSERT INTO trade_accumulators (
@@ -186,8 +385,8 @@ pub async fn save_within_transaction(
             last_updated = CURRENT_TIMESTAMP
         "#,
         symbol_str,
-        calculator.accumulated_long,
-        calculator.accumulated_short,
+        accumulated_long,
+        accumulated_short,
         pending_execution_id
     )
     .execute(sql_tx.as_mut())
@@ -200,76 +399,250 @@ async fn try_create_execution_if_ready(
     sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     base_symbol: &Symbol,
     calculator: &mut PositionCalculator,
-    broker_type: st0x_broker::SupportedBroker,
-) -> Result<Option<OffchainExecution>, OnChainError> {
-    let Some(execution_type) = calculator.determine_execution_type() else {
-        return Ok(None);
+    rules: ExecutionRules<'_>,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    let Some(execution_type) = rules.ready_execution_type(base_symbol, calculator) else {
+        return Ok(vec![]);
     };
 
-    execute_position(
-        &mut *sql_tx,
-        base_symbol,
-        calculator,
-        execution_type,
-        broker_type,
-    )
-    .await
+    execute_position(&mut *sql_tx, base_symbol, calculator, execution_type, rules).await
 }
 
+/// Creates the executions for a ready position: one, or one per compliant
+/// order when the position is above the per-order value limit. Shares of any
+/// order that cannot go out yet stay accumulated.
 async fn execute_position(
     sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     base_symbol: &Symbol,
     calculator: &mut PositionCalculator,
     execution_type: AccumulationBucket,
-    broker_type: st0x_broker::SupportedBroker,
+    rules: ExecutionRules<'_>,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    let shares = calculator.calculate_executable_shares(rules.whole_share_rounding())?;
+
+    // Shares held back by the liquidity limit stay accumulated for a later execution
+    let mut quantities: Vec<Decimal> = match shares {
+        0 => vec![],
+        shares => {
+            let order_sizes = rules.liquidity.order_sizes(base_symbol, shares);
+            if order_sizes.is_empty() {
+                return Ok(vec![]);
+            }
+            order_sizes.into_iter().map(Decimal::from).collect()
+        }
+    };
+
+    // The fraction goes out as its own order once every whole share does
+    let all_whole_shares = quantities.iter().sum::<Decimal>() == Decimal::from(shares);
+    if rules.fractional_shares && all_whole_shares {
+        quantities.extend(calculator.fractional_remainder());
+    }
+
+    let mut executions: Vec<OffchainExecution> = Vec::new();
+    for quantity in quantities {
+        // Later orders of a split are created as children of the first
+        let split_parent_id = executions.first().and_then(|e| e.id);
+        let Some(execution) = execute_order(
+            sql_tx,
+            base_symbol,
+            calculator,
+            execution_type,
+            quantity,
+            rules,
+            split_parent_id,
+        )
+        .await?
+        else {
+            break;
+        };
+
+        executions.push(execution);
+    }
+
+    Ok(executions)
+}
+
+/// Creates one execution of `quantity` shares from the position, as a child
+/// of `split_parent_id` if it is a later order of a split, and links the
+/// trades it takes. A quantity that is not a whole number of shares is
+/// recorded as a fractional-share order.
+async fn execute_order(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    base_symbol: &Symbol,
+    calculator: &mut PositionCalculator,
+    execution_type: AccumulationBucket,
+    quantity: Decimal,
+    rules: ExecutionRules<'_>,
+    split_parent_id: Option<i64>,
 ) -> Result<Option<OffchainExecution>, OnChainError> {
-    let shares = calculator.calculate_executable_shares()?;
+    let ExecutionRules {
+        broker_type,
+        convention,
+        dedup_window,
+        liquidity,
+        ..
+    } = rules;
+    let instruction = execution_direction(execution_type);
+    // A fractional order counts as the whole shares it rounds up to
+    let shares = quantity
+        .ceil()
+        .to_u64()
+        .ok_or(ConversionError::DecimalToU64OutOfRange { value: quantity })?;
+
+    // Dust executions wait for more shares to accumulate, and executions that
+    // would not capture the minimum spread wait for a better quote
+    if liquidity.checks_trade_notional() || liquidity.checks_spread() {
+        let onchain_price =
+            unallocated_onchain_price(sql_tx, convention, base_symbol, execution_type).await?;
+        if !liquidity.is_trade_notional_viable(base_symbol, quantity, onchain_price)
+            || !liquidity.is_spread_viable(base_symbol, instruction, onchain_price)
+        {
+            return Ok(None);
+        }
+    }
 
-    if shares == 0 {
+    // Shares that would take the net position past its limit stay accumulated
+    if !liquidity
+        .position_limits()
+        .allows(sql_tx, base_symbol, instruction, shares)
+        .await?
+    {
         return Ok(None);
     }
 
-    let instruction = match execution_type {
-        AccumulationBucket::LongExposure => Direction::Sell, // Long exposure -> Schwab SELL to offset
-        AccumulationBucket::ShortExposure => Direction::Buy, // Short exposure -> Schwab BUY to offset
+    // An identical execution created within the dedup window leaves the
+    // shares accumulated for a later one. Later orders of a split are
+    // identified by the first, so only it is deduplicated
+    let execution = match split_parent_id {
+        Some(parent_id) => Some(
+            create_split_execution_within_transaction(
+                sql_tx,
+                base_symbol,
+                shares,
+                instruction,
+                broker_type,
+                parent_id,
+            )
+            .await?,
+        ),
+        None => {
+            create_execution_within_transaction(
+                sql_tx,
+                base_symbol,
+                shares,
+                instruction,
+                broker_type,
+                dedup_window,
+            )
+            .await?
+        }
+    };
+    let Some(execution) = execution else {
+        return Ok(None);
     };
-
-    let execution =
-        create_execution_within_transaction(sql_tx, base_symbol, shares, instruction, broker_type)
-            .await?;
 
     let execution_id = execution
         .id
         .ok_or(st0x_broker::PersistenceError::MissingExecutionId)?;
+    if quantity != Decimal::from(shares) {
+        record_fractional_shares_within_transaction(sql_tx, execution_id, quantity).await?;
+    }
 
-    // Find all trades that contributed to this execution and create linkages
-    create_trade_execution_linkages(sql_tx, base_symbol, execution_id, execution_type, shares)
-        .await?;
+    // Shares rounded up past the bucket are not backed by any trade, so only
+    // the shares taken from the bucket are linked
+    let accumulated = calculator.accumulated(execution_type);
+    let taken = calculator.reduce_accumulation(execution_type, quantity);
 
-    calculator.reduce_accumulation(execution_type, shares)?;
+    // Find all trades that contributed to this execution and create linkages
+    create_trade_execution_linkages(
+        sql_tx,
+        convention,
+        base_symbol,
+        execution_id,
+        execution_type,
+        accumulated,
+        taken,
+    )
+    .await?;
+    record_origin_block_within_transaction(sql_tx, execution_id).await?;
+    inherit_strategy_label_within_transaction(sql_tx, execution_id).await?;
 
     info!(
         symbol = %base_symbol,
-        shares = shares,
+        shares = %quantity,
         direction = ?instruction,
         execution_type = ?execution_type,
         execution_id = ?execution.id,
-        remaining_long = calculator.accumulated_long,
-        remaining_short = calculator.accumulated_short,
+        remaining_long = %calculator.accumulated_long,
+        remaining_short = %calculator.accumulated_short,
         "Created Schwab execution with trade linkages"
     );
 
     Ok(Some(execution))
 }
 
+/// Average onchain price, weighted by the amount not yet linked to an
+/// execution, of the trades that built up `execution_type` exposure.
+async fn unallocated_onchain_price(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    convention: &SymbolConvention,
+    base_symbol: &Symbol,
+    execution_type: AccumulationBucket,
+) -> Result<Option<f64>, OnChainError> {
+    let direction_str = match execution_type {
+        AccumulationBucket::ShortExposure => "SELL",
+        AccumulationBucket::LongExposure => "BUY",
+    };
+
+    let [t_prefix, zerox_suffix, configured_suffix] = convention.tokenized_variants(base_symbol);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            CAST(ot.price_usdc AS REAL) as "price_usdc!: f64",
+            CAST(ot.amount AS REAL) - COALESCE(CAST(ot.dropped_amount AS REAL), 0.0)
+                - COALESCE(SUM(tel.contributed_shares), 0.0) as "unallocated!: f64"
+        FROM onchain_trades ot
+        LEFT JOIN trade_execution_links tel ON ot.id = tel.trade_id
+        WHERE (ot.symbol = ?1 OR ot.symbol = ?2 OR ot.symbol = ?3) AND ot.direction = ?4
+        GROUP BY ot.id, ot.amount, ot.dropped_amount, ot.price_usdc
+        HAVING (CAST(ot.amount AS REAL) - COALESCE(CAST(ot.dropped_amount AS REAL), 0.0)
+            - COALESCE(SUM(tel.contributed_shares), 0.0)) > 0.001
+        "#,
+        t_prefix,
+        zerox_suffix,
+        configured_suffix,
+        direction_str
+    )
+    .fetch_all(&mut **sql_tx)
+    .await?;
+
+    let (notional, amount) = rows.iter().fold((0.0, 0.0), |(notional, amount), row| {
+        (
+            row.unallocated.mul_add(row.price_usdc, notional),
+            amount + row.unallocated,
+        )
+    });
+
+    Ok((amount > 0.0).then(|| notional / amount))
+}
+
 /// Creates trade-execution linkages for an execution.
-/// Links trades to executions based on chronological order and remaining available amounts.
+/// Links trades to executions based on chronological order and remaining available amounts,
+/// and records the accumulated total each trade contributed for audit.
+///
+/// `execution_shares` are taken from a bucket holding `accumulated` shares. Any
+/// of those beyond the trades still available are overshoot carried over from
+/// a rounded-up execution in the opposite direction, which no trade backs, so
+/// they are offset first and only the rest of `execution_shares` is linked.
 async fn create_trade_execution_linkages(
     sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    convention: &SymbolConvention,
     base_symbol: &Symbol,
     execution_id: i64,
     execution_type: AccumulationBucket,
-    execution_shares: u64,
+    accumulated: Decimal,
+    execution_shares: Decimal,
 ) -> Result<(), OnChainError> {
     // Find all trades for this symbol that created this accumulated exposure
     // AccumulationBucket::ShortExposure comes from onchain SELL trades (sold stock, now short)
@@ -286,45 +659,48 @@ async fn create_trade_execution_linkages(
     };
 
     // Match all tokenized variants of this base symbol (prefix and suffix patterns)
-    let base_str = base_symbol.to_string();
-    let t_prefix = format!("t{base_str}");
-    let zerox_suffix = format!("{base_str}0x");
-    let s1_suffix = format!("{base_str}s1");
+    let [t_prefix, zerox_suffix, configured_suffix] = convention.tokenized_variants(base_symbol);
 
     let trade_rows = sqlx::query!(
         r#"
         SELECT
             ot.id as trade_id,
-            ot.amount as trade_amount,
+            -- Dropped shares were never accumulated, so they are not linked
+            CAST(ot.amount AS REAL) - COALESCE(CAST(ot.dropped_amount AS REAL), 0.0) as "trade_amount!: f64",
             COALESCE(SUM(tel.contributed_shares), 0.0) as "already_allocated: f64"
         FROM onchain_trades ot
         LEFT JOIN trade_execution_links tel ON ot.id = tel.trade_id
         WHERE (ot.symbol = ?1 OR ot.symbol = ?2 OR ot.symbol = ?3) AND ot.direction = ?4
-        GROUP BY ot.id, ot.amount, ot.created_at
-        HAVING (ot.amount - COALESCE(SUM(tel.contributed_shares), 0.0)) > 0.001  -- Has remaining allocation
+        GROUP BY ot.id, ot.amount, ot.dropped_amount, ot.created_at
+        HAVING (CAST(ot.amount AS REAL) - COALESCE(CAST(ot.dropped_amount AS REAL), 0.0)
+            - COALESCE(SUM(tel.contributed_shares), 0.0)) > 0.001  -- Has remaining allocation
         ORDER BY ot.created_at ASC
         "#,
         t_prefix,
         zerox_suffix,
-        s1_suffix,
+        configured_suffix,
         direction_str
     )
     .fetch_all(&mut **sql_tx)
     .await?;
 
+    let to_f64 = |value: Decimal| {
+        value
+            .to_f64()
+            .ok_or(ConversionError::DecimalToF64 { value })
+    };
+    let available_total: f64 = trade_rows
+        .iter()
+        .map(|row| row.trade_amount - row.already_allocated.unwrap_or(0.0))
+        .sum();
+    // Within floating point precision nothing is carried
+    let carried = Some(to_f64(accumulated)? - available_total).filter(|carried| *carried > 0.001);
     let mut remaining_execution_shares =
-        execution_shares.to_f64().ok_or(OnChainError::Conversion(
-            crate::onchain::position_calculator::ConversionError::U64ToF64PrecisionLoss {
-                value: execution_shares,
-            },
-        ))?;
+        (to_f64(execution_shares)? - carried.unwrap_or(0.0)).max(0.0);
 
-    // Allocate trades to this execution in chronological order
+    // Allocate trades to this execution in chronological order, recording every
+    // trade still in the accumulated total (including the remainder carried forward)
     for row in trade_rows {
-        if remaining_execution_shares <= 0.001 {
-            break; // Execution fully allocated
-        }
-
         let already_allocated = row.already_allocated.unwrap_or(0.0);
         let available_amount = row.trade_amount - already_allocated;
         if available_amount <= 0.001 {
@@ -332,7 +708,24 @@ async fn create_trade_execution_linkages(
         }
 
         // Allocate either the full remaining amount or up to execution remaining shares
-        let contribution = available_amount.min(remaining_execution_shares);
+        let contribution = if remaining_execution_shares > 0.001 {
+            available_amount.min(remaining_execution_shares)
+        } else {
+            0.0 // Execution fully allocated, trade only carries forward
+        };
+
+        AccumulationContribution {
+            execution_id,
+            trade_id: row.trade_id,
+            available_shares: available_amount,
+            allocated_shares: contribution,
+        }
+        .save_within_transaction(sql_tx)
+        .await?;
+
+        if contribution <= 0.0 {
+            continue;
+        }
 
         // Create the linkage
         let link = TradeExecutionLink::new(row.trade_id, execution_id, contribution);
@@ -362,13 +755,16 @@ async fn create_trade_execution_linkages(
     Ok(())
 }
 
+/// Creates a pending execution, or returns `None` if an identical one was
+/// already created within `dedup_window`.
 async fn create_execution_within_transaction(
     sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     symbol: &Symbol,
     shares: u64,
     direction: Direction,
     broker: SupportedBroker,
-) -> Result<OffchainExecution, OnChainError> {
+    dedup_window: Option<Duration>,
+) -> Result<Option<OffchainExecution>, OnChainError> {
     let execution = OffchainExecution {
         id: None,
         symbol: symbol.clone(),
@@ -378,11 +774,54 @@ async fn create_execution_within_transaction(
         state: OrderState::Pending,
     };
 
-    let execution_id = execution.save_within_transaction(sql_tx).await?;
+    let execution_id = match dedup_window {
+        Some(window) => {
+            let key = execution.idempotency_key(Utc::now(), window);
+            let Some(execution_id) = execution
+                .save_with_idempotency_key_within_transaction(sql_tx, &key)
+                .await?
+            else {
+                warn!(
+                    symbol = %symbol,
+                    idempotency_key = %key,
+                    "Identical execution already created within the dedup window, keeping shares accumulated"
+                );
+                return Ok(None);
+            };
+            execution_id
+        }
+        None => execution.save_within_transaction(sql_tx).await?,
+    };
     let mut execution_with_id = execution;
     execution_with_id.id = Some(execution_id);
 
-    Ok(execution_with_id)
+    Ok(Some(execution_with_id))
+}
+
+/// Creates a pending execution split from the in-progress `parent_id`.
+async fn create_split_execution_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    symbol: &Symbol,
+    shares: u64,
+    direction: Direction,
+    broker: SupportedBroker,
+    parent_id: i64,
+) -> Result<OffchainExecution, OnChainError> {
+    let mut execution = OffchainExecution {
+        id: None,
+        symbol: symbol.clone(),
+        shares: Shares::new(shares)?,
+        direction,
+        broker,
+        state: OrderState::Pending,
+    };
+    execution.id = Some(
+        execution
+            .save_split_within_transaction(sql_tx, parent_id)
+            .await?,
+    );
+
+    Ok(execution)
 }
 
 /// Clean up stale executions that have been in PENDING or SUBMITTED state for too long
@@ -455,34 +894,176 @@ async fn clean_up_stale_executions(
     Ok(())
 }
 
+/// Symbols with at least one whole share accumulated and no pending execution,
+/// i.e. the candidates [`check_all_accumulated_positions`] will try to execute.
+pub async fn find_ready_symbols(pool: &SqlitePool) -> Result<Vec<Symbol>, OnChainError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT symbol
+        FROM trade_accumulators
+        WHERE pending_execution_id IS NULL
+          AND ABS(accumulated_long - accumulated_short) >= 1.0
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Symbol::new(row.symbol))
+        .collect::<Result<_, _>>()?)
+}
+
+/// Symbols left with a fractional net position below one whole share and no
+/// pending execution, together with that net position, ordered by symbol.
+pub async fn find_residual_positions(
+    pool: &SqlitePool,
+) -> Result<Vec<(Symbol, f64)>, OnChainError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            symbol,
+            (accumulated_long - accumulated_short) AS "net_position!: f64"
+        FROM trade_accumulators
+        WHERE pending_execution_id IS NULL
+          AND ABS(accumulated_long - accumulated_short) > 0.001
+          AND ABS(accumulated_long - accumulated_short) < 1.0
+        ORDER BY symbol ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| Ok((Symbol::new(row.symbol)?, row.net_position)))
+        .collect()
+}
+
+/// Rounds a symbol's fractional net position up to a one-share execution.
+///
+/// The residual trades are linked to the execution, and the accumulator is
+/// offset by the whole share, so the overshoot stays accumulated on the
+/// opposite side for later trades to offset. Returns `None` if the symbol no
+/// longer has a residual, its direction is not allowed by `trade_side`, or
+/// another worker holds its execution lease.
+pub async fn execute_residual_position(
+    pool: &SqlitePool,
+    symbol: &Symbol,
+    broker_type: SupportedBroker,
+    convention: &SymbolConvention,
+    trade_side: TradeSide,
+) -> Result<Option<OffchainExecution>, OnChainError> {
+    let mut sql_tx = pool.begin().await?;
+
+    if !try_acquire_execution_lease(&mut sql_tx, symbol).await? {
+        sql_tx.commit().await?;
+        return Ok(None);
+    }
+
+    let mut calculator = get_or_create_within_transaction(&mut sql_tx, symbol).await?;
+    let Some(execution_type) = calculator
+        .determine_residual_type()
+        .filter(|execution_type| is_side_allowed(trade_side, symbol, *execution_type))
+    else {
+        clear_execution_lease(&mut sql_tx, symbol).await?;
+        sql_tx.commit().await?;
+        return Ok(None);
+    };
+
+    let residual = calculator.net_position().abs();
+    let accumulated = calculator.accumulated(execution_type);
+    let direction = execution_direction(execution_type);
+    let Some(execution) =
+        create_execution_within_transaction(&mut sql_tx, symbol, 1, direction, broker_type, None)
+            .await?
+    else {
+        clear_execution_lease(&mut sql_tx, symbol).await?;
+        sql_tx.commit().await?;
+        return Ok(None);
+    };
+    let execution_id = execution
+        .id
+        .ok_or(st0x_broker::PersistenceError::MissingExecutionId)?;
+
+    create_trade_execution_linkages(
+        &mut sql_tx,
+        convention,
+        symbol,
+        execution_id,
+        execution_type,
+        accumulated,
+        residual,
+    )
+    .await?;
+    calculator.offset_whole_share(execution_type);
+    set_pending_execution_id(&mut sql_tx, symbol, execution_id).await?;
+    save_within_transaction(&mut sql_tx, symbol, &calculator, Some(execution_id)).await?;
+    sql_tx.commit().await?;
+
+    info!(
+        symbol = %symbol,
+        %residual,
+        direction = ?direction,
+        execution_id,
+        remaining_net_position = %calculator.net_position(),
+        "Created rounding execution for residual position"
+    );
+
+    Ok(Some(execution))
+}
+
 /// Checks all accumulated positions and executes any that are ready for execution.
 ///
 /// This function is designed to be called after processing batches of events
 /// to ensure accumulated positions execute even when no new events arrive for those symbols.
 /// It prevents positions from sitting idle indefinitely when they've accumulated
 /// enough shares to execute but the triggering trade didn't push them over the threshold.
-#[tracing::instrument(skip(pool), fields(broker_type = %broker_type), level = tracing::Level::DEBUG)]
+#[tracing::instrument(skip(pool, rules), fields(broker_type = %rules.broker_type), level = tracing::Level::DEBUG)]
 pub async fn check_all_accumulated_positions(
     pool: &SqlitePool,
-    broker_type: st0x_broker::SupportedBroker,
+    rules: ExecutionRules<'_>,
 ) -> Result<Vec<OffchainExecution>, OnChainError> {
     info!("Checking all accumulated positions for ready executions");
 
-    // Query all symbols with net position >= 1.0 shares absolute value
-    // and no pending execution
+    check_ready_positions(pool, None, rules).await
+}
+
+/// Like [`check_all_accumulated_positions`], but only considers `symbols`.
+pub async fn check_accumulated_positions_for(
+    pool: &SqlitePool,
+    symbols: &[Symbol],
+    rules: ExecutionRules<'_>,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    info!("Checking accumulated positions of {symbols:?} for ready executions");
+
+    check_ready_positions(pool, Some(symbols), rules).await
+}
+
+/// Creates an execution for every ready position, restricted to `only` if
+/// given.
+async fn check_ready_positions(
+    pool: &SqlitePool,
+    only: Option<&[Symbol]>,
+    rules: ExecutionRules<'_>,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    // Query all symbols with a net position of a whole share, or of any
+    // fraction when fractional shares are traded, and no pending execution.
+    // The position is checked exactly under the lease
+    let min_ready_shares = if rules.fractional_shares { 0.001 } else { 1.0 };
     let ready_symbols = sqlx::query!(
         r#"
         SELECT
             symbol,
-            net_position,
+            (accumulated_long - accumulated_short) AS "net_position!: f64",
             accumulated_long,
             accumulated_short,
             pending_execution_id
         FROM trade_accumulators
         WHERE pending_execution_id IS NULL
-          AND ABS(net_position) >= 1.0
+          AND ABS(accumulated_long - accumulated_short) >= ?1
         ORDER BY last_updated ASC
-        "#
+        "#,
+        min_ready_shares
     )
     .fetch_all(pool)
     .await?;
@@ -502,6 +1083,10 @@ pub async fn check_all_accumulated_positions(
     // Process each symbol individually to respect locking
     for row in ready_symbols {
         let symbol = Symbol::new(&row.symbol)?;
+        if only.is_some_and(|only| !only.contains(&symbol)) {
+            continue;
+        }
+
         info!(
             symbol = %symbol,
             accumulated_long = row.accumulated_long,
@@ -510,6 +1095,10 @@ pub async fn check_all_accumulated_positions(
             "Checking symbol for execution"
         );
 
+        if is_deferred_by_blackout(rules.blackout, &symbol) {
+            continue;
+        }
+
         let mut sql_tx = pool.begin().await?;
 
         // Clean up any stale executions for this symbol
@@ -517,55 +1106,7 @@ pub async fn check_all_accumulated_positions(
 
         // Try to acquire execution lease for this symbol
         if try_acquire_execution_lease(&mut sql_tx, &symbol).await? {
-            // Re-fetch calculator to get current state
-            let mut calculator = get_or_create_within_transaction(&mut sql_tx, &symbol).await?;
-
-            // Check if still ready after potentially concurrent processing
-            if let Some(execution_type) = calculator.determine_execution_type() {
-                // The linkage system will handle allocating the oldest available trades
-                let result = execute_position(
-                    &mut sql_tx,
-                    &symbol,
-                    &mut calculator,
-                    execution_type,
-                    broker_type,
-                )
-                .await?;
-
-                if let Some(execution) = &result {
-                    let execution_id = execution
-                        .id
-                        .ok_or(st0x_broker::PersistenceError::MissingExecutionId)?;
-                    set_pending_execution_id(&mut sql_tx, &symbol, execution_id).await?;
-
-                    info!(
-                        symbol = %symbol,
-                        execution_id = ?execution.id,
-                        shares = ?execution.shares,
-                        direction = ?execution.direction,
-                        "Created execution for accumulated position"
-                    );
-
-                    executions.push(execution.clone());
-                } else {
-                    clear_execution_lease(&mut sql_tx, &symbol).await?;
-                    info!(
-                        symbol = %symbol,
-                        "No execution created for symbol (insufficient shares after re-check)"
-                    );
-                }
-
-                // Save updated calculator state
-                let pending_execution_id = result.as_ref().and_then(|e| e.id);
-                save_within_transaction(&mut sql_tx, &symbol, &calculator, pending_execution_id)
-                    .await?;
-            } else {
-                clear_execution_lease(&mut sql_tx, &symbol).await?;
-                info!(
-                    symbol = %symbol,
-                    "No execution needed for symbol (insufficient shares after cleanup)"
-                );
-            }
+            executions.extend(execute_if_still_ready(&mut sql_tx, &symbol, rules).await?);
         } else {
             info!(
                 symbol = %symbol,
@@ -588,27 +1129,352 @@ pub async fn check_all_accumulated_positions(
     Ok(executions)
 }
 
-#[cfg(test)]
+/// Re-checks a symbol's accumulated position under its execution lease and
+/// creates its executions if it is still ready, releasing the lease otherwise.
+async fn execute_if_still_ready(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    symbol: &Symbol,
+    rules: ExecutionRules<'_>,
+) -> Result<Vec<OffchainExecution>, OnChainError> {
+    // Re-fetch calculator to get current state
+    let mut calculator = get_or_create_within_transaction(sql_tx, symbol).await?;
+
+    // Check if still ready after potentially concurrent processing
+    if let Some(execution_type) = rules.ready_execution_type(symbol, &calculator) {
+        // The linkage system will handle allocating the oldest available trades
+        let executions =
+            execute_position(sql_tx, symbol, &mut calculator, execution_type, rules).await?;
+
+        if let Some(execution) = executions.first() {
+            let execution_id = execution
+                .id
+                .ok_or(st0x_broker::PersistenceError::MissingExecutionId)?;
+            set_pending_execution_id(sql_tx, symbol, execution_id).await?;
+
+            for execution in &executions {
+                info!(
+                    symbol = %symbol,
+                    execution_id = ?execution.id,
+                    shares = ?execution.shares,
+                    direction = ?execution.direction,
+                    "Created execution for accumulated position"
+                );
+            }
+        } else {
+            clear_execution_lease(sql_tx, symbol).await?;
+            info!(
+                symbol = %symbol,
+                "No execution created for symbol (insufficient shares after re-check)"
+            );
+        }
+
+        // Save updated calculator state
+        let pending_execution_id = executions.first().and_then(|e| e.id);
+        save_within_transaction(sql_tx, symbol, &calculator, pending_execution_id).await?;
+
+        Ok(executions)
+    } else {
+        clear_execution_lease(sql_tx, symbol).await?;
+        info!(
+            symbol = %symbol,
+            "No execution needed for symbol (insufficient shares after cleanup)"
+        );
+
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
-    use crate::offchain::execution::find_executions_by_symbol_status_and_broker;
+    use crate::offchain::execution::{
+        find_execution_origin_block, find_executions_by_symbol_status_and_broker,
+        find_fractional_shares,
+    };
+    use crate::offchain::liquidity::OversizeAction;
+    use crate::offchain::maintenance::MaintenanceCalendar;
+    use crate::offchain::spread::{SpreadCheck, SpreadPolicy};
     use crate::symbol;
-    use crate::test_utils::setup_test_db;
+    use crate::test_utils::{OnchainTradeBuilder, setup_test_db};
     use crate::tokenized_symbol;
     use crate::trade_execution_link::TradeExecutionLink;
     use alloy::primitives::fixed_bytes;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
     use st0x_broker::{OrderStatus, Symbol};
 
     // Helper function for tests to handle transaction management
     async fn process_trade_with_tx(
         pool: &SqlitePool,
         trade: OnchainTrade,
+    ) -> Result<Option<OffchainExecution>, OnChainError> {
+        process_trade_with_accumulation(pool, trade, true).await
+    }
+
+    async fn process_trade_with_accumulation(
+        pool: &SqlitePool,
+        trade: OnchainTrade,
+        accumulate: bool,
+    ) -> Result<Option<OffchainExecution>, OnChainError> {
+        process_trade_with_rounding(pool, trade, accumulate, ShareRounding::Truncate).await
+    }
+
+    async fn process_trade_with_rounding(
+        pool: &SqlitePool,
+        trade: OnchainTrade,
+        accumulate: bool,
+        rounding: ShareRounding,
     ) -> Result<Option<OffchainExecution>, OnChainError> {
         let mut sql_tx = pool.begin().await?;
-        let result =
-            process_onchain_trade(&mut sql_tx, trade, st0x_broker::SupportedBroker::Schwab).await?;
+        let mut result = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding,
+                fractional_shares: false,
+            },
+            accumulate,
+            false,
+            None,
+        )
+        .await?;
         sql_tx.commit().await?;
-        Ok(result)
+        Ok(result.pop())
+    }
+
+    #[tokio::test]
+    async fn test_identical_execution_within_dedup_window_is_not_created() {
+        let pool = setup_test_db().await;
+        let symbol = Symbol::new("AAPL").unwrap();
+        let window = Some(Duration::from_secs(60));
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let first = create_execution_within_transaction(
+            &mut sql_tx,
+            &symbol,
+            5,
+            Direction::Buy,
+            SupportedBroker::Schwab,
+            window,
+        )
+        .await
+        .unwrap();
+        assert!(first.is_some());
+
+        let duplicate = create_execution_within_transaction(
+            &mut sql_tx,
+            &symbol,
+            5,
+            Direction::Buy,
+            SupportedBroker::Schwab,
+            window,
+        )
+        .await
+        .unwrap();
+        assert!(duplicate.is_none());
+        sql_tx.commit().await.unwrap();
+
+        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM offchain_trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_share_rounding_policy_on_fractional_fill() {
+        let truncated_pool = setup_test_db().await;
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(2.999)).build();
+        let execution =
+            process_trade_with_rounding(&truncated_pool, trade, true, ShareRounding::Truncate)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(execution.shares, Shares::new(2).unwrap());
+
+        let (calculator, _) = find_by_symbol(&truncated_pool, "AAPL")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(calculator.net_position(), dec!(0.999));
+
+        let rounded_pool = setup_test_db().await;
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(2.999)).build();
+        let execution =
+            process_trade_with_rounding(&rounded_pool, trade, true, ShareRounding::Round)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(execution.shares, Shares::new(3).unwrap());
+
+        // The 0.001 share overshoot is carried as opposite exposure
+        let (calculator, _) = find_by_symbol(&rounded_pool, "AAPL")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(calculator.net_position(), dec!(-0.001));
+    }
+
+    /// Fills `execution` and releases its symbol for the next execution.
+    async fn fill_execution(pool: &SqlitePool, execution: &OffchainExecution) {
+        let mut sql_tx = pool.begin().await.unwrap();
+        OrderState::Filled {
+            executed_at: chrono::Utc::now(),
+            order_id: "ORDER1".to_string(),
+            price_cents: 15000,
+            reported_price: None,
+        }
+        .store_update(&mut sql_tx, execution.id.unwrap())
+        .await
+        .unwrap();
+        crate::lock::clear_pending_execution_id(&mut sql_tx, &execution.symbol)
+            .await
+            .unwrap();
+        clear_execution_lease(&mut sql_tx, &execution.symbol)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+    }
+
+    async fn linked_shares(pool: &SqlitePool, execution: &OffchainExecution) -> f64 {
+        TradeExecutionLink::find_trades_for_execution(pool, execution.id.unwrap())
+            .await
+            .unwrap()
+            .iter()
+            .map(|trade| trade.contributed_shares)
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_rounded_up_execution_links_only_accumulated_shares() {
+        let pool = setup_test_db().await;
+
+        let trade = OnchainTradeBuilder::new()
+            .with_amount(dec!(2.6))
+            .with_log_index(1)
+            .build();
+        let execution = process_trade_with_rounding(&pool, trade, true, ShareRounding::Round)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.shares, Shares::new(3).unwrap());
+        assert!((linked_shares(&pool, &execution).await - 2.6).abs() < 1e-9);
+
+        let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.net_position(), dec!(-0.4));
+        fill_execution(&pool, &execution).await;
+
+        // The carried 0.4 share overshoot is backed by no trade, so it is
+        // offset first and the opposite trade only links the rest
+        let trade = OnchainTrade {
+            direction: Direction::Sell,
+            ..OnchainTradeBuilder::new()
+                .with_amount(dec!(1.0))
+                .with_log_index(2)
+                .build()
+        };
+        let execution = process_trade_with_rounding(&pool, trade, true, ShareRounding::Round)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.shares, Shares::new(1).unwrap());
+        assert_eq!(execution.direction, Direction::Buy);
+        assert!((linked_shares(&pool, &execution).await - 0.6).abs() < 1e-9);
+
+        let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_short, dec!(0.4));
+    }
+
+    #[tokio::test]
+    async fn test_accumulating_symbol_carries_fraction_forward() {
+        let pool = setup_test_db().await;
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(1.7)).build();
+        let execution = process_trade_with_accumulation(&pool, trade, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.shares, Shares::new(1).unwrap());
+
+        let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(0.7));
+    }
+
+    #[tokio::test]
+    async fn test_non_accumulating_symbol_executes_whole_shares_and_drops_fraction() {
+        let pool = setup_test_db().await;
+
+        let trade = OnchainTradeBuilder::new()
+            .with_amount(dec!(2.7))
+            .with_log_index(1)
+            .build();
+        let execution = process_trade_with_accumulation(&pool, trade, false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution.shares, Shares::new(2).unwrap());
+        assert_eq!(execution.direction, Direction::Sell);
+
+        let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, Decimal::ZERO);
+
+        // A later fraction is dropped too rather than topping up the last one
+        let trade = OnchainTradeBuilder::new()
+            .with_amount(dec!(0.5))
+            .with_log_index(2)
+            .build();
+        let execution = process_trade_with_accumulation(&pool, trade, false)
+            .await
+            .unwrap();
+        assert!(execution.is_none());
+
+        let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, Decimal::ZERO);
+        assert_eq!(OnchainTrade::db_count(&pool).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_fraction_is_never_linked() {
+        for (rounding, shares, linked) in [
+            (ShareRounding::Truncate, 2, 2.0),
+            (ShareRounding::Round, 3, 2.6),
+        ] {
+            let pool = setup_test_db().await;
+
+            let trade = OnchainTradeBuilder::new()
+                .with_amount(dec!(2.6))
+                .with_log_index(1)
+                .build();
+            let execution = process_trade_with_rounding(&pool, trade, false, rounding)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(execution.shares, Shares::new(shares).unwrap());
+            assert!((linked_shares(&pool, &execution).await - linked).abs() < 1e-9);
+            fill_execution(&pool, &execution).await;
+
+            // Only the later trade backs the next execution
+            let trade = OnchainTradeBuilder::new()
+                .with_amount(dec!(1.0))
+                .with_log_index(2)
+                .build();
+            let execution = process_trade_with_rounding(&pool, trade, false, rounding)
+                .await
+                .unwrap()
+                .unwrap();
+            let links = TradeExecutionLink::find_trades_for_execution(&pool, execution.id.unwrap())
+                .await
+                .unwrap();
+            assert_eq!(links.len(), 1, "{rounding:?}");
+            assert_eq!(links[0].trade_log_index, 2);
+            assert!((links[0].contributed_shares - 1.0).abs() < 1e-9);
+        }
     }
 
     #[tokio::test]
@@ -621,10 +1487,11 @@ mod tests {
                 "0x1111111111111111111111111111111111111111111111111111111111111111"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 0.5,
+            amount: dec!(0.5),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(45000),
@@ -639,9 +1506,9 @@ mod tests {
         assert!(result.is_none());
 
         let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
-        assert!((calculator.accumulated_short - 0.5).abs() < f64::EPSILON); // SELL creates short exposure
-        assert!((calculator.net_position() - (-0.5)).abs() < f64::EPSILON); // Short position = negative net
-        assert!((calculator.accumulated_long - 0.0).abs() < f64::EPSILON); // No long exposure
+        assert_eq!(calculator.accumulated_short, dec!(0.5)); // SELL creates short exposure
+        assert_eq!(calculator.net_position(), dec!(-0.5)); // Short position = negative net
+        assert_eq!(calculator.accumulated_long, dec!(0.0)); // No long exposure
     }
 
     #[tokio::test]
@@ -654,10 +1521,11 @@ mod tests {
                 "0x2222222222222222222222222222222222222222222222222222222222222222"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("MSFT0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 300.0,
+            price_usdc: dec!(300.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(52000),
@@ -675,8 +1543,8 @@ mod tests {
         assert_eq!(execution.direction, Direction::Buy); // Schwab BUY to offset onchain SELL (short exposure)
 
         let (calculator, _) = find_by_symbol(&pool, "MSFT").await.unwrap().unwrap();
-        assert!((calculator.accumulated_short - 0.5).abs() < f64::EPSILON); // SELL creates short exposure
-        assert!((calculator.net_position() - (-0.5)).abs() < f64::EPSILON); // Short position = negative net
+        assert_eq!(calculator.accumulated_short, dec!(0.5)); // SELL creates short exposure
+        assert_eq!(calculator.net_position(), dec!(-0.5)); // Short position = negative net
     }
 
     #[tokio::test]
@@ -689,10 +1557,11 @@ mod tests {
                 "0x3333333333333333333333333333333333333333333333333333333333333333"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 0.3,
+            amount: dec!(0.3),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(43000),
@@ -712,10 +1581,11 @@ mod tests {
                 "0x4444444444444444444444444444444444444444444444444444444444444444"
             ),
             log_index: 2,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 0.4,
+            amount: dec!(0.4),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(44000),
@@ -735,10 +1605,11 @@ mod tests {
                 "0x5555555555555555555555555555555555555555555555555555555555555555"
             ),
             log_index: 3,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 0.4,
+            amount: dec!(0.4),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(46000),
@@ -757,8 +1628,8 @@ mod tests {
         assert_eq!(execution.direction, Direction::Buy); // Schwab BUY to offset onchain SELL (short exposure)
 
         let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
-        assert!((calculator.accumulated_short - 0.1).abs() < f64::EPSILON); // Remaining short exposure
-        assert!((calculator.net_position() - (-0.1)).abs() < f64::EPSILON); // Net short position
+        assert_eq!(calculator.accumulated_short, dec!(0.1)); // Remaining short exposure
+        assert_eq!(calculator.net_position(), dec!(-0.1)); // Net short position
     }
 
     #[tokio::test]
@@ -771,10 +1642,11 @@ mod tests {
                 "0x6666666666666666666666666666666666666666666666666666666666666666"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("INVALID0x"),
-            amount: 1.0,
+            amount: dec!(1.0),
             direction: Direction::Buy,
-            price_usdc: 100.0,
+            price_usdc: dec!(100.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(48000),
@@ -800,10 +1672,11 @@ mod tests {
                 "0x1111111111111111111111111111111111111111111111111111111111111111"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(47000),
@@ -831,10 +1704,11 @@ mod tests {
                 "0x2222222222222222222222222222222222222222222222222222222222222222"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("MSFT0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Buy,
-            price_usdc: 300.0,
+            price_usdc: dec!(300.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(49000),
@@ -879,10 +1753,11 @@ mod tests {
                 "0x8888888888888888888888888888888888888888888888888888888888888888"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(51000),
@@ -933,10 +1808,11 @@ mod tests {
                 "0x9999999999999999999999999999999999999999999999999999999999999999"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 0.8,
+            amount: dec!(0.8),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(42000),
@@ -953,10 +1829,11 @@ mod tests {
                 "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 0.3,
+            amount: dec!(0.3),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(43500),
@@ -981,8 +1858,8 @@ mod tests {
 
         // Verify accumulator shows correct remaining fractional amount
         let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
-        assert!((calculator.accumulated_short - 0.1).abs() < f64::EPSILON); // SELL creates short exposure
-        assert!((calculator.net_position() - (-0.1)).abs() < f64::EPSILON); // Short position = negative net
+        assert_eq!(calculator.accumulated_short, dec!(0.1)); // SELL creates short exposure
+        assert_eq!(calculator.net_position(), dec!(-0.1)); // Short position = negative net
 
         // Verify both trades were saved
         let trade_count = OnchainTrade::db_count(&pool).await.unwrap();
@@ -1022,15 +1899,16 @@ mod tests {
         unreachable!()
     }
 
-    fn create_test_trade(tx_hash_byte: u8, symbol: &str, amount: f64) -> OnchainTrade {
+    fn create_test_trade(tx_hash_byte: u8, symbol: &str, amount: Decimal) -> OnchainTrade {
         OnchainTrade {
             id: None,
             tx_hash: alloy::primitives::B256::repeat_byte(tx_hash_byte),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!(symbol),
             amount,
             direction: Direction::Sell,
-            price_usdc: 15000.0,
+            price_usdc: dec!(15000.0),
             block_timestamp: None,
             created_at: None,
             gas_used: None,
@@ -1042,7 +1920,7 @@ mod tests {
         }
     }
 
-    async fn verify_concurrent_execution_state(pool: &SqlitePool, expected_short: f64) {
+    async fn verify_concurrent_execution_state(pool: &SqlitePool, expected_short: Decimal) {
         let trade_count = super::OnchainTrade::db_count(pool).await.unwrap();
         assert_eq!(trade_count, 2, "Expected 2 trades to be saved");
 
@@ -1061,8 +1939,8 @@ mod tests {
             .unwrap()
             .expect("Accumulator should exist for AAPL");
 
-        assert!(
-            (calculator.accumulated_short - expected_short).abs() < f64::EPSILON,
+        assert_eq!(
+            calculator.accumulated_short, expected_short,
             "Expected {expected_short} accumulated_short remaining, got {}",
             calculator.accumulated_short
         );
@@ -1072,8 +1950,8 @@ mod tests {
     async fn test_concurrent_trade_processing_prevents_duplicate_executions() {
         let pool = setup_test_db().await;
 
-        let trade1 = create_test_trade(0xaa, "AAPL0x", 0.8);
-        let trade2 = create_test_trade(0xbb, "AAPL0x", 0.8);
+        let trade1 = create_test_trade(0xaa, "AAPL0x", dec!(0.8));
+        let trade2 = create_test_trade(0xbb, "AAPL0x", dec!(0.8));
 
         let (result1, result2) = tokio::join!(
             process_with_retry(&pool, trade1),
@@ -1094,7 +1972,7 @@ mod tests {
             "Per-symbol lease should prevent duplicate executions, but got {executions_created}"
         );
 
-        verify_concurrent_execution_state(&pool, 0.6).await;
+        verify_concurrent_execution_state(&pool, dec!(0.6)).await;
     }
 
     #[tokio::test]
@@ -1107,10 +1985,11 @@ mod tests {
                 "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(46500),
@@ -1140,6 +2019,122 @@ mod tests {
         assert!((trades_for_execution[0].contributed_shares - 1.0).abs() < f64::EPSILON);
     }
 
+    #[tokio::test]
+    async fn test_trades_with_configured_suffix_are_linked() {
+        let pool = setup_test_db().await;
+        let convention = SymbolConvention {
+            tokenized_suffix: "x1".to_string(),
+            ..SymbolConvention::default()
+        };
+        let mut trade = OnchainTradeBuilder::new().with_amount(dec!(1.5)).build();
+        trade.symbol = convention.parse_tokenized("AAPLx1").unwrap();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &convention,
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let trades_for_execution =
+            TradeExecutionLink::find_trades_for_execution(&pool, execution.id.unwrap())
+                .await
+                .unwrap();
+        assert_eq!(trades_for_execution.len(), 1);
+        assert!((trades_for_execution[0].contributed_shares - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_execution_records_latest_contributing_block() {
+        let pool = setup_test_db().await;
+
+        let first = OnchainTradeBuilder::new()
+            .with_tx_hash(fixed_bytes!(
+                "0xdddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd"
+            ))
+            .with_amount(dec!(0.6))
+            .build();
+        let second = OnchainTradeBuilder::new()
+            .with_tx_hash(fixed_bytes!(
+                "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
+            ))
+            .with_amount(dec!(0.6))
+            .build();
+
+        for (trade, block_number) in [(&first, 100_i64), (&second, 105_i64)] {
+            let tx_hash = trade.tx_hash.to_string();
+            let log_index = i64::try_from(trade.log_index).unwrap();
+            let orderbook = trade.orderbook.to_string();
+            sqlx::query!(
+                r#"
+                INSERT INTO event_queue
+                (tx_hash, log_index, orderbook, block_number, event_data, processed)
+                VALUES (?1, ?2, ?3, ?4, '{}', 1)
+                "#,
+                tx_hash,
+                log_index,
+                orderbook,
+                block_number
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        assert!(process_trade_with_tx(&pool, first).await.unwrap().is_none());
+        let execution = process_trade_with_tx(&pool, second).await.unwrap().unwrap();
+
+        let block_number = find_execution_origin_block(&pool, execution.id.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(block_number, Some(105));
+    }
+
+    #[tokio::test]
+    async fn test_same_log_index_from_two_orderbooks_is_saved_twice() {
+        let pool = setup_test_db().await;
+
+        let mut first = OnchainTradeBuilder::new().with_amount(dec!(0.4)).build();
+        first.orderbook = alloy::primitives::address!("0x1111111111111111111111111111111111111111");
+        let mut second = first.clone();
+        second.orderbook =
+            alloy::primitives::address!("0x2222222222222222222222222222222222222222");
+
+        assert!(
+            process_trade_with_tx(&pool, first.clone())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            process_trade_with_tx(&pool, second)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // The same trade from the same orderbook is still a duplicate
+        assert!(process_trade_with_tx(&pool, first).await.unwrap().is_none());
+
+        assert_eq!(OnchainTrade::db_count(&pool).await.unwrap(), 2);
+    }
+
     #[tokio::test]
     async fn test_trade_execution_linkage_multiple_trades() {
         let pool = setup_test_db().await;
@@ -1151,10 +2146,11 @@ mod tests {
                     "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
                 ),
                 log_index: 1,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("MSFT0x"),
-                amount: 0.3,
+                amount: dec!(0.3),
                 direction: Direction::Buy,
-                price_usdc: 300.0,
+                price_usdc: dec!(300.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: Some(41000),
@@ -1170,10 +2166,11 @@ mod tests {
                     "0xcccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc"
                 ),
                 log_index: 2,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("MSFT0x"),
-                amount: 0.4,
+                amount: dec!(0.4),
                 direction: Direction::Buy,
-                price_usdc: 305.0,
+                price_usdc: dec!(305.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: Some(42500),
@@ -1189,10 +2186,11 @@ mod tests {
                     "0xdddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd"
                 ),
                 log_index: 3,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("MSFT0x"),
-                amount: 0.5,
+                amount: dec!(0.5),
                 direction: Direction::Buy,
-                price_usdc: 310.0,
+                price_usdc: dec!(310.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: Some(44000),
@@ -1257,10 +2255,11 @@ mod tests {
                     "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
                 ),
                 log_index: 1,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("AAPL0x"),
-                amount: 0.4, // Below threshold
+                amount: dec!(0.4), // Below threshold
                 direction: Direction::Sell,
-                price_usdc: 150.0,
+                price_usdc: dec!(150.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: Some(40000),
@@ -1276,10 +2275,11 @@ mod tests {
                     "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
                 ),
                 log_index: 2,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("AAPL0x"),
-                amount: 0.8, // Combined: 0.4 + 0.8 = 1.2, triggers execution of 1 share
+                amount: dec!(0.8), // Combined: 0.4 + 0.8 = 1.2, triggers execution of 1 share
                 direction: Direction::Sell,
-                price_usdc: 155.0,
+                price_usdc: dec!(155.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: Some(41500),
@@ -1344,10 +2344,11 @@ mod tests {
                 "0x1010101010101010101010101010101010101010101010101010101010101010"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("TSLA0x"),
-            amount: 1.2,
+            amount: dec!(1.2),
             direction: Direction::Buy,
-            price_usdc: 800.0,
+            price_usdc: dec!(800.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(47500),
@@ -1376,7 +2377,7 @@ mod tests {
 
         // Verify the remaining 0.2 is still available for future executions
         let (calculator, _) = find_by_symbol(&pool, "TSLA").await.unwrap().unwrap();
-        assert!((calculator.accumulated_long - 0.2).abs() < f64::EPSILON); // BUY creates long exposure
+        assert_eq!(calculator.accumulated_long, dec!(0.2)); // BUY creates long exposure
     }
 
     #[tokio::test]
@@ -1390,10 +2391,11 @@ mod tests {
                 "0x2020202020202020202020202020202020202020202020202020202020202020"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Buy,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(48500),
@@ -1410,10 +2412,11 @@ mod tests {
                 "0x3030303030303030303030303030303030303030303030303030303030303030"
             ),
             log_index: 2,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("MSFT0x"), // Different symbol
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 155.0,
+            price_usdc: dec!(155.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(49500),
@@ -1504,10 +2507,11 @@ mod tests {
                 "0x1234567890123456789012345678901234567890123456789012345678901234"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(50000),
@@ -1600,10 +2604,11 @@ mod tests {
                 "0xabcdef0123456789abcdef0123456789abcdef0123456789abcdef0123456789"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("NVDA0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 140.0,
+            price_usdc: dec!(140.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(51000),
@@ -1813,10 +2818,11 @@ mod tests {
                 "0x1111111111111111111111111111111111111111111111111111111111111111"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 0.8,
+            amount: dec!(0.8),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(43200),
@@ -1832,19 +2838,30 @@ mod tests {
 
         // Verify AAPL has accumulated position but no pending execution
         let (aapl_calc, aapl_pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
-        assert!((aapl_calc.accumulated_short - 0.8).abs() < f64::EPSILON); // SELL creates short exposure
+        assert_eq!(aapl_calc.accumulated_short, dec!(0.8)); // SELL creates short exposure
         assert!(aapl_pending.is_none());
 
         // Run the function - should not create any executions since 0.8 < 1.0
-        let executions =
-            check_all_accumulated_positions(&pool, st0x_broker::SupportedBroker::Schwab)
-                .await
-                .unwrap();
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
         assert_eq!(executions.len(), 0);
 
         // Verify AAPL state unchanged
         let (aapl_calc, aapl_pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
-        assert!((aapl_calc.accumulated_short - 0.8).abs() < f64::EPSILON); // SELL creates short exposure
+        assert_eq!(aapl_calc.accumulated_short, dec!(0.8)); // SELL creates short exposure
         assert!(aapl_pending.is_none());
     }
 
@@ -1853,10 +2870,21 @@ mod tests {
         let pool = setup_test_db().await;
 
         // Run the function on empty database
-        let executions =
-            check_all_accumulated_positions(&pool, st0x_broker::SupportedBroker::Schwab)
-                .await
-                .unwrap();
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
 
         // Should create no executions
         assert_eq!(executions.len(), 0);
@@ -1883,7 +2911,7 @@ mod tests {
             .unwrap();
 
         // AAPL: Has enough accumulated but already has pending execution (should skip)
-        let aapl_calculator = PositionCalculator::with_positions(1.5, 0.0);
+        let aapl_calculator = PositionCalculator::with_positions(dec!(1.5), dec!(0.0));
         save_within_transaction(
             &mut sql_tx,
             &symbol!("AAPL"),
@@ -1896,17 +2924,28 @@ mod tests {
         sql_tx.commit().await.unwrap();
 
         // Run the function
-        let executions =
-            check_all_accumulated_positions(&pool, st0x_broker::SupportedBroker::Schwab)
-                .await
-                .unwrap();
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
 
         // Should create no executions since AAPL has pending execution
         assert_eq!(executions.len(), 0);
 
         // Verify AAPL was unchanged (still has pending execution)
         let (aapl_calc, aapl_pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
-        assert!((aapl_calc.accumulated_long - 1.5).abs() < f64::EPSILON); // Unchanged
+        assert_eq!(aapl_calc.accumulated_long, dec!(1.5)); // Unchanged
         assert_eq!(aapl_pending, Some(execution_id)); // Still has same pending execution
     }
 
@@ -1921,10 +2960,11 @@ mod tests {
                 "0xaaaa111111111111111111111111111111111111111111111111111111111111"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("GME0x"),
-            amount: 0.6,
+            amount: dec!(0.6),
             direction: Direction::Sell,
-            price_usdc: 120.0,
+            price_usdc: dec!(120.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(39000),
@@ -1941,10 +2981,11 @@ mod tests {
                 "0xbbbb222222222222222222222222222222222222222222222222222222222222"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("GMEs1"),
-            amount: 0.3,
+            amount: dec!(0.3),
             direction: Direction::Sell,
-            price_usdc: 100.0,
+            price_usdc: dec!(100.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(40500),
@@ -1961,10 +3002,11 @@ mod tests {
                 "0xcccc333333333333333333333333333333333333333333333333333333333333"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("tGME"),
-            amount: 0.2,
+            amount: dec!(0.2),
             direction: Direction::Sell,
-            price_usdc: 110.0,
+            price_usdc: dec!(110.0),
             block_timestamp: None,
             created_at: None,
             gas_used: Some(41000),
@@ -1981,8 +3023,8 @@ mod tests {
 
         // Verify accumulation for GME base symbol
         let (calculator, pending) = find_by_symbol(&pool, "GME").await.unwrap().unwrap();
-        assert!((calculator.accumulated_short - 0.6).abs() < f64::EPSILON);
-        assert!((calculator.accumulated_long - 0.0).abs() < f64::EPSILON);
+        assert_eq!(calculator.accumulated_short, dec!(0.6));
+        assert_eq!(calculator.accumulated_long, dec!(0.0));
         assert_eq!(pending, None);
 
         // Process second trade (GMEs1) - should not trigger execution yet
@@ -1991,8 +3033,8 @@ mod tests {
 
         // Verify accumulation increased
         let (calculator2, pending2) = find_by_symbol(&pool, "GME").await.unwrap().unwrap();
-        assert!((calculator2.accumulated_short - 0.9).abs() < f64::EPSILON);
-        assert!((calculator2.accumulated_long - 0.0).abs() < f64::EPSILON);
+        assert_eq!(calculator2.accumulated_short, dec!(0.9));
+        assert_eq!(calculator2.accumulated_long, dec!(0.0));
         assert_eq!(pending2, None);
 
         // Process third trade (tGME) - should trigger execution since total is 1.1 shares
@@ -2016,8 +3058,8 @@ mod tests {
 
         // Verify remaining accumulation
         let (final_calc, final_pending) = find_by_symbol(&pool, "GME").await.unwrap().unwrap();
-        assert!((final_calc.accumulated_short - 0.1).abs() < f64::EPSILON); // 0.6 + 0.3 + 0.2 - 1.0 = 0.1 remaining
-        assert!((final_calc.accumulated_long - 0.0).abs() < f64::EPSILON);
+        assert_eq!(final_calc.accumulated_short, dec!(0.1)); // 0.6 + 0.3 + 0.2 - 1.0 = 0.1 remaining
+        assert_eq!(final_calc.accumulated_long, dec!(0.0));
         assert_eq!(final_pending, execution.id); // Has pending execution
 
         // Verify audit trail shows all three marker types
@@ -2029,4 +3071,761 @@ mod tests {
         // Should include all three trades in the audit trail
         assert_eq!(audit_trail.len(), 3);
     }
+
+    fn blackout_around_today(symbol: &str) -> BlackoutCalendar {
+        let today = Utc::now().date_naive();
+        let window = format!(
+            "{symbol}:{}..{}",
+            today - chrono::Days::new(1),
+            today + chrono::Days::new(1)
+        );
+        BlackoutCalendar::new(vec![window.parse().unwrap()])
+    }
+
+    /// Daily maintenance window from `start` to `end` hours from now.
+    fn maintenance_from_now(start: i64, end: i64) -> BlackoutCalendar {
+        let now = Utc::now().with_timezone(&chrono_tz::US::Eastern);
+        let window = format!(
+            "{}..{}",
+            (now + chrono::Duration::hours(start)).format("%H:%M"),
+            (now + chrono::Duration::hours(end)).format("%H:%M")
+        );
+        BlackoutCalendar::default()
+            .with_maintenance(MaintenanceCalendar::new(vec![window.parse().unwrap()]))
+    }
+
+    async fn process_during(
+        blackout: &BlackoutCalendar,
+    ) -> (SqlitePool, Option<OffchainExecution>) {
+        let pool = setup_test_db().await;
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(1.5)).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let mut execution = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout,
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        (pool, execution.pop())
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_window_defers_execution_but_accumulates() {
+        let (pool, execution) = process_during(&maintenance_from_now(-1, 1)).await;
+        assert!(execution.is_none());
+
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(1.5));
+        assert!(pending.is_none());
+
+        // The periodic check places the position once maintenance is over
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &maintenance_from_now(1, 2),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].shares, Shares::new(1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_execution_proceeds_outside_maintenance_window() {
+        let (_pool, execution) = process_during(&maintenance_from_now(1, 2)).await;
+
+        let execution = execution.unwrap();
+        assert_eq!(execution.symbol, symbol!("AAPL"));
+        assert_eq!(execution.shares, Shares::new(1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_blacked_out_symbol_defers_execution_but_accumulates() {
+        let pool = setup_test_db().await;
+        let blackout = blackout_around_today("AAPL");
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(1.5)).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let result = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &blackout,
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert!(result.is_empty());
+
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(1.5));
+        assert!(pending.is_none());
+
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &blackout,
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(executions.is_empty());
+
+        // Once the blackout no longer applies the accumulated position executes
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].symbol, symbol!("AAPL"));
+        assert_eq!(executions[0].shares, Shares::new(1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_blackout_for_other_symbol_trades_normally() {
+        let pool = setup_test_db().await;
+        let blackout = blackout_around_today("MSFT");
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(1.5)).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let mut result = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &blackout,
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let execution = result.pop().unwrap();
+        assert_eq!(execution.symbol, symbol!("AAPL"));
+        assert_eq!(execution.shares, Shares::new(1).unwrap());
+        assert_eq!(execution.direction, Direction::Sell);
+    }
+
+    #[tokio::test]
+    async fn test_liquidity_limit_splits_oversized_execution() {
+        let pool = setup_test_db().await;
+        let liquidity = LiquidityLimits::new(OversizeAction::Split, [(symbol!("AAPL"), 2)]);
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(5.0)).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &liquidity,
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert_eq!(execution.shares, Shares::new(2).unwrap());
+
+        // The remainder stays accumulated for the next execution
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(3.0));
+        assert_eq!(pending, execution.id);
+    }
+
+    #[tokio::test]
+    async fn test_trade_and_execution_carry_strategy_label() {
+        let pool = setup_test_db().await;
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(2.0)).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            Some("desk-a"),
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let trade_label = sqlx::query_scalar!("SELECT strategy_label FROM onchain_trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(trade_label.as_deref(), Some("desk-a"));
+
+        let execution_label = sqlx::query_scalar!(
+            "SELECT strategy_label FROM offchain_trades WHERE id = ?1",
+            execution.id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(execution_label.as_deref(), Some("desk-a"));
+    }
+
+    async fn process_with_bid(bid_price_cents: u64) -> (SqlitePool, Option<OffchainExecution>) {
+        let pool = setup_test_db().await;
+        let policy = SpreadPolicy {
+            min_net_spread: 10.0,
+            estimated_fee: 2.0,
+            estimated_slippage: 3.0,
+        };
+        let quote = st0x_broker::Quote {
+            symbol: symbol!("AAPL"),
+            bid_price_cents,
+            ask_price_cents: bid_price_cents + 10,
+            quoted_at: chrono::Utc::now(),
+        };
+        let liquidity = LiquidityLimits::new(OversizeAction::Split, [])
+            .with_spread_check(SpreadCheck::new(policy, [quote]));
+
+        // Bought onchain at $150, hedged by selling at the bid
+        let trade = OnchainTradeBuilder::new()
+            .with_amount(dec!(2.0))
+            .with_price(dec!(150.0))
+            .build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let mut execution = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &liquidity,
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        (pool, execution.pop())
+    }
+
+    #[tokio::test]
+    async fn test_profitable_spread_executes() {
+        // $151.00 bid: 66.7bps gross, 61.7bps after costs
+        let (_pool, execution) = process_with_bid(15_100).await;
+
+        let execution = execution.unwrap();
+        assert_eq!(execution.shares, Shares::new(2).unwrap());
+        assert_eq!(execution.direction, Direction::Sell);
+    }
+
+    #[tokio::test]
+    async fn test_unprofitable_spread_is_deferred() {
+        // $150.10 bid: 6.7bps gross, 1.7bps after costs
+        let (pool, execution) = process_with_bid(15_010).await;
+        assert!(execution.is_none());
+
+        // The position stays accumulated for a later quote
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(2.0));
+        assert_eq!(pending, None);
+    }
+
+    #[tokio::test]
+    async fn test_dust_trade_is_accumulated_but_not_executed() {
+        let pool = setup_test_db().await;
+        let liquidity = LiquidityLimits::new(OversizeAction::Split, []).with_min_trade_notional(1);
+
+        // One share at $0.004 is worth 0.4 cents
+        let trade = OnchainTradeBuilder::new()
+            .with_amount(dec!(1.0))
+            .with_price(dec!(0.004))
+            .build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let execution = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &liquidity,
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert!(execution.is_empty());
+
+        // The dust still counts towards the position
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(1.0));
+        assert_eq!(pending, None);
+
+        // Without the minimum the same position executes
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].shares, Shares::new(1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_order_value_cap_splits_execution_into_compliant_orders() {
+        let pool = setup_test_db().await;
+        // Even with ADV limits set to defer, a value cap of 3 shares splits
+        let liquidity = LiquidityLimits::new(OversizeAction::Defer, [])
+            .with_max_order_shares([(symbol!("AAPL"), 3)]);
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(5.0)).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let executions = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: Some(Duration::from_secs(60)),
+                liquidity: &liquidity,
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        // Both compliant orders are created at once instead of waiting for
+        // the first to fill
+        let shares: Vec<_> = executions.iter().map(|e| e.shares).collect();
+        assert_eq!(shares, [Shares::new(3).unwrap(), Shares::new(2).unwrap()]);
+        assert_eq!(executions[0].direction, executions[1].direction);
+        for execution in &executions {
+            let linked = linked_shares(&pool, execution).await;
+            assert!((linked - f64::from(execution.shares.value())).abs() < f64::EPSILON);
+        }
+
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, Decimal::ZERO);
+        assert_eq!(pending, executions[0].id);
+    }
+
+    #[tokio::test]
+    async fn test_fractional_shares_execute_fraction_without_waiting() {
+        let pool = setup_test_db().await;
+        let rules = ExecutionRules {
+            broker_type: st0x_broker::SupportedBroker::Schwab,
+            convention: &SymbolConvention::default(),
+            blackout: &BlackoutCalendar::default(),
+            dedup_window: None,
+            liquidity: &LiquidityLimits::default(),
+            trade_side: TradeSide::Both,
+            rounding: ShareRounding::Round,
+            fractional_shares: true,
+        };
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(2.6)).build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let executions = process_onchain_trade(&mut sql_tx, trade, rules, true, false, None)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        // The whole shares are truncated rather than rounded up, and the
+        // fraction goes out alongside them as a fractional-share order
+        let shares: Vec<_> = executions.iter().map(|e| e.shares).collect();
+        assert_eq!(shares, [Shares::new(2).unwrap(), Shares::new(1).unwrap()]);
+        let fractions = [
+            find_fractional_shares(&pool, executions[0].id.unwrap())
+                .await
+                .unwrap(),
+            find_fractional_shares(&pool, executions[1].id.unwrap())
+                .await
+                .unwrap(),
+        ];
+        assert_eq!(fractions, [None, Some(dec!(0.6))]);
+        assert!((linked_shares(&pool, &executions[1]).await - 0.6).abs() < 1e-9);
+
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.net_position(), Decimal::ZERO);
+        assert_eq!(pending, executions[0].id);
+    }
+
+    #[tokio::test]
+    async fn test_fractional_shares_check_executes_position_below_one_share() {
+        let pool = setup_test_db().await;
+        let rules = ExecutionRules {
+            broker_type: st0x_broker::SupportedBroker::Schwab,
+            convention: &SymbolConvention::default(),
+            blackout: &BlackoutCalendar::default(),
+            dedup_window: None,
+            liquidity: &LiquidityLimits::default(),
+            trade_side: TradeSide::Both,
+            rounding: ShareRounding::Truncate,
+            fractional_shares: false,
+        };
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(0.4)).build();
+        let mut sql_tx = pool.begin().await.unwrap();
+        let executions = process_onchain_trade(&mut sql_tx, trade, rules, true, false, None)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+        assert!(executions.is_empty());
+
+        // Whole-share brokers keep waiting, fractional ones execute the fraction
+        assert!(
+            check_all_accumulated_positions(&pool, rules)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                fractional_shares: true,
+                ..rules
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(executions.len(), 1);
+        assert_eq!(
+            find_fractional_shares(&pool, executions[0].id.unwrap())
+                .await
+                .unwrap(),
+            Some(dec!(0.4))
+        );
+        let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.net_position(), Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_liquidity_limit_defers_oversized_execution() {
+        let pool = setup_test_db().await;
+        let liquidity = LiquidityLimits::new(OversizeAction::Defer, [(symbol!("AAPL"), 2)]);
+
+        let trade = OnchainTradeBuilder::new().with_amount(dec!(5.0)).build();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let result = process_onchain_trade(
+            &mut sql_tx,
+            trade,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &liquidity,
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+            true,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert!(result.is_empty());
+
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(5.0));
+        assert!(pending.is_none());
+
+        // Within a larger limit the whole position executes
+        let liquidity = LiquidityLimits::new(OversizeAction::Defer, [(symbol!("AAPL"), 10)]);
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &liquidity,
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].shares, Shares::new(5).unwrap());
+    }
+
+    /// Processes an onchain buy of AAPL (hedged by an offchain SELL) and an
+    /// onchain sell of MSFT (hedged by an offchain BUY) under `trade_side`,
+    /// returning the directions that executed.
+    async fn executed_directions(pool: &SqlitePool, trade_side: TradeSide) -> Vec<Direction> {
+        let onchain_buy = OnchainTradeBuilder::new()
+            .with_tx_hash(alloy::primitives::B256::repeat_byte(1))
+            .with_amount(dec!(1.5))
+            .build();
+        let mut onchain_sell = OnchainTradeBuilder::new()
+            .with_tx_hash(alloy::primitives::B256::repeat_byte(2))
+            .with_symbol("MSFT0x")
+            .with_amount(dec!(1.5))
+            .build();
+        onchain_sell.direction = Direction::Sell;
+
+        let mut directions = Vec::new();
+        for trade in [onchain_buy, onchain_sell] {
+            let mut sql_tx = pool.begin().await.unwrap();
+            let execution = process_onchain_trade(
+                &mut sql_tx,
+                trade,
+                ExecutionRules {
+                    broker_type: st0x_broker::SupportedBroker::Schwab,
+                    convention: &SymbolConvention::default(),
+                    blackout: &BlackoutCalendar::default(),
+                    dedup_window: None,
+                    liquidity: &LiquidityLimits::default(),
+                    trade_side,
+                    rounding: ShareRounding::Truncate,
+                    fractional_shares: false,
+                },
+                true,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+            sql_tx.commit().await.unwrap();
+            directions.extend(execution.into_iter().map(|execution| execution.direction));
+        }
+
+        directions
+    }
+
+    #[tokio::test]
+    async fn test_trade_side_both_executes_either_direction() {
+        let pool = setup_test_db().await;
+        assert_eq!(
+            executed_directions(&pool, TradeSide::Both).await,
+            vec![Direction::Sell, Direction::Buy]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trade_side_buy_only_withholds_sells() {
+        let pool = setup_test_db().await;
+        assert_eq!(
+            executed_directions(&pool, TradeSide::BuyOnly).await,
+            vec![Direction::Buy]
+        );
+
+        // The disallowed side stays accumulated without a pending execution
+        let (calculator, pending) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(1.5));
+        assert!(pending.is_none());
+
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::BuyOnly,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(executions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trade_side_sell_only_withholds_buys() {
+        let pool = setup_test_db().await;
+        assert_eq!(
+            executed_directions(&pool, TradeSide::SellOnly).await,
+            vec![Direction::Sell]
+        );
+
+        let (calculator, pending) = find_by_symbol(&pool, "MSFT").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_short, dec!(1.5));
+        assert!(pending.is_none());
+
+        // Allowing both sides again executes what accumulated
+        let executions = check_all_accumulated_positions(
+            &pool,
+            ExecutionRules {
+                broker_type: st0x_broker::SupportedBroker::Schwab,
+                convention: &SymbolConvention::default(),
+                blackout: &BlackoutCalendar::default(),
+                dedup_window: None,
+                liquidity: &LiquidityLimits::default(),
+                trade_side: TradeSide::Both,
+                rounding: ShareRounding::Truncate,
+                fractional_shares: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].symbol, symbol!("MSFT"));
+        assert_eq!(executions[0].direction, Direction::Buy);
+    }
+
+    #[tokio::test]
+    async fn test_accumulation_contributions_sum_to_executed_shares_plus_remainder() {
+        let pool = setup_test_db().await;
+
+        let trades = [(1_u8, dec!(0.6)), (2, dec!(0.3)), (3, dec!(0.4))].map(|(index, amount)| {
+            OnchainTradeBuilder::new()
+                .with_tx_hash(alloy::primitives::B256::repeat_byte(index))
+                .with_amount(amount)
+                .build()
+        });
+
+        let mut executions = Vec::new();
+        for trade in trades {
+            executions.extend(process_trade_with_tx(&pool, trade).await.unwrap());
+        }
+
+        assert_eq!(executions.len(), 1);
+        let execution = &executions[0];
+        assert_eq!(execution.shares, Shares::new(1).unwrap());
+
+        let contributions =
+            AccumulationContribution::find_for_execution(&pool, execution.id.unwrap())
+                .await
+                .unwrap();
+        assert_eq!(contributions.len(), 3);
+
+        let (calculator, _) = find_by_symbol(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(calculator.accumulated_long, dec!(0.3));
+
+        let available: f64 = contributions.iter().map(|c| c.available_shares).sum();
+        let allocated: f64 = contributions.iter().map(|c| c.allocated_shares).sum();
+        assert!((available - 1.3).abs() < 1e-9);
+        assert!((allocated - 1.0).abs() < 1e-9);
+    }
 }
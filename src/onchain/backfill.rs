@@ -1,17 +1,24 @@
 use alloy::providers::Provider;
-use alloy::rpc::types::Filter;
+use alloy::rpc::types::{Filter, Log};
 use alloy::sol_types::SolEvent;
+use alloy::transports::{RpcError, TransportErrorKind};
 use backon::{BackoffBuilder, ExponentialBuilder, Retryable};
 use futures_util::future;
+use futures_util::stream::{self, StreamExt};
 use itertools::Itertools;
 use sqlx::SqlitePool;
+use std::num::NonZeroU64;
 use std::time::Duration;
-use tracing::{debug, info, trace, warn};
+use tracing::{debug, info, trace};
 
 use super::EvmEnv;
+use super::backfill_checkpoint::{
+    get_backfill_checkpoint, record_backfill_checkpoint_within_transaction,
+};
 use crate::bindings::IOrderBookV4::{ClearV2, TakeOrderV2};
 use crate::error::OnChainError;
-use crate::queue::enqueue;
+use crate::queue::enqueue_within_transaction;
+use crate::rpc_metrics::{RpcMethod, instrumented};
 
 fn get_backfill_retry_strat() -> ExponentialBuilder {
     const BACKFILL_MAX_RETRIES: usize = 15;
@@ -49,27 +56,71 @@ async fn backfill_events_with_retry_strat<P: Provider + Clone, B: BackoffBuilder
     end_block: u64,
     retry_strategy: B,
 ) -> Result<(), OnChainError> {
-    // Query the last processed block from event_queue to determine start point
-    let start_block = crate::queue::get_max_processed_block(pool)
-        .await?
-        .map_or_else(
-            || {
-                info!(
-                    "Starting initial backfill from deployment block {}",
+    let start_block = match get_backfill_checkpoint(pool, evm_env.orderbook).await? {
+        Some(checkpoint) => {
+            let resume_block = evm_env.deployment_block.max(checkpoint + 1);
+            info!(
+                "Resuming backfill from block {} (checkpoint: {})",
+                resume_block, checkpoint
+            );
+            resume_block
+        }
+        // Databases from before the checkpoint was recorded fall back to the
+        // last processed block in event_queue
+        None => crate::queue::get_max_processed_block(pool)
+            .await?
+            .map_or_else(
+                || {
+                    info!(
+                        "Starting initial backfill from deployment block {}",
+                        evm_env.deployment_block
+                    );
                     evm_env.deployment_block
-                );
-                evm_env.deployment_block
-            },
-            |max_block| {
-                let resume_block = max_block + 1;
-                info!(
-                    "Resuming backfill from block {} (last processed: {})",
-                    resume_block, max_block
-                );
-                resume_block
-            },
-        );
+                },
+                |max_block| {
+                    let resume_block = max_block + 1;
+                    info!(
+                        "Resuming backfill from block {} (last processed: {})",
+                        resume_block, max_block
+                    );
+                    resume_block
+                },
+            ),
+    };
+
+    backfill_range_with_retry_strat(
+        pool,
+        provider,
+        evm_env,
+        start_block,
+        end_block,
+        retry_strategy,
+    )
+    .await
+}
 
+/// Backfills exactly `start_block..=end_block`, regardless of what has already
+/// been processed, e.g. to resync a gap detected from the last-seen block.
+pub(crate) async fn backfill_block_range<P: Provider + Clone>(
+    pool: &SqlitePool,
+    provider: &P,
+    evm_env: &EvmEnv,
+    start_block: u64,
+    end_block: u64,
+) -> Result<(), OnChainError> {
+    let retry_strat = get_backfill_retry_strat();
+    backfill_range_with_retry_strat(pool, provider, evm_env, start_block, end_block, retry_strat)
+        .await
+}
+
+async fn backfill_range_with_retry_strat<P: Provider + Clone, B: BackoffBuilder + Clone>(
+    pool: &SqlitePool,
+    provider: &P,
+    evm_env: &EvmEnv,
+    start_block: u64,
+    end_block: u64,
+    retry_strategy: B,
+) -> Result<(), OnChainError> {
     // Skip if we're already caught up
     if start_block > end_block {
         info!(
@@ -86,36 +137,35 @@ async fn backfill_events_with_retry_strat<P: Provider + Clone, B: BackoffBuilder
         start_block, end_block, total_blocks
     );
 
-    let batch_ranges = generate_batch_ranges(start_block, end_block);
+    const CONCURRENT_BATCH_FETCHES: usize = 8;
 
-    let batch_tasks = batch_ranges
-        .into_iter()
+    let batch_ranges = generate_batch_ranges(start_block, end_block, evm_env.backfill_batch_size);
+
+    // Logs are fetched concurrently, but batches are committed strictly in
+    // block order so the checkpoint never moves past an uncommitted batch
+    let mut batches = stream::iter(batch_ranges)
         .map(|(batch_start, batch_end)| {
-            enqueue_batch_events(
-                pool,
-                provider,
-                evm_env,
-                batch_start,
-                batch_end,
-                retry_strategy.clone(),
-            )
+            let retry_strategy = retry_strategy.clone();
+            async move {
+                fetch_batch_events(provider, evm_env, batch_start, batch_end, retry_strategy)
+                    .await
+                    .map(|events| (batch_end, events))
+            }
         })
-        .collect::<Vec<_>>();
-
-    let batch_results = future::join_all(batch_tasks).await;
+        .buffered(CONCURRENT_BATCH_FETCHES);
 
-    let total_enqueued = batch_results
-        .into_iter()
-        .collect::<Result<Vec<_>, _>>()?
-        .into_iter()
-        .sum::<usize>();
+    let mut total_enqueued = 0;
+    while let Some(batch) = batches.next().await {
+        let (batch_end, events) = batch?;
+        total_enqueued += commit_batch_events(pool, evm_env, batch_end, events).await?;
+    }
 
     info!("Backfill completed: {total_enqueued} events enqueued");
 
     Ok(())
 }
 
-#[tracing::instrument(skip(pool, provider, evm_env, retry_strategy), fields(batch_start, batch_end), level = tracing::Level::DEBUG)]
+#[cfg(test)]
 async fn enqueue_batch_events<P: Provider + Clone, B: BackoffBuilder + Clone>(
     pool: &SqlitePool,
     provider: &P,
@@ -124,47 +174,42 @@ async fn enqueue_batch_events<P: Provider + Clone, B: BackoffBuilder + Clone>(
     batch_end: u64,
     retry_strategy: B,
 ) -> Result<usize, OnChainError> {
+    let events =
+        fetch_batch_events(provider, evm_env, batch_start, batch_end, retry_strategy).await?;
+    commit_batch_events(pool, evm_env, batch_end, events).await
+}
+
+#[tracing::instrument(skip(provider, evm_env, retry_strategy), fields(batch_start, batch_end), level = tracing::Level::DEBUG)]
+async fn fetch_batch_events<P: Provider + Clone, B: BackoffBuilder + Clone>(
+    provider: &P,
+    evm_env: &EvmEnv,
+    batch_start: u64,
+    batch_end: u64,
+    retry_strategy: B,
+) -> Result<Vec<(EventData, Log)>, OnChainError> {
     let clear_filter = Filter::new()
         .address(evm_env.orderbook)
-        .from_block(batch_start)
-        .to_block(batch_end)
         .event_signature(ClearV2::SIGNATURE_HASH);
 
     let take_filter = Filter::new()
         .address(evm_env.orderbook)
-        .from_block(batch_start)
-        .to_block(batch_end)
         .event_signature(TakeOrderV2::SIGNATURE_HASH);
 
-    // Use the provided retry strategy
-
-    let provider_clear = provider.clone();
-    let provider_take = provider.clone();
-    let clear_filter_clone = clear_filter.clone();
-    let take_filter_clone = take_filter.clone();
-
-    let get_clear_logs = move || {
-        let provider = provider_clear.clone();
-        let filter = clear_filter_clone.clone();
-        async move { provider.get_logs(&filter).await }
-    };
-    let get_take_logs = move || {
-        let provider = provider_take.clone();
-        let filter = take_filter_clone.clone();
-        async move { provider.get_logs(&filter).await }
-    };
-
     let (clear_logs, take_logs) = future::try_join(
-        get_clear_logs
-            .retry(retry_strategy.clone().build())
-            .notify(|err, dur| {
-                trace!("Retrying clear_logs for blocks between {batch_start}-{batch_end} after error: {err} (waiting {dur:?})");
-            }),
-        get_take_logs
-            .retry(retry_strategy.build())
-            .notify(|err, dur| {
-                trace!("Retrying take_logs for blocks between {batch_start}-{batch_end} after error: {err} (waiting {dur:?})");
-            }),
+        get_logs_splitting_range(
+            provider,
+            &clear_filter,
+            batch_start,
+            batch_end,
+            retry_strategy.clone(),
+        ),
+        get_logs_splitting_range(
+            provider,
+            &take_filter,
+            batch_start,
+            batch_end,
+            retry_strategy,
+        ),
     )
     .await?;
 
@@ -181,7 +226,7 @@ async fn enqueue_batch_events<P: Provider + Clone, B: BackoffBuilder + Clone>(
         .chain(take_logs.into_iter())
         .collect::<Vec<_>>();
 
-    let enqueue_tasks = all_logs
+    let events = all_logs
         .into_iter()
         .sorted_by_key(|log| (log.block_number, log.log_index))
         .filter_map(|log| {
@@ -197,42 +242,149 @@ async fn enqueue_batch_events<P: Provider + Clone, B: BackoffBuilder + Clone>(
                 None
             }
         })
-        .map(|(event_data, log)| async move {
-            match event_data {
-                EventData::ClearV2(event) => match enqueue(pool, &*event, &log).await {
-                    Ok(()) => Some(()),
-                    Err(e) => {
-                        warn!("Failed to enqueue ClearV2 event during backfill: {e}");
-                        None
-                    }
-                },
-                EventData::TakeOrderV2(event) => match enqueue(pool, &*event, &log).await {
-                    Ok(()) => Some(()),
-                    Err(e) => {
-                        warn!("Failed to enqueue TakeOrderV2 event during backfill: {e}");
-                        None
-                    }
-                },
+        .collect();
+
+    Ok(events)
+}
+
+/// Enqueues a batch's events and advances the backfill checkpoint to
+/// `batch_end` in a single transaction, so a batch is either fully recorded
+/// or refetched on the next run.
+#[tracing::instrument(skip(pool, evm_env, events), fields(batch_end), level = tracing::Level::DEBUG)]
+async fn commit_batch_events(
+    pool: &SqlitePool,
+    evm_env: &EvmEnv,
+    batch_end: u64,
+    events: Vec<(EventData, Log)>,
+) -> Result<usize, OnChainError> {
+    let enqueued_count = events.len();
+    let mut sql_tx = pool.begin().await?;
+
+    for (event_data, log) in events {
+        match event_data {
+            EventData::ClearV2(event) => {
+                enqueue_within_transaction(&mut sql_tx, &*event, &log).await?;
+            }
+            EventData::TakeOrderV2(event) => {
+                enqueue_within_transaction(&mut sql_tx, &*event, &log).await?;
             }
+        }
+    }
+
+    record_backfill_checkpoint_within_transaction(&mut sql_tx, evm_env.orderbook, batch_end)
+        .await?;
+    sql_tx.commit().await?;
+
+    Ok(enqueued_count)
+}
+
+/// Fetches logs for `filter` over `from_block..=to_block`, halving the range
+/// whenever the provider reports it as too large or too dense.
+///
+/// Transient errors are retried with `retry_strategy`, see [`is_retryable`];
+/// a range that cannot be split any further (a single block) is returned as an
+/// error.
+async fn get_logs_splitting_range<P: Provider + Clone, B: BackoffBuilder + Clone>(
+    provider: &P,
+    filter: &Filter,
+    from_block: u64,
+    to_block: u64,
+    retry_strategy: B,
+) -> Result<Vec<Log>, OnChainError> {
+    let range_filter = filter.clone().from_block(from_block).to_block(to_block);
+
+    let get_logs =
+        || async { instrumented(RpcMethod::GetLogs, provider.get_logs(&range_filter)).await };
+
+    let result = get_logs
+        .retry(retry_strategy.clone().build())
+        .when(is_retryable)
+        .notify(|err, dur| {
+            trace!("Retrying get_logs for blocks between {from_block}-{to_block} after error: {err} (waiting {dur:?})");
         })
-        .collect::<Vec<_>>();
+        .await;
 
-    let enqueue_results = future::join_all(enqueue_tasks).await;
+    match result {
+        Err(err) if is_range_too_large(&err) && from_block < to_block => {
+            let mid_block = from_block + (to_block - from_block) / 2;
+            debug!(
+                "Block range {from_block}-{to_block} rejected ({err}), splitting at {mid_block}"
+            );
+
+            let mut lower = Box::pin(get_logs_splitting_range(
+                provider,
+                filter,
+                from_block,
+                mid_block,
+                retry_strategy.clone(),
+            ))
+            .await?;
+            let upper = Box::pin(get_logs_splitting_range(
+                provider,
+                filter,
+                mid_block + 1,
+                to_block,
+                retry_strategy,
+            ))
+            .await?;
+
+            lower.extend(upper);
+            Ok(lower)
+        }
+        Err(err) => Err(err.into()),
+        Ok(logs) => Ok(logs),
+    }
+}
 
-    let enqueued_count = enqueue_results.into_iter().flatten().count();
+/// Whether a failed `eth_getLogs` call may succeed when repeated as is.
+///
+/// Malformed requests or responses and calls the provider rejects as invalid
+/// fail the same way every time, and ranges that are too large are split
+/// instead.
+fn is_retryable(err: &RpcError<TransportErrorKind>) -> bool {
+    const METHOD_NOT_FOUND: i64 = -32601;
+    const INVALID_PARAMS: i64 = -32602;
+
+    if is_range_too_large(err) {
+        return false;
+    }
 
-    Ok(enqueued_count)
+    match err {
+        RpcError::SerError(_) | RpcError::DeserError { .. } | RpcError::UnsupportedFeature(_) => {
+            false
+        }
+        RpcError::ErrorResp(payload) => !matches!(payload.code, METHOD_NOT_FOUND | INVALID_PARAMS),
+        _ => true,
+    }
 }
 
-fn generate_batch_ranges(start_block: u64, end_block: u64) -> Vec<(u64, u64)> {
-    const BACKFILL_BATCH_SIZE: usize = 1_000;
+/// Whether the RPC error means the requested block range or its result set
+/// exceeds the provider's `eth_getLogs` limits.
+fn is_range_too_large(err: &RpcError<TransportErrorKind>) -> bool {
+    const RANGE_TOO_LARGE_MARKERS: [&str; 4] = [
+        "range too large",
+        "too many results",
+        "block range",
+        "query returned more than",
+    ];
+
+    let message = err.to_string().to_lowercase();
+    RANGE_TOO_LARGE_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+}
 
+fn generate_batch_ranges(
+    start_block: u64,
+    end_block: u64,
+    batch_size: NonZeroU64,
+) -> Vec<(u64, u64)> {
     (start_block..=end_block)
-        .step_by(BACKFILL_BATCH_SIZE)
+        .step_by(usize::try_from(batch_size.get()).unwrap_or(usize::MAX))
         .map(|batch_start| {
-            let batch_end = (batch_start + u64::try_from(BACKFILL_BATCH_SIZE).unwrap_or(u64::MAX)
-                - 1)
-            .min(end_block);
+            let batch_end = batch_start
+                .saturating_add(batch_size.get() - 1)
+                .min(end_block);
             (batch_start, batch_end)
         })
         .collect()
@@ -251,7 +403,9 @@ mod tests {
     use super::*;
     use crate::bindings::IERC20::symbolCall;
     use crate::bindings::IOrderBookV4;
-    use crate::onchain::EvmEnv;
+    use crate::onchain::io::SymbolConvention;
+    use crate::onchain::last_seen_block::{BlockGap, detect_block_gap, record_last_seen_block};
+    use crate::onchain::{DEFAULT_BACKFILL_BATCH_SIZE, EvmEnv};
     use crate::test_utils::{get_test_order, setup_test_db};
 
     fn test_retry_strategy() -> ExponentialBuilder {
@@ -265,7 +419,6 @@ mod tests {
     async fn test_backfill_events_empty_results() {
         let pool = setup_test_db().await;
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64)); // get_block_number
         asserter.push_success(&serde_json::json!([])); // clear events
         asserter.push_success(&serde_json::json!([])); // take events
 
@@ -275,6 +428,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         backfill_events(&pool, &provider, &evm_env, 100)
@@ -287,19 +442,19 @@ mod tests {
 
     #[test]
     fn test_generate_batch_ranges_single_batch() {
-        let ranges = generate_batch_ranges(100, 500);
+        let ranges = generate_batch_ranges(100, 500, DEFAULT_BACKFILL_BATCH_SIZE);
         assert_eq!(ranges, vec![(100, 500)]);
     }
 
     #[test]
     fn test_generate_batch_ranges_exact_batch_size() {
-        let ranges = generate_batch_ranges(100, 1099);
+        let ranges = generate_batch_ranges(100, 1099, DEFAULT_BACKFILL_BATCH_SIZE);
         assert_eq!(ranges, vec![(100, 1099)]);
     }
 
     #[test]
     fn test_generate_batch_ranges_multiple_batches() {
-        let ranges = generate_batch_ranges(100, 25000);
+        let ranges = generate_batch_ranges(100, 25000, DEFAULT_BACKFILL_BATCH_SIZE);
         assert_eq!(
             ranges,
             vec![
@@ -334,16 +489,119 @@ mod tests {
 
     #[test]
     fn test_generate_batch_ranges_single_block() {
-        let ranges = generate_batch_ranges(42, 42);
+        let ranges = generate_batch_ranges(42, 42, DEFAULT_BACKFILL_BATCH_SIZE);
         assert_eq!(ranges, vec![(42, 42)]);
     }
 
     #[test]
     fn test_generate_batch_ranges_empty() {
-        let ranges = generate_batch_ranges(100, 99);
+        let ranges = generate_batch_ranges(100, 99, DEFAULT_BACKFILL_BATCH_SIZE);
         assert_eq!(ranges.len(), 0);
     }
 
+    #[test]
+    fn test_generate_batch_ranges_custom_batch_size() {
+        let ranges = generate_batch_ranges(1, 25_000, NonZeroU64::new(10_000).unwrap());
+        assert_eq!(
+            ranges,
+            vec![(1, 10_000), (10_001, 20_000), (20_001, 25_000)]
+        );
+    }
+
+    #[test]
+    fn test_is_range_too_large() {
+        let too_large =
+            RpcError::<TransportErrorKind>::local_usage_str("query exceeds max block range 500");
+        let too_many = RpcError::<TransportErrorKind>::local_usage_str(
+            "Log response size exceeded: too many results",
+        );
+        let unrelated = RpcError::<TransportErrorKind>::local_usage_str("connection reset");
+
+        assert!(is_range_too_large(&too_large));
+        assert!(is_range_too_large(&too_many));
+        assert!(!is_range_too_large(&unrelated));
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_splits_range_rejected_as_too_large() {
+        let lower_log: Log = Log {
+            block_number: Some(250),
+            ..Default::default()
+        };
+        let upper_log: Log = Log {
+            block_number: Some(750),
+            ..Default::default()
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_failure_msg("block range too large, max is 500"); // 1-1000
+        asserter.push_success(&serde_json::json!([lower_log])); // 1-500
+        asserter.push_success(&serde_json::json!([upper_log])); // 501-1000
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let filter = Filter::new().event_signature(ClearV2::SIGNATURE_HASH);
+
+        let logs = get_logs_splitting_range(&provider, &filter, 1, 1_000, test_retry_strategy())
+            .await
+            .unwrap();
+
+        let blocks = logs.iter().map(|log| log.block_number).collect::<Vec<_>>();
+        assert_eq!(blocks, vec![Some(250), Some(750)]);
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_retries_transient_errors() {
+        let log: Log = Log {
+            block_number: Some(150),
+            ..Default::default()
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_failure_msg("connection reset by peer");
+        asserter.push_failure_msg("upstream timeout");
+        asserter.push_success(&serde_json::json!([log]));
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let filter = Filter::new().event_signature(ClearV2::SIGNATURE_HASH);
+
+        let logs = get_logs_splitting_range(&provider, &filter, 100, 200, test_retry_strategy())
+            .await
+            .unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].block_number, Some(150));
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_does_not_retry_undecodable_response() {
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::json!("not a list of logs"));
+        // Only reached if the decode error were retried
+        asserter.push_success(&serde_json::json!([]));
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let filter = Filter::new().event_signature(ClearV2::SIGNATURE_HASH);
+
+        let result =
+            get_logs_splitting_range(&provider, &filter, 100, 200, test_retry_strategy()).await;
+
+        assert!(matches!(result.unwrap_err(), OnChainError::Alloy(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_single_block_too_large_is_an_error() {
+        let asserter = Asserter::new();
+        asserter.push_failure_msg("too many results");
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let filter = Filter::new().event_signature(ClearV2::SIGNATURE_HASH);
+
+        let result =
+            get_logs_splitting_range(&provider, &filter, 42, 42, test_retry_strategy()).await;
+
+        assert!(matches!(result.unwrap_err(), OnChainError::Alloy(_)));
+    }
+
     #[tokio::test]
     async fn test_backfill_events_with_clear_v2_events() {
         let pool = setup_test_db().await;
@@ -353,6 +611,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let tx_hash =
@@ -389,7 +649,6 @@ mod tests {
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64)); // get_block_number
         asserter.push_success(&serde_json::json!([clear_log])); // clear events
         asserter.push_success(&serde_json::json!([])); // take events (empty)
 
@@ -418,6 +677,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let take_event = IOrderBookV4::TakeOrderV2 {
@@ -449,7 +710,6 @@ mod tests {
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64)); // get_block_number
         asserter.push_success(&serde_json::json!([])); // clear events (empty)
         asserter.push_success(&serde_json::json!([take_log])); // take events
         asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
@@ -484,6 +744,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let different_order = get_test_order();
@@ -518,7 +780,6 @@ mod tests {
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64)); // get_block_number
         asserter.push_success(&serde_json::json!([clear_log])); // clear events
         asserter.push_success(&serde_json::json!([])); // take events (empty)
 
@@ -541,6 +802,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
@@ -572,10 +835,11 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 50,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64)); // get_block_number
         asserter.push_success(&serde_json::json!([])); // clear events
         asserter.push_success(&serde_json::json!([])); // take events
 
@@ -637,6 +901,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let tx_hash1 =
@@ -651,7 +917,6 @@ mod tests {
         let take_log2 = create_test_log(evm_env.orderbook, &take_event2, 100, tx_hash2);
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(200u64));
         asserter.push_success(&serde_json::json!([]));
         asserter.push_success(&serde_json::json!([take_log2, take_log1]));
 
@@ -704,10 +969,11 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1000,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(2500u64));
 
         // Batch 1: blocks 1000-1999
         asserter.push_success(&serde_json::json!([]));
@@ -735,10 +1001,11 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 500,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(1900u64));
 
         // Batch 1: blocks 500-1499
         asserter.push_success(&serde_json::json!([]));
@@ -774,6 +1041,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let tx_hash =
@@ -808,10 +1077,11 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 100,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64));
         asserter.push_success(&serde_json::json!([]));
         asserter.push_success(&serde_json::json!([]));
 
@@ -833,10 +1103,11 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(3000u64));
 
         for _ in 0..6 {
             asserter.push_success(&serde_json::json!([]));
@@ -859,6 +1130,41 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    #[tokio::test]
+    async fn test_backfill_events_batch_size_of_one() {
+        let pool = setup_test_db().await;
+        let evm_env = EvmEnv {
+            ws_rpc_url: url::Url::parse("ws://localhost:8545").unwrap(),
+            orderbook: address!("0x1111111111111111111111111111111111111111"),
+            order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            deployment_block: 10,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: NonZeroU64::MIN,
+        };
+
+        let asserter = Asserter::new();
+
+        // One clear and one take request for each of blocks 10, 11 and 12
+        for _ in 0..3 {
+            asserter.push_success(&serde_json::json!([]));
+            asserter.push_success(&serde_json::json!([]));
+        }
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+        backfill_events_with_retry_strat(&pool, &provider, &evm_env, 12, test_retry_strategy())
+            .await
+            .unwrap();
+
+        assert!(asserter.read_q().is_empty());
+        assert_eq!(
+            get_backfill_checkpoint(&pool, evm_env.orderbook)
+                .await
+                .unwrap(),
+            Some(12)
+        );
+    }
+
     #[tokio::test]
     async fn test_backfill_events_deployment_after_current_block() {
         let pool = setup_test_db().await;
@@ -867,6 +1173,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 200,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
@@ -891,6 +1199,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let valid_take_event = create_test_take_event(&order, 100_000_000, "9000000000000000000");
@@ -911,7 +1221,6 @@ mod tests {
             create_test_log(evm_env.orderbook, &invalid_take_event, 51, invalid_tx_hash);
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64));
         asserter.push_success(&serde_json::json!([]));
         asserter.push_success(&serde_json::json!([valid_log, invalid_log]));
 
@@ -935,6 +1244,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let tx_hash1 =
@@ -1000,28 +1311,27 @@ mod tests {
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(150u64)); // 1. get_block_number
-        asserter.push_success(&serde_json::json!([clear_log])); // 2. get_logs clear
-        asserter.push_success(&serde_json::json!([take_log])); // 3. get_logs take
+        asserter.push_success(&serde_json::json!([clear_log])); // 1. get_logs clear
+        asserter.push_success(&serde_json::json!([take_log])); // 2. get_logs take
 
         // Take event processing (processed first due to earlier block)
         asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
-            // 4. symbol input
+            // 3. symbol input
             &"USDC".to_string(),
         ));
         asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
-            // 5. symbol output
+            // 4. symbol output
             &"AAPL0x".to_string(),
         ));
 
         // Clear event processing (processed second due to later block)
-        asserter.push_success(&serde_json::json!([after_clear_log])); // 6. get_logs AfterClear
+        asserter.push_success(&serde_json::json!([after_clear_log])); // 5. get_logs AfterClear
         asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
-            // 7. symbol input
+            // 6. symbol input
             &"USDC".to_string(),
         ));
         asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
-            // 8. symbol output
+            // 7. symbol output
             &"AAPL0x".to_string(),
         ));
 
@@ -1060,6 +1370,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
@@ -1087,6 +1399,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
@@ -1116,6 +1430,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
@@ -1158,6 +1474,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         // Create malformed log with invalid event signature
@@ -1180,7 +1498,6 @@ mod tests {
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(100u64));
         asserter.push_success(&serde_json::json!([corrupted_log]));
         asserter.push_success(&serde_json::json!([]));
 
@@ -1203,10 +1520,11 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 42,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
-        asserter.push_success(&serde_json::Value::from(42u64));
         asserter.push_success(&serde_json::json!([]));
         asserter.push_success(&serde_json::json!([]));
 
@@ -1228,6 +1546,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let order = get_test_order();
@@ -1251,10 +1571,9 @@ mod tests {
         let result =
             enqueue_batch_events(&pool, &provider, &evm_env, 100, 200, test_retry_strategy()).await;
 
-        // Should succeed at RPC level but fail at database level
-        // The function handles enqueue failures gracefully by continuing
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 0); // No events successfully enqueued
+        // Should succeed at RPC level but fail at database level, so the batch
+        // is reported as failed instead of being silently skipped
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -1265,6 +1584,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
@@ -1287,6 +1608,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let order = get_test_order();
@@ -1330,6 +1653,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let order = get_test_order();
@@ -1375,6 +1700,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let asserter = Asserter::new();
@@ -1408,6 +1735,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 100,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         // No RPC calls should be made when deployment block > end block
@@ -1429,6 +1758,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let order = get_test_order();
@@ -1511,6 +1842,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 50, // Earlier than processed block
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         // Mock provider should only receive requests for blocks 101-200, not 50-200
@@ -1526,6 +1859,217 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_backfill_resyncs_gap_after_last_seen_block() {
+        let pool = setup_test_db().await;
+        let evm_env = EvmEnv {
+            ws_rpc_url: url::Url::parse("ws://localhost:8545").unwrap(),
+            orderbook: address!("0x1111111111111111111111111111111111111111"),
+            order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
+        };
+
+        // The live stream saw block 100 before the bot went down and resumes
+        // at block 150 after the restart
+        record_last_seen_block(&pool, 100).await.unwrap();
+        let gap = detect_block_gap(&pool, 150).await.unwrap();
+        assert_eq!(
+            gap,
+            BlockGap::Missed {
+                start: 101,
+                end: 149
+            }
+        );
+
+        let order = get_test_order();
+        let clear_event = IOrderBookV4::ClearV2 {
+            sender: address!("0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef"),
+            alice: order.clone(),
+            bob: order,
+            clearConfig: IOrderBookV4::ClearConfig {
+                aliceInputIOIndex: U256::from(0),
+                aliceOutputIOIndex: U256::from(1),
+                bobInputIOIndex: U256::from(1),
+                bobOutputIOIndex: U256::from(0),
+                aliceBountyVaultId: U256::ZERO,
+                bobBountyVaultId: U256::ZERO,
+            },
+        };
+        let missed_log = Log {
+            inner: alloy::primitives::Log {
+                address: evm_env.orderbook,
+                data: clear_event.to_log_data(),
+            },
+            block_hash: None,
+            block_number: Some(120),
+            block_timestamp: None,
+            transaction_hash: Some(fixed_bytes!(
+                "0xbeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
+            )),
+            transaction_index: None,
+            log_index: Some(1),
+            removed: false,
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::json!([missed_log])); // clear events for 101-149
+        asserter.push_success(&serde_json::json!([])); // take events for 101-149
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let BlockGap::Missed { start, end } = gap else {
+            unreachable!()
+        };
+        backfill_range_with_retry_strat(
+            &pool,
+            &provider,
+            &evm_env,
+            start,
+            end,
+            test_retry_strategy(),
+        )
+        .await
+        .unwrap();
+
+        let queued_event = get_next_unprocessed_event(&pool).await.unwrap().unwrap();
+        assert_eq!(queued_event.block_number, 120);
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_restart_resumes_after_checkpoint() {
+        let pool = setup_test_db().await;
+        let evm_env = EvmEnv {
+            ws_rpc_url: url::Url::parse("ws://localhost:8545").unwrap(),
+            orderbook: address!("0x1111111111111111111111111111111111111111"),
+            order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
+        };
+
+        let order = get_test_order();
+        let first_event = create_test_take_event(&order, 100_000_000, "9000000000000000000");
+        let first_log = create_test_log(
+            evm_env.orderbook,
+            &first_event,
+            50,
+            fixed_bytes!("0x1111111111111111111111111111111111111111111111111111111111111111"),
+        );
+        let second_event = create_test_take_event(&order, 200_000_000, "18000000000000000000");
+        let second_log = create_test_log(
+            evm_env.orderbook,
+            &second_event,
+            1_500,
+            fixed_bytes!("0x2222222222222222222222222222222222222222222222222222222222222222"),
+        );
+
+        // First run: only batch 1-1000 can be fetched before the RPC goes away
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::json!([])); // clear events for 1-1000
+        asserter.push_success(&serde_json::json!([first_log])); // take events for 1-1000
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let result = backfill_events_with_retry_strat(
+            &pool,
+            &provider,
+            &evm_env,
+            3_000,
+            test_retry_strategy(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(
+            get_backfill_checkpoint(&pool, evm_env.orderbook)
+                .await
+                .unwrap(),
+            Some(1_000)
+        );
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 1);
+
+        // After the restart only batches 1001-2000 and 2001-3000 are fetched;
+        // refetching batch 1-1000 would exhaust the mocked responses
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::json!([])); // clear events for 1001-2000
+        asserter.push_success(&serde_json::json!([second_log])); // take events for 1001-2000
+        asserter.push_success(&serde_json::json!([])); // clear events for 2001-3000
+        asserter.push_success(&serde_json::json!([])); // take events for 2001-3000
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        backfill_events_with_retry_strat(&pool, &provider, &evm_env, 3_000, test_retry_strategy())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_backfill_checkpoint(&pool, evm_env.orderbook)
+                .await
+                .unwrap(),
+            Some(3_000)
+        );
+
+        let first_queued = get_next_unprocessed_event(&pool).await.unwrap().unwrap();
+        assert_eq!(first_queued.block_number, 50);
+        let mut sql_tx = pool.begin().await.unwrap();
+        mark_event_processed(&mut sql_tx, first_queued.id.unwrap())
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let second_queued = get_next_unprocessed_event(&pool).await.unwrap().unwrap();
+        assert_eq!(second_queued.block_number, 1_500);
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_checkpoint_takes_precedence_over_processed_block() {
+        let pool = setup_test_db().await;
+
+        // A processed live event far ahead of the backfill must not make the
+        // backfill skip the blocks between its checkpoint and that event
+        sqlx::query!(
+            r#"
+            INSERT INTO event_queue (tx_hash, log_index, block_number, event_data, processed)
+            VALUES ('0x1111111111111111111111111111111111111111111111111111111111111111', 0, 180, '{}', 1)
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let evm_env = EvmEnv {
+            ws_rpc_url: url::Url::parse("ws://localhost:8545").unwrap(),
+            orderbook: address!("0x1111111111111111111111111111111111111111"),
+            order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            deployment_block: 50,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
+        };
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        record_backfill_checkpoint_within_transaction(&mut sql_tx, evm_env.orderbook, 100)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        // Mock provider should only receive requests for blocks 101-200
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::json!([])); // clear events for 101-200
+        asserter.push_success(&serde_json::json!([])); // take events for 101-200
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        backfill_events(&pool, &provider, &evm_env, 200)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_backfill_checkpoint(&pool, evm_env.orderbook)
+                .await
+                .unwrap(),
+            Some(200)
+        );
+    }
+
     #[tokio::test]
     async fn test_backfill_initial_run_starts_from_deployment() {
         let pool = setup_test_db().await;
@@ -1535,6 +2079,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 50,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         // No processed events exist, should start from deployment_block
@@ -1569,6 +2115,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 50,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         // No RPC calls should be made since we're already caught up
@@ -1605,6 +2153,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         // Should resume from block 101 (max processed block 100 + 1)
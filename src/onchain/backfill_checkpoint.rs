@@ -0,0 +1,116 @@
+//! Persistence of backfill progress per orderbook.
+//!
+//! Each backfill batch records its end block in the same transaction that
+//! enqueues the batch's events, so a restart resumes right after the last
+//! committed batch without skipping or refetching any block.
+
+use alloy::primitives::Address;
+use sqlx::SqlitePool;
+
+use crate::error::EventQueueError;
+
+/// Records `block_number` as the end of the last fully enqueued batch for
+/// `orderbook`, keeping the highest block recorded so far.
+pub(crate) async fn record_backfill_checkpoint_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    orderbook: Address,
+    block_number: u64,
+) -> Result<(), EventQueueError> {
+    let orderbook = orderbook.to_string();
+    let block_number = i64::try_from(block_number).map_err(|_| {
+        EventQueueError::Processing(format!("Block number {block_number} conversion failed"))
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO backfill_checkpoint (orderbook, block_number)
+        VALUES (?1, ?2)
+        ON CONFLICT (orderbook) DO UPDATE SET
+            block_number = MAX(block_number, excluded.block_number),
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        orderbook,
+        block_number
+    )
+    .execute(&mut **sql_tx)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn get_backfill_checkpoint(
+    pool: &SqlitePool,
+    orderbook: Address,
+) -> Result<Option<u64>, EventQueueError> {
+    let orderbook = orderbook.to_string();
+    let block_number = sqlx::query_scalar!(
+        "SELECT block_number FROM backfill_checkpoint WHERE orderbook = ?1",
+        orderbook
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    block_number
+        .map(|block| {
+            u64::try_from(block).map_err(|_| {
+                EventQueueError::Processing(format!("Block number {block} conversion failed"))
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    #[tokio::test]
+    async fn test_backfill_checkpoint_is_per_orderbook_and_monotonic() {
+        let pool = setup_test_db().await;
+        let orderbook = address!("0x1111111111111111111111111111111111111111");
+        let other_orderbook = address!("0x2222222222222222222222222222222222222222");
+        assert_eq!(
+            get_backfill_checkpoint(&pool, orderbook).await.unwrap(),
+            None
+        );
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        record_backfill_checkpoint_within_transaction(&mut sql_tx, orderbook, 200)
+            .await
+            .unwrap();
+        record_backfill_checkpoint_within_transaction(&mut sql_tx, orderbook, 100)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        assert_eq!(
+            get_backfill_checkpoint(&pool, orderbook).await.unwrap(),
+            Some(200)
+        );
+        assert_eq!(
+            get_backfill_checkpoint(&pool, other_orderbook)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backfill_checkpoint_rolled_back_with_transaction() {
+        let pool = setup_test_db().await;
+        let orderbook = address!("0x1111111111111111111111111111111111111111");
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        record_backfill_checkpoint_within_transaction(&mut sql_tx, orderbook, 200)
+            .await
+            .unwrap();
+        sql_tx.rollback().await.unwrap();
+
+        assert_eq!(
+            get_backfill_checkpoint(&pool, orderbook).await.unwrap(),
+            None
+        );
+    }
+}
@@ -10,6 +10,7 @@ use crate::onchain::{
     pyth::FeedIdCache,
     trade::{OnchainTrade, OrderFill},
 };
+use crate::rpc_metrics::{RpcMethod, instrumented};
 use crate::symbol::cache::SymbolCache;
 
 impl OnchainTrade {
@@ -128,7 +129,7 @@ async fn fetch_after_clear_event<P: Provider>(
         .address(env.orderbook)
         .event_signature(AfterClear::SIGNATURE_HASH);
 
-    let after_clear_logs = provider.get_logs(&filter).await?;
+    let after_clear_logs = instrumented(RpcMethod::GetLogs, provider.get_logs(&filter)).await?;
     let after_clear_log = after_clear_logs
         .iter()
         .find(|after_clear_log| {
@@ -145,6 +146,8 @@ mod tests {
     use super::*;
     use crate::bindings::IERC20::symbolCall;
     use crate::bindings::IOrderBookV4::{AfterClear, ClearConfig, ClearStateChange};
+    use crate::onchain::DEFAULT_BACKFILL_BATCH_SIZE;
+    use crate::onchain::io::SymbolConvention;
     use crate::onchain::pyth::FeedIdCache;
     use crate::symbol::cache::SymbolCache;
     use crate::test_utils::{get_test_log, get_test_order};
@@ -154,6 +157,7 @@ mod tests {
     use alloy::providers::{ProviderBuilder, mock::Asserter};
     use alloy::rpc::types::Log;
     use alloy::sol_types::SolCall;
+    use rust_decimal_macros::dec;
     use serde_json::json;
     use std::str::FromStr;
 
@@ -163,6 +167,8 @@ mod tests {
             orderbook: address!("0x1111111111111111111111111111111111111111"),
             order_owner: get_test_order().owner,
             deployment_block: 1,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         }
     }
 
@@ -287,7 +293,7 @@ mod tests {
 
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 9.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(9.0));
         assert_eq!(trade.tx_hash, tx_hash);
         assert_eq!(trade.log_index, 1);
     }
@@ -363,7 +369,7 @@ mod tests {
 
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 9.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(9.0));
         assert_eq!(trade.tx_hash, tx_hash);
         assert_eq!(trade.log_index, 1);
     }
@@ -699,7 +705,7 @@ mod tests {
         // Should process Alice first (alice_hash_matches is checked first)
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 9.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(9.0));
         assert_eq!(trade.tx_hash, tx_hash);
         assert_eq!(trade.log_index, 1);
     }
@@ -815,7 +821,7 @@ mod tests {
 
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 9.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(9.0));
     }
 
     #[tokio::test]
@@ -951,6 +957,6 @@ mod tests {
 
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 9.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(9.0));
     }
 }
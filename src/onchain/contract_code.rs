@@ -0,0 +1,144 @@
+//! Startup check that the configured contracts are deployed.
+//!
+//! An orderbook address without code (a typo, or an RPC on the wrong chain)
+//! never emits events, so the bot would subscribe to filters that never fire
+//! and look healthy while hedging nothing. When enabled, the code at the
+//! orderbook, and optionally the Pyth contract, is fetched before subscribing
+//! and startup fails if there is none.
+
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use alloy::transports::{RpcError, TransportErrorKind};
+use tracing::info;
+
+use super::pyth::BASE_PYTH_CONTRACT_ADDRESS;
+
+/// Which contracts must have code at startup.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ContractCodeCheck {
+    None,
+    Orderbook,
+    OrderbookAndPyth,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ContractCodeError {
+    #[error(
+        "No contract code at the {contract} address {address}; check the address and that the \
+         RPC URL is for the right chain"
+    )]
+    NoCode {
+        contract: &'static str,
+        address: Address,
+    },
+    #[error("Failed to fetch code at the {contract} address {address}: {source}")]
+    Rpc {
+        contract: &'static str,
+        address: Address,
+        #[source]
+        source: RpcError<TransportErrorKind>,
+    },
+}
+
+/// Fails if a contract required by `check` has no code deployed.
+pub(crate) async fn verify_contract_code<P: Provider>(
+    provider: &P,
+    check: ContractCodeCheck,
+    orderbook: Address,
+) -> Result<(), ContractCodeError> {
+    let contracts: &[(&'static str, Address)] = match check {
+        ContractCodeCheck::None => return Ok(()),
+        ContractCodeCheck::Orderbook => &[("orderbook", orderbook)],
+        ContractCodeCheck::OrderbookAndPyth => &[
+            ("orderbook", orderbook),
+            ("Pyth", BASE_PYTH_CONTRACT_ADDRESS),
+        ],
+    };
+
+    for &(contract, address) in contracts {
+        let code =
+            provider
+                .get_code_at(address)
+                .await
+                .map_err(|source| ContractCodeError::Rpc {
+                    contract,
+                    address,
+                    source,
+                })?;
+
+        if code.is_empty() {
+            return Err(ContractCodeError::NoCode { contract, address });
+        }
+
+        info!(
+            "Found {} bytes of {contract} contract code at {address}",
+            code.len()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Bytes, address};
+    use alloy::providers::ProviderBuilder;
+    use alloy::providers::mock::Asserter;
+
+    const ORDERBOOK: Address = address!("0x1111111111111111111111111111111111111111");
+
+    #[tokio::test]
+    async fn test_rejects_orderbook_without_code() {
+        let asserter = Asserter::new();
+        asserter.push_success(&Bytes::new());
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let err = verify_contract_code(&provider, ContractCodeCheck::Orderbook, ORDERBOOK)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ContractCodeError::NoCode { contract: "orderbook", address } if address == ORDERBOOK
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_accepts_orderbook_with_code() {
+        let asserter = Asserter::new();
+        asserter.push_success(&Bytes::from_static(&[0x60, 0x80, 0x60, 0x40]));
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        verify_contract_code(&provider, ContractCodeCheck::Orderbook, ORDERBOOK)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_pyth_without_code_when_checked() {
+        let asserter = Asserter::new();
+        asserter.push_success(&Bytes::from_static(&[0x60, 0x80]));
+        asserter.push_success(&Bytes::new());
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let err = verify_contract_code(&provider, ContractCodeCheck::OrderbookAndPyth, ORDERBOOK)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ContractCodeError::NoCode { contract: "Pyth", address } if address == BASE_PYTH_CONTRACT_ADDRESS
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_skips_check_when_disabled() {
+        // No mocked responses, so any RPC call would fail
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+
+        verify_contract_code(&provider, ContractCodeCheck::None, ORDERBOOK)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,34 @@
+//! Selects which kinds of onchain fills are hedged offchain.
+//!
+//! `ClearV2` events are fills of our orders against another maker's order,
+//! while `TakeOrderV2` events are fills by a taker, and the two can warrant
+//! different treatment. Fills of a type that is not selected are still saved
+//! as onchain trades but never enter the accumulator, so they are not hedged
+//! later either.
+
+use super::trade::TradeEvent;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HedgeEventType {
+    #[value(name = "clear-v2")]
+    ClearV2,
+    #[value(name = "take-order-v2")]
+    TakeOrderV2,
+}
+
+impl HedgeEventType {
+    #[cfg(test)]
+    pub(crate) const ALL: [Self; 2] = [Self::ClearV2, Self::TakeOrderV2];
+
+    pub(crate) const fn of(event: &TradeEvent) -> Self {
+        match event {
+            TradeEvent::ClearV2(_) => Self::ClearV2,
+            TradeEvent::TakeOrderV2(_) => Self::TakeOrderV2,
+        }
+    }
+}
+
+/// Whether trades from `event` should be hedged under `hedged_types`.
+pub(crate) fn is_hedged(hedged_types: &[HedgeEventType], event: &TradeEvent) -> bool {
+    hedged_types.contains(&HedgeEventType::of(event))
+}
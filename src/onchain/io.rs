@@ -1,6 +1,7 @@
 //! Order I/O handling logic for processing symbol pairs, amounts, and trade details.
 //! This module centralizes all logic related to parsing and validating onchain order data.
 
+use rust_decimal::Decimal;
 use std::fmt;
 use std::str::FromStr;
 
@@ -27,58 +28,126 @@ macro_rules! symbol {
     };
 }
 
+/// How a fill whose IO is not USDC paired with one tokenized equity is
+/// handled.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymbolConfigurationMode {
+    /// Skip the fill and keep processing the queue
+    #[default]
+    Lenient,
+    /// Stop processing the queue so the orderbook can be investigated
+    Strict,
+}
+
+/// Suffix of tokenized equity symbols unless configured otherwise
+pub(crate) const DEFAULT_TOKENIZED_SUFFIX: &str = "s1";
+
+/// Stablecoin accepted as the cash leg of a trade unless configured otherwise
+pub(crate) const DEFAULT_STABLECOIN: &str = "USDC";
+
+/// Symbol conventions of the traded tokens: the suffix marking tokenized
+/// equities, next to the `t` prefix and `0x` suffix, and the stablecoins
+/// accepted as the cash leg of a trade.
+#[derive(clap::Args, Debug, Clone, PartialEq, Eq)]
+pub struct SymbolConvention {
+    /// Suffix marking tokenized equity symbols, e.g. `s1` in `NVDAs1`
+    #[clap(long, env, default_value = DEFAULT_TOKENIZED_SUFFIX)]
+    pub tokenized_suffix: String,
+    /// Comma-separated stablecoin symbols accepted as the cash leg of a
+    /// trade, e.g. `USDC,USDC.e`
+    #[clap(long, env, value_delimiter = ',', default_value = DEFAULT_STABLECOIN)]
+    pub stablecoins: Vec<String>,
+}
+
+impl Default for SymbolConvention {
+    fn default() -> Self {
+        Self {
+            tokenized_suffix: DEFAULT_TOKENIZED_SUFFIX.to_string(),
+            stablecoins: vec![DEFAULT_STABLECOIN.to_string()],
+        }
+    }
+}
+
+impl SymbolConvention {
+    pub(crate) fn is_stablecoin(&self, symbol: &str) -> bool {
+        self.stablecoins
+            .iter()
+            .any(|stablecoin| stablecoin == symbol)
+    }
+
+    /// Parses a tokenized equity symbol carrying the configured suffix, the
+    /// `t` prefix or the `0x` suffix.
+    pub(crate) fn parse_tokenized(
+        &self,
+        symbol: &str,
+    ) -> Result<TokenizedEquitySymbol, OnChainError> {
+        TokenizedEquitySymbol::parse_with_suffix(symbol, &self.tokenized_suffix)
+    }
+
+    /// Every symbol a tokenized `base` is recorded under: with the `t` prefix,
+    /// the `0x` suffix or the configured suffix.
+    pub(crate) fn tokenized_variants(&self, base: &Symbol) -> [String; 3] {
+        [
+            format!("t{base}"),
+            format!("{base}0x"),
+            format!("{base}{}", self.tokenized_suffix),
+        ]
+    }
+}
+
 /// Represents a validated number of shares (non-negative)
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct Shares(f64);
+pub(crate) struct Shares(Decimal);
 
 impl Shares {
-    pub(crate) fn new(value: f64) -> Result<Self, TradeValidationError> {
-        if value < 0.0 {
+    pub(crate) fn new(value: Decimal) -> Result<Self, TradeValidationError> {
+        if value < Decimal::ZERO {
             return Err(TradeValidationError::NegativeShares(value));
         }
         Ok(Self(value))
     }
 
-    pub(crate) fn value(self) -> f64 {
+    pub(crate) fn value(self) -> Decimal {
         self.0
     }
 }
 
 /// Represents a validated USDC amount (non-negative)
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct Usdc(f64);
+pub(crate) struct Usdc(Decimal);
 
 impl Usdc {
-    pub(crate) fn new(value: f64) -> Result<Self, TradeValidationError> {
-        if value < 0.0 {
+    pub(crate) fn new(value: Decimal) -> Result<Self, TradeValidationError> {
+        if value < Decimal::ZERO {
             return Err(TradeValidationError::NegativeUsdc(value));
         }
         Ok(Self(value))
     }
 
-    pub(crate) fn value(self) -> f64 {
+    pub(crate) fn value(self) -> Decimal {
         self.0
     }
 }
 
 /// The marker for tokenized equity symbols (can be prefix or suffix)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum TokenizedEquityMarker {
     T,     // "t" (prefix)
     ZeroX, // "0x" (suffix)
-    S1,    // "s1" (suffix)
+    /// Configured suffix, "s1" by default
+    Suffix(String),
 }
 
 impl TokenizedEquityMarker {
-    pub(crate) const fn as_str(self) -> &'static str {
+    pub(crate) fn as_str(&self) -> &str {
         match self {
             Self::T => "t",
             Self::ZeroX => "0x",
-            Self::S1 => "s1",
+            Self::Suffix(suffix) => suffix,
         }
     }
 
-    pub(crate) const fn is_prefix(self) -> bool {
+    pub(crate) const fn is_prefix(&self) -> bool {
         matches!(self, Self::T)
     }
 }
@@ -104,7 +173,13 @@ impl TokenizedEquitySymbol {
     }
 
     /// Creates a new TokenizedEquitySymbol from a string (e.g., "AAPL0x", "tAAPL")
+    /// with the default tokenized suffix, see [`SymbolConvention::parse_tokenized`]
+    /// for a configured one.
     pub(crate) fn parse(symbol: &str) -> Result<Self, OnChainError> {
+        Self::parse_with_suffix(symbol, DEFAULT_TOKENIZED_SUFFIX)
+    }
+
+    fn parse_with_suffix(symbol: &str, tokenized_suffix: &str) -> Result<Self, OnChainError> {
         // Try to extract prefix first
         if let Some(stripped) = symbol.strip_prefix('t') {
             let base = Symbol::new(stripped)?;
@@ -117,9 +192,13 @@ impl TokenizedEquitySymbol {
             return Ok(Self::new(base, TokenizedEquityMarker::ZeroX));
         }
 
-        if let Some(stripped) = symbol.strip_suffix("s1") {
+        let custom_stripped = Some(tokenized_suffix)
+            .filter(|suffix| !suffix.is_empty())
+            .and_then(|suffix| symbol.strip_suffix(suffix));
+        if let Some(stripped) = custom_stripped {
             let base = Symbol::new(stripped)?;
-            return Ok(Self::new(base, TokenizedEquityMarker::S1));
+            let marker = TokenizedEquityMarker::Suffix(tokenized_suffix.to_string());
+            return Ok(Self::new(base, marker));
         }
 
         // No valid marker found
@@ -189,21 +268,25 @@ impl TradeDetails {
     }
     /// Extracts trade details from input/output symbol and amount pairs
     pub(crate) fn try_from_io(
+        convention: &SymbolConvention,
         input_symbol: &str,
-        input_amount: f64,
+        input_amount: Decimal,
         output_symbol: &str,
-        output_amount: f64,
+        output_amount: Decimal,
     ) -> Result<Self, OnChainError> {
         // Determine direction and ticker using existing logic
-        let (ticker, direction) = determine_schwab_trade_details(input_symbol, output_symbol)?;
+        let (ticker, direction) =
+            determine_schwab_trade_details(convention, input_symbol, output_symbol)?;
 
         // Extract equity and USDC amounts based on which symbol is the tokenized equity
-        let (equity_amount_raw, usdc_amount_raw) = if input_symbol == "USDC"
-            && TokenizedEquitySymbol::parse(output_symbol).is_ok()
+        let (equity_amount_raw, usdc_amount_raw) = if convention.is_stablecoin(input_symbol)
+            && convention.parse_tokenized(output_symbol).is_ok()
         {
             // USDC → tokenized equity: output is equity, input is USDC
             (output_amount, input_amount)
-        } else if output_symbol == "USDC" && TokenizedEquitySymbol::parse(input_symbol).is_ok() {
+        } else if convention.is_stablecoin(output_symbol)
+            && convention.parse_tokenized(input_symbol).is_ok()
+        {
             // tokenized equity → USDC: input is equity, output is USDC
             (input_amount, output_amount)
         } else {
@@ -230,25 +313,28 @@ impl TradeDetails {
 
 /// Determines onchain trade direction and ticker based on onchain symbol configuration.
 ///
-/// If the on-chain order has USDC as input and a tokenized stock (0x or s1 suffix) as
-/// output then it means the order received USDC and gave away a tokenized stock,
-/// i.e. sold the tokenized stock onchain.
+/// If the on-chain order has a stablecoin as input and a tokenized stock as
+/// output then it means the order received the stablecoin and gave away a
+/// tokenized stock, i.e. sold the tokenized stock onchain.
 fn determine_schwab_trade_details(
+    convention: &SymbolConvention,
     onchain_input_symbol: &str,
     onchain_output_symbol: &str,
 ) -> Result<(Symbol, Direction), OnChainError> {
-    // USDC input + tokenized stock output = sold tokenized stock onchain
-    if onchain_input_symbol == "USDC" {
-        if let Ok(tokenized) = TokenizedEquitySymbol::parse(onchain_output_symbol) {
-            return Ok((tokenized.base().clone(), Direction::Sell));
-        }
+    // stablecoin input + tokenized stock output = sold tokenized stock onchain
+    if let (true, Ok(tokenized)) = (
+        convention.is_stablecoin(onchain_input_symbol),
+        convention.parse_tokenized(onchain_output_symbol),
+    ) {
+        return Ok((tokenized.base().clone(), Direction::Sell));
     }
 
-    // tokenized stock input + USDC output = bought tokenized stock onchain
-    if onchain_output_symbol == "USDC" {
-        if let Ok(tokenized) = TokenizedEquitySymbol::parse(onchain_input_symbol) {
-            return Ok((tokenized.base().clone(), Direction::Buy));
-        }
+    // tokenized stock input + stablecoin output = bought tokenized stock onchain
+    if let (true, Ok(tokenized)) = (
+        convention.is_stablecoin(onchain_output_symbol),
+        convention.parse_tokenized(onchain_input_symbol),
+    ) {
+        return Ok((tokenized.base().clone(), Direction::Buy));
     }
 
     Err(TradeValidationError::InvalidSymbolConfiguration(
@@ -261,6 +347,17 @@ fn determine_schwab_trade_details(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal_macros::dec;
+
+    /// Tokenized suffixes the suffix tests run with, the default first
+    const SUFFIXES: [&str; 2] = [DEFAULT_TOKENIZED_SUFFIX, "x1"];
+
+    fn with_suffix(suffix: &str) -> SymbolConvention {
+        SymbolConvention {
+            tokenized_suffix: suffix.to_string(),
+            ..SymbolConvention::default()
+        }
+    }
 
     #[test]
     fn test_tokenized_equity_symbol_parse() {
@@ -361,51 +458,51 @@ mod tests {
     #[test]
     fn test_shares_validation() {
         // Test valid shares
-        let shares = Shares::new(100.5).unwrap();
-        assert!((shares.value() - 100.5).abs() < f64::EPSILON);
+        let shares = Shares::new(dec!(100.5)).unwrap();
+        assert_eq!(shares.value(), dec!(100.5));
 
         // Test zero shares (valid)
-        let shares = Shares::new(0.0).unwrap();
-        assert!((shares.value() - 0.0).abs() < f64::EPSILON);
+        let shares = Shares::new(dec!(0.0)).unwrap();
+        assert_eq!(shares.value(), dec!(0.0));
 
         // Test negative shares (invalid)
-        let result = Shares::new(-1.0);
+        let result = Shares::new(dec!(-1.0));
         assert!(matches!(
             result.unwrap_err(),
-            TradeValidationError::NegativeShares(-1.0)
+            TradeValidationError::NegativeShares(value) if value == dec!(-1.0)
         ));
     }
 
     #[test]
     fn test_usdc_validation() {
         // Test valid USDC amount
-        let usdc = Usdc::new(1000.50).unwrap();
-        assert!((usdc.value() - 1000.50).abs() < f64::EPSILON);
+        let usdc = Usdc::new(dec!(1000.50)).unwrap();
+        assert_eq!(usdc.value(), dec!(1000.50));
 
         // Test zero USDC (valid)
-        let usdc = Usdc::new(0.0).unwrap();
-        assert!((usdc.value() - 0.0).abs() < f64::EPSILON);
+        let usdc = Usdc::new(dec!(0.0)).unwrap();
+        assert_eq!(usdc.value(), dec!(0.0));
 
         // Test negative USDC (invalid)
-        let result = Usdc::new(-100.0);
+        let result = Usdc::new(dec!(-100.0));
         assert!(matches!(
             result.unwrap_err(),
-            TradeValidationError::NegativeUsdc(-100.0)
+            TradeValidationError::NegativeUsdc(value) if value == dec!(-100.0)
         ));
     }
 
     #[test]
     fn test_shares_usdc_equality() {
-        let shares1 = Shares::new(100.0).unwrap();
-        let shares2 = Shares::new(100.0).unwrap();
-        let shares3 = Shares::new(200.0).unwrap();
+        let shares1 = Shares::new(dec!(100.0)).unwrap();
+        let shares2 = Shares::new(dec!(100.0)).unwrap();
+        let shares3 = Shares::new(dec!(200.0)).unwrap();
 
         assert_eq!(shares1, shares2);
         assert_ne!(shares1, shares3);
 
-        let usdc1 = Usdc::new(1000.0).unwrap();
-        let usdc2 = Usdc::new(1000.0).unwrap();
-        let usdc3 = Usdc::new(2000.0).unwrap();
+        let usdc1 = Usdc::new(dec!(1000.0)).unwrap();
+        let usdc2 = Usdc::new(dec!(1000.0)).unwrap();
+        let usdc3 = Usdc::new(dec!(2000.0)).unwrap();
 
         assert_eq!(usdc1, usdc2);
         assert_ne!(usdc1, usdc3);
@@ -413,83 +510,129 @@ mod tests {
 
     #[test]
     fn test_determine_schwab_trade_details_usdc_to_0x() {
-        let result = determine_schwab_trade_details("USDC", "AAPL0x").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "USDC", "AAPL0x").unwrap();
         assert_eq!(result.0, symbol!("AAPL"));
         assert_eq!(result.1, Direction::Sell); // Onchain sold AAPL0x for USDC
 
-        let result = determine_schwab_trade_details("USDC", "TSLA0x").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "USDC", "TSLA0x").unwrap();
         assert_eq!(result.0, symbol!("TSLA"));
         assert_eq!(result.1, Direction::Sell); // Onchain sold TSLA0x for USDC
     }
 
     #[test]
-    fn test_determine_schwab_trade_details_usdc_to_s1() {
-        let result = determine_schwab_trade_details("USDC", "NVDAs1").unwrap();
-        assert_eq!(result.0, symbol!("NVDA"));
-        assert_eq!(result.1, Direction::Sell); // Onchain sold NVDAs1 for USDC
+    fn test_determine_schwab_trade_details_usdc_to_suffix() {
+        for suffix in SUFFIXES {
+            let result = determine_schwab_trade_details(
+                &with_suffix(suffix),
+                "USDC",
+                &format!("NVDA{suffix}"),
+            )
+            .unwrap();
+            assert_eq!(result.0, symbol!("NVDA"));
+            assert_eq!(result.1, Direction::Sell); // Onchain sold tokenized NVDA for USDC
+        }
     }
 
     #[test]
     fn test_determine_schwab_trade_details_0x_to_usdc() {
-        let result = determine_schwab_trade_details("AAPL0x", "USDC").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "AAPL0x", "USDC").unwrap();
         assert_eq!(result.0, symbol!("AAPL"));
         assert_eq!(result.1, Direction::Buy); // Onchain bought AAPL0x with USDC
 
-        let result = determine_schwab_trade_details("TSLA0x", "USDC").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "TSLA0x", "USDC").unwrap();
         assert_eq!(result.0, symbol!("TSLA"));
         assert_eq!(result.1, Direction::Buy); // Onchain bought TSLA0x with USDC
     }
 
     #[test]
-    fn test_determine_schwab_trade_details_s1_to_usdc() {
-        let result = determine_schwab_trade_details("NVDAs1", "USDC").unwrap();
-        assert_eq!(result.0, symbol!("NVDA"));
-        assert_eq!(result.1, Direction::Buy); // Onchain bought NVDAs1 with USDC
+    fn test_determine_schwab_trade_details_suffix_to_usdc() {
+        for suffix in SUFFIXES {
+            let result = determine_schwab_trade_details(
+                &with_suffix(suffix),
+                &format!("NVDA{suffix}"),
+                "USDC",
+            )
+            .unwrap();
+            assert_eq!(result.0, symbol!("NVDA"));
+            assert_eq!(result.1, Direction::Buy); // Onchain bought tokenized NVDA with USDC
+        }
+    }
+
+    #[test]
+    fn test_determine_schwab_trade_details_configured_convention() {
+        let convention = SymbolConvention {
+            tokenized_suffix: "x1".to_string(),
+            stablecoins: vec!["USDC".to_string(), "USDC.e".to_string()],
+        };
+
+        let result = determine_schwab_trade_details(&convention, "USDC.e", "NVDAx1").unwrap();
+        assert_eq!(result, (symbol!("NVDA"), Direction::Sell));
+
+        let result = determine_schwab_trade_details(&convention, "tGME", "USDC.e").unwrap();
+        assert_eq!(result, (symbol!("GME"), Direction::Buy));
+
+        // The configured suffix replaces the default one
+        for (input, output) in [("NVDAs1", "USDC"), ("USDT", "AAPL0x")] {
+            let error = determine_schwab_trade_details(&convention, input, output).unwrap_err();
+            assert!(matches!(
+                error,
+                OnChainError::Validation(TradeValidationError::InvalidSymbolConfiguration(_, _))
+            ));
+        }
     }
 
     #[test]
     fn test_determine_schwab_trade_details_usdc_to_t() {
-        let result = determine_schwab_trade_details("USDC", "tGME").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "USDC", "tGME").unwrap();
         assert_eq!(result.0, symbol!("GME"));
         assert_eq!(result.1, Direction::Sell);
 
-        let result = determine_schwab_trade_details("USDC", "tAAPL").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "USDC", "tAAPL").unwrap();
         assert_eq!(result.0, symbol!("AAPL"));
         assert_eq!(result.1, Direction::Sell);
     }
 
     #[test]
     fn test_determine_schwab_trade_details_t_to_usdc() {
-        let result = determine_schwab_trade_details("tGME", "USDC").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "tGME", "USDC").unwrap();
         assert_eq!(result.0, symbol!("GME"));
         assert_eq!(result.1, Direction::Buy);
 
-        let result = determine_schwab_trade_details("tAAPL", "USDC").unwrap();
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "tAAPL", "USDC").unwrap();
         assert_eq!(result.0, symbol!("AAPL"));
         assert_eq!(result.1, Direction::Buy);
     }
 
     #[test]
     fn test_determine_schwab_trade_details_invalid_configurations() {
-        let result = determine_schwab_trade_details("BTC", "ETH");
+        let result = determine_schwab_trade_details(&SymbolConvention::default(), "BTC", "ETH");
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::InvalidSymbolConfiguration(_, _))
         ));
 
-        let result = determine_schwab_trade_details("USDC", "USDC");
+        let result = determine_schwab_trade_details(&SymbolConvention::default(), "USDC", "USDC");
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::InvalidSymbolConfiguration(_, _))
         ));
 
-        let result = determine_schwab_trade_details("AAPL0x", "TSLA0x");
+        let result =
+            determine_schwab_trade_details(&SymbolConvention::default(), "AAPL0x", "TSLA0x");
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::InvalidSymbolConfiguration(_, _))
         ));
 
-        let result = determine_schwab_trade_details("", "");
+        let result = determine_schwab_trade_details(&SymbolConvention::default(), "", "");
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::InvalidSymbolConfiguration(_, _))
@@ -498,74 +641,132 @@ mod tests {
 
     #[test]
     fn test_trade_details_try_from_io_usdc_to_0x_equity() {
-        let details = TradeDetails::try_from_io("USDC", 100.0, "AAPL0x", 0.5).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(100.0),
+            "AAPL0x",
+            dec!(0.5),
+        )
+        .unwrap();
 
         assert_eq!(details.ticker(), &symbol!("AAPL"));
-        assert!((details.equity_amount().value() - 0.5).abs() < f64::EPSILON);
-        assert!((details.usdc_amount().value() - 100.0).abs() < f64::EPSILON);
+        assert_eq!(details.equity_amount().value(), dec!(0.5));
+        assert_eq!(details.usdc_amount().value(), dec!(100.0));
         assert_eq!(details.direction(), Direction::Sell);
     }
 
     #[test]
     fn test_trade_details_try_from_io_usdc_to_s1_equity_fixes_bug() {
         // This is the key test - s1 suffix should work correctly now
-        let details = TradeDetails::try_from_io("USDC", 64.17, "NVDAs1", 0.374).unwrap();
+        for suffix in SUFFIXES {
+            let details = TradeDetails::try_from_io(
+                &with_suffix(suffix),
+                "USDC",
+                dec!(64.17),
+                &format!("NVDA{suffix}"),
+                dec!(0.374),
+            )
+            .unwrap();
 
-        assert_eq!(details.ticker(), &symbol!("NVDA"));
-        assert!((details.equity_amount().value() - 0.374).abs() < f64::EPSILON); // Should be 0.374, not 64.17!
-        assert!((details.usdc_amount().value() - 64.17).abs() < f64::EPSILON);
-        assert_eq!(details.direction(), Direction::Sell);
+            assert_eq!(details.ticker(), &symbol!("NVDA"));
+            assert_eq!(details.equity_amount().value(), dec!(0.374)); // Should be 0.374, not 64.17!
+            assert_eq!(details.usdc_amount().value(), dec!(64.17));
+            assert_eq!(details.direction(), Direction::Sell);
+        }
     }
 
     #[test]
     fn test_trade_details_try_from_io_0x_equity_to_usdc() {
-        let details = TradeDetails::try_from_io("AAPL0x", 0.5, "USDC", 100.0).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "AAPL0x",
+            dec!(0.5),
+            "USDC",
+            dec!(100.0),
+        )
+        .unwrap();
 
         assert_eq!(details.ticker(), &symbol!("AAPL"));
-        assert!((details.equity_amount().value() - 0.5).abs() < f64::EPSILON);
-        assert!((details.usdc_amount().value() - 100.0).abs() < f64::EPSILON);
+        assert_eq!(details.equity_amount().value(), dec!(0.5));
+        assert_eq!(details.usdc_amount().value(), dec!(100.0));
         assert_eq!(details.direction(), Direction::Buy);
     }
 
     #[test]
-    fn test_trade_details_try_from_io_s1_equity_to_usdc() {
-        let details = TradeDetails::try_from_io("NVDAs1", 0.374, "USDC", 64.17).unwrap();
+    fn test_trade_details_try_from_io_suffix_equity_to_usdc() {
+        for suffix in SUFFIXES {
+            let details = TradeDetails::try_from_io(
+                &with_suffix(suffix),
+                &format!("NVDA{suffix}"),
+                dec!(0.374),
+                "USDC",
+                dec!(64.17),
+            )
+            .unwrap();
 
-        assert_eq!(details.ticker(), &symbol!("NVDA"));
-        assert!((details.equity_amount().value() - 0.374).abs() < f64::EPSILON);
-        assert!((details.usdc_amount().value() - 64.17).abs() < f64::EPSILON);
-        assert_eq!(details.direction(), Direction::Buy);
+            assert_eq!(details.ticker(), &symbol!("NVDA"));
+            assert_eq!(details.equity_amount().value(), dec!(0.374));
+            assert_eq!(details.usdc_amount().value(), dec!(64.17));
+            assert_eq!(details.direction(), Direction::Buy);
+        }
     }
 
     #[test]
     fn test_trade_details_try_from_io_usdc_to_t_equity() {
-        let details = TradeDetails::try_from_io("USDC", 100.0, "tGME", 0.5).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(100.0),
+            "tGME",
+            dec!(0.5),
+        )
+        .unwrap();
 
         assert_eq!(details.ticker(), &symbol!("GME"));
-        assert!((details.equity_amount().value() - 0.5).abs() < f64::EPSILON);
-        assert!((details.usdc_amount().value() - 100.0).abs() < f64::EPSILON);
+        assert_eq!(details.equity_amount().value(), dec!(0.5));
+        assert_eq!(details.usdc_amount().value(), dec!(100.0));
         assert_eq!(details.direction(), Direction::Sell);
     }
 
     #[test]
     fn test_trade_details_try_from_io_t_equity_to_usdc() {
-        let details = TradeDetails::try_from_io("tAAPL", 0.25, "USDC", 50.0).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "tAAPL",
+            dec!(0.25),
+            "USDC",
+            dec!(50.0),
+        )
+        .unwrap();
 
         assert_eq!(details.ticker(), &symbol!("AAPL"));
-        assert!((details.equity_amount().value() - 0.25).abs() < f64::EPSILON);
-        assert!((details.usdc_amount().value() - 50.0).abs() < f64::EPSILON);
+        assert_eq!(details.equity_amount().value(), dec!(0.25));
+        assert_eq!(details.usdc_amount().value(), dec!(50.0));
         assert_eq!(details.direction(), Direction::Buy);
     }
 
     #[test]
     fn test_trade_details_try_from_io_invalid_configurations() {
-        let result = TradeDetails::try_from_io("USDC", 100.0, "USDC", 100.0);
+        let result = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(100.0),
+            "USDC",
+            dec!(100.0),
+        );
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::InvalidSymbolConfiguration(_, _))
         ));
 
-        let result = TradeDetails::try_from_io("BTC", 1.0, "ETH", 3000.0);
+        let result = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "BTC",
+            dec!(1.0),
+            "ETH",
+            dec!(3000.0),
+        );
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::InvalidSymbolConfiguration(_, _))
@@ -575,14 +776,26 @@ mod tests {
     #[test]
     fn test_trade_details_negative_amount_validation() {
         // Test negative equity amount
-        let result = TradeDetails::try_from_io("USDC", 100.0, "AAPL0x", -0.5);
+        let result = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(100.0),
+            "AAPL0x",
+            dec!(-0.5),
+        );
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::NegativeShares(_))
         ));
 
         // Test negative USDC amount
-        let result = TradeDetails::try_from_io("USDC", -100.0, "AAPL0x", 0.5);
+        let result = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(-100.0),
+            "AAPL0x",
+            dec!(0.5),
+        );
         assert!(matches!(
             result.unwrap_err(),
             OnChainError::Validation(TradeValidationError::NegativeUsdc(_))
@@ -627,12 +840,19 @@ mod tests {
     fn test_real_transaction_0x844_nvda_s1_bug_fix() {
         // Real transaction 0x844...a42d4: 0.374 NVDAs1 sold for 64.169234 USDC
         // The bug was using 64.169234 as share amount instead of 0.374
-        let details = TradeDetails::try_from_io("USDC", 64.169_234, "NVDAs1", 0.374).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(64.169234),
+            "NVDAs1",
+            dec!(0.374),
+        )
+        .unwrap();
 
         // Verify we extract the correct amounts
         assert_eq!(details.ticker(), &symbol!("NVDA"));
-        assert!((details.equity_amount().value() - 0.374).abs() < 0.0001); // Share amount is 0.374, NOT 64.169234
-        assert!((details.usdc_amount().value() - 64.169_234).abs() < 0.0001); // USDC amount is 64.169234
+        assert_eq!(details.equity_amount().value(), dec!(0.374)); // Share amount is 0.374, NOT 64.169234
+        assert_eq!(details.usdc_amount().value(), dec!(64.169234)); // USDC amount is 64.169234
         assert_eq!(details.direction(), Direction::Sell); // Selling NVDAs1 onchain = Sell to Schwab
 
         // Verify price calculation
@@ -644,12 +864,19 @@ mod tests {
     fn test_real_transaction_0x700_nvda_s1_bug_fix() {
         // Real transaction 0x700...bfb85: 0.2 NVDAs1 sold for 34.645024 USDC
         // The bug was using 34.645024 as share amount instead of 0.2
-        let details = TradeDetails::try_from_io("USDC", 34.645_024, "NVDAs1", 0.2).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(34.645024),
+            "NVDAs1",
+            dec!(0.2),
+        )
+        .unwrap();
 
         // Verify we extract the correct amounts
         assert_eq!(details.ticker(), &symbol!("NVDA"));
-        assert!((details.equity_amount().value() - 0.2).abs() < 0.0001); // Share amount is 0.2, NOT 34.645024
-        assert!((details.usdc_amount().value() - 34.645_024).abs() < 0.0001); // USDC amount is 34.645024
+        assert_eq!(details.equity_amount().value(), dec!(0.2)); // Share amount is 0.2, NOT 34.645024
+        assert_eq!(details.usdc_amount().value(), dec!(34.645024)); // USDC amount is 34.645024
         assert_eq!(details.direction(), Direction::Sell); // Selling NVDAs1 onchain = Sell to Schwab
 
         // Verify price calculation
@@ -660,9 +887,30 @@ mod tests {
     #[test]
     fn test_gme_trades_with_different_markers_extract_same_ticker() {
         // Test that GME0x, GMEs1, and tGME all map to base symbol "GME"
-        let gme_0x_details = TradeDetails::try_from_io("USDC", 5.2, "GME0x", 0.2).unwrap();
-        let gme_s1_details = TradeDetails::try_from_io("USDC", 5.1, "GMEs1", 0.2).unwrap();
-        let gme_t_details = TradeDetails::try_from_io("USDC", 5.3, "tGME", 0.2).unwrap();
+        let gme_0x_details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(5.2),
+            "GME0x",
+            dec!(0.2),
+        )
+        .unwrap();
+        let gme_s1_details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(5.1),
+            "GMEs1",
+            dec!(0.2),
+        )
+        .unwrap();
+        let gme_t_details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(5.3),
+            "tGME",
+            dec!(0.2),
+        )
+        .unwrap();
 
         // All should map to the same base ticker
         assert_eq!(gme_0x_details.ticker(), &symbol!("GME"));
@@ -672,37 +920,51 @@ mod tests {
         assert_eq!(gme_0x_details.ticker(), gme_t_details.ticker());
 
         // Verify amounts are extracted correctly
-        assert!((gme_0x_details.equity_amount().value() - 0.2).abs() < f64::EPSILON);
-        assert!((gme_s1_details.equity_amount().value() - 0.2).abs() < f64::EPSILON);
-        assert!((gme_t_details.equity_amount().value() - 0.2).abs() < f64::EPSILON);
-        assert!((gme_0x_details.usdc_amount().value() - 5.2).abs() < f64::EPSILON);
-        assert!((gme_s1_details.usdc_amount().value() - 5.1).abs() < f64::EPSILON);
-        assert!((gme_t_details.usdc_amount().value() - 5.3).abs() < f64::EPSILON);
+        assert_eq!(gme_0x_details.equity_amount().value(), dec!(0.2));
+        assert_eq!(gme_s1_details.equity_amount().value(), dec!(0.2));
+        assert_eq!(gme_t_details.equity_amount().value(), dec!(0.2));
+        assert_eq!(gme_0x_details.usdc_amount().value(), dec!(5.2));
+        assert_eq!(gme_s1_details.usdc_amount().value(), dec!(5.1));
+        assert_eq!(gme_t_details.usdc_amount().value(), dec!(5.3));
     }
 
     #[test]
     fn test_edge_case_validation_very_small_amounts() {
         // Test very small but valid amounts
-        let details = TradeDetails::try_from_io("USDC", 0.01, "AAPLs1", 0.0001).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(0.01),
+            "AAPLs1",
+            dec!(0.0001),
+        )
+        .unwrap();
         assert_eq!(details.ticker(), &symbol!("AAPL"));
-        assert!((details.equity_amount().value() - 0.0001).abs() < f64::EPSILON);
-        assert!((details.usdc_amount().value() - 0.01).abs() < f64::EPSILON);
+        assert_eq!(details.equity_amount().value(), dec!(0.0001));
+        assert_eq!(details.usdc_amount().value(), dec!(0.01));
     }
 
     #[test]
     fn test_edge_case_validation_very_large_amounts() {
         // Test large but realistic amounts
-        let details = TradeDetails::try_from_io("USDC", 1_000_000.0, "BRKs1", 100.0).unwrap();
+        let details = TradeDetails::try_from_io(
+            &SymbolConvention::default(),
+            "USDC",
+            dec!(1000000.0),
+            "BRKs1",
+            dec!(100.0),
+        )
+        .unwrap();
         assert_eq!(details.ticker(), &symbol!("BRK"));
-        assert!((details.equity_amount().value() - 100.0).abs() < f64::EPSILON);
-        assert!((details.usdc_amount().value() - 1_000_000.0).abs() < f64::EPSILON);
+        assert_eq!(details.equity_amount().value(), dec!(100.0));
+        assert_eq!(details.usdc_amount().value(), dec!(1000000.0));
     }
 
     #[test]
     fn test_tokenized_equity_marker_variants() {
         let t_marker = TokenizedEquityMarker::T;
         let zerox_marker = TokenizedEquityMarker::ZeroX;
-        let s1_marker = TokenizedEquityMarker::S1;
+        let s1_marker = TokenizedEquityMarker::Suffix("s1".to_string());
 
         assert_eq!(t_marker.as_str(), "t");
         assert_eq!(zerox_marker.as_str(), "0x");
@@ -0,0 +1,134 @@
+//! Persistence of the highest block seen on the live event stream.
+//!
+//! On restart the recorded block is compared with the block the live stream
+//! resumes from, so only the blocks missed while the bot was down have to be
+//! resynced instead of everything since the last processed event.
+
+use sqlx::SqlitePool;
+use tracing::info;
+
+use crate::error::EventQueueError;
+use crate::queue::get_max_processed_block;
+
+/// Blocks between the last-seen block and the live stream cutoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlockGap {
+    /// No block has been recorded yet, so the regular backfill applies
+    Unknown,
+    /// The live stream resumes right after the last-seen block
+    None,
+    /// Blocks `start..=end` were not observed and need to be resynced
+    Missed { start: u64, end: u64 },
+}
+
+/// Records `block_number` as seen, keeping the highest block recorded so far.
+pub(crate) async fn record_last_seen_block(
+    pool: &SqlitePool,
+    block_number: u64,
+) -> Result<(), EventQueueError> {
+    let block_number = i64::try_from(block_number).map_err(|_| {
+        EventQueueError::Processing(format!("Block number {block_number} conversion failed"))
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO last_seen_block (id, block_number)
+        VALUES (1, ?1)
+        ON CONFLICT (id) DO UPDATE SET
+            block_number = MAX(block_number, excluded.block_number),
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        block_number
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn get_last_seen_block(pool: &SqlitePool) -> Result<Option<u64>, EventQueueError> {
+    let block_number = sqlx::query_scalar!("SELECT block_number FROM last_seen_block WHERE id = 1")
+        .fetch_optional(pool)
+        .await?;
+
+    block_number
+        .map(|block| {
+            u64::try_from(block).map_err(|_| {
+                EventQueueError::Processing(format!("Block number {block} conversion failed"))
+            })
+        })
+        .transpose()
+}
+
+/// Highest block the bot is caught up to: the highest block among processed
+/// queue events or the last-seen block, whichever is higher.
+pub(crate) async fn get_processed_block(pool: &SqlitePool) -> Result<Option<u64>, EventQueueError> {
+    let processed = get_max_processed_block(pool).await?;
+    let last_seen = get_last_seen_block(pool).await?;
+
+    Ok(processed.max(last_seen))
+}
+
+/// Compares the last-seen block with `cutoff_block`, the first block covered
+/// by the live stream after startup.
+pub(crate) async fn detect_block_gap(
+    pool: &SqlitePool,
+    cutoff_block: u64,
+) -> Result<BlockGap, EventQueueError> {
+    let Some(last_seen) = get_last_seen_block(pool).await? else {
+        return Ok(BlockGap::Unknown);
+    };
+
+    let start = last_seen + 1;
+    let Some(end) = cutoff_block.checked_sub(1).filter(|end| *end >= start) else {
+        info!("No blocks missed since last-seen block {last_seen} (cutoff {cutoff_block})");
+        return Ok(BlockGap::None);
+    };
+
+    info!(
+        "Missed blocks {start}-{end} since last-seen block {last_seen}, resyncing {} blocks",
+        end - start + 1
+    );
+
+    Ok(BlockGap::Missed { start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    #[tokio::test]
+    async fn test_last_seen_block_only_moves_forward() {
+        let pool = setup_test_db().await;
+        assert_eq!(get_last_seen_block(&pool).await.unwrap(), None);
+
+        record_last_seen_block(&pool, 100).await.unwrap();
+        record_last_seen_block(&pool, 90).await.unwrap();
+        assert_eq!(get_last_seen_block(&pool).await.unwrap(), Some(100));
+
+        record_last_seen_block(&pool, 120).await.unwrap();
+        assert_eq!(get_last_seen_block(&pool).await.unwrap(), Some(120));
+    }
+
+    #[tokio::test]
+    async fn test_detect_block_gap() {
+        let pool = setup_test_db().await;
+        assert_eq!(
+            detect_block_gap(&pool, 150).await.unwrap(),
+            BlockGap::Unknown
+        );
+
+        record_last_seen_block(&pool, 100).await.unwrap();
+
+        assert_eq!(
+            detect_block_gap(&pool, 150).await.unwrap(),
+            BlockGap::Missed {
+                start: 101,
+                end: 149
+            }
+        );
+        assert_eq!(detect_block_gap(&pool, 101).await.unwrap(), BlockGap::None);
+        assert_eq!(detect_block_gap(&pool, 100).await.unwrap(), BlockGap::None);
+    }
+}
@@ -1,11 +1,19 @@
 use alloy::primitives::Address;
 use clap::Parser;
+use std::num::NonZeroU64;
+
+use io::SymbolConvention;
 
 pub(crate) mod accumulator;
 pub(crate) mod backfill;
+pub(crate) mod backfill_checkpoint;
 mod clear;
+pub(crate) mod contract_code;
+pub(crate) mod hedge_events;
 pub(crate) mod io;
+pub(crate) mod last_seen_block;
 pub(crate) mod position_calculator;
+pub(crate) mod price_source;
 pub(crate) mod pyth;
 mod take_order;
 pub(crate) mod trade;
@@ -22,4 +30,43 @@ pub struct EvmEnv {
     pub order_owner: Address,
     #[clap(short = 'd', long, env)]
     pub deployment_block: u64,
+    #[clap(flatten)]
+    pub symbol_convention: SymbolConvention,
+    /// Maximum number of blocks requested per `eth_getLogs` call during
+    /// backfill; ranges the provider rejects are halved automatically
+    #[clap(long, env, default_value_t = DEFAULT_BACKFILL_BATCH_SIZE)]
+    pub backfill_batch_size: NonZeroU64,
+}
+
+pub(crate) const DEFAULT_BACKFILL_BATCH_SIZE: NonZeroU64 = NonZeroU64::new(1000).unwrap();
+
+/// Zero address in place of a real order owner or orderbook, which is only
+/// ever meant for tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum ZeroAddressError {
+    #[error(
+        "order owner is the zero address, so no trade would ever match and the bot would \
+         run without hedging anything; pass --allow-zero-owner to run anyway"
+    )]
+    OrderOwner,
+    #[error(
+        "orderbook is the zero address, so no events would ever be received; pass \
+         --allow-zero-owner to run anyway"
+    )]
+    Orderbook,
+}
+
+impl EvmEnv {
+    /// Rejects a zero order owner or orderbook address.
+    pub(crate) fn check_nonzero_addresses(&self) -> Result<(), ZeroAddressError> {
+        if self.order_owner == Address::ZERO {
+            return Err(ZeroAddressError::OrderOwner);
+        }
+
+        if self.orderbook == Address::ZERO {
+            return Err(ZeroAddressError::Orderbook);
+        }
+
+        Ok(())
+    }
 }
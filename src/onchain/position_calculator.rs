@@ -1,6 +1,53 @@
 use num_traits::ToPrimitive;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+const SCHWAB_MINIMUM_WHOLE_SHARES: Decimal = Decimal::ONE;
+
+/// Net positions within this many shares of zero count as flat.
+const FLAT_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 3);
+
+/// Precision stored share quantities are restored at.
+const SHARE_DECIMAL_PLACES: u32 = 9;
+
+/// How a fractional share quantity becomes the whole shares sent to the
+/// broker.
+///
+/// Onchain fills are accumulated with their fractions, as exact `Decimal`s. A
+/// position executes once its net reaches one whole share whatever the policy;
+/// the policy only decides how many whole shares that execution takes.
+/// `Truncate` takes the whole shares and keeps the fraction accumulated, so a
+/// 2.999 share position executes 2 shares and keeps 0.999. `Round` takes the
+/// nearest whole number, halves away from zero, so the same position executes
+/// 3 shares and carries the 0.001 share overshoot as exposure in the opposite
+/// direction. The same policy decides the whole shares kept from trades of
+/// symbols that do not accumulate fractions.
+///
+/// Positions are stored as REAL, so they are rounded to
+/// [`SHARE_DECIMAL_PLACES`] when loaded, and float representation error (e.g.
+/// 0.9999999999999999 for ten 0.1 share fills) cannot cost a share.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShareRounding {
+    #[default]
+    Truncate,
+    Round,
+}
+
+impl ShareRounding {
+    /// Whole shares in `quantity` (ignoring its sign) under this policy.
+    pub(crate) fn whole_shares(self, quantity: Decimal) -> Result<u64, ConversionError> {
+        let exact = quantity.abs();
+
+        let whole = match self {
+            Self::Truncate => exact.trunc(),
+            Self::Round => exact.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero),
+        };
 
-const SCHWAB_MINIMUM_WHOLE_SHARES: f64 = 1.0;
+        whole
+            .to_u64()
+            .ok_or(ConversionError::DecimalToU64OutOfRange { value: quantity })
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccumulationBucket {
@@ -10,19 +57,22 @@ pub enum AccumulationBucket {
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum ConversionError {
-    #[error("Failed to convert u64 {value} to f64: precision loss would occur")]
-    U64ToF64PrecisionLoss { value: u64 },
+    #[error("Failed to convert Decimal {value} to u64: value out of range")]
+    DecimalToU64OutOfRange { value: Decimal },
 
-    #[error("Failed to convert f64 {value} to u64: value out of range or invalid")]
-    F64ToU64OutOfRange { value: f64 },
+    #[error("Failed to convert f64 {value} to Decimal")]
+    F64ToDecimal { value: f64 },
+
+    #[error("Failed to convert Decimal {value} to f64")]
+    DecimalToF64 { value: Decimal },
 }
 
 /// Handles position tracking and threshold checking logic.
 /// Separated from TradeAccumulator to follow single responsibility principle.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct PositionCalculator {
-    pub(crate) accumulated_long: f64,
-    pub(crate) accumulated_short: f64,
+    pub(crate) accumulated_long: Decimal,
+    pub(crate) accumulated_short: Decimal,
 }
 
 impl Default for PositionCalculator {
@@ -34,26 +84,68 @@ impl Default for PositionCalculator {
 impl PositionCalculator {
     pub(crate) const fn new() -> Self {
         Self {
-            accumulated_long: 0.0,
-            accumulated_short: 0.0,
+            accumulated_long: Decimal::ZERO,
+            accumulated_short: Decimal::ZERO,
         }
     }
 
-    pub(crate) const fn with_positions(accumulated_long: f64, accumulated_short: f64) -> Self {
+    pub(crate) const fn with_positions(
+        accumulated_long: Decimal,
+        accumulated_short: Decimal,
+    ) -> Self {
         Self {
             accumulated_long,
             accumulated_short,
         }
     }
 
-    pub(crate) fn net_position(&self) -> f64 {
+    /// Restores the positions stored in the REAL accumulator columns.
+    pub(crate) fn from_stored(
+        accumulated_long: f64,
+        accumulated_short: f64,
+    ) -> Result<Self, ConversionError> {
+        let restore = |value: f64| {
+            Decimal::from_f64(value)
+                .map(|exact| exact.round_dp(SHARE_DECIMAL_PLACES))
+                .ok_or(ConversionError::F64ToDecimal { value })
+        };
+
+        Ok(Self::with_positions(
+            restore(accumulated_long)?,
+            restore(accumulated_short)?,
+        ))
+    }
+
+    /// The positions as stored in the REAL accumulator columns.
+    pub(crate) fn to_stored(&self) -> Result<(f64, f64), ConversionError> {
+        let store = |value: Decimal| {
+            value
+                .to_f64()
+                .ok_or(ConversionError::DecimalToF64 { value })
+        };
+
+        Ok((
+            store(self.accumulated_long)?,
+            store(self.accumulated_short)?,
+        ))
+    }
+
+    pub(crate) fn net_position(&self) -> Decimal {
         self.accumulated_long - self.accumulated_short
     }
 
+    /// Shares accumulated in `bucket`.
+    pub(crate) const fn accumulated(&self, bucket: AccumulationBucket) -> Decimal {
+        match bucket {
+            AccumulationBucket::LongExposure => self.accumulated_long,
+            AccumulationBucket::ShortExposure => self.accumulated_short,
+        }
+    }
+
     pub(crate) fn determine_execution_type(&self) -> Option<AccumulationBucket> {
         let net = self.net_position();
         if net.abs() >= SCHWAB_MINIMUM_WHOLE_SHARES {
-            if net > 0.0 {
+            if net.is_sign_positive() {
                 Some(AccumulationBucket::LongExposure) // Net long, need to SELL
             } else {
                 Some(AccumulationBucket::ShortExposure) // Net short, need to BUY
@@ -63,7 +155,21 @@ impl PositionCalculator {
         }
     }
 
-    pub(crate) fn add_trade(&mut self, amount: f64, direction: AccumulationBucket) {
+    /// Bucket of a fractional net position too small for
+    /// [`Self::determine_execution_type`], i.e. what a whole-share rounding
+    /// order would offset, or `None` if the position is flat or a whole share.
+    pub(crate) fn determine_residual_type(&self) -> Option<AccumulationBucket> {
+        let net = self.net_position();
+        if net.abs() <= FLAT_TOLERANCE || net.abs() >= SCHWAB_MINIMUM_WHOLE_SHARES {
+            None
+        } else if net.is_sign_positive() {
+            Some(AccumulationBucket::LongExposure)
+        } else {
+            Some(AccumulationBucket::ShortExposure)
+        }
+    }
+
+    pub(crate) fn add_trade(&mut self, amount: Decimal, direction: AccumulationBucket) {
         match direction {
             AccumulationBucket::LongExposure => {
                 // Long exposure from onchain BUY -> accumulate for Schwab SELL to offset
@@ -76,195 +182,309 @@ impl PositionCalculator {
         }
     }
 
+    /// Takes an execution of `shares` from `execution_type`'s bucket and
+    /// returns the shares the bucket held of it.
     pub(crate) fn reduce_accumulation(
         &mut self,
         execution_type: AccumulationBucket,
-        shares: u64,
-    ) -> Result<(), ConversionError> {
-        let shares_f64 = shares
-            .to_f64()
-            .ok_or(ConversionError::U64ToF64PrecisionLoss { value: shares })?;
+        shares: Decimal,
+    ) -> Decimal {
+        self.offset(execution_type, shares)
+    }
 
-        match execution_type {
+    /// Offsets a one-share rounding order against a residual in
+    /// `execution_type`'s bucket and returns the part of the share the bucket
+    /// held.
+    pub(crate) fn offset_whole_share(&mut self, execution_type: AccumulationBucket) -> Decimal {
+        self.offset(execution_type, SCHWAB_MINIMUM_WHOLE_SHARES)
+    }
+
+    /// Buckets cannot go negative, so shares rounded up past the bucket become
+    /// exposure in the opposite bucket.
+    fn offset(&mut self, execution_type: AccumulationBucket, shares: Decimal) -> Decimal {
+        let (bucket, opposite) = match execution_type {
             AccumulationBucket::LongExposure => {
-                self.accumulated_long -= shares_f64;
+                (&mut self.accumulated_long, &mut self.accumulated_short)
             }
             AccumulationBucket::ShortExposure => {
-                self.accumulated_short -= shares_f64;
+                (&mut self.accumulated_short, &mut self.accumulated_long)
             }
-        }
+        };
+
+        let taken = (*bucket).min(shares);
+        *bucket -= taken;
+        *opposite += shares - taken;
+
+        taken
+    }
 
-        Ok(())
+    /// Fraction of a share left in the net position besides its whole
+    /// shares, or `None` if it is within the flat tolerance.
+    pub(crate) fn fractional_remainder(&self) -> Option<Decimal> {
+        let fraction = self.net_position().abs().fract();
+        (fraction > FLAT_TOLERANCE).then_some(fraction)
     }
 
-    pub(crate) fn calculate_executable_shares(&self) -> Result<u64, ConversionError> {
-        let net = self.net_position().abs().floor();
-        net.to_u64()
-            .ok_or(ConversionError::F64ToU64OutOfRange { value: net })
+    pub(crate) fn calculate_executable_shares(
+        &self,
+        rounding: ShareRounding,
+    ) -> Result<u64, ConversionError> {
+        rounding.whole_shares(self.net_position())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal_macros::dec;
 
     #[test]
     fn test_position_calculator_new() {
         let calc = PositionCalculator::new();
-        assert!((calc.net_position() - 0.0).abs() < f64::EPSILON);
-        assert!((calc.accumulated_long - 0.0).abs() < f64::EPSILON);
-        assert!((calc.accumulated_short - 0.0).abs() < f64::EPSILON);
+        assert_eq!(calc.net_position(), dec!(0.0));
+        assert_eq!(calc.accumulated_long, dec!(0.0));
+        assert_eq!(calc.accumulated_short, dec!(0.0));
     }
 
     #[test]
     fn test_net_position_below_threshold_no_trigger() {
         // net=0.7 (long=1.5, short=0.8): Should NOT trigger
-        let calc = PositionCalculator::with_positions(1.5, 0.8);
-        assert!((calc.net_position() - 0.7).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(1.5), dec!(0.8));
+        assert_eq!(calc.net_position(), dec!(0.7));
         assert!(calc.determine_execution_type().is_none());
     }
 
     #[test]
     fn test_net_position_negative_triggers_buy() {
         // net=-1.2 (long=0.3, short=1.5): Should trigger BUY
-        let calc = PositionCalculator::with_positions(0.3, 1.5);
-        assert!((calc.net_position() - (-1.2)).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(0.3), dec!(1.5));
+        assert_eq!(calc.net_position(), dec!(-1.2));
         assert_eq!(
             calc.determine_execution_type(),
             Some(AccumulationBucket::ShortExposure)
         );
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 1);
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            1
+        );
     }
 
     #[test]
     fn test_net_position_positive_triggers_sell() {
         // net=1.5 (long=2.0, short=0.5): Should trigger SELL
-        let calc = PositionCalculator::with_positions(2.0, 0.5);
-        assert!((calc.net_position() - 1.5).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(2.0), dec!(0.5));
+        assert_eq!(calc.net_position(), dec!(1.5));
         assert_eq!(
             calc.determine_execution_type(),
             Some(AccumulationBucket::LongExposure)
         );
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 1);
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            1
+        );
     }
 
     #[test]
     fn test_net_position_large_negative_multiple_shares() {
         // net=-2.5: Should trigger BUY for 2 shares
-        let calc = PositionCalculator::with_positions(0.5, 3.0);
-        assert!((calc.net_position() - (-2.5)).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(0.5), dec!(3.0));
+        assert_eq!(calc.net_position(), dec!(-2.5));
         assert_eq!(
             calc.determine_execution_type(),
             Some(AccumulationBucket::ShortExposure)
         );
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 2);
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            2
+        );
     }
 
     #[test]
     fn test_net_position_exactly_one() {
         // net=1.0 exactly: Should trigger
-        let calc = PositionCalculator::with_positions(1.0, 0.0);
-        assert!((calc.net_position() - 1.0).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(1.0), dec!(0.0));
+        assert_eq!(calc.net_position(), dec!(1.0));
         assert_eq!(
             calc.determine_execution_type(),
             Some(AccumulationBucket::LongExposure)
         );
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 1);
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            1
+        );
     }
 
     #[test]
     fn test_net_position_just_below_threshold() {
         // net=0.999: Should NOT trigger
-        let calc = PositionCalculator::with_positions(0.999, 0.0);
-        assert!((calc.net_position() - 0.999).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(0.999), dec!(0.0));
+        assert_eq!(calc.net_position(), dec!(0.999));
         assert!(calc.determine_execution_type().is_none());
     }
 
     #[test]
     fn test_net_position_zero() {
         // net=0.0: Should NOT trigger
-        let calc = PositionCalculator::with_positions(1.0, 1.0);
-        assert!((calc.net_position() - 0.0).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(1.0), dec!(1.0));
+        assert_eq!(calc.net_position(), dec!(0.0));
         assert!(calc.determine_execution_type().is_none());
     }
 
     #[test]
     fn test_net_position_large_positive_multiple_shares() {
         // net=3.7: Should trigger SELL for 3 shares
-        let calc = PositionCalculator::with_positions(4.0, 0.3);
-        assert!((calc.net_position() - 3.7).abs() < f64::EPSILON);
+        let calc = PositionCalculator::with_positions(dec!(4.0), dec!(0.3));
+        assert_eq!(calc.net_position(), dec!(3.7));
         assert_eq!(
             calc.determine_execution_type(),
             Some(AccumulationBucket::LongExposure)
         );
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 3);
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            3
+        );
     }
 
     #[test]
     fn test_add_trade_long_accumulation() {
         let mut calc = PositionCalculator::new();
-        calc.add_trade(1.5, AccumulationBucket::LongExposure); // Long exposure from onchain BUY -> accumulate for Schwab SELL
-        assert!((calc.accumulated_long - 1.5).abs() < f64::EPSILON);
-        assert!((calc.accumulated_short - 0.0).abs() < f64::EPSILON);
-        assert!((calc.net_position() - 1.5).abs() < f64::EPSILON);
+        calc.add_trade(dec!(1.5), AccumulationBucket::LongExposure); // Long exposure from onchain BUY -> accumulate for Schwab SELL
+        assert_eq!(calc.accumulated_long, dec!(1.5));
+        assert_eq!(calc.accumulated_short, dec!(0.0));
+        assert_eq!(calc.net_position(), dec!(1.5));
     }
 
     #[test]
     fn test_add_trade_short_accumulation() {
         let mut calc = PositionCalculator::new();
-        calc.add_trade(2.0, AccumulationBucket::ShortExposure); // Short exposure from onchain SELL -> accumulate for Schwab BUY
-        assert!((calc.accumulated_long - 0.0).abs() < f64::EPSILON);
-        assert!((calc.accumulated_short - 2.0).abs() < f64::EPSILON);
-        assert!((calc.net_position() - (-2.0)).abs() < f64::EPSILON);
+        calc.add_trade(dec!(2.0), AccumulationBucket::ShortExposure); // Short exposure from onchain SELL -> accumulate for Schwab BUY
+        assert_eq!(calc.accumulated_long, dec!(0.0));
+        assert_eq!(calc.accumulated_short, dec!(2.0));
+        assert_eq!(calc.net_position(), dec!(-2.0));
     }
 
     #[test]
     fn test_add_trade_zero_amount() {
         let mut calc = PositionCalculator::new();
-        calc.add_trade(0.0, AccumulationBucket::LongExposure); // Zero amount but still affects direction
-        assert!((calc.accumulated_long - 0.0).abs() < f64::EPSILON);
-        assert!((calc.accumulated_short - 0.0).abs() < f64::EPSILON);
-        assert!((calc.net_position() - 0.0).abs() < f64::EPSILON);
+        calc.add_trade(dec!(0.0), AccumulationBucket::LongExposure); // Zero amount but still affects direction
+        assert_eq!(calc.accumulated_long, dec!(0.0));
+        assert_eq!(calc.accumulated_short, dec!(0.0));
+        assert_eq!(calc.net_position(), dec!(0.0));
     }
 
     #[test]
     fn test_add_trade_mixed_directions() {
         let mut calc = PositionCalculator::new();
-        calc.add_trade(1.5, AccumulationBucket::LongExposure); // Long accumulation
-        calc.add_trade(2.0, AccumulationBucket::ShortExposure); // Short accumulation
-        calc.add_trade(0.3, AccumulationBucket::LongExposure); // More long accumulation
+        calc.add_trade(dec!(1.5), AccumulationBucket::LongExposure); // Long accumulation
+        calc.add_trade(dec!(2.0), AccumulationBucket::ShortExposure); // Short accumulation
+        calc.add_trade(dec!(0.3), AccumulationBucket::LongExposure); // More long accumulation
 
-        assert!((calc.accumulated_long - 1.8).abs() < f64::EPSILON); // 1.5 + 0.3
-        assert!((calc.accumulated_short - 2.0).abs() < f64::EPSILON); // 2.0
-        assert!((calc.net_position() - (-0.2)).abs() < f64::EPSILON); // 1.8 - 2.0 = -0.2
+        assert_eq!(calc.accumulated_long, dec!(1.8)); // 1.5 + 0.3
+        assert_eq!(calc.accumulated_short, dec!(2.0)); // 2.0
+        assert_eq!(calc.net_position(), dec!(-0.2)); // 1.8 - 2.0 = -0.2
     }
 
     #[test]
     fn test_reduce_accumulation() {
-        let mut calc = PositionCalculator::with_positions(2.5, 3.0);
-        calc.reduce_accumulation(AccumulationBucket::LongExposure, 2)
-            .unwrap();
-        assert!((calc.accumulated_long - 0.5).abs() < f64::EPSILON);
-        assert!((calc.net_position() - (-2.5)).abs() < f64::EPSILON); // 0.5 - 3.0 = -2.5
-
-        calc.reduce_accumulation(AccumulationBucket::ShortExposure, 1)
-            .unwrap();
-        assert!((calc.accumulated_short - 2.0).abs() < f64::EPSILON);
-        assert!((calc.net_position() - (-1.5)).abs() < f64::EPSILON); // 0.5 - 2.0 = -1.5
+        let mut calc = PositionCalculator::with_positions(dec!(2.5), dec!(3.0));
+        let taken = calc.reduce_accumulation(AccumulationBucket::LongExposure, dec!(2));
+        assert_eq!(taken, dec!(2));
+        assert_eq!(calc.accumulated_long, dec!(0.5));
+        assert_eq!(calc.net_position(), dec!(-2.5)); // 0.5 - 3.0 = -2.5
+
+        calc.reduce_accumulation(AccumulationBucket::ShortExposure, dec!(1));
+        assert_eq!(calc.accumulated_short, dec!(2.0));
+        assert_eq!(calc.net_position(), dec!(-1.5)); // 0.5 - 2.0 = -1.5
     }
 
     #[test]
     fn test_calculate_executable_shares() {
         // Test positive net position
-        let calc = PositionCalculator::with_positions(2.7, 0.0);
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 2);
+        let calc = PositionCalculator::with_positions(dec!(2.7), dec!(0.0));
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            2
+        );
 
         // Test negative net position
-        let calc = PositionCalculator::with_positions(0.0, 3.2);
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 3);
+        let calc = PositionCalculator::with_positions(dec!(0.0), dec!(3.2));
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            3
+        );
 
         // Test zero net position
-        let calc = PositionCalculator::with_positions(1.0, 1.0);
-        assert_eq!(calc.calculate_executable_shares().unwrap(), 0);
+        let calc = PositionCalculator::with_positions(dec!(1.0), dec!(1.0));
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_share_rounding_of_fractional_fill() {
+        let calc = PositionCalculator::with_positions(dec!(2.999), dec!(0.0));
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Round)
+                .unwrap(),
+            3
+        );
+
+        let calc = PositionCalculator::with_positions(dec!(0.0), dec!(2.5));
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Round)
+                .unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_truncation_ignores_float_representation_error() {
+        let stored: f64 = std::iter::repeat_n(0.1, 10).sum();
+        assert!(stored < 1.0);
+
+        let calc = PositionCalculator::from_stored(stored, 0.0).unwrap();
+        assert_eq!(calc.accumulated_long, Decimal::ONE);
+        assert_eq!(
+            calc.calculate_executable_shares(ShareRounding::Truncate)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rounded_up_execution_carries_overshoot_as_opposite_exposure() {
+        let mut calc = PositionCalculator::with_positions(dec!(2.999), dec!(0.0));
+        let shares = calc
+            .calculate_executable_shares(ShareRounding::Round)
+            .unwrap();
+        let taken =
+            calc.reduce_accumulation(AccumulationBucket::LongExposure, Decimal::from(shares));
+
+        assert_eq!(taken, dec!(2.999));
+        assert_eq!(calc.accumulated_long, Decimal::ZERO);
+        assert_eq!(calc.accumulated_short, dec!(0.001));
+    }
+
+    #[test]
+    fn test_fractional_remainder_ignores_flat_positions() {
+        let calc = PositionCalculator::with_positions(dec!(0.3), dec!(2.65));
+        assert_eq!(calc.fractional_remainder(), Some(dec!(0.35)));
+
+        let flat = PositionCalculator::with_positions(dec!(2.0005), dec!(0.0));
+        assert_eq!(flat.fractional_remainder(), None);
     }
 }
@@ -0,0 +1,489 @@
+//! Configurable priority chain for pricing the onchain leg of a trade.
+//!
+//! Each configured source is consulted in order until one yields a usable
+//! price. Every source in the chain gets an audit row recording what it
+//! returned, so the chosen price can always be traced back to its origin.
+
+use alloy::primitives::B256;
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use st0x_broker::{Broker, Symbol};
+use std::fmt;
+use std::str::FromStr;
+use tracing::warn;
+
+use crate::onchain::OnchainTrade;
+
+/// A source of per-share USDC pricing for an onchain trade.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    /// Pyth oracle price extracted from the transaction trace
+    Pyth,
+    /// Broker quote for the underlying equity at execution time
+    BrokerQuote,
+    /// Raw USDC / equity ratio of the onchain fill
+    OnchainRatio,
+}
+
+impl PriceSource {
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pyth => "pyth",
+            Self::BrokerQuote => "broker_quote",
+            Self::OnchainRatio => "onchain_ratio",
+        }
+    }
+}
+
+impl fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for PriceSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pyth" => Ok(Self::Pyth),
+            "broker_quote" => Ok(Self::BrokerQuote),
+            "onchain_ratio" => Ok(Self::OnchainRatio),
+            _ => Err(format!("Invalid price source: '{s}'")),
+        }
+    }
+}
+
+/// Prices available from each source for a single trade. `None` means the
+/// source could not produce a price for this trade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PriceCandidates {
+    pub(crate) pyth: Option<f64>,
+    pub(crate) broker_quote: Option<f64>,
+    pub(crate) onchain_ratio: Option<f64>,
+}
+
+impl PriceCandidates {
+    const fn get(&self, source: PriceSource) -> Option<f64> {
+        match source {
+            PriceSource::Pyth => self.pyth,
+            PriceSource::BrokerQuote => self.broker_quote,
+            PriceSource::OnchainRatio => self.onchain_ratio,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AttemptOutcome {
+    /// Source produced the price used for the trade
+    Selected,
+    /// Source had no price for this trade
+    Unavailable,
+    /// Source returned a price that is not finite and positive
+    Invalid,
+    /// An earlier source in the chain already produced a price
+    NotConsulted,
+}
+
+impl AttemptOutcome {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Selected => "SELECTED",
+            Self::Unavailable => "UNAVAILABLE",
+            Self::Invalid => "INVALID",
+            Self::NotConsulted => "NOT_CONSULTED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PriceAttempt {
+    pub(crate) source: PriceSource,
+    pub(crate) price: Option<f64>,
+    pub(crate) outcome: AttemptOutcome,
+}
+
+/// Result of walking the price source chain for a single trade.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PriceResolution {
+    pub(crate) source: PriceSource,
+    pub(crate) price: f64,
+    pub(crate) attempts: Vec<PriceAttempt>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum PriceSourceError {
+    #[error("Price source chain is empty")]
+    EmptyChain,
+    #[error("No price source in chain {chain:?} produced a valid price")]
+    NoValidPrice { chain: Vec<PriceSource> },
+}
+
+/// Consults each source in `chain` in order and returns the first valid price
+/// together with the outcome of every source in the chain.
+pub(crate) fn resolve_price(
+    chain: &[PriceSource],
+    candidates: &PriceCandidates,
+) -> Result<PriceResolution, PriceSourceError> {
+    if chain.is_empty() {
+        return Err(PriceSourceError::EmptyChain);
+    }
+
+    let (selected, attempts) = chain.iter().fold(
+        (None, Vec::with_capacity(chain.len())),
+        |(selected, mut attempts), &source| {
+            let price = candidates.get(source);
+
+            let outcome = match (selected, price) {
+                (Some(_), _) => AttemptOutcome::NotConsulted,
+                (None, None) => AttemptOutcome::Unavailable,
+                (None, Some(value)) if !value.is_finite() || value <= 0.0 => {
+                    AttemptOutcome::Invalid
+                }
+                (None, Some(_)) => AttemptOutcome::Selected,
+            };
+
+            let selected = match (outcome, price) {
+                (AttemptOutcome::Selected, Some(value)) => Some((source, value)),
+                _ => selected,
+            };
+
+            attempts.push(PriceAttempt {
+                source,
+                price,
+                outcome,
+            });
+
+            (selected, attempts)
+        },
+    );
+
+    let (source, price) = selected.ok_or_else(|| PriceSourceError::NoValidPrice {
+        chain: chain.to_vec(),
+    })?;
+
+    Ok(PriceResolution {
+        source,
+        price,
+        attempts,
+    })
+}
+
+/// Resolves the price of an onchain trade through the configured chain.
+///
+/// Pyth and the onchain ratio come from the converted trade itself. The broker
+/// is only asked for a quote when the chain actually reaches the broker quote
+/// source, so chains that resolve earlier never hit the broker API.
+pub(crate) async fn resolve_trade_price<B: Broker>(
+    broker: &B,
+    chain: &[PriceSource],
+    trade: &OnchainTrade,
+) -> Result<PriceResolution, PriceSourceError> {
+    let candidates = PriceCandidates {
+        pyth: trade.pyth_price,
+        broker_quote: None,
+        onchain_ratio: trade.price_usdc.to_f64(),
+    };
+
+    let without_quote = resolve_price(chain, &candidates);
+
+    let reaches_broker_quote = without_quote.as_ref().map_or_else(
+        |_| chain.contains(&PriceSource::BrokerQuote),
+        |resolution| {
+            resolution.attempts.iter().any(|attempt| {
+                attempt.source == PriceSource::BrokerQuote
+                    && attempt.outcome == AttemptOutcome::Unavailable
+            })
+        },
+    );
+
+    if !reaches_broker_quote {
+        return without_quote;
+    }
+
+    let broker_quote = match fetch_broker_quote(broker, trade).await {
+        Ok(price) => Some(price),
+        Err(e) => {
+            warn!(
+                "Failed to get broker quote for {} (tx_hash={:?}, log_index={}): {e}",
+                trade.symbol, trade.tx_hash, trade.log_index
+            );
+            None
+        }
+    };
+
+    resolve_price(
+        chain,
+        &PriceCandidates {
+            broker_quote,
+            ..candidates
+        },
+    )
+}
+
+async fn fetch_broker_quote<B: Broker>(broker: &B, trade: &OnchainTrade) -> Result<f64, String> {
+    let symbol = Symbol::new(trade.symbol.base().to_string()).map_err(|e| e.to_string())?;
+    let quote = broker.get_quote(&symbol).await.map_err(|e| e.to_string())?;
+
+    #[allow(clippy::cast_precision_loss)]
+    let mid_price = quote.mid_price_cents() as f64 / 100.0;
+
+    Ok(mid_price)
+}
+
+/// Records every attempt of a price resolution for audit. Attempts already
+/// recorded for the same trade and source are left untouched.
+pub(crate) async fn save_price_resolution(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    tx_hash: B256,
+    log_index: u64,
+    resolution: &PriceResolution,
+) -> Result<(), sqlx::Error> {
+    let tx_hash_str = tx_hash.to_string();
+    let log_index_i64 = i64::try_from(log_index)
+        .map_err(|e| sqlx::Error::Decode(format!("log_index out of range: {e}").into()))?;
+
+    for (priority, attempt) in (0_i64..).zip(&resolution.attempts) {
+        let source = attempt.source.as_str();
+        let outcome = attempt.outcome.as_str();
+
+        sqlx::query!(
+            "INSERT OR IGNORE INTO onchain_trade_price_sources (
+                tx_hash,
+                log_index,
+                priority,
+                source,
+                price,
+                outcome
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            tx_hash_str,
+            log_index_i64,
+            priority,
+            source,
+            attempt.price,
+            outcome
+        )
+        .execute(&mut **sql_tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{OnchainTradeBuilder, setup_test_db};
+    use alloy::primitives::fixed_bytes;
+    use rust_decimal_macros::dec;
+    use st0x_broker::MockBroker;
+
+    const FULL_CHAIN: [PriceSource; 3] = [
+        PriceSource::Pyth,
+        PriceSource::BrokerQuote,
+        PriceSource::OnchainRatio,
+    ];
+
+    #[test]
+    fn test_resolve_price_prefers_first_source() {
+        let candidates = PriceCandidates {
+            pyth: Some(150.25),
+            broker_quote: Some(150.10),
+            onchain_ratio: Some(149.90),
+        };
+
+        let resolution = resolve_price(&FULL_CHAIN, &candidates).unwrap();
+
+        assert_eq!(resolution.source, PriceSource::Pyth);
+        assert!((resolution.price - 150.25).abs() < f64::EPSILON);
+        assert_eq!(
+            resolution
+                .attempts
+                .iter()
+                .map(|a| a.outcome)
+                .collect::<Vec<_>>(),
+            vec![
+                AttemptOutcome::Selected,
+                AttemptOutcome::NotConsulted,
+                AttemptOutcome::NotConsulted,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_price_falls_back_to_broker_quote() {
+        let candidates = PriceCandidates {
+            pyth: None,
+            broker_quote: Some(150.10),
+            onchain_ratio: Some(149.90),
+        };
+
+        let resolution = resolve_price(&FULL_CHAIN, &candidates).unwrap();
+
+        assert_eq!(resolution.source, PriceSource::BrokerQuote);
+        assert!((resolution.price - 150.10).abs() < f64::EPSILON);
+        assert_eq!(resolution.attempts[0].outcome, AttemptOutcome::Unavailable);
+        assert_eq!(resolution.attempts[1].outcome, AttemptOutcome::Selected);
+        assert_eq!(resolution.attempts[2].outcome, AttemptOutcome::NotConsulted);
+    }
+
+    #[test]
+    fn test_resolve_price_falls_back_to_onchain_ratio() {
+        let candidates = PriceCandidates {
+            pyth: Some(f64::NAN),
+            broker_quote: None,
+            onchain_ratio: Some(149.90),
+        };
+
+        let resolution = resolve_price(&FULL_CHAIN, &candidates).unwrap();
+
+        assert_eq!(resolution.source, PriceSource::OnchainRatio);
+        assert!((resolution.price - 149.90).abs() < f64::EPSILON);
+        assert_eq!(resolution.attempts[0].outcome, AttemptOutcome::Invalid);
+        assert_eq!(resolution.attempts[1].outcome, AttemptOutcome::Unavailable);
+        assert_eq!(resolution.attempts[2].outcome, AttemptOutcome::Selected);
+    }
+
+    #[test]
+    fn test_resolve_price_respects_configured_order() {
+        let candidates = PriceCandidates {
+            pyth: Some(150.25),
+            broker_quote: None,
+            onchain_ratio: Some(149.90),
+        };
+
+        let resolution =
+            resolve_price(&[PriceSource::OnchainRatio, PriceSource::Pyth], &candidates).unwrap();
+
+        assert_eq!(resolution.source, PriceSource::OnchainRatio);
+        assert_eq!(resolution.attempts.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_price_errors_when_no_source_is_valid() {
+        let candidates = PriceCandidates {
+            pyth: None,
+            broker_quote: Some(-1.0),
+            onchain_ratio: Some(149.90),
+        };
+
+        let error =
+            resolve_price(&[PriceSource::Pyth, PriceSource::BrokerQuote], &candidates).unwrap_err();
+
+        assert!(matches!(error, PriceSourceError::NoValidPrice { .. }));
+    }
+
+    #[test]
+    fn test_resolve_price_errors_on_empty_chain() {
+        let candidates = PriceCandidates {
+            pyth: Some(150.25),
+            broker_quote: None,
+            onchain_ratio: Some(149.90),
+        };
+
+        let error = resolve_price(&[], &candidates).unwrap_err();
+
+        assert!(matches!(error, PriceSourceError::EmptyChain));
+    }
+
+    #[test]
+    fn test_price_source_round_trip() {
+        for source in FULL_CHAIN {
+            assert_eq!(source.as_str().parse::<PriceSource>().unwrap(), source);
+        }
+
+        assert!("quote".parse::<PriceSource>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_trade_price_uses_pyth_without_quoting() {
+        let trade = OnchainTradeBuilder::new()
+            .with_price(dec!(149.90))
+            .with_pyth_price(150.25)
+            .build();
+
+        // A failing broker proves the quote is never requested
+        let broker = MockBroker::with_failure("quote should not be requested");
+
+        let resolution = resolve_trade_price(&broker, &FULL_CHAIN, &trade)
+            .await
+            .unwrap();
+
+        assert_eq!(resolution.source, PriceSource::Pyth);
+        assert!((resolution.price - 150.25).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_trade_price_falls_back_to_broker_quote() {
+        let trade = OnchainTradeBuilder::new().with_price(dec!(149.90)).build();
+        let broker = MockBroker::new();
+
+        let resolution = resolve_trade_price(&broker, &FULL_CHAIN, &trade)
+            .await
+            .unwrap();
+
+        assert_eq!(resolution.source, PriceSource::BrokerQuote);
+        assert!((resolution.price - 100.0).abs() < f64::EPSILON);
+        assert_eq!(resolution.attempts[0].outcome, AttemptOutcome::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_trade_price_falls_back_to_onchain_ratio() {
+        let trade = OnchainTradeBuilder::new().with_price(dec!(149.90)).build();
+        let broker = MockBroker::with_failure("quotes unavailable");
+
+        let resolution = resolve_trade_price(&broker, &FULL_CHAIN, &trade)
+            .await
+            .unwrap();
+
+        assert_eq!(resolution.source, PriceSource::OnchainRatio);
+        assert!((resolution.price - 149.90).abs() < f64::EPSILON);
+        assert_eq!(resolution.attempts[0].outcome, AttemptOutcome::Unavailable);
+        assert_eq!(resolution.attempts[1].outcome, AttemptOutcome::Unavailable);
+        assert_eq!(resolution.attempts[2].outcome, AttemptOutcome::Selected);
+    }
+
+    #[tokio::test]
+    async fn test_save_price_resolution_records_every_attempt() {
+        let pool = setup_test_db().await;
+        let tx_hash =
+            fixed_bytes!("0x1111111111111111111111111111111111111111111111111111111111111111");
+
+        let candidates = PriceCandidates {
+            pyth: None,
+            broker_quote: None,
+            onchain_ratio: Some(149.90),
+        };
+        let resolution = resolve_price(&FULL_CHAIN, &candidates).unwrap();
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        save_price_resolution(&mut sql_tx, tx_hash, 3, &resolution)
+            .await
+            .unwrap();
+        save_price_resolution(&mut sql_tx, tx_hash, 3, &resolution)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let rows = sqlx::query!(
+            "SELECT source, price, outcome
+             FROM onchain_trade_price_sources
+             WHERE tx_hash = ?1 AND log_index = 3
+             ORDER BY priority",
+            "0x1111111111111111111111111111111111111111111111111111111111111111"
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].source, "pyth");
+        assert_eq!(rows[0].outcome, "UNAVAILABLE");
+        assert_eq!(rows[1].source, "broker_quote");
+        assert_eq!(rows[1].outcome, "UNAVAILABLE");
+        assert_eq!(rows[2].source, "onchain_ratio");
+        assert_eq!(rows[2].outcome, "SELECTED");
+        assert_eq!(rows[2].price, Some(149.90));
+    }
+}
@@ -1,16 +1,29 @@
 use alloy::primitives::{B256, fixed_bytes};
-use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use super::PythPriceKind;
+use crate::lru::LruMap;
+
+/// Pyth feed IDs by symbol, cached separately for spot and EMA prices.
 #[derive(Clone)]
 pub struct FeedIdCache {
-    cache: Arc<RwLock<HashMap<String, B256>>>,
+    cache: Arc<RwLock<LruMap<(String, PythPriceKind), B256>>>,
 }
 
 impl FeedIdCache {
     pub fn new() -> Self {
-        let initial = HashMap::from([
+        Self::with_capacity(None)
+    }
+
+    /// Cache seeded with known feed IDs that holds at most `capacity` entries,
+    /// evicting the least recently used beyond that. `None` is unbounded.
+    ///
+    /// Pyth serves the spot and EMA price of an asset from the same feed, so
+    /// both kinds are seeded with the same IDs.
+    pub fn with_capacity(capacity: Option<NonZeroUsize>) -> Self {
+        let initial = [
             (
                 "AAPL".to_string(),
                 fixed_bytes!("0x49f6b65cb1de6b10eaf75e7c03ca029c306d0357e91b5311b175084a5ad55688"),
@@ -43,20 +56,32 @@ impl FeedIdCache {
                 "GME".to_string(),
                 fixed_bytes!("0x6f9cd89ef1b7fd39f667101a91ad578b6c6ace4579d5f7f285a4b06aa4504be6"),
             ),
-        ]);
+        ];
+
+        let mut cache = LruMap::new(capacity);
+        for (symbol, feed_id) in initial {
+            for kind in [PythPriceKind::Spot, PythPriceKind::Ema] {
+                cache.insert((symbol.clone(), kind), feed_id);
+            }
+        }
 
         Self {
-            cache: Arc::new(RwLock::new(initial)),
+            cache: Arc::new(RwLock::new(cache)),
         }
     }
 
-    pub async fn get(&self, symbol: &str) -> Option<B256> {
-        self.cache.read().await.get(symbol).copied()
+    pub async fn get(&self, symbol: &str, kind: PythPriceKind) -> Option<B256> {
+        // Lookups update recency, so they take the write lock
+        self.cache
+            .write()
+            .await
+            .get(&(symbol.to_string(), kind))
+            .copied()
     }
 
-    pub async fn insert(&self, symbol: String, feed_id: B256) {
+    pub async fn insert(&self, symbol: String, kind: PythPriceKind, feed_id: B256) {
         let mut cache = self.cache.write().await;
-        cache.insert(symbol, feed_id);
+        cache.insert((symbol, kind), feed_id);
     }
 }
 
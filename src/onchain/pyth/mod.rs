@@ -14,6 +14,7 @@ use crate::bindings::IPyth::{
     getEmaPriceNoOlderThanCall, getEmaPriceUnsafeCall, getPriceNoOlderThanCall, getPriceUnsafeCall,
 };
 use crate::bindings::PythStructs::Price;
+use crate::rpc_metrics::{RpcMethod, instrumented};
 
 mod feed_id_cache;
 pub use feed_id_cache::FeedIdCache;
@@ -43,9 +44,18 @@ pub enum PythError {
     InvalidTimestamp(U256),
 }
 
+/// Which Pyth price a call reads: the spot price or its exponentially-weighted
+/// moving average.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PythPriceKind {
+    Spot,
+    Ema,
+}
+
 #[derive(Debug, Clone)]
 pub struct PythCall {
     pub price_feed_id: B256,
+    pub kind: PythPriceKind,
     pub output: Bytes,
     pub depth: u32,
 }
@@ -62,9 +72,11 @@ impl PythPricing {
         tx_hash: B256,
         provider: P,
         symbol: &str,
+        kind: PythPriceKind,
         feed_id_cache: &FeedIdCache,
     ) -> Result<Self, PythError> {
-        let pyth_price = extract_pyth_price(tx_hash, &provider, symbol, feed_id_cache).await?;
+        let pyth_price =
+            extract_pyth_price(tx_hash, &provider, symbol, kind, feed_id_cache).await?;
 
         let price_decimal = pyth_price.to_decimal()?;
         let price_f64 = price_decimal
@@ -88,6 +100,17 @@ impl PythPricing {
     }
 }
 
+/// Width of a Pyth confidence interval in basis points of its price, `None`
+/// for a zero price or values that do not convert to `Decimal`.
+pub(crate) fn confidence_bps(price: f64, confidence: f64) -> Option<Decimal> {
+    let price = Decimal::from_f64(price)?.abs();
+    let confidence = Decimal::from_f64(confidence)?;
+
+    confidence
+        .checked_mul(Decimal::from(10_000))?
+        .checked_div(price)
+}
+
 fn scale_with_exponent(value: u64, exponent: i32) -> Result<f64, PythError> {
     let decimal_value = Decimal::from(value);
 
@@ -131,11 +154,12 @@ fn traverse_call_frame(frame: &CallFrame, depth: u32) -> Vec<PythCall> {
     let current_call = frame
         .to
         .filter(|&to| to == BASE_PYTH_CONTRACT_ADDRESS)
-        .filter(|_| is_pyth_method_selector(&frame.input))
-        .and(frame.output.as_ref())
-        .and_then(|output| {
+        .and_then(|_| pyth_price_kind(&frame.input))
+        .zip(frame.output.as_ref())
+        .and_then(|(kind, output)| {
             extract_price_feed_id(&frame.input).map(|feed_id| PythCall {
                 price_feed_id: feed_id,
+                kind,
                 output: output.clone(),
                 depth,
             })
@@ -149,17 +173,20 @@ fn traverse_call_frame(frame: &CallFrame, depth: u32) -> Vec<PythCall> {
     current_call.into_iter().chain(nested_calls).collect()
 }
 
-fn is_pyth_method_selector(input: &Bytes) -> bool {
-    if input.len() < 4 {
-        return false;
-    }
-
-    let selector = &input[0..4];
+/// The price kind read by a Pyth call, `None` if `input` is not a Pyth price
+/// getter.
+fn pyth_price_kind(input: &Bytes) -> Option<PythPriceKind> {
+    let selector = input.get(0..4)?;
 
-    selector == getPriceNoOlderThanCall::SELECTOR
-        || selector == getPriceUnsafeCall::SELECTOR
-        || selector == getEmaPriceNoOlderThanCall::SELECTOR
+    if selector == getPriceNoOlderThanCall::SELECTOR || selector == getPriceUnsafeCall::SELECTOR {
+        Some(PythPriceKind::Spot)
+    } else if selector == getEmaPriceNoOlderThanCall::SELECTOR
         || selector == getEmaPriceUnsafeCall::SELECTOR
+    {
+        Some(PythPriceKind::Ema)
+    } else {
+        None
+    }
 }
 
 fn extract_price_feed_id(input: &Bytes) -> Option<B256> {
@@ -213,6 +240,7 @@ pub async fn extract_pyth_price<P>(
     tx_hash: B256,
     provider: &P,
     symbol: &str,
+    kind: PythPriceKind,
     cache: &FeedIdCache,
 ) -> Result<Price, PythError>
 where
@@ -224,16 +252,19 @@ where
 
     debug!("Parsing trace for Pyth oracle calls");
 
-    let pyth_calls = find_pyth_calls(&trace)?;
+    let pyth_calls = find_pyth_calls(&trace)?
+        .into_iter()
+        .filter(|call| call.kind == kind)
+        .collect::<Vec<_>>();
 
     if pyth_calls.is_empty() {
-        warn!("No Pyth call found in transaction {tx_hash}");
+        warn!("No Pyth {kind:?} price call found in transaction {tx_hash}");
         return Err(PythError::NoPythCall);
     }
 
-    debug!("Found {} Pyth call(s) in trace", pyth_calls.len());
+    debug!("Found {} Pyth {kind:?} call(s) in trace", pyth_calls.len());
 
-    let cached_feed_id = cache.get(symbol).await;
+    let cached_feed_id = cache.get(symbol, kind).await;
 
     let matching_call = if let Some(feed_id) = cached_feed_id {
         debug!("Found cached feed ID for {symbol}: {feed_id}");
@@ -248,15 +279,15 @@ where
                 PythError::NoMatchingFeedId(feed_id)
             })?
     } else {
-        debug!("No cached feed ID for {symbol}, using first Pyth call and caching");
+        debug!("No cached {kind:?} feed ID for {symbol}, using first Pyth call and caching");
 
         let first_call = &pyth_calls[0];
         cache
-            .insert(symbol.to_string(), first_call.price_feed_id)
+            .insert(symbol.to_string(), kind, first_call.price_feed_id)
             .await;
 
         info!(
-            "Cached new feed ID mapping: {symbol} -> {}",
+            "Cached new {kind:?} feed ID mapping: {symbol} -> {}",
             first_call.price_feed_id
         );
 
@@ -292,10 +323,12 @@ where
         ..Default::default()
     };
 
-    let trace = provider
-        .debug_trace_transaction(tx_hash, options)
-        .await
-        .map_err(|e| PythError::RpcError(e.to_string()))?;
+    let trace = instrumented(
+        RpcMethod::DebugTraceTransaction,
+        provider.debug_trace_transaction(tx_hash, options),
+    )
+    .await
+    .map_err(|e| PythError::RpcError(e.to_string()))?;
 
     Ok(trace)
 }
@@ -519,36 +552,49 @@ mod tests {
     }
 
     #[test]
-    fn test_is_pyth_method_selector_valid_selectors() {
+    fn test_pyth_price_kind_valid_selectors() {
         let selectors = [
-            crate::bindings::IPyth::getPriceNoOlderThanCall::SELECTOR,
-            crate::bindings::IPyth::getPriceUnsafeCall::SELECTOR,
-            crate::bindings::IPyth::getEmaPriceNoOlderThanCall::SELECTOR,
-            crate::bindings::IPyth::getEmaPriceUnsafeCall::SELECTOR,
+            (
+                crate::bindings::IPyth::getPriceNoOlderThanCall::SELECTOR,
+                PythPriceKind::Spot,
+            ),
+            (
+                crate::bindings::IPyth::getPriceUnsafeCall::SELECTOR,
+                PythPriceKind::Spot,
+            ),
+            (
+                crate::bindings::IPyth::getEmaPriceNoOlderThanCall::SELECTOR,
+                PythPriceKind::Ema,
+            ),
+            (
+                crate::bindings::IPyth::getEmaPriceUnsafeCall::SELECTOR,
+                PythPriceKind::Ema,
+            ),
         ];
 
-        for selector in selectors {
+        for (selector, kind) in selectors {
             let mut input = selector.to_vec();
             let feed_id = B256::repeat_byte(0xff);
             input.extend_from_slice(feed_id.as_slice());
 
-            assert!(
-                is_pyth_method_selector(&Bytes::from(input)),
+            assert_eq!(
+                pyth_price_kind(&Bytes::from(input)),
+                Some(kind),
                 "Selector {selector:?} should be recognized"
             );
         }
     }
 
     #[test]
-    fn test_is_pyth_method_selector_invalid() {
+    fn test_pyth_price_kind_invalid() {
         let invalid_input = Bytes::from(vec![0xff, 0xff, 0xff, 0xff]);
-        assert!(!is_pyth_method_selector(&invalid_input));
+        assert_eq!(pyth_price_kind(&invalid_input), None);
 
         let short_input = Bytes::from(vec![0x01, 0x02]);
-        assert!(!is_pyth_method_selector(&short_input));
+        assert_eq!(pyth_price_kind(&short_input), None);
 
         let empty_input = Bytes::from(vec![]);
-        assert!(!is_pyth_method_selector(&empty_input));
+        assert_eq!(pyth_price_kind(&empty_input), None);
     }
 
     #[test]
@@ -721,6 +767,14 @@ mod tests {
         assert!((result - 42.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_confidence_bps() {
+        assert_eq!(confidence_bps(150.0, 0.15), Some(Decimal::from(10)));
+        assert_eq!(confidence_bps(-200.0, 1.0), Some(Decimal::from(50)));
+        assert_eq!(confidence_bps(0.0, 1.0), None);
+        assert_eq!(confidence_bps(f64::NAN, 1.0), None);
+    }
+
     #[test]
     fn test_scale_with_exponent_decimal_overflow() {
         let result = scale_with_exponent(u64::MAX, 10);
@@ -798,7 +852,8 @@ mod tests {
         let tx_hash = B256::repeat_byte(0xff);
         let cache = FeedIdCache::new();
 
-        let result = extract_pyth_price(tx_hash, &provider, "TEST", &cache).await;
+        let result =
+            extract_pyth_price(tx_hash, &provider, "TEST", PythPriceKind::Spot, &cache).await;
 
         assert!(matches!(result, Err(PythError::NoPythCall)));
     }
@@ -835,8 +890,91 @@ mod tests {
         let tx_hash = B256::repeat_byte(0xff);
         let cache = FeedIdCache::new();
 
-        let result = PythPricing::try_from_tx_hash(tx_hash, provider, "TEST", &cache).await;
+        let result =
+            PythPricing::try_from_tx_hash(tx_hash, provider, "TEST", PythPriceKind::Spot, &cache)
+                .await;
 
         assert!(matches!(result, Err(PythError::InvalidTimestamp(_))));
     }
+
+    fn spot_and_ema_trace(feed_id: B256) -> GethTrace {
+        let spot_selector = crate::bindings::IPyth::getPriceNoOlderThanCall::SELECTOR;
+        let ema_selector = crate::bindings::IPyth::getEmaPriceUnsafeCall::SELECTOR;
+        let call = |selector: [u8; 4], price: i64| {
+            let mut input = selector.to_vec();
+            input.extend_from_slice(feed_id.as_slice());
+            let output = Price::abi_encode(&Price {
+                price,
+                conf: 500,
+                expo: -2,
+                publishTime: U256::from(1_700_000_000u64),
+            });
+            create_test_call_frame(BASE_PYTH_CONTRACT_ADDRESS, input, Some(output), vec![])
+        };
+
+        // The EMA price is read before the spot price
+        GethTrace::CallTracer(create_test_call_frame(
+            Address::repeat_byte(0x11),
+            vec![0x01, 0x02, 0x03, 0x04],
+            Some(vec![]),
+            vec![call(ema_selector, 18_000), call(spot_selector, 18_250)],
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_extract_pyth_price_selects_requested_kind() {
+        let feed_id = B256::repeat_byte(0xaa);
+        let trace = spot_and_ema_trace(feed_id);
+        let cache = FeedIdCache::new();
+
+        for (kind, expected_price) in [(PythPriceKind::Spot, 18_250), (PythPriceKind::Ema, 18_000)]
+        {
+            let asserter = Asserter::new();
+            asserter.push_success(&serde_json::to_value(&trace).unwrap());
+            let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+            let price =
+                extract_pyth_price(B256::repeat_byte(0xff), &provider, "TEST", kind, &cache)
+                    .await
+                    .unwrap();
+
+            assert_eq!(price.price, expected_price);
+            assert_eq!(cache.get("TEST", kind).await, Some(feed_id));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_pyth_price_without_requested_kind() {
+        let mut input = crate::bindings::IPyth::getPriceUnsafeCall::SELECTOR.to_vec();
+        input.extend_from_slice(B256::repeat_byte(0xaa).as_slice());
+        let output = Price::abi_encode(&Price {
+            price: 18_250,
+            conf: 500,
+            expo: -2,
+            publishTime: U256::from(1_700_000_000u64),
+        });
+        let trace = GethTrace::CallTracer(create_test_call_frame(
+            BASE_PYTH_CONTRACT_ADDRESS,
+            input,
+            Some(output),
+            vec![],
+        ));
+
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::to_value(&trace).unwrap());
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let cache = FeedIdCache::new();
+
+        let result = extract_pyth_price(
+            B256::repeat_byte(0xff),
+            &provider,
+            "TEST",
+            PythPriceKind::Ema,
+            &cache,
+        )
+        .await;
+
+        assert!(matches!(result, Err(PythError::NoPythCall)));
+        assert_eq!(cache.get("TEST", PythPriceKind::Ema).await, None);
+    }
 }
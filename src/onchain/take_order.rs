@@ -1,14 +1,26 @@
 use alloy::providers::Provider;
 use alloy::rpc::types::Log;
+use tracing::debug;
 
-use crate::bindings::IOrderBookV4::{TakeOrderConfigV3, TakeOrderV2};
+use crate::bindings::IOrderBookV4::{OrderV3, TakeOrderConfigV3, TakeOrderV2};
 use crate::error::OnChainError;
 use crate::onchain::pyth::FeedIdCache;
 use crate::onchain::trade::{OnchainTrade, OrderFill};
 use crate::symbol::cache::SymbolCache;
 
 impl OnchainTrade {
-    /// Creates OnchainTrade directly from TakeOrderV2 blockchain events
+    /// Creates OnchainTrade directly from TakeOrderV2 blockchain events in
+    /// which `target_order_owner` is either the owner of the taken order
+    /// (maker) or the `sender` taking it (taker).
+    ///
+    /// The event's `input` and `output` are the amounts the taken order
+    /// received and gave. As the maker, the equity leg of these is our trade
+    /// as is. As the taker we received the order's output and gave its input,
+    /// so the fill is mirrored before deriving the trade: `schwab_quantity` is
+    /// still the equity amount of the fill (`output` when the order gave
+    /// equity, `input` when it received equity) but the direction is the
+    /// opposite of the maker's. A take of our own order nets out and is
+    /// skipped.
     #[tracing::instrument(skip_all, fields(tx_hash = ?log.transaction_hash, log_index = ?log.log_index), level = tracing::Level::DEBUG)]
     pub async fn try_from_take_order_if_target_owner<P: Provider>(
         cache: &SymbolCache,
@@ -18,7 +30,15 @@ impl OnchainTrade {
         target_order_owner: alloy::primitives::Address,
         feed_id_cache: &FeedIdCache,
     ) -> Result<Option<Self>, OnChainError> {
-        if event.config.order.owner != target_order_owner {
+        let owns_order = event.config.order.owner == target_order_owner;
+        let sent_take = event.sender == target_order_owner;
+
+        if owns_order && sent_take {
+            debug!("Skipping take of our own order: it does not change our position");
+            return Ok(None);
+        }
+
+        if !owns_order && !sent_take {
             return Ok(None);
         }
 
@@ -32,11 +52,31 @@ impl OnchainTrade {
         let input_index = usize::try_from(inputIOIndex)?;
         let output_index = usize::try_from(outputIOIndex)?;
 
-        let fill = OrderFill {
-            input_index,
-            input_amount: event.input,
-            output_index,
-            output_amount: event.output,
+        let (order, fill) = if owns_order {
+            (
+                order,
+                OrderFill {
+                    input_index,
+                    input_amount: event.input,
+                    output_index,
+                    output_amount: event.output,
+                },
+            )
+        } else {
+            // Our input is what the order gave and our output what it received
+            (
+                OrderV3 {
+                    validInputs: order.validOutputs,
+                    validOutputs: order.validInputs,
+                    ..order
+                },
+                OrderFill {
+                    input_index: output_index,
+                    input_amount: event.output,
+                    output_index: input_index,
+                    output_amount: event.input,
+                },
+            )
         };
 
         Self::try_from_order_and_fill_details(cache, &provider, order, fill, log, feed_id_cache)
@@ -49,6 +89,7 @@ mod tests {
     use super::*;
     use crate::bindings::IERC20::symbolCall;
     use crate::bindings::IOrderBookV4::{SignedContextV1, TakeOrderConfigV3, TakeOrderV2};
+    use crate::error::{TradeFilterReason, TradeValidationError};
     use crate::onchain::pyth::FeedIdCache;
     use crate::symbol::cache::SymbolCache;
     use crate::test_utils::{get_test_log, get_test_order};
@@ -57,6 +98,8 @@ mod tests {
     use alloy::primitives::{U256, address, fixed_bytes};
     use alloy::providers::{ProviderBuilder, mock::Asserter};
     use alloy::sol_types::SolCall;
+    use rust_decimal_macros::dec;
+    use st0x_broker::{Direction, Symbol};
     use std::str::FromStr;
 
     fn create_take_order_event_with_order(
@@ -134,7 +177,7 @@ mod tests {
 
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 9.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(9.0));
         assert_eq!(
             trade.tx_hash,
             fixed_bytes!("0xbeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee")
@@ -142,6 +185,50 @@ mod tests {
         assert_eq!(trade.log_index, 293);
     }
 
+    #[tokio::test]
+    async fn test_inverted_symbol_hedges_opposite_direction_for_same_fill() {
+        let mut directions = Vec::new();
+
+        for cache in [
+            SymbolCache::default(),
+            SymbolCache::default().with_inverted_directions([Symbol::new("AAPL").unwrap()]),
+        ] {
+            let order = get_test_order();
+            let target_order_owner = order.owner;
+            let take_event = create_take_order_event_with_order(order);
+
+            let asserter = Asserter::new();
+            asserter.push_success(&mocked_receipt_hex(
+                get_test_log().transaction_hash.unwrap(),
+            ));
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &"USDC".to_string(),
+            ));
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &"AAPL0x".to_string(),
+            ));
+            let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+            let trade = OnchainTrade::try_from_take_order_if_target_owner(
+                &cache,
+                provider,
+                take_event,
+                get_test_log(),
+                target_order_owner,
+                &FeedIdCache::default(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+            assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
+            assert_eq!(trade.amount, dec!(9.0));
+            directions.push(trade.direction);
+        }
+
+        assert_eq!(directions, vec![Direction::Sell, Direction::Buy]);
+    }
+
     #[tokio::test]
     async fn test_try_from_take_order_if_target_owner_no_match() {
         let cache = SymbolCache::default();
@@ -223,7 +310,7 @@ mod tests {
 
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 5.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(5.0));
     }
 
     #[tokio::test]
@@ -278,9 +365,9 @@ mod tests {
 
         let trade = result.unwrap();
         assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
-        assert!((trade.amount - 15.0).abs() < f64::EPSILON);
+        assert_eq!(trade.amount, dec!(15.0));
         // Price should be 200 USDC / 15 shares = 13.333... USDC per share
-        assert!((trade.price_usdc - 13.333_333_333_333_334).abs() < 0.001);
+        assert_eq!(trade.price_usdc, dec!(200) / dec!(15));
     }
 
     #[tokio::test]
@@ -332,8 +419,253 @@ mod tests {
         )
         .await;
 
-        // Zero amounts should deterministically return Ok(None)
-        assert_eq!(result.unwrap(), None);
+        // Zero amounts are deterministically filtered as unpriceable
+        assert!(matches!(
+            result.unwrap_err(),
+            OnChainError::Validation(TradeValidationError::Filtered(
+                TradeFilterReason::Unpriceable(_)
+            ))
+        ));
+    }
+
+    /// Converts a take of `input` USDC (6 decimals) for `output` AAPL0x (18
+    /// decimals) from the test order.
+    async fn convert_usdc_for_aapl(
+        input: U256,
+        output: U256,
+    ) -> Result<Option<OnchainTrade>, OnChainError> {
+        let cache = SymbolCache::default();
+        let order = get_test_order();
+        let target_order_owner = order.owner;
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x3333333333333333333333333333333333333333"),
+            config: TakeOrderConfigV3 {
+                order,
+                inputIOIndex: U256::from(0),
+                outputIOIndex: U256::from(1),
+                signedContext: vec![],
+            },
+            input,
+            output,
+        };
+
+        let asserter = Asserter::new();
+        let tx_hash =
+            fixed_bytes!("0xbeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+        asserter.push_success(&mocked_receipt_hex(tx_hash));
+        asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+            &"USDC".to_string(),
+        ));
+        asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+            &"AAPL0x".to_string(),
+        ));
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        OnchainTrade::try_from_take_order_if_target_owner(
+            &cache,
+            provider,
+            take_event,
+            get_test_log(),
+            target_order_owner,
+            &FeedIdCache::default(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_zero_output_take_order_is_filtered() {
+        let trade = convert_usdc_for_aapl(U256::from(100_000_000u64), U256::ZERO).await;
+
+        assert!(matches!(
+            trade.unwrap_err(),
+            OnChainError::Validation(TradeValidationError::Filtered(
+                TradeFilterReason::Unpriceable(reason)
+            )) if reason.contains("equity amount 0 is not positive")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_zero_input_take_order_is_filtered() {
+        let trade =
+            convert_usdc_for_aapl(U256::ZERO, U256::from(1_000_000_000_000_000_000u128)).await;
+
+        assert!(matches!(
+            trade.unwrap_err(),
+            OnChainError::Validation(TradeValidationError::Filtered(
+                TradeFilterReason::Unpriceable(reason)
+            )) if reason.contains("USDC amount 0 is not positive")
+        ));
+    }
+
+    /// Converts a take of the test order in which `target_owner` took the
+    /// order from `sender`, with the token symbols returned in lookup order.
+    async fn convert_take(
+        sender: alloy::primitives::Address,
+        target_owner: alloy::primitives::Address,
+        (input_index, input): (u64, U256),
+        (output_index, output): (u64, U256),
+        symbol_lookups: [&str; 2],
+    ) -> Option<OnchainTrade> {
+        let take_event = TakeOrderV2 {
+            sender,
+            config: TakeOrderConfigV3 {
+                order: get_test_order(),
+                inputIOIndex: U256::from(input_index),
+                outputIOIndex: U256::from(output_index),
+                signedContext: vec![],
+            },
+            input,
+            output,
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_success(&mocked_receipt_hex(
+            get_test_log().transaction_hash.unwrap(),
+        ));
+        for symbol in symbol_lookups {
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &symbol.to_string(),
+            ));
+        }
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        OnchainTrade::try_from_take_order_if_target_owner(
+            &SymbolCache::default(),
+            provider,
+            take_event,
+            get_test_log(),
+            target_owner,
+            &FeedIdCache::default(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_taker_of_order_selling_equity_hedges_opposite_of_maker() {
+        let maker = get_test_order().owner;
+        let taker = address!("0x5555555555555555555555555555555555555555");
+        // The order received 100 USDC (input, IO 0) and gave 9 AAPL0x
+        // (output, IO 1), so the equity leg is the output amount
+        let usdc = (0, U256::from(100_000_000u64));
+        let shares = (1, U256::from_str("9000000000000000000").unwrap());
+
+        let maker_view = convert_take(taker, maker, usdc, shares, ["USDC", "AAPL0x"])
+            .await
+            .unwrap();
+        // The taker received the shares and gave the USDC
+        let taker_view = convert_take(taker, taker, usdc, shares, ["AAPL0x", "USDC"])
+            .await
+            .unwrap();
+
+        assert_eq!(maker_view.direction, Direction::Sell);
+        assert_eq!(taker_view.direction, Direction::Buy);
+        for trade in [maker_view, taker_view] {
+            assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
+            assert_eq!(trade.amount, dec!(9));
+            assert_eq!(trade.price_usdc, dec!(100) / dec!(9));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_taker_of_order_buying_equity_hedges_opposite_of_maker() {
+        let maker = get_test_order().owner;
+        let taker = address!("0x5555555555555555555555555555555555555555");
+        // The order received 4 AAPL0x (input, IO 1) and gave 600 USDC
+        // (output, IO 0), so the equity leg is the input amount
+        let shares = (1, U256::from_str("4000000000000000000").unwrap());
+        let usdc = (0, U256::from(600_000_000u64));
+
+        let maker_view = convert_take(taker, maker, shares, usdc, ["AAPL0x", "USDC"])
+            .await
+            .unwrap();
+        // The taker received the USDC and gave the shares
+        let taker_view = convert_take(taker, taker, shares, usdc, ["USDC", "AAPL0x"])
+            .await
+            .unwrap();
+
+        assert_eq!(maker_view.direction, Direction::Buy);
+        assert_eq!(taker_view.direction, Direction::Sell);
+        for trade in [maker_view, taker_view] {
+            assert_eq!(trade.symbol, tokenized_symbol!("AAPL0x"));
+            assert_eq!(trade.amount, dec!(4));
+            assert_eq!(trade.price_usdc, dec!(150));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_take_of_own_order_is_skipped() {
+        let owner = get_test_order().owner;
+
+        let trade = convert_take(
+            owner,
+            owner,
+            (0, U256::from(100_000_000u64)),
+            (1, U256::from_str("9000000000000000000").unwrap()),
+            ["USDC", "AAPL0x"],
+        )
+        .await;
+
+        assert_eq!(trade, None);
+    }
+
+    #[tokio::test]
+    async fn test_try_from_take_order_if_target_owner_amount_too_large() {
+        let cache = SymbolCache::default();
+        let order = get_test_order();
+        let target_order_owner = order.owner;
+
+        let take_event = TakeOrderV2 {
+            sender: address!("0x3333333333333333333333333333333333333333"),
+            config: TakeOrderConfigV3 {
+                order,
+                inputIOIndex: U256::from(0),
+                outputIOIndex: U256::from(1),
+                signedContext: vec![SignedContextV1 {
+                    signer: address!("0x0000000000000000000000000000000000000000"),
+                    signature: vec![].into(),
+                    context: vec![],
+                }],
+            },
+            input: U256::from(100_000_000),
+            output: U256::MAX - U256::from(1),
+        };
+
+        let log = get_test_log();
+
+        let asserter = Asserter::new();
+
+        let tx_hash =
+            fixed_bytes!("0xbeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+        asserter.push_success(&mocked_receipt_hex(tx_hash));
+        asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+            &"USDC".to_string(),
+        ));
+        asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+            &"AAPL0x".to_string(),
+        ));
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let feed_id_cache = FeedIdCache::default();
+
+        let result = OnchainTrade::try_from_take_order_if_target_owner(
+            &cache,
+            provider,
+            take_event,
+            log,
+            target_order_owner,
+            &feed_id_cache,
+        )
+        .await;
+
+        // An unrepresentable amount filters the trade instead of erroring forever
+        assert!(matches!(
+            result.unwrap_err(),
+            OnChainError::Validation(TradeValidationError::Filtered(
+                TradeFilterReason::AmountTooLarge { .. }
+            ))
+        ));
     }
 
     #[tokio::test]
@@ -1,17 +1,19 @@
-use alloy::primitives::{B256, U256};
+use alloy::primitives::{Address, B256, U256};
 use alloy::providers::Provider;
 use alloy::rpc::types::Log;
 use alloy::sol_types::SolEvent;
 use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
 use serde::{Deserialize, Serialize};
-use std::num::ParseFloatError;
-use tracing::error;
+use tracing::{error, info, warn};
 
-use crate::bindings::IOrderBookV4::{ClearV2, OrderV3, TakeOrderV2};
-use crate::error::{OnChainError, TradeValidationError};
+use crate::bindings::IOrderBookV4::{ClearV2, IO, OrderV3, TakeOrderV2};
+use crate::error::{OnChainError, TradeFilterReason, TradeValidationError};
 use crate::onchain::EvmEnv;
 use crate::onchain::io::{TokenizedEquitySymbol, TradeDetails};
-use crate::onchain::pyth::FeedIdCache;
+use crate::onchain::pyth::{FeedIdCache, PythPriceKind};
 
 use super::pyth::PythPricing;
 use crate::symbol::cache::SymbolCache;
@@ -27,15 +29,46 @@ pub enum TradeEvent {
     TakeOrderV2(Box<TakeOrderV2>),
 }
 
+impl TradeEvent {
+    /// The input and output IOs the event's fill traded, i.e. the token pair
+    /// the trade is in. Indexes outside the order's IO lists are skipped.
+    pub(crate) fn traded_ios(&self) -> Vec<&IO> {
+        let (order, input_index, output_index) = match self {
+            Self::ClearV2(event) => (
+                &event.alice,
+                event.clearConfig.aliceInputIOIndex,
+                event.clearConfig.aliceOutputIOIndex,
+            ),
+            Self::TakeOrderV2(event) => (
+                &event.config.order,
+                event.config.inputIOIndex,
+                event.config.outputIOIndex,
+            ),
+        };
+
+        let input = usize::try_from(input_index)
+            .ok()
+            .and_then(|index| order.validInputs.get(index));
+        let output = usize::try_from(output_index)
+            .ok()
+            .and_then(|index| order.validOutputs.get(index));
+
+        input.into_iter().chain(output).collect()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct OnchainTrade {
     pub id: Option<i64>,
     pub tx_hash: B256,
     pub log_index: u64,
+    /// Orderbook contract that emitted the trade event. Together with
+    /// `tx_hash` and `log_index` it uniquely identifies the trade.
+    pub orderbook: Address,
     pub symbol: TokenizedEquitySymbol,
-    pub amount: f64,
+    pub amount: Decimal,
     pub direction: Direction,
-    pub price_usdc: f64,
+    pub price_usdc: Decimal,
     pub block_timestamp: Option<DateTime<Utc>>,
     pub created_at: Option<DateTime<Utc>>,
     pub gas_used: Option<u64>,
@@ -50,13 +83,25 @@ impl OnchainTrade {
     pub async fn save_within_transaction(
         &self,
         sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    ) -> Result<i64, sqlx::Error> {
+        self.save_labelled_within_transaction(sql_tx, None).await
+    }
+
+    /// Saves the trade tagged with the strategy the bot is configured for.
+    pub(crate) async fn save_labelled_within_transaction(
+        &self,
+        sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        strategy_label: Option<&str>,
     ) -> Result<i64, sqlx::Error> {
         let tx_hash_str = self.tx_hash.to_string();
         #[allow(clippy::cast_possible_wrap)]
         let log_index_i64 = self.log_index as i64;
+        let orderbook_str = self.orderbook.to_string();
 
         let direction_str = self.direction.as_str();
         let symbol_str = self.symbol.to_string();
+        let amount_str = self.amount.to_string();
+        let price_usdc_str = self.price_usdc.to_string();
         let block_timestamp_naive = self.block_timestamp.map(|dt| dt.naive_utc());
 
         let gas_used_i64 = self.gas_used.and_then(|g| i64::try_from(g).ok());
@@ -67,6 +112,7 @@ impl OnchainTrade {
             INSERT INTO onchain_trades (
                 tx_hash,
                 log_index,
+                orderbook,
                 symbol,
                 amount,
                 direction,
@@ -77,23 +123,26 @@ impl OnchainTrade {
                 pyth_price,
                 pyth_confidence,
                 pyth_exponent,
-                pyth_publish_time
+                pyth_publish_time,
+                strategy_label
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
             "#,
             tx_hash_str,
             log_index_i64,
+            orderbook_str,
             symbol_str,
-            self.amount,
+            amount_str,
             direction_str,
-            self.price_usdc,
+            price_usdc_str,
             block_timestamp_naive,
             gas_used_i64,
             effective_gas_price_i64,
             self.pyth_price,
             self.pyth_confidence,
             self.pyth_exponent,
-            self.pyth_publish_time
+            self.pyth_publish_time,
+            strategy_label
         )
         .execute(&mut **sql_tx)
         .await?;
@@ -115,6 +164,7 @@ impl OnchainTrade {
                 id,
                 tx_hash,
                 log_index,
+                orderbook,
                 symbol,
                 amount,
                 direction,
@@ -152,10 +202,11 @@ impl OnchainTrade {
             tx_hash,
             #[allow(clippy::cast_sign_loss)]
             log_index: row.log_index as u64,
+            orderbook: row.orderbook.unwrap().parse().unwrap(),
             symbol: row.symbol.parse::<TokenizedEquitySymbol>().unwrap(),
-            amount: row.amount,
+            amount: parse_stored_decimal(&row.amount).unwrap(),
             direction,
-            price_usdc: row.price_usdc,
+            price_usdc: parse_stored_decimal(&row.price_usdc).unwrap(),
             block_timestamp: row
                 .block_timestamp
                 .map(|naive_dt| DateTime::from_naive_utc_and_offset(naive_dt, Utc)),
@@ -211,59 +262,79 @@ impl OnchainTrade {
             .get(fill.output_index)
             .ok_or(TradeValidationError::NoOutputAtIndex(fill.output_index))?;
 
-        let onchain_input_amount = u256_to_f64(fill.input_amount, input.decimals)?;
-        let onchain_input_symbol = cache.get_io_symbol(&provider, input).await?;
+        let filtered = |reason: TradeFilterReason| {
+            warn!("Filtering trade tx_hash={tx_hash:?}, log_index={log_index}: {reason}");
+            OnChainError::from(TradeValidationError::from(reason))
+        };
+
+        let amounts = u256_to_decimal(fill.input_amount, input.decimals).and_then(|input_amount| {
+            Ok((
+                input_amount,
+                u256_to_decimal(fill.output_amount, output.decimals)?,
+            ))
+        });
+        let (onchain_input_amount, onchain_output_amount) = match amounts {
+            Ok(amounts) => amounts,
+            Err(TradeValidationError::AmountTooLarge { amount, decimals }) => {
+                return Err(filtered(TradeFilterReason::AmountTooLarge {
+                    amount,
+                    decimals,
+                }));
+            }
+            Err(e) => return Err(e.into()),
+        };
 
-        let onchain_output_amount = u256_to_f64(fill.output_amount, output.decimals)?;
+        let onchain_input_symbol = cache.get_io_symbol(&provider, input).await?;
         let onchain_output_symbol = cache.get_io_symbol(&provider, output).await?;
 
+        // A token whose symbol() reverted cannot be mapped to a broker ticker
+        if let Some((token, fallback_symbol)) = [input.token, output.token]
+            .into_iter()
+            .find_map(|token| cache.fallback_symbol(token).map(|symbol| (token, symbol)))
+        {
+            return Err(TradeValidationError::UnresolvedSymbol {
+                token,
+                fallback_symbol,
+            }
+            .into());
+        }
+
         // Use centralized TradeDetails::try_from_io to extract all trade data consistently
+        let convention = cache.symbol_convention();
         let trade_details = TradeDetails::try_from_io(
+            convention,
             &onchain_input_symbol,
             onchain_input_amount,
             &onchain_output_symbol,
             onchain_output_amount,
         )?;
 
-        if trade_details.equity_amount().value() == 0.0 {
-            return Ok(None);
-        }
-
-        // Calculate price per share in USDC (always USDC amount / equity amount)
-        let price_per_share_usdc =
-            trade_details.usdc_amount().value() / trade_details.equity_amount().value();
-
-        if price_per_share_usdc.is_nan() || price_per_share_usdc <= 0.0 {
-            return Ok(None);
-        }
+        let price_per_share_usdc = price_per_share_usdc(&trade_details)
+            .map_err(|reason| filtered(TradeFilterReason::Unpriceable(reason)))?;
 
         // Parse the tokenized equity symbol to ensure it's valid
-        let tokenized_symbol_str = if onchain_input_symbol == "USDC" {
+        let tokenized_symbol_str = if convention.is_stablecoin(&onchain_input_symbol) {
             onchain_output_symbol
         } else {
             onchain_input_symbol
         };
-        let tokenized_symbol = TokenizedEquitySymbol::parse(&tokenized_symbol_str)?;
+        let tokenized_symbol = convention.parse_tokenized(&tokenized_symbol_str)?;
 
-        let pyth_pricing = match PythPricing::try_from_tx_hash(
-            tx_hash,
-            &provider,
-            &tokenized_symbol.base().to_string(),
-            feed_id_cache,
+        let pyth_pricing =
+            fetch_spot_pyth_pricing(tx_hash, &provider, &tokenized_symbol, feed_id_cache).await;
+
+        check_pyth_divergence(
+            price_per_share_usdc,
+            pyth_pricing.as_ref(),
+            cache.max_pyth_divergence_pct(),
         )
-        .await
-        {
-            Ok(pricing) => Some(pricing),
-            Err(e) => {
-                error!("Failed to get Pyth pricing for tx_hash={tx_hash:?}: {e}");
-                None
-            }
-        };
+        .map_err(|reason| filtered(TradeFilterReason::PythDivergence(reason)))?;
 
         let trade = Self {
             id: None,
             tx_hash,
             log_index,
+            orderbook: log.address(),
             symbol: tokenized_symbol,
             amount: trade_details.equity_amount().value(),
             direction: trade_details.direction(),
@@ -281,7 +352,32 @@ impl OnchainTrade {
             pyth_publish_time: pyth_pricing.as_ref().map(|p| p.publish_time),
         };
 
-        Ok(Some(trade))
+        Ok(Some(trade.with_hedge_direction(cache)))
+    }
+
+    /// Inverts the direction derived from the USDC leg for symbols configured
+    /// with inverted directions, logging each inversion for audit.
+    fn with_hedge_direction(mut self, cache: &SymbolCache) -> Self {
+        if !cache.is_direction_inverted(self.symbol.base()) {
+            return self;
+        }
+
+        let derived = self.direction;
+        self.direction = match derived {
+            Direction::Buy => Direction::Sell,
+            Direction::Sell => Direction::Buy,
+        };
+
+        info!(
+            symbol = %self.symbol,
+            derived = ?derived,
+            inverted = ?self.direction,
+            tx_hash = ?self.tx_hash,
+            log_index = self.log_index,
+            "Inverting trade direction for configured symbol"
+        );
+
+        self
     }
 
     /// Attempts to create an OnchainTrade from a transaction hash by looking up
@@ -321,15 +417,57 @@ impl OnchainTrade {
         }
 
         for log in trades {
-            if let Some(trade) =
-                try_convert_log_to_onchain_trade(log, &provider, cache, env, feed_id_cache).await?
+            match try_convert_log_to_onchain_trade(log, &provider, cache, env, feed_id_cache).await
             {
-                return Ok(Some(trade));
+                Ok(Some(trade)) => return Ok(Some(trade)),
+                Ok(None) | Err(OnChainError::Validation(TradeValidationError::Filtered(_))) => {}
+                Err(e) => return Err(e),
             }
         }
 
         Ok(None)
     }
+
+    /// Runs [`Self::try_from_tx_hash`] over many transaction hashes with
+    /// bounded concurrency, returning each hash's outcome in input order.
+    pub async fn try_from_tx_hashes<P: Provider>(
+        tx_hashes: impl IntoIterator<Item = B256>,
+        provider: &P,
+        cache: &SymbolCache,
+        env: &EvmEnv,
+        feed_id_cache: &FeedIdCache,
+    ) -> Vec<(B256, Result<Option<Self>, OnChainError>)> {
+        const CONCURRENT_TX_LIMIT: usize = 8;
+
+        Self::try_from_tx_hashes_with_limit(
+            tx_hashes,
+            provider,
+            cache,
+            env,
+            feed_id_cache,
+            CONCURRENT_TX_LIMIT,
+        )
+        .await
+    }
+
+    async fn try_from_tx_hashes_with_limit<P: Provider>(
+        tx_hashes: impl IntoIterator<Item = B256>,
+        provider: &P,
+        cache: &SymbolCache,
+        env: &EvmEnv,
+        feed_id_cache: &FeedIdCache,
+        concurrency_limit: usize,
+    ) -> Vec<(B256, Result<Option<Self>, OnChainError>)> {
+        stream::iter(tx_hashes)
+            .map(|tx_hash| async move {
+                let result =
+                    Self::try_from_tx_hash(tx_hash, provider, cache, env, feed_id_cache).await;
+                (tx_hash, result)
+            })
+            .buffered(concurrency_limit)
+            .collect()
+            .await
+    }
 }
 
 #[derive(Debug)]
@@ -385,35 +523,158 @@ async fn try_convert_log_to_onchain_trade<P: Provider>(
     Ok(None)
 }
 
-/// Helper that converts a fixed-decimal U256 amount into an f64 using the provided number of decimals.
-fn u256_to_f64(amount: U256, decimals: u8) -> Result<f64, ParseFloatError> {
-    if amount.is_zero() {
-        return Ok(0.);
+/// Price per share in USDC (always USDC amount / equity amount), or why the
+/// trade cannot be priced. A zero amount on either leg would otherwise store a
+/// zero or undefined price.
+fn price_per_share_usdc(trade_details: &TradeDetails) -> Result<Decimal, String> {
+    let equity_amount = trade_details.equity_amount().value();
+    let usdc_amount = trade_details.usdc_amount().value();
+
+    if equity_amount <= Decimal::ZERO {
+        return Err(format!("equity amount {equity_amount} is not positive"));
+    }
+
+    if usdc_amount <= Decimal::ZERO {
+        return Err(format!("USDC amount {usdc_amount} is not positive"));
+    }
+
+    usdc_amount.checked_div(equity_amount).ok_or_else(|| {
+        format!("price per share of {usdc_amount} USDC for {equity_amount} shares overflows")
+    })
+}
+
+/// Spot Pyth price of the equity read by the transaction, or `None` (logged)
+/// if it cannot be found, in which case the trade is stored without one.
+async fn fetch_spot_pyth_pricing<P: Provider>(
+    tx_hash: B256,
+    provider: &P,
+    tokenized_symbol: &TokenizedEquitySymbol,
+    feed_id_cache: &FeedIdCache,
+) -> Option<PythPricing> {
+    match PythPricing::try_from_tx_hash(
+        tx_hash,
+        provider,
+        &tokenized_symbol.base().to_string(),
+        PythPriceKind::Spot,
+        feed_id_cache,
+    )
+    .await
+    {
+        Ok(pricing) => Some(pricing),
+        Err(e) => {
+            error!("Failed to get Pyth pricing for tx_hash={tx_hash:?}: {e}");
+            None
+        }
     }
+}
 
-    let u256_str = amount.to_string();
-    let decimals = decimals as usize;
+/// Rejects an onchain price per share that differs from the Pyth price by
+/// more than `max_divergence_pct` percent of the Pyth price, which points to a
+/// mis-scaled or near-zero leg rather than a real fill. Trades without a Pyth
+/// price cannot be checked and pass.
+fn check_pyth_divergence(
+    price_per_share_usdc: Decimal,
+    pyth_pricing: Option<&PythPricing>,
+    max_divergence_pct: Option<Decimal>,
+) -> Result<(), String> {
+    let (Some(max_divergence_pct), Some(pyth_pricing)) = (max_divergence_pct, pyth_pricing) else {
+        return Ok(());
+    };
 
-    let formatted = if decimals == 0 {
-        u256_str
-    } else if u256_str.len() <= decimals {
-        format!("0.{}{}", "0".repeat(decimals - u256_str.len()), u256_str)
-    } else {
-        let (int_part, frac_part) = u256_str.split_at(u256_str.len() - decimals);
-        format!("{int_part}.{frac_part}")
+    let Some(pyth_price) = Decimal::from_f64(pyth_pricing.price).filter(|p| *p > Decimal::ZERO)
+    else {
+        return Ok(());
     };
 
-    formatted.parse::<f64>()
+    let divergence_pct = (price_per_share_usdc - pyth_price)
+        .abs()
+        .checked_mul(Decimal::ONE_HUNDRED)
+        .and_then(|scaled| scaled.checked_div(pyth_price));
+
+    match divergence_pct {
+        Some(divergence_pct) if divergence_pct <= max_divergence_pct => Ok(()),
+        _ => Err(format!(
+            "price per share {price_per_share_usdc} USDC diverges from Pyth price {pyth_price} \
+             by more than {max_divergence_pct}%"
+        )),
+    }
+}
+
+/// Most decimal places a [`Decimal`] holds. Token amounts with more decimals
+/// are truncated to this precision, far below a share or a cent.
+const MAX_DECIMAL_SCALE: u8 = 28;
+
+/// Converts a fixed-point token amount to a [`Decimal`] built exactly from its
+/// base units, rejecting amounts whose base units do not fit the 96-bit
+/// mantissa with [`TradeValidationError::AmountTooLarge`]. Only decimals
+/// beyond [`MAX_DECIMAL_SCALE`] are dropped.
+fn u256_to_decimal(amount: U256, decimals: u8) -> Result<Decimal, TradeValidationError> {
+    let too_large = || TradeValidationError::AmountTooLarge { amount, decimals };
+
+    // 10^excess overflows U256 only for more than 77 decimals, where every
+    // amount truncates to zero
+    let excess_decimals = decimals.saturating_sub(MAX_DECIMAL_SCALE);
+    let base_units = U256::from(10_u8)
+        .checked_pow(U256::from(excess_decimals))
+        .map_or(U256::ZERO, |scale| amount / scale);
+
+    let base_units = u128::try_from(base_units)
+        .ok()
+        .and_then(|units| i128::try_from(units).ok())
+        .ok_or_else(too_large)?;
+
+    Decimal::try_from_i128_with_scale(base_units, u32::from(decimals.min(MAX_DECIMAL_SCALE)))
+        .map(|decimal| decimal.normalize())
+        .map_err(|_| too_large())
+}
+
+/// Parses an amount or price stored as TEXT. Values migrated from the former
+/// REAL columns may be in scientific notation (e.g. `1.0e-05`).
+pub(crate) fn parse_stored_decimal(value: &str) -> Result<Decimal, rust_decimal::Error> {
+    value
+        .parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(value))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::onchain::EvmEnv;
+    use crate::bindings::IERC20::symbolCall;
+    use crate::onchain::io::SymbolConvention;
+    use crate::onchain::{DEFAULT_BACKFILL_BATCH_SIZE, EvmEnv};
     use crate::symbol::cache::SymbolCache;
-    use crate::test_utils::setup_test_db;
-    use alloy::primitives::fixed_bytes;
+    use crate::test_utils::{create_mock_blockchain_data, get_test_order, setup_test_db};
+    use alloy::primitives::{address, fixed_bytes};
     use alloy::providers::{ProviderBuilder, mock::Asserter};
+    use alloy::sol_types::SolCall;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    fn pyth_pricing(price: f64) -> PythPricing {
+        PythPricing {
+            price,
+            confidence: 0.05,
+            exponent: -8,
+            publish_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_price_diverging_from_pyth_is_rejected() {
+        let pyth = pyth_pricing(150.0);
+
+        // A near-zero leg pricing the share 10x the Pyth price
+        let reason = check_pyth_divergence(dec!(1500), Some(&pyth), Some(dec!(5))).unwrap_err();
+        assert!(reason.contains("diverges from Pyth price 150"), "{reason}");
+        check_pyth_divergence(dec!(15), Some(&pyth), Some(dec!(5))).unwrap_err();
+
+        check_pyth_divergence(dec!(155), Some(&pyth), Some(dec!(5))).unwrap();
+        check_pyth_divergence(dec!(142.5), Some(&pyth), Some(dec!(5))).unwrap();
+
+        // Unchecked without a limit or a Pyth price
+        check_pyth_divergence(dec!(1500), Some(&pyth), None).unwrap();
+        check_pyth_divergence(dec!(1500), None, Some(dec!(5))).unwrap();
+    }
 
     #[tokio::test]
     async fn test_onchain_trade_save_within_transaction_and_find() {
@@ -425,10 +686,11 @@ mod tests {
                 "0xbeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
             ),
             log_index: 42,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: "AAPL0x".parse::<TokenizedEquitySymbol>().unwrap(),
-            amount: 10.0,
+            amount: dec!(10.0),
             direction: Direction::Sell,
-            price_usdc: 150.25,
+            price_usdc: dec!(150.25),
             block_timestamp: DateTime::from_timestamp(1_672_531_200, 0), // Jan 1, 2023 00:00:00 UTC
             created_at: None,
             gas_used: Some(21000),
@@ -452,9 +714,9 @@ mod tests {
         assert_eq!(found.tx_hash, trade.tx_hash);
         assert_eq!(found.log_index, trade.log_index);
         assert_eq!(found.symbol, trade.symbol);
-        assert!((found.amount - trade.amount).abs() < f64::EPSILON);
+        assert_eq!(found.amount, trade.amount);
         assert_eq!(found.direction, trade.direction);
-        assert!((found.price_usdc - trade.price_usdc).abs() < f64::EPSILON);
+        assert_eq!(found.price_usdc, trade.price_usdc);
         assert_eq!(found.block_timestamp, trade.block_timestamp);
         assert_eq!(found.gas_used, trade.gas_used);
         assert_eq!(found.effective_gas_price, trade.effective_gas_price);
@@ -463,16 +725,58 @@ mod tests {
     }
 
     #[test]
-    fn test_u256_to_f64_edge_cases() {
-        assert!((u256_to_f64(U256::ZERO, 18).unwrap() - 0.0).abs() < f64::EPSILON);
+    fn test_u256_to_decimal_edge_cases() {
+        assert_eq!(u256_to_decimal(U256::ZERO, 18).unwrap(), Decimal::ZERO);
 
         let max_safe = U256::from(9_007_199_254_740_991_u64);
-        let result = u256_to_f64(max_safe, 0).unwrap();
-        assert!((result - 9_007_199_254_740_991.0).abs() < 1.0);
+        let result = u256_to_decimal(max_safe, 0).unwrap();
+        assert_eq!(result, Decimal::from(9_007_199_254_740_991_u64));
 
         let very_large = U256::MAX;
-        let result = u256_to_f64(very_large, 18);
-        assert!(result.is_ok());
+        let result = u256_to_decimal(very_large, 18);
+        assert!(matches!(
+            result.unwrap_err(),
+            TradeValidationError::AmountTooLarge { decimals: 18, .. }
+        ));
+    }
+
+    #[test]
+    fn test_u256_to_decimal_amount_too_large() {
+        let near_max = U256::MAX - U256::from(1);
+        for decimals in [0, 6, 18] {
+            assert!(matches!(
+                u256_to_decimal(near_max, decimals).unwrap_err(),
+                TradeValidationError::AmountTooLarge { amount, .. } if amount == near_max
+            ));
+        }
+
+        // The largest mantissa still converts, one base unit above does not
+        let limit = U256::from(Decimal::MAX.mantissa().unsigned_abs());
+        assert_eq!(
+            u256_to_decimal(limit, 6).unwrap(),
+            Decimal::MAX / Decimal::from(1_000_000)
+        );
+        assert!(matches!(
+            u256_to_decimal(limit + U256::from(1), 6).unwrap_err(),
+            TradeValidationError::AmountTooLarge { .. }
+        ));
+
+        // Huge raw values are fine when most of the digits are decimals
+        assert!(u256_to_decimal(U256::MAX, 77).is_ok());
+    }
+
+    #[test]
+    fn test_u256_to_decimal_is_exact_for_18_decimal_tokens() {
+        // 0.1 + 0.2 shares at 18 decimals, which f64 cannot represent exactly
+        let first = u256_to_decimal(U256::from(100_000_000_000_000_000_u64), 18).unwrap();
+        let second = u256_to_decimal(U256::from(200_000_000_000_000_000_u64), 18).unwrap();
+        assert_eq!(first + second, dec!(0.3));
+
+        let amount = U256::from(123_456_789_012_345_678_901_u128);
+        assert_eq!(
+            u256_to_decimal(amount, 18).unwrap(),
+            dec!(123.456789012345678901)
+        );
     }
 
     #[tokio::test]
@@ -517,10 +821,11 @@ mod tests {
                 "0x1111111111111111111111111111111111111111111111111111111111111111"
             ),
             log_index: 100,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: "AAPL0x".parse::<TokenizedEquitySymbol>().unwrap(),
-            amount: 10.0,
+            amount: dec!(10.0),
             direction: Direction::Buy,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: DateTime::from_timestamp(1_672_531_800, 0), // Jan 1, 2023 00:10:00 UTC
             created_at: None,
             gas_used: Some(50000), // Complex contract interaction
@@ -558,10 +863,11 @@ mod tests {
                 "0x2222222222222222222222222222222222222222222222222222222222222222"
             ),
             log_index: u64::MAX, // Will become -1 when cast to i64
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: "AAPL0x".parse::<TokenizedEquitySymbol>().unwrap(),
-            amount: 10.0,
+            amount: dec!(10.0),
             direction: Direction::Buy,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: None,
@@ -581,34 +887,44 @@ mod tests {
     }
 
     #[test]
-    fn test_u256_to_f64_precision_loss() {
-        // Test precision loss with very large numbers
+    fn test_u256_to_decimal_precision_loss() {
+        // Very large numbers are rejected rather than rounded
         let very_large = U256::MAX;
-        let result = u256_to_f64(very_large, 0).unwrap();
-        assert!(result.is_finite());
+        assert!(matches!(
+            u256_to_decimal(very_large, 0).unwrap_err(),
+            TradeValidationError::AmountTooLarge { .. }
+        ));
 
         // Test with maximum decimals
         let small_amount = U256::from(1);
-        let result = u256_to_f64(small_amount, 255).unwrap(); // Max u8 value
-        assert!((result - 0.0).abs() < f64::EPSILON); // Should be rounded to 0 due to extreme precision
+        let result = u256_to_decimal(small_amount, 255).unwrap(); // Max u8 value
+        assert_eq!(result, Decimal::ZERO); // Truncated beyond the maximum scale
     }
 
     #[test]
-    fn test_u256_to_f64_formatting_edge_cases() {
+    fn test_u256_to_decimal_formatting_edge_cases() {
         // Test with exactly decimal places length
         let amount = U256::from(123_456);
-        let result = u256_to_f64(amount, 6).unwrap();
-        assert!((result - 0.123_456).abs() < f64::EPSILON);
+        let result = u256_to_decimal(amount, 6).unwrap();
+        assert_eq!(result, dec!(0.123456));
 
         // Test with more decimals than digits
         let amount = U256::from(5);
-        let result = u256_to_f64(amount, 10).unwrap();
-        assert!((result - 0.000_000_000_5).abs() < f64::EPSILON);
+        let result = u256_to_decimal(amount, 10).unwrap();
+        assert_eq!(result, dec!(0.0000000005));
 
         // Test with zero decimals
         let amount = U256::from(12345);
-        let result = u256_to_f64(amount, 0).unwrap();
-        assert!((result - 12_345.0).abs() < f64::EPSILON);
+        let result = u256_to_decimal(amount, 0).unwrap();
+        assert_eq!(result, dec!(12345));
+    }
+
+    #[test]
+    fn test_parse_stored_decimal_accepts_migrated_reals() {
+        assert_eq!(parse_stored_decimal("150.25").unwrap(), dec!(150.25));
+        assert_eq!(parse_stored_decimal("9.0").unwrap(), dec!(9));
+        assert_eq!(parse_stored_decimal("1.0e-05").unwrap(), dec!(0.00001));
+        assert!(parse_stored_decimal("not a number").is_err());
     }
 
     #[tokio::test]
@@ -624,6 +940,8 @@ mod tests {
             orderbook: alloy::primitives::Address::ZERO,
             order_owner: alloy::primitives::Address::ZERO,
             deployment_block: 0,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
         };
 
         let tx_hash =
@@ -639,6 +957,78 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_try_from_tx_hashes_reports_per_tx_outcomes() {
+        let env = EvmEnv {
+            ws_rpc_url: "ws://localhost:8545".parse().unwrap(),
+            orderbook: address!("0x1111111111111111111111111111111111111111"),
+            order_owner: get_test_order().owner,
+            deployment_block: 0,
+            symbol_convention: SymbolConvention::default(),
+            backfill_batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
+        };
+
+        let not_found_hash =
+            fixed_bytes!("0x4444444444444444444444444444444444444444444444444444444444444444");
+        let non_matching_hash =
+            fixed_bytes!("0x5555555555555555555555555555555555555555555555555555555555555555");
+        let valid_hash =
+            fixed_bytes!("0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef");
+
+        let mut non_matching_receipt =
+            create_mock_blockchain_data(env.orderbook, non_matching_hash, "1", 1).receipt_json;
+        non_matching_receipt["logs"] = json!([]);
+
+        let valid = create_mock_blockchain_data(
+            env.orderbook,
+            valid_hash,
+            "9000000000000000000",
+            100_000_000,
+        );
+
+        // Sequential processing keeps the mocked responses in request order
+        let asserter = Asserter::new();
+        asserter.push_success(&serde_json::Value::Null);
+        asserter.push_success(&non_matching_receipt);
+        asserter.push_success(&valid.receipt_json);
+        asserter.push_success(&json!([valid.after_clear_log]));
+        asserter.push_success(&valid.receipt_json);
+        asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+            &"USDC".to_string(),
+        ));
+        asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+            &"AAPL0x".to_string(),
+        ));
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let outcomes = OnchainTrade::try_from_tx_hashes_with_limit(
+            [not_found_hash, non_matching_hash, valid_hash],
+            &provider,
+            &SymbolCache::default(),
+            &env,
+            &FeedIdCache::default(),
+            1,
+        )
+        .await;
+
+        let hashes: Vec<_> = outcomes.iter().map(|(hash, _)| *hash).collect();
+        assert_eq!(hashes, vec![not_found_hash, non_matching_hash, valid_hash]);
+
+        let mut results = outcomes.into_iter().map(|(_, result)| result);
+
+        assert!(matches!(
+            results.next().unwrap().unwrap_err(),
+            OnChainError::Validation(TradeValidationError::TransactionNotFound(hash))
+                if hash == not_found_hash
+        ));
+        assert!(results.next().unwrap().unwrap().is_none());
+
+        let trade = results.next().unwrap().unwrap().unwrap();
+        assert_eq!(trade.tx_hash, valid_hash);
+        assert_eq!(trade.symbol.to_string(), "AAPL0x");
+        assert_eq!(trade.amount, dec!(9));
+    }
+
     #[tokio::test]
     async fn test_find_by_tx_hash_database_error() {
         let pool = setup_test_db().await;
@@ -674,11 +1064,12 @@ mod tests {
                 id: None,
                 tx_hash: alloy::primitives::B256::from(tx_hash_bytes),
                 log_index: u64::from(i),
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: crate::onchain::io::TokenizedEquitySymbol::parse(&format!("TEST{i}0x"))
                     .unwrap(),
-                amount: 10.0,
+                amount: dec!(10.0),
                 direction: Direction::Buy,
-                price_usdc: 150.0,
+                price_usdc: dec!(150.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: None,
@@ -0,0 +1,184 @@
+//! Notifications for orders that filled or failed at the broker.
+//!
+//! The order poller hands every such status change to an [`OrderNotifier`].
+//! The webhook notifier posts a JSON summary of the order to a configured URL,
+//! e.g. a Slack or Discord incoming webhook. Delivery runs on its own task so
+//! a slow or unreachable webhook never holds up polling, and a failed delivery
+//! is logged rather than retried. Without a configured URL it does nothing.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt::Display;
+use std::time::Duration;
+use tracing::{debug, warn};
+use url::Url;
+
+use st0x_broker::{Direction, OrderStatus, OrderUpdate};
+
+/// How long a webhook call may take before it is abandoned.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Receives order status changes. Implementations must not block the caller.
+pub(crate) trait OrderNotifier {
+    fn on_order_update<OrderId: Display>(&self, update: &OrderUpdate<OrderId>);
+}
+
+/// Body posted to the webhook for an order status change.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct OrderNotification {
+    /// Human readable summary, shown as the message by Slack-style webhooks
+    text: String,
+    order_id: String,
+    symbol: String,
+    shares: u64,
+    direction: Direction,
+    status: OrderStatus,
+    price_cents: Option<u64>,
+    updated_at: DateTime<Utc>,
+}
+
+impl<OrderId: Display> From<&OrderUpdate<OrderId>> for OrderNotification {
+    fn from(update: &OrderUpdate<OrderId>) -> Self {
+        let price = update.price_cents.map_or_else(String::new, |cents| {
+            format!(" at ${}.{:02}", cents / 100, cents % 100)
+        });
+
+        Self {
+            text: format!(
+                "Order {} {}: {} {} {}{price}",
+                update.order_id,
+                update.status.as_str(),
+                update.direction.as_str(),
+                update.shares,
+                update.symbol
+            ),
+            order_id: update.order_id.to_string(),
+            symbol: update.symbol.to_string(),
+            shares: u64::from(update.shares.value()),
+            direction: update.direction,
+            status: update.status,
+            price_cents: update.price_cents,
+            updated_at: update.updated_at,
+        }
+    }
+}
+
+/// Posts order status changes to a webhook, or does nothing when no URL is
+/// configured.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WebhookNotifier(Option<Webhook>);
+
+#[derive(Debug, Clone)]
+struct Webhook {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl WebhookNotifier {
+    pub(crate) fn new(url: Option<Url>) -> Self {
+        Self(url.map(|url| {
+            Webhook {
+                client: reqwest::Client::builder()
+                    .timeout(WEBHOOK_TIMEOUT)
+                    .build()
+                    .unwrap_or_default(),
+                url,
+            }
+        }))
+    }
+}
+
+impl OrderNotifier for WebhookNotifier {
+    fn on_order_update<OrderId: Display>(&self, update: &OrderUpdate<OrderId>) {
+        let Some(webhook) = self.0.clone() else {
+            return;
+        };
+
+        let notification = OrderNotification::from(update);
+        tokio::spawn(async move {
+            let result = webhook
+                .client
+                .post(webhook.url)
+                .json(&notification)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+
+            match result {
+                Ok(_) => debug!("Delivered order notification for {}", notification.order_id),
+                Err(e) => warn!(
+                    "Failed to deliver order notification for {}: {e}",
+                    notification.order_id
+                ),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use httpmock::prelude::*;
+    use st0x_broker::{Shares, Symbol};
+
+    fn filled_update() -> OrderUpdate<String> {
+        OrderUpdate {
+            order_id: "1004055538".to_string(),
+            symbol: Symbol::new("AAPL").unwrap(),
+            shares: Shares::new(25).unwrap(),
+            direction: Direction::Buy,
+            status: OrderStatus::Filled,
+            updated_at: Utc.with_ymd_and_hms(2025, 1, 15, 14, 30, 0).unwrap(),
+            price_cents: Some(15025),
+            reported_price: Some("150.25".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fill_is_posted_to_webhook() {
+        let server = MockServer::start();
+        let webhook = server.mock(|when, then| {
+            when.method(POST).path("/orders").json_body_partial(
+                r#"{
+                    "text": "Order 1004055538 FILLED: BUY 25 AAPL at $150.25",
+                    "order_id": "1004055538",
+                    "symbol": "AAPL",
+                    "shares": 25,
+                    "direction": "BUY",
+                    "status": "FILLED",
+                    "price_cents": 15025,
+                    "updated_at": "2025-01-15T14:30:00Z"
+                }"#,
+            );
+            then.status(200);
+        });
+
+        let notifier = WebhookNotifier::new(Some(server.url("/orders").parse().unwrap()));
+        notifier.on_order_update(&filled_update());
+
+        // Delivery runs in the background
+        for _ in 0..100 {
+            if webhook.hits() > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        webhook.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_order_without_price_and_unconfigured_notifier() {
+        let mut update = filled_update();
+        update.status = OrderStatus::Failed;
+        update.price_cents = None;
+
+        let notification = OrderNotification::from(&update);
+        assert_eq!(notification.text, "Order 1004055538 FAILED: BUY 25 AAPL");
+        assert_eq!(notification.price_cents, None);
+
+        // Without a URL nothing is spawned or sent
+        WebhookNotifier::default().on_order_update(&update);
+    }
+}
@@ -1,15 +1,20 @@
-use alloy::primitives::B256;
+use alloy::primitives::{Address, B256};
+use alloy::providers::Provider;
 use alloy::rpc::types::Log;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::str::FromStr;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+use st0x_broker::Symbol;
 
 use crate::bindings::IOrderBookV4::{ClearV2, TakeOrderV2};
 use crate::error::EventQueueError;
 use crate::onchain::trade::TradeEvent;
+use crate::symbol::cache::SymbolCache;
 
 /// Trait for events that can be enqueued
 pub trait Enqueueable {
@@ -28,11 +33,132 @@ impl Enqueueable for TakeOrderV2 {
     }
 }
 
+/// Processing priority of a base symbol's events, parsed from
+/// `SYMBOL=PRIORITY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SymbolPriority {
+    pub(crate) symbol: Symbol,
+    pub(crate) priority: i64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SymbolPriorityParseError {
+    #[error("Expected SYMBOL=PRIORITY but got '{0}'")]
+    MissingSeparator(String),
+    #[error("Invalid symbol '{0}'")]
+    InvalidSymbol(String),
+    #[error("Invalid priority '{0}'")]
+    InvalidPriority(String),
+}
+
+impl FromStr for SymbolPriority {
+    type Err = SymbolPriorityParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (symbol, priority) = value
+            .split_once('=')
+            .ok_or_else(|| SymbolPriorityParseError::MissingSeparator(value.to_string()))?;
+
+        let symbol = Symbol::new(symbol.trim().to_uppercase())
+            .map_err(|_| SymbolPriorityParseError::InvalidSymbol(symbol.to_string()))?;
+
+        let priority = priority
+            .trim()
+            .parse()
+            .map_err(|_| SymbolPriorityParseError::InvalidPriority(priority.to_string()))?;
+
+        Ok(Self { symbol, priority })
+    }
+}
+
+/// Configured event processing priorities by base symbol. Events for other
+/// symbols, or whose symbol cannot be resolved, have priority 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct EventPriorities {
+    priorities: HashMap<Symbol, i64>,
+}
+
+impl EventPriorities {
+    pub(crate) fn new(priorities: impl IntoIterator<Item = SymbolPriority>) -> Self {
+        Self {
+            priorities: priorities
+                .into_iter()
+                .map(|entry| (entry.symbol, entry.priority))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.priorities.is_empty()
+    }
+
+    /// Base symbol and priority of the tokenized equity traded in `event`,
+    /// the highest priority one when it trades several. The symbol is `None`
+    /// when no traded token resolves to a tokenized equity, and equities
+    /// without a configured priority have priority 0.
+    async fn rank<P: Provider>(
+        &self,
+        event: &TradeEvent,
+        cache: &SymbolCache,
+        provider: &P,
+    ) -> (Option<Symbol>, i64) {
+        let mut ranked_symbol = None;
+        let mut priority = None;
+
+        for io in event.traded_ios() {
+            let symbol = match cache.get_io_symbol(provider, io).await {
+                Ok(symbol) => symbol,
+                Err(e) => {
+                    debug!(
+                        "Could not resolve symbol of token {} for priority: {e}",
+                        io.token
+                    );
+                    continue;
+                }
+            };
+
+            let Ok(equity) = cache.symbol_convention().parse_tokenized(&symbol) else {
+                continue;
+            };
+
+            let configured = self.priorities.get(equity.base()).copied();
+            if ranked_symbol.is_none() || configured > priority {
+                ranked_symbol = Some(equity.base().clone());
+            }
+            priority = priority.max(configured);
+        }
+
+        (ranked_symbol, priority.unwrap_or(0))
+    }
+}
+
+/// Serialization format written to `event_data` for newly queued events.
+///
+/// Bump this whenever the serialized form of [`TradeEvent`] changes, keeping
+/// a decoder for every earlier version in [`decode_trade_event`] so rows
+/// queued by an older build can still be processed.
+pub(crate) const EVENT_FORMAT_VERSION: i64 = 1;
+
+/// Decodes `event_data` written in the given format version.
+fn decode_trade_event(
+    format_version: i64,
+    event_data: &str,
+) -> Result<TradeEvent, EventQueueError> {
+    match format_version {
+        1 => serde_json::from_str(event_data)
+            .map_err(|e| EventQueueError::Processing(format!("Failed to deserialize event: {e}"))),
+        unknown => Err(EventQueueError::UnknownFormatVersion(unknown)),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct QueuedEvent {
     pub(crate) id: Option<i64>,
     pub(crate) tx_hash: B256,
     pub(crate) log_index: u64,
+    /// Orderbook that emitted the event, or `None` for rows queued before the
+    /// orderbook was recorded.
+    pub(crate) orderbook: Option<Address>,
     pub(crate) block_number: u64,
     pub(crate) event: TradeEvent,
     pub(crate) processed: bool,
@@ -45,6 +171,18 @@ async fn enqueue_event(
     pool: &SqlitePool,
     log: &Log,
     event: TradeEvent,
+) -> Result<(), EventQueueError> {
+    let mut sql_tx = pool.begin().await?;
+    enqueue_event_within_transaction(&mut sql_tx, log, event).await?;
+    sql_tx.commit().await?;
+
+    Ok(())
+}
+
+async fn enqueue_event_within_transaction(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    log: &Log,
+    event: TradeEvent,
 ) -> Result<(), EventQueueError> {
     let tx_hash = log
         .transaction_hash
@@ -65,6 +203,7 @@ async fn enqueue_event(
         .map_err(|_| EventQueueError::Processing("Block number too large".to_string()))?;
 
     let tx_hash_str = format!("{tx_hash:#x}");
+    let orderbook_str = log.address().to_string();
     let event_json = serde_json::to_string(&event)
         .map_err(|e| EventQueueError::Processing(format!("Failed to serialize event: {e}")))?;
 
@@ -90,73 +229,407 @@ async fn enqueue_event(
     sqlx::query!(
         r#"
         INSERT OR IGNORE INTO event_queue
-        (tx_hash, log_index, block_number, event_data, processed, block_timestamp)
-        VALUES (?, ?, ?, ?, 0, ?)
+        (tx_hash, log_index, orderbook, block_number, event_data, format_version, processed, block_timestamp)
+        VALUES (?, ?, ?, ?, ?, ?, 0, ?)
         "#,
         tx_hash_str,
         log_index_i64,
+        orderbook_str,
         block_number_i64,
         event_json,
+        EVENT_FORMAT_VERSION,
         block_timestamp_naive
     )
-    .execute(pool)
+    .execute(&mut **sql_tx)
     .await?;
 
     Ok(())
 }
 
-/// Gets the next unprocessed event from the queue, ordered by block number then log index
+struct QueueRow {
+    id: i64,
+    tx_hash: String,
+    log_index: i64,
+    orderbook: Option<String>,
+    block_number: i64,
+    event_data: String,
+    format_version: i64,
+    processed: bool,
+    created_at: NaiveDateTime,
+    processed_at: Option<NaiveDateTime>,
+    block_timestamp: Option<NaiveDateTime>,
+}
+
+impl QueueRow {
+    /// Decodes the row's event, dead-lettering it and returning `None` if it
+    /// was written in an unknown format version.
+    async fn decode(&self, pool: &SqlitePool) -> Result<Option<TradeEvent>, EventQueueError> {
+        match decode_trade_event(self.format_version, &self.event_data) {
+            Ok(event) => Ok(Some(event)),
+            Err(e @ EventQueueError::UnknownFormatVersion(_)) => {
+                error!(
+                    "Dead-lettering queued event {}: {e}, tx_hash={}, log_index={}",
+                    self.id, self.tx_hash, self.log_index
+                );
+                dead_letter_event(pool, self.id, &e.to_string()).await?;
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn into_queued_event(self, event: TradeEvent) -> Result<QueuedEvent, EventQueueError> {
+        let tx_hash = B256::from_str(&self.tx_hash)
+            .map_err(|e| EventQueueError::Processing(format!("Invalid tx_hash format: {e}")))?;
+
+        let orderbook = self
+            .orderbook
+            .map(|orderbook| Address::from_str(&orderbook))
+            .transpose()
+            .map_err(|e| EventQueueError::Processing(format!("Invalid orderbook format: {e}")))?;
+
+        Ok(QueuedEvent {
+            id: Some(self.id),
+            tx_hash,
+            log_index: self.log_index.try_into().map_err(|_| {
+                EventQueueError::Processing("Log index conversion failed".to_string())
+            })?,
+            orderbook,
+            block_number: self.block_number.try_into().map_err(|_| {
+                EventQueueError::Processing("Block number conversion failed".to_string())
+            })?,
+            event,
+            processed: self.processed,
+            created_at: Some(self.created_at.and_utc()),
+            processed_at: self.processed_at.map(|dt| dt.and_utc()),
+            block_timestamp: self.block_timestamp.map(|dt| dt.and_utc()),
+        })
+    }
+}
+
+/// Gets the next unprocessed, unclaimed event from the queue, ordered by block
+/// number then log index.
+///
+/// Events in an unknown format version are dead-lettered and skipped, so a
+/// row written by an incompatible build cannot jam the queue.
 #[tracing::instrument(skip(pool), level = tracing::Level::DEBUG)]
 pub(crate) async fn get_next_unprocessed_event(
     pool: &SqlitePool,
 ) -> Result<Option<QueuedEvent>, EventQueueError> {
-    let row = sqlx::query!(
+    loop {
+        let row = sqlx::query_as!(
+            QueueRow,
+            r#"
+            SELECT
+                id,
+                tx_hash,
+                log_index,
+                orderbook,
+                block_number,
+                event_data,
+                format_version,
+                processed,
+                created_at,
+                processed_at,
+                block_timestamp
+            FROM event_queue
+            WHERE processed = 0 AND claimed_at IS NULL AND dead_lettered_at IS NULL
+            ORDER BY block_number ASC, log_index ASC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let Some(event) = row.decode(pool).await? else {
+            continue;
+        };
+
+        return row.into_queued_event(event).map(Some);
+    }
+}
+
+/// Gets the next unprocessed, unclaimed event from the queue, taking the
+/// highest configured symbol priority first and block number then log index
+/// within a priority.
+///
+/// Events queued since the last call are ranked first, storing their symbol
+/// and priority so each event is decoded and resolved only once. Without
+/// configured priorities this is [`get_next_unprocessed_event`].
+#[tracing::instrument(skip_all, level = tracing::Level::DEBUG)]
+pub(crate) async fn get_next_prioritized_event<P: Provider>(
+    pool: &SqlitePool,
+    priorities: &EventPriorities,
+    cache: &SymbolCache,
+    provider: &P,
+) -> Result<Option<QueuedEvent>, EventQueueError> {
+    if priorities.is_empty() {
+        return get_next_unprocessed_event(pool).await;
+    }
+
+    rank_unranked_events(pool, priorities, cache, provider).await?;
+
+    loop {
+        let row = sqlx::query_as!(
+            QueueRow,
+            r#"
+            SELECT
+                id,
+                tx_hash,
+                log_index,
+                orderbook,
+                block_number,
+                event_data,
+                format_version,
+                processed,
+                created_at,
+                processed_at,
+                block_timestamp
+            FROM event_queue
+            WHERE processed = 0 AND claimed_at IS NULL AND dead_lettered_at IS NULL
+            ORDER BY priority DESC, block_number ASC, log_index ASC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let Some(event) = row.decode(pool).await? else {
+            continue;
+        };
+
+        return row.into_queued_event(event).map(Some);
+    }
+}
+
+/// Stores the symbol and priority of every pending event not ranked yet.
+async fn rank_unranked_events<P: Provider>(
+    pool: &SqlitePool,
+    priorities: &EventPriorities,
+    cache: &SymbolCache,
+    provider: &P,
+) -> Result<(), EventQueueError> {
+    let rows = sqlx::query_as!(
+        QueueRow,
+        r#"
+        SELECT
+            id,
+            tx_hash,
+            log_index,
+            orderbook,
+            block_number,
+            event_data,
+            format_version,
+            processed,
+            created_at,
+            processed_at,
+            block_timestamp
+        FROM event_queue
+        WHERE processed = 0 AND dead_lettered_at IS NULL AND priority IS NULL
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let Some(event) = row.decode(pool).await? else {
+            continue;
+        };
+
+        let (symbol, priority) = priorities.rank(&event, cache, provider).await;
+        let symbol = symbol.map(|symbol| symbol.to_string());
+        sqlx::query!(
+            "UPDATE event_queue SET symbol = ?1, priority = ?2 WHERE id = ?3",
+            symbol,
+            priority,
+            row.id
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Unprocessed events that have not been dead-lettered, claimed or not,
+/// ordered by block number then log index. Nothing is dead-lettered, so an
+/// event in an unknown format version fails the listing.
+pub(crate) async fn list_unprocessed_events(
+    pool: &SqlitePool,
+) -> Result<Vec<QueuedEvent>, EventQueueError> {
+    let rows = sqlx::query_as!(
+        QueueRow,
         r#"
         SELECT
             id,
             tx_hash,
             log_index,
+            orderbook,
             block_number,
             event_data,
+            format_version,
             processed,
             created_at,
             processed_at,
             block_timestamp
         FROM event_queue
-        WHERE processed = 0
+        WHERE processed = 0 AND dead_lettered_at IS NULL
         ORDER BY block_number ASC, log_index ASC
-        LIMIT 1
         "#
     )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let event = decode_trade_event(row.format_version, &row.event_data)?;
+            row.into_queued_event(event)
+        })
+        .collect()
+}
+
+/// The queued event with `event_id`, processed or not.
+pub(crate) async fn find_event(
+    pool: &SqlitePool,
+    event_id: i64,
+) -> Result<Option<QueuedEvent>, EventQueueError> {
+    let row = sqlx::query_as!(
+        QueueRow,
+        r#"
+        SELECT
+            id,
+            tx_hash,
+            log_index,
+            orderbook,
+            block_number,
+            event_data,
+            format_version,
+            processed,
+            created_at,
+            processed_at,
+            block_timestamp
+        FROM event_queue
+        WHERE id = ?
+        "#,
+        event_id
+    )
     .fetch_optional(pool)
     .await?;
 
-    let Some(row) = row else {
-        return Ok(None);
-    };
+    row.map(|row| {
+        let event = decode_trade_event(row.format_version, &row.event_data)?;
+        row.into_queued_event(event)
+    })
+    .transpose()
+}
 
-    let tx_hash = B256::from_str(&row.tx_hash)
-        .map_err(|e| EventQueueError::Processing(format!("Invalid tx_hash format: {e}")))?;
-
-    let event: TradeEvent = serde_json::from_str(&row.event_data)
-        .map_err(|e| EventQueueError::Processing(format!("Failed to deserialize event: {e}")))?;
-
-    Ok(Some(QueuedEvent {
-        id: Some(row.id),
-        tx_hash,
-        log_index: row
-            .log_index
-            .try_into()
-            .map_err(|_| EventQueueError::Processing("Log index conversion failed".to_string()))?,
-        block_number: row.block_number.try_into().map_err(|_| {
-            EventQueueError::Processing("Block number conversion failed".to_string())
-        })?,
-        event,
-        processed: row.processed,
-        created_at: Some(row.created_at.and_utc()),
-        processed_at: row.processed_at.map(|dt| dt.and_utc()),
-        block_timestamp: row.block_timestamp.map(|dt| dt.and_utc()),
-    }))
+/// Sets an undecodable event aside so the queue moves past it
+async fn dead_letter_event(
+    pool: &SqlitePool,
+    event_id: i64,
+    reason: &str,
+) -> Result<(), EventQueueError> {
+    sqlx::query!(
+        r#"
+        UPDATE event_queue
+        SET dead_lettered_at = CURRENT_TIMESTAMP, dead_letter_reason = ?
+        WHERE id = ?
+        "#,
+        reason,
+        event_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Claims an unprocessed event for processing, returning whether the claim
+/// was taken; an event already claimed is left to its claimant. The claim stays
+/// in place until the event is marked processed or the claim is released, so
+/// an event picked up by a process that then crashes is recovered by
+/// [`release_claims`] on the next startup.
+pub(crate) async fn claim_event(pool: &SqlitePool, event_id: i64) -> Result<bool, EventQueueError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE event_queue
+        SET claimed_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND processed = 0 AND claimed_at IS NULL
+        "#,
+        event_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Releases the claim on an unprocessed event so it is picked up again
+pub(crate) async fn release_claim(pool: &SqlitePool, event_id: i64) -> Result<(), EventQueueError> {
+    sqlx::query!(
+        "UPDATE event_queue SET claimed_at = NULL WHERE id = ? AND processed = 0",
+        event_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Releases the claims on all unprocessed events, returning how many events
+/// were put back on the queue. Only called on startup: events are claimed by a
+/// single queue processor, so any claim left then belongs to an interrupted run.
+pub(crate) async fn release_claims(pool: &SqlitePool) -> Result<u64, EventQueueError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE event_queue
+        SET claimed_at = NULL
+        WHERE processed = 0 AND claimed_at IS NOT NULL
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Assigns `orderbook` to queued events and trades recorded before the
+/// orderbook was part of their key, so a legacy event seen again from that
+/// orderbook is recognized as the same event. Returns how many queued events
+/// were adopted.
+pub(crate) async fn adopt_legacy_orderbook(
+    pool: &SqlitePool,
+    orderbook: Address,
+) -> Result<u64, EventQueueError> {
+    let orderbook_str = orderbook.to_string();
+    let mut sql_tx = pool.begin().await?;
+
+    let adopted = sqlx::query!(
+        "UPDATE event_queue SET orderbook = ? WHERE orderbook IS NULL",
+        orderbook_str
+    )
+    .execute(&mut *sql_tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query!(
+        "UPDATE onchain_trades SET orderbook = ? WHERE orderbook IS NULL",
+        orderbook_str
+    )
+    .execute(&mut *sql_tx)
+    .await?;
+
+    sql_tx.commit().await?;
+
+    Ok(adopted)
 }
 
 /// Marks an event as processed in the queue within a transaction
@@ -191,6 +664,18 @@ pub(crate) async fn enqueue<E: Enqueueable>(
     enqueue_event(pool, log, serializable_event).await
 }
 
+/// Enqueues `event` as part of `sql_tx`, so callers can commit it atomically
+/// with other bookkeeping such as the backfill checkpoint.
+#[allow(clippy::future_not_send)]
+pub(crate) async fn enqueue_within_transaction<E: Enqueueable>(
+    sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    event: &E,
+    log: &Log,
+) -> Result<(), EventQueueError> {
+    let serializable_event = event.to_trade_event();
+    enqueue_event_within_transaction(sql_tx, log, serializable_event).await
+}
+
 /// Enqueues buffered events that were collected during coordination phase
 #[tracing::instrument(skip(pool, event_buffer), fields(buffer_size = event_buffer.len()), level = tracing::Level::INFO)]
 pub(crate) async fn enqueue_buffer(
@@ -226,11 +711,13 @@ pub(crate) async fn enqueue_buffer(
         .await;
 }
 
-/// Gets count of unprocessed events in the queue - test utility function
+/// Gets count of unprocessed events in the queue, excluding dead letters
 pub(crate) async fn count_unprocessed(pool: &SqlitePool) -> Result<i64, EventQueueError> {
-    let row = sqlx::query!("SELECT COUNT(*) as count FROM event_queue WHERE processed = 0")
-        .fetch_one(pool)
-        .await?;
+    let row = sqlx::query!(
+        "SELECT COUNT(*) as count FROM event_queue WHERE processed = 0 AND dead_lettered_at IS NULL"
+    )
+    .fetch_one(pool)
+    .await?;
 
     Ok(row.count)
 }
@@ -258,11 +745,14 @@ pub(crate) async fn get_max_processed_block(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bindings::IERC20::symbolCall;
     use crate::bindings::IOrderBookV4::{
-        ClearConfig, ClearV2, OrderV3, TakeOrderConfigV3, TakeOrderV2,
+        ClearConfig, ClearV2, IO, OrderV3, TakeOrderConfigV3, TakeOrderV2,
     };
     use crate::test_utils::setup_test_db;
-    use alloy::primitives::{LogData, Uint, address, b256};
+    use alloy::primitives::{Address, LogData, U256, Uint, address, b256};
+    use alloy::providers::{ProviderBuilder, mock::Asserter};
+    use alloy::sol_types::SolCall;
 
     #[tokio::test]
     async fn test_enqueue_and_process_event() {
@@ -327,6 +817,155 @@ mod tests {
         assert!(next_event.is_none());
     }
 
+    #[tokio::test]
+    async fn test_claim_left_by_crashed_run_is_released_and_event_reprocessed() {
+        let pool = setup_test_db().await;
+
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: address!("1234567890123456789012345678901234567890"),
+                data: LogData::default(),
+            },
+            block_hash: None,
+            block_number: Some(100),
+            block_timestamp: None,
+            transaction_hash: Some(b256!(
+                "3333333333333333333333333333333333333333333333333333333333333333"
+            )),
+            transaction_index: Some(1),
+            log_index: Some(2),
+            removed: false,
+        };
+        let test_event = TradeEvent::ClearV2(Box::new(ClearV2 {
+            sender: log.inner.address,
+            alice: OrderV3::default(),
+            bob: OrderV3::default(),
+            clearConfig: ClearConfig::default(),
+        }));
+        enqueue_event(&pool, &log, test_event).await.unwrap();
+
+        let event_id = get_next_unprocessed_event(&pool)
+            .await
+            .unwrap()
+            .unwrap()
+            .id
+            .unwrap();
+        assert!(claim_event(&pool, event_id).await.unwrap());
+
+        // Claimed events are skipped but still count as unprocessed, and
+        // cannot be claimed again
+        assert!(get_next_unprocessed_event(&pool).await.unwrap().is_none());
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 1);
+        assert!(!claim_event(&pool, event_id).await.unwrap());
+
+        // The run that claimed the event crashed; the next startup releases
+        // its claim however recent it is
+        assert_eq!(release_claims(&pool).await.unwrap(), 1);
+
+        let reprocessed = get_next_unprocessed_event(&pool).await.unwrap().unwrap();
+        assert_eq!(reprocessed.id, Some(event_id));
+
+        assert!(claim_event(&pool, event_id).await.unwrap());
+        let mut sql_tx = pool.begin().await.unwrap();
+        mark_event_processed(&mut sql_tx, event_id).await.unwrap();
+        sql_tx.commit().await.unwrap();
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_old_format_rows_decode_and_unknown_versions_are_dead_lettered() {
+        let pool = setup_test_db().await;
+
+        let old_event = TradeEvent::ClearV2(Box::new(ClearV2 {
+            sender: address!("1111111111111111111111111111111111111111"),
+            alice: OrderV3::default(),
+            bob: OrderV3::default(),
+            clearConfig: ClearConfig::default(),
+        }));
+        let old_event_json = serde_json::to_string(&old_event).unwrap();
+
+        // Rows written before versioning carry no format_version, and one
+        // from an unknown future format sorts first so it would block the queue
+        sqlx::query!(
+            r#"
+            INSERT INTO event_queue (tx_hash, log_index, block_number, event_data, processed)
+            VALUES ('0x1111111111111111111111111111111111111111111111111111111111111111', 0, 100, ?, 0)
+            "#,
+            old_event_json
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            r#"
+            INSERT INTO event_queue
+            (tx_hash, log_index, block_number, event_data, format_version, processed)
+            VALUES ('0x9999999999999999999999999999999999999999999999999999999999999999', 0, 50, '{"v99": {}}', 99, 0)
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: address!("1234567890123456789012345678901234567890"),
+                data: LogData::default(),
+            },
+            block_hash: None,
+            block_number: Some(200),
+            block_timestamp: None,
+            transaction_hash: Some(b256!(
+                "2222222222222222222222222222222222222222222222222222222222222222"
+            )),
+            transaction_index: Some(1),
+            log_index: Some(0),
+            removed: false,
+        };
+        let current_event = TradeEvent::TakeOrderV2(Box::new(TakeOrderV2 {
+            sender: log.inner.address,
+            config: TakeOrderConfigV3::default(),
+            input: Uint::from(100),
+            output: Uint::from(50),
+        }));
+        enqueue_event(&pool, &log, current_event).await.unwrap();
+
+        let mut decoded = Vec::new();
+        while let Some(event) = get_next_unprocessed_event(&pool).await.unwrap() {
+            let mut sql_tx = pool.begin().await.unwrap();
+            mark_event_processed(&mut sql_tx, event.id.unwrap())
+                .await
+                .unwrap();
+            sql_tx.commit().await.unwrap();
+            decoded.push((event.block_number, event.event));
+        }
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].0, 100);
+        assert!(matches!(decoded[0].1, TradeEvent::ClearV2(_)));
+        assert_eq!(decoded[1].0, 200);
+        assert!(matches!(decoded[1].1, TradeEvent::TakeOrderV2(_)));
+
+        let dead_lettered = sqlx::query!(
+            r#"
+            SELECT block_number, processed, dead_letter_reason
+            FROM event_queue
+            WHERE dead_lettered_at IS NOT NULL
+            "#
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(dead_lettered.len(), 1);
+        assert_eq!(dead_lettered[0].block_number, 50);
+        assert!(!dead_lettered[0].processed);
+        assert_eq!(
+            dead_lettered[0].dead_letter_reason.as_deref(),
+            Some("Unknown queued event format version 99")
+        );
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 0);
+    }
+
     #[tokio::test]
     async fn test_duplicate_event_handling() {
         let pool = setup_test_db().await;
@@ -370,6 +1009,105 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[tokio::test]
+    async fn test_same_log_index_from_two_orderbooks_is_stored_twice() {
+        let pool = setup_test_db().await;
+
+        let first_orderbook = address!("1234567890123456789012345678901234567890");
+        let second_orderbook = address!("0987654321098765432109876543210987654321");
+
+        for orderbook in [first_orderbook, second_orderbook] {
+            let log = Log {
+                inner: alloy::primitives::Log {
+                    address: orderbook,
+                    data: LogData::default(),
+                },
+                block_hash: None,
+                block_number: Some(100),
+                block_timestamp: None,
+                transaction_hash: Some(b256!(
+                    "2222222222222222222222222222222222222222222222222222222222222222"
+                )),
+                transaction_index: Some(1),
+                log_index: Some(5),
+                removed: false,
+            };
+
+            let test_event = TradeEvent::ClearV2(Box::new(ClearV2 {
+                sender: orderbook,
+                alice: OrderV3::default(),
+                bob: OrderV3::default(),
+                clearConfig: ClearConfig::default(),
+            }));
+
+            enqueue_event(&pool, &log, test_event).await.unwrap();
+        }
+
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 2);
+
+        let first = get_next_unprocessed_event(&pool).await.unwrap().unwrap();
+        let mut sql_tx = pool.begin().await.unwrap();
+        mark_event_processed(&mut sql_tx, first.id.unwrap())
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+        let second = get_next_unprocessed_event(&pool).await.unwrap().unwrap();
+
+        assert_eq!(first.tx_hash, second.tx_hash);
+        assert_eq!(first.log_index, second.log_index);
+        let mut orderbooks = vec![first.orderbook.unwrap(), second.orderbook.unwrap()];
+        orderbooks.sort();
+        let mut expected = vec![first_orderbook, second_orderbook];
+        expected.sort();
+        assert_eq!(orderbooks, expected);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_events_adopt_configured_orderbook() {
+        let pool = setup_test_db().await;
+        let orderbook = address!("1234567890123456789012345678901234567890");
+
+        // Rows queued before the orderbook was recorded have none
+        sqlx::query!(
+            r#"
+            INSERT INTO event_queue (tx_hash, log_index, block_number, event_data, processed)
+            VALUES ('0x2222222222222222222222222222222222222222222222222222222222222222', 5, 100, '{}', 1)
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(adopt_legacy_orderbook(&pool, orderbook).await.unwrap(), 1);
+        assert_eq!(adopt_legacy_orderbook(&pool, orderbook).await.unwrap(), 0);
+
+        // The same event seen again from that orderbook is not queued twice
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: orderbook,
+                data: LogData::default(),
+            },
+            block_hash: None,
+            block_number: Some(100),
+            block_timestamp: None,
+            transaction_hash: Some(b256!(
+                "2222222222222222222222222222222222222222222222222222222222222222"
+            )),
+            transaction_index: Some(1),
+            log_index: Some(5),
+            removed: false,
+        };
+        let test_event = TradeEvent::ClearV2(Box::new(ClearV2 {
+            sender: orderbook,
+            alice: OrderV3::default(),
+            bob: OrderV3::default(),
+            clearConfig: ClearConfig::default(),
+        }));
+        enqueue_event(&pool, &log, test_event).await.unwrap();
+
+        assert_eq!(count_unprocessed(&pool).await.unwrap(), 0);
+    }
+
     #[tokio::test]
     async fn test_event_ordering() {
         let pool = setup_test_db().await;
@@ -416,6 +1154,104 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn test_parse_symbol_priority() {
+        let parsed: SymbolPriority = " aapl = 10 ".parse().unwrap();
+        assert_eq!(parsed.symbol, Symbol::new("AAPL").unwrap());
+        assert_eq!(parsed.priority, 10);
+
+        assert_eq!("MSFT=-1".parse::<SymbolPriority>().unwrap().priority, -1);
+        assert!(matches!(
+            "AAPL".parse::<SymbolPriority>(),
+            Err(SymbolPriorityParseError::MissingSeparator(_))
+        ));
+        assert!(matches!(
+            "=1".parse::<SymbolPriority>(),
+            Err(SymbolPriorityParseError::InvalidSymbol(_))
+        ));
+        assert!(matches!(
+            "AAPL=high".parse::<SymbolPriority>(),
+            Err(SymbolPriorityParseError::InvalidPriority(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_high_priority_symbol_is_processed_before_earlier_events() {
+        let pool = setup_test_db().await;
+        let usdc = address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let msft = address!("0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+        let aapl = address!("0xcccccccccccccccccccccccccccccccccccccccc");
+
+        // MSFT and AAPL trades alternate, starting with MSFT
+        for (i, equity) in [msft, aapl, msft, aapl].into_iter().enumerate() {
+            let i = u64::try_from(i).unwrap();
+            let io = |token: Address| IO {
+                token,
+                decimals: 18,
+                vaultId: U256::ZERO,
+            };
+            let log = Log {
+                block_number: Some(100 + i),
+                transaction_hash: Some(B256::from([u8::try_from(i).unwrap() + 1; 32])),
+                log_index: Some(0),
+                ..Log::default()
+            };
+
+            let event = TradeEvent::ClearV2(Box::new(ClearV2 {
+                sender: Address::ZERO,
+                alice: OrderV3 {
+                    validInputs: vec![io(usdc)],
+                    validOutputs: vec![io(equity)],
+                    ..OrderV3::default()
+                },
+                bob: OrderV3::default(),
+                clearConfig: ClearConfig::default(),
+            }));
+            enqueue_event(&pool, &log, event).await.unwrap();
+        }
+
+        // Symbols are resolved once, in queue order, and cached after that
+        let asserter = Asserter::new();
+        for symbol in ["USDC", "MSFT0x", "AAPL0x"] {
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &symbol.to_string(),
+            ));
+        }
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let cache = SymbolCache::default();
+        let priorities = EventPriorities::new(["AAPL=10".parse().unwrap()]);
+
+        let mut processed_blocks = vec![];
+        while let Some(event) = get_next_prioritized_event(&pool, &priorities, &cache, &provider)
+            .await
+            .unwrap()
+        {
+            processed_blocks.push(event.block_number);
+            let mut sql_tx = pool.begin().await.unwrap();
+            mark_event_processed(&mut sql_tx, event.id.unwrap())
+                .await
+                .unwrap();
+            sql_tx.commit().await.unwrap();
+        }
+
+        // AAPL events first, then MSFT, each in block order
+        assert_eq!(processed_blocks, vec![101, 103, 100, 102]);
+
+        // Each event was ranked once, when first seen
+        let ranks = sqlx::query!(
+            r#"SELECT symbol, priority AS "priority!" FROM event_queue ORDER BY block_number"#
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (row.symbol, row.priority))
+        .collect::<Vec<_>>();
+        let msft = (Some("MSFT".to_string()), 0);
+        let aapl = (Some("AAPL".to_string()), 10);
+        assert_eq!(ranks, vec![msft.clone(), aapl.clone(), msft, aapl]);
+    }
+
     #[tokio::test]
     async fn test_enqueue_buffer_mixed_events() {
         let pool = setup_test_db().await;
@@ -0,0 +1,302 @@
+//! Webhook alerts when cumulative realized P&L crosses a loss or profit
+//! threshold, for a single symbol or in total across symbols.
+//!
+//! Thresholds are checked after every reporter iteration against the same
+//! figures served by `GET /pnl/summary`. Each crossing is alerted once: it is
+//! recorded in `pnl_alerts` after the webhook accepts it, and cleared when the
+//! P&L moves back inside the threshold so that a later crossing alerts again.
+//! A failed webhook call leaves the crossing unrecorded to be retried on the
+//! next iteration.
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use tracing::{info, warn};
+use url::Url;
+
+use super::summary::{PnlSummaryError, load_pnl_summary};
+
+/// Scope under which crossings of the total P&L are recorded.
+const TOTAL_SCOPE: &str = "*";
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum PnlAlertError {
+    #[error("Failed to load P&L summary: {0}")]
+    Summary(#[from] PnlSummaryError),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Webhook request failed: {0}")]
+    Webhook(#[from] reqwest::Error),
+    #[error("Cannot store {0} as a float")]
+    NonFloat(Decimal),
+}
+
+/// Parses a P&L threshold in dollars for the `--pnl-*-alert-threshold` flags.
+pub(crate) fn parse_pnl_threshold(value: &str) -> Result<Decimal, String> {
+    let threshold = Decimal::from_str(value.trim())
+        .map_err(|e| format!("Invalid P&L threshold '{value}': {e}"))?;
+
+    if threshold > Decimal::ZERO {
+        Ok(threshold)
+    } else {
+        Err(format!("P&L threshold must be positive, got {value}"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum PnlAlertKind {
+    Loss,
+    Profit,
+}
+
+impl PnlAlertKind {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Loss => "LOSS",
+            Self::Profit => "PROFIT",
+        }
+    }
+}
+
+/// Body posted to the webhook for a threshold crossing. `symbol` is `None`
+/// for the total across symbols.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct PnlAlert {
+    symbol: Option<String>,
+    kind: PnlAlertKind,
+    threshold: Decimal,
+    cumulative_pnl: Decimal,
+}
+
+impl PnlAlert {
+    fn scope(&self) -> &str {
+        self.symbol.as_deref().unwrap_or(TOTAL_SCOPE)
+    }
+}
+
+/// Where to send alerts and which thresholds trigger them. Thresholds are
+/// positive amounts in dollars; the loss threshold fires at or below its
+/// negation.
+#[derive(Debug, Clone)]
+pub(crate) struct PnlAlerter {
+    client: reqwest::Client,
+    webhook_url: Url,
+    loss_threshold: Option<Decimal>,
+    profit_threshold: Option<Decimal>,
+}
+
+impl PnlAlerter {
+    pub(crate) fn new(
+        webhook_url: Url,
+        loss_threshold: Option<Decimal>,
+        profit_threshold: Option<Decimal>,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url,
+            loss_threshold,
+            profit_threshold,
+        }
+    }
+
+    fn crossings(&self, symbol: Option<&str>, cumulative_pnl: Decimal) -> Vec<(PnlAlert, bool)> {
+        let thresholds = [
+            (PnlAlertKind::Loss, self.loss_threshold),
+            (PnlAlertKind::Profit, self.profit_threshold),
+        ];
+
+        thresholds
+            .into_iter()
+            .filter_map(|(kind, threshold)| {
+                let threshold = threshold?;
+                let crossed = match kind {
+                    PnlAlertKind::Loss => cumulative_pnl <= -threshold,
+                    PnlAlertKind::Profit => cumulative_pnl >= threshold,
+                };
+
+                let alert = PnlAlert {
+                    symbol: symbol.map(ToString::to_string),
+                    kind,
+                    threshold,
+                    cumulative_pnl,
+                };
+
+                Some((alert, crossed))
+            })
+            .collect()
+    }
+
+    /// Sends an alert for every threshold newly crossed since the last check,
+    /// returning how many were sent.
+    pub(crate) async fn check(&self, pool: &SqlitePool) -> Result<usize, PnlAlertError> {
+        let summary = load_pnl_summary(pool, None).await?;
+
+        let crossings = summary
+            .symbols
+            .iter()
+            .flat_map(|symbol| self.crossings(Some(&symbol.symbol), symbol.cumulative_pnl))
+            .chain(self.crossings(None, summary.total.cumulative_pnl));
+
+        let mut sent = 0;
+        for (alert, crossed) in crossings {
+            if !crossed {
+                clear_alert(pool, &alert).await?;
+                continue;
+            }
+
+            if is_alerted(pool, &alert).await? {
+                continue;
+            }
+
+            self.send(&alert).await?;
+            record_alert(pool, &alert).await?;
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+
+    async fn send(&self, alert: &PnlAlert) -> Result<(), PnlAlertError> {
+        warn!(
+            scope = alert.scope(),
+            kind = alert.kind.as_str(),
+            threshold = %alert.threshold,
+            cumulative_pnl = %alert.cumulative_pnl,
+            "Cumulative P&L crossed alert threshold"
+        );
+
+        self.client
+            .post(self.webhook_url.clone())
+            .json(alert)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        info!("P&L alert delivered for {}", alert.scope());
+        Ok(())
+    }
+}
+
+async fn is_alerted(pool: &SqlitePool, alert: &PnlAlert) -> Result<bool, PnlAlertError> {
+    let scope = alert.scope();
+    let kind = alert.kind.as_str();
+
+    let row = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM pnl_alerts WHERE scope = ?1 AND kind = ?2",
+        scope,
+        kind
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.count > 0)
+}
+
+async fn record_alert(pool: &SqlitePool, alert: &PnlAlert) -> Result<(), PnlAlertError> {
+    let scope = alert.scope();
+    let kind = alert.kind.as_str();
+    let threshold = alert
+        .threshold
+        .to_f64()
+        .ok_or(PnlAlertError::NonFloat(alert.threshold))?;
+    let cumulative_pnl = alert
+        .cumulative_pnl
+        .to_f64()
+        .ok_or(PnlAlertError::NonFloat(alert.cumulative_pnl))?;
+
+    sqlx::query!(
+        "INSERT INTO pnl_alerts (scope, kind, threshold, cumulative_pnl) VALUES (?1, ?2, ?3, ?4)",
+        scope,
+        kind,
+        threshold,
+        cumulative_pnl
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn clear_alert(pool: &SqlitePool, alert: &PnlAlert) -> Result<(), PnlAlertError> {
+    let scope = alert.scope();
+    let kind = alert.kind.as_str();
+
+    let cleared = sqlx::query!(
+        "DELETE FROM pnl_alerts WHERE scope = ?1 AND kind = ?2",
+        scope,
+        kind
+    )
+    .execute(pool)
+    .await?;
+
+    if cleared.rows_affected() > 0 {
+        info!(
+            "Cumulative P&L for {scope} back within {} threshold",
+            alert.kind.as_str()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use rust_decimal_macros::dec;
+
+    async fn insert_metrics_row(pool: &SqlitePool, trade_id: i64, symbol: &str, pnl: f64) {
+        sqlx::query!(
+            "INSERT INTO metrics_pnl (
+                symbol, timestamp, trade_type, trade_id, trade_direction, quantity,
+                price_per_share, realized_pnl, cumulative_pnl, net_position_after
+            ) VALUES (?1, CURRENT_TIMESTAMP, 'ONCHAIN', ?2, 'SELL', 10.0, 100.0, ?3, ?3, 0.0)",
+            symbol,
+            trade_id,
+            pnl
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_pnl_threshold() {
+        assert_eq!(parse_pnl_threshold("1000").unwrap(), dec!(1000));
+        assert_eq!(parse_pnl_threshold(" 250.5 ").unwrap(), dec!(250.5));
+        assert!(parse_pnl_threshold("0").is_err());
+        assert!(parse_pnl_threshold("-100").is_err());
+        assert!(parse_pnl_threshold("abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_loss_threshold_crossing_alerts_once() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // AAPL crosses the loss threshold, the total across symbols does not
+        insert_metrics_row(&pool, 1, "AAPL", -1500.0).await;
+        insert_metrics_row(&pool, 2, "MSFT", 1000.0).await;
+
+        let server = MockServer::start();
+        let webhook = server.mock(|when, then| {
+            when.method(POST)
+                .path("/alerts")
+                .json_body_partial(r#"{"symbol": "AAPL", "kind": "LOSS", "threshold": "1000"}"#);
+            then.status(200);
+        });
+
+        let alerter = PnlAlerter::new(
+            server.url("/alerts").parse().unwrap(),
+            Some(dec!(1000)),
+            None,
+        );
+
+        assert_eq!(alerter.check(&pool).await.unwrap(), 1);
+        assert_eq!(alerter.check(&pool).await.unwrap(), 0);
+        webhook.assert_hits(1);
+    }
+}
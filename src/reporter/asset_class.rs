@@ -0,0 +1,203 @@
+//! Asset class (or sector) tags for reporting P&L by group of symbols.
+//!
+//! Classes are configured per base symbol as `SYMBOL=CLASS`; tokenized
+//! symbols such as `AAPL0x` share the class of their base symbol. Each
+//! `metrics_pnl` row stores the class of its symbol at insert time, so
+//! changing the mapping only affects rows written afterwards. Rows for
+//! untagged symbols have no class.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::onchain::io::SymbolConvention;
+
+/// Asset class of a base symbol, parsed from `SYMBOL=CLASS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SymbolAssetClass {
+    pub(crate) symbol: String,
+    pub(crate) asset_class: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SymbolAssetClassParseError {
+    #[error("Expected SYMBOL=CLASS but got '{0}'")]
+    MissingSeparator(String),
+    #[error("Empty symbol in '{0}'")]
+    EmptySymbol(String),
+    #[error("Empty asset class in '{0}'")]
+    EmptyAssetClass(String),
+}
+
+impl FromStr for SymbolAssetClass {
+    type Err = SymbolAssetClassParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (symbol, asset_class) = value
+            .split_once('=')
+            .ok_or_else(|| SymbolAssetClassParseError::MissingSeparator(value.to_string()))?;
+
+        let symbol = symbol.trim().to_uppercase();
+        if symbol.is_empty() {
+            return Err(SymbolAssetClassParseError::EmptySymbol(value.to_string()));
+        }
+
+        let asset_class = asset_class.trim().to_string();
+        if asset_class.is_empty() {
+            return Err(SymbolAssetClassParseError::EmptyAssetClass(
+                value.to_string(),
+            ));
+        }
+
+        Ok(Self {
+            symbol,
+            asset_class,
+        })
+    }
+}
+
+/// Configured asset classes by base symbol, with the symbol convention that
+/// tokenized symbols are parsed by to find their base.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct AssetClasses {
+    classes: HashMap<String, String>,
+    convention: SymbolConvention,
+}
+
+impl AssetClasses {
+    pub(crate) fn new(
+        classes: impl IntoIterator<Item = SymbolAssetClass>,
+        convention: SymbolConvention,
+    ) -> Self {
+        Self {
+            classes: classes
+                .into_iter()
+                .map(|entry| (entry.symbol, entry.asset_class))
+                .collect(),
+            convention,
+        }
+    }
+
+    /// Asset class of `symbol`, looked up by its base symbol when tokenized.
+    pub(crate) fn class_of(&self, symbol: &str) -> Option<&str> {
+        let base = self.convention.parse_tokenized(symbol).map_or_else(
+            |_| symbol.to_uppercase(),
+            |equity| equity.base().to_string(),
+        );
+
+        self.classes.get(&base).map(String::as_str)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AssetClassPnlError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Cannot represent realized_pnl={0} as a decimal")]
+    NonDecimal(f64),
+}
+
+/// Realized P&L rolled up over all symbols in one asset class. Rows without a
+/// class are grouped under `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct AssetClassPnl {
+    pub(crate) asset_class: Option<String>,
+    pub(crate) realized_pnl: Decimal,
+    pub(crate) trades: i64,
+}
+
+/// Sums realized P&L per asset class, ordered by class with untagged rows
+/// first. Open position marks are not trades and are left out.
+pub(crate) async fn load_pnl_by_asset_class(
+    pool: &SqlitePool,
+) -> Result<Vec<AssetClassPnl>, AssetClassPnlError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            asset_class,
+            COALESCE(SUM(realized_pnl), 0.0) AS "realized_pnl!: f64",
+            COUNT(*) AS "trades!: i64"
+        FROM metrics_pnl
+        WHERE trade_type != 'MARK'
+        GROUP BY asset_class
+        ORDER BY asset_class
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let realized_pnl = Decimal::from_f64(row.realized_pnl)
+                .map(|decimal| decimal.normalize())
+                .ok_or(AssetClassPnlError::NonDecimal(row.realized_pnl))?;
+
+            Ok(AssetClassPnl {
+                asset_class: row.asset_class,
+                realized_pnl,
+                trades: row.trades,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_symbol_asset_class() {
+        let parsed: SymbolAssetClass = " aapl = Technology ".parse().unwrap();
+        assert_eq!(
+            parsed,
+            SymbolAssetClass {
+                symbol: "AAPL".to_string(),
+                asset_class: "Technology".to_string(),
+            }
+        );
+
+        assert!(matches!(
+            "AAPL".parse::<SymbolAssetClass>(),
+            Err(SymbolAssetClassParseError::MissingSeparator(_))
+        ));
+        assert!(matches!(
+            "=Technology".parse::<SymbolAssetClass>(),
+            Err(SymbolAssetClassParseError::EmptySymbol(_))
+        ));
+        assert!(matches!(
+            "AAPL=".parse::<SymbolAssetClass>(),
+            Err(SymbolAssetClassParseError::EmptyAssetClass(_))
+        ));
+    }
+
+    #[test]
+    fn test_tokenized_symbols_share_base_class() {
+        let classes = AssetClasses::new(
+            ["AAPL=Technology".parse().unwrap()],
+            SymbolConvention::default(),
+        );
+
+        assert_eq!(classes.class_of("AAPL"), Some("Technology"));
+        assert_eq!(classes.class_of("AAPL0x"), Some("Technology"));
+        assert_eq!(classes.class_of("AAPLs1"), Some("Technology"));
+        assert_eq!(classes.class_of("XOM"), None);
+    }
+
+    #[test]
+    fn test_configured_suffix_finds_base_class() {
+        let classes = AssetClasses::new(
+            ["AAPL=Technology".parse().unwrap()],
+            SymbolConvention {
+                tokenized_suffix: "x1".to_string(),
+                ..SymbolConvention::default()
+            },
+        );
+
+        assert_eq!(classes.class_of("AAPLx1"), Some("Technology"));
+        assert_eq!(classes.class_of("AAPL0x"), Some("Technology"));
+    }
+}
@@ -0,0 +1,628 @@
+//! Export of `metrics_pnl` for offline analysis.
+//!
+//! Rows are streamed from SQLite straight into the output so large histories
+//! never have to fit in memory. Monetary columns (price, realized, cumulative
+//! and unrealized P&L) are written as decimal strings to avoid exposing binary
+//! float artifacts to analysts; share quantities stay as typed floats.
+//!
+//! Every format uses the same fields in the same order, which is also the
+//! CSV header: `symbol`, `timestamp`, `trade_type`, `trade_id`,
+//! `trade_direction`, `quantity`, `price_per_share`, `realized_pnl`,
+//! `cumulative_pnl`, `net_position_after`, `unrealized_pnl`.
+//!
+//! `MARK` rows value an open position at its mark price and are the only
+//! rows with an `unrealized_pnl`.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use futures_util::TryStreamExt;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    /// A JSON array with one object per row
+    Json,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Cannot represent {column}={value} as a decimal")]
+    NonDecimal { column: &'static str, value: f64 },
+    #[cfg(feature = "parquet")]
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+/// Restricts which rows are exported; both dates are inclusive and compared
+/// against the row's UTC timestamp.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PnlExportFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub symbol: Option<String>,
+}
+
+impl PnlExportFilter {
+    /// Lower bound of the timestamp range, as the start of `from`.
+    fn lower_bound(&self) -> Option<String> {
+        self.from.map(|date| {
+            date.and_time(NaiveTime::MIN)
+                .format(SQLITE_DATETIME)
+                .to_string()
+        })
+    }
+
+    /// Exclusive upper bound of the timestamp range, as the start of the day
+    /// after `to`.
+    fn upper_bound(&self) -> Option<String> {
+        self.to.and_then(|date| date.succ_opt()).map(|date| {
+            date.and_time(NaiveTime::MIN)
+                .format(SQLITE_DATETIME)
+                .to_string()
+        })
+    }
+}
+
+/// Format produced by SQLite's `datetime()`, so bounds compare as strings.
+const SQLITE_DATETIME: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct PnlExportRow {
+    symbol: String,
+    timestamp: DateTime<Utc>,
+    trade_type: String,
+    trade_id: i64,
+    trade_direction: String,
+    quantity: f64,
+    price_per_share: String,
+    realized_pnl: Option<String>,
+    cumulative_pnl: String,
+    net_position_after: f64,
+    unrealized_pnl: Option<String>,
+}
+
+fn to_decimal_string(column: &'static str, value: f64) -> Result<String, ExportError> {
+    Decimal::from_f64(value)
+        .map(|decimal| decimal.normalize().to_string())
+        .ok_or(ExportError::NonDecimal { column, value })
+}
+
+/// Column names shared by every export format, in output order.
+const COLUMNS: [&str; 11] = [
+    "symbol",
+    "timestamp",
+    "trade_type",
+    "trade_id",
+    "trade_direction",
+    "quantity",
+    "price_per_share",
+    "realized_pnl",
+    "cumulative_pnl",
+    "net_position_after",
+    "unrealized_pnl",
+];
+
+trait PnlSink {
+    fn write_row(&mut self, row: PnlExportRow) -> Result<(), ExportError>;
+    fn finish(self: Box<Self>) -> Result<(), ExportError>;
+}
+
+/// Streams the `metrics_pnl` rows matching `filter`, ordered by timestamp,
+/// to `writer` in the requested format. Returns the number of rows exported.
+pub async fn export_pnl<W: Write + Send + 'static>(
+    pool: &SqlitePool,
+    format: ExportFormat,
+    filter: &PnlExportFilter,
+    writer: W,
+) -> Result<usize, ExportError> {
+    let mut sink: Box<dyn PnlSink + Send> = match format {
+        ExportFormat::Csv => Box::new(CsvSink::new(writer)?),
+        ExportFormat::Json => Box::new(JsonSink::new(writer)?),
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet => Box::new(parquet_sink::ParquetSink::new(writer)?),
+    };
+
+    let from = filter.lower_bound();
+    let to = filter.upper_bound();
+    let symbol = filter.symbol.as_deref();
+
+    let mut rows = sqlx::query!(
+        "SELECT
+            symbol,
+            timestamp,
+            trade_type,
+            trade_id,
+            trade_direction,
+            quantity,
+            price_per_share,
+            realized_pnl,
+            cumulative_pnl,
+            net_position_after,
+            unrealized_pnl
+         FROM metrics_pnl
+         WHERE (?1 IS NULL OR datetime(timestamp) >= ?1)
+           AND (?2 IS NULL OR datetime(timestamp) < ?2)
+           AND (?3 IS NULL OR symbol = ?3)
+         ORDER BY timestamp, trade_type, trade_id",
+        from,
+        to,
+        symbol
+    )
+    .fetch(pool);
+
+    let mut exported = 0;
+    while let Some(row) = rows.try_next().await? {
+        sink.write_row(PnlExportRow {
+            symbol: row.symbol,
+            timestamp: row.timestamp.and_utc(),
+            trade_type: row.trade_type,
+            trade_id: row.trade_id,
+            trade_direction: row.trade_direction,
+            quantity: row.quantity,
+            price_per_share: to_decimal_string("price_per_share", row.price_per_share)?,
+            realized_pnl: row
+                .realized_pnl
+                .map(|pnl| to_decimal_string("realized_pnl", pnl))
+                .transpose()?,
+            cumulative_pnl: to_decimal_string("cumulative_pnl", row.cumulative_pnl)?,
+            net_position_after: row.net_position_after,
+            unrealized_pnl: row
+                .unrealized_pnl
+                .map(|pnl| to_decimal_string("unrealized_pnl", pnl))
+                .transpose()?,
+        })?;
+        exported += 1;
+    }
+
+    sink.finish()?;
+    Ok(exported)
+}
+
+struct CsvSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CsvSink<W> {
+    fn new(mut writer: W) -> Result<Self, ExportError> {
+        writeln!(writer, "{}", COLUMNS.join(","))?;
+        Ok(Self { writer })
+    }
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl<W: Write> PnlSink for CsvSink<W> {
+    fn write_row(&mut self, row: PnlExportRow) -> Result<(), ExportError> {
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&row.symbol),
+            row.timestamp.to_rfc3339(),
+            csv_field(&row.trade_type),
+            row.trade_id,
+            csv_field(&row.trade_direction),
+            row.quantity,
+            row.price_per_share,
+            row.realized_pnl.unwrap_or_default(),
+            row.cumulative_pnl,
+            row.net_position_after,
+            row.unrealized_pnl.unwrap_or_default()
+        )?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), ExportError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes a JSON array incrementally, one object per line, so rows are never
+/// collected in memory.
+struct JsonSink<W: Write> {
+    writer: W,
+    first: bool,
+}
+
+impl<W: Write> JsonSink<W> {
+    fn new(mut writer: W) -> Result<Self, ExportError> {
+        write!(writer, "[")?;
+        Ok(Self {
+            writer,
+            first: true,
+        })
+    }
+}
+
+impl<W: Write> PnlSink for JsonSink<W> {
+    fn write_row(&mut self, row: PnlExportRow) -> Result<(), ExportError> {
+        let separator = if self.first { "\n" } else { ",\n" };
+        self.first = false;
+
+        write!(self.writer, "{separator}")?;
+        serde_json::to_writer(&mut self.writer, &row)?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), ExportError> {
+        writeln!(self.writer, "\n]")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_sink {
+    use arrow_array::{
+        ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray,
+    };
+    use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use super::{COLUMNS, ExportError, PnlExportRow, PnlSink};
+
+    /// Rows buffered per Parquet row group write.
+    const BATCH_SIZE: usize = 4_096;
+
+    pub(super) fn schema() -> SchemaRef {
+        let types = [
+            DataType::Utf8,
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            DataType::Utf8,
+            DataType::Int64,
+            DataType::Utf8,
+            DataType::Float64,
+            DataType::Utf8,
+            DataType::Utf8,
+            DataType::Utf8,
+            DataType::Float64,
+            DataType::Utf8,
+        ];
+
+        Arc::new(Schema::new(
+            COLUMNS
+                .iter()
+                .zip(types)
+                .map(|(name, data_type)| {
+                    let nullable = matches!(*name, "realized_pnl" | "unrealized_pnl");
+                    Field::new(*name, data_type, nullable)
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    pub(super) struct ParquetSink<W: Write + Send> {
+        writer: ArrowWriter<W>,
+        schema: SchemaRef,
+        buffered: Vec<PnlExportRow>,
+    }
+
+    impl<W: Write + Send> ParquetSink<W> {
+        pub(super) fn new(writer: W) -> Result<Self, ExportError> {
+            let schema = schema();
+            Ok(Self {
+                writer: ArrowWriter::try_new(writer, schema.clone(), None)?,
+                schema,
+                buffered: Vec::with_capacity(BATCH_SIZE),
+            })
+        }
+
+        fn flush_batch(&mut self) -> Result<(), ExportError> {
+            if self.buffered.is_empty() {
+                return Ok(());
+            }
+
+            let rows = std::mem::take(&mut self.buffered);
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|row| row.symbol.as_str()),
+                )),
+                Arc::new(
+                    TimestampMicrosecondArray::from_iter_values(
+                        rows.iter().map(|row| row.timestamp.timestamp_micros()),
+                    )
+                    .with_timezone("UTC"),
+                ),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|row| row.trade_type.as_str()),
+                )),
+                Arc::new(Int64Array::from_iter_values(
+                    rows.iter().map(|row| row.trade_id),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|row| row.trade_direction.as_str()),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|row| row.quantity),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|row| row.price_per_share.as_str()),
+                )),
+                Arc::new(StringArray::from(
+                    rows.iter()
+                        .map(|row| row.realized_pnl.as_deref())
+                        .collect::<Vec<_>>(),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|row| row.cumulative_pnl.as_str()),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|row| row.net_position_after),
+                )),
+                Arc::new(StringArray::from(
+                    rows.iter()
+                        .map(|row| row.unrealized_pnl.as_deref())
+                        .collect::<Vec<_>>(),
+                )),
+            ];
+
+            let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+            self.writer.write(&batch)?;
+            Ok(())
+        }
+    }
+
+    impl<W: Write + Send> PnlSink for ParquetSink<W> {
+        fn write_row(&mut self, row: PnlExportRow) -> Result<(), ExportError> {
+            self.buffered.push(row);
+            if self.buffered.len() >= BATCH_SIZE {
+                self.flush_batch()?;
+            }
+            Ok(())
+        }
+
+        fn finish(mut self: Box<Self>) -> Result<(), ExportError> {
+            self.flush_batch()?;
+            self.writer.close()?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+    use std::sync::{Arc, Mutex};
+
+    /// Cloneable in-memory writer so the test can inspect what the sink wrote.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    async fn insert_metrics(pool: &SqlitePool) {
+        sqlx::query(
+            "INSERT INTO metrics_pnl (
+                symbol, timestamp, trade_type, trade_id, trade_direction, quantity,
+                price_per_share, realized_pnl, cumulative_pnl, net_position_after
+            ) VALUES
+                ('AAPL', '2025-01-06 15:30:00', 'ONCHAIN', 1, 'BUY', 10.0, 150.25, NULL, 0.0, 10.0),
+                ('AAPL', '2025-01-06 15:31:00', 'OFFCHAIN', 1, 'SELL', 10.0, 151.1, 8.5, 8.5, 0.0)",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_pnl_csv() {
+        let pool = setup_test_db().await;
+        insert_metrics(&pool).await;
+
+        let buffer = SharedBuffer::default();
+        let exported = export_pnl(
+            &pool,
+            ExportFormat::Csv,
+            &PnlExportFilter::default(),
+            buffer.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(exported, 2);
+        let csv = String::from_utf8(buffer.contents()).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "symbol,timestamp,trade_type,trade_id,trade_direction,quantity,price_per_share,realized_pnl,cumulative_pnl,net_position_after,unrealized_pnl",
+                "AAPL,2025-01-06T15:30:00+00:00,ONCHAIN,1,BUY,10,150.25,,0,10,",
+                "AAPL,2025-01-06T15:31:00+00:00,OFFCHAIN,1,SELL,10,151.1,8.5,8.5,0,",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_pnl_json_with_filters() {
+        let pool = setup_test_db().await;
+        insert_metrics(&pool).await;
+        sqlx::query(
+            "INSERT INTO metrics_pnl (
+                symbol, timestamp, trade_type, trade_id, trade_direction, quantity,
+                price_per_share, realized_pnl, cumulative_pnl, net_position_after
+            ) VALUES
+                ('MSFT', '2025-01-06 16:00:00', 'ONCHAIN', 2, 'BUY', 5.0, 400.0, NULL, 0.0, 5.0),
+                ('AAPL', '2025-01-07 09:30:00', 'ONCHAIN', 3, 'BUY', 1.0, 152.0, NULL, 8.5, 1.0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let filter = PnlExportFilter {
+            from: NaiveDate::from_ymd_opt(2025, 1, 6),
+            to: NaiveDate::from_ymd_opt(2025, 1, 6),
+            symbol: Some("AAPL".to_string()),
+        };
+        let buffer = SharedBuffer::default();
+        let exported = export_pnl(&pool, ExportFormat::Json, &filter, buffer.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(exported, 2);
+        let json: serde_json::Value = serde_json::from_slice(&buffer.contents()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "symbol": "AAPL",
+                    "timestamp": "2025-01-06T15:30:00Z",
+                    "trade_type": "ONCHAIN",
+                    "trade_id": 1,
+                    "trade_direction": "BUY",
+                    "quantity": 10.0,
+                    "price_per_share": "150.25",
+                    "realized_pnl": null,
+                    "cumulative_pnl": "0",
+                    "net_position_after": 10.0,
+                    "unrealized_pnl": null
+                },
+                {
+                    "symbol": "AAPL",
+                    "timestamp": "2025-01-06T15:31:00Z",
+                    "trade_type": "OFFCHAIN",
+                    "trade_id": 1,
+                    "trade_direction": "SELL",
+                    "quantity": 10.0,
+                    "price_per_share": "151.1",
+                    "realized_pnl": "8.5",
+                    "cumulative_pnl": "8.5",
+                    "net_position_after": 0.0,
+                    "unrealized_pnl": null
+                }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_pnl_json_empty() {
+        let pool = setup_test_db().await;
+
+        let buffer = SharedBuffer::default();
+        let exported = export_pnl(
+            &pool,
+            ExportFormat::Json,
+            &PnlExportFilter::default(),
+            buffer.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(exported, 0);
+        let json: serde_json::Value = serde_json::from_slice(&buffer.contents()).unwrap();
+        assert_eq!(json, serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_csv_field_quotes_special_characters() {
+        assert_eq!(csv_field("AAPL"), "AAPL");
+        assert_eq!(csv_field("A,B"), "\"A,B\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[tokio::test]
+    async fn test_export_pnl_parquet_round_trip() {
+        use arrow_array::{Array, Float64Array, StringArray, TimestampMicrosecondArray};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let pool = setup_test_db().await;
+        insert_metrics(&pool).await;
+
+        let path = std::env::temp_dir().join(format!(
+            "pnl-export-{}-{}.parquet",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        let file = std::fs::File::create(&path).unwrap();
+
+        let exported = export_pnl(
+            &pool,
+            ExportFormat::Parquet,
+            &PnlExportFilter::default(),
+            file,
+        )
+        .await
+        .unwrap();
+        assert_eq!(exported, 2);
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.schema(), parquet_sink::schema());
+        assert_eq!(batch.num_rows(), 2);
+
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+
+        let timestamps = column("timestamp");
+        let timestamps = timestamps
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(
+            timestamps.value(1),
+            DateTime::parse_from_rfc3339("2025-01-06T15:31:00Z")
+                .unwrap()
+                .timestamp_micros()
+        );
+
+        let prices = column("price_per_share");
+        let prices = prices.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(prices.value(0), "150.25");
+        assert_eq!(prices.value(1), "151.1");
+
+        let realized = column("realized_pnl");
+        let realized = realized.as_any().downcast_ref::<StringArray>().unwrap();
+        assert!(realized.is_null(0));
+        assert_eq!(realized.value(1), "8.5");
+
+        let quantities = column("quantity");
+        let quantities = quantities.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert!((quantities.value(0) - 10.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,116 @@
+//! Mark prices for valuing open positions.
+//!
+//! After processing new trades the reporter values every open FIFO inventory
+//! at a current mark price and keeps one synthetic `MARK` row per position
+//! in `metrics_pnl`, so dashboards can show live exposure and unrealized
+//! P&L. MARK rows never feed back into the FIFO state or the checkpoint.
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use sqlx::SqlitePool;
+
+use crate::symbol::Symbol;
+
+/// `trade_type` of the synthetic rows valuing open positions.
+pub(super) const MARK_TRADE_TYPE: &str = "MARK";
+
+/// Source of the current price per share used to value open positions.
+#[async_trait]
+pub(crate) trait MarkPriceSource: Send + Sync {
+    /// Current price per share of `symbol`, or `None` when no price is known.
+    async fn mark_price(&self, symbol: &Symbol) -> anyhow::Result<Option<Decimal>>;
+}
+
+/// Marks positions at the Pyth price recorded with the latest onchain trade
+/// of the symbol. Broker symbols (e.g. `AAPL`) also match the tokenized
+/// symbols they are traded as onchain (`AAPL0x`, `AAPLs1`).
+pub(crate) struct StoredPythPrice {
+    pool: SqlitePool,
+}
+
+impl StoredPythPrice {
+    pub(crate) const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MarkPriceSource for StoredPythPrice {
+    async fn mark_price(&self, symbol: &Symbol) -> anyhow::Result<Option<Decimal>> {
+        let symbol = symbol.as_str();
+        let pyth_price = sqlx::query_scalar!(
+            r#"
+            SELECT pyth_price AS "pyth_price!: f64"
+            FROM onchain_trades
+            WHERE pyth_price IS NOT NULL
+              AND symbol IN (?1, ?1 || '0x', ?1 || 's1')
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+            "#,
+            symbol
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        pyth_price
+            .map(|price| {
+                Decimal::from_f64(price).ok_or_else(|| {
+                    anyhow::anyhow!("Cannot represent Pyth price {price} as decimal")
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+    use rust_decimal_macros::dec;
+
+    async fn insert_priced_trade(pool: &SqlitePool, log_index: i64, symbol: &str, pyth: f64) {
+        sqlx::query(
+            "INSERT INTO onchain_trades
+                (tx_hash, log_index, symbol, amount, direction, price_usdc, pyth_price)
+             VALUES
+                ('0x1111111111111111111111111111111111111111111111111111111111111111',
+                 ?1, ?2, '1', 'BUY', '100', ?3)",
+        )
+        .bind(log_index)
+        .bind(symbol)
+        .bind(pyth)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stored_pyth_price_uses_latest_trade() {
+        let pool = setup_test_db().await;
+        let source = StoredPythPrice::new(pool.clone());
+        let aapl = Symbol::try_from("AAPL").unwrap();
+
+        assert_eq!(source.mark_price(&aapl).await.unwrap(), None);
+
+        insert_priced_trade(&pool, 1, "AAPL0x", 150.25).await;
+        insert_priced_trade(&pool, 2, "AAPL0x", 151.5).await;
+        insert_priced_trade(&pool, 3, "MSFT0x", 400.0).await;
+
+        assert_eq!(source.mark_price(&aapl).await.unwrap(), Some(dec!(151.5)));
+        assert_eq!(
+            source
+                .mark_price(&Symbol::try_from("AAPL0x").unwrap())
+                .await
+                .unwrap(),
+            Some(dec!(151.5))
+        );
+        assert_eq!(
+            source
+                .mark_price(&Symbol::try_from("AA").unwrap())
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -1,27 +1,100 @@
-use chrono::{DateTime, Utc};
-use clap::Parser;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
 use pnl::{FifoInventory, PnlError, PnlResult, TradeType};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::time::Duration;
-use tracing::{error, info};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+use url::Url;
 
+use crate::env::ReadPool;
+use crate::onchain::io::SymbolConvention;
+use crate::onchain::trade::parse_stored_decimal;
 use crate::symbol::Symbol;
+use alert::{PnlAlerter, parse_pnl_threshold};
+use asset_class::{AssetClasses, SymbolAssetClass};
+use export::{ExportFormat, PnlExportFilter, export_pnl};
+use mark::{MARK_TRADE_TYPE, MarkPriceSource, StoredPythPrice};
+use slippage::load_execution_slippage;
 use st0x_broker::Direction;
 
+mod alert;
+pub(crate) mod asset_class;
+pub mod export;
+pub(crate) mod mark;
 mod pnl;
+pub(crate) mod slippage;
+pub(crate) mod summary;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct ReporterEnv {
     #[clap(long, env, default_value = "sqlite:./data/schwab.db")]
     database_url: String,
+    /// Read-only database that trades are loaded from for P&L processing,
+    /// instead of `database_url`
+    #[clap(long, env)]
+    read_database_url: Option<String>,
     #[clap(long, env, default_value = "30")]
     reporter_processing_interval_secs: u64,
     #[clap(long, env, default_value = "info")]
     log_level: crate::env::LogLevel,
+    #[clap(long, env, value_enum, default_value = "pretty")]
+    log_format: crate::env::LogFormat,
+    /// Webhook notified when cumulative realized P&L crosses an alert
+    /// threshold, per symbol or in total
+    #[clap(long, env)]
+    pnl_alert_webhook_url: Option<Url>,
+    /// Loss in dollars at which cumulative realized P&L triggers an alert
+    #[clap(long, env, value_parser = parse_pnl_threshold)]
+    pnl_loss_alert_threshold: Option<Decimal>,
+    /// Profit in dollars at which cumulative realized P&L triggers an alert
+    #[clap(long, env, value_parser = parse_pnl_threshold)]
+    pnl_profit_alert_threshold: Option<Decimal>,
+    /// Comma-separated asset classes (or sectors) by base symbol, as
+    /// `SYMBOL=CLASS`, recorded with each P&L row for per-class reporting
+    #[clap(long, env, value_delimiter = ',')]
+    asset_class: Vec<SymbolAssetClass>,
+    /// Match onchain trades against the fills the paper-trading broker
+    /// recorded in `paper_orders` instead of the broker fills in
+    /// `offchain_trades`
+    #[clap(long, env)]
+    paper_orders: bool,
+    #[clap(flatten)]
+    symbol_convention: SymbolConvention,
+    #[command(subcommand)]
+    command: Option<ReporterCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ReporterCommand {
+    /// Write `metrics_pnl` rows to stdout and exit instead of running the
+    /// reporter. Fields, in order: symbol, timestamp, trade_type, trade_id,
+    /// trade_direction, quantity, price_per_share, realized_pnl,
+    /// cumulative_pnl, net_position_after, unrealized_pnl
+    Export {
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+        /// First day to export (YYYY-MM-DD, UTC, inclusive)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to export (YYYY-MM-DD, UTC, inclusive)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Only export rows for this symbol
+        #[arg(long)]
+        symbol: Option<String>,
+    },
+    /// Write to stdout, as JSON, the slippage of a filled execution split
+    /// across the onchain trades it hedged, and exit
+    Slippage {
+        /// Id of the execution in `offchain_trades`
+        #[arg(long)]
+        execution_id: i64,
+    },
 }
 
 impl crate::env::HasSqlite for ReporterEnv {
@@ -35,9 +108,60 @@ impl ReporterEnv {
         &self.log_level
     }
 
+    pub const fn log_format(&self) -> crate::env::LogFormat {
+        self.log_format
+    }
+
+    /// Whether the reporter writes an export or report to stdout, where log
+    /// lines would corrupt the output.
+    pub fn is_export(&self) -> bool {
+        matches!(
+            self.command,
+            Some(ReporterCommand::Export { .. } | ReporterCommand::Slippage { .. })
+        )
+    }
+
     fn processing_interval(&self) -> Duration {
         Duration::from_secs(self.reporter_processing_interval_secs)
     }
+
+    fn asset_classes(&self) -> AssetClasses {
+        AssetClasses::new(
+            self.asset_class.iter().cloned(),
+            self.symbol_convention.clone(),
+        )
+    }
+
+    const fn hedge_source(&self) -> HedgeSource {
+        if self.paper_orders {
+            HedgeSource::PaperOrders
+        } else {
+            HedgeSource::BrokerFills
+        }
+    }
+
+    fn pnl_alerter(&self) -> Option<PnlAlerter> {
+        let webhook_url = self.pnl_alert_webhook_url.clone()?;
+
+        if self.pnl_loss_alert_threshold.is_none() && self.pnl_profit_alert_threshold.is_none() {
+            return None;
+        }
+
+        Some(PnlAlerter::new(
+            webhook_url,
+            self.pnl_loss_alert_threshold,
+            self.pnl_profit_alert_threshold,
+        ))
+    }
+}
+
+/// Where the offchain hedges matched against onchain trades are loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HedgeSource {
+    /// FILLED executions in `offchain_trades`
+    BrokerFills,
+    /// FILLED orders the paper-trading broker recorded in `paper_orders`
+    PaperOrders,
 }
 
 #[derive(Debug, Clone)]
@@ -49,9 +173,18 @@ struct Trade {
     price_per_share: Decimal,
     direction: Direction,
     timestamp: DateTime<Utc>,
+    strategy_label: Option<String>,
 }
 
+/// Trades of each strategy are matched against each other only, so that
+/// every strategy's P&L stands on its own.
+type InventoryKey = (Option<String>, Symbol);
+
 impl Trade {
+    fn inventory_key(&self) -> InventoryKey {
+        (self.strategy_label.clone(), self.symbol.clone())
+    }
+
     fn checkpoint_key(&self) -> Checkpoint {
         Checkpoint {
             timestamp: self.timestamp,
@@ -63,17 +196,17 @@ impl Trade {
     fn from_onchain_row(
         id: i64,
         symbol: String,
-        amount: f64,
+        amount: &str,
         direction: &str,
-        price_usdc: f64,
+        price_usdc: &str,
         created_at: Option<chrono::NaiveDateTime>,
+        strategy_label: Option<String>,
     ) -> anyhow::Result<Self> {
-        let quantity = Decimal::from_f64_retain(amount)
-            .ok_or_else(|| anyhow::anyhow!("Failed to convert amount f64 to Decimal: {amount}"))?;
+        let quantity = parse_stored_decimal(amount)
+            .map_err(|e| anyhow::anyhow!("Invalid amount '{amount}': {e}"))?;
 
-        let price_per_share = Decimal::from_f64_retain(price_usdc).ok_or_else(|| {
-            anyhow::anyhow!("Failed to convert price_usdc f64 to Decimal: {price_usdc}")
-        })?;
+        let price_per_share = parse_stored_decimal(price_usdc)
+            .map_err(|e| anyhow::anyhow!("Invalid price_usdc '{price_usdc}': {e}"))?;
 
         let direction = direction
             .parse()
@@ -91,16 +224,18 @@ impl Trade {
             price_per_share,
             direction,
             timestamp,
+            strategy_label,
         })
     }
 
     fn from_offchain_row(
         id: i64,
         symbol: String,
-        shares: i64,
+        quantity: Decimal,
         direction: &str,
         price_cents: Option<i64>,
         executed_at: Option<chrono::NaiveDateTime>,
+        strategy_label: Option<String>,
     ) -> anyhow::Result<Self> {
         let executed_at =
             executed_at.ok_or_else(|| anyhow::anyhow!("FILLED execution missing executed_at"))?;
@@ -108,8 +243,6 @@ impl Trade {
         let price_cents =
             price_cents.ok_or_else(|| anyhow::anyhow!("FILLED execution missing price_cents"))?;
 
-        let quantity = Decimal::from(shares);
-
         let price_per_share = Decimal::from(price_cents)
             .checked_div(Decimal::from(100))
             .ok_or_else(|| anyhow::anyhow!("Division by 100 failed"))?;
@@ -126,10 +259,15 @@ impl Trade {
             price_per_share,
             direction,
             timestamp: executed_at.and_utc(),
+            strategy_label,
         })
     }
 
-    fn to_db_values(&self, result: &PnlResult) -> anyhow::Result<DbMetricsRow> {
+    fn to_db_values(
+        &self,
+        result: &PnlResult,
+        asset_classes: &AssetClasses,
+    ) -> anyhow::Result<DbMetricsRow> {
         let trade_type_str = match self.r#type {
             TradeType::Onchain => "ONCHAIN",
             TradeType::Offchain => "OFFCHAIN",
@@ -176,6 +314,11 @@ impl Trade {
             realized_pnl: realized_pnl_f64,
             cumulative_pnl: cumulative_pnl_f64,
             net_position_after: net_position_after_f64,
+            unrealized_pnl: None,
+            asset_class: asset_classes
+                .class_of(self.symbol.as_str())
+                .map(str::to_string),
+            strategy_label: self.strategy_label.clone(),
         })
     }
 }
@@ -191,6 +334,10 @@ struct DbMetricsRow {
     realized_pnl: Option<f64>,
     cumulative_pnl: f64,
     net_position_after: f64,
+    /// Only set on MARK rows
+    unrealized_pnl: Option<f64>,
+    asset_class: Option<String>,
+    strategy_label: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -204,6 +351,7 @@ async fn load_checkpoint(pool: &SqlitePool) -> anyhow::Result<Option<Checkpoint>
     let result = sqlx::query!(
         "SELECT timestamp, trade_type, trade_id
          FROM metrics_pnl
+         WHERE trade_type != 'MARK'
          ORDER BY timestamp DESC, trade_type DESC, trade_id DESC
          LIMIT 1"
     )
@@ -226,7 +374,7 @@ async fn load_checkpoint(pool: &SqlitePool) -> anyhow::Result<Option<Checkpoint>
         .transpose()
 }
 
-async fn load_all_trades(pool: &SqlitePool) -> anyhow::Result<Vec<Trade>> {
+async fn load_all_trades(pool: &SqlitePool, hedges: HedgeSource) -> anyhow::Result<Vec<Trade>> {
     let onchain = sqlx::query!(
         "SELECT
             id,
@@ -234,21 +382,52 @@ async fn load_all_trades(pool: &SqlitePool) -> anyhow::Result<Vec<Trade>> {
             amount,
             direction,
             price_usdc,
-            created_at
+            created_at,
+            strategy_label
          FROM onchain_trades
          ORDER BY created_at, id"
     )
     .fetch_all(pool)
     .await?;
 
+    let onchain_trades = onchain
+        .into_iter()
+        .map(|row| {
+            Trade::from_onchain_row(
+                row.id,
+                row.symbol,
+                &row.amount,
+                &row.direction,
+                &row.price_usdc,
+                row.created_at,
+                row.strategy_label,
+            )
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let offchain_trades = match hedges {
+        HedgeSource::BrokerFills => load_broker_fills(pool).await?,
+        HedgeSource::PaperOrders => load_paper_fills(pool).await?,
+    };
+
+    let mut trades = onchain_trades;
+    trades.extend(offchain_trades);
+
+    trades.sort_by_key(|t| (t.timestamp, t.r#type as u8, t.id));
+    Ok(trades)
+}
+
+async fn load_broker_fills(pool: &SqlitePool) -> anyhow::Result<Vec<Trade>> {
     let offchain = sqlx::query!(
         "SELECT
             id,
             symbol,
             shares,
+            fractional_shares,
             direction,
             price_cents,
-            executed_at
+            executed_at,
+            strategy_label
          FROM offchain_trades
          WHERE status = 'FILLED'
          ORDER BY executed_at, id"
@@ -256,55 +435,81 @@ async fn load_all_trades(pool: &SqlitePool) -> anyhow::Result<Vec<Trade>> {
     .fetch_all(pool)
     .await?;
 
-    let onchain_trades = onchain
-        .into_iter()
-        .map(|row| {
-            Trade::from_onchain_row(
-                row.id,
-                row.symbol,
-                row.amount,
-                &row.direction,
-                row.price_usdc,
-                row.created_at,
-            )
-        })
-        .collect::<anyhow::Result<Vec<_>>>()?;
-
-    let offchain_trades = offchain
+    offchain
         .into_iter()
         .map(|row| {
             let id = row
                 .id
                 .ok_or_else(|| anyhow::anyhow!("offchain trade missing id"))?;
 
+            // A fractional-share order filled its exact quantity, not the
+            // whole shares it rounds up to
+            let quantity = match row.fractional_shares {
+                Some(fractional_shares) => parse_stored_decimal(&fractional_shares)?,
+                None => Decimal::from(row.shares),
+            };
+
             Trade::from_offchain_row(
                 id,
                 row.symbol,
-                row.shares,
+                quantity,
                 &row.direction,
                 row.price_cents,
                 row.executed_at,
+                row.strategy_label,
             )
         })
-        .collect::<anyhow::Result<Vec<_>>>()?;
+        .collect()
+}
 
-    let mut trades = onchain_trades;
-    trades.extend(offchain_trades);
+/// Filled paper orders, labelled with the strategy of the execution that
+/// recorded them.
+async fn load_paper_fills(pool: &SqlitePool) -> anyhow::Result<Vec<Trade>> {
+    let paper = sqlx::query!(
+        r#"SELECT
+            paper_orders.id AS "id!",
+            paper_orders.symbol,
+            paper_orders.shares,
+            paper_orders.direction,
+            paper_orders.price_cents,
+            paper_orders.executed_at,
+            offchain_trades.strategy_label
+         FROM paper_orders
+         LEFT JOIN offchain_trades
+           ON offchain_trades.broker = 'paper'
+          AND offchain_trades.order_id = 'PAPER_' || paper_orders.id
+         WHERE paper_orders.status = 'FILLED'
+         ORDER BY paper_orders.executed_at, paper_orders.id"#
+    )
+    .fetch_all(pool)
+    .await?;
 
-    trades.sort_by_key(|t| (t.timestamp, t.r#type as u8, t.id));
-    Ok(trades)
+    paper
+        .into_iter()
+        .map(|row| {
+            Trade::from_offchain_row(
+                row.id,
+                row.symbol,
+                Decimal::from(row.shares),
+                &row.direction,
+                row.price_cents,
+                row.executed_at,
+                row.strategy_label,
+            )
+        })
+        .collect()
 }
 
 fn rebuild_fifo_state(
     trades: &[Trade],
     checkpoint: Option<Checkpoint>,
-) -> anyhow::Result<HashMap<Symbol, FifoInventory>> {
+) -> anyhow::Result<HashMap<InventoryKey, FifoInventory>> {
     trades
         .iter()
         .take_while(|t| checkpoint.is_some_and(|cp| t.checkpoint_key() <= cp))
         .try_fold(HashMap::new(), |mut inventories, trade| {
             let inventory = inventories
-                .entry(trade.symbol.clone())
+                .entry(trade.inventory_key())
                 .or_insert_with(FifoInventory::new);
 
             inventory
@@ -327,8 +532,11 @@ async fn persist_metrics_row(pool: &SqlitePool, row: &DbMetricsRow) -> anyhow::R
             price_per_share,
             realized_pnl,
             cumulative_pnl,
-            net_position_after
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            net_position_after,
+            unrealized_pnl,
+            asset_class,
+            strategy_label
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         row.symbol,
         row.timestamp,
         row.trade_type,
@@ -339,6 +547,9 @@ async fn persist_metrics_row(pool: &SqlitePool, row: &DbMetricsRow) -> anyhow::R
         row.realized_pnl,
         row.cumulative_pnl,
         row.net_position_after,
+        row.unrealized_pnl,
+        row.asset_class,
+        row.strategy_label,
     )
     .execute(pool)
     .await
@@ -349,29 +560,201 @@ async fn persist_metrics_row(pool: &SqlitePool, row: &DbMetricsRow) -> anyhow::R
 
 async fn process_and_persist_trade(
     pool: &SqlitePool,
-    inventories: &mut HashMap<Symbol, FifoInventory>,
+    inventories: &mut HashMap<InventoryKey, FifoInventory>,
     trade: &Trade,
+    asset_classes: &AssetClasses,
 ) -> anyhow::Result<()> {
     let inventory = inventories
-        .entry(trade.symbol.clone())
+        .entry(trade.inventory_key())
         .or_insert_with(FifoInventory::new);
 
     let result = inventory
         .process_trade(trade.quantity, trade.price_per_share, trade.direction)
         .map_err(|e: PnlError| anyhow::anyhow!("FIFO processing error: {e}"))?;
 
-    let row = trade.to_db_values(&result)?;
+    let row = trade.to_db_values(&result, asset_classes)?;
     persist_metrics_row(pool, &row).await
 }
 
-pub(crate) async fn process_iteration(pool: &SqlitePool) -> anyhow::Result<usize> {
+/// Deletes the MARK row of the `strategy_label`/`symbol` position.
+async fn delete_mark_row(
+    pool: &SqlitePool,
+    symbol: &Symbol,
+    strategy_label: Option<&str>,
+) -> anyhow::Result<()> {
+    let symbol = symbol.as_str();
+    sqlx::query!(
+        "DELETE FROM metrics_pnl
+         WHERE trade_type = 'MARK' AND symbol = ?1 AND strategy_label IS ?2",
+        symbol,
+        strategy_label
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Replaces the MARK row of the position with `row` in a single transaction.
+async fn replace_mark_row(pool: &SqlitePool, row: &DbMetricsRow) -> anyhow::Result<()> {
+    let mut sql_tx = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM metrics_pnl
+         WHERE trade_type = 'MARK' AND symbol = ?1 AND strategy_label IS ?2",
+        row.symbol,
+        row.strategy_label
+    )
+    .execute(&mut *sql_tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO metrics_pnl (
+            symbol,
+            timestamp,
+            trade_type,
+            trade_id,
+            trade_direction,
+            quantity,
+            price_per_share,
+            realized_pnl,
+            cumulative_pnl,
+            net_position_after,
+            unrealized_pnl,
+            asset_class,
+            strategy_label
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        row.symbol,
+        row.timestamp,
+        row.trade_type,
+        row.trade_id,
+        row.trade_direction,
+        row.quantity,
+        row.price_per_share,
+        row.realized_pnl,
+        row.cumulative_pnl,
+        row.net_position_after,
+        row.unrealized_pnl,
+        row.asset_class,
+        row.strategy_label,
+    )
+    .execute(&mut *sql_tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to insert MARK row into metrics_pnl: {e}"))?;
+
+    sql_tx.commit().await?;
+    Ok(())
+}
+
+/// Builds the MARK row valuing `inventory` at `mark_price`.
+fn mark_row(
+    (strategy_label, symbol): &InventoryKey,
+    inventory: &FifoInventory,
+    mark_price: Decimal,
+    asset_classes: &AssetClasses,
+) -> anyhow::Result<DbMetricsRow> {
+    let net_position = inventory.net_position();
+    let direction = if net_position > Decimal::ZERO {
+        Direction::Buy
+    } else {
+        Direction::Sell
+    };
+
+    let to_f64 = |column: &str, value: Decimal| {
+        value
+            .to_f64()
+            .ok_or_else(|| anyhow::anyhow!("Failed to convert {column} to f64"))
+    };
+
+    let unrealized_pnl = inventory
+        .unrealized_pnl(mark_price)
+        .map_err(|e| anyhow::anyhow!("Unrealized P&L error: {e}"))?;
+
+    Ok(DbMetricsRow {
+        symbol: symbol.as_str().to_string(),
+        timestamp: Utc::now(),
+        trade_type: MARK_TRADE_TYPE.to_string(),
+        trade_id: 0,
+        trade_direction: direction.as_str().to_string(),
+        quantity: to_f64("quantity", net_position.abs())?,
+        price_per_share: to_f64("mark_price", mark_price)?,
+        realized_pnl: None,
+        cumulative_pnl: to_f64("cumulative_pnl", inventory.cumulative_pnl())?,
+        net_position_after: to_f64("net_position_after", net_position)?,
+        unrealized_pnl: Some(to_f64("unrealized_pnl", unrealized_pnl)?),
+        asset_class: asset_classes.class_of(symbol.as_str()).map(str::to_string),
+        strategy_label: strategy_label.clone(),
+    })
+}
+
+/// Writes a MARK row for every open position with a known mark price and
+/// removes the MARK rows of flat or unpriced positions. Returns the number
+/// of positions marked.
+async fn mark_open_positions(
+    pool: &SqlitePool,
+    inventories: &HashMap<InventoryKey, FifoInventory>,
+    mark_prices: &impl MarkPriceSource,
+    asset_classes: &AssetClasses,
+) -> anyhow::Result<usize> {
+    let mut marked = 0;
+
+    for (key, inventory) in inventories {
+        let (strategy_label, symbol) = key;
+
+        let mark_price = if inventory.net_position().is_zero() {
+            None
+        } else {
+            mark_prices.mark_price(symbol).await?
+        };
+
+        let Some(mark_price) = mark_price else {
+            debug!("No open position or mark price for {symbol}, clearing its MARK row");
+            delete_mark_row(pool, symbol, strategy_label.as_deref()).await?;
+            continue;
+        };
+
+        let row = mark_row(key, inventory, mark_price, asset_classes)?;
+        replace_mark_row(pool, &row).await?;
+        marked += 1;
+    }
+
+    Ok(marked)
+}
+
+/// Processes trades newer than the checkpoint, then marks the open positions
+/// at `mark_prices`. Trades are loaded through `read_pool`; the checkpoint
+/// and P&L rows go through the write pool.
+#[cfg(test)]
+pub(crate) async fn process_iteration(
+    pool: &SqlitePool,
+    read_pool: &ReadPool,
+    asset_classes: &AssetClasses,
+    mark_prices: &impl MarkPriceSource,
+) -> anyhow::Result<usize> {
+    process_iteration_with_hedges(
+        pool,
+        read_pool,
+        HedgeSource::BrokerFills,
+        asset_classes,
+        mark_prices,
+    )
+    .await
+}
+
+async fn process_iteration_with_hedges(
+    pool: &SqlitePool,
+    read_pool: &ReadPool,
+    hedges: HedgeSource,
+    asset_classes: &AssetClasses,
+    mark_prices: &impl MarkPriceSource,
+) -> anyhow::Result<usize> {
     let checkpoint = load_checkpoint(pool).await?;
 
     if checkpoint.is_none() {
         info!("No checkpoint found, processing all historical trades");
     }
 
-    let all_trades = load_all_trades(pool).await?;
+    let all_trades = load_all_trades(read_pool.pool(), hedges).await?;
     let mut inventories = rebuild_fifo_state(&all_trades, checkpoint)?;
 
     let new_trades: Vec<_> = all_trades
@@ -380,17 +763,106 @@ pub(crate) async fn process_iteration(pool: &SqlitePool) -> anyhow::Result<usize
         .collect();
 
     for trade in &new_trades {
-        process_and_persist_trade(pool, &mut inventories, trade).await?;
+        process_and_persist_trade(pool, &mut inventories, trade, asset_classes).await?;
     }
 
+    let marked = mark_open_positions(pool, &inventories, mark_prices, asset_classes).await?;
+    debug!("Marked {marked} open positions");
+
     Ok(new_trades.len())
 }
 
+/// Processes new trades and then checks P&L alerts, logging any failure.
+async fn run_once(
+    pool: &SqlitePool,
+    read_pool: &ReadPool,
+    hedges: HedgeSource,
+    asset_classes: &AssetClasses,
+    mark_prices: &impl MarkPriceSource,
+    alerter: Option<&PnlAlerter>,
+) {
+    match process_iteration_with_hedges(pool, read_pool, hedges, asset_classes, mark_prices).await {
+        Ok(count) => info!("Processed {count} new trades"),
+        Err(e) => error!("Processing error: {e}"),
+    }
+
+    if let Some(alerter) = alerter {
+        match alerter.check(pool).await {
+            Ok(sent) if sent > 0 => info!("Sent {sent} P&L alerts"),
+            Ok(_) => {}
+            Err(e) => error!("P&L alert check failed: {e}"),
+        }
+    }
+}
+
+/// Runs the reporter every `interval` as a task of the bot process, sharing
+/// its pools, for deployments that do not run the reporter binary.
+pub(crate) fn spawn_inline_reporter(
+    pool: SqlitePool,
+    read_pool: ReadPool,
+    interval: Duration,
+) -> JoinHandle<()> {
+    info!(
+        "Starting inline P&L reporter with processing interval: {}s",
+        interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let asset_classes = AssetClasses::default();
+        let mark_prices = StoredPythPrice::new(read_pool.pool().clone());
+
+        loop {
+            tokio::time::sleep(interval).await;
+            run_once(
+                &pool,
+                &read_pool,
+                HedgeSource::BrokerFills,
+                &asset_classes,
+                &mark_prices,
+                None,
+            )
+            .await;
+        }
+    })
+}
+
 pub async fn run(env: ReporterEnv) -> anyhow::Result<()> {
     use crate::env::HasSqlite;
 
     let pool = env.get_sqlite_pool().await?;
+
+    match &env.command {
+        Some(ReporterCommand::Export {
+            format,
+            from,
+            to,
+            symbol,
+        }) => {
+            let filter = PnlExportFilter {
+                from: *from,
+                to: *to,
+                symbol: symbol.clone(),
+            };
+            let stdout = std::io::BufWriter::new(std::io::stdout());
+            export_pnl(&pool, *format, &filter, stdout).await?;
+            return Ok(());
+        }
+        Some(ReporterCommand::Slippage { execution_id }) => {
+            let slippage =
+                load_execution_slippage(&pool, &env.symbol_convention, *execution_id).await?;
+            serde_json::to_writer_pretty(std::io::stdout(), &slippage)?;
+            println!();
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let read_pool = ReadPool::connect_or_share(env.read_database_url.as_deref(), &pool).await?;
     let interval = env.processing_interval();
+    let alerter = env.pnl_alerter();
+    let asset_classes = env.asset_classes();
+    let hedges = env.hedge_source();
+    let mark_prices = StoredPythPrice::new(read_pool.pool().clone());
 
     info!("Starting P&L reporter");
     sqlx::migrate!().run(&pool).await?;
@@ -410,10 +882,15 @@ pub async fn run(env: ReporterEnv) -> anyhow::Result<()> {
                 break;
             }
             () = tokio::time::sleep(interval) => {
-                match process_iteration(&pool).await {
-                    Ok(count) => info!("Processed {count} new trades"),
-                    Err(e) => error!("Processing error: {e}"),
-                }
+                run_once(
+                    &pool,
+                    &read_pool,
+                    hedges,
+                    &asset_classes,
+                    &mark_prices,
+                    alerter.as_ref(),
+                )
+                .await;
             }
         }
     }
@@ -425,8 +902,19 @@ pub async fn run(env: ReporterEnv) -> anyhow::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
     use rust_decimal_macros::dec;
 
+    /// Marks every symbol at the same price, or leaves positions unmarked.
+    struct FixedMarkPrice(Option<Decimal>);
+
+    #[async_trait]
+    impl MarkPriceSource for FixedMarkPrice {
+        async fn mark_price(&self, _symbol: &Symbol) -> anyhow::Result<Option<Decimal>> {
+            Ok(self.0)
+        }
+    }
+
     async fn create_test_pool() -> SqlitePool {
         let pool = SqlitePool::connect(":memory:").await.unwrap();
         sqlx::migrate!().run(&pool).await.unwrap();
@@ -440,6 +928,47 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_export_subcommand_parsing() {
+        let env = ReporterEnv::try_parse_from([
+            "reporter",
+            "export",
+            "--format",
+            "json",
+            "--from",
+            "2025-01-06",
+            "--symbol",
+            "AAPL",
+        ])
+        .unwrap();
+
+        assert!(env.is_export());
+        let Some(ReporterCommand::Export {
+            format,
+            from,
+            to,
+            symbol,
+        }) = env.command
+        else {
+            panic!("expected export subcommand");
+        };
+        assert_eq!(format, ExportFormat::Json);
+        assert_eq!(from, NaiveDate::from_ymd_opt(2025, 1, 6));
+        assert_eq!(to, None);
+        assert_eq!(symbol.as_deref(), Some("AAPL"));
+
+        let env =
+            ReporterEnv::try_parse_from(["reporter", "slippage", "--execution-id", "42"]).unwrap();
+        assert!(env.is_export());
+        assert!(matches!(
+            env.command,
+            Some(ReporterCommand::Slippage { execution_id: 42 })
+        ));
+
+        let env = ReporterEnv::try_parse_from(["reporter"]).unwrap();
+        assert!(!env.is_export());
+    }
+
     #[tokio::test]
     async fn test_rebuild_fifo_state_empty() {
         let trades: Vec<Trade> = vec![];
@@ -452,9 +981,16 @@ mod tests {
     async fn test_trade_from_onchain_row() {
         let naive_dt = DateTime::from_timestamp(0, 0).unwrap().naive_utc();
 
-        let trade =
-            Trade::from_onchain_row(1, "AAPL".to_string(), 10.0, "BUY", 100.0, Some(naive_dt))
-                .unwrap();
+        let trade = Trade::from_onchain_row(
+            1,
+            "AAPL".to_string(),
+            "10.0",
+            "BUY",
+            "100.0",
+            Some(naive_dt),
+            None,
+        )
+        .unwrap();
 
         assert_eq!(trade.id, 1);
         assert_eq!(trade.symbol.as_str(), "AAPL");
@@ -470,10 +1006,11 @@ mod tests {
         let trade = Trade::from_offchain_row(
             2,
             "AAPL".to_string(),
-            5,
+            Decimal::from(5),
             "SELL",
             Some(10500),
             Some(naive_dt),
+            None,
         )
         .unwrap();
 
@@ -487,7 +1024,14 @@ mod tests {
     #[tokio::test]
     async fn test_process_iteration_no_trades() {
         let pool = create_test_pool().await;
-        let count = process_iteration(&pool).await.unwrap();
+        let count = process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .unwrap();
         assert_eq!(count, 0);
     }
 
@@ -520,6 +1064,8 @@ mod tests {
         let tx_hash = format!("0x{:064x}", rand::random::<u64>());
         let log_index = i64::try_from(rand::random::<u64>() % 1000).expect("log_index overflow");
         let naive_timestamp = timestamp.naive_utc();
+        let amount_str = amount.to_string();
+        let price_usdc_str = price_usdc.to_string();
 
         sqlx::query!(
             "INSERT INTO onchain_trades (
@@ -534,9 +1080,9 @@ mod tests {
             tx_hash,
             log_index,
             symbol,
-            amount,
+            amount_str,
             direction,
-            price_usdc,
+            price_usdc_str,
             naive_timestamp,
         )
         .execute(pool)
@@ -579,6 +1125,26 @@ mod tests {
         .expect("Failed to insert offchain trade");
     }
 
+    async fn insert_labelled_onchain_trade(
+        pool: &SqlitePool,
+        strategy_label: &str,
+        amount: f64,
+        price_usdc: f64,
+        direction: &str,
+        timestamp: DateTime<Utc>,
+    ) {
+        insert_onchain_trade(pool, "AAPL", amount, price_usdc, direction, timestamp).await;
+
+        sqlx::query!(
+            "UPDATE onchain_trades SET strategy_label = ?1
+             WHERE id = (SELECT MAX(id) FROM onchain_trades)",
+            strategy_label
+        )
+        .execute(pool)
+        .await
+        .expect("Failed to label onchain trade");
+    }
+
     struct PnlMetric {
         realized_pnl: Option<f64>,
         cumulative_pnl: f64,
@@ -622,9 +1188,14 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", t1).await;
         insert_onchain_trade(&pool, "AAPL", 100.0, 11.0, "SELL", t2).await;
 
-        let count = process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        let count = process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
         assert_eq!(count, 2);
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
@@ -638,6 +1209,73 @@ mod tests {
         assert_f64_eq(metrics[1].net_position_after, 0.0);
     }
 
+    #[tokio::test]
+    async fn test_paper_orders_matched_instead_of_broker_fills() {
+        let pool = create_test_pool().await;
+
+        let t1 = DateTime::from_timestamp(1000, 0).expect("Invalid timestamp");
+        let t2 = DateTime::from_timestamp(2000, 0).expect("Invalid timestamp");
+        insert_onchain_trade(&pool, "AAPL", 10.0, 150.0, "BUY", t1).await;
+        insert_offchain_trade(&pool, "AAPL", 10, "SELL", 14000, t2).await;
+
+        let executed_at = t2.naive_utc();
+        sqlx::query!(
+            "INSERT INTO paper_orders
+                (symbol, shares, direction, status, price_cents, placed_at, executed_at)
+             VALUES ('AAPL', 10, 'SELL', 'FILLED', 15100, ?1, ?1)",
+            executed_at
+        )
+        .execute(&pool)
+        .await
+        .expect("Failed to insert paper order");
+
+        let count = process_iteration_with_hedges(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            HedgeSource::PaperOrders,
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
+        assert_eq!(count, 2);
+
+        let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[1].trade_type, "OFFCHAIN");
+        assert_option_f64_eq(metrics[1].realized_pnl, Some(10.0));
+        assert_f64_eq(metrics[1].net_position_after, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_inline_reporter_processes_trades() {
+        let pool = create_test_pool().await;
+
+        let t1 = DateTime::from_timestamp(1000, 0).expect("Invalid timestamp");
+        let t2 = DateTime::from_timestamp(2000, 0).expect("Invalid timestamp");
+        insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", t1).await;
+        insert_onchain_trade(&pool, "AAPL", 100.0, 11.0, "SELL", t2).await;
+
+        let reporter = spawn_inline_reporter(
+            pool.clone(),
+            ReadPool::new(pool.clone()),
+            Duration::from_millis(10),
+        );
+
+        let mut metrics = Vec::new();
+        for _ in 0..100 {
+            metrics = query_all_pnl_metrics(&pool, "AAPL").await;
+            if metrics.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        reporter.abort();
+
+        assert_eq!(metrics.len(), 2);
+        assert_option_f64_eq(metrics[1].realized_pnl, Some(100.0));
+    }
+
     #[tokio::test]
     async fn test_multiple_trades_fifo_ordering() {
         let pool = create_test_pool().await;
@@ -650,9 +1288,14 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 50.0, 12.0, "BUY", t2).await;
         insert_onchain_trade(&pool, "AAPL", 80.0, 11.0, "SELL", t3).await;
 
-        process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
         assert_eq!(metrics.len(), 3);
@@ -672,9 +1315,14 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", t1).await;
         insert_onchain_trade(&pool, "AAPL", 150.0, 11.0, "SELL", t2).await;
 
-        process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
         assert_eq!(metrics.len(), 2);
@@ -694,16 +1342,26 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", t1).await;
         insert_onchain_trade(&pool, "AAPL", 100.0, 11.0, "SELL", t2).await;
 
-        let count = process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        let count = process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
         assert_eq!(count, 2);
 
         insert_onchain_trade(&pool, "AAPL", 50.0, 12.0, "BUY", t3).await;
 
-        let count = process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        let count = process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
         assert_eq!(count, 1);
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
@@ -724,9 +1382,14 @@ mod tests {
         insert_offchain_trade(&pool, "AAPL", 50, "SELL", 1100, t2).await;
         insert_onchain_trade(&pool, "AAPL", 30.0, 12.0, "SELL", t3).await;
 
-        process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
         assert_eq!(metrics.len(), 3);
@@ -751,9 +1414,14 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 100.0, 12.0, "SELL", t2).await;
         insert_onchain_trade(&pool, "MSFT", 50.0, 210.0, "SELL", t2).await;
 
-        process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
         let aapl_metrics = query_all_pnl_metrics(&pool, "AAPL").await;
         assert_eq!(aapl_metrics.len(), 2);
@@ -765,19 +1433,227 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_duplicate_prevention() {
+    async fn test_trades_tagged_with_asset_class_and_rolled_up() {
         let pool = create_test_pool().await;
+        let asset_classes = AssetClasses::new(
+            [
+                "AAPL=Technology".parse().unwrap(),
+                "MSFT=Technology".parse().unwrap(),
+                "XOM=Energy".parse().unwrap(),
+            ],
+            SymbolConvention::default(),
+        );
 
         let t1 = DateTime::from_timestamp(1000, 0).expect("Invalid timestamp");
+        let t2 = DateTime::from_timestamp(2000, 0).expect("Invalid timestamp");
 
         insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", t1).await;
+        insert_onchain_trade(&pool, "MSFT", 50.0, 200.0, "BUY", t1).await;
+        insert_onchain_trade(&pool, "XOM", 10.0, 100.0, "BUY", t1).await;
+        insert_onchain_trade(&pool, "JPM", 10.0, 150.0, "BUY", t1).await;
+
+        insert_onchain_trade(&pool, "AAPL", 100.0, 12.0, "SELL", t2).await;
+        insert_onchain_trade(&pool, "MSFT", 50.0, 210.0, "SELL", t2).await;
+        insert_onchain_trade(&pool, "XOM", 10.0, 95.0, "SELL", t2).await;
+        insert_onchain_trade(&pool, "JPM", 10.0, 151.0, "SELL", t2).await;
+
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &asset_classes,
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
+
+        let tags =
+            sqlx::query!("SELECT DISTINCT symbol, asset_class FROM metrics_pnl ORDER BY symbol")
+                .fetch_all(&pool)
+                .await
+                .expect("Failed to query asset classes")
+                .into_iter()
+                .map(|row| (row.symbol, row.asset_class))
+                .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![
+                ("AAPL".to_string(), Some("Technology".to_string())),
+                ("JPM".to_string(), None),
+                ("MSFT".to_string(), Some("Technology".to_string())),
+                ("XOM".to_string(), Some("Energy".to_string())),
+            ]
+        );
+
+        let rollup = asset_class::load_pnl_by_asset_class(&pool)
+            .await
+            .expect("Failed to roll up P&L by asset class");
+        assert_eq!(
+            rollup,
+            vec![
+                asset_class::AssetClassPnl {
+                    asset_class: None,
+                    realized_pnl: dec!(10),
+                    trades: 2,
+                },
+                asset_class::AssetClassPnl {
+                    asset_class: Some("Energy".to_string()),
+                    realized_pnl: dec!(-50),
+                    trades: 2,
+                },
+                asset_class::AssetClassPnl {
+                    asset_class: Some("Technology".to_string()),
+                    realized_pnl: dec!(700),
+                    trades: 4,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strategies_matched_separately_and_summarized_by_label() {
+        let pool = create_test_pool().await;
+
+        let t1 = DateTime::from_timestamp(1000, 0).expect("Invalid timestamp");
+        let t2 = DateTime::from_timestamp(2000, 0).expect("Invalid timestamp");
+        let t3 = DateTime::from_timestamp(3000, 0).expect("Invalid timestamp");
+        let t4 = DateTime::from_timestamp(4000, 0).expect("Invalid timestamp");
+
+        // A single FIFO would close desk-a's sell against desk-b's older lot
+        insert_labelled_onchain_trade(&pool, "desk-b", 50.0, 20.0, "BUY", t1).await;
+        insert_labelled_onchain_trade(&pool, "desk-a", 100.0, 10.0, "BUY", t2).await;
+        insert_labelled_onchain_trade(&pool, "desk-a", 100.0, 11.0, "SELL", t3).await;
+        insert_labelled_onchain_trade(&pool, "desk-b", 50.0, 19.0, "SELL", t4).await;
+
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
-        process_iteration(&pool)
+        let summary = summary::load_pnl_summary(&pool, None)
             .await
-            .expect("Failed to process iteration");
-        let count = process_iteration(&pool)
+            .expect("Failed to load P&L summary");
+        assert_eq!(
+            summary.strategies,
+            vec![
+                summary::StrategyPnlSummary {
+                    strategy_label: Some("desk-a".to_string()),
+                    realized_pnl: dec!(100),
+                    cumulative_pnl: dec!(100),
+                },
+                summary::StrategyPnlSummary {
+                    strategy_label: Some("desk-b".to_string()),
+                    realized_pnl: dec!(-50),
+                    cumulative_pnl: dec!(-50),
+                },
+            ]
+        );
+        assert_eq!(summary.symbols.len(), 1);
+        assert_eq!(summary.symbols[0].realized_pnl, dec!(50));
+        assert_f64_eq(summary.symbols[0].net_position, 0.0);
+        assert_eq!(summary.total.cumulative_pnl, dec!(50));
+
+        let desk_b = summary::load_pnl_summary(&pool, Some("desk-b"))
+            .await
+            .expect("Failed to load P&L summary");
+        assert_eq!(desk_b.strategies.len(), 1);
+        assert_eq!(desk_b.symbols[0].realized_pnl, dec!(-50));
+        assert_eq!(desk_b.total.realized_pnl, dec!(-50));
+    }
+
+    #[tokio::test]
+    async fn test_open_positions_marked_and_excluded_from_checkpoint() {
+        let pool = create_test_pool().await;
+        let read_pool = ReadPool::new(pool.clone());
+
+        let t1 = DateTime::from_timestamp(1000, 0).expect("Invalid timestamp");
+        let t2 = DateTime::from_timestamp(2000, 0).expect("Invalid timestamp");
+        insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", t1).await;
+
+        let count = process_iteration(
+            &pool,
+            &read_pool,
+            &AssetClasses::default(),
+            &FixedMarkPrice(Some(dec!(12))),
+        )
+        .await
+        .expect("Failed to process iteration");
+        assert_eq!(count, 1);
+
+        let mark = sqlx::query!(
+            "SELECT trade_direction, quantity, price_per_share, net_position_after, unrealized_pnl
+             FROM metrics_pnl WHERE trade_type = 'MARK' AND symbol = 'AAPL'"
+        )
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to query MARK row");
+        assert_eq!(mark.trade_direction, "BUY");
+        assert_f64_eq(mark.quantity, 100.0);
+        assert_f64_eq(mark.price_per_share, 12.0);
+        assert_f64_eq(mark.net_position_after, 100.0);
+        assert_option_f64_eq(mark.unrealized_pnl, Some(200.0));
+
+        // The mark is neither a trade nor realized P&L of its asset class
+        let rollup = asset_class::load_pnl_by_asset_class(&pool)
             .await
-            .expect("Failed to process iteration");
+            .expect("Failed to roll up P&L by asset class");
+        assert_eq!(
+            rollup,
+            vec![asset_class::AssetClassPnl {
+                asset_class: None,
+                realized_pnl: dec!(0),
+                trades: 1,
+            }]
+        );
+
+        // The MARK row is timestamped now, after this trade; it must not move
+        // the checkpoint past the trade or feed into the FIFO state
+        insert_onchain_trade(&pool, "AAPL", 100.0, 11.0, "SELL", t2).await;
+
+        let count = process_iteration(
+            &pool,
+            &read_pool,
+            &AssetClasses::default(),
+            &FixedMarkPrice(Some(dec!(12))),
+        )
+        .await
+        .expect("Failed to process iteration");
+        assert_eq!(count, 1);
+
+        let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
+        assert_eq!(metrics.len(), 2);
+        assert!(metrics.iter().all(|m| m.trade_type == "ONCHAIN"));
+        assert_option_f64_eq(metrics[1].realized_pnl, Some(100.0));
+        assert_f64_eq(metrics[1].net_position_after, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_prevention() {
+        let pool = create_test_pool().await;
+
+        let t1 = DateTime::from_timestamp(1000, 0).expect("Invalid timestamp");
+
+        insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", t1).await;
+
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
+        let count = process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
         assert_eq!(count, 0);
 
@@ -801,9 +1677,14 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 70.0, 12.0, "SELL", timestamps[5]).await;
         insert_onchain_trade(&pool, "AAPL", 20.0, 11.5, "BUY", timestamps[6]).await;
 
-        process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
         assert_eq!(metrics.len(), 7);
@@ -850,9 +1731,14 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 0.5, 149.0, "SELL", timestamps[2]).await;
         insert_onchain_trade(&pool, "AAPL", 0.6, 148.0, "BUY", timestamps[3]).await;
 
-        process_iteration(&pool)
-            .await
-            .expect("Failed to process iteration");
+        process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process iteration");
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
         assert_eq!(metrics.len(), 4);
@@ -885,9 +1771,14 @@ mod tests {
         insert_onchain_trade(&pool, "AAPL", 100.0, 10.0, "BUY", same_timestamp).await;
         insert_onchain_trade(&pool, "AAPL", 50.0, 11.0, "BUY", same_timestamp).await;
 
-        let count = process_iteration(&pool)
-            .await
-            .expect("Failed to process first iteration");
+        let count = process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process first iteration");
         assert_eq!(count, 2, "First iteration should process both trades");
 
         let metrics = query_all_pnl_metrics(&pool, "AAPL").await;
@@ -899,9 +1790,14 @@ mod tests {
 
         insert_onchain_trade(&pool, "AAPL", 30.0, 12.0, "BUY", same_timestamp).await;
 
-        let count = process_iteration(&pool)
-            .await
-            .expect("Failed to process second iteration");
+        let count = process_iteration(
+            &pool,
+            &ReadPool::new(pool.clone()),
+            &AssetClasses::default(),
+            &FixedMarkPrice(None),
+        )
+        .await
+        .expect("Failed to process second iteration");
         assert_eq!(
             count, 1,
             "Second iteration should process only the new trade with same timestamp"
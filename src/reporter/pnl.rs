@@ -196,7 +196,25 @@ impl FifoInventory {
         });
     }
 
-    fn net_position(&self) -> Decimal {
+    /// P&L of the open lots if they were closed at `mark_price`.
+    pub(super) fn unrealized_pnl(&self, mark_price: Decimal) -> Result<Decimal, PnlError> {
+        self.lots.iter().try_fold(Decimal::ZERO, |acc, lot| {
+            let pnl = match lot.direction {
+                Direction::Buy => mark_price - lot.cost_basis_per_share,
+                Direction::Sell => lot.cost_basis_per_share - mark_price,
+            }
+            .checked_mul(lot.quantity_remaining)
+            .ok_or(PnlError::ArithmeticOverflow)?;
+
+            acc.checked_add(pnl).ok_or(PnlError::ArithmeticOverflow)
+        })
+    }
+
+    pub(super) fn cumulative_pnl(&self) -> Decimal {
+        self.cumulative_pnl
+    }
+
+    pub(super) fn net_position(&self) -> Decimal {
         self.lots
             .iter()
             .fold(Decimal::ZERO, |acc, lot| match lot.direction {
@@ -238,6 +256,29 @@ mod tests {
         assert_eq!(result.net_position_after, dec!(0));
     }
 
+    #[test]
+    fn test_unrealized_pnl_values_open_lots_at_mark() {
+        let mut fifo = FifoInventory::new();
+        assert_eq!(fifo.unrealized_pnl(dec!(12.00)).unwrap(), dec!(0));
+
+        fifo.process_trade(dec!(100), dec!(10.00), Direction::Buy)
+            .unwrap();
+        fifo.process_trade(dec!(50), dec!(12.00), Direction::Buy)
+            .unwrap();
+        fifo.process_trade(dec!(80), dec!(11.00), Direction::Sell)
+            .unwrap();
+
+        // 20 shares left at 10.00 and 50 at 12.00
+        assert_eq!(fifo.unrealized_pnl(dec!(11.50)).unwrap(), dec!(5.00));
+        assert_eq!(fifo.cumulative_pnl(), dec!(80.00));
+
+        let mut short = FifoInventory::new();
+        short
+            .process_trade(dec!(10), dec!(20.00), Direction::Sell)
+            .unwrap();
+        assert_eq!(short.unrealized_pnl(dec!(18.00)).unwrap(), dec!(20.00));
+    }
+
     #[test]
     fn test_multiple_lots_fifo() {
         let mut fifo = FifoInventory::new();
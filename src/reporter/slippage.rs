@@ -0,0 +1,179 @@
+//! Slippage of a broker fill, attributed to the onchain trades it hedges.
+//!
+//! One whole-share execution often aggregates several partial onchain fills
+//! at different prices. Each linked trade is charged the difference between
+//! its onchain price and the execution's fill price for the shares it
+//! contributed, so the slippage of the fill can be traced back to the
+//! specific onchain fills behind it. Slippage is a cost: positive when the
+//! hedge filled worse than the onchain price (bought higher or sold lower).
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use st0x_broker::{Direction, OrderState};
+
+use crate::error::OnChainError;
+use crate::offchain::execution::find_execution_by_id;
+use crate::onchain::io::SymbolConvention;
+use crate::trade_execution_link::get_linked_trades;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SlippageError {
+    #[error(transparent)]
+    Load(#[from] OnChainError),
+    #[error("Execution {0} not found")]
+    NotFound(i64),
+    #[error("Execution {0} has not filled")]
+    NotFilled(i64),
+    #[error("Cannot represent contributed shares {0} as a decimal")]
+    NonDecimalShares(f64),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TradeSlippage {
+    pub(crate) trade_id: Option<i64>,
+    pub(crate) tx_hash: String,
+    pub(crate) log_index: u64,
+    pub(crate) shares: Decimal,
+    pub(crate) onchain_price: Decimal,
+    pub(crate) slippage: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ExecutionSlippage {
+    pub(crate) execution_id: i64,
+    pub(crate) symbol: String,
+    pub(crate) direction: Direction,
+    pub(crate) fill_price: Decimal,
+    pub(crate) trades: Vec<TradeSlippage>,
+    pub(crate) total_slippage: Decimal,
+}
+
+/// Slippage of the filled execution `execution_id`, split across the onchain
+/// trades linked to it.
+pub(crate) async fn load_execution_slippage(
+    pool: &SqlitePool,
+    convention: &SymbolConvention,
+    execution_id: i64,
+) -> Result<ExecutionSlippage, SlippageError> {
+    let execution = find_execution_by_id(pool, execution_id)
+        .await?
+        .ok_or(SlippageError::NotFound(execution_id))?;
+
+    let OrderState::Filled { price_cents, .. } = execution.state else {
+        return Err(SlippageError::NotFilled(execution_id));
+    };
+    let fill_price = Decimal::from(price_cents) / Decimal::ONE_HUNDRED;
+
+    let trades = get_linked_trades(pool, convention, execution_id)
+        .await?
+        .into_iter()
+        .map(|linked| {
+            let shares = Decimal::from_f64(linked.contributed_shares)
+                .map(|shares| shares.normalize())
+                .ok_or(SlippageError::NonDecimalShares(linked.contributed_shares))?;
+
+            let price_difference = match execution.direction {
+                Direction::Buy => fill_price - linked.trade.price_usdc,
+                Direction::Sell => linked.trade.price_usdc - fill_price,
+            };
+
+            Ok(TradeSlippage {
+                trade_id: linked.trade.id,
+                tx_hash: linked.trade.tx_hash.to_string(),
+                log_index: linked.trade.log_index,
+                shares,
+                onchain_price: linked.trade.price_usdc,
+                slippage: price_difference * shares,
+            })
+        })
+        .collect::<Result<Vec<_>, SlippageError>>()?;
+
+    Ok(ExecutionSlippage {
+        execution_id,
+        symbol: execution.symbol.to_string(),
+        direction: execution.direction,
+        fill_price,
+        total_slippage: trades.iter().map(|trade| trade.slippage).sum(),
+        trades,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offchain::execution::OffchainExecution;
+    use crate::test_utils::{OnchainTradeBuilder, setup_test_db};
+    use crate::trade_execution_link::TradeExecutionLink;
+    use alloy::primitives::fixed_bytes;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use st0x_broker::{Shares, SupportedBroker, Symbol};
+
+    #[tokio::test]
+    async fn test_slippage_is_attributed_to_each_linked_trade() {
+        let pool = setup_test_db().await;
+
+        let mut first = OnchainTradeBuilder::new()
+            .with_amount(dec!(0.4))
+            .with_price(dec!(150.10))
+            .build();
+        first.tx_hash =
+            fixed_bytes!("0x7777777777777777777777777777777777777777777777777777777777777777");
+        let mut second = OnchainTradeBuilder::new()
+            .with_amount(dec!(0.6))
+            .with_price(dec!(150.40))
+            .build();
+        second.tx_hash =
+            fixed_bytes!("0x8888888888888888888888888888888888888888888888888888888888888888");
+
+        // Hedged by buying one share at $150.30
+        let execution = OffchainExecution {
+            id: None,
+            symbol: Symbol::new("AAPL").unwrap(),
+            shares: Shares::new(1).unwrap(),
+            direction: Direction::Buy,
+            broker: SupportedBroker::Schwab,
+            state: OrderState::Filled {
+                executed_at: Utc::now(),
+                order_id: "1004055538123".to_string(),
+                price_cents: 15030,
+                reported_price: None,
+            },
+        };
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let first_id = first.save_within_transaction(&mut sql_tx).await.unwrap();
+        let second_id = second.save_within_transaction(&mut sql_tx).await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        for (trade_id, shares) in [(first_id, 0.4), (second_id, 0.6)] {
+            TradeExecutionLink::new(trade_id, execution_id, shares)
+                .save_within_transaction(&mut sql_tx)
+                .await
+                .unwrap();
+        }
+        sql_tx.commit().await.unwrap();
+
+        let slippage = load_execution_slippage(&pool, &SymbolConvention::default(), execution_id)
+            .await
+            .unwrap();
+
+        assert_eq!(slippage.fill_price, dec!(150.30));
+        assert_eq!(slippage.trades.len(), 2);
+        // Bought $0.20 above the first fill and $0.10 below the second
+        assert_eq!(slippage.trades[0].trade_id, Some(first_id));
+        assert_eq!(slippage.trades[0].slippage, dec!(0.08));
+        assert_eq!(slippage.trades[1].trade_id, Some(second_id));
+        assert_eq!(slippage.trades[1].slippage, dec!(-0.06));
+        assert_eq!(slippage.total_slippage, dec!(0.02));
+
+        assert!(matches!(
+            load_execution_slippage(&pool, &SymbolConvention::default(), execution_id + 1).await,
+            Err(SlippageError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+//! P&L summary over `metrics_pnl`, served as JSON by `GET /pnl/summary`.
+//!
+//! Each symbol reports its total realized P&L together with the cumulative
+//! P&L and net position from its latest row, summed across strategies since
+//! every strategy keeps its own FIFO inventory. The same figures are also
+//! segmented by strategy label, and the summary can be restricted to a single
+//! label. Like the export, monetary values are decimals (serialized as
+//! strings) while share quantities stay floats. MARK rows are left out, so
+//! the summary only reflects realized P&L.
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum PnlSummaryError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Cannot represent {column}={value} as a decimal")]
+    NonDecimal { column: &'static str, value: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SymbolPnlSummary {
+    pub(crate) symbol: String,
+    pub(crate) realized_pnl: Decimal,
+    pub(crate) cumulative_pnl: Decimal,
+    pub(crate) net_position: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PnlTotals {
+    pub(crate) realized_pnl: Decimal,
+    pub(crate) cumulative_pnl: Decimal,
+}
+
+/// P&L of one strategy across its symbols; `strategy_label` is `None` for
+/// trades recorded without a label.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StrategyPnlSummary {
+    pub(crate) strategy_label: Option<String>,
+    pub(crate) realized_pnl: Decimal,
+    pub(crate) cumulative_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PnlSummary {
+    pub(crate) symbols: Vec<SymbolPnlSummary>,
+    pub(crate) strategies: Vec<StrategyPnlSummary>,
+    pub(crate) total: PnlTotals,
+}
+
+fn to_decimal(column: &'static str, value: f64) -> Result<Decimal, PnlSummaryError> {
+    Decimal::from_f64(value)
+        .map(|decimal| decimal.normalize())
+        .ok_or(PnlSummaryError::NonDecimal { column, value })
+}
+
+/// Summarizes P&L per symbol, ordered by symbol, per strategy, ordered by
+/// label with unlabelled trades first, plus totals. Only rows of
+/// `strategy_label` are included when it is set.
+pub(crate) async fn load_pnl_summary(
+    pool: &SqlitePool,
+    strategy_label: Option<&str>,
+) -> Result<PnlSummary, PnlSummaryError> {
+    let rows = sqlx::query!(
+        r#"
+        WITH filtered AS (
+            SELECT *
+            FROM metrics_pnl
+            WHERE trade_type != 'MARK'
+              AND (?1 IS NULL OR strategy_label = ?1)
+        ),
+        latest AS (
+            SELECT
+                strategy_label,
+                symbol,
+                cumulative_pnl,
+                net_position_after,
+                ROW_NUMBER() OVER (
+                    PARTITION BY strategy_label, symbol ORDER BY id DESC
+                ) AS row_rank
+            FROM filtered
+        ),
+        realized AS (
+            SELECT
+                strategy_label,
+                symbol,
+                COALESCE(SUM(realized_pnl), 0.0) AS realized_pnl
+            FROM filtered
+            GROUP BY strategy_label, symbol
+        )
+        SELECT
+            latest.strategy_label AS "strategy_label?: String",
+            latest.symbol AS "symbol!: String",
+            realized.realized_pnl AS "realized_pnl!: f64",
+            latest.cumulative_pnl AS "cumulative_pnl!: f64",
+            latest.net_position_after AS "net_position!: f64"
+        FROM latest
+        JOIN realized
+            ON realized.symbol = latest.symbol
+            AND realized.strategy_label IS latest.strategy_label
+        WHERE latest.row_rank = 1
+        "#,
+        strategy_label
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut symbols: BTreeMap<String, SymbolPnlSummary> = BTreeMap::new();
+    let mut strategies: BTreeMap<Option<String>, StrategyPnlSummary> = BTreeMap::new();
+
+    for row in rows {
+        let realized_pnl = to_decimal("realized_pnl", row.realized_pnl)?;
+        let cumulative_pnl = to_decimal("cumulative_pnl", row.cumulative_pnl)?;
+
+        let symbol = symbols
+            .entry(row.symbol.clone())
+            .or_insert_with(|| SymbolPnlSummary {
+                symbol: row.symbol,
+                realized_pnl: Decimal::ZERO,
+                cumulative_pnl: Decimal::ZERO,
+                net_position: 0.0,
+            });
+        symbol.realized_pnl += realized_pnl;
+        symbol.cumulative_pnl += cumulative_pnl;
+        symbol.net_position += row.net_position;
+
+        let strategy = strategies
+            .entry(row.strategy_label.clone())
+            .or_insert_with(|| StrategyPnlSummary {
+                strategy_label: row.strategy_label,
+                realized_pnl: Decimal::ZERO,
+                cumulative_pnl: Decimal::ZERO,
+            });
+        strategy.realized_pnl += realized_pnl;
+        strategy.cumulative_pnl += cumulative_pnl;
+    }
+
+    let symbols: Vec<_> = symbols.into_values().collect();
+    let total = PnlTotals {
+        realized_pnl: symbols.iter().map(|summary| summary.realized_pnl).sum(),
+        cumulative_pnl: symbols.iter().map(|summary| summary.cumulative_pnl).sum(),
+    };
+
+    Ok(PnlSummary {
+        symbols,
+        strategies: strategies.into_values().collect(),
+        total,
+    })
+}
@@ -0,0 +1,241 @@
+//! Per-method call counts and latency histograms for provider RPC calls.
+//!
+//! Calls are wrapped with [`instrumented`] where they are made, which times
+//! them against a process-wide [`RpcMetrics`] when recording is enabled with
+//! `--rpc-metrics`, so slowness can be attributed to the chain rather than
+//! the broker. The counters are served by `GET /stats/rpc` and, like
+//! [`crate::stats::Stats`], only ever increase for the lifetime of the
+//! process.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::IntoFuture;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Upper bounds, in milliseconds, of the latency histogram buckets. Slower
+/// calls fall into a final unbounded bucket.
+const LATENCY_BUCKETS_MS: [u64; 8] = [10, 50, 100, 250, 500, 1_000, 2_500, 5_000];
+
+static RPC_METRICS: LazyLock<RpcMetrics> = LazyLock::new(RpcMetrics::default);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RpcMethod {
+    GetLogs,
+    DebugTraceTransaction,
+    Symbol,
+    GetBlockNumber,
+}
+
+impl RpcMethod {
+    const ALL: [Self; 4] = [
+        Self::GetLogs,
+        Self::DebugTraceTransaction,
+        Self::Symbol,
+        Self::GetBlockNumber,
+    ];
+
+    const fn index(self) -> usize {
+        match self {
+            Self::GetLogs => 0,
+            Self::DebugTraceTransaction => 1,
+            Self::Symbol => 2,
+            Self::GetBlockNumber => 3,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MethodMetrics {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    total_latency_micros: AtomicU64,
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RpcMetrics {
+    enabled: AtomicBool,
+    methods: [MethodMetrics; RpcMethod::ALL.len()],
+}
+
+/// Number of calls whose latency fell within a histogram bucket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LatencyBucket {
+    /// Inclusive upper bound in milliseconds, or `None` for the last bucket
+    pub(crate) le_ms: Option<u64>,
+    pub(crate) count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RpcMethodSnapshot {
+    pub(crate) calls: u64,
+    pub(crate) errors: u64,
+    pub(crate) total_latency_micros: u64,
+    pub(crate) latency_buckets: Vec<LatencyBucket>,
+}
+
+/// Point-in-time copy of the RPC metrics, served by `GET /stats/rpc`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RpcMetricsSnapshot {
+    pub(crate) enabled: bool,
+    pub(crate) methods: BTreeMap<RpcMethod, RpcMethodSnapshot>,
+}
+
+impl RpcMetrics {
+    /// The process-wide metrics recorded by [`instrumented`].
+    pub(crate) fn global() -> &'static Self {
+        &RPC_METRICS
+    }
+
+    pub(crate) fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Awaits `call`, recording its latency and outcome under `method` if
+    /// recording is enabled.
+    pub(crate) async fn instrument<T, E>(
+        &self,
+        method: RpcMethod,
+        call: impl IntoFuture<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return call.await;
+        }
+
+        let started = Instant::now();
+        let result = call.await;
+        self.record(method, started.elapsed(), result.is_ok());
+        result
+    }
+
+    fn record(&self, method: RpcMethod, latency: Duration, succeeded: bool) {
+        let metrics = &self.methods[method.index()];
+
+        metrics.calls.fetch_add(1, Ordering::Relaxed);
+        if !succeeded {
+            metrics.errors.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let latency_micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        metrics
+            .total_latency_micros
+            .fetch_add(latency_micros, Ordering::Relaxed);
+
+        let latency_ms = latency_micros / 1_000;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        metrics.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> RpcMetricsSnapshot {
+        let bounds = LATENCY_BUCKETS_MS.iter().copied().map(Some).chain([None]);
+
+        let methods = RpcMethod::ALL
+            .into_iter()
+            .map(|method| {
+                let metrics = &self.methods[method.index()];
+                let latency_buckets = bounds
+                    .clone()
+                    .zip(&metrics.latency_buckets)
+                    .map(|(le_ms, count)| LatencyBucket {
+                        le_ms,
+                        count: count.load(Ordering::Relaxed),
+                    })
+                    .collect();
+
+                let snapshot = RpcMethodSnapshot {
+                    calls: metrics.calls.load(Ordering::Relaxed),
+                    errors: metrics.errors.load(Ordering::Relaxed),
+                    total_latency_micros: metrics.total_latency_micros.load(Ordering::Relaxed),
+                    latency_buckets,
+                };
+
+                (method, snapshot)
+            })
+            .collect();
+
+        RpcMetricsSnapshot {
+            enabled: self.enabled.load(Ordering::Relaxed),
+            methods,
+        }
+    }
+}
+
+/// Awaits a provider RPC call, recording it in the process-wide metrics.
+pub(crate) async fn instrumented<T, E>(
+    method: RpcMethod,
+    call: impl IntoFuture<Output = Result<T, E>>,
+) -> Result<T, E> {
+    RpcMetrics::global().instrument(method, call).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::providers::{Provider, ProviderBuilder, mock::Asserter};
+    use alloy::rpc::types::Filter;
+
+    #[tokio::test]
+    async fn test_counters_increment_for_instrumented_calls() {
+        let asserter = Asserter::new();
+        asserter.push_success(&100_u64);
+        asserter.push_success(&101_u64);
+        asserter.push_success(&Vec::<serde_json::Value>::new());
+        asserter.push_failure_msg("query returned more than 10000 results");
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let metrics = RpcMetrics::default();
+
+        // Nothing is recorded until enabled
+        metrics
+            .instrument(RpcMethod::GetBlockNumber, provider.get_block_number())
+            .await
+            .unwrap();
+        assert_eq!(
+            metrics.snapshot().methods[&RpcMethod::GetBlockNumber].calls,
+            0
+        );
+
+        metrics.enable();
+        metrics
+            .instrument(RpcMethod::GetBlockNumber, provider.get_block_number())
+            .await
+            .unwrap();
+
+        let filter = Filter::new().from_block(1).to_block(2);
+        metrics
+            .instrument(RpcMethod::GetLogs, provider.get_logs(&filter))
+            .await
+            .unwrap();
+        metrics
+            .instrument(RpcMethod::GetLogs, provider.get_logs(&filter))
+            .await
+            .unwrap_err();
+
+        let snapshot = metrics.snapshot();
+        assert!(snapshot.enabled);
+
+        let block_number = &snapshot.methods[&RpcMethod::GetBlockNumber];
+        assert_eq!((block_number.calls, block_number.errors), (1, 0));
+
+        let get_logs = &snapshot.methods[&RpcMethod::GetLogs];
+        assert_eq!((get_logs.calls, get_logs.errors), (2, 1));
+        assert_eq!(
+            get_logs
+                .latency_buckets
+                .iter()
+                .map(|bucket| bucket.count)
+                .sum::<u64>(),
+            2
+        );
+        assert_eq!(get_logs.latency_buckets.len(), LATENCY_BUCKETS_MS.len() + 1);
+        assert_eq!(get_logs.latency_buckets.last().unwrap().le_ms, None);
+
+        assert_eq!(snapshot.methods[&RpcMethod::Symbol].calls, 0);
+    }
+}
@@ -0,0 +1,166 @@
+//! The single reason the bot process is shutting down.
+//!
+//! `launch` waits on the shutdown signal, the HTTP server and the bot task,
+//! and whichever finishes first decides the reason. It is logged once and
+//! mapped to the process exit code so orchestrators can tell a requested stop
+//! from a failure worth restarting or alerting on.
+//!
+//! A shutdown signal does not abort the bot outright: it is first drained, so
+//! that an order placed with the broker is recorded before the process exits.
+
+use std::fmt::{self, Display};
+use std::future::Future;
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Time the bot gets on top of the drain timeout to stop its other tasks.
+const BOT_STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// Exit code for a task that failed with an error.
+const FAILURE_EXIT_CODE: u8 = 1;
+/// Exit code for a task that panicked, matching Rust's own panic exit code.
+const PANIC_EXIT_CODE: u8 = 101;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// Ctrl-C (SIGINT) was received
+    Signal,
+    /// The HTTP server stopped without an error
+    ServerStopped,
+    ServerFailed(String),
+    ServerPanicked(String),
+    /// The bot ran to completion, e.g. a session without market hours ended
+    BotCompleted,
+    BotFailed(String),
+    BotPanicked(String),
+}
+
+impl ShutdownReason {
+    pub const fn is_failure(&self) -> bool {
+        self.exit_status() != 0
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(self.exit_status())
+    }
+
+    /// Process exit status, 0 for a requested or clean stop.
+    pub const fn exit_status(&self) -> u8 {
+        match self {
+            Self::Signal | Self::ServerStopped | Self::BotCompleted => 0,
+            Self::ServerFailed(_) | Self::BotFailed(_) => FAILURE_EXIT_CODE,
+            Self::ServerPanicked(_) | Self::BotPanicked(_) => PANIC_EXIT_CODE,
+        }
+    }
+}
+
+impl Display for ShutdownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signal => write!(f, "shutdown signal received"),
+            Self::ServerStopped => write!(f, "server stopped"),
+            Self::ServerFailed(e) => write!(f, "server failed: {e}"),
+            Self::ServerPanicked(e) => write!(f, "server task panicked: {e}"),
+            Self::BotCompleted => write!(f, "bot completed"),
+            Self::BotFailed(e) => write!(f, "bot failed: {e}"),
+            Self::BotPanicked(e) => write!(f, "bot task panicked: {e}"),
+        }
+    }
+}
+
+/// Waits for the first of `shutdown_signal`, `server_task` and `bot_task` to
+/// finish and returns why.
+pub(crate) async fn wait_for_shutdown<S, E>(
+    shutdown_signal: impl Future<Output = std::io::Result<()>>,
+    server_task: JoinHandle<Result<S, E>>,
+    bot_task: &mut JoinHandle<anyhow::Result<()>>,
+) -> ShutdownReason
+where
+    E: Display,
+{
+    tokio::select! {
+        _ = shutdown_signal => ShutdownReason::Signal,
+
+        result = server_task => match result {
+            Ok(Ok(_)) => ShutdownReason::ServerStopped,
+            Ok(Err(e)) => ShutdownReason::ServerFailed(e.to_string()),
+            Err(e) => ShutdownReason::ServerPanicked(e.to_string()),
+        },
+
+        result = bot_task => match result {
+            Ok(Ok(())) => ShutdownReason::BotCompleted,
+            Ok(Err(e)) => ShutdownReason::BotFailed(e.to_string()),
+            Err(e) => ShutdownReason::BotPanicked(e.to_string()),
+        },
+    }
+}
+
+/// Cancels `shutdown` so the bot drains its in-flight executions, waiting up
+/// to `drain_timeout` (plus a short grace) for it to stop before aborting it.
+pub(crate) async fn drain_bot(
+    shutdown: &CancellationToken,
+    mut bot_task: JoinHandle<anyhow::Result<()>>,
+    drain_timeout: Duration,
+) {
+    info!("Draining in-flight executions before shutting down");
+    shutdown.cancel();
+
+    match tokio::time::timeout(drain_timeout + BOT_STOP_GRACE, &mut bot_task).await {
+        Ok(Ok(Ok(()))) => info!("Bot drained"),
+        Ok(Ok(Err(e))) => warn!("Bot failed while draining: {e}"),
+        Ok(Err(e)) => warn!("Bot task panicked while draining: {e}"),
+        Err(_) => {
+            warn!("Bot still running after {drain_timeout:?}, aborting it");
+            bot_task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::{pending, ready};
+
+    fn pending_bot() -> JoinHandle<anyhow::Result<()>> {
+        tokio::spawn(pending())
+    }
+
+    #[tokio::test]
+    async fn test_ctrl_c_is_a_clean_shutdown() {
+        let server = tokio::spawn(pending::<Result<(), String>>());
+
+        let reason = wait_for_shutdown(ready(Ok(())), server, &mut pending_bot()).await;
+
+        assert_eq!(reason, ShutdownReason::Signal);
+        assert!(!reason.is_failure());
+        assert_eq!(reason.exit_status(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_server_failure_exits_nonzero() {
+        let server = tokio::spawn(async { Err::<(), _>("address in use".to_string()) });
+
+        let reason = wait_for_shutdown(pending(), server, &mut pending_bot()).await;
+
+        assert_eq!(
+            reason,
+            ShutdownReason::ServerFailed("address in use".to_string())
+        );
+        assert!(reason.is_failure());
+        assert_eq!(reason.exit_status(), FAILURE_EXIT_CODE);
+    }
+
+    #[tokio::test]
+    async fn test_bot_panic_exits_with_panic_code() {
+        let server = tokio::spawn(pending::<Result<(), String>>());
+        let mut bot: JoinHandle<anyhow::Result<()>> = tokio::spawn(async { panic!("boom") });
+
+        let reason = wait_for_shutdown(pending(), server, &mut bot).await;
+
+        assert!(matches!(reason, ShutdownReason::BotPanicked(_)));
+        assert_eq!(reason.exit_status(), PANIC_EXIT_CODE);
+    }
+}
@@ -0,0 +1,30 @@
+//! Warm standby for running a second instance ready to take over trading.
+//!
+//! A standby instance ingests, converts and accumulates every event like the
+//! active one, but creates no executions, so it never trades. Once promoted
+//! through `POST /admin/promote`, which only accepts requests from the local
+//! machine, it executes whatever has accumulated on the next position check
+//! and trades normally from then on. Promotion is one-way for the lifetime of
+//! the process.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Shared standby flag; clones observe the same promotion.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Standby(Arc<AtomicBool>);
+
+impl Standby {
+    pub(crate) fn new(standby: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(standby)))
+    }
+
+    pub(crate) fn is_standby(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Enables trading, returning whether the instance was in standby.
+    pub(crate) fn promote(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,214 @@
+//! In-process counters for quick sanity checks without a metrics backend.
+//!
+//! A single [`Stats`] instance is shared between the bot tasks and the HTTP
+//! server. Counters only ever increase for the lifetime of the process; the
+//! open executions gauge holds the count last seen by the execution cap check.
+//! The DEX stream liveness fields back `GET /health` rather than `GET /stats`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    events_received: AtomicU64,
+    events_processed: AtomicU64,
+    events_filtered: AtomicU64,
+    executions_placed: AtomicU64,
+    fills: AtomicU64,
+    failures: AtomicU64,
+    open_executions: AtomicU64,
+    /// DEX event receivers currently subscribed; a receiver from the last
+    /// market session can briefly overlap the next session's
+    dex_stream_receivers: AtomicU64,
+    /// Unix milliseconds of the last DEX event or heartbeat, 0 when none yet
+    last_dex_activity_ms: AtomicI64,
+}
+
+/// Point-in-time copy of the counters, served by `GET /stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct StatsSnapshot {
+    pub(crate) events_received: u64,
+    pub(crate) events_processed: u64,
+    pub(crate) events_filtered: u64,
+    pub(crate) executions_placed: u64,
+    pub(crate) fills: u64,
+    pub(crate) failures: u64,
+    pub(crate) open_executions: u64,
+}
+
+impl Stats {
+    /// A live DEX event was received from the websocket subscription.
+    pub(crate) fn record_event_received(&self) {
+        self.events_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A queued event was converted to a trade and committed.
+    pub(crate) fn record_event_processed(&self) {
+        self.events_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A queued event was skipped without a trade, e.g. because it did not
+    /// involve our order owner or its symbol or Pyth price was filtered out.
+    pub(crate) fn record_event_filtered(&self) {
+        self.events_filtered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// An offchain order was accepted by the broker.
+    pub(crate) fn record_execution_placed(&self) {
+        self.executions_placed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The order poller observed a filled offchain order.
+    pub(crate) fn record_fill(&self) {
+        self.fills.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// An offchain order was rejected at placement or reported failed.
+    pub(crate) fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Executions currently open at the broker, see
+    /// [`crate::offchain::open_executions`].
+    pub(crate) fn record_open_executions(&self, count: u64) {
+        self.open_executions.store(count, Ordering::Relaxed);
+    }
+
+    /// A DEX event receiver subscribed to its websocket streams.
+    pub(crate) fn record_dex_streams_opened(&self) {
+        self.dex_stream_receivers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A DEX event receiver stopped after its websocket streams ended.
+    pub(crate) fn record_dex_streams_closed(&self) {
+        // Never below zero, even if a close is recorded without an open
+        let _ = self.dex_stream_receivers.fetch_update(
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |receivers| receivers.checked_sub(1),
+        );
+    }
+
+    /// A DEX event arrived or a heartbeat over the websocket succeeded.
+    pub(crate) fn record_dex_activity(&self) {
+        self.last_dex_activity_ms
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// Whether the DEX streams are connected and active within `stale_after`.
+    pub(crate) fn dex_stream_status(&self, stale_after: Duration) -> DexStreamStatus {
+        let connected = self.dex_stream_receivers.load(Ordering::Relaxed) > 0;
+        let last_activity = match self.last_dex_activity_ms.load(Ordering::Relaxed) {
+            0 => None,
+            millis => DateTime::from_timestamp_millis(millis),
+        };
+        let stale = last_activity.is_none_or(|at| {
+            chrono::Duration::from_std(stale_after)
+                .is_ok_and(|stale_after| Utc::now() - at > stale_after)
+        });
+
+        DexStreamStatus {
+            connected,
+            last_activity,
+            stale,
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            events_received: self.events_received.load(Ordering::Relaxed),
+            events_processed: self.events_processed.load(Ordering::Relaxed),
+            events_filtered: self.events_filtered.load(Ordering::Relaxed),
+            executions_placed: self.executions_placed.load(Ordering::Relaxed),
+            fills: self.fills.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            open_executions: self.open_executions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Liveness of the websocket DEX event streams, served by `GET /health`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DexStreamStatus {
+    pub(crate) connected: bool,
+    pub(crate) last_activity: Option<DateTime<Utc>>,
+    /// No event or heartbeat within the staleness window
+    pub(crate) stale: bool,
+}
+
+impl fmt::Display for StatsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "events_received={}, events_processed={}, events_filtered={}, executions_placed={}, fills={}, failures={}, open_executions={}",
+            self.events_received,
+            self.events_processed,
+            self.events_filtered,
+            self.executions_placed,
+            self.fills,
+            self.failures,
+            self.open_executions
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_reflects_recorded_counters() {
+        let stats = Stats::default();
+
+        stats.record_event_received();
+        stats.record_event_received();
+        stats.record_event_filtered();
+        stats.record_failure();
+        stats.record_open_executions(3);
+
+        assert_eq!(
+            stats.snapshot(),
+            StatsSnapshot {
+                events_received: 2,
+                events_processed: 0,
+                events_filtered: 1,
+                executions_placed: 0,
+                fills: 0,
+                failures: 1,
+                open_executions: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_dex_stream_status_tracks_connection_and_staleness() {
+        let stats = Stats::default();
+        let stale_after = Duration::from_secs(60);
+
+        let stream = stats.dex_stream_status(stale_after);
+        assert!(!stream.connected);
+        assert_eq!(stream.last_activity, None);
+        assert!(stream.stale);
+
+        stats.record_dex_streams_opened();
+        stats.record_dex_activity();
+        let stream = stats.dex_stream_status(stale_after);
+        assert!(stream.connected);
+        assert!(stream.last_activity.is_some());
+        assert!(!stream.stale);
+
+        stats.last_dex_activity_ms.store(
+            (Utc::now() - chrono::Duration::seconds(61)).timestamp_millis(),
+            Ordering::Relaxed,
+        );
+        assert!(stats.dex_stream_status(stale_after).stale);
+
+        stats.record_dex_streams_closed();
+        assert!(!stats.dex_stream_status(stale_after).connected);
+        stats.record_dex_streams_closed();
+        assert!(!stats.dex_stream_status(stale_after).connected);
+    }
+}
@@ -1,30 +1,202 @@
-use alloy::{primitives::Address, providers::Provider};
+use alloy::{contract::Error as ContractError, primitives::Address, providers::Provider};
 use backon::{ExponentialBuilder, Retryable};
+use rust_decimal::Decimal;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    num::NonZeroUsize,
+    str::FromStr,
     sync::{Arc, RwLock},
 };
+use tracing::warn;
+
+use st0x_broker::Symbol;
 
 use crate::bindings::{IERC20::IERC20Instance, IOrderBookV4::IO};
 use crate::error::OnChainError;
+use crate::lru::LruMap;
+use crate::onchain::io::SymbolConvention;
+use crate::rpc_metrics::{RpcMethod, instrumented};
+
+/// Operator-provided symbol for a token whose `symbol()` call reverts, parsed
+/// from `ADDRESS=SYMBOL`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TokenSymbolAlias {
+    pub(crate) token: Address,
+    pub(crate) symbol: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TokenSymbolAliasParseError {
+    #[error("Expected ADDRESS=SYMBOL but got '{0}'")]
+    MissingSeparator(String),
+    #[error("Invalid token address '{0}'")]
+    InvalidAddress(String),
+    #[error("Alias for token {0} is empty")]
+    EmptySymbol(Address),
+}
+
+impl FromStr for TokenSymbolAlias {
+    type Err = TokenSymbolAliasParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (token, symbol) = value
+            .split_once('=')
+            .ok_or_else(|| TokenSymbolAliasParseError::MissingSeparator(value.to_string()))?;
+
+        let token = Address::from_str(token.trim())
+            .map_err(|_| TokenSymbolAliasParseError::InvalidAddress(token.to_string()))?;
+
+        let symbol = symbol.trim();
+        if symbol.is_empty() {
+            return Err(TokenSymbolAliasParseError::EmptySymbol(token));
+        }
+
+        Ok(Self {
+            token,
+            symbol: symbol.to_string(),
+        })
+    }
+}
+
+/// Fallback used when a token's `symbol()` call reverts: the configured alias
+/// for the token, or otherwise its checksummed address.
+///
+/// Trades involving such a token cannot be mapped to a broker ticker, so the
+/// cache remembers which symbols came from here and trade conversion flags
+/// them for manual review instead of hedging.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SymbolFallback {
+    aliases: BTreeMap<Address, String>,
+}
+
+impl SymbolFallback {
+    pub(crate) fn new(aliases: impl IntoIterator<Item = TokenSymbolAlias>) -> Self {
+        Self {
+            aliases: aliases
+                .into_iter()
+                .map(|alias| (alias.token, alias.symbol))
+                .collect(),
+        }
+    }
+
+    fn symbol_for(&self, token: Address) -> String {
+        self.aliases
+            .get(&token)
+            .cloned()
+            .unwrap_or_else(|| token.to_checksum(None))
+    }
+}
+
+/// Parses a base symbol for the `--invert-direction` flag.
+pub(crate) fn parse_inverted_symbol(value: &str) -> Result<Symbol, String> {
+    Symbol::new(value.trim().to_uppercase())
+        .map_err(|e| format!("Invalid symbol for direction inversion: {e}"))
+}
+
+fn is_revert(error: &ContractError) -> bool {
+    match error {
+        ContractError::TransportError(e) => e
+            .as_error_resp()
+            .is_some_and(|resp| resp.message.contains("revert")),
+        _ => false,
+    }
+}
 
 #[derive(Debug, Default, Clone)]
 pub(crate) struct SymbolCache {
-    map: Arc<RwLock<BTreeMap<Address, String>>>,
+    map: Arc<RwLock<LruMap<Address, String>>>,
+    fallback: Option<SymbolFallback>,
+    fallback_tokens: Arc<RwLock<BTreeSet<Address>>>,
+    inverted_directions: Arc<HashSet<Symbol>>,
+    convention: Arc<SymbolConvention>,
+    max_pyth_divergence_pct: Option<Decimal>,
 }
 
 impl SymbolCache {
+    pub(crate) fn new(fallback: Option<SymbolFallback>) -> Self {
+        Self {
+            fallback,
+            ..Self::default()
+        }
+    }
+
+    /// Bounds the number of cached token symbols, evicting the least recently
+    /// used token beyond `capacity`. `None` keeps the cache unbounded.
+    #[must_use]
+    pub(crate) fn with_capacity(mut self, capacity: Option<NonZeroUsize>) -> Self {
+        self.map = Arc::new(RwLock::new(LruMap::new(capacity)));
+        self
+    }
+
+    /// Marks base symbols, e.g. inverse or short products, whose onchain fills
+    /// hedge in the opposite direction to the one derived from the USDC leg.
+    #[must_use]
+    pub(crate) fn with_inverted_directions(
+        mut self,
+        symbols: impl IntoIterator<Item = Symbol>,
+    ) -> Self {
+        self.inverted_directions = Arc::new(symbols.into_iter().collect());
+        self
+    }
+
+    pub(crate) fn is_direction_inverted(&self, symbol: &Symbol) -> bool {
+        self.inverted_directions.contains(symbol)
+    }
+
+    /// Sets the tokenized suffix and stablecoins that tell the equity and
+    /// cash legs of a trade apart.
+    #[must_use]
+    pub(crate) fn with_symbol_convention(mut self, convention: SymbolConvention) -> Self {
+        self.convention = Arc::new(convention);
+        self
+    }
+
+    pub(crate) fn symbol_convention(&self) -> &SymbolConvention {
+        &self.convention
+    }
+
+    /// Rejects trades whose onchain price per share differs from the Pyth
+    /// price by more than `max_pct` percent of it. `None` disables the check.
+    #[must_use]
+    pub(crate) const fn with_max_pyth_divergence(mut self, max_pct: Option<Decimal>) -> Self {
+        self.max_pyth_divergence_pct = max_pct;
+        self
+    }
+
+    pub(crate) const fn max_pyth_divergence_pct(&self) -> Option<Decimal> {
+        self.max_pyth_divergence_pct
+    }
+
+    /// Returns the fallback symbol used for `token` if its `symbol()` call
+    /// reverted, or `None` if the symbol was read from the token.
+    pub(crate) fn fallback_symbol(&self, token: Address) -> Option<String> {
+        let is_fallback = match self.fallback_tokens.read() {
+            Ok(guard) => guard.contains(&token),
+            Err(poison) => poison.into_inner().contains(&token),
+        };
+
+        if !is_fallback {
+            return None;
+        }
+
+        match self.map.write() {
+            Ok(mut guard) => guard.get(&token).cloned(),
+            Err(poison) => poison.into_inner().get(&token).cloned(),
+        }
+    }
+
     pub async fn get_io_symbol<P: Provider>(
         &self,
         provider: P,
         io: &IO,
     ) -> Result<String, OnChainError> {
+        // Lookups update recency, so even a cache hit takes the write lock
         let maybe_symbol = {
-            let read_guard = match self.map.read() {
+            let mut guard = match self.map.write() {
                 Ok(guard) => guard,
                 Err(poison) => poison.into_inner(),
             };
-            read_guard.get(&io.token).cloned()
+            guard.get(&io.token).cloned()
         };
 
         if let Some(symbol) = maybe_symbol {
@@ -34,14 +206,34 @@ impl SymbolCache {
         const SYMBOL_FETCH_MAX_RETRIES: usize = 3;
 
         let erc20 = IERC20Instance::new(io.token, provider);
-        let symbol = (|| async { erc20.symbol().call().await })
+        let fetched = (|| async { instrumented(RpcMethod::Symbol, erc20.symbol().call()).await })
             .retry(ExponentialBuilder::new().with_max_times(SYMBOL_FETCH_MAX_RETRIES))
-            .await?;
+            .when(|e| !is_revert(e))
+            .await;
+
+        let symbol = match (fetched, &self.fallback) {
+            (Ok(symbol), _) => symbol,
+            (Err(e), Some(fallback)) if is_revert(&e) => {
+                let symbol = fallback.symbol_for(io.token);
+                warn!(
+                    "symbol() reverted for token {}, falling back to '{symbol}': {e}",
+                    io.token
+                );
+
+                match self.fallback_tokens.write() {
+                    Ok(mut guard) => guard.insert(io.token),
+                    Err(poison) => poison.into_inner().insert(io.token),
+                };
+
+                symbol
+            }
+            (Err(e), _) => return Err(e.into()),
+        };
 
         match self.map.write() {
             Ok(mut guard) => guard.insert(io.token, symbol.clone()),
             Err(poison) => poison.into_inner().insert(io.token, symbol.clone()),
-        };
+        }
 
         Ok(symbol)
     }
@@ -50,8 +242,10 @@ impl SymbolCache {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bindings::IERC20::symbolCall;
     use alloy::primitives::{U256, address};
     use alloy::providers::{ProviderBuilder, mock::Asserter};
+    use alloy::sol_types::SolCall;
 
     #[tokio::test]
     async fn test_symbol_cache_hit() {
@@ -76,6 +270,49 @@ mod tests {
         assert_eq!(result, "TEST");
     }
 
+    #[tokio::test]
+    async fn test_symbol_cache_evicts_least_recently_used_beyond_capacity() {
+        let cache = SymbolCache::default().with_capacity(NonZeroUsize::new(2));
+        let io = |token| IO {
+            token,
+            decimals: 18,
+            vaultId: U256::from(0),
+        };
+        let first = address!("0x1111111111111111111111111111111111111111");
+        let second = address!("0x2222222222222222222222222222222222222222");
+        let third = address!("0x3333333333333333333333333333333333333333");
+
+        let asserter = Asserter::new();
+        for symbol in ["FIRST", "SECOND", "THIRD"] {
+            asserter.push_success(&<symbolCall as SolCall>::abi_encode_returns(
+                &symbol.to_string(),
+            ));
+        }
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        cache.get_io_symbol(&provider, &io(first)).await.unwrap();
+        cache.get_io_symbol(&provider, &io(second)).await.unwrap();
+
+        // A cache hit makes `first` the most recently used entry
+        assert_eq!(
+            cache.get_io_symbol(&provider, &io(first)).await.unwrap(),
+            "FIRST"
+        );
+
+        assert_eq!(
+            cache.get_io_symbol(&provider, &io(third)).await.unwrap(),
+            "THIRD"
+        );
+
+        let (len, cached) = {
+            let map = cache.map.read().expect("Test cache lock poisoned");
+            let cached = [first, second, third].map(|token| map.contains_key(&token));
+            (map.len(), cached)
+        };
+        assert_eq!(len, 2);
+        assert_eq!(cached, [true, false, true]);
+    }
+
     #[tokio::test]
     async fn test_symbol_cache_miss_rpc_failure() {
         let cache = SymbolCache::default();
@@ -96,4 +333,91 @@ mod tests {
             OnChainError::Alloy(crate::error::AlloyError::GetSymbol(_))
         ));
     }
+
+    #[tokio::test]
+    async fn test_symbol_revert_falls_back_to_address_or_alias() {
+        let unaliased = address!("0x1234567890123456789012345678901234567890");
+        let aliased = address!("0xabcdefabcdefabcdefabcdefabcdefabcdefabcd");
+        let cache = SymbolCache::new(Some(SymbolFallback::new([TokenSymbolAlias {
+            token: aliased,
+            symbol: "WEIRD".to_string(),
+        }])));
+
+        let asserter = Asserter::new();
+        asserter.push_failure_msg("execution reverted");
+        asserter.push_failure_msg("execution reverted");
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let io = |token| IO {
+            token,
+            decimals: 18,
+            vaultId: U256::from(0),
+        };
+
+        let symbol = cache
+            .get_io_symbol(&provider, &io(unaliased))
+            .await
+            .unwrap();
+        assert_eq!(symbol, unaliased.to_checksum(None));
+        assert_eq!(cache.fallback_symbol(unaliased), Some(symbol));
+
+        let symbol = cache.get_io_symbol(&provider, &io(aliased)).await.unwrap();
+        assert_eq!(symbol, "WEIRD");
+        assert_eq!(cache.fallback_symbol(aliased), Some(symbol));
+
+        // Cached fallbacks are served without another RPC call
+        assert_eq!(
+            cache.get_io_symbol(&provider, &io(aliased)).await.unwrap(),
+            "WEIRD"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_symbol_revert_without_fallback_fails() {
+        let cache = SymbolCache::default();
+        let io = IO {
+            token: address!("0x1234567890123456789012345678901234567890"),
+            decimals: 18,
+            vaultId: U256::from(0),
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_failure_msg("execution reverted");
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        assert!(matches!(
+            cache.get_io_symbol(provider, &io).await.unwrap_err(),
+            OnChainError::Alloy(crate::error::AlloyError::GetSymbol(_))
+        ));
+        assert_eq!(cache.fallback_symbol(io.token), None);
+    }
+
+    #[test]
+    fn test_parse_token_symbol_alias() {
+        let alias: TokenSymbolAlias = "0x1234567890123456789012345678901234567890=WEIRD"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            alias,
+            TokenSymbolAlias {
+                token: address!("0x1234567890123456789012345678901234567890"),
+                symbol: "WEIRD".to_string(),
+            }
+        );
+
+        assert!(matches!(
+            "WEIRD".parse::<TokenSymbolAlias>().unwrap_err(),
+            TokenSymbolAliasParseError::MissingSeparator(_)
+        ));
+        assert!(matches!(
+            "0x12=WEIRD".parse::<TokenSymbolAlias>().unwrap_err(),
+            TokenSymbolAliasParseError::InvalidAddress(_)
+        ));
+        assert!(matches!(
+            "0x1234567890123456789012345678901234567890= "
+                .parse::<TokenSymbolAlias>()
+                .unwrap_err(),
+            TokenSymbolAliasParseError::EmptySymbol(_)
+        ));
+    }
 }
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use st0x_broker::Symbol;
+
+/// Base symbols the bot trades. Trades of a denylisted symbol are skipped, and
+/// when an allowlist is set so are trades of every symbol not on it. A symbol
+/// on both lists is skipped.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SymbolFilter {
+    allowlist: Option<HashSet<Symbol>>,
+    denylist: HashSet<Symbol>,
+}
+
+impl SymbolFilter {
+    /// An empty `allowlist` allows every symbol not on `denylist`.
+    pub(crate) fn new(
+        allowlist: impl IntoIterator<Item = Symbol>,
+        denylist: impl IntoIterator<Item = Symbol>,
+    ) -> Self {
+        let allowlist: HashSet<_> = allowlist.into_iter().collect();
+
+        Self {
+            allowlist: (!allowlist.is_empty()).then_some(allowlist),
+            denylist: denylist.into_iter().collect(),
+        }
+    }
+
+    /// Why trades of `symbol` are skipped, or `None` if it is traded.
+    pub(crate) fn skip_reason(&self, symbol: &Symbol) -> Option<&'static str> {
+        if self.denylist.contains(symbol) {
+            return Some("symbol is denylisted");
+        }
+
+        if self
+            .allowlist
+            .as_ref()
+            .is_some_and(|allowlist| !allowlist.contains(symbol))
+        {
+            return Some("symbol is not allowlisted");
+        }
+
+        None
+    }
+}
+
+pub(crate) fn parse_filtered_symbol(value: &str) -> Result<Symbol, String> {
+    Symbol::new(value.trim().to_uppercase())
+        .map_err(|e| format!("Invalid symbol for the symbol allowlist or denylist: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(symbols: &[&str]) -> Vec<Symbol> {
+        symbols.iter().map(|s| Symbol::new(*s).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_denylist_skips_only_denied_symbols() {
+        let filter = SymbolFilter::new(vec![], symbols(&["GME"]));
+
+        assert_eq!(
+            filter.skip_reason(&Symbol::new("GME").unwrap()),
+            Some("symbol is denylisted")
+        );
+        assert_eq!(filter.skip_reason(&Symbol::new("AAPL").unwrap()), None);
+    }
+
+    #[test]
+    fn test_allowlist_skips_symbols_not_on_it() {
+        let filter = SymbolFilter::new(symbols(&["AAPL", "MSFT"]), vec![]);
+
+        assert_eq!(filter.skip_reason(&Symbol::new("AAPL").unwrap()), None);
+        assert_eq!(filter.skip_reason(&Symbol::new("MSFT").unwrap()), None);
+        assert_eq!(
+            filter.skip_reason(&Symbol::new("TSLA").unwrap()),
+            Some("symbol is not allowlisted")
+        );
+    }
+
+    #[test]
+    fn test_denylist_wins_over_allowlist() {
+        let filter = SymbolFilter::new(symbols(&["AAPL", "GME"]), symbols(&["GME"]));
+
+        assert_eq!(filter.skip_reason(&Symbol::new("AAPL").unwrap()), None);
+        assert_eq!(
+            filter.skip_reason(&Symbol::new("GME").unwrap()),
+            Some("symbol is denylisted")
+        );
+        assert_eq!(
+            filter.skip_reason(&Symbol::new("TSLA").unwrap()),
+            Some("symbol is not allowlisted")
+        );
+    }
+
+    #[test]
+    fn test_default_trades_every_symbol() {
+        assert_eq!(
+            SymbolFilter::default().skip_reason(&Symbol::new("AAPL").unwrap()),
+            None
+        );
+    }
+}
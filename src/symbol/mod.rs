@@ -1,4 +1,5 @@
 pub(crate) mod cache;
+pub(crate) mod filter;
 pub(crate) mod lock;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
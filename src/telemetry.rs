@@ -70,11 +70,14 @@ use thiserror::Error;
 use tracing_subscriber::Registry;
 use tracing_subscriber::layer::{Layer, SubscriberExt};
 
+use crate::env::LogFormat;
+
 #[derive(Debug, Clone)]
 pub struct HyperDxConfig {
     pub(crate) api_key: String,
     pub(crate) service_name: String,
     pub(crate) log_level: tracing::Level,
+    pub(crate) log_format: LogFormat,
 }
 
 impl HyperDxConfig {
@@ -134,7 +137,14 @@ impl HyperDxConfig {
         let telemetry_filter = tracing_subscriber::EnvFilter::try_from_default_env()
             .unwrap_or_else(|_| default_filter.into());
 
-        let fmt_layer = tracing_subscriber::fmt::layer().with_filter(fmt_filter);
+        let fmt_layer = match self.log_format {
+            LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+            LogFormat::Json => tracing_subscriber::fmt::layer()
+                .json()
+                .flatten_event(true)
+                .boxed(),
+        }
+        .with_filter(fmt_filter);
         let telemetry_layer = telemetry_layer.with_filter(telemetry_filter);
 
         let subscriber = Registry::default().with(fmt_layer).with(telemetry_layer);
@@ -1,14 +1,22 @@
-use crate::bindings::IOrderBookV4::{EvaluableV3, IO, OrderV3};
+use crate::bindings::IOrderBookV4::{
+    AfterClear, ClearConfig, ClearStateChange, ClearV2, EvaluableV3, IO, OrderV3,
+};
 use crate::offchain::execution::OffchainExecution;
 use crate::onchain::OnchainTrade;
 use crate::onchain::io::TokenizedEquitySymbol;
-use alloy::primitives::{LogData, U256, address, bytes, fixed_bytes};
+use alloy::hex;
+use alloy::primitives::{IntoLogData, LogData, U256, address, bytes, fixed_bytes};
 use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
 use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::json;
 use sqlx::SqlitePool;
 use st0x_broker::OrderState;
 use st0x_broker::schwab::{SchwabAuthEnv, SchwabTokens};
 use st0x_broker::{Direction, Shares, SupportedBroker, Symbol};
+use std::str::FromStr;
 
 /// Returns a test `OrderV3` instance that is shared across multiple
 /// unit-tests. The exact values are not important – only that the
@@ -117,10 +125,11 @@ impl OnchainTradeBuilder {
                     "0x1111111111111111111111111111111111111111111111111111111111111111"
                 ),
                 log_index: 1,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: "AAPL0x".parse::<TokenizedEquitySymbol>().unwrap(),
-                amount: 1.0,
+                amount: dec!(1.0),
                 direction: Direction::Buy,
-                price_usdc: 150.0,
+                price_usdc: dec!(150.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: None,
@@ -140,17 +149,29 @@ impl OnchainTradeBuilder {
     }
 
     #[must_use]
-    pub(crate) fn with_amount(mut self, amount: f64) -> Self {
+    pub(crate) fn with_amount(mut self, amount: Decimal) -> Self {
         self.trade.amount = amount;
         self
     }
 
     #[must_use]
-    pub(crate) fn with_price(mut self, price: f64) -> Self {
+    pub(crate) fn with_price(mut self, price: Decimal) -> Self {
         self.trade.price_usdc = price;
         self
     }
 
+    #[must_use]
+    pub(crate) fn with_pyth_price(mut self, price: f64) -> Self {
+        self.trade.pyth_price = Some(price);
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn with_pyth_confidence(mut self, confidence: f64) -> Self {
+        self.trade.pyth_confidence = Some(confidence);
+        self
+    }
+
     #[must_use]
     pub(crate) fn with_tx_hash(mut self, hash: alloy::primitives::B256) -> Self {
         self.trade.tx_hash = hash;
@@ -194,7 +215,103 @@ impl OffchainExecutionBuilder {
         }
     }
 
+    #[must_use]
+    pub(crate) fn with_symbol(mut self, symbol: &str) -> Self {
+        self.execution.symbol = Symbol::new(symbol).unwrap();
+        self
+    }
+
     pub(crate) fn build(self) -> OffchainExecution {
         self.execution
     }
 }
+
+/// RPC fixtures for a transaction containing a single `ClearV2` between two
+/// copies of [`get_test_order`], plus the matching `AfterClear` log.
+pub(crate) struct MockBlockchainData {
+    pub(crate) order_owner: alloy::primitives::Address,
+    pub(crate) receipt_json: serde_json::Value,
+    pub(crate) after_clear_log: alloy::rpc::types::Log,
+}
+
+pub(crate) fn create_mock_blockchain_data(
+    orderbook: alloy::primitives::Address,
+    tx_hash: alloy::primitives::B256,
+    alice_output_shares: &str, // e.g., "9000000000000000000" for 9 shares
+    bob_output_usdc: u64,      // e.g., 100_000_000 for 100 USDC
+) -> MockBlockchainData {
+    let order = get_test_order();
+    let order_owner = order.owner;
+
+    let clear_event = ClearV2 {
+        sender: address!("0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef"),
+        alice: order.clone(),
+        bob: order,
+        clearConfig: ClearConfig {
+            aliceInputIOIndex: U256::from(0),
+            aliceOutputIOIndex: U256::from(1),
+            bobInputIOIndex: U256::from(1),
+            bobOutputIOIndex: U256::from(0),
+            aliceBountyVaultId: U256::ZERO,
+            bobBountyVaultId: U256::ZERO,
+        },
+    };
+
+    let receipt_json = json!({
+        "transactionHash": tx_hash,
+        "transactionIndex": "0x0",
+        "blockHash": "0x1111111111111111111111111111111111111111111111111111111111111111",
+        "blockNumber": "0x64",
+        "from": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        "to": "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+        "contractAddress": null,
+        "gasUsed": "0x5208",
+        "cumulativeGasUsed": "0xf4240",
+        "effectiveGasPrice": "0x3b9aca00",
+        "status": "0x1",
+        "type": "0x2",
+        "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "logs": [{
+            "address": orderbook,
+            "topics": [ClearV2::SIGNATURE_HASH],
+            "data": format!("0x{}", hex::encode(clear_event.into_log_data().data)),
+            "blockNumber": "0x64",
+            "transactionHash": tx_hash,
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "removed": false
+        }]
+    });
+
+    let after_clear_event = AfterClear {
+        sender: address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+        clearStateChange: ClearStateChange {
+            aliceOutput: U256::from_str(alice_output_shares).unwrap(),
+            bobOutput: U256::from(bob_output_usdc),
+            aliceInput: U256::from(bob_output_usdc),
+            bobInput: U256::from_str(alice_output_shares).unwrap(),
+        },
+    };
+
+    let after_clear_log = alloy::rpc::types::Log {
+        inner: alloy::primitives::Log {
+            address: orderbook,
+            data: after_clear_event.into_log_data(),
+        },
+        block_hash: Some(fixed_bytes!(
+            "0x1111111111111111111111111111111111111111111111111111111111111111"
+        )),
+        block_number: Some(100),
+        block_timestamp: None,
+        transaction_hash: Some(tx_hash),
+        transaction_index: Some(0),
+        log_index: Some(1),
+        removed: false,
+    };
+
+    MockBlockchainData {
+        order_owner,
+        receipt_json,
+        after_clear_log,
+    }
+}
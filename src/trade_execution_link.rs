@@ -1,13 +1,13 @@
 use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
 use st0x_broker::{Direction, OrderState};
 
-#[cfg(test)]
 use crate::error::OnChainError;
+use crate::onchain::OnchainTrade;
+use crate::onchain::io::SymbolConvention;
 #[cfg(test)]
 use crate::onchain::io::TokenizedEquitySymbol;
-#[cfg(test)]
-use sqlx::SqlitePool;
-#[cfg(test)]
+use crate::onchain::trade::parse_stored_decimal;
 use st0x_broker::PersistenceError;
 #[cfg(test)]
 use st0x_broker::{OrderStatus, Shares, SupportedBroker, Symbol};
@@ -124,7 +124,6 @@ impl TradeExecutionLink {
     }
 
     /// Find all trades that contributed to a specific execution
-    #[cfg(test)]
     pub async fn find_trades_for_execution(
         pool: &SqlitePool,
         execution_id: i64,
@@ -132,16 +131,16 @@ impl TradeExecutionLink {
         let rows = sqlx::query!(
             r#"
             SELECT
-                tel.id,
+                tel.id AS "id!",
                 tel.trade_id,
                 tel.contributed_shares,
                 tel.created_at,
                 ot.tx_hash,
                 ot.log_index,
                 ot.symbol,
-                ot.amount,
+                CAST(ot.amount AS REAL) as "amount!: f64",
                 ot.direction,
-                ot.price_usdc
+                CAST(ot.price_usdc AS REAL) as "price_usdc!: f64"
             FROM trade_execution_links tel
             JOIN onchain_trades ot ON tel.trade_id = ot.id
             WHERE tel.execution_id = ?1
@@ -155,7 +154,7 @@ impl TradeExecutionLink {
         rows.into_iter()
             .map(|row| {
                 Ok(TradeContribution {
-                    link_id: row.id.unwrap(),
+                    link_id: row.id,
                     trade_id: row.trade_id,
                     contributed_shares: row.contributed_shares,
                     trade_tx_hash: row.tx_hash,
@@ -189,9 +188,9 @@ impl TradeExecutionLink {
                 ot.id as trade_id,
                 ot.tx_hash,
                 ot.log_index,
-                ot.amount as trade_amount,
+                CAST(ot.amount AS REAL) as "trade_amount!: f64",
                 ot.direction as trade_direction,
-                ot.price_usdc,
+                CAST(ot.price_usdc AS REAL) as "price_usdc!: f64",
                 ot.created_at as trade_created_at,
                 se.id as execution_id,
                 se.shares as execution_shares,
@@ -256,6 +255,105 @@ impl TradeExecutionLink {
     }
 }
 
+/// An onchain trade linked to an execution, with the shares it contributed.
+/// A trade split across several executions contributes only part of its
+/// amount to each.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LinkedTrade {
+    pub(crate) trade: OnchainTrade,
+    pub(crate) contributed_shares: f64,
+}
+
+/// The onchain trades that contributed to `execution_id`, in the order they
+/// were linked.
+pub(crate) async fn get_linked_trades(
+    pool: &SqlitePool,
+    convention: &SymbolConvention,
+    execution_id: i64,
+) -> Result<Vec<LinkedTrade>, OnChainError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            ot.id,
+            ot.tx_hash,
+            ot.log_index,
+            ot.orderbook,
+            ot.symbol,
+            ot.amount,
+            ot.direction,
+            ot.price_usdc,
+            ot.created_at,
+            ot.block_timestamp,
+            ot.gas_used,
+            ot.effective_gas_price,
+            ot.pyth_price,
+            ot.pyth_confidence,
+            ot.pyth_exponent,
+            ot.pyth_publish_time,
+            tel.contributed_shares
+        FROM trade_execution_links tel
+        JOIN onchain_trades ot ON tel.trade_id = ot.id
+        WHERE tel.execution_id = ?1
+        ORDER BY tel.created_at ASC, tel.id ASC
+        "#,
+        execution_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let invalid = |field: &str, value: &str| {
+        OnChainError::Persistence(PersistenceError::InvalidTradeStatus(format!(
+            "Invalid {field} of linked trade: {value}"
+        )))
+    };
+
+    rows.into_iter()
+        .map(|row| {
+            let trade = OnchainTrade {
+                id: Some(row.id),
+                tx_hash: row
+                    .tx_hash
+                    .parse()
+                    .map_err(|_| invalid("tx_hash", &row.tx_hash))?,
+                #[allow(clippy::cast_sign_loss)]
+                log_index: row.log_index as u64,
+                orderbook: row
+                    .orderbook
+                    .as_deref()
+                    .and_then(|orderbook| orderbook.parse().ok())
+                    .ok_or_else(|| {
+                        invalid("orderbook", row.orderbook.as_deref().unwrap_or("NULL"))
+                    })?,
+                symbol: convention.parse_tokenized(&row.symbol)?,
+                amount: parse_stored_decimal(&row.amount)
+                    .map_err(|_| invalid("amount", &row.amount))?,
+                direction: row.direction.parse()?,
+                price_usdc: parse_stored_decimal(&row.price_usdc)
+                    .map_err(|_| invalid("price_usdc", &row.price_usdc))?,
+                block_timestamp: row
+                    .block_timestamp
+                    .map(|naive_dt| DateTime::from_naive_utc_and_offset(naive_dt, Utc)),
+                created_at: row
+                    .created_at
+                    .map(|naive_dt| DateTime::from_naive_utc_and_offset(naive_dt, Utc)),
+                gas_used: row.gas_used.and_then(|g| u64::try_from(g).ok()),
+                effective_gas_price: row.effective_gas_price.and_then(|p| u128::try_from(p).ok()),
+                pyth_price: row.pyth_price,
+                pyth_confidence: row.pyth_confidence,
+                pyth_exponent: row.pyth_exponent.and_then(|exp| i32::try_from(exp).ok()),
+                pyth_publish_time: row
+                    .pyth_publish_time
+                    .map(|naive_dt| DateTime::from_naive_utc_and_offset(naive_dt, Utc)),
+            };
+
+            Ok(LinkedTrade {
+                trade,
+                contributed_shares: row.contributed_shares,
+            })
+        })
+        .collect()
+}
+
 /// Represents an execution that a trade contributed to, with execution details
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExecutionContribution {
@@ -313,11 +411,11 @@ pub struct AuditTrailEntry {
 mod tests {
     use super::*;
     use crate::offchain::execution::OffchainExecution;
-    use crate::onchain::OnchainTrade;
     use crate::test_utils::setup_test_db;
     use crate::tokenized_symbol;
     use alloy::primitives::fixed_bytes;
     use chrono::Utc;
+    use rust_decimal_macros::dec;
 
     #[tokio::test]
     async fn test_trade_execution_link_save_and_find() {
@@ -330,10 +428,11 @@ mod tests {
                 "0x1111111111111111111111111111111111111111111111111111111111111111"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 1.5,
+            amount: dec!(1.5),
             direction: Direction::Sell,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: None,
@@ -384,6 +483,87 @@ mod tests {
         assert!((trades[0].contributed_shares - 1.0).abs() < f64::EPSILON);
     }
 
+    #[tokio::test]
+    async fn test_get_linked_trades_returns_every_contributing_trade() {
+        let pool = setup_test_db().await;
+
+        let partial_fill = |tx_hash, amount, price_usdc| OnchainTrade {
+            id: None,
+            tx_hash,
+            log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
+            symbol: tokenized_symbol!("AAPL0x"),
+            amount,
+            direction: Direction::Sell,
+            price_usdc,
+            block_timestamp: None,
+            created_at: None,
+            gas_used: None,
+            effective_gas_price: None,
+            pyth_price: None,
+            pyth_confidence: None,
+            pyth_exponent: None,
+            pyth_publish_time: None,
+        };
+        let first = partial_fill(
+            fixed_bytes!("0x5555555555555555555555555555555555555555555555555555555555555555"),
+            dec!(0.4),
+            dec!(150.10),
+        );
+        let second = partial_fill(
+            fixed_bytes!("0x6666666666666666666666666666666666666666666666666666666666666666"),
+            dec!(0.6),
+            dec!(150.40),
+        );
+
+        let execution = OffchainExecution {
+            id: None,
+            symbol: Symbol::new("AAPL").unwrap(),
+            shares: Shares::new(1).unwrap(),
+            direction: Direction::Buy,
+            broker: SupportedBroker::Schwab,
+            state: OrderState::Pending,
+        };
+
+        let mut sql_tx = pool.begin().await.unwrap();
+        let first_id = first.save_within_transaction(&mut sql_tx).await.unwrap();
+        let second_id = second.save_within_transaction(&mut sql_tx).await.unwrap();
+        let execution_id = execution
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        TradeExecutionLink::new(first_id, execution_id, 0.4)
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        TradeExecutionLink::new(second_id, execution_id, 0.6)
+            .save_within_transaction(&mut sql_tx)
+            .await
+            .unwrap();
+        sql_tx.commit().await.unwrap();
+
+        let linked = get_linked_trades(&pool, &SymbolConvention::default(), execution_id)
+            .await
+            .unwrap();
+
+        assert_eq!(linked.len(), 2);
+        assert_eq!(linked[0].trade.id, Some(first_id));
+        assert_eq!(linked[0].trade.tx_hash, first.tx_hash);
+        assert_eq!(linked[0].trade.price_usdc, dec!(150.10));
+        assert!((linked[0].contributed_shares - 0.4).abs() < f64::EPSILON);
+        assert_eq!(linked[1].trade.id, Some(second_id));
+        assert_eq!(linked[1].trade.amount, dec!(0.6));
+        assert_eq!(linked[1].trade.price_usdc, dec!(150.40));
+        assert!((linked[1].contributed_shares - 0.6).abs() < f64::EPSILON);
+
+        assert!(
+            get_linked_trades(&pool, &SymbolConvention::default(), execution_id + 1)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn test_symbol_audit_trail() {
         let pool = setup_test_db().await;
@@ -396,10 +576,11 @@ mod tests {
                     "0x2222222222222222222222222222222222222222222222222222222222222222"
                 ),
                 log_index: 1,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("MSFT0x"),
-                amount: 0.5,
+                amount: dec!(0.5),
                 direction: Direction::Buy,
-                price_usdc: 300.0,
+                price_usdc: dec!(300.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: None,
@@ -415,10 +596,11 @@ mod tests {
                     "0x3333333333333333333333333333333333333333333333333333333333333333"
                 ),
                 log_index: 2,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("MSFT0x"),
-                amount: 0.8,
+                amount: dec!(0.8),
                 direction: Direction::Buy,
-                price_usdc: 305.0,
+                price_usdc: dec!(305.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: None,
@@ -440,6 +622,7 @@ mod tests {
                 executed_at: Utc::now(),
                 order_id: "1004055538123".to_string(),
                 price_cents: 30250,
+                reported_price: None,
             },
         };
 
@@ -479,7 +662,7 @@ mod tests {
         let pool = setup_test_db().await;
 
         // Simulate multiple small trades that together trigger one execution
-        let trades = vec![(0.3, 1u64), (0.4, 2u64), (0.5, 3u64)];
+        let trades = vec![(dec!(0.3), 1u64), (dec!(0.4), 2u64), (dec!(0.5), 3u64)];
 
         let execution = OffchainExecution {
             id: None,
@@ -500,10 +683,11 @@ mod tests {
                     "0x4444444444444444444444444444444444444444444444444444444444444444"
                 ),
                 log_index,
+                orderbook: alloy::primitives::Address::ZERO,
                 symbol: tokenized_symbol!("AAPL0x"),
                 amount,
                 direction: Direction::Sell,
-                price_usdc: 150.0,
+                price_usdc: dec!(150.0),
                 block_timestamp: None,
                 created_at: None,
                 gas_used: None,
@@ -559,10 +743,11 @@ mod tests {
                 "0x5555555555555555555555555555555555555555555555555555555555555555"
             ),
             log_index: 1,
+            orderbook: alloy::primitives::Address::ZERO,
             symbol: tokenized_symbol!("AAPL0x"),
-            amount: 1.0,
+            amount: dec!(1.0),
             direction: Direction::Buy,
-            price_usdc: 150.0,
+            price_usdc: dec!(150.0),
             block_timestamp: None,
             created_at: None,
             gas_used: None,
@@ -0,0 +1,110 @@
+//! Live feed of persisted onchain trades and execution status changes.
+//!
+//! The processing paths publish to a bounded broadcast channel that
+//! `GET /stream/trades` relays to dashboards as Server-Sent Events. Publishing
+//! never blocks: a client that falls more than the channel capacity behind is
+//! disconnected rather than slowing the bot down, and can reconnect. Without a
+//! configured capacity the feed is disabled and publishing does nothing.
+
+use alloy::primitives::B256;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use tokio::sync::broadcast;
+
+use crate::onchain::OnchainTrade;
+use st0x_broker::{Direction, OrderStatus, Symbol};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum TradeFeedEvent {
+    /// An onchain trade was persisted.
+    Trade {
+        tx_hash: B256,
+        log_index: u64,
+        symbol: String,
+        amount: Decimal,
+        direction: Direction,
+        price_usdc: Decimal,
+    },
+    /// An execution was created or reached a new status.
+    ExecutionStatus {
+        execution_id: i64,
+        symbol: Symbol,
+        status: OrderStatus,
+    },
+}
+
+impl From<&OnchainTrade> for TradeFeedEvent {
+    fn from(trade: &OnchainTrade) -> Self {
+        Self::Trade {
+            tx_hash: trade.tx_hash,
+            log_index: trade.log_index,
+            symbol: trade.symbol.to_string(),
+            amount: trade.amount,
+            direction: trade.direction,
+            price_usdc: trade.price_usdc,
+        }
+    }
+}
+
+/// Sending side of the feed; clones publish to the same subscribers.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TradeFeed(Option<broadcast::Sender<TradeFeedEvent>>);
+
+impl TradeFeed {
+    /// Feed buffering up to `capacity` events per subscriber, disabled if
+    /// `None`.
+    pub(crate) fn new(capacity: Option<NonZeroUsize>) -> Self {
+        Self(capacity.map(|capacity| broadcast::channel(capacity.get()).0))
+    }
+
+    pub(crate) fn publish(&self, event: TradeFeedEvent) {
+        if let Some(sender) = &self.0 {
+            // Fails only when nobody is subscribed, which is not an error
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Receiver of events published from now on, or `None` when disabled.
+    pub(crate) fn subscribe(&self) -> Option<broadcast::Receiver<TradeFeedEvent>> {
+        self.0.as_ref().map(broadcast::Sender::subscribe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+    fn execution_status(execution_id: i64) -> TradeFeedEvent {
+        TradeFeedEvent::ExecutionStatus {
+            execution_id,
+            symbol: Symbol::new("AAPL").unwrap(),
+            status: OrderStatus::Filled,
+        }
+    }
+
+    #[test]
+    fn test_disabled_feed_has_no_subscribers() {
+        let feed = TradeFeed::new(None);
+
+        feed.publish(execution_status(1));
+        assert!(feed.subscribe().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_lags_without_blocking_publisher() {
+        let feed = TradeFeed::new(NonZeroUsize::new(2));
+        let mut receiver = feed.subscribe().unwrap();
+
+        for execution_id in 1..=3 {
+            feed.publish(execution_status(execution_id));
+        }
+
+        assert!(matches!(receiver.recv().await, Err(RecvError::Lagged(1))));
+        assert_eq!(receiver.recv().await.unwrap(), execution_status(2));
+        assert_eq!(receiver.recv().await.unwrap(), execution_status(3));
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    }
+}
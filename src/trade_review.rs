@@ -0,0 +1,81 @@
+use alloy::primitives::{Address, B256};
+#[cfg(test)]
+use sqlx::SqlitePool;
+
+/// An onchain trade that was left unhedged and needs manual review, e.g.
+/// because one of its tokens has no readable `symbol()` and the trade could
+/// only be labelled with a fallback symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TradeReview {
+    pub(crate) tx_hash: B256,
+    pub(crate) log_index: u64,
+    pub(crate) token: Address,
+    pub(crate) fallback_symbol: String,
+    pub(crate) reason: String,
+}
+
+impl TradeReview {
+    pub(crate) async fn save_within_transaction(
+        &self,
+        sql_tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    ) -> Result<i64, sqlx::Error> {
+        let tx_hash = format!("{:#x}", self.tx_hash);
+        let log_index =
+            i64::try_from(self.log_index).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let token_address = self.token.to_string();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO trades_for_review (
+                tx_hash,
+                log_index,
+                token_address,
+                fallback_symbol,
+                reason
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            tx_hash,
+            log_index,
+            token_address,
+            self.fallback_symbol,
+            self.reason
+        )
+        .execute(&mut **sql_tx)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    #[cfg(test)]
+    pub(crate) async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT tx_hash, log_index, token_address, fallback_symbol, reason
+            FROM trades_for_review
+            ORDER BY id ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Self {
+                    tx_hash: row
+                        .tx_hash
+                        .parse()
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    log_index: u64::try_from(row.log_index)
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    token: row
+                        .token_address
+                        .parse()
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    fallback_symbol: row.fallback_symbol,
+                    reason: row.reason,
+                })
+            })
+            .collect()
+    }
+}
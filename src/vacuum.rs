@@ -0,0 +1,158 @@
+//! Scheduled `VACUUM` to reclaim the free pages left behind by prunes.
+//!
+//! `VACUUM` rewrites the whole database and holds the write lock while it
+//! runs, so it only runs in the market-closed window after the trading tasks
+//! have been stopped, and at most once per configured interval. Completed runs
+//! are recorded in `vacuum_runs` so the interval holds across restarts.
+
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Whether the bot is trading, which rules out vacuuming.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MarketWindow {
+    Trading,
+    Closed,
+}
+
+/// Vacuums the database if `window` is closed and the last run was at least
+/// `interval` ago. Returns whether it vacuumed; never does without `interval`.
+pub(crate) async fn vacuum_if_due(
+    pool: &SqlitePool,
+    interval: Option<Duration>,
+    window: MarketWindow,
+) -> Result<bool, sqlx::Error> {
+    let Some(interval) = interval else {
+        return Ok(false);
+    };
+
+    if window == MarketWindow::Trading {
+        debug!("Skipping database vacuum while trading");
+        return Ok(false);
+    }
+
+    let interval_param = format!("-{} seconds", interval.as_secs());
+    let recently_vacuumed = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM vacuum_runs WHERE completed_at > datetime('now', ?1)
+        ) AS "recently_vacuumed!: bool"
+        "#,
+        interval_param
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if recently_vacuumed {
+        debug!("Database vacuumed within the last {interval:?}, skipping");
+        return Ok(false);
+    }
+
+    let free_pages: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+        .fetch_one(pool)
+        .await?;
+    info!("Vacuuming database to reclaim {free_pages} free pages");
+
+    sqlx::query("VACUUM").execute(pool).await?;
+    sqlx::query!("INSERT INTO vacuum_runs DEFAULT VALUES")
+        .execute(pool)
+        .await?;
+
+    info!("Database vacuum complete");
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    const INTERVAL: Option<Duration> = Some(Duration::from_secs(24 * 60 * 60));
+
+    /// Inserts and deletes enough rows to leave free pages behind.
+    async fn leave_free_pages(pool: &SqlitePool) {
+        for i in 0..200 {
+            sqlx::query(
+                "INSERT INTO conversion_outcomes (tx_hash, log_index, outcome, reason) \
+                 VALUES (?1, ?2, 'filtered', ?3)",
+            )
+            .bind(format!("0x{i:064x}"))
+            .bind(i)
+            .bind("x".repeat(512))
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+
+        sqlx::query("DELETE FROM conversion_outcomes")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn free_pages(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_vacuums_only_in_closed_market_window() {
+        let pool = setup_test_db().await;
+        leave_free_pages(&pool).await;
+        let before = free_pages(&pool).await;
+        assert!(before > 0);
+
+        let vacuumed = vacuum_if_due(&pool, INTERVAL, MarketWindow::Trading)
+            .await
+            .unwrap();
+        assert!(!vacuumed);
+        assert_eq!(free_pages(&pool).await, before);
+
+        let vacuumed = vacuum_if_due(&pool, INTERVAL, MarketWindow::Closed)
+            .await
+            .unwrap();
+        assert!(vacuumed);
+        assert_eq!(free_pages(&pool).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_vacuums_at_most_once_per_interval() {
+        let pool = setup_test_db().await;
+
+        assert!(
+            vacuum_if_due(&pool, INTERVAL, MarketWindow::Closed)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !vacuum_if_due(&pool, INTERVAL, MarketWindow::Closed)
+                .await
+                .unwrap()
+        );
+
+        sqlx::query("UPDATE vacuum_runs SET completed_at = datetime('now', '-2 days')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(
+            vacuum_if_due(&pool, INTERVAL, MarketWindow::Closed)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_never_vacuums_when_disabled() {
+        let pool = setup_test_db().await;
+        leave_free_pages(&pool).await;
+
+        let vacuumed = vacuum_if_due(&pool, None, MarketWindow::Closed)
+            .await
+            .unwrap();
+        assert!(!vacuumed);
+        assert!(free_pages(&pool).await > 0);
+    }
+}